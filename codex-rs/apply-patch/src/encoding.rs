@@ -0,0 +1,203 @@
+//! Detects the line endings, trailing newline and byte order mark of a text
+//! file so that they can be restored after patching it. Patches are always
+//! applied to `\n`-separated text without a BOM.
+
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// Byte order marks of encodings we refuse to edit, longest first so that
+/// UTF-32LE is not mistaken for UTF-16LE.
+const FOREIGN_BOMS: [(&[u8], &str); 4] = [
+    (b"\xFF\xFE\x00\x00", "UTF-32LE"),
+    (b"\x00\x00\xFE\xFF", "UTF-32BE"),
+    (b"\xFF\xFE", "UTF-16LE"),
+    (b"\xFE\xFF", "UTF-16BE"),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct TextFormat {
+    bom: bool,
+    /// Whether most lines end in `\r\n`. Lines added by a patch get this
+    /// ending.
+    crlf: bool,
+    /// For files that mix `\n` and `\r\n`, whether each line of the decoded
+    /// text ended in `\r\n`. `None` if all lines end the same way.
+    crlf_lines: Option<Vec<bool>>,
+    trailing_newline: bool,
+}
+
+impl TextFormat {
+    /// The same format, but ending in a newline iff `trailing_newline`. Used
+    /// when a patch explicitly adds or removes the final newline.
+    pub(crate) fn with_trailing_newline(self, trailing_newline: bool) -> Self {
+        Self {
+            trailing_newline,
+            ..self
+        }
+    }
+
+    /// Adds or removes the final newline of `text` to match the original file.
+    pub(crate) fn restore_trailing_newline(&self, mut text: String) -> String {
+        if self.trailing_newline {
+            if !text.is_empty() && !text.ends_with('\n') {
+                text.push('\n');
+            }
+        } else if text.ends_with('\n') {
+            text.pop();
+        }
+        text
+    }
+
+    /// Converts `\n`-separated `text`, the patched version of the decoded
+    /// `original`, back to the file's line endings and BOM. In files with mixed
+    /// line endings, lines carried over from `original` keep their own ending.
+    pub(crate) fn encode(&self, original: &str, text: &str) -> String {
+        let mut encoded = String::with_capacity(text.len() + 3);
+        if self.bom {
+            encoded.push('\u{FEFF}');
+        }
+        let Some(crlf_lines) = &self.crlf_lines else {
+            if self.crlf {
+                encoded.push_str(&text.replace('\n', "\r\n"));
+            } else {
+                encoded.push_str(text);
+            }
+            return encoded;
+        };
+
+        let old_lines: Vec<&str> = original.split('\n').collect();
+        let new_lines: Vec<&str> = text.split('\n').collect();
+        let mut new_crlf = vec![self.crlf; new_lines.len()];
+        for op in similar::capture_diff_slices(similar::Algorithm::Myers, &old_lines, &new_lines) {
+            if let similar::DiffOp::Equal {
+                old_index,
+                new_index,
+                len,
+            } = op
+            {
+                for i in 0..len {
+                    if let Some(&crlf) = crlf_lines.get(old_index + i) {
+                        new_crlf[new_index + i] = crlf;
+                    }
+                }
+            }
+        }
+        for (i, line) in new_lines.iter().enumerate() {
+            if i > 0 {
+                encoded.push_str(if new_crlf[i - 1] { "\r\n" } else { "\n" });
+            }
+            encoded.push_str(line);
+        }
+        encoded
+    }
+}
+
+/// Decodes the contents of a text file. `\r\n` line endings are returned as
+/// `\n`; files that mostly use `\r\n` are treated as CRLF files.
+///
+/// Returns a description of the problem if the file is not UTF-8.
+pub(crate) fn decode(bytes: &[u8]) -> Result<(String, TextFormat), String> {
+    if let Some((_, name)) = FOREIGN_BOMS.iter().find(|(bom, _)| bytes.starts_with(bom)) {
+        return Err(format!("it starts with a {name} byte order mark"));
+    }
+    let bom = bytes.starts_with(UTF8_BOM);
+    let body = if bom { &bytes[UTF8_BOM.len()..] } else { bytes };
+    let text = std::str::from_utf8(body).map_err(|err| {
+        let offset = err.valid_up_to() + if bom { UTF8_BOM.len() } else { 0 };
+        format!("invalid UTF-8 at byte {offset}")
+    })?;
+
+    let line_endings = text.matches('\n').count();
+    let crlf_endings = text.matches("\r\n").count();
+    let crlf = crlf_endings * 2 > line_endings;
+    let crlf_lines = (crlf_endings > 0 && crlf_endings < line_endings).then(|| {
+        text.split_inclusive('\n')
+            .filter(|line| line.ends_with('\n'))
+            .map(|line| line.ends_with("\r\n"))
+            .collect()
+    });
+    let text = if crlf_endings > 0 {
+        text.replace("\r\n", "\n")
+    } else {
+        text.to_string()
+    };
+    let trailing_newline = text.is_empty() || text.ends_with('\n');
+    Ok((
+        text,
+        TextFormat {
+            bom,
+            crlf,
+            crlf_lines,
+            trailing_newline,
+        },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_round_trip_preserves_bom_crlf_and_missing_newline() {
+        let original = "\u{FEFF}one\r\ntwo";
+        let (text, format) = decode(original.as_bytes()).unwrap();
+        assert_eq!(text, "one\ntwo");
+        assert_eq!(
+            format,
+            TextFormat {
+                bom: true,
+                crlf: true,
+                crlf_lines: None,
+                trailing_newline: false,
+            }
+        );
+        let patched = format.restore_trailing_newline("one\ntwo\nthree\n".to_string());
+        assert_eq!(
+            format.encode(&text, &patched),
+            "\u{FEFF}one\r\ntwo\r\nthree"
+        );
+    }
+
+    #[test]
+    fn test_mixed_line_endings_are_kept_per_line() {
+        let (text, format) = decode(b"a\r\nb\r\nc\n").unwrap();
+        assert_eq!(text, "a\nb\nc\n");
+        assert!(format.crlf);
+        assert_eq!(format.encode(&text, &text), "a\r\nb\r\nc\n");
+        // Changed and added lines get the majority ending.
+        assert_eq!(
+            format.encode(&text, "A\nb\nnew\nc\n"),
+            "A\r\nb\r\nnew\r\nc\n"
+        );
+
+        let (text, format) = decode(b"a\nb\nc\r\n").unwrap();
+        assert_eq!(text, "a\nb\nc\n");
+        assert!(!format.crlf);
+        assert_eq!(format.encode(&text, "new\na\nb\nc\n"), "new\na\nb\nc\r\n");
+    }
+
+    #[test]
+    fn test_empty_file_gets_trailing_newline() {
+        let (_, format) = decode(b"").unwrap();
+        assert_eq!(
+            format.restore_trailing_newline("new\n".to_string()),
+            "new\n"
+        );
+    }
+
+    #[test]
+    fn test_rejects_non_utf8() {
+        assert_eq!(
+            decode(b"\xFF\xFEh\x00i\x00"),
+            Err("it starts with a UTF-16LE byte order mark".to_string())
+        );
+        assert_eq!(
+            decode(b"\xFF\xFE\x00\x00h\x00\x00\x00"),
+            Err("it starts with a UTF-32LE byte order mark".to_string())
+        );
+        assert_eq!(
+            decode(b"\xEF\xBB\xBFcaf\xE9\n"),
+            Err("invalid UTF-8 at byte 6".to_string())
+        );
+    }
+}
@@ -1,14 +1,18 @@
+mod encoding;
 mod parser;
+mod report;
 mod seek_sequence;
 mod standalone_executable;
+mod unified_diff;
 
 use std::collections::HashMap;
+use std::collections::HashSet;
 use std::path::Path;
 use std::path::PathBuf;
 use std::str::Utf8Error;
 
-use anyhow::Context;
 use anyhow::Result;
+use encoding::TextFormat;
 use once_cell::sync::Lazy;
 pub use parser::Hunk;
 pub use parser::ParseError;
@@ -31,6 +35,48 @@ pub const APPLY_PATCH_TOOL_INSTRUCTIONS: &str = include_str!("../apply_patch_too
 
 const APPLY_PATCH_COMMANDS: [&str; 2] = ["apply_patch", "applypatch"];
 
+/// Default for [`MatchOptions::fuzzy_threshold`]: fuzzy matching is opt-in.
+pub const DEFAULT_FUZZY_THRESHOLD: f64 = 1.0;
+
+/// Environment variable through which Codex passes
+/// [`MatchOptions::fuzzy_threshold`] to the `apply_patch` process it spawns.
+pub const FUZZY_THRESHOLD_ENV_VAR: &str = "CODEX_APPLY_PATCH_FUZZY_THRESHOLD";
+
+/// Regions less similar than this are not worth showing as the closest match
+/// when a hunk cannot be located.
+const MIN_CLOSEST_MATCH_SIMILARITY: f64 = 0.5;
+
+/// Controls how the lines of an update hunk are located in the file.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MatchOptions {
+    /// Minimum similarity (between 0.0 and 1.0) for a region of the file to
+    /// be accepted as the location of a hunk whose lines do not match even
+    /// after whitespace and punctuation normalisation. `1.0` disables fuzzy
+    /// matching.
+    pub fuzzy_threshold: f64,
+}
+
+impl Default for MatchOptions {
+    fn default() -> Self {
+        Self {
+            fuzzy_threshold: DEFAULT_FUZZY_THRESHOLD,
+        }
+    }
+}
+
+impl MatchOptions {
+    /// Reads the options from [`FUZZY_THRESHOLD_ENV_VAR`], falling back to
+    /// the defaults if it is unset or not a number between 0.0 and 1.0.
+    pub fn from_env() -> Self {
+        let fuzzy_threshold = std::env::var(FUZZY_THRESHOLD_ENV_VAR)
+            .ok()
+            .and_then(|value| value.parse::<f64>().ok())
+            .filter(|value| (0.0..=1.0).contains(value))
+            .unwrap_or(DEFAULT_FUZZY_THRESHOLD);
+        Self { fuzzy_threshold }
+    }
+}
+
 #[derive(Debug, Error, PartialEq)]
 pub enum ApplyPatchError {
     #[error(transparent)]
@@ -40,6 +86,23 @@ pub enum ApplyPatchError {
     /// Error that occurs while computing replacements when applying patch chunks
     #[error("{0}")]
     ComputeReplacements(String),
+    /// The patch did not contain any hunks.
+    #[error("No files were modified.")]
+    NoChanges,
+    /// A file to update is not UTF-8 text.
+    #[error(
+        "Cannot update {}: {reason}. apply_patch only edits UTF-8 text files.",
+        .path.display()
+    )]
+    UnsupportedEncoding { path: PathBuf, reason: String },
+    /// The patch cannot be reversed because it does not record what it
+    /// replaced.
+    #[error("Cannot reverse the patch: {0}")]
+    NotReversible(String),
+    /// Writing the patch to disk failed after some files had already been
+    /// changed.
+    #[error(transparent)]
+    PartialApply(#[from] PartialApplyError),
 }
 
 impl From<std::io::Error> for ApplyPatchError {
@@ -74,6 +137,53 @@ impl PartialEq for IoError {
     }
 }
 
+/// A patch failed while its changes were being moved into place. Every file
+/// changed before the failure is rolled back; `not_restored` lists the ones
+/// for which that also failed, i.e. the workspace is only left half-patched
+/// if it is non-empty.
+#[derive(Debug, Error)]
+#[error(
+    "Failed to apply patch to {}: {source}. {}",
+    .path.display(),
+    describe_rollback(.restored, .not_restored)
+)]
+pub struct PartialApplyError {
+    /// The file whose change could not be applied.
+    pub path: PathBuf,
+    #[source]
+    pub source: std::io::Error,
+    /// Files that had already been changed and were restored to their
+    /// original state.
+    pub restored: Vec<PathBuf>,
+    /// Files that had already been changed and could not be restored, with
+    /// the reason.
+    pub not_restored: Vec<(PathBuf, String)>,
+}
+
+impl PartialEq for PartialApplyError {
+    fn eq(&self, other: &Self) -> bool {
+        self.path == other.path
+            && self.source.to_string() == other.source.to_string()
+            && self.restored == other.restored
+            && self.not_restored == other.not_restored
+    }
+}
+
+fn describe_rollback(restored: &[PathBuf], not_restored: &[(PathBuf, String)]) -> String {
+    if not_restored.is_empty() {
+        return match restored.len() {
+            0 => "No files were modified.".to_string(),
+            n => format!("Rolled back changes to {n} file(s); no files were modified."),
+        };
+    }
+    let failures = not_restored
+        .iter()
+        .map(|(path, reason)| format!("{} ({reason})", path.display()))
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!("The workspace is partially patched; could not restore: {failures}")
+}
+
 #[derive(Debug, PartialEq)]
 pub enum MaybeApplyPatch {
     Body(ApplyPatchArgs),
@@ -130,6 +240,23 @@ pub enum ApplyPatchFileChange {
         /// new_content that will result after the unified_diff is applied.
         new_content: String,
     },
+    /// The file is created or overwritten with arbitrary bytes.
+    Binary {
+        /// Size of the file being replaced, if any.
+        old_size: Option<u64>,
+        new_content: Vec<u8>,
+    },
+    /// The permission bits of the file change, possibly in addition to an
+    /// earlier change to the same file in the patch.
+    Mode {
+        old_mode: Option<u32>,
+        new_mode: u32,
+        contents: Option<Box<ApplyPatchFileChange>>,
+    },
+    /// A symlink pointing at `target` is created.
+    Symlink {
+        target: PathBuf,
+    },
 }
 
 #[derive(Debug, PartialEq)]
@@ -206,7 +333,11 @@ impl ApplyPatchAction {
 
 /// cwd must be an absolute path so that we can resolve relative paths in the
 /// patch.
-pub fn maybe_parse_apply_patch_verified(argv: &[String], cwd: &Path) -> MaybeApplyPatchVerified {
+pub fn maybe_parse_apply_patch_verified(
+    argv: &[String],
+    cwd: &Path,
+    options: MatchOptions,
+) -> MaybeApplyPatchVerified {
     match maybe_parse_apply_patch(argv) {
         MaybeApplyPatch::Body(ApplyPatchArgs {
             patch,
@@ -232,8 +363,11 @@ pub fn maybe_parse_apply_patch_verified(argv: &[String], cwd: &Path) -> MaybeApp
                         changes.insert(path, ApplyPatchFileChange::Add { content: contents });
                     }
                     Hunk::DeleteFile { .. } => {
-                        let content = match std::fs::read_to_string(&path) {
-                            Ok(content) => content,
+                        // Binary files are deleted without showing their contents.
+                        let content = match std::fs::read(&path) {
+                            Ok(bytes) => encoding::decode(&bytes)
+                                .map(|(text, _)| text)
+                                .unwrap_or_default(),
                             Err(e) => {
                                 return MaybeApplyPatchVerified::CorrectnessError(
                                     ApplyPatchError::IoError(IoError {
@@ -251,7 +385,8 @@ pub fn maybe_parse_apply_patch_verified(argv: &[String], cwd: &Path) -> MaybeApp
                         let ApplyPatchFileUpdate {
                             unified_diff,
                             content: contents,
-                        } = match unified_diff_from_chunks(&path, &chunks) {
+                        } = match unified_diff_from_chunks_with_context(&path, &chunks, 1, options)
+                        {
                             Ok(diff) => diff,
                             Err(e) => {
                                 return MaybeApplyPatchVerified::CorrectnessError(e);
@@ -266,6 +401,72 @@ pub fn maybe_parse_apply_patch_verified(argv: &[String], cwd: &Path) -> MaybeApp
                             },
                         );
                     }
+                    Hunk::AddBinaryFile { contents, .. } => {
+                        let old_size = std::fs::metadata(&path).ok().map(|m| m.len());
+                        changes.insert(
+                            path,
+                            ApplyPatchFileChange::Binary {
+                                old_size,
+                                new_content: contents,
+                            },
+                        );
+                    }
+                    Hunk::ReplaceBinaryFile { contents, .. } => {
+                        let old_size = match std::fs::metadata(&path) {
+                            Ok(metadata) => metadata.len(),
+                            Err(e) => {
+                                return MaybeApplyPatchVerified::CorrectnessError(
+                                    ApplyPatchError::IoError(IoError {
+                                        context: format!("Failed to read {}", path.display()),
+                                        source: e,
+                                    }),
+                                );
+                            }
+                        };
+                        changes.insert(
+                            path,
+                            ApplyPatchFileChange::Binary {
+                                old_size: Some(old_size),
+                                new_content: contents,
+                            },
+                        );
+                    }
+                    Hunk::ChmodFile { mode, .. } => {
+                        // Fold the mode change into an earlier change to the
+                        // same file, which may be a move to `path`.
+                        let key = changes
+                            .iter()
+                            .find(|(key, change)| {
+                                **key == path
+                                    || matches!(
+                                        change,
+                                        ApplyPatchFileChange::Update {
+                                            move_path: Some(dest),
+                                            ..
+                                        } if *dest == path
+                                    )
+                            })
+                            .map(|(key, _)| key.clone());
+                        let previous = key.as_ref().and_then(|key| changes.remove(key));
+                        let change = match previous {
+                            Some(ApplyPatchFileChange::Mode {
+                                old_mode, contents, ..
+                            }) => ApplyPatchFileChange::Mode {
+                                old_mode,
+                                new_mode: mode,
+                                contents,
+                            },
+                            previous => ApplyPatchFileChange::Mode {
+                                old_mode: file_mode(key.as_ref().unwrap_or(&path)),
+                                new_mode: mode,
+                                contents: previous.map(Box::new),
+                            },
+                        };
+                        changes.insert(key.unwrap_or(path), change);
+                    }
+                    Hunk::AddSymlink { target, .. } => {
+                        changes.insert(path, ApplyPatchFileChange::Symlink { target });
+                    }
                 }
             }
             MaybeApplyPatchVerified::Body(ApplyPatchAction {
@@ -447,8 +648,29 @@ pub fn apply_patch(
     stdout: &mut impl std::io::Write,
     stderr: &mut impl std::io::Write,
 ) -> Result<(), ApplyPatchError> {
-    let hunks = match parse_patch(patch) {
-        Ok(source) => source.hunks,
+    apply_patch_with_options(patch, MatchOptions::default(), stdout, stderr)
+}
+
+/// Like [`apply_patch`], but with explicit [`MatchOptions`].
+pub fn apply_patch_with_options(
+    patch: &str,
+    options: MatchOptions,
+    stdout: &mut impl std::io::Write,
+    stderr: &mut impl std::io::Write,
+) -> Result<(), ApplyPatchError> {
+    let hunks = parse_hunks(patch, stderr)?;
+    apply_hunks(&hunks, options, stdout, stderr)?;
+
+    Ok(())
+}
+
+/// Parses `patch`, describing any parse error on `stderr`.
+fn parse_hunks(
+    patch: &str,
+    stderr: &mut impl std::io::Write,
+) -> Result<Vec<Hunk>, ApplyPatchError> {
+    match parse_patch(patch) {
+        Ok(source) => Ok(source.hunks),
         Err(e) => {
             match &e {
                 InvalidPatchError(message) => {
@@ -465,18 +687,82 @@ pub fn apply_patch(
                     .map_err(ApplyPatchError::from)?;
                 }
             }
-            return Err(ApplyPatchError::ParseError(e));
+            Err(ApplyPatchError::ParseError(e))
         }
-    };
+    }
+}
 
-    apply_hunks(&hunks, stdout, stderr)?;
+/// Checks that `hunks` apply cleanly to the filesystem without writing
+/// anything, returning the paths that applying them would touch.
+pub fn check_hunks(
+    hunks: &[Hunk],
+    options: MatchOptions,
+) -> Result<AffectedPaths, ApplyPatchError> {
+    PatchPlan::from_hunks(hunks, options).map(|(_, affected)| affected)
+}
 
-    Ok(())
+/// Returns hunks that undo `hunks` once they have been applied. Deletions,
+/// binary replacements and mode changes cannot be reversed because the patch
+/// does not contain what they replaced, except for mode changes of files the
+/// patch adds, which go away with the file.
+pub fn reverse_hunks(hunks: &[Hunk]) -> Result<Vec<Hunk>, ApplyPatchError> {
+    let added: HashSet<&Path> = hunks
+        .iter()
+        .filter(|hunk| {
+            matches!(
+                hunk,
+                Hunk::AddFile { .. } | Hunk::AddBinaryFile { .. } | Hunk::AddSymlink { .. }
+            )
+        })
+        .map(Hunk::path)
+        .collect();
+    hunks
+        .iter()
+        .rev()
+        .filter(|hunk| !(matches!(hunk, Hunk::ChmodFile { .. }) && added.contains(hunk.path())))
+        .map(|hunk| match hunk {
+            Hunk::AddFile { path, .. }
+            | Hunk::AddBinaryFile { path, .. }
+            | Hunk::AddSymlink { path, .. } => Ok(Hunk::DeleteFile { path: path.clone() }),
+            Hunk::UpdateFile {
+                path,
+                move_path,
+                chunks,
+            } => Ok(Hunk::UpdateFile {
+                path: move_path.clone().unwrap_or_else(|| path.clone()),
+                move_path: move_path.as_ref().map(|_| path.clone()),
+                chunks: chunks
+                    .iter()
+                    .map(|chunk| UpdateFileChunk {
+                        change_context: chunk.change_context.clone(),
+                        old_lines: chunk.new_lines.clone(),
+                        new_lines: chunk.old_lines.clone(),
+                        is_end_of_file: chunk.is_end_of_file,
+                        old_missing_newline: chunk.new_missing_newline,
+                        new_missing_newline: chunk.old_missing_newline,
+                    })
+                    .collect(),
+            }),
+            Hunk::DeleteFile { path } => Err(ApplyPatchError::NotReversible(format!(
+                "the contents of deleted file {} are not part of the patch",
+                path.display()
+            ))),
+            Hunk::ReplaceBinaryFile { path, .. } => Err(ApplyPatchError::NotReversible(format!(
+                "the previous contents of {} are not part of the patch",
+                path.display()
+            ))),
+            Hunk::ChmodFile { path, .. } => Err(ApplyPatchError::NotReversible(format!(
+                "the previous mode of {} is not part of the patch",
+                path.display()
+            ))),
+        })
+        .collect()
 }
 
 /// Applies hunks and continues to update stdout/stderr
 pub fn apply_hunks(
     hunks: &[Hunk],
+    options: MatchOptions,
     stdout: &mut impl std::io::Write,
     stderr: &mut impl std::io::Write,
 ) -> Result<(), ApplyPatchError> {
@@ -487,7 +773,10 @@ pub fn apply_hunks(
                 // The file is being added, so it doesn't exist yet.
                 None
             }
-            Hunk::DeleteFile { path } => Some(path.as_path()),
+            Hunk::AddBinaryFile { .. } | Hunk::AddSymlink { .. } => None,
+            Hunk::DeleteFile { path }
+            | Hunk::ReplaceBinaryFile { path, .. }
+            | Hunk::ChmodFile { path, .. } => Some(path.as_path()),
             Hunk::UpdateFile {
                 path, move_path, ..
             } => match move_path {
@@ -507,22 +796,14 @@ pub fn apply_hunks(
         .collect::<Vec<&Path>>();
 
     // Delegate to a helper that applies each hunk to the filesystem.
-    match apply_hunks_to_files(hunks) {
+    match apply_hunks_to_files(hunks, options) {
         Ok(affected) => {
             print_summary(&affected, stdout).map_err(ApplyPatchError::from)?;
             Ok(())
         }
         Err(err) => {
-            let msg = err.to_string();
-            writeln!(stderr, "{msg}").map_err(ApplyPatchError::from)?;
-            if let Some(io) = err.downcast_ref::<std::io::Error>() {
-                Err(ApplyPatchError::from(io))
-            } else {
-                Err(ApplyPatchError::IoError(IoError {
-                    context: msg,
-                    source: std::io::Error::other(err),
-                }))
-            }
+            writeln!(stderr, "{err}").map_err(ApplyPatchError::from)?;
+            Err(err)
         }
     }
 }
@@ -537,72 +818,552 @@ pub struct AffectedPaths {
 }
 
 /// Apply the hunks to the filesystem, returning which files were added, modified, or deleted.
-/// Returns an error if the patch could not be applied.
-fn apply_hunks_to_files(hunks: &[Hunk]) -> anyhow::Result<AffectedPaths> {
-    if hunks.is_empty() {
-        anyhow::bail!("No files were modified.");
-    }
-
-    let mut added: Vec<PathBuf> = Vec::new();
-    let mut modified: Vec<PathBuf> = Vec::new();
-    let mut deleted: Vec<PathBuf> = Vec::new();
-    for hunk in hunks {
-        match hunk {
-            Hunk::AddFile { path, contents } => {
-                if let Some(parent) = path.parent()
-                    && !parent.as_os_str().is_empty()
-                {
-                    std::fs::create_dir_all(parent).with_context(|| {
-                        format!("Failed to create parent directories for {}", path.display())
-                    })?;
-                }
-                std::fs::write(path, contents)
-                    .with_context(|| format!("Failed to write file {}", path.display()))?;
-                added.push(path.clone());
-            }
-            Hunk::DeleteFile { path } => {
-                std::fs::remove_file(path)
-                    .with_context(|| format!("Failed to delete file {}", path.display()))?;
-                deleted.push(path.clone());
+///
+/// This is all-or-nothing: the final contents of every file are computed
+/// before anything is written, staged in temporary files next to their
+/// destinations and then renamed into place. If any step fails, the files
+/// changed so far are restored.
+fn apply_hunks_to_files(
+    hunks: &[Hunk],
+    options: MatchOptions,
+) -> Result<AffectedPaths, ApplyPatchError> {
+    let (plan, affected) = PatchPlan::from_hunks(hunks, options)?;
+    plan.commit()?;
+    Ok(affected)
+}
+
+/// The state of every file touched by a patch once all of its hunks have been
+/// applied, in the order in which the patch first touches them.
+struct PatchPlan {
+    files: Vec<PlannedFile>,
+}
+
+struct PlannedFile {
+    path: PathBuf,
+    new_state: NewState,
+    /// Permissions for the new file, taken from the file it replaces (or, for
+    /// a move, the file it was moved from) unless the patch sets them.
+    permissions: Option<std::fs::Permissions>,
+    /// Snapshot used for rollback; `None` if nothing existed at `path`.
+    original: Option<OriginalFile>,
+}
+
+/// What `path` should be once the patch has been applied.
+enum NewState {
+    Deleted,
+    File(Vec<u8>),
+    /// Only the permissions of the existing file change.
+    Unchanged,
+    Symlink(PathBuf),
+}
+
+enum OriginalFile {
+    File {
+        contents: Vec<u8>,
+        permissions: std::fs::Permissions,
+    },
+    Symlink(PathBuf),
+}
+
+impl PatchPlan {
+    /// Applies `hunks` in memory. Later hunks see the effect of earlier ones,
+    /// e.g. a file can be added and then updated by the same patch.
+    fn from_hunks(
+        hunks: &[Hunk],
+        options: MatchOptions,
+    ) -> Result<(Self, AffectedPaths), ApplyPatchError> {
+        if hunks.is_empty() {
+            return Err(ApplyPatchError::NoChanges);
+        }
+
+        let mut plan = PatchPlan { files: Vec::new() };
+        let mut added: Vec<PathBuf> = Vec::new();
+        let mut modified: Vec<PathBuf> = Vec::new();
+        let mut deleted: Vec<PathBuf> = Vec::new();
+        for hunk in hunks {
+            plan.check_not_below_added_symlink(hunk.path())?;
+            if let Hunk::UpdateFile {
+                move_path: Some(dest),
+                ..
+            } = hunk
+            {
+                plan.check_not_below_added_symlink(dest)?;
             }
-            Hunk::UpdateFile {
-                path,
-                move_path,
-                chunks,
-            } => {
-                let AppliedPatch { new_contents, .. } =
-                    derive_new_contents_from_chunks(path, chunks)?;
-                if let Some(dest) = move_path {
-                    if let Some(parent) = dest.parent()
-                        && !parent.as_os_str().is_empty()
-                    {
-                        std::fs::create_dir_all(parent).with_context(|| {
-                            format!("Failed to create parent directories for {}", dest.display())
-                        })?;
+            match hunk {
+                Hunk::AddFile { path, contents } => {
+                    plan.set(path, NewState::File(contents.clone().into_bytes()), None)?;
+                    added.push(path.clone());
+                }
+                Hunk::AddBinaryFile { path, contents } => {
+                    plan.set(path, NewState::File(contents.clone()), None)?;
+                    added.push(path.clone());
+                }
+                Hunk::AddSymlink { path, target } => {
+                    plan.set(path, NewState::Symlink(target.clone()), None)?;
+                    added.push(path.clone());
+                }
+                Hunk::DeleteFile { path } => {
+                    if !plan.exists(path) {
+                        return Err(ApplyPatchError::IoError(IoError {
+                            context: format!("Failed to delete file {}", path.display()),
+                            source: std::io::Error::from(std::io::ErrorKind::NotFound),
+                        }));
+                    }
+                    plan.set(path, NewState::Deleted, None)?;
+                    deleted.push(path.clone());
+                }
+                Hunk::ReplaceBinaryFile { path, contents } => {
+                    let target = plan.resolve(path);
+                    if !plan.exists(&target) {
+                        return Err(ApplyPatchError::IoError(IoError {
+                            context: format!("Failed to replace file {}", path.display()),
+                            source: std::io::Error::from(std::io::ErrorKind::NotFound),
+                        }));
                     }
-                    std::fs::write(dest, new_contents)
-                        .with_context(|| format!("Failed to write file {}", dest.display()))?;
-                    std::fs::remove_file(path)
-                        .with_context(|| format!("Failed to remove original {}", path.display()))?;
-                    modified.push(dest.clone());
-                } else {
-                    std::fs::write(path, new_contents)
-                        .with_context(|| format!("Failed to write file {}", path.display()))?;
+                    plan.set(&target, NewState::File(contents.clone()), None)?;
                     modified.push(path.clone());
                 }
+                Hunk::ChmodFile { path, mode } => {
+                    let target = plan.resolve(path);
+                    plan.chmod(&target, *mode)?;
+                    if !added.contains(path) && !modified.contains(path) {
+                        modified.push(path.clone());
+                    }
+                }
+                Hunk::UpdateFile {
+                    path,
+                    move_path,
+                    chunks,
+                } => {
+                    // Edits to a symlink go to the file it points to, unless
+                    // the file is moved, which moves the link itself.
+                    let target = match move_path {
+                        Some(dest) if dest != path => path.clone(),
+                        _ => plan.resolve(path),
+                    };
+                    let Some((original_contents, format)) = plan.text(&target)? else {
+                        return Err(ApplyPatchError::IoError(IoError {
+                            context: format!("Failed to read file to update {}", path.display()),
+                            source: std::io::Error::from(std::io::ErrorKind::NotFound),
+                        }));
+                    };
+                    let format = requested_format(format, chunks);
+                    let new_contents = format.restore_trailing_newline(apply_chunks_to_contents(
+                        path,
+                        &original_contents,
+                        chunks,
+                        options,
+                    )?);
+                    let new_state = NewState::File(
+                        format
+                            .encode(&original_contents, &new_contents)
+                            .into_bytes(),
+                    );
+                    match move_path {
+                        Some(dest) if dest != path => {
+                            let permissions = plan.permissions(path);
+                            plan.set(dest, new_state, permissions)?;
+                            plan.set(path, NewState::Deleted, None)?;
+                            modified.push(dest.clone());
+                        }
+                        _ => {
+                            plan.set(&target, new_state, None)?;
+                            modified.push(path.clone());
+                        }
+                    }
+                }
+            }
+        }
+        Ok((
+            plan,
+            AffectedPaths {
+                added,
+                modified,
+                deleted,
+            },
+        ))
+    }
+
+    fn find(&self, path: &Path) -> Option<&PlannedFile> {
+        self.files.iter().find(|file| file.path == path)
+    }
+
+    /// Fails if a directory above `path` is a symlink added by this patch: the
+    /// write would land wherever the link points, which the caller's checks of
+    /// the patch's paths do not see.
+    fn check_not_below_added_symlink(&self, path: &Path) -> Result<(), ApplyPatchError> {
+        match path.ancestors().skip(1).find(|dir| {
+            self.find(dir)
+                .is_some_and(|file| matches!(file.new_state, NewState::Symlink(_)))
+        }) {
+            Some(link) => Err(ApplyPatchError::IoError(IoError {
+                context: format!(
+                    "Failed to write {}: {} is a symlink added by the same patch",
+                    path.display(),
+                    link.display()
+                ),
+                source: std::io::Error::from(std::io::ErrorKind::InvalidInput),
+            })),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns the file that a content change to `path` should be written to:
+    /// the target of `path` if it is a symlink on disk, otherwise `path`.
+    fn resolve(&self, path: &Path) -> PathBuf {
+        if self.find(path).is_none()
+            && std::fs::symlink_metadata(path).is_ok_and(|m| m.file_type().is_symlink())
+            && let Ok(target) = std::fs::canonicalize(path)
+        {
+            return target;
+        }
+        path.to_path_buf()
+    }
+
+    /// Whether anything exists at `path`, taking earlier hunks into account.
+    fn exists(&self, path: &Path) -> bool {
+        match self.find(path) {
+            Some(file) => !matches!(file.new_state, NewState::Deleted),
+            None => std::fs::symlink_metadata(path).is_ok(),
+        }
+    }
+
+    /// Current contents of `path` as `\n`-separated text, taking earlier
+    /// hunks into account, along with the format needed to write it back.
+    fn text(&self, path: &Path) -> Result<Option<(String, TextFormat)>, ApplyPatchError> {
+        let read = || match std::fs::read(path) {
+            Ok(contents) => Ok(Some(contents)),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(ApplyPatchError::IoError(IoError {
+                context: format!("Failed to read {}", path.display()),
+                source: err,
+            })),
+        };
+        let bytes = match self.find(path).map(|file| &file.new_state) {
+            None | Some(NewState::Unchanged) => read()?,
+            Some(NewState::Deleted) => None,
+            Some(NewState::File(contents)) => Some(contents.clone()),
+            Some(NewState::Symlink(_)) => {
+                return Err(ApplyPatchError::UnsupportedEncoding {
+                    path: path.to_path_buf(),
+                    reason: "it is a symlink".to_string(),
+                });
+            }
+        };
+        bytes.map(|bytes| decode_text(path, &bytes)).transpose()
+    }
+
+    fn permissions(&self, path: &Path) -> Option<std::fs::Permissions> {
+        match self.find(path) {
+            Some(file) => file.permissions.clone(),
+            None => std::fs::metadata(path).ok().map(|m| m.permissions()),
+        }
+    }
+
+    /// Sets the permission bits of the file at `path`, which must exist and
+    /// not be a symlink created by this patch.
+    fn chmod(&mut self, path: &Path, mode: u32) -> Result<(), ApplyPatchError> {
+        let chmod_error = |source| {
+            ApplyPatchError::IoError(IoError {
+                context: format!("Failed to change the mode of {}", path.display()),
+                source,
+            })
+        };
+        if !self.exists(path) {
+            return Err(chmod_error(std::io::Error::from(
+                std::io::ErrorKind::NotFound,
+            )));
+        }
+        let permissions = Some(permissions_from_mode(mode).map_err(chmod_error)?);
+        match self.files.iter_mut().find(|file| file.path == path) {
+            Some(file) if matches!(file.new_state, NewState::Symlink(_)) => Err(chmod_error(
+                std::io::Error::new(std::io::ErrorKind::InvalidInput, "path is a symlink"),
+            )),
+            Some(file) => {
+                file.permissions = permissions;
+                Ok(())
+            }
+            None => self.set(path, NewState::Unchanged, permissions),
+        }
+    }
+
+    /// Records the new state of `path`, snapshotting what is on disk the
+    /// first time it is touched.
+    fn set(
+        &mut self,
+        path: &Path,
+        new_state: NewState,
+        permissions: Option<std::fs::Permissions>,
+    ) -> Result<(), ApplyPatchError> {
+        if let Some(file) = self.files.iter_mut().find(|file| file.path == path) {
+            file.new_state = new_state;
+            if permissions.is_some() {
+                file.permissions = permissions;
+            }
+            return Ok(());
+        }
+        let read_error = |source| {
+            ApplyPatchError::IoError(IoError {
+                context: format!("Failed to read {}", path.display()),
+                source,
+            })
+        };
+        let original = match std::fs::symlink_metadata(path) {
+            Ok(metadata) if metadata.file_type().is_symlink() => Some(OriginalFile::Symlink(
+                std::fs::read_link(path).map_err(read_error)?,
+            )),
+            Ok(metadata) => Some(OriginalFile::File {
+                contents: std::fs::read(path).map_err(read_error)?,
+                permissions: metadata.permissions(),
+            }),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => None,
+            Err(err) => return Err(read_error(err)),
+        };
+        let permissions = permissions.or_else(|| match &original {
+            Some(OriginalFile::File { permissions, .. }) => Some(permissions.clone()),
+            _ => None,
+        });
+        self.files.push(PlannedFile {
+            path: path.to_path_buf(),
+            new_state,
+            permissions,
+            original,
+        });
+        Ok(())
+    }
+
+    /// Writes the plan to disk. New files and symlinks are staged next to
+    /// their destinations first so that a failure while writing leaves the
+    /// workspace untouched; only the final renames, permission changes and
+    /// deletions need to be rolled back.
+    fn commit(self) -> Result<(), ApplyPatchError> {
+        let mut created_dirs: Vec<PathBuf> = Vec::new();
+        let staged = match self.stage(&mut created_dirs) {
+            Ok(staged) => staged,
+            Err(err) => {
+                remove_created_dirs(&created_dirs);
+                return Err(err);
+            }
+        };
+
+        // Write every new file before deleting anything so that a move never
+        // leaves the moved contents without a copy on disk.
+        let (writes, rest): (Vec<_>, Vec<_>) = self
+            .files
+            .iter()
+            .zip(staged)
+            .partition(|(_, staged)| staged.is_some());
+        let (chmods, deletes): (Vec<_>, Vec<_>) = rest
+            .into_iter()
+            .partition(|(file, _)| matches!(file.new_state, NewState::Unchanged));
+        let mut committed: Vec<&PlannedFile> = Vec::new();
+        for (file, staged) in writes.into_iter().chain(chmods).chain(deletes) {
+            let result = match (staged, &file.permissions) {
+                (Some(temp), _) => temp.persist(&file.path).map_err(|err| err.error),
+                (None, Some(permissions)) if matches!(file.new_state, NewState::Unchanged) => {
+                    std::fs::set_permissions(&file.path, permissions.clone())
+                }
+                (None, _) => std::fs::remove_file(&file.path),
+            };
+            if let Err(source) = result {
+                let (restored, not_restored) = roll_back(&committed);
+                remove_created_dirs(&created_dirs);
+                return Err(ApplyPatchError::PartialApply(PartialApplyError {
+                    path: file.path.clone(),
+                    source,
+                    restored,
+                    not_restored,
+                }));
             }
+            committed.push(file);
+        }
+        Ok(())
+    }
+
+    /// Creates each new file or symlink under a temporary name in the same
+    /// directory, returning one entry per planned file (`None` for deletions
+    /// and permission-only changes).
+    fn stage(
+        &self,
+        created_dirs: &mut Vec<PathBuf>,
+    ) -> Result<Vec<Option<tempfile::TempPath>>, ApplyPatchError> {
+        let mut staged = Vec::with_capacity(self.files.len());
+        for file in &self.files {
+            if matches!(file.new_state, NewState::Deleted | NewState::Unchanged) {
+                staged.push(None);
+                continue;
+            }
+            let write_error = |source| {
+                ApplyPatchError::IoError(IoError {
+                    context: format!("Failed to write file {}", file.path.display()),
+                    source,
+                })
+            };
+            // Renaming over a read-only file would succeed, so check up front
+            // that the file could have been written in place.
+            if matches!(file.original, Some(OriginalFile::File { .. })) {
+                std::fs::OpenOptions::new()
+                    .write(true)
+                    .open(&file.path)
+                    .map_err(write_error)?;
+            }
+            let parent = match file.path.parent() {
+                Some(parent) if !parent.as_os_str().is_empty() => parent,
+                _ => Path::new("."),
+            };
+            create_dir_all_recording(parent, created_dirs).map_err(|source| {
+                ApplyPatchError::IoError(IoError {
+                    context: format!(
+                        "Failed to create parent directories for {}",
+                        file.path.display()
+                    ),
+                    source,
+                })
+            })?;
+            let mut builder = tempfile::Builder::new();
+            builder.prefix(".apply_patch");
+            let temp = match &file.new_state {
+                NewState::Symlink(target) => Some(
+                    builder
+                        .make_in(parent, |temp_path| create_symlink(target, temp_path))
+                        .map_err(write_error)?
+                        .into_temp_path(),
+                ),
+                NewState::File(contents) => {
+                    // Unlike `tempfile_in`, this honours the umask for new files.
+                    let mut temp = builder
+                        .make_in(parent, |temp_path| {
+                            std::fs::OpenOptions::new()
+                                .write(true)
+                                .create_new(true)
+                                .open(temp_path)
+                        })
+                        .map_err(write_error)?;
+                    std::io::Write::write_all(temp.as_file_mut(), contents).map_err(write_error)?;
+                    if let Some(permissions) = &file.permissions {
+                        temp.as_file()
+                            .set_permissions(permissions.clone())
+                            .map_err(write_error)?;
+                    }
+                    Some(temp.into_temp_path())
+                }
+                NewState::Deleted | NewState::Unchanged => None,
+            };
+            staged.push(temp);
         }
+        Ok(staged)
+    }
+}
+
+#[cfg(unix)]
+fn create_symlink(target: &Path, link: &Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(target, link)
+}
+
+#[cfg(not(unix))]
+fn create_symlink(_target: &Path, _link: &Path) -> std::io::Result<()> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "symlinks can only be created on Unix",
+    ))
+}
+
+#[cfg(unix)]
+fn permissions_from_mode(mode: u32) -> std::io::Result<std::fs::Permissions> {
+    use std::os::unix::fs::PermissionsExt;
+    Ok(std::fs::Permissions::from_mode(mode))
+}
+
+#[cfg(not(unix))]
+fn permissions_from_mode(_mode: u32) -> std::io::Result<std::fs::Permissions> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "file modes can only be changed on Unix",
+    ))
+}
+
+/// Permission bits of the file at `path`, if it exists and the platform has them.
+fn file_mode(path: &Path) -> Option<u32> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::metadata(path)
+            .ok()
+            .map(|m| m.permissions().mode() & 0o7777)
     }
-    Ok(AffectedPaths {
-        added,
-        modified,
-        deleted,
+    #[cfg(not(unix))]
+    {
+        let _ = path;
+        None
+    }
+}
+
+fn decode_text(path: &Path, bytes: &[u8]) -> Result<(String, TextFormat), ApplyPatchError> {
+    encoding::decode(bytes).map_err(|reason| ApplyPatchError::UnsupportedEncoding {
+        path: path.to_path_buf(),
+        reason,
     })
 }
 
+/// Like [`std::fs::create_dir_all`], but records the directories it created
+/// (outermost first) so that they can be removed again on rollback.
+fn create_dir_all_recording(dir: &Path, created: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    let missing: Vec<&Path> = dir.ancestors().take_while(|d| !d.exists()).collect();
+    std::fs::create_dir_all(dir)?;
+    created.extend(missing.into_iter().rev().map(Path::to_path_buf));
+    Ok(())
+}
+
+fn remove_created_dirs(created: &[PathBuf]) {
+    for dir in created.iter().rev() {
+        // Only succeeds if the directory is still empty, which is what we want.
+        let _ = std::fs::remove_dir(dir);
+    }
+}
+
+/// Restores the original state of `committed`, most recent change first.
+fn roll_back(committed: &[&PlannedFile]) -> (Vec<PathBuf>, Vec<(PathBuf, String)>) {
+    let mut restored = Vec::new();
+    let mut not_restored = Vec::new();
+    for file in committed.iter().rev() {
+        match restore(file) {
+            Ok(()) => restored.push(file.path.clone()),
+            Err(err) => not_restored.push((file.path.clone(), err.to_string())),
+        }
+    }
+    (restored, not_restored)
+}
+
+fn restore(file: &PlannedFile) -> std::io::Result<()> {
+    // Writing through a symlink created by the patch would clobber its
+    // target, so remove it first.
+    let replaced_by_symlink = matches!(file.new_state, NewState::Symlink(_));
+    match &file.original {
+        Some(OriginalFile::File {
+            contents,
+            permissions,
+        }) => {
+            if replaced_by_symlink {
+                std::fs::remove_file(&file.path)?;
+            }
+            std::fs::write(&file.path, contents)?;
+            std::fs::set_permissions(&file.path, permissions.clone())
+        }
+        Some(OriginalFile::Symlink(target)) => {
+            if !matches!(file.new_state, NewState::Deleted) {
+                std::fs::remove_file(&file.path)?;
+            }
+            create_symlink(target, &file.path)
+        }
+        None => std::fs::remove_file(&file.path),
+    }
+}
+
 struct AppliedPatch {
+    /// Both contents are `\n`-separated; see [`encoding`].
     original_contents: String,
     new_contents: String,
+    format: TextFormat,
 }
 
 /// Return *only* the new file contents (joined into a single `String`) after
@@ -610,9 +1371,10 @@ struct AppliedPatch {
 fn derive_new_contents_from_chunks(
     path: &Path,
     chunks: &[UpdateFileChunk],
+    options: MatchOptions,
 ) -> std::result::Result<AppliedPatch, ApplyPatchError> {
-    let original_contents = match std::fs::read_to_string(path) {
-        Ok(contents) => contents,
+    let (original_contents, format) = match std::fs::read(path) {
+        Ok(bytes) => decode_text(path, &bytes)?,
         Err(err) => {
             return Err(ApplyPatchError::IoError(IoError {
                 context: format!("Failed to read file to update {}", path.display()),
@@ -620,7 +1382,40 @@ fn derive_new_contents_from_chunks(
             }));
         }
     };
+    let format = requested_format(format, chunks);
+    let new_contents = format.restore_trailing_newline(apply_chunks_to_contents(
+        path,
+        &original_contents,
+        chunks,
+        options,
+    )?);
+    Ok(AppliedPatch {
+        original_contents,
+        new_contents,
+        format,
+    })
+}
+
+/// `format` with the trailing newline requested by the last chunk that adds or
+/// removes it, if any.
+fn requested_format(format: TextFormat, chunks: &[UpdateFileChunk]) -> TextFormat {
+    match chunks
+        .iter()
+        .rev()
+        .find(|chunk| chunk.old_missing_newline != chunk.new_missing_newline)
+    {
+        Some(chunk) => format.with_trailing_newline(!chunk.new_missing_newline),
+        None => format,
+    }
+}
 
+/// Applies `chunks` to `original_contents`, the current contents of `path`.
+fn apply_chunks_to_contents(
+    path: &Path,
+    original_contents: &str,
+    chunks: &[UpdateFileChunk],
+    options: MatchOptions,
+) -> std::result::Result<String, ApplyPatchError> {
     let mut original_lines: Vec<String> = original_contents
         .split('\n')
         .map(|s| s.to_string())
@@ -632,17 +1427,13 @@ fn derive_new_contents_from_chunks(
         original_lines.pop();
     }
 
-    let replacements = compute_replacements(&original_lines, path, chunks)?;
+    let replacements = compute_replacements(&original_lines, path, chunks, options)?;
     let new_lines = apply_replacements(original_lines, &replacements);
     let mut new_lines = new_lines;
     if !new_lines.last().is_some_and(|s| s.is_empty()) {
         new_lines.push(String::new());
     }
-    let new_contents = new_lines.join("\n");
-    Ok(AppliedPatch {
-        original_contents,
-        new_contents,
-    })
+    Ok(new_lines.join("\n"))
 }
 
 /// Compute a list of replacements needed to transform `original_lines` into the
@@ -652,6 +1443,7 @@ fn compute_replacements(
     original_lines: &[String],
     path: &Path,
     chunks: &[UpdateFileChunk],
+    options: MatchOptions,
 ) -> std::result::Result<Vec<(usize, usize, Vec<String>)>, ApplyPatchError> {
     let mut replacements: Vec<(usize, usize, Vec<String>)> = Vec::new();
     let mut line_index: usize = 0;
@@ -660,18 +1452,27 @@ fn compute_replacements(
         // If a chunk has a `change_context`, we use seek_sequence to find it, then
         // adjust our `line_index` to continue from there.
         if let Some(ctx_line) = &chunk.change_context {
-            if let Some(idx) = seek_sequence::seek_sequence(
-                original_lines,
-                std::slice::from_ref(ctx_line),
-                line_index,
-                false,
-            ) {
+            let ctx_pattern = std::slice::from_ref(ctx_line);
+            if let Some(idx) =
+                seek_sequence::seek_sequence(original_lines, ctx_pattern, line_index, false)
+                    .or_else(|| {
+                        seek_sequence::seek_sequence_fuzzy(
+                            original_lines,
+                            ctx_pattern,
+                            line_index,
+                            false,
+                            options.fuzzy_threshold,
+                        )
+                        .map(|region| region.start)
+                    })
+            {
                 line_index = idx + 1;
             } else {
                 return Err(ApplyPatchError::ComputeReplacements(format!(
-                    "Failed to find context '{}' in {}",
+                    "Failed to find context '{}' in {}{}",
                     ctx_line,
-                    path.display()
+                    path.display(),
+                    closest_match_hint(original_lines, ctx_pattern, line_index),
                 )));
             }
         }
@@ -724,11 +1525,27 @@ fn compute_replacements(
         if let Some(start_idx) = found {
             replacements.push((start_idx, pattern.len(), new_slice.to_vec()));
             line_index = start_idx + pattern.len();
+        } else if let Some((region, new_lines)) = seek_sequence::seek_sequence_fuzzy(
+            original_lines,
+            pattern,
+            line_index,
+            chunk.is_end_of_file,
+            options.fuzzy_threshold,
+        )
+        .and_then(|region| {
+            // The patch's copy of the context lines is only approximately
+            // right, so keep the file's own version of them.
+            let matched = &original_lines[region.start..region.start + pattern.len()];
+            keep_file_context_lines(matched, pattern, new_slice).map(|lines| (region, lines))
+        }) {
+            replacements.push((region.start, pattern.len(), new_lines));
+            line_index = region.start + pattern.len();
         } else {
             return Err(ApplyPatchError::ComputeReplacements(format!(
-                "Failed to find expected lines in {}:\n{}",
+                "Failed to find expected lines in {}:\n{}{}",
                 path.display(),
                 chunk.old_lines.join("\n"),
+                closest_match_hint(original_lines, pattern, line_index),
             )));
         }
     }
@@ -736,6 +1553,95 @@ fn compute_replacements(
     Ok(replacements)
 }
 
+/// Rebuilds `new_lines` for a hunk whose `old_lines` were fuzzily matched to
+/// `matched`, taking every line the hunk leaves unchanged from the file
+/// rather than from the patch. Returns `None` if a line the hunk deletes or
+/// replaces differs from the file, since removing it would silently discard
+/// text the model never saw.
+fn keep_file_context_lines(
+    matched: &[String],
+    old_lines: &[String],
+    new_lines: &[String],
+) -> Option<Vec<String>> {
+    let mut result = Vec::with_capacity(new_lines.len());
+    for op in similar::capture_diff_slices(similar::Algorithm::Myers, old_lines, new_lines) {
+        match op {
+            similar::DiffOp::Equal { old_index, len, .. } => {
+                result.extend_from_slice(&matched[old_index..old_index + len])
+            }
+            similar::DiffOp::Insert {
+                new_index, new_len, ..
+            } => result.extend_from_slice(&new_lines[new_index..new_index + new_len]),
+            similar::DiffOp::Replace {
+                old_index,
+                old_len,
+                new_index,
+                new_len,
+            } => {
+                if !seek_sequence::lines_match(
+                    &matched[old_index..old_index + old_len],
+                    &old_lines[old_index..old_index + old_len],
+                ) {
+                    return None;
+                }
+                result.extend_from_slice(&new_lines[new_index..new_index + new_len]);
+            }
+            similar::DiffOp::Delete {
+                old_index, old_len, ..
+            } => {
+                if !seek_sequence::lines_match(
+                    &matched[old_index..old_index + old_len],
+                    &old_lines[old_index..old_index + old_len],
+                ) {
+                    return None;
+                }
+            }
+        }
+    }
+    Some(result)
+}
+
+/// Describes the region of the file closest to `pattern` (if any is
+/// reasonably close) with its line numbers and a small diff, so that the
+/// model can correct the hunk on its next attempt.
+fn closest_match_hint(original_lines: &[String], pattern: &[String], start: usize) -> String {
+    let Some(region) = seek_sequence::most_similar_region(
+        original_lines,
+        pattern,
+        start,
+        MIN_CLOSEST_MATCH_SIMILARITY,
+    ) else {
+        return String::new();
+    };
+    let end = region.start + pattern.len();
+    let lines = if pattern.len() == 1 {
+        format!("line {end}")
+    } else {
+        format!("lines {}-{end}", region.start + 1)
+    };
+    let mut hint = format!(
+        "\n\nClosest match is at {lines} ({:.0}% similar). Lines starting with '-' are from the patch, lines starting with '+' are from the file:",
+        region.similarity * 100.0
+    );
+    let expected: Vec<&str> = pattern.iter().map(String::as_str).collect();
+    let actual: Vec<&str> = original_lines[region.start..end]
+        .iter()
+        .map(String::as_str)
+        .collect();
+    let diff = TextDiff::from_slices(&expected, &actual);
+    for change in diff.iter_all_changes() {
+        let sign = match change.tag() {
+            similar::ChangeTag::Equal => ' ',
+            similar::ChangeTag::Delete => '-',
+            similar::ChangeTag::Insert => '+',
+        };
+        hint.push('\n');
+        hint.push(sign);
+        hint.push_str(change.value());
+    }
+    hint
+}
+
 /// Apply the `(start_index, old_len, new_lines)` replacements to `original_lines`,
 /// returning the modified file contents as a vector of lines.
 fn apply_replacements(
@@ -775,23 +1681,25 @@ pub fn unified_diff_from_chunks(
     path: &Path,
     chunks: &[UpdateFileChunk],
 ) -> std::result::Result<ApplyPatchFileUpdate, ApplyPatchError> {
-    unified_diff_from_chunks_with_context(path, chunks, 1)
+    unified_diff_from_chunks_with_context(path, chunks, 1, MatchOptions::default())
 }
 
 pub fn unified_diff_from_chunks_with_context(
     path: &Path,
     chunks: &[UpdateFileChunk],
     context: usize,
+    options: MatchOptions,
 ) -> std::result::Result<ApplyPatchFileUpdate, ApplyPatchError> {
     let AppliedPatch {
         original_contents,
         new_contents,
-    } = derive_new_contents_from_chunks(path, chunks)?;
+        format,
+    } = derive_new_contents_from_chunks(path, chunks, options)?;
     let text_diff = TextDiff::from_lines(&original_contents, &new_contents);
     let unified_diff = text_diff.unified_diff().context_radius(context).to_string();
     Ok(ApplyPatchFileUpdate {
         unified_diff,
-        content: new_contents,
+        content: format.encode(&original_contents, &new_contents),
     })
 }
 
@@ -801,7 +1709,15 @@ pub fn print_summary(
     affected: &AffectedPaths,
     out: &mut impl std::io::Write,
 ) -> std::io::Result<()> {
-    writeln!(out, "Success. Updated the following files:")?;
+    write_summary("Success. Updated the following files:", affected, out)
+}
+
+fn write_summary(
+    header: &str,
+    affected: &AffectedPaths,
+    out: &mut impl std::io::Write,
+) -> std::io::Result<()> {
+    writeln!(out, "{header}")?;
     for path in &affected.added {
         writeln!(out, "A {}", path.display())?;
     }
@@ -1501,7 +2417,8 @@ g
                 .to_string(),
         ];
 
-        let result = maybe_parse_apply_patch_verified(&argv, session_dir.path());
+        let result =
+            maybe_parse_apply_patch_verified(&argv, session_dir.path(), MatchOptions::default());
 
         // Verify the patch contents - as otherwise we may have pulled contents
         // from the wrong file (as we're using relative paths)
@@ -1526,6 +2443,614 @@ g
         );
     }
 
+    #[test]
+    fn test_git_diff_in_heredoc_is_verified() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("old.txt"), "one\ntwo\n").unwrap();
+        fs::write(dir.path().join("gone.txt"), "bye\n").unwrap();
+        let diff = r#"diff --git a/old.txt b/new.txt
+similarity index 50%
+rename from old.txt
+rename to new.txt
+--- a/old.txt
++++ b/new.txt
+@@ -1,2 +1,2 @@
+ one
+-two
++2
+diff --git a/gone.txt b/gone.txt
+deleted file mode 100644
+--- a/gone.txt
++++ /dev/null
+@@ -1 +0,0 @@
+-bye
+diff --git a/added.txt b/added.txt
+new file mode 100644
+--- /dev/null
++++ b/added.txt
+@@ -0,0 +1 @@
++hi
+\ No newline at end of file"#;
+        let argv = args_bash(&format!("apply_patch <<'EOF'\n{diff}\nEOF\n"));
+
+        let result = maybe_parse_apply_patch_verified(&argv, dir.path(), MatchOptions::default());
+
+        assert_eq!(
+            result,
+            MaybeApplyPatchVerified::Body(ApplyPatchAction {
+                changes: HashMap::from([
+                    (
+                        dir.path().join("old.txt"),
+                        ApplyPatchFileChange::Update {
+                            unified_diff: "@@ -1,2 +1,2 @@\n one\n-two\n+2\n".to_string(),
+                            move_path: Some(dir.path().join("new.txt")),
+                            new_content: "one\n2\n".to_string(),
+                        },
+                    ),
+                    (
+                        dir.path().join("gone.txt"),
+                        ApplyPatchFileChange::Delete {
+                            content: "bye\n".to_string(),
+                        },
+                    ),
+                    (
+                        dir.path().join("added.txt"),
+                        ApplyPatchFileChange::Add {
+                            content: "hi".to_string(),
+                        },
+                    ),
+                ]),
+                patch: diff.to_string(),
+                cwd: dir.path().to_path_buf(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_apply_plain_unified_diff() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("plain.txt");
+        fs::write(&path, "alpha\nbeta\ngamma\n").unwrap();
+        let patch = format!(
+            "--- {path}\t2024-01-01 00:00:00\n+++ {path}\t2024-01-02 00:00:00\n@@ -1,3 +1,3 @@\n alpha\n-beta\n+BETA\n gamma\n",
+            path = path.display()
+        );
+
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        apply_patch(&patch, &mut stdout, &mut stderr).unwrap();
+
+        assert_eq!(
+            String::from_utf8(stdout).unwrap(),
+            format!(
+                "Success. Updated the following files:\nM {}\n",
+                path.display()
+            )
+        );
+        assert_eq!(fs::read_to_string(&path).unwrap(), "alpha\nBETA\ngamma\n");
+    }
+
+    #[test]
+    fn test_unified_diff_changes_trailing_newline() {
+        let dir = tempdir().unwrap();
+        let added = dir.path().join("added.txt");
+        let removed = dir.path().join("removed.txt");
+        fs::write(&added, "one\ntwo").unwrap();
+        fs::write(&removed, "one\ntwo\n").unwrap();
+        let patch = format!(
+            "--- {added}\n+++ {added}\n@@ -1,2 +1,2 @@\n one\n-two\n\\ No newline at end of file\n+two\n\
+             --- {removed}\n+++ {removed}\n@@ -1,2 +1,2 @@\n one\n-two\n+two\n\\ No newline at end of file\n",
+            added = added.display(),
+            removed = removed.display()
+        );
+
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        apply_patch(&patch, &mut stdout, &mut stderr).unwrap();
+
+        assert_eq!(fs::read(&added).unwrap(), b"one\ntwo\n");
+        assert_eq!(fs::read(&removed).unwrap(), b"one\ntwo");
+    }
+
+    /// Files used by the transactional tests: `a.txt` is updated, `b.txt` is
+    /// updated and moved into a new directory, and `d.txt` is deleted.
+    fn move_delete_update_patch(dir: &Path, last_hunk: &str) -> String {
+        fs::write(dir.join("a.txt"), "a1\na2\n").unwrap();
+        fs::write(dir.join("b.txt"), "b1\n").unwrap();
+        fs::write(dir.join("d.txt"), "d1\n").unwrap();
+        wrap_patch(&format!(
+            r#"*** Update File: {a}
+@@
+-a1
++A1
+*** Update File: {b}
+*** Move to: {c}
+@@
+-b1
++B1
+*** Delete File: {d}
+{last_hunk}"#,
+            a = dir.join("a.txt").display(),
+            b = dir.join("b.txt").display(),
+            c = dir.join("sub/c.txt").display(),
+            d = dir.join("d.txt").display(),
+        ))
+    }
+
+    #[test]
+    fn test_move_delete_and_update_apply_together() {
+        let dir = tempdir().unwrap();
+        let patch = move_delete_update_patch(
+            dir.path(),
+            &format!("*** Add File: {}\n+e1", dir.path().join("e.txt").display()),
+        );
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(dir.path().join("a.txt"), fs::Permissions::from_mode(0o755))
+                .unwrap();
+        }
+
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        apply_patch(&patch, &mut stdout, &mut stderr).unwrap();
+
+        assert_eq!(String::from_utf8(stderr).unwrap(), "");
+        assert_eq!(
+            fs::read_to_string(dir.path().join("a.txt")).unwrap(),
+            "A1\na2\n"
+        );
+        assert!(!dir.path().join("b.txt").exists());
+        assert_eq!(
+            fs::read_to_string(dir.path().join("sub/c.txt")).unwrap(),
+            "B1\n"
+        );
+        assert!(!dir.path().join("d.txt").exists());
+        assert_eq!(
+            fs::read_to_string(dir.path().join("e.txt")).unwrap(),
+            "e1\n"
+        );
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(dir.path().join("a.txt"))
+                .unwrap()
+                .permissions()
+                .mode();
+            assert_eq!(mode & 0o777, 0o755);
+        }
+        // No temporary files are left behind.
+        let mut names: Vec<String> = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        assert_eq!(names, vec!["a.txt", "e.txt", "sub"]);
+    }
+
+    fn assert_workspace_untouched(dir: &Path) {
+        assert_eq!(fs::read_to_string(dir.join("a.txt")).unwrap(), "a1\na2\n");
+        assert_eq!(fs::read_to_string(dir.join("b.txt")).unwrap(), "b1\n");
+        assert_eq!(fs::read_to_string(dir.join("d.txt")).unwrap(), "d1\n");
+        assert!(!dir.join("sub").exists());
+        assert!(!dir.join("e.txt").exists());
+    }
+
+    #[test]
+    fn test_failing_hunk_leaves_workspace_untouched() {
+        let dir = tempdir().unwrap();
+        let patch = move_delete_update_patch(
+            dir.path(),
+            &format!(
+                "*** Update File: {}\n@@\n-not there\n+x",
+                dir.path().join("a.txt").display()
+            ),
+        );
+
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        let result = apply_patch(&patch, &mut stdout, &mut stderr);
+
+        assert_eq!(
+            result,
+            Err(ApplyPatchError::ComputeReplacements(format!(
+                "Failed to find expected lines in {}:\nnot there",
+                dir.path().join("a.txt").display()
+            )))
+        );
+        assert_eq!(String::from_utf8(stdout).unwrap(), "");
+        assert_workspace_untouched(dir.path());
+    }
+
+    #[test]
+    fn test_failure_while_committing_rolls_back() {
+        let dir = tempdir().unwrap();
+        let patch = move_delete_update_patch(
+            dir.path(),
+            &format!("*** Add File: {}\n+e1", dir.path().join("e.txt").display()),
+        );
+        let hunks = parse_patch(&patch).unwrap().hunks;
+        let (plan, _) = PatchPlan::from_hunks(&hunks, MatchOptions::default()).unwrap();
+        // Simulate the file disappearing between planning and committing.
+        fs::remove_file(dir.path().join("d.txt")).unwrap();
+
+        let err = match plan.commit() {
+            Err(ApplyPatchError::PartialApply(err)) => err,
+            other => panic!("expected a partial apply error, got {other:?}"),
+        };
+
+        assert_eq!(err.path, dir.path().join("d.txt"));
+        assert_eq!(err.source.kind(), std::io::ErrorKind::NotFound);
+        // Writes are committed before deletions, and rolled back in reverse.
+        assert_eq!(
+            err.restored,
+            vec![
+                dir.path().join("b.txt"),
+                dir.path().join("e.txt"),
+                dir.path().join("sub/c.txt"),
+                dir.path().join("a.txt"),
+            ]
+        );
+        assert_eq!(err.not_restored, Vec::new());
+        assert!(
+            err.to_string()
+                .ends_with("Rolled back changes to 4 file(s); no files were modified."),
+            "{err}"
+        );
+        fs::write(dir.path().join("d.txt"), "d1\n").unwrap();
+        assert_workspace_untouched(dir.path());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_binary_chmod_and_symlink_hunks() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("run.sh"), "echo hi\n").unwrap();
+        fs::write(dir.path().join("logo.bin"), [0u8, 1, 2]).unwrap();
+        let patch = wrap_patch(&format!(
+            "*** Add Binary File: {dot}\n\
+             AP8Q\n\
+             *** Replace Binary File: {logo}\n\
+             gA==\n\
+             *** Chmod File: {run}\n\
+             *** Mode: 755\n\
+             *** Add Symlink: {link}\n\
+             *** Target: run.sh",
+            dot = dir.path().join("img/dot.bin").display(),
+            logo = dir.path().join("logo.bin").display(),
+            run = dir.path().join("run.sh").display(),
+            link = dir.path().join("latest").display(),
+        ));
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        apply_patch(&patch, &mut stdout, &mut stderr).unwrap();
+
+        assert_eq!(
+            String::from_utf8(stdout).unwrap(),
+            format!(
+                "Success. Updated the following files:\nA {}\nA {}\nM {}\nM {}\n",
+                dir.path().join("img/dot.bin").display(),
+                dir.path().join("latest").display(),
+                dir.path().join("logo.bin").display(),
+                dir.path().join("run.sh").display(),
+            )
+        );
+        assert_eq!(
+            fs::read(dir.path().join("img/dot.bin")).unwrap(),
+            [0, 255, 16]
+        );
+        assert_eq!(fs::read(dir.path().join("logo.bin")).unwrap(), [128]);
+        let mode = fs::metadata(dir.path().join("run.sh"))
+            .unwrap()
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o755);
+        assert_eq!(
+            fs::read_link(dir.path().join("latest")).unwrap(),
+            PathBuf::from("run.sh")
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_update_through_symlink_edits_target() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("real.txt"), "one\n").unwrap();
+        std::os::unix::fs::symlink("real.txt", dir.path().join("link.txt")).unwrap();
+        let patch = wrap_patch(&format!(
+            "*** Update File: {}\n@@\n-one\n+two",
+            dir.path().join("link.txt").display()
+        ));
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        apply_patch(&patch, &mut stdout, &mut stderr).unwrap();
+
+        assert!(
+            fs::symlink_metadata(dir.path().join("link.txt"))
+                .unwrap()
+                .file_type()
+                .is_symlink()
+        );
+        assert_eq!(
+            fs::read_to_string(dir.path().join("real.txt")).unwrap(),
+            "two\n"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_below_added_symlink_is_rejected() {
+        let dir = tempdir().unwrap();
+        let outside = tempdir().unwrap();
+        let patch = wrap_patch(&format!(
+            "*** Add Symlink: {}\n*** Target: {}\n*** Add File: {}\n+pwned",
+            dir.path().join("evil").display(),
+            outside.path().display(),
+            dir.path().join("evil/passwd").display()
+        ));
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+
+        assert!(apply_patch(&patch, &mut stdout, &mut stderr).is_err());
+        assert!(!outside.path().join("passwd").exists());
+        assert!(fs::symlink_metadata(dir.path().join("evil")).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_rollback_restores_replaced_symlink() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("real.txt"), "keep\n").unwrap();
+        std::os::unix::fs::symlink("real.txt", dir.path().join("link")).unwrap();
+        fs::write(dir.path().join("gone.txt"), "x\n").unwrap();
+        let patch = wrap_patch(&format!(
+            "*** Add Symlink: {}\n*** Target: elsewhere\n*** Delete File: {}",
+            dir.path().join("link").display(),
+            dir.path().join("gone.txt").display()
+        ));
+        let hunks = parse_patch(&patch).unwrap().hunks;
+        let (plan, _) = PatchPlan::from_hunks(&hunks, MatchOptions::default()).unwrap();
+        fs::remove_file(dir.path().join("gone.txt")).unwrap();
+
+        assert!(matches!(
+            plan.commit(),
+            Err(ApplyPatchError::PartialApply(_))
+        ));
+        assert_eq!(
+            fs::read_link(dir.path().join("link")).unwrap(),
+            PathBuf::from("real.txt")
+        );
+        assert_eq!(
+            fs::read_to_string(dir.path().join("real.txt")).unwrap(),
+            "keep\n"
+        );
+    }
+
+    #[test]
+    fn test_verified_chmod_wraps_earlier_change() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("run.sh");
+        let patch =
+            wrap_patch("*** Add File: run.sh\n+echo hi\n*** Chmod File: run.sh\n*** Mode: 755");
+        let argv = vec!["apply_patch".to_string(), patch];
+        let MaybeApplyPatchVerified::Body(action) =
+            maybe_parse_apply_patch_verified(&argv, dir.path(), MatchOptions::default())
+        else {
+            panic!("expected a verified patch");
+        };
+        assert_eq!(
+            action.changes(),
+            &HashMap::from([(
+                path,
+                ApplyPatchFileChange::Mode {
+                    old_mode: None,
+                    new_mode: 0o755,
+                    contents: Some(Box::new(ApplyPatchFileChange::Add {
+                        content: "echo hi\n".to_string()
+                    })),
+                }
+            )])
+        );
+    }
+
+    #[test]
+    fn test_update_keeps_mixed_line_endings() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("mixed.txt");
+        fs::write(&path, "a\r\nb\r\nc\n").unwrap();
+        let patch = wrap_patch(&format!(
+            "*** Update File: {}\n@@\n-a\n+A\n+inserted\n b",
+            path.display()
+        ));
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        apply_patch(&patch, &mut stdout, &mut stderr).unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"A\r\ninserted\r\nb\r\nc\n");
+
+        // An explicit trailing newline change wins over the file's own.
+        let patch = format!(
+            "--- {path}\n+++ {path}\n@@ -3 +3 @@\n-c\n+c\n\\ No newline at end of file\n",
+            path = path.display()
+        );
+        apply_patch(&patch, &mut stdout, &mut stderr).unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"A\r\ninserted\r\nb\r\nc");
+    }
+
+    #[test]
+    fn test_update_preserves_crlf_bom_and_missing_final_newline() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("win.txt");
+        fs::write(&path, "\u{FEFF}first\r\nsecond\r\nthird").unwrap();
+        let patch = wrap_patch(&format!(
+            "*** Update File: {}\n@@\n first\n-second\n+changed\n+added",
+            path.display()
+        ));
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        apply_patch(&patch, &mut stdout, &mut stderr).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "\u{FEFF}first\r\nchanged\r\nadded\r\nthird"
+        );
+        let update = unified_diff_from_chunks(
+            &path,
+            &parse_patch(&wrap_patch(&format!(
+                "*** Update File: {}\n@@\n-third\n+last",
+                path.display()
+            )))
+            .unwrap()
+            .hunks
+            .into_iter()
+            .find_map(|hunk| match hunk {
+                Hunk::UpdateFile { chunks, .. } => Some(chunks),
+                _ => None,
+            })
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            update,
+            ApplyPatchFileUpdate {
+                unified_diff: "@@ -3,2 +3,2 @@\n added\n-third\n\\ No newline at end of file\n+last\n\\ No newline at end of file\n"
+                    .to_string(),
+                content: "\u{FEFF}first\r\nchanged\r\nadded\r\nlast".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_update_refuses_non_utf8_file() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("latin1.txt");
+        fs::write(&path, b"caf\xE9\n").unwrap();
+        let patch = wrap_patch(&format!(
+            "*** Update File: {}\n@@\n-caf\n+cafe",
+            path.display()
+        ));
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        let result = apply_patch(&patch, &mut stdout, &mut stderr);
+
+        assert_eq!(
+            result,
+            Err(ApplyPatchError::UnsupportedEncoding {
+                path: path.clone(),
+                reason: "invalid UTF-8 at byte 3".to_string(),
+            })
+        );
+        assert_eq!(
+            String::from_utf8(stderr).unwrap(),
+            format!(
+                "Cannot update {}: invalid UTF-8 at byte 3. apply_patch only edits UTF-8 text files.\n",
+                path.display()
+            )
+        );
+        assert_eq!(fs::read(&path).unwrap(), b"caf\xE9\n");
+    }
+
+    #[test]
+    fn test_fuzzy_match_keeps_file_context_lines() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("fuzzy.py");
+        fs::write(
+            &path,
+            "def total(items):\n    result = sum(item.price for item in items)\n    return result\n",
+        )
+        .unwrap();
+        // The context line has a typo (`prices`) that no exact pass tolerates.
+        let patch = wrap_patch(&format!(
+            r#"*** Update File: {}
+@@
+     result = sum(item.prices for item in items)
+-    return result
++    return round(result, 2)"#,
+            path.display()
+        ));
+
+        let options = MatchOptions {
+            fuzzy_threshold: 0.9,
+        };
+        apply_patch_with_options(&patch, options, &mut Vec::new(), &mut Vec::new()).unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "def total(items):\n    result = sum(item.price for item in items)\n    return round(result, 2)\n"
+        );
+
+        // Fuzzy matching is off by default, so the same patch is rejected.
+        fs::write(&path, "def total(items):\n    result = sum(item.price for item in items)\n    return result\n").unwrap();
+        let result = apply_patch(&patch, &mut Vec::new(), &mut Vec::new());
+        assert!(
+            matches!(result, Err(ApplyPatchError::ComputeReplacements(_))),
+            "{result:?}"
+        );
+    }
+
+    #[test]
+    fn test_fuzzy_match_rejects_near_miss_deleted_line() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("near_miss.py");
+        let original = "def total(items):\n    result = sum(item.price for item in items)\n    return result\n";
+        fs::write(&path, original).unwrap();
+        // The deleted line is 90% similar to the file's line but not the same
+        // text, so applying the hunk would remove a line the model never saw.
+        let patch = wrap_patch(&format!(
+            r#"*** Update File: {}
+@@
+-    result = sum(item.prices for item in items)
++    result = sum(item.cost for item in items)
+     return result"#,
+            path.display()
+        ));
+        let options = MatchOptions {
+            fuzzy_threshold: 0.9,
+        };
+
+        let result = apply_patch_with_options(&patch, options, &mut Vec::new(), &mut Vec::new());
+
+        let Err(ApplyPatchError::ComputeReplacements(message)) = result else {
+            panic!("expected the hunk to be rejected, got {result:?}");
+        };
+        assert!(
+            message.contains("Closest match is at lines 2-3"),
+            "{message}"
+        );
+        assert_eq!(fs::read_to_string(&path).unwrap(), original);
+    }
+
+    #[test]
+    fn test_failed_match_reports_closest_region() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("closest.txt");
+        fs::write(&path, "alpha\nbeta\ngamma one\ndelta two\nepsilon\n").unwrap();
+        let patch = wrap_patch(&format!(
+            r#"*** Update File: {}
+@@
+-gamma 1
+-delta 2
++replaced"#,
+            path.display()
+        ));
+
+        let result = apply_patch(&patch, &mut Vec::new(), &mut Vec::new());
+
+        assert_eq!(
+            result,
+            Err(ApplyPatchError::ComputeReplacements(format!(
+                "Failed to find expected lines in {}:\ngamma 1\ndelta 2\n\n\
+                 Closest match is at lines 3-4 (67% similar). Lines starting with '-' are from the patch, lines starting with '+' are from the file:\n\
+                 -gamma 1\n-delta 2\n+gamma one\n+delta two",
+                path.display()
+            )))
+        );
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "alpha\nbeta\ngamma one\ndelta two\nepsilon\n"
+        );
+    }
+
     #[test]
     fn test_apply_patch_fails_on_write_error() {
         let dir = tempdir().unwrap();
@@ -7,12 +7,16 @@
 //! begin_patch: "*** Begin Patch" LF
 //! end_patch: "*** End Patch" LF?
 //!
-//! hunk: add_hunk | delete_hunk | update_hunk
+//! hunk: add_hunk | delete_hunk | update_hunk | binary_hunk | chmod_hunk | symlink_hunk
 //! add_hunk: "*** Add File: " filename LF add_line+
 //! delete_hunk: "*** Delete File: " filename LF
 //! update_hunk: "*** Update File: " filename LF change_move? change?
+//! binary_hunk: ("*** Add Binary File: " | "*** Replace Binary File: ") filename LF base64_line*
+//! chmod_hunk: "*** Chmod File: " filename LF "*** Mode: " /[0-7]{3,6}/ LF
+//! symlink_hunk: "*** Add Symlink: " filename LF "*** Target: " filename LF
 //! filename: /(.+)/
 //! add_line: "+" /(.+)/ LF -> line
+//! base64_line: /[A-Za-z0-9+\/=]+/ LF
 //!
 //! change_move: "*** Move to: " filename LF
 //! change: (change_context | change_line)+ eof_line?
@@ -22,7 +26,13 @@
 //!
 //! The parser below is a little more lenient than the explicit spec and allows for
 //! leading/trailing whitespace around patch markers.
+//!
+//! Regular unified diffs (`diff -u`, `git diff`) are also accepted and are
+//! translated into the same hunks by [`crate::unified_diff`]. They are
+//! recognized by a file header on their first line.
 use crate::ApplyPatchArgs;
+use crate::unified_diff;
+use base64::Engine;
 use std::path::Path;
 use std::path::PathBuf;
 
@@ -34,6 +44,12 @@ const ADD_FILE_MARKER: &str = "*** Add File: ";
 const DELETE_FILE_MARKER: &str = "*** Delete File: ";
 const UPDATE_FILE_MARKER: &str = "*** Update File: ";
 const MOVE_TO_MARKER: &str = "*** Move to: ";
+const ADD_BINARY_FILE_MARKER: &str = "*** Add Binary File: ";
+const REPLACE_BINARY_FILE_MARKER: &str = "*** Replace Binary File: ";
+const CHMOD_FILE_MARKER: &str = "*** Chmod File: ";
+const MODE_MARKER: &str = "*** Mode: ";
+const ADD_SYMLINK_MARKER: &str = "*** Add Symlink: ";
+const TARGET_MARKER: &str = "*** Target: ";
 const EOF_MARKER: &str = "*** End of File";
 const CHANGE_CONTEXT_MARKER: &str = "@@ ";
 const EMPTY_CHANGE_CONTEXT_MARKER: &str = "@@";
@@ -73,14 +89,45 @@ pub enum Hunk {
         /// should occur later in the file than the previous chunk.
         chunks: Vec<UpdateFileChunk>,
     },
+    /// Creates (or overwrites) a file with arbitrary bytes, given as base64
+    /// in the patch.
+    AddBinaryFile {
+        path: PathBuf,
+        contents: Vec<u8>,
+    },
+    /// Overwrites an existing file with arbitrary bytes.
+    ReplaceBinaryFile {
+        path: PathBuf,
+        contents: Vec<u8>,
+    },
+    /// Sets the permission bits of an existing file, e.g. `0o755`.
+    ChmodFile {
+        path: PathBuf,
+        mode: u32,
+    },
+    /// Creates a symbolic link at `path` that points to `target`. The target
+    /// is stored verbatim, i.e. relative targets are relative to the link.
+    AddSymlink {
+        path: PathBuf,
+        target: PathBuf,
+    },
 }
 
 impl Hunk {
     pub fn resolve_path(&self, cwd: &Path) -> PathBuf {
+        cwd.join(self.path())
+    }
+
+    /// The path the hunk applies to, as written in the patch.
+    pub fn path(&self) -> &Path {
         match self {
-            Hunk::AddFile { path, .. } => cwd.join(path),
-            Hunk::DeleteFile { path } => cwd.join(path),
-            Hunk::UpdateFile { path, .. } => cwd.join(path),
+            Hunk::AddFile { path, .. }
+            | Hunk::DeleteFile { path }
+            | Hunk::UpdateFile { path, .. }
+            | Hunk::AddBinaryFile { path, .. }
+            | Hunk::ReplaceBinaryFile { path, .. }
+            | Hunk::ChmodFile { path, .. }
+            | Hunk::AddSymlink { path, .. } => path,
         }
     }
 }
@@ -101,6 +148,13 @@ pub struct UpdateFileChunk {
     /// If set to true, `old_lines` must occur at the end of the source file.
     /// (Tolerance around trailing newlines should be encouraged.)
     pub is_end_of_file: bool,
+
+    /// Whether the old and new sides of the chunk end without a final newline,
+    /// as marked by `\ No newline at end of file` in unified diffs. When the
+    /// two differ, the chunk adds or removes the file's trailing newline;
+    /// otherwise the file keeps its original one.
+    pub old_missing_newline: bool,
+    pub new_missing_newline: bool,
 }
 
 pub fn parse_patch(patch: &str) -> Result<ApplyPatchArgs, ParseError> {
@@ -153,6 +207,9 @@ enum ParseMode {
 
 fn parse_patch_text(patch: &str, mode: ParseMode) -> Result<ApplyPatchArgs, ParseError> {
     let lines: Vec<&str> = patch.trim().lines().collect();
+    if let Some(args) = parse_unified_diff_text(&lines, &mode)? {
+        return Ok(args);
+    }
     let lines: &[&str] = match check_patch_boundaries_strict(&lines) {
         Ok(()) => &lines,
         Err(e) => match mode {
@@ -182,6 +239,31 @@ fn parse_patch_text(patch: &str, mode: ParseMode) -> Result<ApplyPatchArgs, Pars
     })
 }
 
+/// If the patch text (optionally wrapped in a heredoc in lenient mode) is a
+/// unified diff rather than a `*** Begin Patch` envelope, parses it as such.
+fn parse_unified_diff_text(
+    lines: &[&str],
+    mode: &ParseMode,
+) -> Result<Option<ApplyPatchArgs>, ParseError> {
+    if lines.first() == Some(&BEGIN_PATCH_MARKER) {
+        return Ok(None);
+    }
+    let (diff_lines, line_number) = if unified_diff::is_unified_diff(lines) {
+        (lines, 1)
+    } else {
+        match (mode, strip_heredoc(lines)) {
+            (ParseMode::Lenient, Some(inner)) if unified_diff::is_unified_diff(inner) => (inner, 2),
+            _ => return Ok(None),
+        }
+    };
+    let hunks = unified_diff::parse_unified_diff(diff_lines, line_number)?;
+    Ok(Some(ApplyPatchArgs {
+        hunks,
+        patch: diff_lines.join("\n"),
+        workdir: None,
+    }))
+}
+
 /// Checks the start and end lines of the patch text for `apply_patch`,
 /// returning an error if they do not match the expected markers.
 fn check_patch_boundaries_strict(lines: &[&str]) -> Result<(), ParseError> {
@@ -204,25 +286,31 @@ fn check_patch_boundaries_lenient<'a>(
     original_lines: &'a [&'a str],
     original_parse_error: ParseError,
 ) -> Result<&'a [&'a str], ParseError> {
-    match original_lines {
-        [first, .., last] => {
-            if (first == &"<<EOF" || first == &"<<'EOF'" || first == &"<<\"EOF\"")
-                && last.ends_with("EOF")
-                && original_lines.len() >= 4
-            {
-                let inner_lines = &original_lines[1..original_lines.len() - 1];
-                match check_patch_boundaries_strict(inner_lines) {
-                    Ok(()) => Ok(inner_lines),
-                    Err(e) => Err(e),
-                }
-            } else {
-                Err(original_parse_error)
+    match strip_heredoc(original_lines) {
+        Some(inner_lines) if inner_lines.len() >= 2 => {
+            match check_patch_boundaries_strict(inner_lines) {
+                Ok(()) => Ok(inner_lines),
+                Err(e) => Err(e),
             }
         }
         _ => Err(original_parse_error),
     }
 }
 
+/// Returns the lines between a leading `<<EOF` (possibly quoted) and a
+/// trailing line ending with `EOF`, if the text is wrapped like that.
+fn strip_heredoc<'a>(lines: &'a [&'a str]) -> Option<&'a [&'a str]> {
+    match lines {
+        [first, inner @ .., last]
+            if (first == &"<<EOF" || first == &"<<'EOF'" || first == &"<<\"EOF\"")
+                && last.ends_with("EOF") =>
+        {
+            Some(inner)
+        }
+        _ => None,
+    }
+}
+
 fn check_start_and_end_lines_strict(
     first_line: Option<&&str>,
     last_line: Option<&&str>,
@@ -327,16 +415,112 @@ fn parse_one_hunk(lines: &[&str], line_number: usize) -> Result<(Hunk, usize), P
             },
             parsed_lines,
         ));
+    } else if let Some(path) = first_line.strip_prefix(ADD_BINARY_FILE_MARKER) {
+        let (contents, parsed_lines) = parse_base64_payload(lines, line_number)?;
+        return Ok((
+            AddBinaryFile {
+                path: PathBuf::from(path),
+                contents,
+            },
+            parsed_lines,
+        ));
+    } else if let Some(path) = first_line.strip_prefix(REPLACE_BINARY_FILE_MARKER) {
+        let (contents, parsed_lines) = parse_base64_payload(lines, line_number)?;
+        return Ok((
+            ReplaceBinaryFile {
+                path: PathBuf::from(path),
+                contents,
+            },
+            parsed_lines,
+        ));
+    } else if let Some(path) = first_line.strip_prefix(CHMOD_FILE_MARKER) {
+        let mode = required_header_value(lines, MODE_MARKER, line_number)?;
+        let mode = parse_mode(mode).ok_or_else(|| InvalidHunkError {
+            message: format!(
+                "Invalid mode '{mode}'; expected octal permission bits such as 644 or 755"
+            ),
+            line_number: line_number + 1,
+        })?;
+        return Ok((
+            ChmodFile {
+                path: PathBuf::from(path),
+                mode,
+            },
+            2,
+        ));
+    } else if let Some(path) = first_line.strip_prefix(ADD_SYMLINK_MARKER) {
+        let target = required_header_value(lines, TARGET_MARKER, line_number)?;
+        return Ok((
+            AddSymlink {
+                path: PathBuf::from(path),
+                target: PathBuf::from(target),
+            },
+            2,
+        ));
     }
 
     Err(InvalidHunkError {
         message: format!(
-            "'{first_line}' is not a valid hunk header. Valid hunk headers: '*** Add File: {{path}}', '*** Delete File: {{path}}', '*** Update File: {{path}}'"
+            "'{first_line}' is not a valid hunk header. Valid hunk headers: '*** Add File: {{path}}', '*** Delete File: {{path}}', '*** Update File: {{path}}', '*** Add Binary File: {{path}}', '*** Replace Binary File: {{path}}', '*** Chmod File: {{path}}', '*** Add Symlink: {{path}}'"
         ),
         line_number,
     })
 }
 
+/// Decodes the base64 lines following a binary hunk header, up to the next
+/// `***` marker.
+fn parse_base64_payload(
+    lines: &[&str],
+    line_number: usize,
+) -> Result<(Vec<u8>, usize), ParseError> {
+    let payload_lines = lines[1..]
+        .iter()
+        .take_while(|line| !line.trim_start().starts_with("***"))
+        .count();
+    let encoded: String = lines[1..=payload_lines]
+        .iter()
+        .map(|line| line.trim())
+        .collect();
+    let contents = base64::engine::general_purpose::STANDARD
+        .decode(encoded.as_bytes())
+        .map_err(|err| InvalidHunkError {
+            message: format!("Invalid base64 payload: {err}"),
+            line_number: line_number + 1,
+        })?;
+    Ok((contents, payload_lines + 1))
+}
+
+/// Returns the value of the `marker` line that must directly follow a hunk
+/// header.
+fn required_header_value<'a>(
+    lines: &[&'a str],
+    marker: &str,
+    line_number: usize,
+) -> Result<&'a str, ParseError> {
+    lines
+        .get(1)
+        .and_then(|line| line.trim().strip_prefix(marker))
+        .ok_or_else(|| InvalidHunkError {
+            message: format!("Expected '{marker}...' after '{}'", lines[0].trim()),
+            line_number: line_number + 1,
+        })
+}
+
+/// Parses octal permission bits. Git-style modes such as `100755` are
+/// accepted too; only their permission bits are kept.
+pub(crate) fn parse_mode(mode: &str) -> Option<u32> {
+    let mode = mode.trim();
+    if !(3..=6).contains(&mode.len()) {
+        return None;
+    }
+    let value = u32::from_str_radix(mode, 8).ok()?;
+    match value {
+        0..=0o7777 => Some(value),
+        _ if value & 0o170000 == 0o100000 => Some(value & 0o7777),
+        _ => None,
+    }
+}
+
 fn parse_update_file_chunk(
     lines: &[&str],
     line_number: usize,
@@ -377,6 +561,8 @@ fn parse_update_file_chunk(
         old_lines: Vec::new(),
         new_lines: Vec::new(),
         is_end_of_file: false,
+        old_missing_newline: false,
+        new_missing_newline: false,
     };
     let mut parsed_lines = 0;
     for line in &lines[start_index..] {
@@ -498,7 +684,9 @@ fn test_parse_patch() {
                     change_context: Some("def f():".to_string()),
                     old_lines: vec!["    pass".to_string()],
                     new_lines: vec!["    return 123".to_string()],
-                    is_end_of_file: false
+                    is_end_of_file: false,
+                    old_missing_newline: false,
+                    new_missing_newline: false
                 }]
             }
         ]
@@ -525,7 +713,9 @@ fn test_parse_patch() {
                     change_context: None,
                     old_lines: vec![],
                     new_lines: vec!["line".to_string()],
-                    is_end_of_file: false
+                    is_end_of_file: false,
+                    old_missing_newline: false,
+                    new_missing_newline: false
                 }],
             },
             AddFile {
@@ -556,6 +746,8 @@ fn test_parse_patch() {
                 old_lines: vec!["import foo".to_string()],
                 new_lines: vec!["import foo".to_string(), "bar".to_string()],
                 is_end_of_file: false,
+                old_missing_newline: false,
+                new_missing_newline: false,
             }],
         }]
     );
@@ -576,6 +768,8 @@ fn test_parse_patch_lenient() {
             old_lines: vec!["import foo".to_string()],
             new_lines: vec!["import foo".to_string(), "bar".to_string()],
             is_end_of_file: false,
+            old_missing_newline: false,
+            new_missing_newline: false,
         }],
     }];
     let expected_error =
@@ -653,7 +847,8 @@ fn test_parse_one_hunk() {
         parse_one_hunk(&["bad"], 234),
         Err(InvalidHunkError {
             message: "'bad' is not a valid hunk header. \
-            Valid hunk headers: '*** Add File: {path}', '*** Delete File: {path}', '*** Update File: {path}'".to_string(),
+            Valid hunk headers: '*** Add File: {path}', '*** Delete File: {path}', '*** Update File: {path}', \
+            '*** Add Binary File: {path}', '*** Replace Binary File: {path}', '*** Chmod File: {path}', '*** Add Symlink: {path}'".to_string(),
             line_number: 234
         })
     );
@@ -721,7 +916,9 @@ fn test_update_file_chunk() {
                     "add".to_string(),
                     "context2".to_string()
                 ],
-                is_end_of_file: false
+                is_end_of_file: false,
+                old_missing_newline: false,
+                new_missing_newline: false
             }),
             6
         ))
@@ -733,9 +930,77 @@ fn test_update_file_chunk() {
                 change_context: None,
                 old_lines: vec![],
                 new_lines: vec!["line".to_string()],
-                is_end_of_file: true
+                is_end_of_file: true,
+                old_missing_newline: false,
+                new_missing_newline: false
             }),
             3
         ))
     );
 }
+
+#[test]
+fn test_parse_binary_chmod_and_symlink_hunks() {
+    assert_eq!(
+        parse_patch_text(
+            "*** Begin Patch\n\
+             *** Add Binary File: img/dot.png\n\
+             iVBORw0K\n\
+             GgoAAAAN\n\
+             *** Replace Binary File: empty.bin\n\
+             *** Chmod File: run.sh\n\
+             *** Mode: 755\n\
+             *** Add Symlink: latest\n\
+             *** Target: releases/v2\n\
+             *** End Patch",
+            ParseMode::Strict
+        )
+        .unwrap()
+        .hunks,
+        vec![
+            AddBinaryFile {
+                path: PathBuf::from("img/dot.png"),
+                contents: vec![
+                    0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a, 0x00, 0x00, 0x00, 0x0d
+                ],
+            },
+            ReplaceBinaryFile {
+                path: PathBuf::from("empty.bin"),
+                contents: Vec::new(),
+            },
+            ChmodFile {
+                path: PathBuf::from("run.sh"),
+                mode: 0o755,
+            },
+            AddSymlink {
+                path: PathBuf::from("latest"),
+                target: PathBuf::from("releases/v2"),
+            },
+        ]
+    );
+    assert_eq!(
+        parse_one_hunk(&["*** Chmod File: run.sh", "*** Mode: 999"], 7),
+        Err(InvalidHunkError {
+            message: "Invalid mode '999'; expected octal permission bits such as 644 or 755"
+                .to_string(),
+            line_number: 8,
+        })
+    );
+    assert_eq!(
+        parse_one_hunk(&["*** Add Symlink: latest", "+oops"], 3),
+        Err(InvalidHunkError {
+            message: "Expected '*** Target: ...' after '*** Add Symlink: latest'".to_string(),
+            line_number: 4,
+        })
+    );
+    assert_eq!(
+        parse_one_hunk(&["*** Add Binary File: x.bin", "not base64!"], 5),
+        Err(InvalidHunkError {
+            message: "Invalid base64 payload: Invalid symbol 32, offset 3.".to_string(),
+            line_number: 6,
+        })
+    );
+    assert_eq!(parse_mode("100755"), Some(0o755));
+    assert_eq!(parse_mode("0644"), Some(0o644));
+    assert_eq!(parse_mode("120000"), None);
+}
@@ -0,0 +1,130 @@
+//! Machine-readable report printed by `apply_patch --json`.
+use std::path::Path;
+use std::path::PathBuf;
+
+use serde::Serialize;
+
+use crate::ApplyPatchFileUpdate;
+use crate::Hunk;
+use crate::MatchOptions;
+use crate::apply_hunks_to_files;
+use crate::check_hunks;
+use crate::parse_patch;
+use crate::reverse_hunks;
+use crate::unified_diff_from_chunks_with_context;
+
+#[derive(Debug, Serialize)]
+pub(crate) struct PatchReport {
+    /// Whether the patch was applied or, for a dry run, would apply cleanly.
+    pub(crate) success: bool,
+    pub(crate) dry_run: bool,
+    pub(crate) files: Vec<FileReport>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct FileReport {
+    action: FileAction,
+    /// The file's path resolved against the working directory.
+    path: PathBuf,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    move_path: Option<PathBuf>,
+    /// For updates, the diff against the file as it was before the patch.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    unified_diff: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum FileAction {
+    Add,
+    Delete,
+    Update,
+    AddBinary,
+    ReplaceBinary,
+    Chmod,
+    AddSymlink,
+}
+
+/// Parses `patch` and applies it (or only checks it, if `dry_run` is set),
+/// describing the outcome for each file.
+pub(crate) fn run(
+    patch: &str,
+    cwd: &Path,
+    dry_run: bool,
+    reverse: bool,
+    options: MatchOptions,
+) -> PatchReport {
+    let failed = |error: String| PatchReport {
+        success: false,
+        dry_run,
+        files: Vec::new(),
+        error: Some(error),
+    };
+    let hunks = match parse_patch(patch) {
+        Ok(args) => args.hunks,
+        Err(err) => return failed(err.to_string()),
+    };
+    let hunks = if reverse {
+        match reverse_hunks(&hunks) {
+            Ok(hunks) => hunks,
+            Err(err) => return failed(err.to_string()),
+        }
+    } else {
+        hunks
+    };
+
+    // Describe the files before applying so that diffs show the old contents.
+    let files = hunks
+        .iter()
+        .map(|hunk| describe_hunk(hunk, cwd, options))
+        .collect();
+    let result = if dry_run {
+        check_hunks(&hunks, options).map(|_| ())
+    } else {
+        apply_hunks_to_files(&hunks, options).map(|_| ())
+    };
+    PatchReport {
+        success: result.is_ok(),
+        dry_run,
+        files,
+        error: result.err().map(|err| err.to_string()),
+    }
+}
+
+fn describe_hunk(hunk: &Hunk, cwd: &Path, options: MatchOptions) -> FileReport {
+    let path = hunk.resolve_path(cwd);
+    let report = |action| FileReport {
+        action,
+        path: path.clone(),
+        move_path: None,
+        unified_diff: None,
+        error: None,
+    };
+    match hunk {
+        Hunk::AddFile { .. } => report(FileAction::Add),
+        Hunk::DeleteFile { .. } => report(FileAction::Delete),
+        Hunk::AddBinaryFile { .. } => report(FileAction::AddBinary),
+        Hunk::ReplaceBinaryFile { .. } => report(FileAction::ReplaceBinary),
+        Hunk::ChmodFile { .. } => report(FileAction::Chmod),
+        Hunk::AddSymlink { .. } => report(FileAction::AddSymlink),
+        Hunk::UpdateFile {
+            move_path, chunks, ..
+        } => {
+            let (unified_diff, error) =
+                match unified_diff_from_chunks_with_context(&path, chunks, 1, options) {
+                    Ok(ApplyPatchFileUpdate { unified_diff, .. }) => (Some(unified_diff), None),
+                    Err(err) => (None, Some(err.to_string())),
+                };
+            FileReport {
+                move_path: move_path.as_ref().map(|dest| cwd.join(dest)),
+                unified_diff,
+                error,
+                ..report(FileAction::Update)
+            }
+        }
+    }
+}
@@ -73,26 +73,6 @@ pub(crate) fn seek_sequence(
     // differences when locating context lines.
     // ------------------------------------------------------------------
 
-    fn normalise(s: &str) -> String {
-        s.trim()
-            .chars()
-            .map(|c| match c {
-                // Various dash / hyphen code-points → ASCII '-'
-                '\u{2010}' | '\u{2011}' | '\u{2012}' | '\u{2013}' | '\u{2014}' | '\u{2015}'
-                | '\u{2212}' => '-',
-                // Fancy single quotes → '\''
-                '\u{2018}' | '\u{2019}' | '\u{201A}' | '\u{201B}' => '\'',
-                // Fancy double quotes → '"'
-                '\u{201C}' | '\u{201D}' | '\u{201E}' | '\u{201F}' => '"',
-                // Non-breaking space and other odd spaces → normal space
-                '\u{00A0}' | '\u{2002}' | '\u{2003}' | '\u{2004}' | '\u{2005}' | '\u{2006}'
-                | '\u{2007}' | '\u{2008}' | '\u{2009}' | '\u{200A}' | '\u{202F}' | '\u{205F}'
-                | '\u{3000}' => ' ',
-                other => other,
-            })
-            .collect::<String>()
-    }
-
     for i in search_start..=lines.len().saturating_sub(pattern.len()) {
         let mut ok = true;
         for (p_idx, pat) in pattern.iter().enumerate() {
@@ -109,9 +89,171 @@ pub(crate) fn seek_sequence(
     None
 }
 
+/// Whether `a` and `b` are the same lines once trimmed and with typographic
+/// punctuation mapped to ASCII, the loosest comparison [`seek_sequence`]
+/// accepts.
+pub(crate) fn lines_match(a: &[String], b: &[String]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).all(|(a, b)| normalise(a) == normalise(b))
+}
+
+/// Trims `s` and maps typographic punctuation to its ASCII equivalent.
+fn normalise(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.trim().chars() {
+        match c {
+            // Various dash / hyphen code-points → ASCII '-'
+            '\u{2010}' | '\u{2011}' | '\u{2012}' | '\u{2013}' | '\u{2014}' | '\u{2015}'
+            | '\u{2212}' => out.push('-'),
+            // Fancy single quotes and primes → '\''
+            '\u{2018}' | '\u{2019}' | '\u{201A}' | '\u{201B}' | '\u{2032}' => out.push('\''),
+            // Fancy double quotes and double primes → '"'
+            '\u{201C}' | '\u{201D}' | '\u{201E}' | '\u{201F}' | '\u{2033}' | '\u{00AB}'
+            | '\u{00BB}' => out.push('"'),
+            // Ellipsis → '...'
+            '\u{2026}' => out.push_str("..."),
+            // Non-breaking space and other odd spaces → normal space
+            '\u{00A0}' | '\u{2002}' | '\u{2003}' | '\u{2004}' | '\u{2005}' | '\u{2006}'
+            | '\u{2007}' | '\u{2008}' | '\u{2009}' | '\u{200A}' | '\u{202F}' | '\u{205F}'
+            | '\u{3000}' => out.push(' '),
+            // Zero-width characters and byte order marks are dropped.
+            '\u{200B}' | '\u{200C}' | '\u{200D}' | '\u{2060}' | '\u{FEFF}' => {}
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+/// Upper bound on `lines × pattern` line comparisons for the similarity based
+/// searches below, so that huge files cannot stall a patch.
+const MAX_FUZZY_LINE_PAIRS: usize = 2_000_000;
+
+/// The region of a file that is most similar to a pattern.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct SimilarRegion {
+    /// Index of the first line of the region.
+    pub start: usize,
+    /// Similarity between 0.0 and 1.0, based on the Levenshtein distance
+    /// between the normalised lines of the region and the pattern.
+    pub similarity: f64,
+}
+
+/// Similarity-based fallback for [`seek_sequence`]: finds the window of
+/// `pattern.len()` lines at or after `start` whose similarity to `pattern` is
+/// highest and at least `threshold`. When `eof` is true the window at the end
+/// of the file is preferred if it qualifies.
+pub(crate) fn seek_sequence_fuzzy(
+    lines: &[String],
+    pattern: &[String],
+    start: usize,
+    eof: bool,
+    threshold: f64,
+) -> Option<SimilarRegion> {
+    if threshold >= 1.0 {
+        return None;
+    }
+    if eof
+        && let Some(end_start) = lines.len().checked_sub(pattern.len())
+        && end_start >= start
+        && let Some(region) = most_similar_region(lines, pattern, end_start, threshold)
+        && region.start == end_start
+    {
+        return Some(region);
+    }
+    most_similar_region(lines, pattern, start, threshold)
+}
+
+/// Finds the region most similar to `pattern` at or after `start`, if any
+/// region is at least `min_similarity` similar. Used both for fuzzy matching
+/// and to point the model at the closest candidate when a hunk does not apply.
+pub(crate) fn most_similar_region(
+    lines: &[String],
+    pattern: &[String],
+    start: usize,
+    min_similarity: f64,
+) -> Option<SimilarRegion> {
+    if pattern.is_empty()
+        || pattern.len() > lines.len().saturating_sub(start)
+        || lines.len().saturating_mul(pattern.len()) > MAX_FUZZY_LINE_PAIRS
+    {
+        return None;
+    }
+    let to_chars = |s: &String| normalise(s).chars().collect::<Vec<char>>();
+    let pattern: Vec<Vec<char>> = pattern.iter().map(to_chars).collect();
+    let candidates: Vec<Vec<char>> = lines[start..].iter().map(to_chars).collect();
+
+    let mut best: Option<(usize, usize, usize)> = None; // (start, distance, total)
+    for offset in 0..=candidates.len() - pattern.len() {
+        let window = &candidates[offset..offset + pattern.len()];
+        let total: usize = window
+            .iter()
+            .zip(&pattern)
+            .map(|(line, pat)| line.len().max(pat.len()))
+            .sum();
+        // Allowed number of edits for this window to reach `min_similarity`,
+        // tightened by the best window seen so far.
+        let mut budget = ((1.0 - min_similarity) * total as f64).floor() as usize;
+        if let Some((_, best_distance, _)) = best {
+            budget = budget.min(best_distance.saturating_sub(1));
+        }
+        let mut distance = 0;
+        let within_budget =
+            window.iter().zip(&pattern).all(|(line, pat)| {
+                match bounded_levenshtein(line, pat, budget - distance) {
+                    Some(d) => {
+                        distance += d;
+                        true
+                    }
+                    None => false,
+                }
+            });
+        if within_budget {
+            best = Some((start + offset, distance, total));
+            if distance == 0 {
+                break;
+            }
+        }
+    }
+    best.map(|(start, distance, total)| SimilarRegion {
+        start,
+        similarity: if total == 0 {
+            1.0
+        } else {
+            1.0 - distance as f64 / total as f64
+        },
+    })
+}
+
+/// Levenshtein distance between `a` and `b`, or `None` if it exceeds `max`.
+fn bounded_levenshtein(a: &[char], b: &[char], max: usize) -> Option<usize> {
+    if a.len().abs_diff(b.len()) > max {
+        return None;
+    }
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut cur = vec![0; b.len() + 1];
+    for (i, ca) in a.iter().enumerate() {
+        cur[0] = i + 1;
+        let mut row_min = cur[0];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = prev[j] + usize::from(ca != cb);
+            cur[j + 1] = substitution.min(prev[j + 1] + 1).min(cur[j] + 1);
+            row_min = row_min.min(cur[j + 1]);
+        }
+        if row_min > max {
+            return None;
+        }
+        std::mem::swap(&mut prev, &mut cur);
+    }
+    let distance = prev[b.len()];
+    (distance <= max).then_some(distance)
+}
+
 #[cfg(test)]
 mod tests {
+    use super::SimilarRegion;
+    use super::bounded_levenshtein;
+    use super::most_similar_region;
     use super::seek_sequence;
+    use super::seek_sequence_fuzzy;
 
     fn to_vec(strings: &[&str]) -> Vec<String> {
         strings.iter().map(|s| s.to_string()).collect()
@@ -147,4 +289,82 @@ mod tests {
         // Should not panic – must return None when pattern cannot possibly fit.
         assert_eq!(seek_sequence(&lines, &pattern, 0, false), None);
     }
+
+    #[test]
+    fn test_normalised_match_handles_ellipsis_and_zero_width_chars() {
+        let lines = to_vec(&["println!(\u{201C}Loading\u{2026}\u{201D});\u{200B}"]);
+        let pattern = to_vec(&["println!(\"Loading...\");"]);
+        assert_eq!(seek_sequence(&lines, &pattern, 0, false), Some(0));
+    }
+
+    #[test]
+    fn test_bounded_levenshtein() {
+        let chars = |s: &str| s.chars().collect::<Vec<char>>();
+        assert_eq!(
+            bounded_levenshtein(&chars("kitten"), &chars("sitting"), 3),
+            Some(3)
+        );
+        assert_eq!(
+            bounded_levenshtein(&chars("kitten"), &chars("sitting"), 2),
+            None
+        );
+        assert_eq!(bounded_levenshtein(&chars(""), &chars("abc"), 3), Some(3));
+        assert_eq!(
+            bounded_levenshtein(&chars("same"), &chars("same"), 0),
+            Some(0)
+        );
+    }
+
+    #[test]
+    fn test_fuzzy_match_tolerates_small_typos() {
+        let lines = to_vec(&[
+            "fn main() {",
+            "    let total = compute_total(&items);",
+            "    println!(\"{total}\");",
+            "}",
+        ]);
+        let pattern = to_vec(&[
+            "    let total = compute_totals(&items);",
+            "    println!(\"{total}\");",
+        ]);
+        assert_eq!(seek_sequence(&lines, &pattern, 0, false), None);
+        let region = seek_sequence_fuzzy(&lines, &pattern, 0, false, 0.9).unwrap();
+        assert_eq!(region.start, 1);
+        assert!(region.similarity > 0.95, "{region:?}");
+        // A threshold of 1.0 disables fuzzy matching.
+        assert_eq!(seek_sequence_fuzzy(&lines, &pattern, 0, false, 1.0), None);
+        // So does a threshold that the closest region does not meet.
+        assert_eq!(
+            seek_sequence_fuzzy(&lines, &to_vec(&["let x = 1;"]), 0, false, 0.9),
+            None
+        );
+    }
+
+    #[test]
+    fn test_fuzzy_match_prefers_end_of_file_when_requested() {
+        let lines = to_vec(&["value = 1", "other", "value = 1"]);
+        let pattern = to_vec(&["value = 2"]);
+        assert_eq!(
+            seek_sequence_fuzzy(&lines, &pattern, 0, false, 0.8).map(|r| r.start),
+            Some(0)
+        );
+        assert_eq!(
+            seek_sequence_fuzzy(&lines, &pattern, 0, true, 0.8).map(|r| r.start),
+            Some(2)
+        );
+    }
+
+    #[test]
+    fn test_most_similar_region_picks_closest_window() {
+        let lines = to_vec(&["alpha", "beta", "gamma", "delta"]);
+        let pattern = to_vec(&["gamma", "delto"]);
+        assert_eq!(
+            most_similar_region(&lines, &pattern, 0, 0.0),
+            Some(SimilarRegion {
+                start: 2,
+                similarity: 0.9,
+            })
+        );
+        assert_eq!(most_similar_region(&lines, &pattern, 3, 0.0), None);
+    }
 }
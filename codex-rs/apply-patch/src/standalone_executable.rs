@@ -1,6 +1,19 @@
 use std::io::Read;
 use std::io::Write;
 
+const USAGE: &str = "Usage: apply_patch [--check] [--json] [--reverse] 'PATCH'\n       echo 'PATCH' | apply-patch [--check] [--json] [--reverse]";
+
+/// Flags accepted before (or after) the PATCH argument.
+#[derive(Debug, Default)]
+struct Flags {
+    /// Only check that the patch applies; do not write anything.
+    check: bool,
+    /// Print a JSON report instead of the plain-text summary.
+    json: bool,
+    /// Undo the patch instead of applying it.
+    reverse: bool,
+}
+
 pub fn main() -> ! {
     let exit_code = run_main();
     std::process::exit(exit_code);
@@ -13,21 +26,35 @@ pub fn run_main() -> i32 {
     let mut args = std::env::args_os();
     let _argv0 = args.next();
 
-    let patch_arg = match args.next() {
-        Some(arg) => match arg.into_string() {
-            Ok(s) => s,
-            Err(_) => {
-                eprintln!("Error: apply_patch requires a UTF-8 PATCH argument.");
-                return 1;
+    let mut flags = Flags::default();
+    let mut patch_arg: Option<String> = None;
+    for arg in args {
+        let Ok(arg) = arg.into_string() else {
+            eprintln!("Error: apply_patch requires a UTF-8 PATCH argument.");
+            return 1;
+        };
+        match arg.as_str() {
+            "--check" => flags.check = true,
+            "--json" => flags.json = true,
+            "--reverse" => flags.reverse = true,
+            _ if patch_arg.is_none() => patch_arg = Some(arg),
+            // Refuse extra args to avoid ambiguity.
+            _ => {
+                eprintln!("Error: apply_patch accepts exactly one PATCH argument.");
+                return 2;
             }
-        },
+        }
+    }
+
+    let patch_arg = match patch_arg {
+        Some(patch) => patch,
         None => {
             // No argument provided; attempt to read the patch from stdin.
             let mut buf = String::new();
             match std::io::stdin().read_to_string(&mut buf) {
                 Ok(_) => {
                     if buf.is_empty() {
-                        eprintln!("Usage: apply_patch 'PATCH'\n       echo 'PATCH' | apply-patch");
+                        eprintln!("{USAGE}");
                         return 2;
                     }
                     buf
@@ -40,20 +67,78 @@ pub fn run_main() -> i32 {
         }
     };
 
-    // Refuse extra args to avoid ambiguity.
-    if args.next().is_some() {
-        eprintln!("Error: apply_patch accepts exactly one argument.");
-        return 2;
-    }
-
     let mut stdout = std::io::stdout();
     let mut stderr = std::io::stderr();
-    match crate::apply_patch(&patch_arg, &mut stdout, &mut stderr) {
-        Ok(()) => {
-            // Flush to ensure output ordering when used in pipelines.
-            let _ = stdout.flush();
-            0
+    let options = crate::MatchOptions::from_env();
+    let result = if flags.json {
+        run_json(&patch_arg, &flags, options, &mut stdout)
+    } else if flags.check || flags.reverse {
+        run_text(&patch_arg, &flags, options, &mut stdout, &mut stderr)
+    } else {
+        crate::apply_patch_with_options(&patch_arg, options, &mut stdout, &mut stderr)
+            .map_err(|_| ())
+    };
+    // Flush to ensure output ordering when used in pipelines.
+    let _ = stdout.flush();
+    match result {
+        Ok(()) => 0,
+        Err(()) => 1,
+    }
+}
+
+fn run_text(
+    patch: &str,
+    flags: &Flags,
+    options: crate::MatchOptions,
+    stdout: &mut impl Write,
+    stderr: &mut impl Write,
+) -> Result<(), ()> {
+    let hunks = crate::parse_hunks(patch, stderr).map_err(|_| ())?;
+    let hunks = if flags.reverse {
+        crate::reverse_hunks(&hunks).map_err(|err| {
+            let _ = writeln!(stderr, "{err}");
+        })?
+    } else {
+        hunks
+    };
+    if !flags.check {
+        return crate::apply_hunks(&hunks, options, stdout, stderr).map_err(|_| ());
+    }
+    match crate::check_hunks(&hunks, options) {
+        Ok(affected) => crate::write_summary(
+            "Patch applies cleanly. Applying it would update the following files:",
+            &affected,
+            stdout,
+        )
+        .map_err(|_| ()),
+        Err(err) => {
+            let _ = writeln!(stderr, "{err}");
+            Err(())
         }
-        Err(_) => 1,
     }
 }
+
+fn run_json(
+    patch: &str,
+    flags: &Flags,
+    options: crate::MatchOptions,
+    stdout: &mut impl Write,
+) -> Result<(), ()> {
+    let cwd = match std::env::current_dir() {
+        Ok(cwd) => cwd,
+        Err(err) => {
+            eprintln!("Error: Failed to determine the current directory.\n{err}");
+            return Err(());
+        }
+    };
+    let report = crate::report::run(patch, &cwd, flags.check, flags.reverse, options);
+    let json = match serde_json::to_string_pretty(&report) {
+        Ok(json) => json,
+        Err(err) => {
+            eprintln!("Error: Failed to serialize the report.\n{err}");
+            return Err(());
+        }
+    };
+    writeln!(stdout, "{json}").map_err(|_| ())?;
+    if report.success { Ok(()) } else { Err(()) }
+}
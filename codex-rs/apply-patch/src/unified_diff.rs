@@ -0,0 +1,879 @@
+//! Front-end that turns a regular unified diff (as produced by `diff -u`,
+//! `git diff` or `git format-patch`) into the same [`Hunk`]s that the
+//! `*** Begin Patch` parser produces. A patch is only treated as a unified
+//! diff if it starts with a file header, so the mail headers and commit
+//! message of `git format-patch` output have to be left out.
+//!
+//! Supported:
+//! - plain `--- old` / `+++ new` file headers, with optional timestamps;
+//! - `diff --git` headers with `new file mode`, `deleted file mode`,
+//!   `old mode` / `new mode`, `rename from` / `rename to` and the other
+//!   informational extended headers; new symlinks (mode `120000`) become
+//!   [`Hunk::AddSymlink`] and mode changes become [`Hunk::ChmodFile`];
+//! - `/dev/null` on either side for added and deleted files;
+//! - `\ No newline at end of file` markers.
+//!
+//! Like the rest of the parser this is lenient: text between or after file
+//! diffs (e.g. a trailing signature) is ignored, and hunk bodies may run past
+//! the line counts in their `@@` header because model-written diffs frequently
+//! get those counts wrong.
+use std::path::PathBuf;
+
+use crate::parser::Hunk;
+use crate::parser::ParseError;
+use crate::parser::ParseError::*;
+use crate::parser::UpdateFileChunk;
+use crate::parser::parse_mode;
+
+const GIT_DIFF_MARKER: &str = "diff --git ";
+const OLD_FILE_MARKER: &str = "--- ";
+const NEW_FILE_MARKER: &str = "+++ ";
+const HUNK_MARKER: &str = "@@";
+const DEV_NULL: &str = "/dev/null";
+const NO_NEWLINE_MARKER: char = '\\';
+const SYMLINK_MODE: &str = "120000";
+const DEFAULT_FILE_MODE: u32 = 0o644;
+
+/// Returns true if `lines` start with a unified diff file header: a
+/// `diff --git` line or a `---` line followed by a `+++` line. Headers further
+/// down are not considered, as other text can contain such lines too.
+pub(crate) fn is_unified_diff(lines: &[&str]) -> bool {
+    !lines.is_empty() && is_file_header(lines, 0, false)
+}
+
+/// Parses a unified diff into hunks. `line_number` is the 1-based line number
+/// of `lines[0]` in the original patch text and is used for error messages.
+pub(crate) fn parse_unified_diff(
+    lines: &[&str],
+    line_number: usize,
+) -> Result<Vec<Hunk>, ParseError> {
+    let mut hunks = Vec::new();
+    let mut index = 0;
+    while index < lines.len() {
+        if is_file_header(lines, index, false) {
+            let (file_hunks, parsed_lines) = parse_file_diff(&lines[index..], line_number + index)?;
+            hunks.extend(file_hunks);
+            index += parsed_lines;
+        } else if lines[index].starts_with(HUNK_MARKER) {
+            return Err(InvalidHunkError {
+                message: format!(
+                    "'{}' is not preceded by a '--- {{path}}' / '+++ {{path}}' file header",
+                    lines[index]
+                ),
+                line_number: line_number + index,
+            });
+        } else {
+            // Mail headers, commit messages, diffstats, `Index:` lines etc.
+            index += 1;
+        }
+    }
+    Ok(hunks)
+}
+
+/// `lines[index]` starts a new file section. Inside a hunk whose line counts
+/// are not yet exhausted, a `--- `/`+++ ` pair is only treated as a header if
+/// it is immediately followed by a hunk header, because `--- x` is also a
+/// valid removal of the line `-- x`.
+fn is_file_header(lines: &[&str], index: usize, inside_hunk: bool) -> bool {
+    let line = lines[index];
+    if line.starts_with(GIT_DIFF_MARKER) {
+        return true;
+    }
+    line.starts_with(OLD_FILE_MARKER)
+        && lines
+            .get(index + 1)
+            .is_some_and(|next| next.starts_with(NEW_FILE_MARKER))
+        && (!inside_hunk
+            || lines
+                .get(index + 2)
+                .is_some_and(|next| next.starts_with(HUNK_MARKER)))
+}
+
+/// Git file mode from an extended header: either a symlink or a regular file
+/// with the given permission bits.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum GitMode {
+    Symlink,
+    File(u32),
+}
+
+fn parse_git_mode(mode: &str, line_number: usize) -> Result<GitMode, ParseError> {
+    let mode = mode.trim();
+    if mode == SYMLINK_MODE {
+        return Ok(GitMode::Symlink);
+    }
+    parse_mode(mode)
+        .filter(|_| mode.len() == 6)
+        .map(GitMode::File)
+        .ok_or_else(|| InvalidHunkError {
+            message: format!("Unsupported file mode '{mode}'"),
+            line_number,
+        })
+}
+
+/// Parses one file section starting at its header. Returns the hunks for the
+/// section (none for e.g. an `index`-only section), together with the number
+/// of lines consumed.
+fn parse_file_diff(lines: &[&str], line_number: usize) -> Result<(Vec<Hunk>, usize), ParseError> {
+    let mut index = 0;
+    let mut is_git = false;
+    let mut git_paths: Option<(String, String)> = None;
+    if let Some(rest) = lines[0].strip_prefix(GIT_DIFF_MARKER) {
+        is_git = true;
+        git_paths = parse_git_header_paths(rest);
+        index = 1;
+    }
+
+    let mut is_new_file = false;
+    let mut is_deleted_file = false;
+    let mut new_file_mode: Option<GitMode> = None;
+    let mut old_mode: Option<GitMode> = None;
+    let mut new_mode: Option<GitMode> = None;
+    let mut rename_from: Option<String> = None;
+    let mut rename_to: Option<String> = None;
+    if is_git {
+        while let Some(line) = lines.get(index) {
+            if let Some(path) = line.strip_prefix("rename from ") {
+                rename_from = Some(unquote_path(path));
+            } else if let Some(path) = line.strip_prefix("rename to ") {
+                rename_to = Some(unquote_path(path));
+            } else if let Some(mode) = line.strip_prefix("new file mode ") {
+                is_new_file = true;
+                new_file_mode = Some(parse_git_mode(mode, line_number + index)?);
+            } else if line.starts_with("deleted file mode ") {
+                is_deleted_file = true;
+            } else if let Some(mode) = line.strip_prefix("old mode ") {
+                old_mode = Some(parse_git_mode(mode, line_number + index)?);
+            } else if let Some(mode) = line.strip_prefix("new mode ") {
+                new_mode = Some(parse_git_mode(mode, line_number + index)?);
+            } else if line.starts_with("copy from ") || line.starts_with("copy to ") {
+                return Err(InvalidHunkError {
+                    message: "copies are not supported; add the new file explicitly".to_string(),
+                    line_number: line_number + index,
+                });
+            } else if line.starts_with("Binary files ") || *line == "GIT binary patch" {
+                return Err(InvalidHunkError {
+                    message: "binary diffs are not supported".to_string(),
+                    line_number: line_number + index,
+                });
+            } else if !["similarity index ", "dissimilarity index ", "index "]
+                .iter()
+                .any(|prefix| line.starts_with(prefix))
+            {
+                break;
+            }
+            index += 1;
+        }
+    }
+
+    // `---`/`+++` are optional for git diffs that only rename a file, change
+    // its mode, or add/delete an empty file.
+    let mut old_path: Option<String> = None;
+    let mut new_path: Option<String> = None;
+    match (lines.get(index), lines.get(index + 1)) {
+        (Some(old), Some(new))
+            if old.starts_with(OLD_FILE_MARKER) && new.starts_with(NEW_FILE_MARKER) =>
+        {
+            let old = parse_header_path(&old[OLD_FILE_MARKER.len()..]);
+            let new = parse_header_path(&new[NEW_FILE_MARKER.len()..]);
+            is_new_file |= old.is_none();
+            is_deleted_file |= new.is_none();
+            (old_path, new_path) = strip_ab_prefixes(old, new, is_git);
+            index += 2;
+        }
+        (Some(old), _) if old.starts_with(OLD_FILE_MARKER) => {
+            return Err(InvalidHunkError {
+                message: format!("Expected '+++ {{path}}' after '{old}'"),
+                line_number: line_number + index + 1,
+            });
+        }
+        _ if !is_git => {
+            return Err(InvalidHunkError {
+                message: "Expected '--- {path}' and '+++ {path}' file headers".to_string(),
+                line_number: line_number + index,
+            });
+        }
+        _ => {}
+    }
+
+    let mut parsed_hunks = Vec::new();
+    while lines
+        .get(index)
+        .is_some_and(|line| line.starts_with(HUNK_MARKER))
+    {
+        let (mut parsed, parsed_lines) = parse_hunk(&lines[index..], line_number + index)?;
+        parsed.offset = index;
+        parsed_hunks.push(parsed);
+        index += parsed_lines;
+    }
+
+    let (git_old, git_new) = git_paths.unzip();
+    let old_path = rename_from.or(old_path).or(git_old);
+    let new_path = rename_to.or(new_path).or(git_new);
+    let missing_path = || InvalidHunkError {
+        message: "Could not determine the file path from the diff header".to_string(),
+        line_number,
+    };
+
+    if is_new_file && is_deleted_file {
+        return Err(InvalidHunkError {
+            message: "A file cannot be both added and deleted in the same diff".to_string(),
+            line_number,
+        });
+    }
+
+    if is_deleted_file {
+        let path = old_path.ok_or_else(missing_path)?;
+        return Ok((
+            vec![Hunk::DeleteFile {
+                path: PathBuf::from(path),
+            }],
+            index,
+        ));
+    }
+
+    let chmod = match (old_mode, new_mode) {
+        (Some(GitMode::File(_)), Some(GitMode::Symlink))
+        | (Some(GitMode::Symlink), Some(GitMode::File(_))) => {
+            return Err(InvalidHunkError {
+                message: "Changing a file into a symlink or back is not supported; delete and re-add it instead"
+                    .to_string(),
+                line_number,
+            });
+        }
+        (_, Some(GitMode::File(mode))) if old_mode != new_mode => Some(mode),
+        _ => None,
+    };
+
+    if is_new_file {
+        let path = new_path.ok_or_else(missing_path)?;
+        let mut contents = String::new();
+        for parsed in &parsed_hunks {
+            if !parsed.old_lines.is_empty() {
+                return Err(InvalidHunkError {
+                    message: format!("New file '{path}' must not contain removed or context lines"),
+                    line_number: line_number + parsed.offset,
+                });
+            }
+            for line in &parsed.new_lines {
+                contents.push_str(line);
+                contents.push('\n');
+            }
+        }
+        if parsed_hunks
+            .last()
+            .is_some_and(|parsed| parsed.new_missing_newline)
+        {
+            contents.pop();
+        }
+        let path = PathBuf::from(path);
+        let hunks = match new_file_mode {
+            // Git stores the target of a symlink as its contents.
+            Some(GitMode::Symlink) => vec![Hunk::AddSymlink {
+                path,
+                target: PathBuf::from(contents),
+            }],
+            Some(GitMode::File(mode)) if mode != DEFAULT_FILE_MODE => vec![
+                Hunk::AddFile {
+                    path: path.clone(),
+                    contents,
+                },
+                Hunk::ChmodFile { path, mode },
+            ],
+            _ => vec![Hunk::AddFile { path, contents }],
+        };
+        return Ok((hunks, index));
+    }
+
+    let path = old_path.ok_or_else(missing_path)?;
+    let new_path = new_path.ok_or_else(missing_path)?;
+    let move_path = (new_path != path).then(|| PathBuf::from(new_path));
+    let mut chunks = Vec::with_capacity(parsed_hunks.len());
+    for parsed in parsed_hunks {
+        if parsed.old_lines.is_empty() && parsed.old_start != 0 {
+            return Err(InvalidHunkError {
+                message: "Hunks that only add lines must include surrounding context lines"
+                    .to_string(),
+                line_number: line_number + parsed.offset,
+            });
+        }
+        chunks.push(UpdateFileChunk {
+            change_context: None,
+            old_lines: parsed.old_lines,
+            new_lines: parsed.new_lines,
+            // A `\ No newline at end of file` marker means the hunk reaches
+            // the end of the file.
+            is_end_of_file: parsed.old_missing_newline || parsed.new_missing_newline,
+            old_missing_newline: parsed.old_missing_newline,
+            new_missing_newline: parsed.new_missing_newline,
+        });
+    }
+    let mut hunks = Vec::new();
+    // The mode change applies to the file at its final location.
+    let final_path = move_path.clone().unwrap_or_else(|| PathBuf::from(&path));
+    if !chunks.is_empty() || move_path.is_some() {
+        hunks.push(Hunk::UpdateFile {
+            path: PathBuf::from(&path),
+            move_path,
+            chunks,
+        });
+    }
+    if let Some(mode) = chmod {
+        hunks.push(Hunk::ChmodFile {
+            path: final_path,
+            mode,
+        });
+    }
+    Ok((hunks, index))
+}
+
+struct ParsedHunk {
+    /// Offset of the `@@` line relative to the start of the file section.
+    offset: usize,
+    old_start: usize,
+    old_lines: Vec<String>,
+    new_lines: Vec<String>,
+    old_missing_newline: bool,
+    new_missing_newline: bool,
+}
+
+/// Which side(s) of the diff the previous body line belonged to, so that a
+/// `\ No newline at end of file` marker can be attributed correctly.
+#[derive(Clone, Copy)]
+enum LastLine {
+    None,
+    Context,
+    Removed,
+    Added,
+}
+
+fn parse_hunk(lines: &[&str], line_number: usize) -> Result<(ParsedHunk, usize), ParseError> {
+    let (old_start, old_count, new_count) =
+        parse_hunk_header(lines[0]).ok_or_else(|| InvalidHunkError {
+            message: format!(
+                "Invalid hunk header '{}'; expected '@@ -start,count +start,count @@'",
+                lines[0]
+            ),
+            line_number,
+        })?;
+    let mut parsed = ParsedHunk {
+        offset: 0,
+        old_start,
+        old_lines: Vec::new(),
+        new_lines: Vec::new(),
+        old_missing_newline: false,
+        new_missing_newline: false,
+    };
+    let mut old_remaining = old_count;
+    let mut new_remaining = new_count;
+    let mut last_line = LastLine::None;
+    let mut index = 1;
+    while let Some(line) = lines.get(index) {
+        let counts_exhausted = old_remaining == 0 && new_remaining == 0;
+        if line.starts_with(HUNK_MARKER) || is_file_header(lines, index, !counts_exhausted) {
+            break;
+        }
+        match line.chars().next() {
+            // Editors often strip the single space of empty context lines.
+            None if !counts_exhausted => {
+                parsed.old_lines.push(String::new());
+                parsed.new_lines.push(String::new());
+                last_line = LastLine::Context;
+            }
+            // `git format-patch` ends with a `-- ` signature separator.
+            Some('-') if counts_exhausted && line.trim_end() == "--" => break,
+            Some(' ') => {
+                parsed.old_lines.push(line[1..].to_string());
+                parsed.new_lines.push(line[1..].to_string());
+                last_line = LastLine::Context;
+            }
+            Some('-') => {
+                parsed.old_lines.push(line[1..].to_string());
+                last_line = LastLine::Removed;
+            }
+            Some('+') => {
+                parsed.new_lines.push(line[1..].to_string());
+                last_line = LastLine::Added;
+            }
+            Some(NO_NEWLINE_MARKER) => match last_line {
+                LastLine::None => {
+                    return Err(InvalidHunkError {
+                        message: format!("'{line}' must follow a diff line"),
+                        line_number: line_number + index,
+                    });
+                }
+                LastLine::Context => {
+                    parsed.old_missing_newline = true;
+                    parsed.new_missing_newline = true;
+                }
+                LastLine::Removed => parsed.old_missing_newline = true,
+                LastLine::Added => parsed.new_missing_newline = true,
+            },
+            _ if counts_exhausted => break,
+            _ => {
+                return Err(InvalidHunkError {
+                    message: format!(
+                        "Unexpected line found in hunk: '{line}'. Every line should start with ' ' (context line), '+' (added line), '-' (removed line) or '\\'"
+                    ),
+                    line_number: line_number + index,
+                });
+            }
+        }
+        match last_line {
+            _ if line.starts_with(NO_NEWLINE_MARKER) => {}
+            LastLine::Context => {
+                old_remaining = old_remaining.saturating_sub(1);
+                new_remaining = new_remaining.saturating_sub(1);
+            }
+            LastLine::Removed => old_remaining = old_remaining.saturating_sub(1),
+            LastLine::Added => new_remaining = new_remaining.saturating_sub(1),
+            LastLine::None => {}
+        }
+        index += 1;
+    }
+    Ok((parsed, index))
+}
+
+/// Parses `@@ -old_start[,old_count] +new_start[,new_count] @@ [heading]`,
+/// returning `(old_start, old_count, new_count)`.
+fn parse_hunk_header(line: &str) -> Option<(usize, usize, usize)> {
+    let rest = line.strip_prefix("@@ -")?;
+    let (ranges, _heading) = rest.split_once(" @@")?;
+    let (old_range, new_range) = ranges.split_once(" +")?;
+    let parse_range = |range: &str| -> Option<(usize, usize)> {
+        match range.split_once(',') {
+            Some((start, count)) => Some((start.parse().ok()?, count.parse().ok()?)),
+            None => Some((range.parse().ok()?, 1)),
+        }
+    };
+    let (old_start, old_count) = parse_range(old_range)?;
+    let (_new_start, new_count) = parse_range(new_range)?;
+    Some((old_start, old_count, new_count))
+}
+
+/// Parses the path from a `---`/`+++` header, dropping any timestamp that
+/// follows a tab. Returns `None` for `/dev/null`.
+fn parse_header_path(raw: &str) -> Option<String> {
+    let raw = raw.split('\t').next().unwrap_or(raw).trim_end();
+    let path = unquote_path(raw);
+    (path != DEV_NULL).then_some(path)
+}
+
+/// Git prefixes paths in `---`/`+++` headers with `a/` and `b/`. Plain diffs
+/// may use the same convention, in which case the prefixes are only stripped
+/// if both sides have them.
+fn strip_ab_prefixes(
+    old: Option<String>,
+    new: Option<String>,
+    is_git: bool,
+) -> (Option<String>, Option<String>) {
+    let old_prefixed = old.as_deref().is_none_or(|path| path.starts_with("a/"));
+    let new_prefixed = new.as_deref().is_none_or(|path| path.starts_with("b/"));
+    if !(is_git || (old_prefixed && new_prefixed)) {
+        return (old, new);
+    }
+    let strip = |path: Option<String>, prefix: &str| {
+        path.map(|path| match path.strip_prefix(prefix) {
+            Some(stripped) => stripped.to_string(),
+            None => path,
+        })
+    };
+    (strip(old, "a/"), strip(new, "b/"))
+}
+
+/// Extracts the two paths from the remainder of a `diff --git a/x b/y` line.
+/// Unquoted paths containing spaces are ambiguous, so prefer a split where
+/// both sides name the same file, which is always the case unless the diff
+/// also has `rename from`/`rename to` headers.
+fn parse_git_header_paths(rest: &str) -> Option<(String, String)> {
+    if rest.starts_with('"') {
+        let (old, remainder) = split_quoted(rest)?;
+        let new = remainder.trim_start();
+        let new = if new.starts_with('"') {
+            split_quoted(new)?.0
+        } else {
+            new.to_string()
+        };
+        return Some((strip_one(&old, "a/"), strip_one(&new, "b/")));
+    }
+    let splits: Vec<usize> = rest.match_indices(" b/").map(|(i, _)| i).collect();
+    let split = splits
+        .iter()
+        .copied()
+        .find(|&i| rest[..i].strip_prefix("a/") == Some(&rest[i + 3..]))
+        .or_else(|| splits.first().copied())?;
+    Some((
+        strip_one(&rest[..split], "a/"),
+        rest[split + 3..].to_string(),
+    ))
+}
+
+fn strip_one(path: &str, prefix: &str) -> String {
+    path.strip_prefix(prefix).unwrap_or(path).to_string()
+}
+
+/// Undoes git's C-style quoting of paths with special characters, e.g.
+/// `"a/caf\303\251 menu.txt"`. Unquoted input is returned unchanged.
+fn unquote_path(raw: &str) -> String {
+    if raw.starts_with('"')
+        && let Some((path, rest)) = split_quoted(raw)
+        && rest.trim().is_empty()
+    {
+        return path;
+    }
+    raw.to_string()
+}
+
+/// Splits a leading C-style quoted string off `input`, returning the unescaped
+/// string and the remaining input after the closing quote.
+fn split_quoted(input: &str) -> Option<(String, &str)> {
+    let bytes = input.as_bytes();
+    let mut out = Vec::new();
+    let mut i = 1;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'"' => return Some((String::from_utf8_lossy(&out).into_owned(), &input[i + 1..])),
+            b'\\' => {
+                let escaped = *bytes.get(i + 1)?;
+                i += 2;
+                match escaped {
+                    b'n' => out.push(b'\n'),
+                    b't' => out.push(b'\t'),
+                    b'r' => out.push(b'\r'),
+                    b'a' => out.push(0x07),
+                    b'b' => out.push(0x08),
+                    b'f' => out.push(0x0c),
+                    b'v' => out.push(0x0b),
+                    b'0'..=b'7' => {
+                        let digits = bytes.get(i - 1..i + 2)?;
+                        let value = digits.iter().try_fold(0u32, |acc, d| match d {
+                            b'0'..=b'7' => Some(acc * 8 + u32::from(d - b'0')),
+                            _ => None,
+                        })?;
+                        out.push(u8::try_from(value).ok()?);
+                        i += 2;
+                    }
+                    other => out.push(other),
+                }
+            }
+            byte => {
+                out.push(byte);
+                i += 1;
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn parse(text: &str) -> Result<Vec<Hunk>, ParseError> {
+        let lines: Vec<&str> = text.lines().collect();
+        parse_unified_diff(&lines, 1)
+    }
+
+    fn strings(lines: &[&str]) -> Vec<String> {
+        lines.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_only_leading_file_headers_mark_a_unified_diff() {
+        let detect = |text: &str| is_unified_diff(&text.lines().collect::<Vec<_>>());
+        assert!(detect(
+            "diff --git a/x b/x\nold mode 100644\nnew mode 100755"
+        ));
+        assert!(detect("--- a/x\n+++ b/x\n@@ -1 +1 @@\n-a\n+b"));
+        assert!(!detect("--- a/x\nnot a header"));
+        assert!(!detect(""));
+        assert!(!detect(
+            "From 1234567 Mon Sep 17 00:00:00 2001\n\ndiff --git a/x b/x\n--- a/x\n+++ b/x"
+        ));
+        assert!(!detect(
+            "*** Begin Pach\n*** Update File: notes.md\n@@\n--- a/x\n+++ b/x\n*** End Patch"
+        ));
+    }
+
+    #[test]
+    fn test_plain_unified_diff_with_timestamps() {
+        let diff = "--- src/main.rs\t2024-01-01 00:00:00.000000000 +0000\n\
+                    +++ src/main.rs\t2024-01-02 00:00:00.000000000 +0000\n\
+                    @@ -1,3 +1,3 @@\n \
+                    fn main() {\n\
+                    -    println!(\"hi\");\n\
+                    +    println!(\"hello\");\n \
+                    }";
+        assert_eq!(
+            parse(diff),
+            Ok(vec![Hunk::UpdateFile {
+                path: PathBuf::from("src/main.rs"),
+                move_path: None,
+                chunks: vec![UpdateFileChunk {
+                    change_context: None,
+                    old_lines: strings(&["fn main() {", "    println!(\"hi\");", "}"]),
+                    new_lines: strings(&["fn main() {", "    println!(\"hello\");", "}"]),
+                    is_end_of_file: false,
+                    old_missing_newline: false,
+                    new_missing_newline: false,
+                }],
+            }])
+        );
+    }
+
+    #[test]
+    fn test_git_diff_with_add_delete_and_rename() {
+        let diff = r#"From 1234567 Mon Sep 17 00:00:00 2001
+Subject: [PATCH] shuffle files
+
+---
+ 3 files changed
+
+diff --git a/new.txt b/new.txt
+new file mode 100644
+index 0000000..3b18e51
+--- /dev/null
++++ b/new.txt
+@@ -0,0 +1,2 @@
++hello
++world
+\ No newline at end of file
+diff --git a/gone.txt b/gone.txt
+deleted file mode 100644
+index 3b18e51..0000000
+--- a/gone.txt
++++ /dev/null
+@@ -1 +0,0 @@
+-bye
+diff --git a/old name.txt b/dir/new name.txt
+similarity index 80%
+rename from old name.txt
+rename to dir/new name.txt
+index 1111111..2222222 100644
+--- a/old name.txt
++++ b/dir/new name.txt
+@@ -1,2 +1,2 @@
+ keep
+--- removed dashes
++++ added pluses
+--
+2.43.0"#;
+        assert_eq!(
+            parse(diff),
+            Ok(vec![
+                Hunk::AddFile {
+                    path: PathBuf::from("new.txt"),
+                    contents: "hello\nworld".to_string(),
+                },
+                Hunk::DeleteFile {
+                    path: PathBuf::from("gone.txt"),
+                },
+                Hunk::UpdateFile {
+                    path: PathBuf::from("old name.txt"),
+                    move_path: Some(PathBuf::from("dir/new name.txt")),
+                    chunks: vec![UpdateFileChunk {
+                        change_context: None,
+                        old_lines: strings(&["keep", "-- removed dashes"]),
+                        new_lines: strings(&["keep", "++ added pluses"]),
+                        is_end_of_file: false,
+                        old_missing_newline: false,
+                        new_missing_newline: false,
+                    }],
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_pure_rename_and_mode_change() {
+        let diff = "diff --git a/a.txt b/b.txt\n\
+                    similarity index 100%\n\
+                    rename from a.txt\n\
+                    rename to b.txt\n\
+                    diff --git a/run.sh b/run.sh\n\
+                    old mode 100644\n\
+                    new mode 100755\n\
+                    diff --git a/empty b/empty\n\
+                    new file mode 100644\n\
+                    index 0000000..e69de29";
+        assert_eq!(
+            parse(diff),
+            Ok(vec![
+                Hunk::UpdateFile {
+                    path: PathBuf::from("a.txt"),
+                    move_path: Some(PathBuf::from("b.txt")),
+                    chunks: Vec::new(),
+                },
+                Hunk::ChmodFile {
+                    path: PathBuf::from("run.sh"),
+                    mode: 0o755,
+                },
+                Hunk::AddFile {
+                    path: PathBuf::from("empty"),
+                    contents: String::new(),
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_new_executable_and_symlink() {
+        let diff = "diff --git a/run.sh b/run.sh\n\
+                    new file mode 100755\n\
+                    index 0000000..1111111\n\
+                    --- /dev/null\n\
+                    +++ b/run.sh\n\
+                    @@ -0,0 +1 @@\n\
+                    +echo hi\n\
+                    diff --git a/latest b/latest\n\
+                    new file mode 120000\n\
+                    index 0000000..2222222\n\
+                    --- /dev/null\n\
+                    +++ b/latest\n\
+                    @@ -0,0 +1 @@\n\
+                    +releases/v2\n\
+                    \\ No newline at end of file\n\
+                    diff --git a/link b/link\n\
+                    old mode 120000\n\
+                    new mode 100644";
+        assert_eq!(
+            parse(diff),
+            Err(InvalidHunkError {
+                message: "Changing a file into a symlink or back is not supported; delete and re-add it instead"
+                    .to_string(),
+                line_number: 16,
+            })
+        );
+        let (diff, _) = diff.split_at(diff.find("diff --git a/link").unwrap_or(diff.len()));
+        assert_eq!(
+            parse(diff),
+            Ok(vec![
+                Hunk::AddFile {
+                    path: PathBuf::from("run.sh"),
+                    contents: "echo hi\n".to_string(),
+                },
+                Hunk::ChmodFile {
+                    path: PathBuf::from("run.sh"),
+                    mode: 0o755,
+                },
+                Hunk::AddSymlink {
+                    path: PathBuf::from("latest"),
+                    target: PathBuf::from("releases/v2"),
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_no_newline_marks_end_of_file() {
+        let diff = "--- a/f.txt\n\
+                    +++ b/f.txt\n\
+                    @@ -1,2 +1,2 @@\n \
+                    one\n\
+                    -two\n\
+                    \\ No newline at end of file\n\
+                    +three\n\
+                    \\ No newline at end of file";
+        assert_eq!(
+            parse(diff),
+            Ok(vec![Hunk::UpdateFile {
+                path: PathBuf::from("f.txt"),
+                move_path: None,
+                chunks: vec![UpdateFileChunk {
+                    change_context: None,
+                    old_lines: strings(&["one", "two"]),
+                    new_lines: strings(&["one", "three"]),
+                    is_end_of_file: true,
+                    old_missing_newline: true,
+                    new_missing_newline: true,
+                }],
+            }])
+        );
+    }
+
+    #[test]
+    fn test_hunk_body_may_exceed_header_counts() {
+        // Both counts are understated; the body still runs until the next header.
+        let diff = "--- a/x.py\n\
+                    +++ b/x.py\n\
+                    @@ -1 +1 @@\n \
+                    import os\n\
+                    -import sys\n\
+                    +import re\n\
+                    --- a/y.py\n\
+                    +++ b/y.py\n\
+                    @@ -1 +1 @@\n\
+                    -a\n\
+                    +b";
+        let hunks = parse(diff).expect("diff should parse");
+        assert_eq!(hunks.len(), 2);
+        assert_eq!(
+            hunks[0],
+            Hunk::UpdateFile {
+                path: PathBuf::from("x.py"),
+                move_path: None,
+                chunks: vec![UpdateFileChunk {
+                    change_context: None,
+                    old_lines: strings(&["import os", "import sys"]),
+                    new_lines: strings(&["import os", "import re"]),
+                    is_end_of_file: false,
+                    old_missing_newline: false,
+                    new_missing_newline: false,
+                }],
+            }
+        );
+    }
+
+    #[test]
+    fn test_quoted_git_paths() {
+        assert_eq!(
+            parse_git_header_paths(r#""a/caf\303\251.txt" "b/caf\303\251.txt""#),
+            Some(("café.txt".to_string(), "café.txt".to_string()))
+        );
+        assert_eq!(
+            parse_git_header_paths("a/x b/y.txt b/x b/y.txt"),
+            Some(("x b/y.txt".to_string(), "x b/y.txt".to_string()))
+        );
+        assert_eq!(
+            parse_header_path(r#""b/tab\there""#),
+            Some("b/tab\there".to_string())
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            parse("--- a/f\n+++ b/f\n@@ bogus @@\n+x"),
+            Err(InvalidHunkError {
+                message:
+                    "Invalid hunk header '@@ bogus @@'; expected '@@ -start,count +start,count @@'"
+                        .to_string(),
+                line_number: 3,
+            })
+        );
+        assert_eq!(
+            parse("--- a/f\n+++ b/f\n@@ -3,0 +4,1 @@\n+x"),
+            Err(InvalidHunkError {
+                message: "Hunks that only add lines must include surrounding context lines"
+                    .to_string(),
+                line_number: 3,
+            })
+        );
+        assert_eq!(
+            parse("--- a/f\n+++ b/f\n@@ -1,2 +1,2 @@\n x\n*bad"),
+            Err(InvalidHunkError {
+                message: "Unexpected line found in hunk: '*bad'. Every line should start with ' ' (context line), '+' (added line), '-' (removed line) or '\\'"
+                    .to_string(),
+                line_number: 5,
+            })
+        );
+        assert_eq!(
+            parse("diff --git a/bin b/bin\nindex 1..2 100644\nBinary files a/bin and b/bin differ"),
+            Err(InvalidHunkError {
+                message: "binary diffs are not supported".to_string(),
+                line_number: 3,
+            })
+        );
+    }
+}
@@ -88,3 +88,137 @@ fn test_apply_patch_cli_stdin_add_and_update() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_apply_patch_cli_check_does_not_write() -> anyhow::Result<()> {
+    let tmp = tempdir()?;
+    fs::write(tmp.path().join("a.txt"), "one\n")?;
+    let patch = "*** Begin Patch\n*** Update File: a.txt\n@@\n-one\n+two\n*** Add File: b.txt\n+new\n*** End Patch";
+    Command::cargo_bin("apply_patch")
+        .expect("should find apply_patch binary")
+        .args(["--check", patch])
+        .current_dir(tmp.path())
+        .assert()
+        .success()
+        .stdout("Patch applies cleanly. Applying it would update the following files:\nA b.txt\nM a.txt\n");
+    assert_eq!(fs::read_to_string(tmp.path().join("a.txt"))?, "one\n");
+    assert!(!tmp.path().join("b.txt").exists());
+
+    let stale = "*** Begin Patch\n*** Update File: a.txt\n@@\n-missing\n+two\n*** End Patch";
+    let output = Command::cargo_bin("apply_patch")
+        .expect("should find apply_patch binary")
+        .args(["--check", stale])
+        .current_dir(tmp.path())
+        .output()?;
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr)?;
+    assert!(
+        stderr.starts_with("Failed to find expected lines in a.txt:\nmissing"),
+        "{stderr}"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_apply_patch_cli_json_report() -> anyhow::Result<()> {
+    let tmp = tempdir()?;
+    fs::write(tmp.path().join("a.txt"), "one\n")?;
+    let patch = "*** Begin Patch\n*** Update File: a.txt\n*** Move to: c.txt\n@@\n-one\n+two\n*** Add File: b.txt\n+new\n*** End Patch";
+    let output = Command::cargo_bin("apply_patch")
+        .expect("should find apply_patch binary")
+        .args(["--json", patch])
+        .current_dir(tmp.path())
+        .output()?;
+    assert!(output.status.success());
+    let cwd = tmp.path().canonicalize()?;
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+    assert_eq!(
+        report,
+        serde_json::json!({
+            "success": true,
+            "dry_run": false,
+            "files": [
+                {
+                    "action": "update",
+                    "path": cwd.join("a.txt"),
+                    "move_path": cwd.join("c.txt"),
+                    "unified_diff": "@@ -1 +1 @@\n-one\n+two\n",
+                },
+                {
+                    "action": "add",
+                    "path": cwd.join("b.txt"),
+                },
+            ],
+        })
+    );
+    assert_eq!(fs::read_to_string(tmp.path().join("c.txt"))?, "two\n");
+
+    // A failing patch reports the error and leaves the workspace untouched.
+    let output = Command::cargo_bin("apply_patch")
+        .expect("should find apply_patch binary")
+        .args([
+            "--json",
+            "--check",
+            "*** Begin Patch\n*** Delete File: nope.txt\n*** End Patch",
+        ])
+        .current_dir(tmp.path())
+        .output()?;
+    assert!(!output.status.success());
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+    assert_eq!(report["success"], false);
+    assert_eq!(report["dry_run"], true);
+    assert_eq!(report["files"][0]["action"], "delete");
+    assert_eq!(
+        report["error"],
+        "Failed to delete file nope.txt: entity not found"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_apply_patch_cli_reverse() -> anyhow::Result<()> {
+    let tmp = tempdir()?;
+    fs::write(tmp.path().join("a.txt"), "one\ntwo\n")?;
+    let patch = "*** Begin Patch\n*** Update File: a.txt\n*** Move to: moved.txt\n@@\n one\n-two\n+three\n*** Add File: b.txt\n+new\n*** End Patch";
+    for args in [vec![patch], vec!["--reverse", patch]] {
+        Command::cargo_bin("apply_patch")
+            .expect("should find apply_patch binary")
+            .args(args)
+            .current_dir(tmp.path())
+            .assert()
+            .success();
+    }
+    assert_eq!(fs::read_to_string(tmp.path().join("a.txt"))?, "one\ntwo\n");
+    assert!(!tmp.path().join("moved.txt").exists());
+    assert!(!tmp.path().join("b.txt").exists());
+
+    Command::cargo_bin("apply_patch")
+        .expect("should find apply_patch binary")
+        .args([
+            "--reverse",
+            "*** Begin Patch\n*** Delete File: a.txt\n*** End Patch",
+        ])
+        .current_dir(tmp.path())
+        .assert()
+        .failure()
+        .stderr(
+            "Cannot reverse the patch: the contents of deleted file a.txt are not part of the patch\n",
+        );
+    Ok(())
+}
+
+#[test]
+fn test_apply_patch_cli_reverse_git_diff_adding_executable() -> anyhow::Result<()> {
+    let tmp = tempdir()?;
+    let patch = "diff --git a/run.sh b/run.sh\nnew file mode 100755\n--- /dev/null\n+++ b/run.sh\n@@ -0,0 +1 @@\n+echo hi\n";
+    for args in [vec![patch], vec!["--reverse", patch]] {
+        Command::cargo_bin("apply_patch")
+            .expect("should find apply_patch binary")
+            .args(args)
+            .current_dir(tmp.path())
+            .assert()
+            .success();
+    }
+    assert!(!tmp.path().join("run.sh").exists());
+    Ok(())
+}
@@ -59,7 +59,13 @@ where
             Some(patch_arg) => {
                 let mut stdout = std::io::stdout();
                 let mut stderr = std::io::stderr();
-                match codex_apply_patch::apply_patch(&patch_arg, &mut stdout, &mut stderr) {
+                let options = codex_apply_patch::MatchOptions::from_env();
+                match codex_apply_patch::apply_patch_with_options(
+                    &patch_arg,
+                    options,
+                    &mut stdout,
+                    &mut stderr,
+                ) {
                     Ok(()) => 0,
                     Err(_) => 1,
                 }
@@ -0,0 +1,37 @@
+use codex_core::config::Config;
+use codex_core::config::ConfigOverrides;
+use codex_core::exec_env::create_env;
+
+use crate::EnvCommand;
+
+/// Prints the environment that `shell` tool calls would receive, one
+/// `NAME=value` pair per line, sorted by name.
+pub fn run_debug_env(command: EnvCommand) -> anyhow::Result<()> {
+    let EnvCommand {
+        config_profile,
+        names_only,
+        config_overrides,
+    } = command;
+    let config = Config::load_with_cli_overrides(
+        config_overrides
+            .parse_overrides()
+            .map_err(anyhow::Error::msg)?,
+        ConfigOverrides {
+            config_profile,
+            ..Default::default()
+        },
+    )?;
+
+    let mut env: Vec<(String, String)> = create_env(&config.shell_environment_policy)
+        .into_iter()
+        .collect();
+    env.sort();
+    for (name, value) in env {
+        if names_only {
+            println!("{name}");
+        } else {
+            println!("{name}={value}");
+        }
+    }
+    Ok(())
+}
@@ -4,6 +4,7 @@ use codex_common::CliConfigOverrides;
 use codex_core::config::Config;
 use codex_core::config::ConfigOverrides;
 use codex_core::exec_env::create_env;
+use codex_core::landlock::print_linux_sandbox_policy;
 use codex_core::landlock::spawn_command_under_linux_sandbox;
 use codex_core::seatbelt::spawn_command_under_seatbelt;
 use codex_core::spawn::StdioPolicy;
@@ -38,6 +39,7 @@ pub async fn run_command_under_landlock(
 ) -> anyhow::Result<()> {
     let LandlockCommand {
         full_auto,
+        print_policy,
         config_overrides,
         command,
     } = command;
@@ -46,14 +48,14 @@ pub async fn run_command_under_landlock(
         command,
         config_overrides,
         codex_linux_sandbox_exe,
-        SandboxType::Landlock,
+        SandboxType::Landlock { print_policy },
     )
     .await
 }
 
 enum SandboxType {
     Seatbelt,
-    Landlock,
+    Landlock { print_policy: bool },
 }
 
 async fn run_command_under_sandbox(
@@ -78,28 +80,46 @@ async fn run_command_under_sandbox(
     let stdio_policy = StdioPolicy::Inherit;
     let env = create_env(&config.shell_environment_policy);
 
-    let mut child = match sandbox_type {
+    let status = match sandbox_type {
         SandboxType::Seatbelt => {
-            spawn_command_under_seatbelt(command, &config.sandbox_policy, cwd, stdio_policy, env)
-                .await?
-        }
-        SandboxType::Landlock => {
-            #[expect(clippy::expect_used)]
-            let codex_linux_sandbox_exe = config
-                .codex_linux_sandbox_exe
-                .expect("codex-linux-sandbox executable not found");
-            spawn_command_under_linux_sandbox(
-                codex_linux_sandbox_exe,
+            let mut child = spawn_command_under_seatbelt(
                 command,
                 &config.sandbox_policy,
                 cwd,
                 stdio_policy,
                 env,
             )
-            .await?
+            .await?;
+            child.wait().await?
+        }
+        SandboxType::Landlock { print_policy } => {
+            #[expect(clippy::expect_used)]
+            let codex_linux_sandbox_exe = config
+                .codex_linux_sandbox_exe
+                .expect("codex-linux-sandbox executable not found");
+            if print_policy {
+                print_linux_sandbox_policy(
+                    codex_linux_sandbox_exe,
+                    &config.sandbox_policy,
+                    &config.seccomp,
+                    cwd,
+                )
+                .await?
+            } else {
+                let mut child = spawn_command_under_linux_sandbox(
+                    codex_linux_sandbox_exe,
+                    command,
+                    &config.sandbox_policy,
+                    &config.seccomp,
+                    cwd,
+                    stdio_policy,
+                    env,
+                )
+                .await?;
+                child.wait().await?
+            }
         }
     };
-    let status = child.wait().await?;
 
     handle_exit_status(status);
 }
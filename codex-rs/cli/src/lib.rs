@@ -1,3 +1,4 @@
+pub mod debug_env;
 pub mod debug_sandbox;
 mod exit_status;
 pub mod login;
@@ -26,6 +27,11 @@ pub struct LandlockCommand {
     #[arg(long = "full-auto", default_value_t = false)]
     pub full_auto: bool,
 
+    /// Print the Landlock rules and seccomp filter that would be applied,
+    /// including the `[seccomp]` settings from config.toml, and exit.
+    #[arg(long = "print-policy", default_value_t = false)]
+    pub print_policy: bool,
+
     #[clap(skip)]
     pub config_overrides: CliConfigOverrides,
 
@@ -33,3 +39,18 @@ pub struct LandlockCommand {
     #[arg(trailing_var_arg = true)]
     pub command: Vec<String>,
 }
+
+#[derive(Debug, Parser)]
+pub struct EnvCommand {
+    /// Configuration profile from config.toml whose environment should be
+    /// resolved.
+    #[arg(long = "profile", short = 'p')]
+    pub config_profile: Option<String>,
+
+    /// Print only the variable names, not their values.
+    #[arg(long = "names-only", default_value_t = false)]
+    pub names_only: bool,
+
+    #[clap(skip)]
+    pub config_overrides: CliConfigOverrides,
+}
@@ -5,6 +5,7 @@ use clap_complete::generate;
 use codex_arg0::arg0_dispatch_or_else;
 use codex_chatgpt::apply_command::ApplyCommand;
 use codex_chatgpt::apply_command::run_apply_command;
+use codex_cli::EnvCommand;
 use codex_cli::LandlockCommand;
 use codex_cli::SeatbeltCommand;
 use codex_cli::login::run_login_status;
@@ -98,6 +99,10 @@ enum DebugCommand {
 
     /// Run a command under Landlock+seccomp (Linux only).
     Landlock(LandlockCommand),
+
+    /// Print the environment that tool calls receive under the resolved
+    /// `shell_environment_policy`.
+    Env(EnvCommand),
 }
 
 #[derive(Debug, Parser)]
@@ -204,6 +209,10 @@ async fn cli_main(codex_linux_sandbox_exe: Option<PathBuf>) -> anyhow::Result<()
                 )
                 .await?;
             }
+            DebugCommand::Env(mut env_cli) => {
+                prepend_config_flags(&mut env_cli.config_overrides, cli.config_overrides);
+                codex_cli::debug_env::run_debug_env(env_cli)?;
+            }
         },
         Some(Subcommand::Apply(mut apply_cli)) => {
             prepend_config_flags(&mut apply_cli.config_overrides, cli.config_overrides);
@@ -105,23 +105,47 @@ pub(crate) fn convert_apply_patch_to_protocol(
     let changes = action.changes();
     let mut result = HashMap::with_capacity(changes.len());
     for (path, change) in changes {
-        let protocol_change = match change {
-            ApplyPatchFileChange::Add { content } => FileChange::Add {
-                content: content.clone(),
-            },
-            ApplyPatchFileChange::Delete { content } => FileChange::Delete {
-                content: content.clone(),
-            },
-            ApplyPatchFileChange::Update {
-                unified_diff,
-                move_path,
-                new_content: _new_content,
-            } => FileChange::Update {
-                unified_diff: unified_diff.clone(),
-                move_path: move_path.clone(),
-            },
-        };
-        result.insert(path.clone(), protocol_change);
+        result.insert(path.clone(), convert_file_change(change));
     }
     result
 }
+
+fn convert_file_change(change: &ApplyPatchFileChange) -> FileChange {
+    match change {
+        ApplyPatchFileChange::Add { content } => FileChange::Add {
+            content: content.clone(),
+        },
+        ApplyPatchFileChange::Delete { content } => FileChange::Delete {
+            content: content.clone(),
+        },
+        ApplyPatchFileChange::Update {
+            unified_diff,
+            move_path,
+            new_content: _new_content,
+        } => FileChange::Update {
+            unified_diff: unified_diff.clone(),
+            move_path: move_path.clone(),
+        },
+        ApplyPatchFileChange::Binary {
+            old_size,
+            new_content,
+        } => FileChange::Binary {
+            old_size: *old_size,
+            new_size: new_content.len() as u64,
+        },
+        ApplyPatchFileChange::Mode {
+            old_mode,
+            new_mode,
+            contents,
+        } => FileChange::Mode {
+            old_mode: *old_mode,
+            new_mode: *new_mode,
+            contents: contents
+                .as_deref()
+                .map(|contents| Box::new(convert_file_change(contents))),
+        },
+        ApplyPatchFileChange::Symlink { target } => FileChange::Symlink {
+            target: target.clone(),
+        },
+    }
+}
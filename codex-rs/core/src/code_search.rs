@@ -0,0 +1,687 @@
+//! The `code_search` tool: a structural view of source files built with
+//! tree-sitter. It lists the symbols of a file, finds definitions by name and
+//! returns the full source of the item enclosing a line, so the agent does not
+//! have to dump whole files to navigate them.
+
+use std::collections::BTreeMap;
+use std::fs::Metadata;
+use std::path::Path;
+use std::path::PathBuf;
+
+use serde::Deserialize;
+use tree_sitter::Language;
+use tree_sitter::Node;
+use tree_sitter::Parser;
+
+use crate::openai_tools::JsonSchema;
+use crate::openai_tools::OpenAiTool;
+use crate::openai_tools::ResponsesApiTool;
+
+pub(crate) const CODE_SEARCH_TOOL_NAME: &str = "code_search";
+
+/// Larger files are skipped: they are usually generated or vendored.
+const MAX_FILE_BYTES: u64 = 1024 * 1024;
+/// Files visited per walk of a directory.
+const MAX_FILES: usize = 20_000;
+/// Definitions returned per `definition` search.
+const MAX_MATCHES: usize = 10;
+/// Lines of source returned per item.
+const MAX_ITEM_LINES: usize = 400;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Lang {
+    Rust,
+    Python,
+    JavaScript,
+    TypeScript,
+    Tsx,
+    Go,
+}
+
+impl Lang {
+    fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?;
+        Some(match extension {
+            "rs" => Self::Rust,
+            "py" | "pyi" => Self::Python,
+            "js" | "mjs" | "cjs" | "jsx" => Self::JavaScript,
+            "ts" | "mts" | "cts" => Self::TypeScript,
+            "tsx" => Self::Tsx,
+            "go" => Self::Go,
+            _ => return None,
+        })
+    }
+
+    /// Separates the names of nested items, as written in the language.
+    fn separator(self) -> &'static str {
+        match self {
+            Self::Rust => "::",
+            _ => ".",
+        }
+    }
+
+    fn language(self) -> Language {
+        match self {
+            Self::Rust => tree_sitter_rust::LANGUAGE.into(),
+            Self::Python => tree_sitter_python::LANGUAGE.into(),
+            Self::JavaScript => tree_sitter_javascript::LANGUAGE.into(),
+            Self::TypeScript => tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
+            Self::Tsx => tree_sitter_typescript::LANGUAGE_TSX.into(),
+            Self::Go => tree_sitter_go::LANGUAGE.into(),
+        }
+    }
+}
+
+/// A named item in a source file: a function, type, impl block, etc.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Symbol {
+    kind: &'static str,
+    name: String,
+    /// Names of the enclosing symbols, outermost first.
+    parents: Vec<String>,
+    /// The name qualified by its parents, e.g. `<Config as fmt::Display>::fmt`.
+    qualified: String,
+    /// 1-based, inclusive.
+    start_line: usize,
+    end_line: usize,
+    start_byte: usize,
+    end_byte: usize,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Action {
+    Symbols,
+    Definition,
+    Enclosing,
+}
+
+#[derive(Debug, Deserialize)]
+struct CodeSearchArgs {
+    action: Action,
+    #[serde(default)]
+    path: Option<String>,
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    line: Option<usize>,
+}
+
+/// Handles a `code_search` call. Relative paths are resolved against `cwd`.
+pub(crate) async fn handle_code_search(cwd: &Path, arguments: &str) -> Result<String, String> {
+    let args: CodeSearchArgs = serde_json::from_str(arguments)
+        .map_err(|e| format!("failed to parse function arguments: {e}"))?;
+    let cwd = cwd.to_path_buf();
+    // Parsing a large tree is CPU-bound; keep it off the async workers.
+    tokio::task::spawn_blocking(move || run(&cwd, args))
+        .await
+        .map_err(|e| format!("code_search failed: {e}"))?
+}
+
+fn run(cwd: &Path, args: CodeSearchArgs) -> Result<String, String> {
+    let path = cwd.join(args.path.as_deref().unwrap_or("."));
+    match args.action {
+        Action::Symbols => {
+            let (source, symbols) = parse_file(&path)?;
+            Ok(format_outline(&display_path(cwd, &path), &source, &symbols))
+        }
+        Action::Definition => {
+            let name = args
+                .name
+                .filter(|name| !name.is_empty())
+                .ok_or("`definition` requires a `name`")?;
+            find_definitions(cwd, &path, &name)
+        }
+        Action::Enclosing => {
+            let line = args.line.ok_or("`enclosing` requires a `line`")?;
+            let (source, symbols) = parse_file(&path)?;
+            let symbol = symbols
+                .iter()
+                .filter(|s| s.start_line <= line && line <= s.end_line)
+                .min_by_key(|s| s.end_line - s.start_line)
+                .ok_or_else(|| {
+                    format!(
+                        "line {line} of {} is not inside a function, type or other item",
+                        display_path(cwd, &path)
+                    )
+                })?;
+            Ok(format_item(&display_path(cwd, &path), &source, symbol))
+        }
+    }
+}
+
+/// The symbols of `source`, read from `path`, as `kind qualified_name`
+/// strings in document order. Empty for unsupported or unparsable files.
+pub(crate) fn symbol_outline(path: &Path, source: &str) -> Vec<String> {
+    Lang::from_path(path)
+        .and_then(|lang| parse_symbols(lang, source))
+        .unwrap_or_default()
+        .into_iter()
+        .map(|symbol| format!("{} {}", symbol.kind, symbol.qualified))
+        .collect()
+}
+
+fn parse_file(path: &Path) -> Result<(String, Vec<Symbol>), String> {
+    let lang = Lang::from_path(path)
+        .ok_or_else(|| format!("unsupported file type: {}", path.display()))?;
+    let source = std::fs::read_to_string(path)
+        .map_err(|e| format!("failed to read {}: {e}", path.display()))?;
+    let symbols = parse_symbols(lang, &source)
+        .ok_or_else(|| format!("failed to parse {}", path.display()))?;
+    Ok((source, symbols))
+}
+
+/// Returns the symbols of `source` in document order.
+fn parse_symbols(lang: Lang, source: &str) -> Option<Vec<Symbol>> {
+    let mut parser = Parser::new();
+    parser.set_language(&lang.language()).ok()?;
+    let tree = parser.parse(source, None)?;
+    let mut symbols = Vec::new();
+    collect_symbols(
+        lang,
+        tree.root_node(),
+        source,
+        &mut Vec::new(),
+        &mut symbols,
+    );
+    Some(symbols)
+}
+
+fn collect_symbols(
+    lang: Lang,
+    node: Node,
+    source: &str,
+    parents: &mut Vec<String>,
+    out: &mut Vec<Symbol>,
+) {
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        match classify(lang, child, source) {
+            Some((kind, name, extent)) => {
+                let qualified = parents
+                    .iter()
+                    .map(|parent| qualifier(parent))
+                    .chain([name.clone()])
+                    .collect::<Vec<_>>()
+                    .join(lang.separator());
+                out.push(Symbol {
+                    kind,
+                    name: name.clone(),
+                    parents: parents.clone(),
+                    qualified,
+                    start_line: extent.start_position().row + 1,
+                    end_line: extent.end_position().row + 1,
+                    start_byte: extent.start_byte(),
+                    end_byte: extent.end_byte(),
+                });
+                parents.push(name);
+                collect_symbols(lang, child, source, parents, out);
+                parents.pop();
+            }
+            None => collect_symbols(lang, child, source, parents, out),
+        }
+    }
+}
+
+/// Decides whether `node` defines a symbol, returning its kind, its name and
+/// the node spanning its full source.
+fn classify<'a>(
+    lang: Lang,
+    node: Node<'a>,
+    source: &str,
+) -> Option<(&'static str, String, Node<'a>)> {
+    let text = |node: Node| source.get(node.byte_range()).map(str::to_string);
+    let named = |kind: &'static str| Some((kind, text(node.child_by_field_name("name")?)?, node));
+    match (lang, node.kind()) {
+        (Lang::Rust, "function_item" | "function_signature_item") => named("fn"),
+        (Lang::Rust, "struct_item") => named("struct"),
+        (Lang::Rust, "enum_item") => named("enum"),
+        (Lang::Rust, "union_item") => named("union"),
+        (Lang::Rust, "trait_item") => named("trait"),
+        (Lang::Rust, "mod_item") => named("mod"),
+        (Lang::Rust, "const_item") => named("const"),
+        (Lang::Rust, "static_item") => named("static"),
+        (Lang::Rust, "type_item") => named("type"),
+        (Lang::Rust, "macro_definition") => named("macro"),
+        (Lang::Rust, "impl_item") => {
+            let ty = text(node.child_by_field_name("type")?)?;
+            let name = match node.child_by_field_name("trait").and_then(text) {
+                Some(trait_name) => format!("{trait_name} for {ty}"),
+                None => ty,
+            };
+            Some(("impl", name, node))
+        }
+        (Lang::Python, "function_definition") => named("def"),
+        (Lang::Python, "class_definition") => named("class"),
+        (
+            Lang::JavaScript | Lang::TypeScript | Lang::Tsx,
+            "function_declaration" | "generator_function_declaration" | "function_signature",
+        ) => named("function"),
+        (
+            Lang::JavaScript | Lang::TypeScript | Lang::Tsx,
+            "class_declaration" | "abstract_class_declaration",
+        ) => named("class"),
+        (Lang::JavaScript | Lang::TypeScript | Lang::Tsx, "method_definition") => named("method"),
+        (Lang::TypeScript | Lang::Tsx, "interface_declaration") => named("interface"),
+        (Lang::TypeScript | Lang::Tsx, "type_alias_declaration") => named("type"),
+        (Lang::TypeScript | Lang::Tsx, "enum_declaration") => named("enum"),
+        // `const f = () => {}` and `const f = function () {}`.
+        (Lang::JavaScript | Lang::TypeScript | Lang::Tsx, "variable_declarator") => {
+            let value = node.child_by_field_name("value")?;
+            if !matches!(
+                value.kind(),
+                "arrow_function" | "function_expression" | "function"
+            ) {
+                return None;
+            }
+            let declaration = node
+                .parent()
+                .filter(|p| matches!(p.kind(), "lexical_declaration" | "variable_declaration"))
+                .unwrap_or(node);
+            let extent = declaration
+                .parent()
+                .filter(|p| p.kind() == "export_statement")
+                .unwrap_or(declaration);
+            Some(("function", text(node.child_by_field_name("name")?)?, extent))
+        }
+        (Lang::Go, "function_declaration") => named("func"),
+        (Lang::Go, "method_declaration") => named("method"),
+        (Lang::Go, "type_spec") => {
+            let kind = match node.child_by_field_name("type").map(|t| t.kind()) {
+                Some("struct_type") => "struct",
+                Some("interface_type") => "interface",
+                _ => "type",
+            };
+            named(kind)
+        }
+        _ => None,
+    }
+}
+
+/// Finds the definitions of `query` in `path` (a file or directory). A
+/// qualified query such as `Config::load` or `Config.load` only matches
+/// symbols nested in one named `Config`.
+fn find_definitions(cwd: &Path, path: &Path, query: &str) -> Result<String, String> {
+    let mut parts: Vec<&str> = query.split(['.', ':']).filter(|p| !p.is_empty()).collect();
+    let name = parts.pop().ok_or("`name` must not be empty")?;
+
+    let mut matches = Vec::new();
+    let mut total = 0;
+    for file in source_files(path)? {
+        let Ok((source, symbols)) = parse_file(&file) else {
+            continue;
+        };
+        for symbol in symbols {
+            if symbol.name == name && qualifiers_match(&symbol.parents, &parts) {
+                total += 1;
+                if matches.len() < MAX_MATCHES {
+                    matches.push(format_item(&display_path(cwd, &file), &source, &symbol));
+                }
+            }
+        }
+    }
+
+    if matches.is_empty() {
+        return Ok(format!(
+            "No definition of `{query}` found in {}.",
+            display_path(cwd, path)
+        ));
+    }
+    let mut out = matches.join("\n");
+    if total > MAX_MATCHES {
+        let more = total - MAX_MATCHES;
+        out.push_str(&format!("\n[... {more} more definitions ...]\n"));
+    }
+    Ok(out)
+}
+
+/// Renders the name of a parent symbol as a qualifier: trait impls are
+/// written `<Type as Trait>`.
+fn qualifier(parent: &str) -> String {
+    match parent.split_once(" for ") {
+        Some((trait_name, ty)) => format!("<{ty} as {trait_name}>"),
+        None => parent.to_string(),
+    }
+}
+
+/// Whether `qualifiers` name the innermost of `parents`. Only base names are
+/// compared, so `Config` matches `impl Config<T>` and both `Config` and
+/// `Display` match `impl fmt::Display for Config`.
+fn qualifiers_match(parents: &[String], qualifiers: &[&str]) -> bool {
+    if qualifiers.len() > parents.len() {
+        return false;
+    }
+    let base_name = |name: &str| {
+        let name = name.split('<').next().unwrap_or(name);
+        name.rsplit("::").next().unwrap_or(name).trim().to_string()
+    };
+    parents[parents.len() - qualifiers.len()..]
+        .iter()
+        .zip(qualifiers)
+        .all(|(parent, qualifier)| {
+            parent
+                .split(" for ")
+                .any(|name| base_name(name) == *qualifier)
+        })
+}
+
+/// Lists the supported source files under `path`, honouring `.gitignore`.
+fn source_files(path: &Path) -> Result<Vec<PathBuf>, String> {
+    if path.is_file() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+    if !path.is_dir() {
+        return Err(format!("{} does not exist", path.display()));
+    }
+    let mut files: Vec<PathBuf> = walk_source_files(path).map(|(path, _)| path).collect();
+    files.sort();
+    Ok(files)
+}
+
+/// Walks `root` for supported source files, honouring `.gitignore` and
+/// skipping files larger than [`MAX_FILE_BYTES`], and stops after
+/// [`MAX_FILES`] of them.
+pub(crate) fn walk_source_files(root: &Path) -> impl Iterator<Item = (PathBuf, Metadata)> {
+    ignore::WalkBuilder::new(root)
+        .build()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_some_and(|t| t.is_file()))
+        .filter(|entry| Lang::from_path(entry.path()).is_some())
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok()?;
+            (metadata.len() <= MAX_FILE_BYTES).then(|| (entry.into_path(), metadata))
+        })
+        .take(MAX_FILES)
+}
+
+fn display_path(cwd: &Path, path: &Path) -> String {
+    let relative = path.strip_prefix(cwd).unwrap_or(path);
+    if relative.as_os_str().is_empty() {
+        ".".to_string()
+    } else {
+        relative.display().to_string()
+    }
+}
+
+/// An indented outline of `symbols`, one per line with its line range.
+fn format_outline(path: &str, source: &str, symbols: &[Symbol]) -> String {
+    if symbols.is_empty() {
+        return format!("{path}: no symbols found\n");
+    }
+    let total_lines = source.lines().count();
+    let mut out = format!("{path} ({total_lines} lines)\n");
+    for symbol in symbols {
+        let indent = "  ".repeat(symbol.parents.len() + 1);
+        out.push_str(&format!(
+            "{indent}{} {} (lines {}-{})\n",
+            symbol.kind, symbol.name, symbol.start_line, symbol.end_line
+        ));
+    }
+    out
+}
+
+/// The full source of `symbol`, headed by its location.
+fn format_item(path: &str, source: &str, symbol: &Symbol) -> String {
+    // Start at the beginning of the line so that indentation is kept.
+    let start = source[..symbol.start_byte]
+        .rfind('\n')
+        .map_or(0, |newline| newline + 1);
+    let body = source.get(start..symbol.end_byte).unwrap_or_default();
+    let mut lines: Vec<&str> = body.lines().collect();
+    let omitted = lines.len().saturating_sub(MAX_ITEM_LINES);
+    lines.truncate(MAX_ITEM_LINES);
+
+    let mut out = format!(
+        "{path}:{}-{}: {} {}\n{}\n",
+        symbol.start_line,
+        symbol.end_line,
+        symbol.kind,
+        symbol.qualified,
+        lines.join("\n")
+    );
+    if omitted > 0 {
+        out.push_str(&format!("[... omitted {omitted} more lines ...]\n"));
+    }
+    out
+}
+
+pub(crate) fn create_code_search_tool() -> OpenAiTool {
+    let mut properties = BTreeMap::new();
+    properties.insert(
+        "action".to_string(),
+        JsonSchema::String {
+            description: Some(
+                "One of: `symbols` (outline of the file at `path`), `definition` (source of every definition of `name` under `path`, which defaults to the working directory), `enclosing` (source of the innermost item containing `line` of the file at `path`).".to_string(),
+            ),
+        },
+    );
+    properties.insert(
+        "path".to_string(),
+        JsonSchema::String {
+            description: Some("File or directory, relative to the working directory.".to_string()),
+        },
+    );
+    properties.insert(
+        "name".to_string(),
+        JsonSchema::String {
+            description: Some(
+                "Symbol to find for `definition`, optionally qualified (`Config::load`, `Parser.parse`).".to_string(),
+            ),
+        },
+    );
+    properties.insert(
+        "line".to_string(),
+        JsonSchema::Number {
+            description: Some("1-based line for `enclosing`.".to_string()),
+        },
+    );
+    OpenAiTool::Function(ResponsesApiTool {
+        name: CODE_SEARCH_TOOL_NAME.to_string(),
+        description: "Navigates source code structurally (Rust, Python, JavaScript, TypeScript and Go). Prefer it to reading whole files when looking for a function, type or method.".to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: Some(vec!["action".to_string()]),
+            additional_properties: Some(false),
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    const RUST_SOURCE: &str = r#"use std::fmt;
+
+pub struct Config {
+    name: String,
+}
+
+impl Config {
+    pub fn load() -> Self {
+        Self {
+            name: String::new(),
+        }
+    }
+}
+
+impl fmt::Display for Config {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+fn load() {}
+"#;
+
+    fn write(dir: &TempDir, name: &str, contents: &str) -> PathBuf {
+        let path = dir.path().join(name);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).unwrap();
+        }
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn lists_rust_symbols_as_an_outline() {
+        let dir = TempDir::new().unwrap();
+        write(&dir, "src/config.rs", RUST_SOURCE);
+        let args = CodeSearchArgs {
+            action: Action::Symbols,
+            path: Some("src/config.rs".to_string()),
+            name: None,
+            line: None,
+        };
+        assert_eq!(
+            run(dir.path(), args),
+            Ok("src/config.rs (21 lines)
+  struct Config (lines 3-5)
+  impl Config (lines 7-13)
+    fn load (lines 8-12)
+  impl fmt::Display for Config (lines 15-19)
+    fn fmt (lines 16-18)
+  fn load (lines 21-21)
+"
+            .to_string())
+        );
+    }
+
+    #[test]
+    fn finds_qualified_definitions_across_files() {
+        let dir = TempDir::new().unwrap();
+        write(&dir, "src/config.rs", RUST_SOURCE);
+        write(
+            &dir,
+            "web/app.ts",
+            "export const load = () => {\n  return 1;\n};\n",
+        );
+        write(&dir, "target/generated.rs", "fn load() {}\n");
+        write(&dir, ".gitignore", "target/\n");
+        std::fs::create_dir(dir.path().join(".git")).unwrap();
+
+        let search = |name: &str| {
+            run(
+                dir.path(),
+                CodeSearchArgs {
+                    action: Action::Definition,
+                    path: None,
+                    name: Some(name.to_string()),
+                    line: None,
+                },
+            )
+        };
+
+        assert_eq!(
+            search("Config::load"),
+            Ok("src/config.rs:8-12: fn Config::load
+    pub fn load() -> Self {
+        Self {
+            name: String::new(),
+        }
+    }
+"
+            .to_string())
+        );
+        assert_eq!(
+            search("load"),
+            Ok("src/config.rs:8-12: fn Config::load
+    pub fn load() -> Self {
+        Self {
+            name: String::new(),
+        }
+    }
+
+src/config.rs:21-21: fn load
+fn load() {}
+
+web/app.ts:1-3: function load
+export const load = () => {
+  return 1;
+};
+"
+            .to_string())
+        );
+        assert_eq!(
+            search("Display.fmt"),
+            Ok("src/config.rs:16-18: fn <Config as fmt::Display>::fmt
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, \"{}\", self.name)
+    }
+"
+            .to_string())
+        );
+        assert_eq!(
+            search("missing"),
+            Ok("No definition of `missing` found in ..".to_string())
+        );
+    }
+
+    #[test]
+    fn returns_the_innermost_enclosing_item() {
+        let dir = TempDir::new().unwrap();
+        write(
+            &dir,
+            "app.py",
+            "import os\n\nclass App:\n    def run(self):\n        return os.getcwd()\n",
+        );
+        let enclosing = |line| {
+            run(
+                dir.path(),
+                CodeSearchArgs {
+                    action: Action::Enclosing,
+                    path: Some("app.py".to_string()),
+                    name: None,
+                    line: Some(line),
+                },
+            )
+        };
+        assert_eq!(
+            enclosing(5),
+            Ok(
+                "app.py:4-5: def App.run\n    def run(self):\n        return os.getcwd()\n"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            enclosing(1),
+            Err("line 1 of app.py is not inside a function, type or other item".to_string())
+        );
+    }
+
+    #[test]
+    fn lists_go_and_typescript_types() {
+        let go = parse_symbols(
+            Lang::Go,
+            "package main\n\ntype Server struct{}\n\ntype Handler interface{}\n\nfunc (s *Server) Run() {}\n",
+        )
+        .unwrap();
+        let names: Vec<(&str, &str)> = go.iter().map(|s| (s.kind, s.name.as_str())).collect();
+        assert_eq!(
+            names,
+            vec![
+                ("struct", "Server"),
+                ("interface", "Handler"),
+                ("method", "Run"),
+            ]
+        );
+
+        let ts = parse_symbols(
+            Lang::TypeScript,
+            "interface Props {}\ntype Id = string;\nclass Store {\n  get(id: Id) {}\n}\n",
+        )
+        .unwrap();
+        let names: Vec<(&str, &str)> = ts.iter().map(|s| (s.kind, s.name.as_str())).collect();
+        assert_eq!(
+            names,
+            vec![
+                ("interface", "Props"),
+                ("type", "Id"),
+                ("class", "Store"),
+                ("method", "get"),
+            ]
+        );
+    }
+}
@@ -1,6 +1,7 @@
 use std::borrow::Cow;
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::path::Path;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::Mutex;
@@ -10,10 +11,13 @@ use std::time::Duration;
 
 use crate::AuthManager;
 use crate::event_mapping::map_response_item_to_event_messages;
+use crate::rollout::recorder::ExecEnvRecord;
 use crate::rollout::recorder::RolloutItem;
 use async_channel::Receiver;
 use async_channel::Sender;
 use codex_apply_patch::ApplyPatchAction;
+use codex_apply_patch::FUZZY_THRESHOLD_ENV_VAR;
+use codex_apply_patch::MatchOptions;
 use codex_apply_patch::MaybeApplyPatchVerified;
 use codex_apply_patch::maybe_parse_apply_patch_verified;
 use codex_protocol::mcp_protocol::ConversationId;
@@ -42,7 +46,10 @@ use crate::apply_patch::convert_apply_patch_to_protocol;
 use crate::client::ModelClient;
 use crate::client_common::Prompt;
 use crate::client_common::ResponseEvent;
+use crate::code_search::CODE_SEARCH_TOOL_NAME;
+use crate::code_search::handle_code_search;
 use crate::config::Config;
+use crate::config_types::Seccomp;
 use crate::config_types::ShellEnvironmentPolicy;
 use crate::conversation_history::ConversationHistory;
 use crate::conversation_manager::InitialHistory;
@@ -57,12 +64,22 @@ use crate::exec::SandboxType;
 use crate::exec::StdoutStream;
 use crate::exec::StreamOutput;
 use crate::exec::process_exec_tool_call;
+use crate::exec_command::BACKGROUND_PROCESS_LOGS_TOOL_NAME;
+use crate::exec_command::BACKGROUND_PROCESS_START_TOOL_NAME;
+use crate::exec_command::BACKGROUND_PROCESS_STOP_TOOL_NAME;
+use crate::exec_command::BackgroundProcessLogsParams;
 use crate::exec_command::EXEC_COMMAND_TOOL_NAME;
 use crate::exec_command::ExecCommandParams;
 use crate::exec_command::ExecSessionManager;
+use crate::exec_command::StartBackgroundProcessParams;
+use crate::exec_command::StopBackgroundProcessParams;
 use crate::exec_command::WRITE_STDIN_TOOL_NAME;
 use crate::exec_command::WriteStdinParams;
 use crate::exec_env::create_env;
+use crate::lsp::DIAGNOSTICS_TOOL_NAME;
+use crate::lsp::FIND_DEFINITION_TOOL_NAME;
+use crate::lsp::FIND_REFERENCES_TOOL_NAME;
+use crate::lsp::LspManager;
 use crate::mcp_connection_manager::McpConnectionManager;
 use crate::mcp_tool_call::handle_mcp_tool_call;
 use crate::model_family::find_family_for_model;
@@ -72,7 +89,10 @@ use crate::openai_tools::ToolsConfig;
 use crate::openai_tools::ToolsConfigParams;
 use crate::openai_tools::get_openai_tools;
 use crate::parse_command::parse_command;
+use crate::persistent_shell::PersistentShellManager;
+use crate::persistent_shell::sandbox_type_for_policy;
 use crate::plan_tool::handle_update_plan;
+use crate::post_edit::PostEditHooks;
 use crate::project_doc::get_user_instructions;
 use crate::protocol::AgentMessageDeltaEvent;
 use crate::protocol::AgentMessageEvent;
@@ -82,6 +102,7 @@ use crate::protocol::AgentReasoningSectionBreakEvent;
 use crate::protocol::ApplyPatchApprovalRequestEvent;
 use crate::protocol::AskForApproval;
 use crate::protocol::BackgroundEventEvent;
+use crate::protocol::BackgroundProcessesUpdateEvent;
 use crate::protocol::ErrorEvent;
 use crate::protocol::Event;
 use crate::protocol::EventMsg;
@@ -103,6 +124,9 @@ use crate::protocol::TaskCompleteEvent;
 use crate::protocol::TokenUsageInfo;
 use crate::protocol::TurnDiffEvent;
 use crate::protocol::WebSearchBeginEvent;
+use crate::redact::Redactor;
+use crate::repo_index::REPO_SEARCH_TOOL_NAME;
+use crate::repo_index::RepoIndex;
 use crate::rollout::RolloutRecorder;
 use crate::rollout::RolloutRecorderParams;
 use crate::safety::SafetyCheck;
@@ -110,6 +134,7 @@ use crate::safety::assess_command_safety;
 use crate::safety::assess_safety_for_untrusted_command;
 use crate::shell;
 use crate::turn_diff_tracker::TurnDiffTracker;
+use crate::turn_diff_tracker::move_destination;
 use crate::user_instructions::UserInstructions;
 use crate::user_notification::UserNotification;
 use crate::util::backoff;
@@ -278,6 +303,24 @@ pub(crate) struct Session {
     mcp_connection_manager: McpConnectionManager,
     session_manager: ExecSessionManager,
 
+    /// Long-lived shell backing the `shell` tool when
+    /// `tools.persistent_shell` is enabled (the default on Unix).
+    persistent_shell: Option<PersistentShellManager>,
+
+    /// Scrubs secrets from tool output before it reaches the model.
+    redactor: Redactor,
+
+    /// Formatters and linters run on the files touched by `apply_patch`.
+    post_edit: PostEditHooks,
+
+    /// Language servers backing the LSP tools and the diagnostics reported
+    /// after each patch.
+    lsp: LspManager,
+
+    /// Index behind the `repo_search` tool and the repository map, when
+    /// `repo_index.enabled` is set.
+    repo_index: Option<RepoIndex>,
+
     /// External notifier command (will be passed as args to exec()). When
     /// `None` this feature is disabled.
     notify: Option<Vec<String>>,
@@ -287,6 +330,8 @@ pub(crate) struct Session {
     rollout: Mutex<Option<RolloutRecorder>>,
     state: Mutex<State>,
     codex_linux_sandbox_exe: Option<PathBuf>,
+    seccomp: Seccomp,
+    apply_patch_match_options: MatchOptions,
     user_shell: shell::Shell,
     show_raw_agent_reasoning: bool,
 }
@@ -467,6 +512,11 @@ impl Session {
                 include_apply_patch_tool: config.include_apply_patch_tool,
                 include_web_search_request: config.tools_web_search_request,
                 use_streamable_shell_tool: config.use_experimental_streamable_shell_tool,
+                use_persistent_shell_tool: config.use_persistent_shell_tool,
+                include_background_process_tools: config.include_background_process_tools,
+                include_lsp_tools: !config.lsp_servers.is_empty(),
+                include_code_search_tool: config.include_code_search_tool,
+                include_repo_search_tool: config.repo_index.enabled,
                 include_view_image_tool: config.include_view_image_tool,
             }),
             user_instructions,
@@ -482,10 +532,28 @@ impl Session {
             tx_event: tx_event.clone(),
             mcp_connection_manager,
             session_manager: ExecSessionManager::default(),
+            persistent_shell: (config.use_persistent_shell_tool && cfg!(unix))
+                .then(|| PersistentShellManager::new(turn_context.cwd.clone())),
+            redactor: Redactor::new(&config.redaction)?,
+            post_edit: PostEditHooks::new(&config.formatters, &config.linters),
+            lsp: LspManager::new(turn_context.cwd.clone(), &config.lsp_servers),
+            repo_index: config.repo_index.enabled.then(|| {
+                let repo_index = RepoIndex::new(
+                    &config.codex_home,
+                    turn_context.cwd.clone(),
+                    config.repo_index.context_files,
+                );
+                repo_index.refresh_in_background();
+                repo_index
+            }),
             notify,
             state: Mutex::new(state),
             rollout: Mutex::new(Some(rollout_recorder)),
             codex_linux_sandbox_exe: config.codex_linux_sandbox_exe.clone(),
+            seccomp: config.seccomp.clone(),
+            apply_patch_match_options: MatchOptions {
+                fuzzy_threshold: config.apply_patch_fuzzy_threshold,
+            },
             user_shell: default_shell,
             show_raw_agent_reasoning: config.show_raw_agent_reasoning,
         });
@@ -512,6 +580,25 @@ impl Session {
             sess.send_event(event).await;
         }
 
+        // Keep front-ends informed about background processes, including ones
+        // that become ready or exit between tool calls.
+        let mut background_processes = sess.session_manager.subscribe_background_processes();
+        let tx_background_event = tx_event.clone();
+        tokio::spawn(async move {
+            while background_processes.changed().await.is_ok() {
+                let processes = background_processes.borrow_and_update().clone();
+                let event = Event {
+                    id: INITIAL_SUBMIT_ID.to_owned(),
+                    msg: EventMsg::BackgroundProcessesUpdate(BackgroundProcessesUpdateEvent {
+                        processes,
+                    }),
+                };
+                if tx_background_event.send(event).await.is_err() {
+                    break;
+                }
+            }
+        });
+
         Ok((sess, turn_context))
     }
 
@@ -540,7 +627,7 @@ impl Session {
         match conversation_history {
             InitialHistory::New => {
                 // Build and record initial items (user instructions + environment context)
-                let items = self.build_initial_context(turn_context);
+                let items = self.build_initial_context(turn_context).await;
                 self.record_conversation_items(&items).await;
             }
             InitialHistory::Resumed(_) | InitialHistory::Forked(_) => {
@@ -679,20 +766,50 @@ impl Session {
         self.persist_rollout_items(&rollout_items).await;
     }
 
-    fn build_initial_context(&self, turn_context: &TurnContext) -> Vec<ResponseItem> {
+    async fn build_initial_context(&self, turn_context: &TurnContext) -> Vec<ResponseItem> {
         let mut items = Vec::<ResponseItem>::with_capacity(2);
         if let Some(user_instructions) = turn_context.user_instructions.as_deref() {
             items.push(UserInstructions::new(user_instructions.to_string()).into());
         }
-        items.push(ResponseItem::from(EnvironmentContext::new(
-            Some(turn_context.cwd.clone()),
-            Some(turn_context.approval_policy),
-            Some(turn_context.sandbox_policy.clone()),
-            Some(self.user_shell.clone()),
-        )));
+        let repository_map = match &self.repo_index {
+            Some(repo_index) => repo_index.repository_map().await,
+            None => None,
+        };
+        items.push(ResponseItem::from(
+            EnvironmentContext::new(
+                Some(turn_context.cwd.clone()),
+                Some(turn_context.approval_policy),
+                Some(turn_context.sandbox_policy.clone()),
+                Some(self.user_shell.clone()),
+            )
+            .with_repository_map(repository_map),
+        ));
         items
     }
 
+    /// Records the names of the environment variables passed to a tool call
+    /// when `shell_environment_policy.record_in_rollout` is set. A call that
+    /// runs in the persistent shell does not get `env`, which the record notes.
+    async fn record_exec_env(
+        &self,
+        turn_context: &TurnContext,
+        call_id: &str,
+        env: &HashMap<String, String>,
+        in_persistent_shell: bool,
+    ) {
+        if !turn_context.shell_environment_policy.record_in_rollout {
+            return;
+        }
+        let mut variables: Vec<String> = env.keys().cloned().collect();
+        variables.sort();
+        self.persist_rollout_items(&[RolloutItem::ExecEnv(ExecEnvRecord {
+            call_id: call_id.to_string(),
+            variables,
+            inexact: in_persistent_shell && self.persistent_shell.is_some(),
+        })])
+        .await;
+    }
+
     async fn persist_rollout_items(&self, items: &[RolloutItem]) {
         let recorder = {
             let guard = self.rollout.lock_unchecked();
@@ -847,14 +964,38 @@ impl Session {
         self.on_exec_command_begin(turn_diff_tracker, begin_ctx.clone())
             .await;
 
-        let result = process_exec_tool_call(
-            exec_args.params,
-            exec_args.sandbox_type,
-            exec_args.sandbox_policy,
-            exec_args.codex_linux_sandbox_exe,
-            exec_args.stdout_stream,
-        )
-        .await;
+        let result = match &self.persistent_shell {
+            Some(shell) if exec_args.persistent_shell_sandbox_cwd.is_some() => {
+                shell.run(exec_args).await
+            }
+            _ => {
+                process_exec_tool_call(
+                    exec_args.params,
+                    exec_args.sandbox_type,
+                    exec_args.sandbox_policy,
+                    exec_args.codex_linux_sandbox_exe,
+                    exec_args.seccomp,
+                    exec_args.stdout_stream,
+                )
+                .await
+            }
+        };
+
+        let mut result = result;
+        if let (Some(patch), Ok(output)) = (&begin_ctx.apply_patch, &mut result)
+            && output.exit_code == 0
+        {
+            // Formatters run before the turn diff is computed so that their
+            // edits are folded into it.
+            append_post_edit_reports(
+                &self.post_edit,
+                &self.lsp,
+                &begin_ctx.cwd,
+                &patch.changes,
+                output,
+            )
+            .await;
+        }
 
         let output_stderr;
         let borrowed: &ExecToolCallOutput = match &result {
@@ -940,9 +1081,11 @@ impl Session {
         arguments: Option<serde_json::Value>,
         timeout: Option<Duration>,
     ) -> anyhow::Result<CallToolResult> {
-        self.mcp_connection_manager
+        let result = self
+            .mcp_connection_manager
             .call_tool(server, tool, arguments, timeout)
-            .await
+            .await?;
+        Ok(self.redactor.redact_call_tool_result(result))
     }
 
     fn interrupt_task(&self) {
@@ -1006,6 +1149,36 @@ pub(crate) struct ApplyPatchCommandContext {
     pub(crate) changes: HashMap<PathBuf, FileChange>,
 }
 
+/// Runs the formatters, linters and language servers on the files touched by
+/// a successful patch and appends what they report to its output.
+async fn append_post_edit_reports(
+    post_edit: &PostEditHooks,
+    lsp: &LspManager,
+    cwd: &Path,
+    changes: &HashMap<PathBuf, FileChange>,
+    output: &mut ExecToolCallOutput,
+) {
+    let files: Vec<PathBuf> = changes
+        .iter()
+        .flat_map(|(path, change)| {
+            std::iter::once(path.clone()).chain(move_destination(change).cloned())
+        })
+        .collect();
+    let formatted = post_edit.run(cwd, &files).await;
+    let created: Vec<PathBuf> = changes
+        .iter()
+        .filter(|(_, change)| matches!(change, FileChange::Add { .. }))
+        .map(|(path, _)| path.clone())
+        .collect();
+    let diagnostics = lsp.new_diagnostics(cwd, &files, &created).await;
+    for report in formatted.into_iter().chain(diagnostics) {
+        for stream in [&mut output.stdout, &mut output.aggregated_output] {
+            stream.text.push('\n');
+            stream.text.push_str(&report);
+        }
+    }
+}
+
 /// A series of Turns in response to user input.
 pub(crate) struct AgentTask {
     sess: Arc<Session>,
@@ -1146,6 +1319,11 @@ async fn submission_loop(
                     include_apply_patch_tool: config.include_apply_patch_tool,
                     include_web_search_request: config.tools_web_search_request,
                     use_streamable_shell_tool: config.use_experimental_streamable_shell_tool,
+                    use_persistent_shell_tool: config.use_persistent_shell_tool,
+                    include_background_process_tools: config.include_background_process_tools,
+                    include_lsp_tools: !config.lsp_servers.is_empty(),
+                    include_code_search_tool: config.include_code_search_tool,
+                    include_repo_search_tool: config.repo_index.enabled,
                     include_view_image_tool: config.include_view_image_tool,
                 });
 
@@ -1231,6 +1409,12 @@ async fn submission_loop(
                             include_web_search_request: config.tools_web_search_request,
                             use_streamable_shell_tool: config
                                 .use_experimental_streamable_shell_tool,
+                            use_persistent_shell_tool: config.use_persistent_shell_tool,
+                            include_background_process_tools: config
+                                .include_background_process_tools,
+                            include_lsp_tools: !config.lsp_servers.is_empty(),
+                            include_code_search_tool: config.include_code_search_tool,
+                            include_repo_search_tool: config.repo_index.enabled,
                             include_view_image_tool: config.include_view_image_tool,
                         }),
                         user_instructions: turn_context.user_instructions.clone(),
@@ -1355,6 +1539,11 @@ async fn submission_loop(
             Op::Shutdown => {
                 info!("Shutting down Codex instance");
 
+                if let Some(shell) = &sess.persistent_shell {
+                    shell.shutdown().await;
+                }
+                sess.session_manager.stop_all_background_processes().await;
+
                 // Gracefully flush and shutdown rollout recorder on session end so tests
                 // that inspect the rollout file do not race with the background writer.
                 let recorder_opt = sess.rollout.lock_unchecked().take();
@@ -2006,7 +2195,7 @@ async fn handle_response_item(
                 }
             };
 
-            let exec_params = to_exec_params(params, turn_context);
+            let exec_params = to_exec_params(params, sess, turn_context);
             Some(
                 handle_container_exec_with_params(
                     exec_params,
@@ -2074,12 +2263,13 @@ async fn handle_function_call(
 ) -> ResponseInputItem {
     match name.as_str() {
         "container.exec" | "shell" => {
-            let params = match parse_container_exec_arguments(arguments, turn_context, &call_id) {
-                Ok(params) => params,
-                Err(output) => {
-                    return *output;
-                }
-            };
+            let params =
+                match parse_container_exec_arguments(arguments, sess, turn_context, &call_id) {
+                    Ok(params) => params,
+                    Err(output) => {
+                        return *output;
+                    }
+                };
             handle_container_exec_with_params(
                 params,
                 sess,
@@ -2170,7 +2360,9 @@ async fn handle_function_call(
                 .session_manager
                 .handle_exec_command_request(exec_params)
                 .await;
-            let function_call_output = crate::exec_command::result_into_payload(result);
+            let function_call_output = sess
+                .redactor
+                .redact_function_output(crate::exec_command::result_into_payload(result));
             ResponseInputItem::FunctionCallOutput {
                 call_id,
                 output: function_call_output,
@@ -2193,13 +2385,120 @@ async fn handle_function_call(
                 .session_manager
                 .handle_write_stdin_request(write_stdin_params)
                 .await;
-            let function_call_output: FunctionCallOutputPayload =
-                crate::exec_command::result_into_payload(result);
+            let function_call_output: FunctionCallOutputPayload = sess
+                .redactor
+                .redact_function_output(crate::exec_command::result_into_payload(result));
             ResponseInputItem::FunctionCallOutput {
                 call_id,
                 output: function_call_output,
             }
         }
+        DIAGNOSTICS_TOOL_NAME | FIND_DEFINITION_TOOL_NAME | FIND_REFERENCES_TOOL_NAME => {
+            let output = match sess
+                .lsp
+                .handle_tool_call(&turn_context.cwd, &name, &arguments)
+                .await
+            {
+                Ok(content) => FunctionCallOutputPayload {
+                    content,
+                    success: Some(true),
+                },
+                Err(content) => FunctionCallOutputPayload {
+                    content,
+                    success: Some(false),
+                },
+            };
+            ResponseInputItem::FunctionCallOutput {
+                call_id,
+                output: sess.redactor.redact_function_output(output),
+            }
+        }
+        REPO_SEARCH_TOOL_NAME => {
+            let result = match &sess.repo_index {
+                Some(repo_index) => repo_index.handle_repo_search(&arguments).await,
+                None => Err("repo_search is not enabled".to_string()),
+            };
+            let output = match result {
+                Ok(content) => FunctionCallOutputPayload {
+                    content,
+                    success: Some(true),
+                },
+                Err(content) => FunctionCallOutputPayload {
+                    content,
+                    success: Some(false),
+                },
+            };
+            ResponseInputItem::FunctionCallOutput {
+                call_id,
+                output: sess.redactor.redact_function_output(output),
+            }
+        }
+        CODE_SEARCH_TOOL_NAME => {
+            let output = match handle_code_search(&turn_context.cwd, &arguments).await {
+                Ok(content) => FunctionCallOutputPayload {
+                    content,
+                    success: Some(true),
+                },
+                Err(content) => FunctionCallOutputPayload {
+                    content,
+                    success: Some(false),
+                },
+            };
+            ResponseInputItem::FunctionCallOutput {
+                call_id,
+                output: sess.redactor.redact_function_output(output),
+            }
+        }
+        BACKGROUND_PROCESS_START_TOOL_NAME => {
+            handle_background_process_start(sess, turn_context, sub_id, call_id, arguments).await
+        }
+        BACKGROUND_PROCESS_LOGS_TOOL_NAME => {
+            let params = match serde_json::from_str::<BackgroundProcessLogsParams>(&arguments) {
+                Ok(params) => params,
+                Err(e) => {
+                    return ResponseInputItem::FunctionCallOutput {
+                        call_id,
+                        output: FunctionCallOutputPayload {
+                            content: format!("failed to parse function arguments: {e}"),
+                            success: Some(false),
+                        },
+                    };
+                }
+            };
+            let result = sess
+                .session_manager
+                .handle_background_process_logs_request(params);
+            ResponseInputItem::FunctionCallOutput {
+                call_id,
+                output: sess.redactor.redact_function_output(
+                    crate::exec_command::background_process_result_into_payload(result),
+                ),
+            }
+        }
+        BACKGROUND_PROCESS_STOP_TOOL_NAME => {
+            let params = match serde_json::from_str::<StopBackgroundProcessParams>(&arguments) {
+                Ok(params) => params,
+                Err(e) => {
+                    return ResponseInputItem::FunctionCallOutput {
+                        call_id,
+                        output: FunctionCallOutputPayload {
+                            content: format!("failed to parse function arguments: {e}"),
+                            success: Some(false),
+                        },
+                    };
+                }
+            };
+            let result = sess
+                .session_manager
+                .handle_stop_background_process_request(params)
+                .await;
+            ResponseInputItem::FunctionCallOutput {
+                call_id,
+                output: sess.redactor.redact_function_output(
+                    crate::exec_command::background_process_result_into_payload(result),
+                ),
+            }
+        }
         _ => {
             match sess.mcp_connection_manager.parse_tool_name(&name) {
                 Some((server, tool_name)) => {
@@ -2277,10 +2576,20 @@ async fn handle_custom_tool_call(
     }
 }
 
-fn to_exec_params(params: ShellToolCallParams, turn_context: &TurnContext) -> ExecParams {
+fn to_exec_params(
+    params: ShellToolCallParams,
+    sess: &Session,
+    turn_context: &TurnContext,
+) -> ExecParams {
+    // With a persistent shell, commands without an explicit `workdir` run
+    // wherever the previous command of this turn cwd left the shell.
+    let cwd = match (&sess.persistent_shell, &params.workdir) {
+        (Some(shell), None) => shell.cwd_for_turn(&turn_context.cwd),
+        _ => turn_context.resolve_path(params.workdir.clone()),
+    };
     ExecParams {
         command: params.command,
-        cwd: turn_context.resolve_path(params.workdir.clone()),
+        cwd,
         timeout_ms: params.timeout_ms,
         env: create_env(&turn_context.shell_environment_policy),
         with_escalated_permissions: params.with_escalated_permissions,
@@ -2290,12 +2599,15 @@ fn to_exec_params(params: ShellToolCallParams, turn_context: &TurnContext) -> Ex
 
 fn parse_container_exec_arguments(
     arguments: String,
+    sess: &Session,
     turn_context: &TurnContext,
     call_id: &str,
 ) -> Result<ExecParams, Box<ResponseInputItem>> {
     // parse command
     match serde_json::from_str::<ShellToolCallParams>(&arguments) {
-        Ok(shell_tool_call_params) => Ok(to_exec_params(shell_tool_call_params, turn_context)),
+        Ok(shell_tool_call_params) => {
+            Ok(to_exec_params(shell_tool_call_params, sess, turn_context))
+        }
         Err(e) => {
             // allow model to re-sample
             let output = ResponseInputItem::FunctionCallOutput {
@@ -2315,7 +2627,12 @@ pub struct ExecInvokeArgs<'a> {
     pub sandbox_type: SandboxType,
     pub sandbox_policy: &'a SandboxPolicy,
     pub codex_linux_sandbox_exe: &'a Option<PathBuf>,
+    pub seccomp: &'a Seccomp,
     pub stdout_stream: Option<StdoutStream>,
+    /// When set and the session has a persistent shell, the command runs in
+    /// that shell, sandboxed relative to this directory, instead of in a
+    /// fresh process.
+    pub persistent_shell_sandbox_cwd: Option<&'a Path>,
 }
 
 fn should_translate_shell_command(
@@ -2335,8 +2652,10 @@ fn maybe_translate_shell_command(
     sess: &Session,
     turn_context: &TurnContext,
 ) -> ExecParams {
-    let should_translate =
-        should_translate_shell_command(&sess.user_shell, &turn_context.shell_environment_policy);
+    // The persistent shell runs the command itself; wrapping it in the user's
+    // login shell would confine `cd`/`export` to a subshell.
+    let should_translate = sess.persistent_shell.is_none()
+        && should_translate_shell_command(&sess.user_shell, &turn_context.shell_environment_policy);
 
     if should_translate
         && let Some(command) = sess
@@ -2357,7 +2676,11 @@ async fn handle_container_exec_with_params(
     call_id: String,
 ) -> ResponseInputItem {
     // check if this was a patch, and apply it if so
-    let apply_patch_exec = match maybe_parse_apply_patch_verified(&params.command, &params.cwd) {
+    let apply_patch_exec = match maybe_parse_apply_patch_verified(
+        &params.command,
+        &params.cwd,
+        sess.apply_patch_match_options,
+    ) {
         MaybeApplyPatchVerified::Body(changes) => {
             match apply_patch::apply_patch(sess, turn_context, &sub_id, &call_id, changes).await {
                 InternalApplyPatchInvocation::Output(item) => return item,
@@ -2411,7 +2734,12 @@ async fn handle_container_exec_with_params(
                 ],
                 cwd: cwd.clone(),
                 timeout_ms: params.timeout_ms,
-                env: HashMap::new(),
+                // Make sure the patch is applied with the same matching rules
+                // that were used to verify it.
+                env: HashMap::from([(
+                    FUZZY_THRESHOLD_ENV_VAR.to_string(),
+                    sess.apply_patch_match_options.fuzzy_threshold.to_string(),
+                )]),
                 with_escalated_permissions: params.with_escalated_permissions,
                 justification: params.justification.clone(),
             };
@@ -2448,8 +2776,8 @@ async fn handle_container_exec_with_params(
         }
     };
 
-    let sandbox_type = match safety {
-        SafetyCheck::AutoApprove { sandbox_type } => sandbox_type,
+    let (sandbox_type, escalated) = match safety {
+        SafetyCheck::AutoApprove { sandbox_type } => (sandbox_type, false),
         SafetyCheck::AskUser => {
             let rx_approve = sess
                 .request_command_approval(
@@ -2479,7 +2807,8 @@ async fn handle_container_exec_with_params(
             // explicit approval. Often, we end up in this case because
             // the command cannot be run in a sandbox, such as
             // installing a new dependency that requires network access.
-            SandboxType::None
+            // Such commands also bypass the (sandboxed) persistent shell.
+            (SandboxType::None, true)
         }
         SafetyCheck::Reject { reason } => {
             return ResponseInputItem::FunctionCallOutput {
@@ -2509,6 +2838,13 @@ async fn handle_container_exec_with_params(
     };
 
     let params = maybe_translate_shell_command(params, sess, turn_context);
+    let in_persistent_shell = exec_command_context.apply_patch.is_none()
+        && !escalated
+        && sandbox_type == sandbox_type_for_policy(&turn_context.sandbox_policy);
+    if exec_command_context.apply_patch.is_none() {
+        sess.record_exec_env(turn_context, &call_id, &params.env, in_persistent_shell)
+            .await;
+    }
     let output_result = sess
         .run_exec_with_events(
             turn_diff_tracker,
@@ -2518,6 +2854,7 @@ async fn handle_container_exec_with_params(
                 sandbox_type,
                 sandbox_policy: &turn_context.sandbox_policy,
                 codex_linux_sandbox_exe: &sess.codex_linux_sandbox_exe,
+                seccomp: &sess.seccomp,
                 stdout_stream: if exec_command_context.apply_patch.is_some() {
                     None
                 } else {
@@ -2527,6 +2864,8 @@ async fn handle_container_exec_with_params(
                         tx_event: sess.tx_event.clone(),
                     })
                 },
+                persistent_shell_sandbox_cwd: in_persistent_shell
+                    .then_some(turn_context.cwd.as_path()),
             },
         )
         .await;
@@ -2536,7 +2875,10 @@ async fn handle_container_exec_with_params(
             let ExecToolCallOutput { exit_code, .. } = &output;
 
             let is_success = *exit_code == 0;
-            let content = format_exec_output(&output);
+            let content = sess
+                .redactor
+                .redact(&format_exec_output(&output))
+                .into_owned();
             ResponseInputItem::FunctionCallOutput {
                 call_id: call_id.clone(),
                 output: FunctionCallOutputPayload {
@@ -2567,6 +2909,101 @@ async fn handle_container_exec_with_params(
     }
 }
 
+/// Starts a background process after the same safety checks and approval flow
+/// as a regular `shell` call.
+async fn handle_background_process_start(
+    sess: &Session,
+    turn_context: &TurnContext,
+    sub_id: String,
+    call_id: String,
+    arguments: String,
+) -> ResponseInputItem {
+    let params = match serde_json::from_str::<StartBackgroundProcessParams>(&arguments) {
+        Ok(params) => params,
+        Err(e) => {
+            return ResponseInputItem::FunctionCallOutput {
+                call_id,
+                output: FunctionCallOutputPayload {
+                    content: format!("failed to parse function arguments: {e}"),
+                    success: Some(false),
+                },
+            };
+        }
+    };
+    let cwd = turn_context.resolve_path(params.workdir.clone());
+    let safety = {
+        let state = sess.state.lock_unchecked();
+        assess_command_safety(
+            &params.command,
+            turn_context.approval_policy,
+            &turn_context.sandbox_policy,
+            &state.approved_commands,
+            params.with_escalated_permissions.unwrap_or(false),
+        )
+    };
+    let sandbox_type = match safety {
+        SafetyCheck::AutoApprove { sandbox_type } => sandbox_type,
+        SafetyCheck::AskUser => {
+            let rx_approve = sess
+                .request_command_approval(
+                    sub_id,
+                    call_id.clone(),
+                    params.command.clone(),
+                    cwd.clone(),
+                    params.justification.clone(),
+                )
+                .await;
+            match rx_approve.await.unwrap_or_default() {
+                ReviewDecision::Approved => (),
+                ReviewDecision::ApprovedForSession => {
+                    sess.add_approved_command(params.command.clone());
+                }
+                ReviewDecision::Denied | ReviewDecision::Abort => {
+                    return ResponseInputItem::FunctionCallOutput {
+                        call_id,
+                        output: FunctionCallOutputPayload {
+                            content: "background process rejected by user".to_string(),
+                            success: None,
+                        },
+                    };
+                }
+            }
+            SandboxType::None
+        }
+        SafetyCheck::Reject { reason } => {
+            return ResponseInputItem::FunctionCallOutput {
+                call_id,
+                output: FunctionCallOutputPayload {
+                    content: format!("background process rejected: {reason}"),
+                    success: None,
+                },
+            };
+        }
+    };
+
+    let env = create_env(&turn_context.shell_environment_policy);
+    sess.record_exec_env(turn_context, &call_id, &env, false)
+        .await;
+    let result = sess
+        .session_manager
+        .handle_start_background_process_request(
+            params,
+            turn_context.cwd.clone(),
+            env,
+            sandbox_type,
+            &turn_context.sandbox_policy,
+            &sess.codex_linux_sandbox_exe,
+            &sess.seccomp,
+        )
+        .await;
+    ResponseInputItem::FunctionCallOutput {
+        call_id,
+        output: sess.redactor.redact_function_output(
+            crate::exec_command::background_process_result_into_payload(result),
+        ),
+    }
+}
+
 async fn handle_sandbox_error(
     turn_diff_tracker: &mut TurnDiffTracker,
     params: ExecParams,
@@ -2655,6 +3092,7 @@ async fn handle_sandbox_error(
                         sandbox_type: SandboxType::None,
                         sandbox_policy: &turn_context.sandbox_policy,
                         codex_linux_sandbox_exe: &sess.codex_linux_sandbox_exe,
+                        seccomp: &sess.seccomp,
                         stdout_stream: if exec_command_context.apply_patch.is_some() {
                             None
                         } else {
@@ -2664,6 +3102,7 @@ async fn handle_sandbox_error(
                                 tx_event: sess.tx_event.clone(),
                             })
                         },
+                        persistent_shell_sandbox_cwd: None,
                     },
                 )
                 .await;
@@ -2673,7 +3112,10 @@ async fn handle_sandbox_error(
                     let ExecToolCallOutput { exit_code, .. } = &retry_output;
 
                     let is_success = *exit_code == 0;
-                    let content = format_exec_output(&retry_output);
+                    let content = sess
+                        .redactor
+                        .redact(&format_exec_output(&retry_output))
+                        .into_owned();
 
                     ResponseInputItem::FunctionCallOutput {
                         call_id: call_id.clone(),
@@ -2980,6 +3422,7 @@ mod tests {
             r#set: HashMap::new(),
             include_only: Vec::new(),
             use_profile,
+            record_in_rollout: false,
         }
     }
 
@@ -3166,4 +3609,72 @@ mod tests {
 
         assert_eq!(expected, got);
     }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn post_edit_reports_cover_moved_and_created_files() {
+        use crate::config_types::PostEditCommand;
+        use std::collections::BTreeMap;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let cwd = dir.path();
+        std::fs::write(cwd.join("new.txt"), "added\n").unwrap();
+        std::fs::write(cwd.join("moved.txt"), "moved\n").unwrap();
+        std::fs::write(cwd.join("tidy.txt"), "TIDY\n").unwrap();
+        let formatters = BTreeMap::from([(
+            "upper".to_string(),
+            PostEditCommand {
+                files: vec!["*.txt".to_string()],
+                command: vec![
+                    "sh".to_string(),
+                    "-c".to_string(),
+                    r#"for f; do tr a-z A-Z < "$f" > "$f.tmp" && mv "$f.tmp" "$f"; done"#
+                        .to_string(),
+                    "sh".to_string(),
+                ],
+                timeout_ms: None,
+            },
+        )]);
+        let post_edit = PostEditHooks::new(&formatters, &BTreeMap::new());
+        let lsp = LspManager::new(cwd.to_path_buf(), &BTreeMap::new());
+        let changes = HashMap::from([
+            (
+                cwd.join("new.txt"),
+                FileChange::Add {
+                    content: "added\n".to_string(),
+                },
+            ),
+            (
+                cwd.join("old.txt"),
+                FileChange::Update {
+                    unified_diff: String::new(),
+                    move_path: Some(cwd.join("moved.txt")),
+                },
+            ),
+            (
+                cwd.join("tidy.txt"),
+                FileChange::Update {
+                    unified_diff: String::new(),
+                    move_path: None,
+                },
+            ),
+        ]);
+        let mut output = ExecToolCallOutput {
+            exit_code: 0,
+            stdout: StreamOutput::new("Success.\n".to_string()),
+            stderr: StreamOutput::new(String::new()),
+            aggregated_output: StreamOutput::new("Success.\n".to_string()),
+            duration: StdDuration::ZERO,
+        };
+
+        append_post_edit_reports(&post_edit, &lsp, cwd, &changes, &mut output).await;
+
+        let expected = "Success.\n\nformatted: moved.txt, new.txt\n";
+        assert_eq!(output.stdout.text, expected);
+        assert_eq!(output.aggregated_output.text, expected);
+        assert_eq!(
+            std::fs::read_to_string(cwd.join("moved.txt")).unwrap(),
+            "MOVED\n"
+        );
+    }
 }
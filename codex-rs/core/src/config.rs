@@ -1,8 +1,13 @@
 use crate::config_profile::ConfigProfile;
 use crate::config_types::History;
+use crate::config_types::LspServerConfig;
 use crate::config_types::McpServerConfig;
+use crate::config_types::PostEditCommand;
 use crate::config_types::ReasoningSummaryFormat;
+use crate::config_types::Redaction;
+use crate::config_types::RepoIndexConfig;
 use crate::config_types::SandboxWorkspaceWrite;
+use crate::config_types::Seccomp;
 use crate::config_types::ShellEnvironmentPolicy;
 use crate::config_types::ShellEnvironmentPolicyToml;
 use crate::config_types::Tui;
@@ -24,6 +29,7 @@ use codex_protocol::mcp_protocol::Tools;
 use codex_protocol::mcp_protocol::UserSavedConfig;
 use dirs::home_dir;
 use serde::Deserialize;
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::path::Path;
 use std::path::PathBuf;
@@ -125,6 +131,31 @@ pub struct Config {
     /// Settings that govern if and what will be written to `~/.codex/history.jsonl`.
     pub history: History,
 
+    /// Secret redaction applied to tool output and rollout files.
+    pub redaction: Redaction,
+
+    /// Extra seccomp restrictions applied by the Linux sandbox.
+    pub seccomp: Seccomp,
+
+    /// Minimum similarity for `apply_patch` to accept a region of a file as
+    /// the location of a hunk that does not match exactly. `1.0` disables
+    /// fuzzy matching.
+    pub apply_patch_fuzzy_threshold: f64,
+
+    /// Formatters run, by name, on the files touched by `apply_patch`.
+    pub formatters: BTreeMap<String, PostEditCommand>,
+
+    /// Linters run, by name, on the files touched by `apply_patch` after the
+    /// formatters. Their diagnostics are appended to the patch output.
+    pub linters: BTreeMap<String, PostEditCommand>,
+
+    /// Language servers backing the `diagnostics`, `find_definition` and
+    /// `find_references` tools, keyed by name.
+    pub lsp_servers: BTreeMap<String, LspServerConfig>,
+
+    /// The repository index behind the `repo_search` tool.
+    pub repo_index: RepoIndexConfig,
+
     /// Optional URI-based file opener. If set, citations to files in the model
     /// output will be hyperlinked using the specified URI scheme.
     pub file_opener: UriBasedFileOpener,
@@ -174,6 +205,15 @@ pub struct Config {
 
     /// Include the `view_image` tool that lets the agent attach a local image path to context.
     pub include_view_image_tool: bool,
+
+    /// Run `shell` tool calls in a persistent shell session (Unix only).
+    pub use_persistent_shell_tool: bool,
+
+    /// Include the `background_process_*` tools.
+    pub include_background_process_tools: bool,
+
+    /// Include the tree-sitter backed `code_search` tool.
+    pub include_code_search_tool: bool,
     /// When true, disables burst-paste detection for typed input entirely.
     /// All characters are inserted as they are received, and no buffering
     /// or placeholder replacement will occur for fast keypress bursts.
@@ -401,6 +441,15 @@ pub struct ConfigToml {
     #[serde(default)]
     pub shell_environment_policy: ShellEnvironmentPolicyToml,
 
+    /// Named alternatives to `shell_environment_policy`, selected with
+    /// `shell_environment_preset` (usually from a profile).
+    #[serde(default)]
+    pub shell_environment_presets: HashMap<String, ShellEnvironmentPolicyToml>,
+
+    /// Name of the entry in `shell_environment_presets` to use. A profile's
+    /// `shell_environment_preset` takes precedence.
+    pub shell_environment_preset: Option<String>,
+
     /// Sandbox mode to use.
     pub sandbox_mode: Option<SandboxMode>,
 
@@ -436,6 +485,33 @@ pub struct ConfigToml {
     #[serde(default)]
     pub history: Option<History>,
 
+    /// Secret redaction applied to tool output and rollout files.
+    #[serde(default)]
+    pub redaction: Option<Redaction>,
+
+    /// Extra seccomp restrictions applied by the Linux sandbox.
+    #[serde(default)]
+    pub seccomp: Option<Seccomp>,
+
+    /// Minimum similarity (0.0 to 1.0) for fuzzy hunk matching in
+    /// `apply_patch`. Defaults to 0.9; `1.0` disables fuzzy matching.
+    pub apply_patch_fuzzy_threshold: Option<f64>,
+
+    /// Formatters to run on the files touched by `apply_patch`, keyed by name.
+    #[serde(default)]
+    pub formatters: BTreeMap<String, PostEditCommand>,
+
+    /// Linters to run on the files touched by `apply_patch`, keyed by name.
+    #[serde(default)]
+    pub linters: BTreeMap<String, PostEditCommand>,
+
+    /// Language servers to start on demand, keyed by name.
+    #[serde(default)]
+    pub lsp_servers: BTreeMap<String, LspServerConfig>,
+
+    /// Settings for the repository index behind the `repo_search` tool.
+    pub repo_index: Option<RepoIndexConfig>,
+
     /// Optional URI-based file opener. If set, citations to files in the model
     /// output will be hyperlinked using the specified URI scheme.
     pub file_opener: Option<UriBasedFileOpener>,
@@ -523,6 +599,22 @@ pub struct ToolsToml {
     /// Enable the `view_image` tool that lets the agent attach local images.
     #[serde(default)]
     pub view_image: Option<bool>,
+
+    /// Back the `shell` tool with one long-lived shell per conversation so
+    /// that the working directory and exported variables persist across calls.
+    /// Defaults to `true`; set to `false` to run every call in a fresh process.
+    #[serde(default)]
+    pub persistent_shell: Option<bool>,
+
+    /// Enable tools to start, tail and stop long-running background
+    /// processes such as dev servers.
+    #[serde(default)]
+    pub background_processes: Option<bool>,
+
+    /// Enable the `code_search` tool for structural navigation of source
+    /// files.
+    #[serde(default)]
+    pub code_search: Option<bool>,
 }
 
 impl From<ToolsToml> for Tools {
@@ -530,6 +622,9 @@ impl From<ToolsToml> for Tools {
         Self {
             web_search: tools_toml.web_search,
             view_image: tools_toml.view_image,
+            persistent_shell: tools_toml.persistent_shell,
+            background_processes: tools_toml.background_processes,
+            code_search: tools_toml.code_search,
         }
     }
 }
@@ -689,7 +784,24 @@ impl Config {
             })?
             .clone();
 
-        let shell_environment_policy = cfg.shell_environment_policy.into();
+        let shell_environment_policy = match config_profile
+            .shell_environment_preset
+            .as_ref()
+            .or(cfg.shell_environment_preset.as_ref())
+        {
+            Some(name) => cfg
+                .shell_environment_presets
+                .get(name)
+                .ok_or_else(|| {
+                    std::io::Error::new(
+                        std::io::ErrorKind::NotFound,
+                        format!("shell environment preset `{name}` not found"),
+                    )
+                })?
+                .clone()
+                .into(),
+            None => cfg.shell_environment_policy.into(),
+        };
 
         let resolved_cwd = {
             use std::env;
@@ -712,6 +824,51 @@ impl Config {
 
         let history = cfg.history.unwrap_or_default();
 
+        let redaction = cfg.redaction.unwrap_or_default();
+        if let Err(e) = crate::redact::Redactor::new(&redaction) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("invalid redaction pattern: {e}"),
+            ));
+        }
+
+        let seccomp = cfg.seccomp.unwrap_or_default();
+        if let Err(e) = crate::landlock::seccomp_denied_syscalls(&seccomp) {
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, e));
+        }
+
+        let apply_patch_fuzzy_threshold = cfg
+            .apply_patch_fuzzy_threshold
+            .unwrap_or(codex_apply_patch::DEFAULT_FUZZY_THRESHOLD);
+        if !(0.0..=1.0).contains(&apply_patch_fuzzy_threshold) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!(
+                    "apply_patch_fuzzy_threshold must be between 0.0 and 1.0, got {apply_patch_fuzzy_threshold}"
+                ),
+            ));
+        }
+
+        for (kind, commands) in [("formatters", &cfg.formatters), ("linters", &cfg.linters)] {
+            for (name, command) in commands {
+                if command.command.is_empty() || command.files.is_empty() {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        format!("{kind}.{name} must set a non-empty `command` and `files`"),
+                    ));
+                }
+            }
+        }
+
+        for (name, server) in &cfg.lsp_servers {
+            if server.files.is_empty() {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("lsp_servers.{name} must set a non-empty `files`"),
+                ));
+            }
+        }
+
         let tools_web_search_request = override_tools_web_search_request
             .or(cfg.tools.as_ref().and_then(|t| t.web_search))
             .unwrap_or(false);
@@ -787,6 +944,13 @@ impl Config {
             project_doc_max_bytes: cfg.project_doc_max_bytes.unwrap_or(PROJECT_DOC_MAX_BYTES),
             codex_home,
             history,
+            redaction,
+            seccomp,
+            apply_patch_fuzzy_threshold,
+            formatters: cfg.formatters,
+            linters: cfg.linters,
+            lsp_servers: cfg.lsp_servers,
+            repo_index: cfg.repo_index.unwrap_or_default(),
             file_opener: cfg.file_opener.unwrap_or(UriBasedFileOpener::VsCode),
             tui: cfg.tui.unwrap_or_default(),
             codex_linux_sandbox_exe,
@@ -819,6 +983,21 @@ impl Config {
                 .experimental_use_exec_command_tool
                 .unwrap_or(false),
             include_view_image_tool,
+            use_persistent_shell_tool: cfg
+                .tools
+                .as_ref()
+                .and_then(|t| t.persistent_shell)
+                .unwrap_or(true),
+            include_background_process_tools: cfg
+                .tools
+                .as_ref()
+                .and_then(|t| t.background_processes)
+                .unwrap_or(false),
+            include_code_search_tool: cfg
+                .tools
+                .as_ref()
+                .and_then(|t| t.code_search)
+                .unwrap_or(false),
             disable_paste_burst: cfg.disable_paste_burst.unwrap_or(false),
         };
         Ok(config)
@@ -925,7 +1104,15 @@ pub fn log_dir(cfg: &Config) -> std::io::Result<PathBuf> {
 
 #[cfg(test)]
 mod tests {
+    use crate::config_types::ColorSpec;
     use crate::config_types::HistoryPersistence;
+    use crate::config_types::KeyAction;
+    use crate::config_types::KeyCodeSpec;
+    use crate::config_types::KeySpec;
+    use crate::config_types::ShellEnvironmentPolicyInherit;
+    use crate::config_types::StyleSpec;
+    use crate::config_types::SubmitMode;
+    use crate::config_types::ThemeName;
 
     use super::*;
     use pretty_assertions::assert_eq;
@@ -1019,6 +1206,121 @@ exclude_slash_tmp = true
         );
     }
 
+    #[test]
+    fn test_tui_theme_parsing() {
+        let cfg = toml::from_str::<ConfigToml>(
+            r##"
+[tui]
+theme = "colorblind-safe"
+
+[tui.colors]
+diff_add = "bold blue on #102030"
+secondary = "dark-gray italic"
+"##,
+        )
+        .expect("TOML deserialization should succeed");
+        let tui = cfg.tui.expect("tui table");
+        assert_eq!(tui.theme, ThemeName::ColorblindSafe);
+        assert_eq!(
+            tui.colors.diff_add,
+            Some(StyleSpec {
+                fg: Some(ColorSpec::Blue),
+                bg: Some(ColorSpec::Rgb(0x10, 0x20, 0x30)),
+                bold: true,
+                ..StyleSpec::default()
+            })
+        );
+        assert_eq!(
+            tui.colors.secondary,
+            Some(StyleSpec {
+                fg: Some(ColorSpec::DarkGray),
+                italic: true,
+                ..StyleSpec::default()
+            })
+        );
+
+        for invalid in [
+            "[tui.colors]\ndiff_addd = \"green\"",
+            "[tui.colors]\ndiff_add = \"greenish\"",
+            "[tui.colors]\ndiff_add = \"green red\"",
+            "[tui.colors]\ndiff_add = \"green on\"",
+            "[tui.colors]\ndiff_add = \"#12345\"",
+        ] {
+            assert!(
+                toml::from_str::<ConfigToml>(invalid).is_err(),
+                "expected {invalid:?} to be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn test_tui_keybindings_parsing() {
+        let cfg = toml::from_str::<ConfigToml>(
+            r#"
+[tui.keybindings]
+submit_mode = "ctrl-enter"
+history_previous = "ctrl-p"
+history_next = ["ctrl-n", "Down"]
+kill_line_end = []
+"#,
+        )
+        .expect("TOML deserialization should succeed");
+        let bindings = cfg.tui.expect("tui table").keybindings;
+        assert_eq!(bindings.submit_mode, SubmitMode::CtrlEnter);
+        assert_eq!(
+            bindings.keys(KeyAction::Submit),
+            vec![
+                KeySpec::new(KeyCodeSpec::Enter).ctrl(),
+                KeySpec::new(KeyCodeSpec::Enter).alt(),
+            ]
+        );
+        assert_eq!(
+            bindings.keys(KeyAction::HistoryPrevious),
+            vec![KeySpec::new(KeyCodeSpec::Char('p')).ctrl()]
+        );
+        assert_eq!(
+            bindings.keys(KeyAction::HistoryNext),
+            vec![
+                KeySpec::new(KeyCodeSpec::Char('n')).ctrl(),
+                KeySpec::new(KeyCodeSpec::Down),
+            ]
+        );
+        assert_eq!(bindings.keys(KeyAction::KillLineEnd), vec![]);
+        assert_eq!(
+            bindings.keys(KeyAction::Transcript),
+            vec![KeySpec::new(KeyCodeSpec::Char('t')).ctrl()]
+        );
+
+        for (key, expected) in [
+            ("Ctrl+Shift+Tab", "ctrl-shift-tab"),
+            ("ctrl-X", "ctrl-x"),
+            ("alt--", "alt--"),
+            ("G", "G"),
+            ("shift-g", "G"),
+            ("space", "space"),
+            ("F5", "f5"),
+            ("pgdn", "pagedown"),
+        ] {
+            let parsed: KeySpec = key.parse().expect("valid key");
+            assert_eq!(parsed.to_string(), expected);
+        }
+
+        for invalid in [
+            "[tui.keybindings]\nsubmitt = \"enter\"",
+            "[tui.keybindings]\nsubmit = \"hyper-enter\"",
+            "[tui.keybindings]\nsubmit = \"ctrl-\"",
+            "[tui.keybindings]\nnewline = \"enter\"",
+            "[tui.keybindings]\nsubmit_mode = \"ctrl-enter\"\nsubmit = \"enter\"",
+            "[tui.keybindings]\nscroll_up = \"q\"",
+            "[tui.keybindings]\nword_left = \"b\"",
+        ] {
+            assert!(
+                toml::from_str::<ConfigToml>(invalid).is_err(),
+                "expected {invalid:?} to be rejected"
+            );
+        }
+    }
+
     struct PrecedenceTestFixture {
         cwd: TempDir,
         codex_home: TempDir,
@@ -1176,6 +1478,13 @@ model_verbosity = "high"
                 project_doc_max_bytes: PROJECT_DOC_MAX_BYTES,
                 codex_home: fixture.codex_home(),
                 history: History::default(),
+                redaction: Redaction::default(),
+                seccomp: Seccomp::default(),
+                apply_patch_fuzzy_threshold: codex_apply_patch::DEFAULT_FUZZY_THRESHOLD,
+                formatters: BTreeMap::new(),
+                linters: BTreeMap::new(),
+                lsp_servers: BTreeMap::new(),
+                repo_index: RepoIndexConfig::default(),
                 file_opener: UriBasedFileOpener::VsCode,
                 tui: Tui::default(),
                 codex_linux_sandbox_exe: None,
@@ -1193,6 +1502,9 @@ model_verbosity = "high"
                 preferred_auth_method: AuthMode::ChatGPT,
                 use_experimental_streamable_shell_tool: false,
                 include_view_image_tool: true,
+                use_persistent_shell_tool: true,
+                include_background_process_tools: false,
+                include_code_search_tool: false,
                 disable_paste_burst: false,
             },
             o3_profile_config
@@ -1232,6 +1544,13 @@ model_verbosity = "high"
             project_doc_max_bytes: PROJECT_DOC_MAX_BYTES,
             codex_home: fixture.codex_home(),
             history: History::default(),
+            redaction: Redaction::default(),
+            seccomp: Seccomp::default(),
+            apply_patch_fuzzy_threshold: codex_apply_patch::DEFAULT_FUZZY_THRESHOLD,
+            formatters: BTreeMap::new(),
+            linters: BTreeMap::new(),
+            lsp_servers: BTreeMap::new(),
+            repo_index: RepoIndexConfig::default(),
             file_opener: UriBasedFileOpener::VsCode,
             tui: Tui::default(),
             codex_linux_sandbox_exe: None,
@@ -1249,6 +1568,9 @@ model_verbosity = "high"
             preferred_auth_method: AuthMode::ChatGPT,
             use_experimental_streamable_shell_tool: false,
             include_view_image_tool: true,
+            use_persistent_shell_tool: true,
+            include_background_process_tools: false,
+            include_code_search_tool: false,
             disable_paste_burst: false,
         };
 
@@ -1303,6 +1625,13 @@ model_verbosity = "high"
             project_doc_max_bytes: PROJECT_DOC_MAX_BYTES,
             codex_home: fixture.codex_home(),
             history: History::default(),
+            redaction: Redaction::default(),
+            seccomp: Seccomp::default(),
+            apply_patch_fuzzy_threshold: codex_apply_patch::DEFAULT_FUZZY_THRESHOLD,
+            formatters: BTreeMap::new(),
+            linters: BTreeMap::new(),
+            lsp_servers: BTreeMap::new(),
+            repo_index: RepoIndexConfig::default(),
             file_opener: UriBasedFileOpener::VsCode,
             tui: Tui::default(),
             codex_linux_sandbox_exe: None,
@@ -1320,6 +1649,9 @@ model_verbosity = "high"
             preferred_auth_method: AuthMode::ChatGPT,
             use_experimental_streamable_shell_tool: false,
             include_view_image_tool: true,
+            use_persistent_shell_tool: true,
+            include_background_process_tools: false,
+            include_code_search_tool: false,
             disable_paste_burst: false,
         };
 
@@ -1360,6 +1692,13 @@ model_verbosity = "high"
             project_doc_max_bytes: PROJECT_DOC_MAX_BYTES,
             codex_home: fixture.codex_home(),
             history: History::default(),
+            redaction: Redaction::default(),
+            seccomp: Seccomp::default(),
+            apply_patch_fuzzy_threshold: codex_apply_patch::DEFAULT_FUZZY_THRESHOLD,
+            formatters: BTreeMap::new(),
+            linters: BTreeMap::new(),
+            lsp_servers: BTreeMap::new(),
+            repo_index: RepoIndexConfig::default(),
             file_opener: UriBasedFileOpener::VsCode,
             tui: Tui::default(),
             codex_linux_sandbox_exe: None,
@@ -1377,6 +1716,9 @@ model_verbosity = "high"
             preferred_auth_method: AuthMode::ChatGPT,
             use_experimental_streamable_shell_tool: false,
             include_view_image_tool: true,
+            use_persistent_shell_tool: true,
+            include_background_process_tools: false,
+            include_code_search_tool: false,
             disable_paste_burst: false,
         };
 
@@ -1453,5 +1795,84 @@ trust_level = "trusted"
         Ok(())
     }
 
+    #[test]
+    fn test_persistent_shell_is_on_unless_disabled() -> std::io::Result<()> {
+        let codex_home = TempDir::new()?;
+        let load = |toml: &str| {
+            let cfg: ConfigToml =
+                toml::from_str(toml).expect("TOML deserialization should succeed");
+            Config::load_from_base_config_with_overrides(
+                cfg,
+                ConfigOverrides::default(),
+                codex_home.path().to_path_buf(),
+            )
+        };
+
+        assert!(load("")?.use_persistent_shell_tool);
+        assert!(
+            !load(
+                r#"
+[tools]
+persistent_shell = false
+"#
+            )?
+            .use_persistent_shell_tool
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_shell_environment_preset_selected_by_profile() -> std::io::Result<()> {
+        let toml = r#"
+[shell_environment_policy]
+inherit = "all"
+
+[shell_environment_presets.minimal]
+inherit = "none"
+set = { LANG = "C.UTF-8" }
+record_in_rollout = true
+
+[profiles.locked-down]
+shell_environment_preset = "minimal"
+
+[profiles.missing]
+shell_environment_preset = "does-not-exist"
+"#;
+        let cfg: ConfigToml = toml::from_str(toml).expect("TOML deserialization should succeed");
+        let cwd = TempDir::new()?;
+        let codex_home = TempDir::new()?;
+        let load = |profile: Option<&str>| {
+            Config::load_from_base_config_with_overrides(
+                cfg.clone(),
+                ConfigOverrides {
+                    config_profile: profile.map(str::to_string),
+                    cwd: Some(cwd.path().to_path_buf()),
+                    ..Default::default()
+                },
+                codex_home.path().to_path_buf(),
+            )
+        };
+
+        let default_config = load(None)?;
+        assert_eq!(
+            default_config.shell_environment_policy.inherit,
+            ShellEnvironmentPolicyInherit::All
+        );
+        assert!(!default_config.shell_environment_policy.record_in_rollout);
+
+        let preset_config = load(Some("locked-down"))?;
+        let policy = preset_config.shell_environment_policy;
+        assert_eq!(policy.inherit, ShellEnvironmentPolicyInherit::None);
+        assert_eq!(
+            policy.r#set,
+            HashMap::from([("LANG".to_string(), "C.UTF-8".to_string())])
+        );
+        assert!(policy.record_in_rollout);
+
+        let err = load(Some("missing")).expect_err("unknown preset should fail");
+        assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+        Ok(())
+    }
+
     // No test enforcing the presence of a standalone [projects] header.
 }
@@ -20,6 +20,9 @@ pub struct ConfigProfile {
     pub model_verbosity: Option<Verbosity>,
     pub chatgpt_base_url: Option<String>,
     pub experimental_instructions_file: Option<PathBuf>,
+    /// Name of an entry in `shell_environment_presets` to use instead of the
+    /// top-level `shell_environment_policy`.
+    pub shell_environment_preset: Option<String>,
 }
 
 impl From<ConfigProfile> for codex_protocol::mcp_protocol::Profile {
@@ -3,11 +3,13 @@
 // Note this file should generally be restricted to simple struct/enum
 // definitions that do not contain business logic.
 
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::path::PathBuf;
 use wildmatch::WildMatchPattern;
 
 use serde::Deserialize;
+use strum_macros::Display as DeriveDisplay;
 
 #[derive(Deserialize, Debug, Clone, PartialEq)]
 pub struct McpServerConfig {
@@ -76,9 +78,733 @@ pub enum HistoryPersistence {
     None,
 }
 
+/// A language server that Codex starts, on first use, for the session's
+/// working directory.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct LspServerConfig {
+    pub command: String,
+
+    #[serde(default)]
+    pub args: Vec<String>,
+
+    #[serde(default)]
+    pub env: Option<HashMap<String, String>>,
+
+    /// Glob patterns selecting the files this server handles, e.g. `*.rs`.
+    pub files: Vec<String>,
+
+    /// The `languageId` sent when opening a document. Derived from the file
+    /// extension when unset.
+    #[serde(default)]
+    pub language_id: Option<String>,
+
+    /// How long to wait for diagnostics after a file changes. Defaults to
+    /// 5 seconds.
+    #[serde(default)]
+    pub diagnostics_timeout_ms: Option<u64>,
+}
+
+/// Settings for the local repository index behind the `repo_search` tool.
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct RepoIndexConfig {
+    /// Index the working directory and expose the `repo_search` tool.
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Number of top-ranked files listed, with their summaries, in the
+    /// context of a new conversation. `0` lists none.
+    #[serde(default)]
+    pub context_files: usize,
+}
+
+/// A formatter or linter that runs on the files touched by `apply_patch`.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct PostEditCommand {
+    /// Glob patterns selecting the files to run on, e.g. `*.rs`. Patterns
+    /// without a `/` match the file name; others match the path relative to
+    /// the session's working directory.
+    pub files: Vec<String>,
+
+    /// Program and arguments. The matching files are appended as further
+    /// arguments.
+    pub command: Vec<String>,
+
+    /// Kill the command if it runs longer than this. Defaults to 30 seconds.
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+}
+
+/// Settings that control how secrets are scrubbed from tool output and
+/// rollout files.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct Redaction {
+    /// Apply the built-in detectors (cloud keys, API tokens, private keys).
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// Also redact long random-looking tokens that no built-in detector
+    /// recognizes. Off by default because it also hits hashes, base64 data
+    /// and generated identifiers. Ignored when `enabled` is false.
+    #[serde(default)]
+    pub high_entropy: bool,
+
+    /// Additional regular expressions to redact. These apply even when the
+    /// built-in detectors are disabled. If a pattern has a capture group, only
+    /// the first group is redacted.
+    #[serde(default)]
+    pub patterns: Vec<String>,
+}
+
+impl Default for Redaction {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            high_entropy: false,
+            patterns: Vec::new(),
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Extra seccomp restrictions applied by the Linux sandbox on top of the
+/// network filter. Ignored on other platforms.
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct Seccomp {
+    /// Named set of syscalls to deny.
+    #[serde(default)]
+    pub profile: SeccompProfile,
+
+    /// Additional syscalls to deny, by name. Only the syscalls listed in
+    /// [`crate::landlock::DENYABLE_SYSCALLS`] are accepted.
+    #[serde(default)]
+    pub deny: Vec<String>,
+}
+
+#[derive(Deserialize, Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum SeccompProfile {
+    /// Only network access (and `ptrace`) is restricted.
+    #[default]
+    Default,
+
+    /// Additionally deny syscalls that are rarely needed by build tools but
+    /// widen the kernel attack surface: mounts and namespaces, the kernel
+    /// keyring, BPF, perf events, io_uring and `personality`.
+    Hardened,
+}
+
 /// Collection of settings that are specific to the TUI.
 #[derive(Deserialize, Debug, Clone, PartialEq, Default)]
-pub struct Tui {}
+pub struct Tui {
+    /// How file diffs are laid out in patch approvals and the `/diff` pager.
+    #[serde(default)]
+    pub diff_layout: DiffLayout,
+
+    /// Built-in color scheme.
+    #[serde(default)]
+    pub theme: ThemeName,
+
+    /// Per-element overrides of the styles of `theme`.
+    #[serde(default)]
+    pub colors: ThemeColors,
+
+    /// Keys bound to TUI actions.
+    #[serde(default)]
+    pub keybindings: KeyBindings,
+
+    /// How keys typed into the composer edit the message.
+    #[serde(default)]
+    pub editing_mode: EditingMode,
+}
+
+#[derive(Deserialize, Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum DiffLayout {
+    /// Removed and added lines interleaved in a single column.
+    #[default]
+    Unified,
+
+    /// Old and new versions of each hunk next to each other. Falls back to
+    /// the unified layout when the terminal is too narrow.
+    SideBySide,
+}
+
+#[derive(Deserialize, Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum EditingMode {
+    /// Keys insert text; movement and deletion use the key bindings.
+    #[default]
+    Emacs,
+
+    /// Modal editing with insert, normal and visual modes, as in vi.
+    Vi,
+}
+
+#[derive(Deserialize, Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum ThemeName {
+    /// ANSI colors tuned for dark terminal backgrounds.
+    #[default]
+    Dark,
+
+    /// Darker accents and no faint text, for light terminal backgrounds.
+    Light,
+
+    /// Bold colors and no faint text.
+    HighContrast,
+
+    /// Blue and yellow instead of green and red, which are hard to tell
+    /// apart with the most common forms of color blindness.
+    ColorblindSafe,
+}
+
+/// Styles of individual TUI elements. Unset elements keep the style of the
+/// selected theme.
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(deny_unknown_fields)]
+pub struct ThemeColors {
+    /// Key hints, selections and status indicators.
+    pub accent: Option<StyleSpec>,
+    pub success: Option<StyleSpec>,
+    pub error: Option<StyleSpec>,
+    /// Codex's own messages.
+    pub brand: Option<StyleSpec>,
+    /// Secondary text such as metadata and explanations.
+    pub secondary: Option<StyleSpec>,
+    pub diff_add: Option<StyleSpec>,
+    pub diff_delete: Option<StyleSpec>,
+    /// Line numbers of diffs.
+    pub diff_gutter: Option<StyleSpec>,
+    pub syntax_keyword: Option<StyleSpec>,
+    pub syntax_string: Option<StyleSpec>,
+    pub syntax_comment: Option<StyleSpec>,
+    /// Numbers, booleans and other constants.
+    pub syntax_constant: Option<StyleSpec>,
+    pub syntax_type: Option<StyleSpec>,
+    pub syntax_attribute: Option<StyleSpec>,
+}
+
+/// A text style written as space-separated words: at most one foreground
+/// color, `on <color>` for the background and any of `bold`, `dim`,
+/// `italic`, `underlined` and `reversed`, e.g. `"bold blue"` or
+/// `"black on #d7ffd7"`.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+#[serde(try_from = "String")]
+pub struct StyleSpec {
+    pub fg: Option<ColorSpec>,
+    pub bg: Option<ColorSpec>,
+    pub bold: bool,
+    pub dim: bool,
+    pub italic: bool,
+    pub underlined: bool,
+    pub reversed: bool,
+}
+
+/// One of the 16 ANSI colors, the terminal's default color, or an RGB color
+/// written as `#rrggbb`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ColorSpec {
+    Default,
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    Gray,
+    DarkGray,
+    LightRed,
+    LightGreen,
+    LightYellow,
+    LightBlue,
+    LightMagenta,
+    LightCyan,
+    White,
+    Rgb(u8, u8, u8),
+}
+
+impl std::str::FromStr for ColorSpec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(hex) = s.strip_prefix('#') {
+            let channel = |i: usize| {
+                hex.get(i..i + 2)
+                    .and_then(|c| u8::from_str_radix(c, 16).ok())
+            };
+            return match (hex.len(), channel(0), channel(2), channel(4)) {
+                (6, Some(r), Some(g), Some(b)) => Ok(Self::Rgb(r, g, b)),
+                _ => Err(format!("invalid RGB color `{s}` (expected `#rrggbb`)")),
+            };
+        }
+        Ok(
+            match s.to_ascii_lowercase().replace(['-', '_'], "").as_str() {
+                "default" | "reset" => Self::Default,
+                "black" => Self::Black,
+                "red" => Self::Red,
+                "green" => Self::Green,
+                "yellow" => Self::Yellow,
+                "blue" => Self::Blue,
+                "magenta" => Self::Magenta,
+                "cyan" => Self::Cyan,
+                "gray" | "grey" => Self::Gray,
+                "darkgray" | "darkgrey" => Self::DarkGray,
+                "lightred" => Self::LightRed,
+                "lightgreen" => Self::LightGreen,
+                "lightyellow" => Self::LightYellow,
+                "lightblue" => Self::LightBlue,
+                "lightmagenta" => Self::LightMagenta,
+                "lightcyan" => Self::LightCyan,
+                "white" => Self::White,
+                _ => return Err(format!("unknown color `{s}`")),
+            },
+        )
+    }
+}
+
+impl TryFrom<String> for StyleSpec {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let mut spec = Self::default();
+        let mut words = value.split_whitespace();
+        while let Some(word) = words.next() {
+            match word {
+                "bold" => spec.bold = true,
+                "dim" => spec.dim = true,
+                "italic" => spec.italic = true,
+                "underlined" => spec.underlined = true,
+                "reversed" => spec.reversed = true,
+                "on" => {
+                    let color = words
+                        .next()
+                        .ok_or_else(|| format!("missing background color in `{value}`"))?;
+                    spec.bg = Some(color.parse()?);
+                }
+                color if spec.fg.is_none() => spec.fg = Some(color.parse()?),
+                _ => return Err(format!("more than one foreground color in `{value}`")),
+            }
+        }
+        Ok(spec)
+    }
+}
+
+/// Keys bound to TUI actions, from `[tui.keybindings]`. Validated when the
+/// config is loaded: unknown actions, malformed keys and keys bound to two
+/// actions that are active at the same time are rejected.
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(try_from = "KeyBindingsToml")]
+pub struct KeyBindings {
+    pub submit_mode: SubmitMode,
+
+    /// Actions whose default keys were replaced.
+    pub overrides: BTreeMap<KeyAction, Vec<KeySpec>>,
+}
+
+impl KeyBindings {
+    /// Keys bound to `action`, which may be none if the user unbound it.
+    pub fn keys(&self, action: KeyAction) -> Vec<KeySpec> {
+        match self.overrides.get(&action) {
+            Some(keys) => keys.clone(),
+            None => action.default_keys(self.submit_mode),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct KeyBindingsToml {
+    #[serde(default)]
+    submit_mode: SubmitMode,
+
+    #[serde(flatten)]
+    actions: BTreeMap<KeyAction, KeyList>,
+}
+
+/// A single key or a list of keys.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum KeyList {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl TryFrom<KeyBindingsToml> for KeyBindings {
+    type Error = String;
+
+    fn try_from(value: KeyBindingsToml) -> Result<Self, Self::Error> {
+        let mut overrides = BTreeMap::new();
+        for (action, keys) in value.actions {
+            let keys = match keys {
+                KeyList::One(key) => vec![key],
+                KeyList::Many(keys) => keys,
+            };
+            let keys = keys
+                .iter()
+                .map(|key| key.parse())
+                .collect::<Result<Vec<KeySpec>, _>>()
+                .map_err(|err| format!("invalid key for `{action}`: {err}"))?;
+            overrides.insert(action, keys);
+        }
+        let bindings = Self {
+            submit_mode: value.submit_mode,
+            overrides,
+        };
+
+        let mut seen: HashMap<(KeyContext, KeySpec), KeyAction> = HashMap::new();
+        for action in KeyAction::ALL {
+            for key in bindings.keys(action) {
+                if action.context() != KeyContext::Pager
+                    && matches!(key.code, KeyCodeSpec::Char(_))
+                    && !key.ctrl
+                    && !key.alt
+                {
+                    return Err(format!(
+                        "`{key}` is typed as text and cannot be bound to `{action}`"
+                    ));
+                }
+                if let Some(other) = seen.insert((action.context(), key), action) {
+                    return Err(format!("`{key}` is bound to both `{other}` and `{action}`"));
+                }
+            }
+        }
+        Ok(bindings)
+    }
+}
+
+#[derive(Deserialize, Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum SubmitMode {
+    /// Enter sends the message; Shift+Enter or Ctrl+J inserts a newline.
+    #[default]
+    Enter,
+
+    /// Enter inserts a newline; Ctrl+Enter or Alt+Enter sends the message.
+    CtrlEnter,
+}
+
+/// Rebindable TUI actions. Each belongs to a [`KeyContext`] in which its
+/// keys must be unique.
+#[derive(Deserialize, Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, DeriveDisplay)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum KeyAction {
+    Submit,
+    Newline,
+    /// Exit Codex when the composer is empty.
+    Quit,
+    HistoryPrevious,
+    HistoryNext,
+    /// Open the transcript pager, or close it when open.
+    Transcript,
+    /// Pressed twice with an empty composer: edit a previous message.
+    Backtrack,
+    /// Edit the composer text in `$VISUAL` or `$EDITOR`.
+    ExternalEditor,
+    /// Switch to the next or previous conversation tab.
+    NextTab,
+    PreviousTab,
+
+    CursorLeft,
+    CursorRight,
+    CursorUp,
+    CursorDown,
+    WordLeft,
+    WordRight,
+    LineStart,
+    LineEnd,
+    DeleteBackward,
+    DeleteForward,
+    DeleteWordBackward,
+    DeleteWordForward,
+    KillLineStart,
+    KillLineEnd,
+
+    PagerClose,
+    ScrollUp,
+    ScrollDown,
+    PageUp,
+    PageDown,
+    ScrollTop,
+    ScrollBottom,
+    /// Search the pager; `search_next` and `search_previous` jump between
+    /// the matches.
+    Search,
+    SearchNext,
+    SearchPrevious,
+    /// Move to the next `/diff` file, or focus the next command output in
+    /// the transcript.
+    NextFile,
+    PreviousFile,
+    /// Fold the current `/diff` file, or expand the focused transcript
+    /// output into its own pager.
+    ToggleFold,
+    ToggleFoldAll,
+    /// Copy the focused or expanded command output to the clipboard.
+    CopyOutput,
+}
+
+/// Where an action is active.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum KeyContext {
+    /// The chat composer, before keys reach the text editor.
+    Composer,
+    /// Text editing in the composer.
+    Editor,
+    /// The transcript, `/diff` and other full-screen pagers.
+    Pager,
+}
+
+impl KeyAction {
+    pub const ALL: [KeyAction; 39] = [
+        Self::Submit,
+        Self::Newline,
+        Self::Quit,
+        Self::HistoryPrevious,
+        Self::HistoryNext,
+        Self::Transcript,
+        Self::Backtrack,
+        Self::ExternalEditor,
+        Self::NextTab,
+        Self::PreviousTab,
+        Self::CursorLeft,
+        Self::CursorRight,
+        Self::CursorUp,
+        Self::CursorDown,
+        Self::WordLeft,
+        Self::WordRight,
+        Self::LineStart,
+        Self::LineEnd,
+        Self::DeleteBackward,
+        Self::DeleteForward,
+        Self::DeleteWordBackward,
+        Self::DeleteWordForward,
+        Self::KillLineStart,
+        Self::KillLineEnd,
+        Self::PagerClose,
+        Self::ScrollUp,
+        Self::ScrollDown,
+        Self::PageUp,
+        Self::PageDown,
+        Self::ScrollTop,
+        Self::ScrollBottom,
+        Self::Search,
+        Self::SearchNext,
+        Self::SearchPrevious,
+        Self::NextFile,
+        Self::PreviousFile,
+        Self::ToggleFold,
+        Self::ToggleFoldAll,
+        Self::CopyOutput,
+    ];
+
+    pub fn context(self) -> KeyContext {
+        use KeyAction::*;
+        match self {
+            Submit | Newline | Quit | HistoryPrevious | HistoryNext | Transcript | Backtrack
+            | ExternalEditor | NextTab | PreviousTab => KeyContext::Composer,
+            CursorLeft | CursorRight | CursorUp | CursorDown | WordLeft | WordRight | LineStart
+            | LineEnd | DeleteBackward | DeleteForward | DeleteWordBackward | DeleteWordForward
+            | KillLineStart | KillLineEnd => KeyContext::Editor,
+            PagerClose | ScrollUp | ScrollDown | PageUp | PageDown | ScrollTop | ScrollBottom
+            | Search | SearchNext | SearchPrevious | NextFile | PreviousFile | ToggleFold
+            | ToggleFoldAll | CopyOutput => KeyContext::Pager,
+        }
+    }
+
+    pub fn default_keys(self, submit_mode: SubmitMode) -> Vec<KeySpec> {
+        use KeyCodeSpec::*;
+        let key = KeySpec::new;
+        let ctrl = |c: char| KeySpec::new(Char(c)).ctrl();
+        let alt = |code: KeyCodeSpec| KeySpec::new(code).alt();
+        match (self, submit_mode) {
+            (Self::Submit, SubmitMode::Enter) => vec![key(Enter)],
+            (Self::Submit, SubmitMode::CtrlEnter) => vec![key(Enter).ctrl(), alt(Enter)],
+            (Self::Newline, SubmitMode::Enter) => vec![key(Enter).shift(), ctrl('j')],
+            (Self::Newline, SubmitMode::CtrlEnter) => {
+                vec![key(Enter), key(Enter).shift(), ctrl('j')]
+            }
+            (Self::Quit, _) => vec![ctrl('d')],
+            (Self::HistoryPrevious, _) => vec![key(Up)],
+            (Self::HistoryNext, _) => vec![key(Down)],
+            (Self::Transcript, _) => vec![ctrl('t')],
+            (Self::Backtrack, _) => vec![key(Esc)],
+            (Self::ExternalEditor, _) => vec![ctrl('g')],
+            (Self::NextTab, _) => vec![key(PageDown).ctrl(), alt(Char('.'))],
+            (Self::PreviousTab, _) => vec![key(PageUp).ctrl(), alt(Char(','))],
+            (Self::CursorLeft, _) => vec![key(Left), ctrl('b')],
+            (Self::CursorRight, _) => vec![key(Right), ctrl('f')],
+            (Self::CursorUp, _) => vec![key(Up)],
+            (Self::CursorDown, _) => vec![key(Down)],
+            (Self::WordLeft, _) => vec![alt(Left), key(Left).ctrl(), alt(Char('b'))],
+            (Self::WordRight, _) => vec![alt(Right), key(Right).ctrl(), alt(Char('f'))],
+            (Self::LineStart, _) => vec![key(Home), ctrl('a')],
+            (Self::LineEnd, _) => vec![key(End), ctrl('e')],
+            (Self::DeleteBackward, _) => vec![key(Backspace), ctrl('h')],
+            (Self::DeleteForward, _) => vec![key(Delete), ctrl('d')],
+            (Self::DeleteWordBackward, _) => {
+                vec![alt(Backspace), ctrl('w'), ctrl('h').alt()]
+            }
+            (Self::DeleteWordForward, _) => vec![alt(Delete)],
+            (Self::KillLineStart, _) => vec![ctrl('u')],
+            (Self::KillLineEnd, _) => vec![ctrl('k')],
+            (Self::PagerClose, _) => vec![key(Char('q')), ctrl('c')],
+            (Self::ScrollUp, _) => vec![key(Up)],
+            (Self::ScrollDown, _) => vec![key(Down)],
+            (Self::PageUp, _) => vec![key(PageUp)],
+            (Self::PageDown, _) => vec![key(PageDown), key(Char(' '))],
+            (Self::ScrollTop, _) => vec![key(Home)],
+            (Self::ScrollBottom, _) => vec![key(End)],
+            (Self::Search, _) => vec![key(Char('/'))],
+            (Self::SearchNext, _) => vec![key(Char('n'))],
+            (Self::SearchPrevious, _) => vec![key(Char('N'))],
+            (Self::NextFile, _) => vec![key(Tab)],
+            (Self::PreviousFile, _) => vec![key(Tab).shift()],
+            (Self::ToggleFold, _) => vec![key(Enter)],
+            (Self::ToggleFoldAll, _) => vec![key(Char('a'))],
+            (Self::CopyOutput, _) => vec![key(Char('y'))],
+        }
+    }
+}
+
+/// A key with modifiers, written like `"ctrl-enter"`, `"alt-b"`, `"q"` or
+/// `"shift-tab"`. Letters combined with Ctrl are case-insensitive.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct KeySpec {
+    pub code: KeyCodeSpec,
+    pub ctrl: bool,
+    pub alt: bool,
+    pub shift: bool,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum KeyCodeSpec {
+    Char(char),
+    Enter,
+    Esc,
+    Tab,
+    Backspace,
+    Delete,
+    Insert,
+    Up,
+    Down,
+    Left,
+    Right,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    F(u8),
+}
+
+impl KeySpec {
+    pub fn new(code: KeyCodeSpec) -> Self {
+        Self {
+            code,
+            ctrl: false,
+            alt: false,
+            shift: false,
+        }
+    }
+
+    pub fn ctrl(self) -> Self {
+        Self { ctrl: true, ..self }
+    }
+
+    pub fn alt(self) -> Self {
+        Self { alt: true, ..self }
+    }
+
+    pub fn shift(self) -> Self {
+        Self {
+            shift: true,
+            ..self
+        }
+    }
+}
+
+impl std::str::FromStr for KeySpec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut key = Self::new(KeyCodeSpec::Enter);
+        let mut rest = s.trim();
+        // A modifier is followed by `-` or `+`; whatever remains is the key,
+        // which may itself be `-` or `+`.
+        while let Some((modifier, tail)) = rest.split_once(['-', '+'])
+            && !tail.is_empty()
+        {
+            match modifier.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => key.ctrl = true,
+                "alt" | "meta" | "option" => key.alt = true,
+                "shift" => key.shift = true,
+                _ => break,
+            }
+            rest = tail;
+        }
+        key.code = match rest.to_ascii_lowercase().as_str() {
+            "enter" | "return" => KeyCodeSpec::Enter,
+            "esc" | "escape" => KeyCodeSpec::Esc,
+            "tab" => KeyCodeSpec::Tab,
+            "backspace" => KeyCodeSpec::Backspace,
+            "delete" | "del" => KeyCodeSpec::Delete,
+            "insert" | "ins" => KeyCodeSpec::Insert,
+            "up" => KeyCodeSpec::Up,
+            "down" => KeyCodeSpec::Down,
+            "left" => KeyCodeSpec::Left,
+            "right" => KeyCodeSpec::Right,
+            "home" => KeyCodeSpec::Home,
+            "end" => KeyCodeSpec::End,
+            "pageup" | "pgup" => KeyCodeSpec::PageUp,
+            "pagedown" | "pgdn" => KeyCodeSpec::PageDown,
+            "space" => KeyCodeSpec::Char(' '),
+            name => {
+                let mut chars = rest.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) if key.ctrl => KeyCodeSpec::Char(c.to_ascii_lowercase()),
+                    (Some(c), None) => KeyCodeSpec::Char(c),
+                    _ => match name.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+                        Some(n @ 1..=24) => KeyCodeSpec::F(n),
+                        _ => return Err(format!("unknown key `{s}`")),
+                    },
+                }
+            }
+        };
+        // Terminals report Shift with letters as the shifted character.
+        if let KeyCodeSpec::Char(c) = key.code
+            && key.shift
+        {
+            key.shift = false;
+            if !key.ctrl {
+                key.code = KeyCodeSpec::Char(c.to_ascii_uppercase());
+            }
+        }
+        Ok(key)
+    }
+}
+
+impl std::fmt::Display for KeySpec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (set, name) in [
+            (self.ctrl, "ctrl-"),
+            (self.alt, "alt-"),
+            (self.shift, "shift-"),
+        ] {
+            if set {
+                f.write_str(name)?;
+            }
+        }
+        match self.code {
+            KeyCodeSpec::Char(' ') => f.write_str("space"),
+            KeyCodeSpec::Char(c) => write!(f, "{c}"),
+            KeyCodeSpec::F(n) => write!(f, "f{n}"),
+            code => f.write_str(&format!("{code:?}").to_ascii_lowercase()),
+        }
+    }
+}
 
 #[derive(Deserialize, Debug, Clone, PartialEq, Default)]
 pub struct SandboxWorkspaceWrite {
@@ -135,6 +861,10 @@ pub struct ShellEnvironmentPolicyToml {
     pub include_only: Option<Vec<String>>,
 
     pub experimental_use_profile: Option<bool>,
+
+    /// Record the names (never the values) of the variables passed to each
+    /// tool call in the session rollout.
+    pub record_in_rollout: Option<bool>,
 }
 
 pub type EnvironmentVariablePattern = WildMatchPattern<'*', '?'>;
@@ -166,6 +896,10 @@ pub struct ShellEnvironmentPolicy {
 
     /// If true, the shell profile will be used to run the command.
     pub use_profile: bool,
+
+    /// If true, the names of the variables passed to each tool call are
+    /// recorded in the session rollout.
+    pub record_in_rollout: bool,
 }
 
 impl From<ShellEnvironmentPolicyToml> for ShellEnvironmentPolicy {
@@ -187,6 +921,7 @@ impl From<ShellEnvironmentPolicyToml> for ShellEnvironmentPolicy {
             .map(|s| EnvironmentVariablePattern::new_case_insensitive(&s))
             .collect();
         let use_profile = toml.experimental_use_profile.unwrap_or(false);
+        let record_in_rollout = toml.record_in_rollout.unwrap_or(false);
 
         Self {
             inherit,
@@ -195,6 +930,7 @@ impl From<ShellEnvironmentPolicyToml> for ShellEnvironmentPolicy {
             r#set,
             include_only,
             use_profile,
+            record_in_rollout,
         }
     }
 }
@@ -27,6 +27,9 @@ pub(crate) struct EnvironmentContext {
     pub sandbox_mode: Option<SandboxMode>,
     pub network_access: Option<NetworkAccess>,
     pub shell: Option<Shell>,
+    /// The most central files of the repository, from the repository index.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repository_map: Option<String>,
 }
 
 impl EnvironmentContext {
@@ -58,8 +61,14 @@ impl EnvironmentContext {
                 None => None,
             },
             shell,
+            repository_map: None,
         }
     }
+
+    pub fn with_repository_map(mut self, repository_map: Option<String>) -> Self {
+        self.repository_map = repository_map;
+        self
+    }
 }
 
 impl EnvironmentContext {
@@ -74,6 +83,9 @@ impl EnvironmentContext {
     ///   <sandbox_mode>...</sandbox_mode>
     ///   <network_access>...</network_access>
     ///   <shell>...</shell>
+    ///   <repository_map>
+    ///     path: summary
+    ///   </repository_map>
     /// </environment_context>
     /// ```
     pub fn serialize_to_xml(self) -> String {
@@ -99,6 +111,11 @@ impl EnvironmentContext {
         {
             lines.push(format!("  <shell>{shell_name}</shell>"));
         }
+        if let Some(repository_map) = self.repository_map {
+            lines.push("  <repository_map>".to_string());
+            lines.extend(repository_map.lines().map(|line| format!("    {line}")));
+            lines.push("  </repository_map>".to_string());
+        }
         lines.push(ENVIRONMENT_CONTEXT_CLOSE_TAG.to_string());
         lines.join("\n")
     }
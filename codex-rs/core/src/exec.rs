@@ -14,6 +14,7 @@ use tokio::io::AsyncReadExt;
 use tokio::io::BufReader;
 use tokio::process::Child;
 
+use crate::config_types::Seccomp;
 use crate::error::CodexErr;
 use crate::error::Result;
 use crate::error::SandboxErr;
@@ -82,6 +83,7 @@ pub async fn process_exec_tool_call(
     sandbox_type: SandboxType,
     sandbox_policy: &SandboxPolicy,
     codex_linux_sandbox_exe: &Option<PathBuf>,
+    seccomp: &Seccomp,
     stdout_stream: Option<StdoutStream>,
 ) -> Result<ExecToolCallOutput> {
     let start = Instant::now();
@@ -117,6 +119,7 @@ pub async fn process_exec_tool_call(
                 codex_linux_sandbox_exe,
                 command,
                 sandbox_policy,
+                seccomp,
                 cwd,
                 StdioPolicy::RedirectForShellTool,
                 env,
@@ -171,7 +174,7 @@ pub async fn process_exec_tool_call(
 /// error, but the command itself might fail or succeed for other reasons.
 /// For now, we conservatively check for 'command not found' (exit code 127),
 /// and can add additional cases as necessary.
-fn is_likely_sandbox_denied(sandbox_type: SandboxType, exit_code: i32) -> bool {
+pub(crate) fn is_likely_sandbox_denied(sandbox_type: SandboxType, exit_code: i32) -> bool {
     if sandbox_type == SandboxType::None {
         return false;
     }
@@ -186,6 +189,57 @@ fn is_likely_sandbox_denied(sandbox_type: SandboxType, exit_code: i32) -> bool {
     true
 }
 
+/// Spawns `command` under `sandbox_type` without waiting for it to finish, for
+/// callers that manage the lifetime of the child themselves.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn spawn_sandboxed_child(
+    command: Vec<String>,
+    cwd: PathBuf,
+    env: HashMap<String, String>,
+    sandbox_type: SandboxType,
+    sandbox_policy: &SandboxPolicy,
+    codex_linux_sandbox_exe: &Option<PathBuf>,
+    seccomp: &Seccomp,
+    stdio_policy: StdioPolicy,
+) -> Result<Child> {
+    let child = match sandbox_type {
+        SandboxType::None => {
+            let (program, args) = command
+                .split_first()
+                .ok_or_else(|| CodexErr::Io(io::Error::other("command args are empty")))?;
+            spawn_child_async(
+                PathBuf::from(program),
+                args.to_vec(),
+                None,
+                cwd,
+                sandbox_policy,
+                stdio_policy,
+                env,
+            )
+            .await?
+        }
+        SandboxType::MacosSeatbelt => {
+            spawn_command_under_seatbelt(command, sandbox_policy, cwd, stdio_policy, env).await?
+        }
+        SandboxType::LinuxSeccomp => {
+            let codex_linux_sandbox_exe = codex_linux_sandbox_exe
+                .as_ref()
+                .ok_or(CodexErr::LandlockSandboxExecutableNotProvided)?;
+            spawn_command_under_linux_sandbox(
+                codex_linux_sandbox_exe,
+                command,
+                sandbox_policy,
+                seccomp,
+                cwd,
+                stdio_policy,
+                env,
+            )
+            .await?
+        }
+    };
+    Ok(child)
+}
+
 #[derive(Debug)]
 pub struct StreamOutput<T> {
     pub text: T,
@@ -0,0 +1,923 @@
+//! Long-running processes (dev servers, databases, file watchers) that the
+//! agent starts by name and that outlive the tool call that started them.
+//!
+//! Output is kept in a bounded buffer so that it can be tailed later, and a
+//! process can optionally be considered ready once a line of its output
+//! matches a regex or a TCP port on localhost starts accepting connections.
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::process::ExitStatus;
+use std::sync::Arc;
+use std::sync::Mutex as StdMutex;
+use std::sync::Weak;
+
+use codex_protocol::models::FunctionCallOutputPayload;
+use regex_lite::Regex;
+use tokio::io::AsyncRead;
+use tokio::io::AsyncReadExt;
+use tokio::net::TcpStream;
+use tokio::process::Child;
+use tokio::sync::oneshot;
+use tokio::sync::watch;
+use tokio::time::Duration;
+use tokio::time::Instant;
+
+use crate::config_types::Seccomp;
+use crate::exec::SandboxType;
+use crate::exec::spawn_sandboxed_child;
+use crate::exec_command::exec_command_params::StartBackgroundProcessParams;
+use crate::protocol::BackgroundProcessInfo;
+use crate::protocol::BackgroundProcessStatus;
+use crate::protocol::SandboxPolicy;
+use crate::spawn::StdioPolicy;
+
+/// Maximum number of output bytes retained per process; older output is
+/// discarded first.
+const MAX_LOG_BYTES: usize = 512 * 1024;
+
+/// How long `start` waits for a process without a readiness check, so that
+/// commands that fail immediately are reported as such.
+const STARTUP_GRACE: Duration = Duration::from_millis(500);
+
+const READINESS_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How long a stopped process gets to exit after SIGTERM before it is killed.
+const STOP_GRACE: Duration = Duration::from_secs(3);
+
+const READ_CHUNK_SIZE: usize = 8192;
+
+/// The set of background processes owned by one conversation.
+#[derive(Debug)]
+pub(crate) struct BackgroundProcesses {
+    processes: StdMutex<BTreeMap<String, BackgroundProcess>>,
+    /// Names of processes being spawned, claimed while `processes` is locked
+    /// so that two concurrent starts cannot both take a name.
+    starting: StdMutex<HashSet<String>>,
+    updates: watch::Sender<Vec<BackgroundProcessInfo>>,
+}
+
+impl Default for BackgroundProcesses {
+    fn default() -> Self {
+        Self {
+            processes: StdMutex::new(BTreeMap::new()),
+            starting: StdMutex::new(HashSet::new()),
+            updates: watch::channel(Vec::new()).0,
+        }
+    }
+}
+
+/// A name claimed by a process that is being started, released on drop.
+struct NameClaim<'a> {
+    processes: &'a BackgroundProcesses,
+    name: String,
+}
+
+impl Drop for NameClaim<'_> {
+    fn drop(&mut self) {
+        self.processes.lock_starting().remove(&self.name);
+    }
+}
+
+#[derive(Debug)]
+struct BackgroundProcess {
+    command: Vec<String>,
+    cwd: PathBuf,
+    pid: Option<u32>,
+    started_at: Instant,
+    state: Arc<StdMutex<ProcessState>>,
+    /// Dropping (or sending on) this asks the supervisor task to terminate the
+    /// process group.
+    stop_tx: Option<oneshot::Sender<()>>,
+    exited_rx: watch::Receiver<bool>,
+}
+
+#[derive(Debug)]
+struct ProcessState {
+    log: LogBuffer,
+    readiness: Readiness,
+    exit_code: Option<i32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Readiness {
+    /// No readiness check was requested.
+    Unchecked,
+    Pending,
+    Ready,
+}
+
+/// Result of a background process tool call.
+#[derive(Debug)]
+pub struct BackgroundProcessOutput {
+    name: String,
+    pid: Option<u32>,
+    uptime: Duration,
+    status: BackgroundProcessStatus,
+    readiness_checked: bool,
+    stopped: bool,
+    original_token_count: Option<u64>,
+    output: String,
+}
+
+impl BackgroundProcessOutput {
+    fn to_text_output(&self) -> String {
+        let pid = match self.pid {
+            Some(pid) => format!(" (pid {pid})"),
+            None => String::new(),
+        };
+        let status = match (&self.status, self.stopped) {
+            (BackgroundProcessStatus::Exited { exit_code }, true) => {
+                format!("stopped (exit code {exit_code})")
+            }
+            (BackgroundProcessStatus::Exited { exit_code }, false) => {
+                format!("exited with code {exit_code}")
+            }
+            (BackgroundProcessStatus::Starting, _) => "running, not ready yet".to_string(),
+            (BackgroundProcessStatus::Running, _) if self.readiness_checked => {
+                "running, ready".to_string()
+            }
+            (BackgroundProcessStatus::Running, _) => "running".to_string(),
+        };
+        let truncation_status = match self.original_token_count {
+            Some(tokens) => {
+                format!(
+                    "\nWarning: showing the end of the output only (original token count: {tokens})"
+                )
+            }
+            None => String::new(),
+        };
+        format!(
+            r#"Background process "{name}"{pid}: {status}
+Uptime: {uptime:.3} seconds{truncation_status}
+Output:
+{output}"#,
+            name = self.name,
+            uptime = self.uptime.as_secs_f32(),
+            output = self.output,
+        )
+    }
+}
+
+pub fn background_process_result_into_payload(
+    result: Result<BackgroundProcessOutput, String>,
+) -> FunctionCallOutputPayload {
+    match result {
+        Ok(output) => {
+            let success =
+                output.stopped || !matches!(output.status, BackgroundProcessStatus::Exited { .. });
+            FunctionCallOutputPayload {
+                content: output.to_text_output(),
+                success: Some(success),
+            }
+        }
+        Err(err) => FunctionCallOutputPayload {
+            content: err,
+            success: Some(false),
+        },
+    }
+}
+
+impl BackgroundProcesses {
+    pub(crate) fn subscribe(&self) -> watch::Receiver<Vec<BackgroundProcessInfo>> {
+        self.updates.subscribe()
+    }
+
+    /// Starts a process in `workdir`, resolved against `cwd`.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) async fn start(
+        self: &Arc<Self>,
+        params: StartBackgroundProcessParams,
+        cwd: PathBuf,
+        env: HashMap<String, String>,
+        sandbox_type: SandboxType,
+        sandbox_policy: &SandboxPolicy,
+        codex_linux_sandbox_exe: &Option<PathBuf>,
+        seccomp: &Seccomp,
+    ) -> Result<BackgroundProcessOutput, String> {
+        let StartBackgroundProcessParams {
+            name,
+            command,
+            workdir,
+            ready_pattern,
+            ready_port,
+            ready_timeout_ms,
+            max_output_tokens,
+            with_escalated_permissions: _,
+            justification: _,
+        } = params;
+
+        if name.trim().is_empty() {
+            return Err("background process name must not be empty".to_string());
+        }
+        if command.is_empty() {
+            return Err("background process command must not be empty".to_string());
+        }
+        let ready_pattern = ready_pattern
+            .map(|pattern| {
+                Regex::new(&pattern).map_err(|err| format!("invalid ready_pattern: {err}"))
+            })
+            .transpose()?;
+        let claim = self.claim_name(&name)?;
+        // Otherwise whatever already listens would make the process look ready.
+        if let Some(port) = ready_port
+            && port_accepts_connections(port).await
+        {
+            return Err(format!(
+                "port {port} is already in use, so ready_port cannot tell when \"{name}\" is ready"
+            ));
+        }
+
+        let cwd = match workdir {
+            Some(workdir) => cwd.join(workdir),
+            None => cwd,
+        };
+        let mut child = spawn_sandboxed_child(
+            command.clone(),
+            cwd.clone(),
+            env,
+            sandbox_type,
+            sandbox_policy,
+            codex_linux_sandbox_exe,
+            seccomp,
+            StdioPolicy::RedirectForBackgroundProcess,
+        )
+        .await
+        .map_err(|err| format!("failed to start background process \"{name}\": {err}"))?;
+
+        let readiness_checked = ready_pattern.is_some() || ready_port.is_some();
+        let state = Arc::new(StdMutex::new(ProcessState {
+            log: LogBuffer::default(),
+            readiness: if readiness_checked {
+                Readiness::Pending
+            } else {
+                Readiness::Unchecked
+            },
+            exit_code: None,
+        }));
+
+        let ready_pattern = ready_pattern.map(Arc::new);
+        if let Some(stdout) = child.stdout.take() {
+            tokio::spawn(read_output(
+                stdout,
+                state.clone(),
+                ready_pattern.clone(),
+                Arc::downgrade(self),
+            ));
+        }
+        if let Some(stderr) = child.stderr.take() {
+            tokio::spawn(read_output(
+                stderr,
+                state.clone(),
+                ready_pattern,
+                Arc::downgrade(self),
+            ));
+        }
+        if let Some(port) = ready_port {
+            tokio::spawn(wait_for_port(port, state.clone(), Arc::downgrade(self)));
+        }
+
+        let pid = child.id();
+        let (stop_tx, stop_rx) = oneshot::channel();
+        let (exited_tx, exited_rx) = watch::channel(false);
+        tokio::spawn(supervise(
+            child,
+            stop_rx,
+            exited_tx,
+            state.clone(),
+            Arc::downgrade(self),
+        ));
+
+        let started_at = Instant::now();
+        let process = BackgroundProcess {
+            command,
+            cwd,
+            pid,
+            started_at,
+            state,
+            stop_tx: Some(stop_tx),
+            exited_rx,
+        };
+        let previous = self.lock_processes().insert(name.clone(), process);
+        drop(claim);
+        // Only an exited process can be replaced; dropping it is enough.
+        drop(previous);
+        self.publish();
+
+        let wait_time = if readiness_checked {
+            Duration::from_millis(ready_timeout_ms)
+        } else {
+            STARTUP_GRACE
+        };
+        let deadline = started_at + wait_time;
+        while Instant::now() < deadline {
+            if self.is_settled(&name) {
+                break;
+            }
+            tokio::time::sleep(READINESS_POLL_INTERVAL.min(deadline - Instant::now())).await;
+        }
+
+        self.output(&name, max_output_tokens)
+    }
+
+    pub(crate) fn logs(
+        &self,
+        name: &str,
+        max_output_tokens: u64,
+    ) -> Result<BackgroundProcessOutput, String> {
+        self.output(name, max_output_tokens)
+    }
+
+    pub(crate) async fn stop(
+        &self,
+        name: &str,
+        max_output_tokens: u64,
+    ) -> Result<BackgroundProcessOutput, String> {
+        let process = self
+            .lock_processes()
+            .remove(name)
+            .ok_or_else(|| format!("unknown background process \"{name}\""))?;
+        let mut output = process.stop(name, max_output_tokens).await;
+        output.stopped = true;
+        self.publish();
+        Ok(output)
+    }
+
+    /// Stops every background process, e.g. when the session shuts down.
+    pub(crate) async fn stop_all(&self) {
+        let processes = std::mem::take(&mut *self.lock_processes());
+        futures::future::join_all(
+            processes
+                .into_iter()
+                .map(|(name, process)| async move { process.stop(&name, 0).await }),
+        )
+        .await;
+        self.publish();
+    }
+
+    fn output(
+        &self,
+        name: &str,
+        max_output_tokens: u64,
+    ) -> Result<BackgroundProcessOutput, String> {
+        let processes = self.lock_processes();
+        let process = processes
+            .get(name)
+            .ok_or_else(|| format!("unknown background process \"{name}\""))?;
+        Ok(process.output(name, max_output_tokens))
+    }
+
+    /// Claims `name` for a new process unless a process by that name is
+    /// running or being started.
+    fn claim_name(&self, name: &str) -> Result<NameClaim<'_>, String> {
+        let processes = self.lock_processes();
+        let running = processes
+            .get(name)
+            .is_some_and(|process| process.lock_state().exit_code.is_none());
+        if running || !self.lock_starting().insert(name.to_string()) {
+            return Err(format!(
+                "background process \"{name}\" is already running; stop it first"
+            ));
+        }
+        Ok(NameClaim {
+            processes: self,
+            name: name.to_string(),
+        })
+    }
+
+    /// Whether the process has either exited or passed its readiness check.
+    fn is_settled(&self, name: &str) -> bool {
+        self.lock_processes().get(name).is_none_or(|process| {
+            let state = process.lock_state();
+            state.exit_code.is_some() || state.readiness == Readiness::Ready
+        })
+    }
+
+    fn publish(&self) {
+        let snapshot: Vec<BackgroundProcessInfo> = self
+            .lock_processes()
+            .iter()
+            .map(|(name, process)| process.info(name))
+            .collect();
+        self.updates.send_if_modified(|current| {
+            if *current == snapshot {
+                false
+            } else {
+                *current = snapshot;
+                true
+            }
+        });
+    }
+
+    fn lock_processes(&self) -> std::sync::MutexGuard<'_, BTreeMap<String, BackgroundProcess>> {
+        self.processes
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    fn lock_starting(&self) -> std::sync::MutexGuard<'_, HashSet<String>> {
+        self.starting
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+impl BackgroundProcess {
+    fn lock_state(&self) -> std::sync::MutexGuard<'_, ProcessState> {
+        lock_state(&self.state)
+    }
+
+    fn status(&self) -> BackgroundProcessStatus {
+        let state = self.lock_state();
+        match (state.exit_code, state.readiness) {
+            (Some(exit_code), _) => BackgroundProcessStatus::Exited { exit_code },
+            (None, Readiness::Pending) => BackgroundProcessStatus::Starting,
+            (None, Readiness::Unchecked | Readiness::Ready) => BackgroundProcessStatus::Running,
+        }
+    }
+
+    fn info(&self, name: &str) -> BackgroundProcessInfo {
+        BackgroundProcessInfo {
+            name: name.to_string(),
+            command: self.command.clone(),
+            cwd: self.cwd.clone(),
+            pid: self.pid,
+            status: self.status(),
+        }
+    }
+
+    fn output(&self, name: &str, max_output_tokens: u64) -> BackgroundProcessOutput {
+        let cap_bytes = usize::try_from(max_output_tokens.saturating_mul(4)).unwrap_or(usize::MAX);
+        let (output, original_token_count, readiness) = {
+            let state = self.lock_state();
+            let (output, original_token_count) = state.log.tail(cap_bytes);
+            (output, original_token_count, state.readiness)
+        };
+        BackgroundProcessOutput {
+            name: name.to_string(),
+            pid: self.pid,
+            uptime: self.started_at.elapsed(),
+            status: self.status(),
+            readiness_checked: readiness != Readiness::Unchecked,
+            stopped: false,
+            original_token_count,
+            output,
+        }
+    }
+
+    async fn stop(mut self, name: &str, max_output_tokens: u64) -> BackgroundProcessOutput {
+        if let Some(stop_tx) = self.stop_tx.take() {
+            let _ = stop_tx.send(());
+        }
+        let _ = self.exited_rx.wait_for(|exited| *exited).await;
+        self.output(name, max_output_tokens)
+    }
+}
+
+fn lock_state(state: &StdMutex<ProcessState>) -> std::sync::MutexGuard<'_, ProcessState> {
+    state
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+fn mark_ready(state: &StdMutex<ProcessState>, processes: &Weak<BackgroundProcesses>) {
+    {
+        let mut state = lock_state(state);
+        if state.readiness != Readiness::Pending || state.exit_code.is_some() {
+            return;
+        }
+        state.readiness = Readiness::Ready;
+    }
+    if let Some(processes) = processes.upgrade() {
+        processes.publish();
+    }
+}
+
+/// Waits for the child to exit or for a stop request, whichever comes first,
+/// and records the exit code.
+async fn supervise(
+    mut child: Child,
+    stop_rx: oneshot::Receiver<()>,
+    exited_tx: watch::Sender<bool>,
+    state: Arc<StdMutex<ProcessState>>,
+    processes: Weak<BackgroundProcesses>,
+) {
+    let status = tokio::select! {
+        status = child.wait() => status,
+        // Either an explicit stop or the process entry being dropped.
+        _ = stop_rx => terminate(&mut child).await,
+    };
+    let exit_code = match status {
+        Ok(status) => exit_code(status),
+        Err(_) => -1,
+    };
+    lock_state(&state).exit_code = Some(exit_code);
+    exited_tx.send_replace(true);
+    if let Some(processes) = processes.upgrade() {
+        processes.publish();
+    }
+}
+
+/// Sends SIGTERM to the process group, then SIGKILL if it does not exit within
+/// [`STOP_GRACE`].
+async fn terminate(child: &mut Child) -> std::io::Result<ExitStatus> {
+    #[cfg(unix)]
+    if let Some(pid) = child.id() {
+        signal_process_group(pid, libc::SIGTERM);
+        let result = tokio::time::timeout(STOP_GRACE, child.wait()).await;
+        // Also reap anything the leader left behind in its group.
+        signal_process_group(pid, libc::SIGKILL);
+        if let Ok(status) = result {
+            return status;
+        }
+    }
+    child.kill().await?;
+    child.wait().await
+}
+
+#[cfg(unix)]
+fn signal_process_group(pid: u32, signal: libc::c_int) {
+    if let Ok(pid) = libc::pid_t::try_from(pid) {
+        // SAFETY: killpg has no memory-safety preconditions; failures (e.g.
+        // the group is already gone) are deliberately ignored.
+        unsafe {
+            libc::killpg(pid, signal);
+        }
+    }
+}
+
+fn exit_code(status: ExitStatus) -> i32 {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        if let Some(signal) = status.signal() {
+            return 128 + signal;
+        }
+    }
+    status.code().unwrap_or(-1)
+}
+
+async fn read_output<R: AsyncRead + Unpin>(
+    mut reader: R,
+    state: Arc<StdMutex<ProcessState>>,
+    ready_pattern: Option<Arc<Regex>>,
+    processes: Weak<BackgroundProcesses>,
+) {
+    let mut buf = [0u8; READ_CHUNK_SIZE];
+    // Bytes of the current, not yet terminated line; only tracked until the
+    // readiness pattern has matched.
+    let mut line = Vec::new();
+    let mut ready_pattern = ready_pattern;
+    loop {
+        let n = match reader.read(&mut buf).await {
+            Ok(0) | Err(_) => break,
+            Ok(n) => n,
+        };
+        let chunk = &buf[..n];
+        lock_state(&state).log.push(chunk);
+
+        let Some(pattern) = &ready_pattern else {
+            continue;
+        };
+        let mut matched = false;
+        for &byte in chunk {
+            if byte == b'\n' {
+                matched |= pattern.is_match(&String::from_utf8_lossy(&line));
+                line.clear();
+            } else {
+                line.push(byte);
+            }
+        }
+        // Servers often print a prompt without a trailing newline.
+        matched |= !line.is_empty() && pattern.is_match(&String::from_utf8_lossy(&line));
+        if matched {
+            mark_ready(&state, &processes);
+            ready_pattern = None;
+            line = Vec::new();
+        } else if line.len() > MAX_LOG_BYTES {
+            line.clear();
+        }
+    }
+}
+
+/// Polls `port` on localhost until it accepts a connection or the process
+/// exits.
+async fn wait_for_port(
+    port: u16,
+    state: Arc<StdMutex<ProcessState>>,
+    processes: Weak<BackgroundProcesses>,
+) {
+    loop {
+        {
+            let state = lock_state(&state);
+            if state.exit_code.is_some() || state.readiness == Readiness::Ready {
+                return;
+            }
+        }
+        if port_accepts_connections(port).await {
+            mark_ready(&state, &processes);
+            return;
+        }
+        tokio::time::sleep(READINESS_POLL_INTERVAL).await;
+    }
+}
+
+async fn port_accepts_connections(port: u16) -> bool {
+    TcpStream::connect(("127.0.0.1", port)).await.is_ok()
+        || TcpStream::connect(("::1", port)).await.is_ok()
+}
+
+/// Combined stdout/stderr of a process, keeping only the most recent
+/// [`MAX_LOG_BYTES`].
+#[derive(Debug, Default)]
+struct LogBuffer {
+    bytes: VecDeque<u8>,
+    total_bytes: u64,
+}
+
+impl LogBuffer {
+    fn push(&mut self, chunk: &[u8]) {
+        self.total_bytes += chunk.len() as u64;
+        self.bytes.extend(chunk);
+        let excess = self.bytes.len().saturating_sub(MAX_LOG_BYTES);
+        self.bytes.drain(..excess);
+    }
+
+    /// Returns the end of the log, at most `max_bytes` long and starting on a
+    /// line boundary when possible, along with the estimated token count of
+    /// all output seen so far if anything was left out.
+    fn tail(&self, max_bytes: usize) -> (String, Option<u64>) {
+        let original_token_count = self.total_bytes.div_ceil(4);
+        let truncated = self.total_bytes > self.bytes.len() as u64;
+        if self.bytes.len() <= max_bytes {
+            let (front, back) = self.bytes.as_slices();
+            let text = String::from_utf8_lossy(&[front, back].concat()).into_owned();
+            return (text, truncated.then_some(original_token_count));
+        }
+
+        let tail: Vec<u8> = self
+            .bytes
+            .iter()
+            .skip(self.bytes.len() - max_bytes)
+            .copied()
+            .collect();
+        let start = match tail.iter().position(|&b| b == b'\n') {
+            Some(i) if i + 1 < tail.len() => i + 1,
+            _ => 0,
+        };
+        let text = String::from_utf8_lossy(&tail[start..]).into_owned();
+        (text, Some(original_token_count))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn log_tail_starts_on_line_boundary() {
+        let mut log = LogBuffer::default();
+        log.push(b"first line\nsecond line\nthird line\n");
+
+        assert_eq!(
+            log.tail(1024),
+            ("first line\nsecond line\nthird line\n".to_string(), None)
+        );
+        // 16 bytes would start mid-way through "second line".
+        assert_eq!(log.tail(16), ("third line\n".to_string(), Some(9)));
+    }
+
+    #[test]
+    fn log_drops_oldest_output_beyond_cap() {
+        let mut log = LogBuffer::default();
+        log.push(&vec![b'a'; MAX_LOG_BYTES]);
+        log.push(b"\nlatest\n");
+
+        assert_eq!(log.bytes.len(), MAX_LOG_BYTES);
+        let (text, original_token_count) = log.tail(MAX_LOG_BYTES);
+        assert!(text.ends_with("\nlatest\n"));
+        assert_eq!(
+            original_token_count,
+            Some((MAX_LOG_BYTES as u64 + 8).div_ceil(4))
+        );
+    }
+
+    #[cfg(unix)]
+    fn start_params(name: &str, script: &str) -> StartBackgroundProcessParams {
+        StartBackgroundProcessParams {
+            name: name.to_string(),
+            command: vec!["sh".to_string(), "-c".to_string(), script.to_string()],
+            workdir: None,
+            ready_pattern: None,
+            ready_port: None,
+            ready_timeout_ms: 5_000,
+            max_output_tokens: 1_000,
+            with_escalated_permissions: None,
+            justification: None,
+        }
+    }
+
+    #[cfg(unix)]
+    async fn start(
+        processes: &Arc<BackgroundProcesses>,
+        params: StartBackgroundProcessParams,
+    ) -> Result<BackgroundProcessOutput, String> {
+        start_in(processes, params, std::env::temp_dir()).await
+    }
+
+    #[cfg(unix)]
+    async fn start_in(
+        processes: &Arc<BackgroundProcesses>,
+        params: StartBackgroundProcessParams,
+        cwd: PathBuf,
+    ) -> Result<BackgroundProcessOutput, String> {
+        processes
+            .start(
+                params,
+                cwd,
+                std::env::vars().collect(),
+                SandboxType::None,
+                &SandboxPolicy::DangerFullAccess,
+                &None,
+                &Seccomp::default(),
+            )
+            .await
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn ready_pattern_waits_for_matching_line() {
+        let processes = Arc::new(BackgroundProcesses::default());
+        let mut params = start_params(
+            "server",
+            "echo booting; sleep 0.3; echo 'listening on 4000'; sleep 30",
+        );
+        params.ready_pattern = Some(r"listening on \d+".to_string());
+
+        let output = start(&processes, params).await.expect("start");
+        assert_eq!(output.status, BackgroundProcessStatus::Running);
+        assert!(output.readiness_checked);
+        assert_eq!(output.output, "booting\nlistening on 4000\n");
+
+        // A second process with the same name is rejected while running.
+        let err = start(&processes, start_params("server", "sleep 30"))
+            .await
+            .expect_err("duplicate name");
+        assert_eq!(
+            err,
+            "background process \"server\" is already running; stop it first"
+        );
+
+        let stopped = processes.stop("server", 1_000).await.expect("stop");
+        assert!(stopped.stopped);
+        assert!(matches!(
+            stopped.status,
+            BackgroundProcessStatus::Exited { .. }
+        ));
+        assert!(processes.subscribe().borrow().is_empty());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn ready_port_waits_for_listener() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("bind");
+        let port = listener.local_addr().expect("addr").port();
+        drop(listener);
+
+        // The test stands in for the server and starts listening a little
+        // after the process was spawned.
+        let listen = tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(300)).await;
+            let listener = tokio::net::TcpListener::bind(("127.0.0.1", port))
+                .await
+                .expect("bind");
+            tokio::time::sleep(Duration::from_secs(30)).await;
+            drop(listener);
+        });
+        let processes = Arc::new(BackgroundProcesses::default());
+        let mut params = start_params("listener", "sleep 30");
+        params.ready_port = Some(port);
+
+        let output = start(&processes, params).await.expect("start");
+        assert_eq!(output.status, BackgroundProcessStatus::Running);
+        assert!(output.uptime >= Duration::from_millis(300));
+
+        processes.stop_all().await;
+        assert!(processes.subscribe().borrow().is_empty());
+        listen.abort();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn ready_port_must_be_free_before_starting() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("bind");
+        let port = listener.local_addr().expect("addr").port();
+
+        let processes = Arc::new(BackgroundProcesses::default());
+        let mut params = start_params("server", "sleep 30");
+        params.ready_port = Some(port);
+
+        let err = start(&processes, params).await.expect_err("port in use");
+        assert_eq!(
+            err,
+            format!(
+                "port {port} is already in use, so ready_port cannot tell when \"server\" is ready"
+            )
+        );
+        // The name is free again.
+        let output = start(&processes, start_params("server", "echo ok"))
+            .await
+            .expect("start");
+        assert_eq!(output.output, "ok\n");
+        drop(listener);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn concurrent_starts_cannot_share_a_name() {
+        let processes = Arc::new(BackgroundProcesses::default());
+        let (first, second) = tokio::join!(
+            start(&processes, start_params("dup", "sleep 30")),
+            start(&processes, start_params("dup", "sleep 30")),
+        );
+        assert_eq!(
+            [first.is_ok(), second.is_ok()]
+                .iter()
+                .filter(|ok| **ok)
+                .count(),
+            1
+        );
+        processes.stop_all().await;
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn workdir_is_resolved_against_the_cwd() {
+        let dir = tempfile::TempDir::new().expect("tempdir");
+        std::fs::create_dir(dir.path().join("web")).expect("mkdir");
+        let processes = Arc::new(BackgroundProcesses::default());
+        let mut params = start_params("pwd", "pwd");
+        params.workdir = Some("web".to_string());
+
+        let output = start_in(&processes, params, dir.path().to_path_buf())
+            .await
+            .expect("start");
+        let expected = dir.path().join("web").canonicalize().expect("canonicalize");
+        assert_eq!(
+            PathBuf::from(output.output.trim()).canonicalize().ok(),
+            Some(expected)
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn exited_process_keeps_logs_and_can_be_replaced() {
+        let processes = Arc::new(BackgroundProcesses::default());
+        let output = start(&processes, start_params("job", "echo oops >&2; exit 3"))
+            .await
+            .expect("start");
+        assert_eq!(
+            output.status,
+            BackgroundProcessStatus::Exited { exit_code: 3 }
+        );
+        assert_eq!(
+            background_process_result_into_payload(Ok(output)).success,
+            Some(false)
+        );
+
+        let logs = processes.logs("job", 1_000).expect("logs");
+        assert_eq!(logs.output, "oops\n");
+
+        let output = start(&processes, start_params("job", "echo again; sleep 30"))
+            .await
+            .expect("restart");
+        assert_eq!(output.status, BackgroundProcessStatus::Running);
+        assert_eq!(output.output, "again\n");
+        processes.stop_all().await;
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn stop_terminates_children_in_the_process_group() {
+        let processes = Arc::new(BackgroundProcesses::default());
+        let output = start(&processes, start_params("tree", "sleep 30 & echo $!; wait"))
+            .await
+            .expect("start");
+        let grandchild: i32 = output.output.trim().parse().expect("pid");
+
+        processes.stop("tree", 0).await.expect("stop");
+
+        // Give the signal a moment to be delivered. The grandchild may linger
+        // as a zombie if nothing reaps it, which still counts as terminated.
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        let stat = std::fs::read_to_string(format!("/proc/{grandchild}/stat")).unwrap_or_default();
+        let alive = stat
+            .rsplit_once(')')
+            .is_some_and(|(_, rest)| !rest.trim_start().starts_with('Z'));
+        assert!(!alive, "grandchild {grandchild} survived stop: {stat}");
+    }
+}
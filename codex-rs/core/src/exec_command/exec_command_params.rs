@@ -55,3 +55,57 @@ fn write_stdin_default_yield_time_ms() -> u64 {
 fn write_stdin_default_max_output_tokens() -> u64 {
     10_000
 }
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct StartBackgroundProcessParams {
+    pub(crate) name: String,
+    pub(crate) command: Vec<String>,
+
+    #[serde(default)]
+    pub(crate) workdir: Option<String>,
+
+    /// Regex that marks the process as ready once it matches a line of output.
+    #[serde(default)]
+    pub(crate) ready_pattern: Option<String>,
+
+    /// TCP port on localhost that marks the process as ready once it accepts
+    /// connections.
+    #[serde(default)]
+    pub(crate) ready_port: Option<u16>,
+
+    #[serde(default = "default_ready_timeout_ms")]
+    pub(crate) ready_timeout_ms: u64,
+
+    #[serde(default = "background_process_default_max_output_tokens")]
+    pub(crate) max_output_tokens: u64,
+
+    #[serde(default)]
+    pub(crate) with_escalated_permissions: Option<bool>,
+
+    #[serde(default)]
+    pub(crate) justification: Option<String>,
+}
+
+fn default_ready_timeout_ms() -> u64 {
+    30_000
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BackgroundProcessLogsParams {
+    pub(crate) name: String,
+
+    #[serde(default = "background_process_default_max_output_tokens")]
+    pub(crate) max_output_tokens: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct StopBackgroundProcessParams {
+    pub(crate) name: String,
+
+    #[serde(default = "background_process_default_max_output_tokens")]
+    pub(crate) max_output_tokens: u64,
+}
+
+fn background_process_default_max_output_tokens() -> u64 {
+    2_000
+}
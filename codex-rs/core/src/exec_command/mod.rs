@@ -1,13 +1,24 @@
+mod background_process;
 mod exec_command_params;
 mod exec_command_session;
 mod responses_api;
 mod session_id;
 mod session_manager;
 
+pub use background_process::background_process_result_into_payload;
+pub use exec_command_params::BackgroundProcessLogsParams;
 pub use exec_command_params::ExecCommandParams;
+pub use exec_command_params::StartBackgroundProcessParams;
+pub use exec_command_params::StopBackgroundProcessParams;
 pub use exec_command_params::WriteStdinParams;
+pub use responses_api::BACKGROUND_PROCESS_LOGS_TOOL_NAME;
+pub use responses_api::BACKGROUND_PROCESS_START_TOOL_NAME;
+pub use responses_api::BACKGROUND_PROCESS_STOP_TOOL_NAME;
 pub use responses_api::EXEC_COMMAND_TOOL_NAME;
 pub use responses_api::WRITE_STDIN_TOOL_NAME;
+pub use responses_api::create_background_process_logs_tool_for_responses_api;
+pub use responses_api::create_background_process_start_tool_for_responses_api;
+pub use responses_api::create_background_process_stop_tool_for_responses_api;
 pub use responses_api::create_exec_command_tool_for_responses_api;
 pub use responses_api::create_write_stdin_tool_for_responses_api;
 pub use session_manager::SessionManager as ExecSessionManager;
@@ -5,6 +5,9 @@ use crate::openai_tools::ResponsesApiTool;
 
 pub const EXEC_COMMAND_TOOL_NAME: &str = "exec_command";
 pub const WRITE_STDIN_TOOL_NAME: &str = "write_stdin";
+pub const BACKGROUND_PROCESS_START_TOOL_NAME: &str = "background_process_start";
+pub const BACKGROUND_PROCESS_LOGS_TOOL_NAME: &str = "background_process_logs";
+pub const BACKGROUND_PROCESS_STOP_TOOL_NAME: &str = "background_process_stop";
 
 pub fn create_exec_command_tool_for_responses_api() -> ResponsesApiTool {
     let mut properties = BTreeMap::<String, JsonSchema>::new();
@@ -96,3 +99,133 @@ Can write control characters (\u0003 for Ctrl-C), or an empty string to just pol
         },
     }
 }
+
+fn background_process_name_property(properties: &mut BTreeMap<String, JsonSchema>) {
+    properties.insert(
+        "name".to_string(),
+        JsonSchema::String {
+            description: Some("The name that identifies the background process.".to_string()),
+        },
+    );
+}
+
+fn max_output_tokens_property(properties: &mut BTreeMap<String, JsonSchema>) {
+    properties.insert(
+        "max_output_tokens".to_string(),
+        JsonSchema::Number {
+            description: Some(
+                "The maximum number of tokens of recent output to return. Defaults to 2000."
+                    .to_string(),
+            ),
+        },
+    );
+}
+
+pub fn create_background_process_start_tool_for_responses_api() -> ResponsesApiTool {
+    let mut properties = BTreeMap::<String, JsonSchema>::new();
+    background_process_name_property(&mut properties);
+    properties.insert(
+        "command".to_string(),
+        JsonSchema::Array {
+            items: Box::new(JsonSchema::String { description: None }),
+            description: Some("The command to run".to_string()),
+        },
+    );
+    properties.insert(
+        "workdir".to_string(),
+        JsonSchema::String {
+            description: Some("The working directory to run the command in".to_string()),
+        },
+    );
+    properties.insert(
+        "ready_pattern".to_string(),
+        JsonSchema::String {
+            description: Some(
+                "Regex that marks the process as ready once a line of its output matches."
+                    .to_string(),
+            ),
+        },
+    );
+    properties.insert(
+        "ready_port".to_string(),
+        JsonSchema::Number {
+            description: Some(
+                "TCP port on localhost that marks the process as ready once it accepts connections."
+                    .to_string(),
+            ),
+        },
+    );
+    properties.insert(
+        "ready_timeout_ms".to_string(),
+        JsonSchema::Number {
+            description: Some(
+                "How long to wait for the process to become ready. Defaults to 30000.".to_string(),
+            ),
+        },
+    );
+    max_output_tokens_property(&mut properties);
+    properties.insert(
+        "with_escalated_permissions".to_string(),
+        JsonSchema::Boolean {
+            description: Some(
+                "Whether to request running the process without sandbox restrictions.".to_string(),
+            ),
+        },
+    );
+    properties.insert(
+        "justification".to_string(),
+        JsonSchema::String {
+            description: Some(
+                "Only set if with_escalated_permissions is true. 1-sentence explanation of why the process needs it."
+                    .to_string(),
+            ),
+        },
+    );
+
+    ResponsesApiTool {
+        name: BACKGROUND_PROCESS_START_TOOL_NAME.to_owned(),
+        description: r#"Start a long-running process (e.g. a dev server or database) that keeps running after this call returns.
+If ready_pattern or ready_port is given, waits until the process is ready, exits or ready_timeout_ms elapses. Returns the process status and its output so far."#
+            .to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: Some(vec!["name".to_string(), "command".to_string()]),
+            additional_properties: Some(false),
+        },
+    }
+}
+
+pub fn create_background_process_logs_tool_for_responses_api() -> ResponsesApiTool {
+    let mut properties = BTreeMap::<String, JsonSchema>::new();
+    background_process_name_property(&mut properties);
+    max_output_tokens_property(&mut properties);
+
+    ResponsesApiTool {
+        name: BACKGROUND_PROCESS_LOGS_TOOL_NAME.to_owned(),
+        description: "Return the status (including readiness and exit code) and the most recent output of a background process.".to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: Some(vec!["name".to_string()]),
+            additional_properties: Some(false),
+        },
+    }
+}
+
+pub fn create_background_process_stop_tool_for_responses_api() -> ResponsesApiTool {
+    let mut properties = BTreeMap::<String, JsonSchema>::new();
+    background_process_name_property(&mut properties);
+    max_output_tokens_property(&mut properties);
+
+    ResponsesApiTool {
+        name: BACKGROUND_PROCESS_STOP_TOOL_NAME.to_owned(),
+        description: "Stop a background process and any processes it started.".to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: Some(vec!["name".to_string()]),
+            additional_properties: Some(false),
+        },
+    }
+}
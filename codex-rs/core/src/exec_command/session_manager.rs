@@ -1,6 +1,7 @@
 use std::collections::HashMap;
 use std::io::ErrorKind;
 use std::io::Read;
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::Mutex as StdMutex;
 use std::sync::atomic::AtomicU32;
@@ -11,20 +12,31 @@ use portable_pty::native_pty_system;
 use tokio::sync::Mutex;
 use tokio::sync::mpsc;
 use tokio::sync::oneshot;
+use tokio::sync::watch;
 use tokio::time::Duration;
 use tokio::time::Instant;
 use tokio::time::timeout;
 
+use crate::config_types::Seccomp;
+use crate::exec::SandboxType;
+use crate::exec_command::background_process::BackgroundProcessOutput;
+use crate::exec_command::background_process::BackgroundProcesses;
+use crate::exec_command::exec_command_params::BackgroundProcessLogsParams;
 use crate::exec_command::exec_command_params::ExecCommandParams;
+use crate::exec_command::exec_command_params::StartBackgroundProcessParams;
+use crate::exec_command::exec_command_params::StopBackgroundProcessParams;
 use crate::exec_command::exec_command_params::WriteStdinParams;
 use crate::exec_command::exec_command_session::ExecCommandSession;
 use crate::exec_command::session_id::SessionId;
+use crate::protocol::BackgroundProcessInfo;
+use crate::protocol::SandboxPolicy;
 use codex_protocol::models::FunctionCallOutputPayload;
 
 #[derive(Debug, Default)]
 pub struct SessionManager {
     next_session_id: AtomicU32,
     sessions: Mutex<HashMap<SessionId, ExecCommandSession>>,
+    background_processes: Arc<BackgroundProcesses>,
 }
 
 #[derive(Debug)]
@@ -175,6 +187,62 @@ impl SessionManager {
         })
     }
 
+    /// Start a named background process that keeps running after this call
+    /// returns, waiting up to `ready_timeout_ms` for its readiness check. The
+    /// process runs in `params.workdir`, resolved against `cwd`.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn handle_start_background_process_request(
+        &self,
+        params: StartBackgroundProcessParams,
+        cwd: PathBuf,
+        env: HashMap<String, String>,
+        sandbox_type: SandboxType,
+        sandbox_policy: &SandboxPolicy,
+        codex_linux_sandbox_exe: &Option<PathBuf>,
+        seccomp: &Seccomp,
+    ) -> Result<BackgroundProcessOutput, String> {
+        self.background_processes
+            .start(
+                params,
+                cwd,
+                env,
+                sandbox_type,
+                sandbox_policy,
+                codex_linux_sandbox_exe,
+                seccomp,
+            )
+            .await
+    }
+
+    /// Return the status and the most recent output of a background process.
+    pub fn handle_background_process_logs_request(
+        &self,
+        params: BackgroundProcessLogsParams,
+    ) -> Result<BackgroundProcessOutput, String> {
+        self.background_processes
+            .logs(&params.name, params.max_output_tokens)
+    }
+
+    /// Stop a background process and everything it spawned.
+    pub async fn handle_stop_background_process_request(
+        &self,
+        params: StopBackgroundProcessParams,
+    ) -> Result<BackgroundProcessOutput, String> {
+        self.background_processes
+            .stop(&params.name, params.max_output_tokens)
+            .await
+    }
+
+    pub async fn stop_all_background_processes(&self) {
+        self.background_processes.stop_all().await;
+    }
+
+    /// Snapshots of the background processes, updated whenever one starts,
+    /// becomes ready, exits or is stopped.
+    pub fn subscribe_background_processes(&self) -> watch::Receiver<Vec<BackgroundProcessInfo>> {
+        self.background_processes.subscribe()
+    }
+
     /// Write characters to a session's stdin and collect combined output for up to `yield_time_ms`.
     pub async fn handle_write_stdin_request(
         &self,
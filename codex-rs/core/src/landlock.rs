@@ -1,11 +1,91 @@
+use crate::config_types::Seccomp;
+use crate::config_types::SeccompProfile;
 use crate::protocol::SandboxPolicy;
 use crate::spawn::StdioPolicy;
 use crate::spawn::spawn_child_async;
 use std::collections::HashMap;
 use std::path::Path;
 use std::path::PathBuf;
+use std::process::ExitStatus;
 use tokio::process::Child;
 
+/// Syscalls denied by [`SeccompProfile::Hardened`].
+pub const HARDENED_SECCOMP_SYSCALLS: &[&str] = &[
+    "mount",
+    "umount2",
+    "pivot_root",
+    "unshare",
+    "setns",
+    "keyctl",
+    "add_key",
+    "request_key",
+    "bpf",
+    "perf_event_open",
+    "io_uring_setup",
+    "io_uring_enter",
+    "io_uring_register",
+    "personality",
+    "userfaultfd",
+];
+
+/// Syscalls that may be listed in `seccomp.deny`. `codex-linux-sandbox` knows
+/// the syscall number of each of these on every supported architecture.
+pub const DENYABLE_SYSCALLS: &[&str] = &[
+    "mount",
+    "umount2",
+    "pivot_root",
+    "chroot",
+    "unshare",
+    "setns",
+    "keyctl",
+    "add_key",
+    "request_key",
+    "bpf",
+    "perf_event_open",
+    "io_uring_setup",
+    "io_uring_enter",
+    "io_uring_register",
+    "personality",
+    "userfaultfd",
+    "process_vm_readv",
+    "process_vm_writev",
+    "kexec_load",
+    "init_module",
+    "finit_module",
+    "delete_module",
+    "swapon",
+    "swapoff",
+    "reboot",
+];
+
+/// Returns the syscalls that `seccomp` asks the Linux sandbox to deny in
+/// addition to the network filter, without duplicates.
+///
+/// # Errors
+/// Returns the first name in `seccomp.deny` that is not in
+/// [`DENYABLE_SYSCALLS`].
+pub fn seccomp_denied_syscalls(seccomp: &Seccomp) -> Result<Vec<String>, String> {
+    let mut denied: Vec<String> = match seccomp.profile {
+        SeccompProfile::Default => Vec::new(),
+        SeccompProfile::Hardened => HARDENED_SECCOMP_SYSCALLS
+            .iter()
+            .map(|name| name.to_string())
+            .collect(),
+    };
+    for name in &seccomp.deny {
+        if !DENYABLE_SYSCALLS.contains(&name.as_str()) {
+            return Err(format!(
+                "unsupported syscall `{name}` in seccomp.deny (supported: {})",
+                DENYABLE_SYSCALLS.join(", ")
+            ));
+        }
+        if !denied.contains(name) {
+            denied.push(name.clone());
+        }
+    }
+    Ok(denied)
+}
+
 /// Spawn a shell tool command under the Linux Landlock+seccomp sandbox helper
 /// (codex-linux-sandbox).
 ///
@@ -17,6 +97,7 @@ pub async fn spawn_command_under_linux_sandbox<P>(
     codex_linux_sandbox_exe: P,
     command: Vec<String>,
     sandbox_policy: &SandboxPolicy,
+    seccomp: &Seccomp,
     cwd: PathBuf,
     stdio_policy: StdioPolicy,
     env: HashMap<String, String>,
@@ -24,7 +105,7 @@ pub async fn spawn_command_under_linux_sandbox<P>(
 where
     P: AsRef<Path>,
 {
-    let args = create_linux_sandbox_command_args(command, sandbox_policy, &cwd);
+    let args = create_linux_sandbox_command_args(command, sandbox_policy, seccomp, &cwd, false)?;
     let arg0 = Some("codex-linux-sandbox");
     spawn_child_async(
         codex_linux_sandbox_exe.as_ref().to_path_buf(),
@@ -38,12 +119,39 @@ where
     .await
 }
 
+/// Asks the Linux sandbox helper to print the Landlock rules and seccomp
+/// filter it would install for `sandbox_policy`, without running a command.
+pub async fn print_linux_sandbox_policy<P>(
+    codex_linux_sandbox_exe: P,
+    sandbox_policy: &SandboxPolicy,
+    seccomp: &Seccomp,
+    cwd: PathBuf,
+) -> std::io::Result<ExitStatus>
+where
+    P: AsRef<Path>,
+{
+    let args = create_linux_sandbox_command_args(Vec::new(), sandbox_policy, seccomp, &cwd, true)?;
+    let mut child = spawn_child_async(
+        codex_linux_sandbox_exe.as_ref().to_path_buf(),
+        args,
+        Some("codex-linux-sandbox"),
+        cwd,
+        sandbox_policy,
+        StdioPolicy::Inherit,
+        HashMap::new(),
+    )
+    .await?;
+    child.wait().await
+}
+
 /// Converts the sandbox policy into the CLI invocation for `codex-linux-sandbox`.
 fn create_linux_sandbox_command_args(
     command: Vec<String>,
     sandbox_policy: &SandboxPolicy,
+    seccomp: &Seccomp,
     cwd: &Path,
-) -> Vec<String> {
+    print_policy: bool,
+) -> std::io::Result<Vec<String>> {
     #[expect(clippy::expect_used)]
     let sandbox_policy_cwd = cwd.to_str().expect("cwd must be valid UTF-8").to_string();
 
@@ -51,16 +159,85 @@ fn create_linux_sandbox_command_args(
     let sandbox_policy_json =
         serde_json::to_string(sandbox_policy).expect("Failed to serialize SandboxPolicy to JSON");
 
-    let mut linux_cmd: Vec<String> = vec![
+    let denied_syscalls = seccomp_denied_syscalls(seccomp).map_err(std::io::Error::other)?;
+
+    let mut linux_cmd: Vec<String> = Vec::new();
+    if print_policy {
+        linux_cmd.push("--print-policy".to_string());
+    }
+    for name in denied_syscalls {
+        linux_cmd.push("--deny-syscall".to_string());
+        linux_cmd.push(name);
+    }
+    linux_cmd.extend([
         sandbox_policy_cwd,
         sandbox_policy_json,
         // Separator so that command arguments starting with `-` are not parsed as
         // options of the helper itself.
         "--".to_string(),
-    ];
+    ]);
 
     // Append the original tool command.
     linux_cmd.extend(command);
 
-    linux_cmd
+    Ok(linux_cmd)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn hardened_profile_merges_extra_syscalls_without_duplicates() {
+        let seccomp = Seccomp {
+            profile: SeccompProfile::Hardened,
+            deny: vec!["mount".to_string(), "chroot".to_string()],
+        };
+        let denied = seccomp_denied_syscalls(&seccomp).expect("valid names");
+        assert_eq!(denied.len(), HARDENED_SECCOMP_SYSCALLS.len() + 1);
+        assert_eq!(denied.last().map(String::as_str), Some("chroot"));
+        assert!(
+            HARDENED_SECCOMP_SYSCALLS
+                .iter()
+                .all(|name| DENYABLE_SYSCALLS.contains(name))
+        );
+    }
+
+    #[test]
+    fn unknown_syscall_is_rejected() {
+        let seccomp = Seccomp {
+            profile: SeccompProfile::Default,
+            deny: vec!["clone3".to_string()],
+        };
+        let err = seccomp_denied_syscalls(&seccomp).expect_err("clone3 is not denyable");
+        assert!(err.contains("`clone3`"), "{err}");
+    }
+
+    #[test]
+    fn denied_syscalls_are_passed_before_positional_args() {
+        let seccomp = Seccomp {
+            profile: SeccompProfile::Default,
+            deny: vec!["bpf".to_string()],
+        };
+        let args = create_linux_sandbox_command_args(
+            vec!["ls".to_string()],
+            &SandboxPolicy::ReadOnly,
+            &seccomp,
+            Path::new("/repo"),
+            false,
+        )
+        .expect("valid config");
+        assert_eq!(
+            args,
+            vec![
+                "--deny-syscall".to_string(),
+                "bpf".to_string(),
+                "/repo".to_string(),
+                r#"{"mode":"read-only"}"#.to_string(),
+                "--".to_string(),
+                "ls".to_string(),
+            ]
+        );
+    }
 }
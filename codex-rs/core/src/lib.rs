@@ -11,6 +11,7 @@ pub mod bash;
 mod chat_completions;
 mod client;
 mod client_common;
+mod code_search;
 pub mod codex;
 mod codex_conversation;
 pub mod token_data;
@@ -29,11 +30,16 @@ mod flags;
 pub mod git_info;
 mod is_safe_command;
 pub mod landlock;
+mod lsp;
 mod mcp_connection_manager;
 mod mcp_tool_call;
 mod message_history;
 mod model_provider_info;
 pub mod parse_command;
+mod persistent_shell;
+mod post_edit;
+mod redact;
+mod repo_index;
 mod user_instructions;
 pub use model_provider_info::BUILT_IN_OSS_MODEL_PROVIDER_ID;
 pub use model_provider_info::ModelProviderInfo;
@@ -67,8 +73,11 @@ pub use rollout::RolloutRecorder;
 pub use rollout::SESSIONS_SUBDIR;
 pub use rollout::SessionMeta;
 pub use rollout::list::ConversationItem;
+pub use rollout::list::ConversationMessage;
+pub use rollout::list::ConversationSummary;
 pub use rollout::list::ConversationsPage;
 pub use rollout::list::Cursor;
+pub use rollout::list::MessageRole;
 mod user_notification;
 pub mod util;
 pub use apply_patch::CODEX_APPLY_PATCH_ARG1;
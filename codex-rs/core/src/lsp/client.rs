@@ -0,0 +1,547 @@
+//! A minimal Language Server Protocol client: JSON-RPC over a byte stream
+//! with `Content-Length` framing, and just enough of the protocol to keep
+//! documents in sync and ask for diagnostics, definitions and references.
+
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::Mutex as StdMutex;
+use std::sync::PoisonError;
+use std::sync::atomic::AtomicI64;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+use serde::Deserialize;
+use serde::Serialize;
+use serde_json::Value;
+use serde_json::json;
+use tokio::io::AsyncBufRead;
+use tokio::io::AsyncBufReadExt;
+use tokio::io::AsyncRead;
+use tokio::io::AsyncReadExt;
+use tokio::io::AsyncWrite;
+use tokio::io::AsyncWriteExt;
+use tokio::io::BufReader;
+use tokio::process::Child;
+use tokio::sync::Mutex;
+use tokio::sync::mpsc;
+use tokio::sync::oneshot;
+use tokio::sync::watch;
+use tokio::time::Instant;
+use tracing::debug;
+use url::Url;
+
+/// How long to wait for a response to a request, including `initialize`.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Servers often publish diagnostics in several rounds (e.g. rust-analyzer
+/// reports syntax errors first and `cargo check` results later). Once a
+/// document has diagnostics, keep collecting until the server has been quiet
+/// for this long.
+const DIAGNOSTICS_SETTLE: Duration = Duration::from_millis(500);
+
+const SEVERITY_ERROR: u8 = 1;
+const SEVERITY_WARNING: u8 = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct Position {
+    /// Zero-based line.
+    pub(crate) line: u32,
+    /// Zero-based offset in UTF-16 code units.
+    pub(crate) character: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct Range {
+    pub(crate) start: Position,
+    pub(crate) end: Position,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub(crate) struct Diagnostic {
+    pub(crate) range: Range,
+    #[serde(default)]
+    pub(crate) severity: Option<u8>,
+    pub(crate) message: String,
+    #[serde(default)]
+    pub(crate) source: Option<String>,
+    #[serde(default)]
+    pub(crate) code: Option<Value>,
+}
+
+impl Diagnostic {
+    pub(crate) fn severity_label(&self) -> &'static str {
+        match self.severity {
+            Some(SEVERITY_ERROR) => "error",
+            Some(SEVERITY_WARNING) => "warning",
+            Some(3) => "info",
+            Some(4) => "hint",
+            _ => "diagnostic",
+        }
+    }
+
+    /// Whether this is an error or a warning (servers that omit the severity
+    /// are treated as reporting errors).
+    pub(crate) fn is_problem(&self) -> bool {
+        matches!(
+            self.severity,
+            None | Some(SEVERITY_ERROR | SEVERITY_WARNING)
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Location {
+    pub(crate) path: PathBuf,
+    pub(crate) range: Range,
+}
+
+struct Document {
+    version: i32,
+    text: String,
+}
+
+/// Diagnostics most recently published for a document, tagged with the
+/// value of the publish counter at the time.
+struct Published {
+    generation: u64,
+    diagnostics: Vec<Diagnostic>,
+}
+
+type PendingSender = oneshot::Sender<Result<Value, String>>;
+
+/// State shared with the task reading from the server.
+struct Shared {
+    /// Messages for the task writing to the server. The reader answers server
+    /// requests through it too, so it never waits for a write to finish.
+    outgoing: mpsc::UnboundedSender<Value>,
+    pending: StdMutex<HashMap<i64, PendingSender>>,
+    published: StdMutex<HashMap<Url, Published>>,
+    /// Counts `textDocument/publishDiagnostics` notifications.
+    generation: watch::Sender<u64>,
+}
+
+pub(crate) struct LspClient {
+    shared: Arc<Shared>,
+    documents: Mutex<HashMap<PathBuf, Document>>,
+    next_id: AtomicI64,
+    /// Kept so that the server is killed when the client is dropped.
+    _child: Option<Child>,
+}
+
+impl LspClient {
+    /// Starts the `initialize` handshake with a server reachable through
+    /// `reader` and `writer`, rooted at `root`.
+    pub(crate) async fn start(
+        reader: impl AsyncRead + Send + Unpin + 'static,
+        writer: impl AsyncWrite + Send + Unpin + 'static,
+        child: Option<Child>,
+        root: &Path,
+    ) -> io::Result<Self> {
+        let (outgoing, outgoing_rx) = mpsc::unbounded_channel();
+        tokio::spawn(write_loop(writer, outgoing_rx));
+        let shared = Arc::new(Shared {
+            outgoing,
+            pending: StdMutex::new(HashMap::new()),
+            published: StdMutex::new(HashMap::new()),
+            generation: watch::Sender::new(0),
+        });
+        tokio::spawn(read_loop(BufReader::new(reader), Arc::clone(&shared)));
+        let client = Self {
+            shared,
+            documents: Mutex::new(HashMap::new()),
+            next_id: AtomicI64::new(1),
+            _child: child,
+        };
+
+        let root_uri = path_to_uri(root)?;
+        let name = root
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        client
+            .request(
+                "initialize",
+                json!({
+                    "processId": std::process::id(),
+                    "clientInfo": { "name": "codex" },
+                    "rootUri": root_uri,
+                    "workspaceFolders": [{ "uri": root_uri, "name": name }],
+                    "capabilities": {
+                        "textDocument": {
+                            "synchronization": { "didSave": true },
+                            "publishDiagnostics": { "versionSupport": true },
+                            "definition": { "linkSupport": true },
+                            "references": {},
+                        },
+                        "workspace": { "configuration": true, "workspaceFolders": true },
+                    },
+                }),
+            )
+            .await?;
+        client.shared.notify("initialized", json!({}))?;
+        Ok(client)
+    }
+
+    /// Returns the current diagnostics for `paths`, waiting up to `wait` for
+    /// the server to publish diagnostics for documents that changed on disk
+    /// since it last saw them.
+    pub(crate) async fn diagnostics(
+        &self,
+        paths: &[(PathBuf, String)],
+        wait: Duration,
+    ) -> io::Result<Vec<(PathBuf, Vec<Diagnostic>)>> {
+        let mut generation = self.shared.generation.subscribe();
+        let start = *generation.borrow_and_update();
+        let mut waiting = Vec::new();
+        for (path, language_id) in paths {
+            let uri = path_to_uri(path)?;
+            if self.sync(path, language_id).await? || !self.shared.has_published(&uri) {
+                waiting.push(uri);
+            }
+        }
+
+        let deadline = Instant::now() + wait;
+        let mut settle_until = None;
+        while !waiting.is_empty() || settle_until.is_some() {
+            let until = settle_until.map_or(deadline, |settle: Instant| settle.min(deadline));
+            match tokio::time::timeout_at(until, generation.changed()).await {
+                Ok(Ok(())) => {}
+                // Timed out, or the server exited.
+                Ok(Err(_)) | Err(_) => break,
+            }
+            waiting.retain(|uri| !self.shared.published_since(uri, start));
+            if waiting.is_empty() {
+                settle_until = Some(Instant::now() + DIAGNOSTICS_SETTLE);
+            }
+        }
+
+        paths
+            .iter()
+            .map(|(path, _)| {
+                let diagnostics = self.known_diagnostics(path)?.unwrap_or_default();
+                Ok((path.clone(), diagnostics))
+            })
+            .collect()
+    }
+
+    /// The diagnostics last published for `path`, without syncing it first,
+    /// or `None` if the server has not reported on it yet.
+    pub(crate) fn known_diagnostics(&self, path: &Path) -> io::Result<Option<Vec<Diagnostic>>> {
+        let uri = path_to_uri(path)?;
+        Ok(self
+            .shared
+            .lock_published()
+            .get(&uri)
+            .map(|published| published.diagnostics.clone()))
+    }
+
+    pub(crate) async fn definition(
+        &self,
+        path: &Path,
+        language_id: &str,
+        position: Position,
+    ) -> io::Result<Vec<Location>> {
+        self.sync(path, language_id).await?;
+        let result = self
+            .request(
+                "textDocument/definition",
+                json!({
+                    "textDocument": { "uri": path_to_uri(path)? },
+                    "position": position,
+                }),
+            )
+            .await?;
+        Ok(parse_locations(result))
+    }
+
+    pub(crate) async fn references(
+        &self,
+        path: &Path,
+        language_id: &str,
+        position: Position,
+    ) -> io::Result<Vec<Location>> {
+        self.sync(path, language_id).await?;
+        let result = self
+            .request(
+                "textDocument/references",
+                json!({
+                    "textDocument": { "uri": path_to_uri(path)? },
+                    "position": position,
+                    "context": { "includeDeclaration": true },
+                }),
+            )
+            .await?;
+        Ok(parse_locations(result))
+    }
+
+    /// Tells the server about the current contents of `path`. Returns whether
+    /// the server's copy changed.
+    async fn sync(&self, path: &Path, language_id: &str) -> io::Result<bool> {
+        let text = tokio::fs::read_to_string(path).await?;
+        let uri = path_to_uri(path)?;
+        let mut documents = self.documents.lock().await;
+        match documents.get_mut(path) {
+            None => {
+                self.shared.notify(
+                    "textDocument/didOpen",
+                    json!({
+                        "textDocument": {
+                            "uri": uri,
+                            "languageId": language_id,
+                            "version": 1,
+                            "text": text,
+                        },
+                    }),
+                )?;
+                documents.insert(path.to_path_buf(), Document { version: 1, text });
+            }
+            Some(document) if document.text == text => return Ok(false),
+            Some(document) => {
+                document.version += 1;
+                self.shared.notify(
+                    "textDocument/didChange",
+                    json!({
+                        "textDocument": { "uri": uri, "version": document.version },
+                        "contentChanges": [{ "text": text }],
+                    }),
+                )?;
+                // Some servers (e.g. rust-analyzer) only run their full
+                // checks when a file is saved.
+                self.shared.notify(
+                    "textDocument/didSave",
+                    json!({ "textDocument": { "uri": uri } }),
+                )?;
+                document.text = text;
+            }
+        }
+        Ok(true)
+    }
+
+    async fn request(&self, method: &str, params: Value) -> io::Result<Value> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (tx, rx) = oneshot::channel();
+        self.shared.lock_pending().insert(id, tx);
+        self.shared
+            .send(json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params }))?;
+        match tokio::time::timeout(REQUEST_TIMEOUT, rx).await {
+            Ok(Ok(Ok(result))) => Ok(result),
+            Ok(Ok(Err(message))) => Err(io::Error::other(format!("{method} failed: {message}"))),
+            Ok(Err(_)) => Err(io::Error::new(
+                io::ErrorKind::BrokenPipe,
+                "the language server exited",
+            )),
+            Err(_) => {
+                self.shared.lock_pending().remove(&id);
+                Err(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    format!("{method} timed out"),
+                ))
+            }
+        }
+    }
+}
+
+impl Shared {
+    fn lock_pending(&self) -> std::sync::MutexGuard<'_, HashMap<i64, PendingSender>> {
+        self.pending.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn lock_published(&self) -> std::sync::MutexGuard<'_, HashMap<Url, Published>> {
+        self.published
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn has_published(&self, uri: &Url) -> bool {
+        self.lock_published().contains_key(uri)
+    }
+
+    fn published_since(&self, uri: &Url, generation: u64) -> bool {
+        self.lock_published()
+            .get(uri)
+            .is_some_and(|published| published.generation > generation)
+    }
+
+    fn notify(&self, method: &str, params: Value) -> io::Result<()> {
+        self.send(json!({ "jsonrpc": "2.0", "method": method, "params": params }))
+    }
+
+    /// Queues `message` for the writer task.
+    fn send(&self, message: Value) -> io::Result<()> {
+        self.outgoing
+            .send(message)
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "the language server exited"))
+    }
+
+    /// Handles a message from the server: a response to one of our requests,
+    /// a request of its own, or a notification.
+    fn handle(&self, message: Value) {
+        let method = message.get("method").and_then(Value::as_str);
+        let id = message.get("id");
+        match (method, id) {
+            (None, Some(id)) => {
+                let Some(id) = id.as_i64() else {
+                    return;
+                };
+                let Some(tx) = self.lock_pending().remove(&id) else {
+                    return;
+                };
+                let result = match message.get("error") {
+                    Some(error) => Err(error
+                        .get("message")
+                        .and_then(Value::as_str)
+                        .unwrap_or("unknown error")
+                        .to_string()),
+                    None => Ok(message.get("result").cloned().unwrap_or(Value::Null)),
+                };
+                let _ = tx.send(result);
+            }
+            (Some(method), Some(id)) => {
+                // We do not implement any server-to-client requests, but
+                // servers wait for an answer, so send back empty results.
+                let result = match method {
+                    "workspace/configuration" => {
+                        let items = message
+                            .pointer("/params/items")
+                            .and_then(Value::as_array)
+                            .map_or(0, Vec::len);
+                        Value::Array(vec![Value::Null; items])
+                    }
+                    _ => Value::Null,
+                };
+                let response = json!({ "jsonrpc": "2.0", "id": id, "result": result });
+                if let Err(err) = self.send(response) {
+                    debug!("failed to answer language server request {method}: {err}");
+                }
+            }
+            (Some("textDocument/publishDiagnostics"), None) => {
+                #[derive(Deserialize)]
+                struct Params {
+                    uri: Url,
+                    diagnostics: Vec<Diagnostic>,
+                }
+                let Some(params) = message.get("params").cloned() else {
+                    return;
+                };
+                let Ok(Params { uri, diagnostics }) = serde_json::from_value::<Params>(params)
+                else {
+                    return;
+                };
+                let generation = *self.generation.borrow() + 1;
+                self.lock_published().insert(
+                    uri,
+                    Published {
+                        generation,
+                        diagnostics,
+                    },
+                );
+                self.generation.send_replace(generation);
+            }
+            _ => {}
+        }
+    }
+}
+
+async fn write_loop(
+    mut writer: impl AsyncWrite + Unpin,
+    mut outgoing: mpsc::UnboundedReceiver<Value>,
+) {
+    while let Some(message) = outgoing.recv().await {
+        if let Err(err) = write_message(&mut writer, &message).await {
+            debug!("failed to write to language server: {err}");
+            break;
+        }
+    }
+}
+
+async fn read_loop(mut reader: impl AsyncBufRead + Unpin, shared: Arc<Shared>) {
+    loop {
+        match read_message(&mut reader).await {
+            Ok(Some(message)) => shared.handle(message),
+            Ok(None) => break,
+            Err(err) => {
+                debug!("failed to read from language server: {err}");
+                break;
+            }
+        }
+    }
+    // Dropping the senders fails every outstanding request.
+    shared.lock_pending().clear();
+}
+
+pub(crate) async fn read_message(
+    reader: &mut (impl AsyncBufRead + Unpin),
+) -> io::Result<Option<Value>> {
+    let mut content_length = None;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).await? == 0 {
+            return Ok(None);
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            if content_length.is_some() {
+                break;
+            }
+            continue;
+        }
+        if let Some((name, value)) = line.split_once(':')
+            && name.eq_ignore_ascii_case("content-length")
+        {
+            content_length = value.trim().parse::<usize>().ok();
+        }
+    }
+    let mut body = vec![0; content_length.unwrap_or_default()];
+    reader.read_exact(&mut body).await?;
+    serde_json::from_slice(&body)
+        .map(Some)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+pub(crate) async fn write_message(
+    writer: &mut (impl AsyncWrite + Unpin),
+    message: &Value,
+) -> io::Result<()> {
+    let body = message.to_string();
+    let header = format!("Content-Length: {}\r\n\r\n", body.len());
+    writer.write_all(header.as_bytes()).await?;
+    writer.write_all(body.as_bytes()).await?;
+    writer.flush().await
+}
+
+fn path_to_uri(path: &Path) -> io::Result<Url> {
+    Url::from_file_path(path).map_err(|()| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("not an absolute path: {}", path.display()),
+        )
+    })
+}
+
+/// Parses the result of a definition or references request: `null`, a
+/// `Location`, or an array of `Location`s or `LocationLink`s. Locations
+/// outside the file system are skipped.
+fn parse_locations(result: Value) -> Vec<Location> {
+    let items = match result {
+        Value::Array(items) => items,
+        Value::Null => Vec::new(),
+        item => vec![item],
+    };
+    items
+        .into_iter()
+        .filter_map(|item| {
+            let (uri, range) = match item.get("targetUri") {
+                Some(uri) => (uri, item.get("targetSelectionRange")?),
+                None => (item.get("uri")?, item.get("range")?),
+            };
+            let uri = Url::parse(uri.as_str()?).ok()?;
+            Some(Location {
+                path: uri.to_file_path().ok()?,
+                range: serde_json::from_value(range.clone()).ok()?,
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,814 @@
+//! Language servers configured under `[lsp_servers]`, started on first use
+//! for the session's working directory, and the `diagnostics`,
+//! `find_definition` and `find_references` tools backed by them.
+
+mod client;
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::Deserialize;
+use tokio::process::Command;
+use tokio::sync::OnceCell;
+
+use crate::config_types::LspServerConfig;
+use crate::openai_tools::JsonSchema;
+use crate::openai_tools::OpenAiTool;
+use crate::openai_tools::ResponsesApiTool;
+use crate::post_edit::FilePatterns;
+use client::Diagnostic;
+use client::Location;
+use client::LspClient;
+use client::Position;
+
+pub(crate) const DIAGNOSTICS_TOOL_NAME: &str = "diagnostics";
+pub(crate) const FIND_DEFINITION_TOOL_NAME: &str = "find_definition";
+pub(crate) const FIND_REFERENCES_TOOL_NAME: &str = "find_references";
+
+const DEFAULT_DIAGNOSTICS_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Locations listed per lookup; a popular symbol can have thousands of
+/// references.
+const MAX_LOCATIONS: usize = 50;
+
+struct Server {
+    name: String,
+    config: LspServerConfig,
+    files: FilePatterns,
+    /// Started on first use. A server that fails to start is not retried.
+    client: OnceCell<Result<Arc<LspClient>, String>>,
+}
+
+/// The language servers of a session.
+pub(crate) struct LspManager {
+    root: PathBuf,
+    servers: Vec<Server>,
+}
+
+impl LspManager {
+    pub(crate) fn new(root: PathBuf, servers: &BTreeMap<String, LspServerConfig>) -> Self {
+        let servers = servers
+            .iter()
+            .map(|(name, config)| Server {
+                name: name.clone(),
+                files: FilePatterns::new(&config.files),
+                config: config.clone(),
+                client: OnceCell::new(),
+            })
+            .collect();
+        Self { root, servers }
+    }
+
+    /// Handles a call to one of the LSP tools. Relative paths are resolved
+    /// against `cwd`.
+    pub(crate) async fn handle_tool_call(
+        &self,
+        cwd: &Path,
+        name: &str,
+        arguments: &str,
+    ) -> Result<String, String> {
+        let parse_error = |e: serde_json::Error| format!("failed to parse function arguments: {e}");
+        match name {
+            DIAGNOSTICS_TOOL_NAME => {
+                #[derive(Deserialize)]
+                struct Args {
+                    paths: Vec<String>,
+                }
+                let args: Args = serde_json::from_str(arguments).map_err(parse_error)?;
+                let paths: Vec<PathBuf> = args.paths.iter().map(|p| cwd.join(p)).collect();
+                Ok(self.diagnostics(cwd, &paths).await)
+            }
+            FIND_DEFINITION_TOOL_NAME | FIND_REFERENCES_TOOL_NAME => {
+                #[derive(Deserialize)]
+                struct Args {
+                    path: String,
+                    line: u32,
+                    symbol: String,
+                }
+                let args: Args = serde_json::from_str(arguments).map_err(parse_error)?;
+                let path = cwd.join(&args.path);
+                let references = name == FIND_REFERENCES_TOOL_NAME;
+                self.locate(cwd, &path, args.line, &args.symbol, references)
+                    .await
+            }
+            _ => Err(format!("unsupported call: {name}")),
+        }
+    }
+
+    /// Describes the current diagnostics for `paths`.
+    async fn diagnostics(&self, cwd: &Path, paths: &[PathBuf]) -> String {
+        let (groups, unhandled) = self.group_by_server(paths);
+        let mut out = String::new();
+        for path in unhandled {
+            out.push_str(&format!(
+                "{}: no language server is configured for this file\n",
+                display_path(cwd, path)
+            ));
+        }
+        for (server, paths) in groups {
+            let language_ids = with_language_ids(server, paths);
+            let result = match self.client(server).await {
+                Ok(client) => client.diagnostics(&language_ids, server.timeout()).await,
+                Err(err) => {
+                    for (path, _) in &language_ids {
+                        out.push_str(&format!("{}: {err}\n", display_path(cwd, path)));
+                    }
+                    continue;
+                }
+            };
+            match result {
+                Ok(files) => {
+                    for (path, diagnostics) in files {
+                        if diagnostics.is_empty() {
+                            out.push_str(&format!(
+                                "{}: no diagnostics\n",
+                                display_path(cwd, &path)
+                            ));
+                        }
+                        for diagnostic in &diagnostics {
+                            out.push_str(&format_diagnostic(cwd, &path, diagnostic));
+                        }
+                    }
+                }
+                Err(err) => out.push_str(&format!("{}: {err}\n", server.name)),
+            }
+        }
+        out
+    }
+
+    /// Returns the errors and warnings in `files` that were not reported
+    /// before they changed, for appending to the output of a patch. Files in
+    /// `created` did not exist before, so all of their problems are new.
+    /// Other files the server never reported on have no baseline to compare
+    /// with and only get one for later edits. Files not handled by any server
+    /// are ignored.
+    pub(crate) async fn new_diagnostics(
+        &self,
+        cwd: &Path,
+        files: &[PathBuf],
+        created: &[PathBuf],
+    ) -> Option<String> {
+        let files: Vec<PathBuf> = files.iter().filter(|f| f.is_file()).cloned().collect();
+        let mut out = String::new();
+        for (server, paths) in self.group_by_server(&files).0 {
+            let Ok(client) = self.client(server).await else {
+                continue;
+            };
+            let before: Vec<Option<Vec<Diagnostic>>> = paths
+                .iter()
+                .map(|path| match client.known_diagnostics(path) {
+                    Ok(None) if created.iter().any(|c| c == path) => Some(Vec::new()),
+                    Ok(before) => before,
+                    Err(_) => None,
+                })
+                .collect();
+            let language_ids = with_language_ids(server, paths);
+            let Ok(after) = client.diagnostics(&language_ids, server.timeout()).await else {
+                continue;
+            };
+            for ((path, diagnostics), before) in after.iter().zip(&before) {
+                let Some(before) = before else {
+                    continue;
+                };
+                for diagnostic in diagnostics {
+                    let known = before.iter().any(|old| {
+                        old.severity == diagnostic.severity && old.message == diagnostic.message
+                    });
+                    if diagnostic.is_problem() && !known {
+                        out.push_str(&format_diagnostic(cwd, path, diagnostic));
+                    }
+                }
+            }
+        }
+        (!out.is_empty()).then(|| format!("New diagnostics in the edited files:\n{out}"))
+    }
+
+    async fn locate(
+        &self,
+        cwd: &Path,
+        path: &Path,
+        line: u32,
+        symbol: &str,
+        references: bool,
+    ) -> Result<String, String> {
+        let server = self
+            .server_for(path)
+            .ok_or_else(|| format!("no language server is configured for {}", path.display()))?;
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("failed to read {}: {e}", path.display()))?;
+        let position = find_symbol(&text, line, symbol).ok_or_else(|| {
+            format!(
+                "`{symbol}` does not appear on line {line} of {}",
+                display_path(cwd, path)
+            )
+        })?;
+
+        let client = self.client(server).await?;
+        let language_id = language_id(server, path);
+        let locations = if references {
+            client.references(path, &language_id, position).await
+        } else {
+            client.definition(path, &language_id, position).await
+        }
+        .map_err(|e| e.to_string())?;
+
+        if locations.is_empty() {
+            let what = if references {
+                "references"
+            } else {
+                "definition"
+            };
+            return Ok(format!("No {what} found for `{symbol}`."));
+        }
+        Ok(format_locations(cwd, &locations))
+    }
+
+    fn server_for(&self, path: &Path) -> Option<&Server> {
+        let relative = path.strip_prefix(&self.root).unwrap_or(path);
+        self.servers
+            .iter()
+            .find(|server| server.files.matches(relative))
+    }
+
+    /// Groups `paths` by the server handling them. Also returns the paths
+    /// that no server handles.
+    fn group_by_server<'a>(
+        &'a self,
+        paths: &'a [PathBuf],
+    ) -> (Vec<(&'a Server, Vec<&'a Path>)>, Vec<&'a Path>) {
+        let mut groups: Vec<(&Server, Vec<&Path>)> = Vec::new();
+        let mut unhandled = Vec::new();
+        for path in paths {
+            let Some(server) = self.server_for(path) else {
+                unhandled.push(path.as_path());
+                continue;
+            };
+            match groups.iter_mut().find(|(s, _)| std::ptr::eq(*s, server)) {
+                Some((_, group)) => group.push(path),
+                None => groups.push((server, vec![path])),
+            }
+        }
+        (groups, unhandled)
+    }
+
+    async fn client(&self, server: &Server) -> Result<Arc<LspClient>, String> {
+        server
+            .client
+            .get_or_init(|| async {
+                start_server(&server.config, &self.root)
+                    .await
+                    .map(Arc::new)
+                    .map_err(|e| format!("failed to start language server `{}`: {e}", server.name))
+            })
+            .await
+            .clone()
+    }
+}
+
+impl Server {
+    fn timeout(&self) -> Duration {
+        self.config
+            .diagnostics_timeout_ms
+            .map(Duration::from_millis)
+            .unwrap_or(DEFAULT_DIAGNOSTICS_TIMEOUT)
+    }
+}
+
+async fn start_server(config: &LspServerConfig, root: &Path) -> std::io::Result<LspClient> {
+    let mut command = Command::new(&config.command);
+    command
+        .args(&config.args)
+        .current_dir(root)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .kill_on_drop(true);
+    if let Some(env) = &config.env {
+        command.envs(env);
+    }
+    let mut child = command.spawn()?;
+    let (Some(stdout), Some(stdin)) = (child.stdout.take(), child.stdin.take()) else {
+        return Err(std::io::Error::other(
+            "failed to capture the server's stdio",
+        ));
+    };
+    LspClient::start(stdout, stdin, Some(child), root).await
+}
+
+fn with_language_ids(server: &Server, paths: Vec<&Path>) -> Vec<(PathBuf, String)> {
+    paths
+        .into_iter()
+        .map(|path| (path.to_path_buf(), language_id(server, path)))
+        .collect()
+}
+
+fn language_id(server: &Server, path: &Path) -> String {
+    if let Some(language_id) = &server.config.language_id {
+        return language_id.clone();
+    }
+    let extension = path
+        .extension()
+        .map(|ext| ext.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    match extension.as_str() {
+        "rs" => "rust",
+        "py" | "pyi" => "python",
+        "ts" | "mts" | "cts" => "typescript",
+        "tsx" => "typescriptreact",
+        "js" | "mjs" | "cjs" => "javascript",
+        "jsx" => "javascriptreact",
+        "go" => "go",
+        "c" | "h" => "c",
+        "cc" | "cpp" | "cxx" | "hpp" => "cpp",
+        "rb" => "ruby",
+        "java" => "java",
+        "sh" | "bash" => "shellscript",
+        other => other,
+    }
+    .to_string()
+}
+
+/// Finds the position of `symbol` on the 1-based `line` of `text`.
+fn find_symbol(text: &str, line: u32, symbol: &str) -> Option<Position> {
+    let index = usize::try_from(line.checked_sub(1)?).ok()?;
+    let line_text = text.lines().nth(index)?;
+    let byte_offset = find_word(line_text, symbol)?;
+    let character = line_text[..byte_offset].encode_utf16().count();
+    Some(Position {
+        line: line - 1,
+        character: u32::try_from(character).ok()?,
+    })
+}
+
+/// Finds `word` in `line`, preferring an occurrence that is not part of a
+/// longer identifier.
+fn find_word(line: &str, word: &str) -> Option<usize> {
+    if word.is_empty() {
+        return None;
+    }
+    let is_ident = |c: char| c.is_alphanumeric() || c == '_';
+    let mut first = None;
+    for (offset, _) in line.match_indices(word) {
+        first.get_or_insert(offset);
+        let before = line[..offset].chars().next_back();
+        let after = line[offset + word.len()..].chars().next();
+        if !before.is_some_and(is_ident) && !after.is_some_and(is_ident) {
+            return Some(offset);
+        }
+    }
+    first
+}
+
+fn display_path(cwd: &Path, path: &Path) -> String {
+    path.strip_prefix(cwd).unwrap_or(path).display().to_string()
+}
+
+/// Converts a zero-based LSP position to a 1-based line and column
+/// (counting characters rather than UTF-16 code units).
+fn line_and_column(text: Option<&str>, position: Position) -> (usize, usize) {
+    let line = position.line as usize;
+    let column = text
+        .and_then(|text| text.lines().nth(line))
+        .map(|line_text| {
+            let mut units = 0;
+            line_text
+                .chars()
+                .take_while(|c| {
+                    units += c.len_utf16();
+                    units <= position.character as usize
+                })
+                .count()
+        })
+        .unwrap_or(position.character as usize);
+    (line + 1, column + 1)
+}
+
+fn format_diagnostic(cwd: &Path, path: &Path, diagnostic: &Diagnostic) -> String {
+    let text = std::fs::read_to_string(path).ok();
+    let (line, column) = line_and_column(text.as_deref(), diagnostic.range.start);
+    let mut origin: Vec<String> = diagnostic.source.iter().cloned().collect();
+    match &diagnostic.code {
+        Some(serde_json::Value::String(code)) => origin.push(code.clone()),
+        Some(serde_json::Value::Number(code)) => origin.push(code.to_string()),
+        _ => {}
+    }
+    let origin = if origin.is_empty() {
+        String::new()
+    } else {
+        format!(" [{}]", origin.join(" "))
+    };
+    // Multi-line messages (e.g. rustc notes) are indented under the location.
+    let message = diagnostic.message.trim_end().replace('\n', "\n    ");
+    format!(
+        "{}:{line}:{column}: {}: {message}{origin}\n",
+        display_path(cwd, path),
+        diagnostic.severity_label()
+    )
+}
+
+/// Lists `locations` as `path:line:column: <source line>`.
+fn format_locations(cwd: &Path, locations: &[Location]) -> String {
+    let mut files: BTreeMap<&Path, Option<String>> = BTreeMap::new();
+    let mut out = String::new();
+    for location in locations.iter().take(MAX_LOCATIONS) {
+        let text = files
+            .entry(&location.path)
+            .or_insert_with(|| std::fs::read_to_string(&location.path).ok());
+        let (line, column) = line_and_column(text.as_deref(), location.range.start);
+        let source = text
+            .as_deref()
+            .and_then(|text| text.lines().nth(line - 1))
+            .map(str::trim)
+            .unwrap_or_default();
+        out.push_str(&format!(
+            "{}:{line}:{column}: {source}\n",
+            display_path(cwd, &location.path)
+        ));
+    }
+    if locations.len() > MAX_LOCATIONS {
+        let more = locations.len() - MAX_LOCATIONS;
+        out.push_str(&format!("[... {more} more locations ...]\n"));
+    }
+    out
+}
+
+pub(crate) fn create_lsp_tools() -> Vec<OpenAiTool> {
+    let mut diagnostics_properties = BTreeMap::new();
+    diagnostics_properties.insert(
+        "paths".to_string(),
+        JsonSchema::Array {
+            items: Box::new(JsonSchema::String { description: None }),
+            description: Some("Files to check, relative to the working directory.".to_string()),
+        },
+    );
+    let diagnostics = ResponsesApiTool {
+        name: DIAGNOSTICS_TOOL_NAME.to_string(),
+        description: "Returns the errors and warnings that the project's language server reports for the given files. Much faster than running a full build after an edit.".to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties: diagnostics_properties,
+            required: Some(vec!["paths".to_string()]),
+            additional_properties: Some(false),
+        },
+    };
+
+    let lookup = |name: &str, description: &str| {
+        let mut properties = BTreeMap::new();
+        properties.insert(
+            "path".to_string(),
+            JsonSchema::String {
+                description: Some(
+                    "File containing the symbol, relative to the working directory.".to_string(),
+                ),
+            },
+        );
+        properties.insert(
+            "line".to_string(),
+            JsonSchema::Number {
+                description: Some("1-based line on which the symbol appears.".to_string()),
+            },
+        );
+        properties.insert(
+            "symbol".to_string(),
+            JsonSchema::String {
+                description: Some(
+                    "The identifier to look up, as written on that line.".to_string(),
+                ),
+            },
+        );
+        ResponsesApiTool {
+            name: name.to_string(),
+            description: description.to_string(),
+            strict: false,
+            parameters: JsonSchema::Object {
+                properties,
+                required: Some(vec![
+                    "path".to_string(),
+                    "line".to_string(),
+                    "symbol".to_string(),
+                ]),
+                additional_properties: Some(false),
+            },
+        }
+    };
+
+    vec![
+        OpenAiTool::Function(diagnostics),
+        OpenAiTool::Function(lookup(
+            FIND_DEFINITION_TOOL_NAME,
+            "Finds where a symbol used in a file is defined, using the project's language server.",
+        )),
+        OpenAiTool::Function(lookup(
+            FIND_REFERENCES_TOOL_NAME,
+            "Lists every reference to a symbol across the project, using the project's language server.",
+        )),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use client::read_message;
+    use client::write_message;
+    use pretty_assertions::assert_eq;
+    use serde_json::Value;
+    use serde_json::json;
+    use std::collections::HashMap;
+    use tempfile::TempDir;
+    use tokio::io::BufReader;
+
+    /// A language server that reports an error for every line containing
+    /// `ERROR`, a warning for every line containing `WARN`, resolves a
+    /// definition to the line declaring `fn <name>` and a reference to every
+    /// line mentioning the name.
+    async fn stub_server(reader: tokio::io::DuplexStream, mut writer: tokio::io::DuplexStream) {
+        let mut reader = BufReader::new(reader);
+        let mut documents: HashMap<String, String> = HashMap::new();
+        while let Ok(Some(message)) = read_message(&mut reader).await {
+            let method = message["method"].as_str().unwrap_or_default();
+            let params = &message["params"];
+            let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+            let reply =
+                |result: Value| json!({ "jsonrpc": "2.0", "id": message["id"], "result": result });
+            let response = match method {
+                "initialize" => reply(json!({ "capabilities": {} })),
+                "textDocument/didOpen" | "textDocument/didChange" => {
+                    let text = match method {
+                        "textDocument/didOpen" => &params["textDocument"]["text"],
+                        _ => &params["contentChanges"][0]["text"],
+                    };
+                    let text = text.as_str().unwrap_or_default().to_string();
+                    let diagnostics: Vec<Value> = text
+                        .lines()
+                        .enumerate()
+                        .flat_map(|(line, line_text)| {
+                            [("ERROR", 1), ("WARN", 2)].into_iter().filter_map(
+                                move |(word, severity)| {
+                                    let column = line_text.find(word)?;
+                                    let start = json!({ "line": line, "character": column });
+                                    Some(json!({
+                                        "range": { "start": start, "end": start },
+                                        "severity": severity,
+                                        "message": format!("found {word}"),
+                                        "source": "stub",
+                                    }))
+                                },
+                            )
+                        })
+                        .collect();
+                    documents.insert(uri.to_string(), text);
+                    json!({
+                        "jsonrpc": "2.0",
+                        "method": "textDocument/publishDiagnostics",
+                        "params": { "uri": uri, "diagnostics": diagnostics },
+                    })
+                }
+                "textDocument/definition" | "textDocument/references" => {
+                    let text = documents.get(uri).cloned().unwrap_or_default();
+                    let line = params["position"]["line"].as_u64().unwrap_or_default() as usize;
+                    let character =
+                        params["position"]["character"].as_u64().unwrap_or_default() as usize;
+                    let word: String = text.lines().nth(line).unwrap_or_default()[character..]
+                        .chars()
+                        .take_while(|c| c.is_alphanumeric() || *c == '_')
+                        .collect();
+                    let needle = if method == "textDocument/definition" {
+                        format!("fn {word}")
+                    } else {
+                        word.clone()
+                    };
+                    let locations: Vec<Value> = text
+                        .lines()
+                        .enumerate()
+                        .filter_map(|(line, line_text)| {
+                            let column = line_text.find(&needle)? + needle.len() - word.len();
+                            let start = json!({ "line": line, "character": column });
+                            Some(json!({ "uri": uri, "range": { "start": start, "end": start } }))
+                        })
+                        .collect();
+                    reply(Value::Array(locations))
+                }
+                _ => continue,
+            };
+            write_message(&mut writer, &response).await.unwrap();
+        }
+    }
+
+    async fn manager_with_stub(root: &Path) -> LspManager {
+        let (client_reader, server_writer) = tokio::io::duplex(64 * 1024);
+        let (server_reader, client_writer) = tokio::io::duplex(64 * 1024);
+        tokio::spawn(stub_server(server_reader, server_writer));
+        let client = LspClient::start(client_reader, client_writer, None, root)
+            .await
+            .unwrap();
+        let config = LspServerConfig {
+            command: "stub".to_string(),
+            args: Vec::new(),
+            env: None,
+            files: vec!["*.rs".to_string()],
+            language_id: None,
+            diagnostics_timeout_ms: Some(2000),
+        };
+        LspManager {
+            root: root.to_path_buf(),
+            servers: vec![Server {
+                name: "stub".to_string(),
+                files: FilePatterns::new(&config.files),
+                config,
+                client: OnceCell::new_with(Some(Ok(Arc::new(client)))),
+            }],
+        }
+    }
+
+    #[tokio::test]
+    async fn diagnostics_tool_reports_current_problems() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        std::fs::write(root.join("lib.rs"), "fn ok() {}\nfn bad() { ERROR }\n").unwrap();
+        std::fs::write(root.join("clean.rs"), "fn ok() {}\n").unwrap();
+        let manager = manager_with_stub(root).await;
+
+        let output = manager
+            .handle_tool_call(
+                root,
+                DIAGNOSTICS_TOOL_NAME,
+                r#"{"paths": ["lib.rs", "clean.rs", "README.md"]}"#,
+            )
+            .await;
+
+        assert_eq!(
+            output,
+            Ok(
+                "README.md: no language server is configured for this file\n\
+                lib.rs:2:12: error: found ERROR [stub]\n\
+                clean.rs: no diagnostics\n"
+                    .to_string()
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn new_diagnostics_only_lists_problems_introduced_by_an_edit() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        let path = root.join("lib.rs");
+        std::fs::write(&path, "fn a() { ERROR }\n").unwrap();
+        let manager = manager_with_stub(root).await;
+        let files = std::slice::from_ref(&path);
+
+        // A file the server never saw has no baseline, so its existing
+        // problems are not reported as new.
+        assert_eq!(manager.new_diagnostics(root, files, &[]).await, None);
+
+        // A file created by the edit reports all of its problems.
+        let created = root.join("new.rs");
+        std::fs::write(&created, "fn c() { ERROR }\n").unwrap();
+        let created = std::slice::from_ref(&created);
+        assert_eq!(
+            manager.new_diagnostics(root, created, created).await,
+            Some(
+                "New diagnostics in the edited files:\nnew.rs:1:10: error: found ERROR [stub]\n"
+                    .to_string()
+            )
+        );
+
+        std::fs::write(&path, "fn a() { ERROR }\nfn b() { WARN }\n").unwrap();
+        assert_eq!(
+            manager.new_diagnostics(root, files, &[]).await,
+            Some(
+                "New diagnostics in the edited files:\nlib.rs:2:10: warning: found WARN [stub]\n"
+                    .to_string()
+            )
+        );
+
+        // Unchanged files and files without a server add nothing.
+        std::fs::write(root.join("notes.txt"), "ERROR\n").unwrap();
+        assert_eq!(
+            manager
+                .new_diagnostics(root, &[path, root.join("notes.txt")], &[])
+                .await,
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn definition_and_references_lookups() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        std::fs::write(
+            root.join("lib.rs"),
+            "fn helper() {}\n\nfn main() {\n    helper();\n    let _ = helper;\n}\n",
+        )
+        .unwrap();
+        let manager = manager_with_stub(root).await;
+
+        let definition = manager
+            .handle_tool_call(
+                root,
+                FIND_DEFINITION_TOOL_NAME,
+                r#"{"path": "lib.rs", "line": 4, "symbol": "helper"}"#,
+            )
+            .await;
+        assert_eq!(definition, Ok("lib.rs:1:4: fn helper() {}\n".to_string()));
+
+        let references = manager
+            .handle_tool_call(
+                root,
+                FIND_REFERENCES_TOOL_NAME,
+                r#"{"path": "lib.rs", "line": 1, "symbol": "helper"}"#,
+            )
+            .await;
+        assert_eq!(
+            references,
+            Ok(
+                "lib.rs:1:4: fn helper() {}\nlib.rs:4:5: helper();\nlib.rs:5:13: let _ = helper;\n"
+                    .to_string()
+            )
+        );
+
+        let missing = manager
+            .handle_tool_call(
+                root,
+                FIND_DEFINITION_TOOL_NAME,
+                r#"{"path": "lib.rs", "line": 2, "symbol": "helper"}"#,
+            )
+            .await;
+        assert_eq!(
+            missing,
+            Err("`helper` does not appear on line 2 of lib.rs".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn server_requests_are_answered_while_a_write_is_blocked() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("lib.rs");
+        std::fs::write(&path, "fn a() {}\n".repeat(1000)).unwrap();
+        // Small pipes, so that neither side can write much while the other
+        // is not reading.
+        let (client_reader, mut server_writer) = tokio::io::duplex(256);
+        let (server_reader, client_writer) = tokio::io::duplex(256);
+        tokio::spawn(async move {
+            let mut reader = BufReader::new(server_reader);
+            let initialize = read_message(&mut reader).await.unwrap().unwrap();
+            let reply = json!({ "jsonrpc": "2.0", "id": initialize["id"], "result": {} });
+            write_message(&mut server_writer, &reply).await.unwrap();
+            read_message(&mut reader).await.unwrap();
+            // Ask for configuration many times without reading, while the
+            // client is busy sending a large document.
+            for id in 0..100 {
+                let request = json!({
+                    "jsonrpc": "2.0",
+                    "id": format!("config-{id}"),
+                    "method": "workspace/configuration",
+                    "params": { "items": [{ "section": "stub" }] },
+                });
+                write_message(&mut server_writer, &request).await.unwrap();
+            }
+            while let Ok(Some(message)) = read_message(&mut reader).await {
+                if message["method"] == "textDocument/didOpen" {
+                    let uri = &message["params"]["textDocument"]["uri"];
+                    let publish = json!({
+                        "jsonrpc": "2.0",
+                        "method": "textDocument/publishDiagnostics",
+                        "params": { "uri": uri, "diagnostics": [] },
+                    });
+                    write_message(&mut server_writer, &publish).await.unwrap();
+                }
+            }
+        });
+
+        let client = LspClient::start(client_reader, client_writer, None, dir.path())
+            .await
+            .unwrap();
+        let diagnostics = tokio::time::timeout(
+            Duration::from_secs(10),
+            client.diagnostics(
+                &[(path.clone(), "rust".to_string())],
+                Duration::from_secs(5),
+            ),
+        )
+        .await
+        .expect("the client deadlocked")
+        .unwrap();
+        assert_eq!(diagnostics, vec![(path, Vec::new())]);
+    }
+
+    #[test]
+    fn find_symbol_prefers_whole_words_and_counts_utf16() {
+        // The emoji takes two UTF-16 code units.
+        let text = "let s = \"😀\"; let helper_fn = helper();\n";
+        assert_eq!(
+            find_symbol(text, 1, "helper"),
+            Some(Position {
+                line: 0,
+                character: 30,
+            })
+        );
+        assert_eq!(find_symbol(text, 2, "helper"), None);
+    }
+}
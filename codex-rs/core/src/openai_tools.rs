@@ -70,6 +70,11 @@ pub(crate) struct ToolsConfig {
     pub apply_patch_tool_type: Option<ApplyPatchToolType>,
     pub web_search_request: bool,
     pub include_view_image_tool: bool,
+    pub persistent_shell: bool,
+    pub background_process_tools: bool,
+    pub lsp_tools: bool,
+    pub code_search_tool: bool,
+    pub repo_search_tool: bool,
 }
 
 pub(crate) struct ToolsConfigParams<'a> {
@@ -80,6 +85,11 @@ pub(crate) struct ToolsConfigParams<'a> {
     pub(crate) include_apply_patch_tool: bool,
     pub(crate) include_web_search_request: bool,
     pub(crate) use_streamable_shell_tool: bool,
+    pub(crate) use_persistent_shell_tool: bool,
+    pub(crate) include_background_process_tools: bool,
+    pub(crate) include_lsp_tools: bool,
+    pub(crate) include_code_search_tool: bool,
+    pub(crate) include_repo_search_tool: bool,
     pub(crate) include_view_image_tool: bool,
 }
 
@@ -93,6 +103,11 @@ impl ToolsConfig {
             include_apply_patch_tool,
             include_web_search_request,
             use_streamable_shell_tool,
+            use_persistent_shell_tool,
+            include_background_process_tools,
+            include_lsp_tools,
+            include_code_search_tool,
+            include_repo_search_tool,
             include_view_image_tool,
         } = params;
         let mut shell_type = if *use_streamable_shell_tool {
@@ -126,6 +141,11 @@ impl ToolsConfig {
             apply_patch_tool_type,
             web_search_request: *include_web_search_request,
             include_view_image_tool: *include_view_image_tool,
+            persistent_shell: *use_persistent_shell_tool && cfg!(unix),
+            background_process_tools: *include_background_process_tools,
+            lsp_tools: *include_lsp_tools,
+            code_search_tool: *include_code_search_tool,
+            repo_search_tool: *include_repo_search_tool,
         }
     }
 }
@@ -200,6 +220,20 @@ fn create_shell_tool() -> OpenAiTool {
     })
 }
 
+/// Tells the model that `cd` and `export` carry over between calls when the
+/// shell tool is backed by a persistent shell session.
+fn with_persistent_shell_note(tool: OpenAiTool, config: &ToolsConfig) -> OpenAiTool {
+    match tool {
+        OpenAiTool::Function(mut tool) if config.persistent_shell => {
+            tool.description.push_str(
+                "\nCommands run in a persistent shell session: the working directory and exported environment variables carry over between calls.",
+            );
+            OpenAiTool::Function(tool)
+        }
+        tool => tool,
+    }
+}
+
 fn create_shell_tool_for_sandbox(sandbox_policy: &SandboxPolicy) -> OpenAiTool {
     let mut properties = BTreeMap::new();
     properties.insert(
@@ -536,10 +570,13 @@ pub(crate) fn get_openai_tools(
 
     match &config.shell_type {
         ConfigShellToolType::DefaultShell => {
-            tools.push(create_shell_tool());
+            tools.push(with_persistent_shell_note(create_shell_tool(), config));
         }
         ConfigShellToolType::ShellWithRequest { sandbox_policy } => {
-            tools.push(create_shell_tool_for_sandbox(sandbox_policy));
+            tools.push(with_persistent_shell_note(
+                create_shell_tool_for_sandbox(sandbox_policy),
+                config,
+            ));
         }
         ConfigShellToolType::LocalShell => {
             tools.push(OpenAiTool::LocalShell {});
@@ -554,6 +591,30 @@ pub(crate) fn get_openai_tools(
         }
     }
 
+    if config.background_process_tools {
+        tools.push(OpenAiTool::Function(
+            crate::exec_command::create_background_process_start_tool_for_responses_api(),
+        ));
+        tools.push(OpenAiTool::Function(
+            crate::exec_command::create_background_process_logs_tool_for_responses_api(),
+        ));
+        tools.push(OpenAiTool::Function(
+            crate::exec_command::create_background_process_stop_tool_for_responses_api(),
+        ));
+    }
+
+    if config.lsp_tools {
+        tools.extend(crate::lsp::create_lsp_tools());
+    }
+
+    if config.code_search_tool {
+        tools.push(crate::code_search::create_code_search_tool());
+    }
+
+    if config.repo_search_tool {
+        tools.push(crate::repo_index::create_repo_search_tool());
+    }
+
     if config.plan_tool {
         tools.push(PLAN_TOOL.clone());
     }
@@ -641,6 +702,11 @@ mod tests {
             include_apply_patch_tool: false,
             include_web_search_request: true,
             use_streamable_shell_tool: false,
+            use_persistent_shell_tool: false,
+            include_background_process_tools: false,
+            include_lsp_tools: false,
+            include_code_search_tool: false,
+            include_repo_search_tool: false,
             include_view_image_tool: true,
         });
         let tools = get_openai_tools(&config, Some(HashMap::new()));
@@ -662,6 +728,11 @@ mod tests {
             include_apply_patch_tool: false,
             include_web_search_request: true,
             use_streamable_shell_tool: false,
+            use_persistent_shell_tool: false,
+            include_background_process_tools: false,
+            include_lsp_tools: false,
+            include_code_search_tool: false,
+            include_repo_search_tool: false,
             include_view_image_tool: true,
         });
         let tools = get_openai_tools(&config, Some(HashMap::new()));
@@ -672,6 +743,70 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_get_openai_tools_background_processes() {
+        let model_family = find_family_for_model("o3").expect("o3 should be a valid model family");
+        let config = ToolsConfig::new(&ToolsConfigParams {
+            model_family: &model_family,
+            approval_policy: AskForApproval::Never,
+            sandbox_policy: SandboxPolicy::ReadOnly,
+            include_plan_tool: false,
+            include_apply_patch_tool: false,
+            include_web_search_request: false,
+            use_streamable_shell_tool: false,
+            use_persistent_shell_tool: false,
+            include_background_process_tools: true,
+            include_lsp_tools: false,
+            include_code_search_tool: false,
+            include_repo_search_tool: false,
+            include_view_image_tool: false,
+        });
+        let tools = get_openai_tools(&config, Some(HashMap::new()));
+
+        assert_eq_tool_names(
+            &tools,
+            &[
+                "shell",
+                "background_process_start",
+                "background_process_logs",
+                "background_process_stop",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_get_openai_tools_lsp_code_search_and_repo_search() {
+        let model_family = find_family_for_model("o3").expect("o3 should be a valid model family");
+        let config = ToolsConfig::new(&ToolsConfigParams {
+            model_family: &model_family,
+            approval_policy: AskForApproval::Never,
+            sandbox_policy: SandboxPolicy::ReadOnly,
+            include_plan_tool: false,
+            include_apply_patch_tool: false,
+            include_web_search_request: false,
+            use_streamable_shell_tool: false,
+            use_persistent_shell_tool: false,
+            include_background_process_tools: false,
+            include_lsp_tools: true,
+            include_code_search_tool: true,
+            include_repo_search_tool: true,
+            include_view_image_tool: false,
+        });
+        let tools = get_openai_tools(&config, Some(HashMap::new()));
+
+        assert_eq_tool_names(
+            &tools,
+            &[
+                "shell",
+                "diagnostics",
+                "find_definition",
+                "find_references",
+                "code_search",
+                "repo_search",
+            ],
+        );
+    }
+
     #[test]
     fn test_get_openai_tools_mcp_tools() {
         let model_family = find_family_for_model("o3").expect("o3 should be a valid model family");
@@ -683,6 +818,11 @@ mod tests {
             include_apply_patch_tool: false,
             include_web_search_request: true,
             use_streamable_shell_tool: false,
+            use_persistent_shell_tool: false,
+            include_background_process_tools: false,
+            include_lsp_tools: false,
+            include_code_search_tool: false,
+            include_repo_search_tool: false,
             include_view_image_tool: true,
         });
         let tools = get_openai_tools(
@@ -788,6 +928,11 @@ mod tests {
             include_apply_patch_tool: false,
             include_web_search_request: false,
             use_streamable_shell_tool: false,
+            use_persistent_shell_tool: false,
+            include_background_process_tools: false,
+            include_lsp_tools: false,
+            include_code_search_tool: false,
+            include_repo_search_tool: false,
             include_view_image_tool: true,
         });
 
@@ -865,6 +1010,11 @@ mod tests {
             include_apply_patch_tool: false,
             include_web_search_request: true,
             use_streamable_shell_tool: false,
+            use_persistent_shell_tool: false,
+            include_background_process_tools: false,
+            include_lsp_tools: false,
+            include_code_search_tool: false,
+            include_repo_search_tool: false,
             include_view_image_tool: true,
         });
 
@@ -927,6 +1077,11 @@ mod tests {
             include_apply_patch_tool: false,
             include_web_search_request: true,
             use_streamable_shell_tool: false,
+            use_persistent_shell_tool: false,
+            include_background_process_tools: false,
+            include_lsp_tools: false,
+            include_code_search_tool: false,
+            include_repo_search_tool: false,
             include_view_image_tool: true,
         });
 
@@ -984,6 +1139,11 @@ mod tests {
             include_apply_patch_tool: false,
             include_web_search_request: true,
             use_streamable_shell_tool: false,
+            use_persistent_shell_tool: false,
+            include_background_process_tools: false,
+            include_lsp_tools: false,
+            include_code_search_tool: false,
+            include_repo_search_tool: false,
             include_view_image_tool: true,
         });
 
@@ -1041,6 +1201,11 @@ mod tests {
             include_apply_patch_tool: false,
             include_web_search_request: true,
             use_streamable_shell_tool: false,
+            use_persistent_shell_tool: false,
+            include_background_process_tools: false,
+            include_lsp_tools: false,
+            include_code_search_tool: false,
+            include_repo_search_tool: false,
             include_view_image_tool: true,
         });
 
@@ -0,0 +1,688 @@
+//! A long-lived shell process that backs the `shell` tool unless
+//! `tools.persistent_shell` is disabled.
+//!
+//! Every command is fed to the same `bash` process over stdin, so `cd`,
+//! exported variables and activated virtualenvs carry over between calls. The
+//! shell is spawned under the same sandbox that a one-off command would get,
+//! and is replaced transparently when it exits, times out, or when the sandbox
+//! policy changes. Its environment is built from `shell_environment_policy`
+//! once, at spawn; the environment computed for later calls is ignored.
+
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Mutex as StdMutex;
+use std::time::Duration;
+use std::time::Instant;
+
+use tokio::io::AsyncRead;
+use tokio::io::AsyncReadExt;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Child;
+use tokio::process::ChildStdin;
+use tokio::sync::Mutex;
+use tokio::sync::mpsc;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::codex::ExecInvokeArgs;
+use crate::config_types::Seccomp;
+use crate::error::CodexErr;
+use crate::error::Result;
+use crate::error::SandboxErr;
+use crate::exec::ExecToolCallOutput;
+use crate::exec::MAX_EXEC_OUTPUT_DELTAS_PER_CALL;
+use crate::exec::SandboxType;
+use crate::exec::StdoutStream;
+use crate::exec::StreamOutput;
+use crate::exec::is_likely_sandbox_denied;
+use crate::exec::spawn_sandboxed_child;
+use crate::protocol::Event;
+use crate::protocol::EventMsg;
+use crate::protocol::ExecCommandOutputDeltaEvent;
+use crate::protocol::ExecOutputStream;
+use crate::protocol::SandboxPolicy;
+use crate::safety::get_platform_sandbox;
+use crate::spawn::StdioPolicy;
+
+const READ_CHUNK_SIZE: usize = 8192;
+
+/// How long to keep draining output after the shell process has exited.
+const EXIT_DRAIN_GRACE: Duration = Duration::from_millis(50);
+
+/// Owns the session's persistent shell and the working directory it was last
+/// observed in.
+pub(crate) struct PersistentShellManager {
+    shell: Mutex<Option<PersistentShell>>,
+
+    /// Working directory reported by the shell after the last command, and the
+    /// turn cwd it was reached from. Used as the directory of subsequent calls
+    /// and to restore it when the shell has to be respawned.
+    cwd: StdMutex<TrackedCwd>,
+
+    /// Why the previous shell went away, if it did. Reported alongside the
+    /// output of the next command so the model knows its environment was
+    /// reset.
+    restart_reason: StdMutex<Option<String>>,
+}
+
+impl PersistentShellManager {
+    pub(crate) fn new(cwd: PathBuf) -> Self {
+        Self {
+            shell: Mutex::new(None),
+            cwd: StdMutex::new(TrackedCwd {
+                turn_cwd: cwd.clone(),
+                cwd,
+            }),
+            restart_reason: StdMutex::new(None),
+        }
+    }
+
+    /// The directory the next command of a turn running in `turn_cwd` will
+    /// run in unless the call specifies its own `workdir`. A directory reached
+    /// under a different turn cwd is discarded in favour of `turn_cwd`.
+    pub(crate) fn cwd_for_turn(&self, turn_cwd: &Path) -> PathBuf {
+        let mut guard = match self.cwd.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        if guard.turn_cwd != turn_cwd {
+            *guard = TrackedCwd {
+                turn_cwd: turn_cwd.to_path_buf(),
+                cwd: turn_cwd.to_path_buf(),
+            };
+        }
+        guard.cwd.clone()
+    }
+
+    fn set_cwd(&self, cwd: PathBuf) {
+        if let Ok(mut guard) = self.cwd.lock() {
+            guard.cwd = cwd;
+        }
+    }
+
+    fn set_restart_reason(&self, reason: impl Into<String>) {
+        if let Ok(mut guard) = self.restart_reason.lock() {
+            *guard = Some(reason.into());
+        }
+    }
+
+    fn take_restart_reason(&self) -> Option<String> {
+        self.restart_reason.lock().ok().and_then(|mut r| r.take())
+    }
+
+    /// Runs `exec_args.params.command` in the persistent shell, spawning it
+    /// first if necessary. The shell runs under `exec_args.sandbox_type`, with
+    /// the sandbox policy resolved against
+    /// `exec_args.persistent_shell_sandbox_cwd` (the turn's cwd), which may
+    /// differ from the directory the shell is currently in.
+    pub(crate) async fn run(&self, exec_args: ExecInvokeArgs<'_>) -> Result<ExecToolCallOutput> {
+        let start = Instant::now();
+        let ExecInvokeArgs {
+            params,
+            sandbox_type,
+            sandbox_policy,
+            codex_linux_sandbox_exe,
+            seccomp,
+            stdout_stream,
+            persistent_shell_sandbox_cwd,
+        } = exec_args;
+        let timeout = params.timeout_duration();
+        let sandbox_cwd = persistent_shell_sandbox_cwd.unwrap_or(params.cwd.as_path());
+
+        let mut guard = self.shell.lock().await;
+        if let Some(shell) = guard.as_ref()
+            && (shell.sandbox_type != sandbox_type
+                || shell.sandbox_policy != *sandbox_policy
+                || shell.sandbox_cwd != sandbox_cwd)
+        {
+            guard.take();
+            self.set_restart_reason("the sandbox policy changed");
+        }
+        let restart_reason = self.take_restart_reason();
+        let shell = match guard.as_mut() {
+            Some(shell) => shell,
+            None => guard.insert(
+                PersistentShell::spawn(
+                    sandbox_type,
+                    sandbox_policy,
+                    sandbox_cwd,
+                    params.env.clone(),
+                    codex_linux_sandbox_exe,
+                    seccomp,
+                )
+                .await?,
+            ),
+        };
+
+        let marker = format!("__CODEX_PERSISTENT_SHELL_{}__", Uuid::new_v4().simple());
+        let cd_to = (params.cwd != shell.cwd).then_some(params.cwd.as_path());
+        let script = build_script(&params.command, cd_to, &marker);
+
+        let outcome = shell
+            .run_script(&script, &marker, timeout, stdout_stream.as_ref())
+            .await;
+        let (exit_code, mut output) = match outcome {
+            Ok(ScriptOutcome::Completed {
+                exit_code,
+                output,
+                cwd,
+            }) => {
+                shell.cwd = cwd.clone();
+                self.set_cwd(cwd);
+                (exit_code, output)
+            }
+            Ok(ScriptOutcome::ShellExited { exit_code, output }) => {
+                guard.take();
+                self.set_restart_reason(format!("the shell exited with status {exit_code}"));
+                (exit_code, output)
+            }
+            Err(ScriptError::Timeout) => {
+                guard.take();
+                self.set_restart_reason("the previous command timed out");
+                return Err(CodexErr::Sandbox(SandboxErr::Timeout));
+            }
+            Err(ScriptError::Io(e)) => {
+                guard.take();
+                self.set_restart_reason(format!("the shell failed: {e}"));
+                return Err(CodexErr::Io(e));
+            }
+        };
+        drop(guard);
+
+        if let Some(reason) = restart_reason {
+            output.insert_str(
+                0,
+                &format!(
+                    "[shell session restarted because {reason}; exported variables and shell state were reset]\n"
+                ),
+            );
+        }
+
+        if exit_code != 0 && is_likely_sandbox_denied(sandbox_type, exit_code) {
+            return Err(CodexErr::Sandbox(SandboxErr::Denied(
+                exit_code,
+                output,
+                String::new(),
+            )));
+        }
+
+        Ok(ExecToolCallOutput {
+            exit_code,
+            stdout: StreamOutput::new(output.clone()),
+            stderr: StreamOutput::new(String::new()),
+            aggregated_output: StreamOutput::new(output),
+            duration: start.elapsed(),
+        })
+    }
+
+    /// Kills the shell, if one is running.
+    pub(crate) async fn shutdown(&self) {
+        self.shell.lock().await.take();
+    }
+}
+
+/// Sandbox applied to the shell itself: the platform sandbox unless the user
+/// opted out of sandboxing entirely. Calls that were approved to run with a
+/// different sandbox bypass the persistent shell.
+pub(crate) fn sandbox_type_for_policy(sandbox_policy: &SandboxPolicy) -> SandboxType {
+    match sandbox_policy {
+        SandboxPolicy::DangerFullAccess => SandboxType::None,
+        _ => get_platform_sandbox().unwrap_or(SandboxType::None),
+    }
+}
+
+struct TrackedCwd {
+    turn_cwd: PathBuf,
+    cwd: PathBuf,
+}
+
+struct PersistentShell {
+    child: Child,
+    stdin: ChildStdin,
+    output_rx: mpsc::UnboundedReceiver<Vec<u8>>,
+    sandbox_type: SandboxType,
+    sandbox_policy: SandboxPolicy,
+    sandbox_cwd: PathBuf,
+    /// Directory the shell was in after the last command.
+    cwd: PathBuf,
+}
+
+enum ScriptOutcome {
+    Completed {
+        exit_code: i32,
+        output: String,
+        cwd: PathBuf,
+    },
+    /// The script made the shell exit (e.g. via `exit`).
+    ShellExited { exit_code: i32, output: String },
+}
+
+enum ScriptError {
+    Timeout,
+    Io(std::io::Error),
+}
+
+impl PersistentShell {
+    async fn spawn(
+        sandbox_type: SandboxType,
+        sandbox_policy: &SandboxPolicy,
+        sandbox_cwd: &Path,
+        env: std::collections::HashMap<String, String>,
+        codex_linux_sandbox_exe: &Option<PathBuf>,
+        seccomp: &Seccomp,
+    ) -> Result<Self> {
+        let cwd = sandbox_cwd.to_path_buf();
+        let mut child = spawn_sandboxed_child(
+            shell_command(),
+            cwd.clone(),
+            env,
+            sandbox_type,
+            sandbox_policy,
+            codex_linux_sandbox_exe,
+            seccomp,
+            StdioPolicy::RedirectForPersistentShell,
+        )
+        .await?;
+
+        let mut stdin = child.stdin.take().ok_or_else(|| {
+            CodexErr::Io(std::io::Error::other(
+                "stdin pipe was unexpectedly not available",
+            ))
+        })?;
+        let stdout = child.stdout.take().ok_or_else(|| {
+            CodexErr::Io(std::io::Error::other(
+                "stdout pipe was unexpectedly not available",
+            ))
+        })?;
+        let stderr = child.stderr.take().ok_or_else(|| {
+            CodexErr::Io(std::io::Error::other(
+                "stderr pipe was unexpectedly not available",
+            ))
+        })?;
+
+        let (output_tx, output_rx) = mpsc::unbounded_channel();
+        tokio::spawn(forward_output(stdout, output_tx.clone()));
+        tokio::spawn(forward_output(stderr, output_tx));
+
+        // Fold the shell's own diagnostics (e.g. syntax errors reported by
+        // `eval`) into the same stream as command output so ordering is
+        // preserved relative to the end-of-command marker.
+        stdin.write_all(b"exec 2>&1\n").await?;
+        stdin.flush().await?;
+
+        Ok(Self {
+            child,
+            stdin,
+            output_rx,
+            sandbox_type,
+            sandbox_policy: sandbox_policy.clone(),
+            sandbox_cwd: sandbox_cwd.to_path_buf(),
+            cwd,
+        })
+    }
+
+    async fn run_script(
+        &mut self,
+        script: &str,
+        marker: &str,
+        timeout: Duration,
+        stdout_stream: Option<&StdoutStream>,
+    ) -> std::result::Result<ScriptOutcome, ScriptError> {
+        // Discard anything background jobs printed since the last command.
+        while self.output_rx.try_recv().is_ok() {}
+
+        self.stdin
+            .write_all(script.as_bytes())
+            .await
+            .map_err(ScriptError::Io)?;
+        self.stdin.flush().await.map_err(ScriptError::Io)?;
+
+        let marker_prefix = format!("\n{marker} ");
+        let deadline = tokio::time::Instant::now() + timeout;
+        let mut buf: Vec<u8> = Vec::new();
+        let mut streamed = 0usize;
+        let mut emitted_deltas = 0usize;
+
+        loop {
+            tokio::select! {
+                chunk = self.output_rx.recv() => {
+                    let Some(chunk) = chunk else {
+                        let exit_code = self.wait_for_exit().await;
+                        return Ok(ScriptOutcome::ShellExited {
+                            exit_code,
+                            output: String::from_utf8_lossy(&buf).to_string(),
+                        });
+                    };
+                    buf.extend_from_slice(&chunk);
+
+                    if let Some(pos) = find_subslice(&buf, marker_prefix.as_bytes()) {
+                        let status_start = pos + marker_prefix.len();
+                        if let Some(line_len) = buf[status_start..].iter().position(|b| *b == b'\n') {
+                            let status_line =
+                                String::from_utf8_lossy(&buf[status_start..status_start + line_len])
+                                    .to_string();
+                            let (exit_code, cwd) = parse_status_line(&status_line)
+                                .unwrap_or((-1, self.cwd.clone()));
+                            if let Some(stream) = stdout_stream {
+                                send_delta(stream, &buf[streamed..pos], &mut emitted_deltas).await;
+                            }
+                            buf.truncate(pos);
+                            return Ok(ScriptOutcome::Completed {
+                                exit_code,
+                                output: String::from_utf8_lossy(&buf).to_string(),
+                                cwd,
+                            });
+                        }
+                        continue;
+                    }
+
+                    // Stream complete lines only; the trailing partial line may
+                    // turn out to be the start of the marker.
+                    if let Some(stream) = stdout_stream
+                        && let Some(last_newline) = buf.iter().rposition(|b| *b == b'\n')
+                        && last_newline > streamed
+                    {
+                        send_delta(stream, &buf[streamed..last_newline], &mut emitted_deltas).await;
+                        streamed = last_newline;
+                    }
+                }
+                status = self.child.wait() => {
+                    let exit_code = status.ok().and_then(|s| s.code()).unwrap_or(-1);
+                    let grace = tokio::time::Instant::now() + EXIT_DRAIN_GRACE;
+                    while let Ok(Some(chunk)) =
+                        tokio::time::timeout_at(grace, self.output_rx.recv()).await
+                    {
+                        buf.extend_from_slice(&chunk);
+                    }
+                    return Ok(ScriptOutcome::ShellExited {
+                        exit_code,
+                        output: String::from_utf8_lossy(&buf).to_string(),
+                    });
+                }
+                _ = tokio::time::sleep_until(deadline) => {
+                    let _ = self.child.start_kill();
+                    return Err(ScriptError::Timeout);
+                }
+            }
+        }
+    }
+
+    async fn wait_for_exit(&mut self) -> i32 {
+        match tokio::time::timeout(EXIT_DRAIN_GRACE, self.child.wait()).await {
+            Ok(Ok(status)) => status.code().unwrap_or(-1),
+            _ => {
+                warn!("persistent shell closed its output but did not exit");
+                let _ = self.child.start_kill();
+                -1
+            }
+        }
+    }
+}
+
+async fn forward_output<R: AsyncRead + Unpin>(mut reader: R, tx: mpsc::UnboundedSender<Vec<u8>>) {
+    let mut tmp = [0u8; READ_CHUNK_SIZE];
+    loop {
+        match reader.read(&mut tmp).await {
+            Ok(0) | Err(_) => break,
+            Ok(n) => {
+                if tx.send(tmp[..n].to_vec()).is_err() {
+                    break;
+                }
+            }
+        }
+    }
+}
+
+async fn send_delta(stream: &StdoutStream, bytes: &[u8], emitted_deltas: &mut usize) {
+    if bytes.is_empty() || *emitted_deltas >= MAX_EXEC_OUTPUT_DELTAS_PER_CALL {
+        return;
+    }
+    let event = Event {
+        id: stream.sub_id.clone(),
+        msg: EventMsg::ExecCommandOutputDelta(ExecCommandOutputDeltaEvent {
+            call_id: stream.call_id.clone(),
+            stream: ExecOutputStream::Stdout,
+            chunk: bytes.to_vec(),
+        }),
+    };
+    let _ = stream.tx_event.send(event).await;
+    *emitted_deltas += 1;
+}
+
+/// Prefer bash so that scripts written for `bash -lc` behave the same; fall
+/// back to the POSIX shell when bash is unavailable.
+fn shell_command() -> Vec<String> {
+    if Path::new("/bin/bash").exists() {
+        vec![
+            "/bin/bash".to_string(),
+            "--noprofile".to_string(),
+            "--norc".to_string(),
+        ]
+    } else {
+        vec!["/bin/sh".to_string()]
+    }
+}
+
+/// Wraps the tool call's command so that it runs in the current shell, cannot
+/// read the shell's own stdin, and is followed by a unique marker line that
+/// carries the exit status and the resulting working directory.
+///
+/// The command is passed through `eval` so that a syntax error is reported as
+/// a failed command rather than terminating the non-interactive shell.
+fn build_script(command: &[String], cd_to: Option<&Path>, marker: &str) -> String {
+    let body = command_to_script(command);
+    let mut inner = String::new();
+    if let Some(dir) = cd_to {
+        inner.push_str("cd -- ");
+        inner.push_str(&single_quote(&dir.to_string_lossy()));
+        inner.push_str(" && ");
+    }
+    inner.push_str("eval ");
+    inner.push_str(&single_quote(&body));
+    format!(
+        "{{ {inner}\n}} </dev/null 2>&1\n__codex_status=$?\nprintf '\\n%s %d %s\\n' '{marker}' \"$__codex_status\" \"$PWD\"\n"
+    )
+}
+
+/// Models usually call the shell tool as `["bash", "-lc", "<script>"]`; run
+/// the script itself so that `cd` and `export` affect the persistent shell
+/// rather than a subshell. Any other argv is quoted word by word.
+fn command_to_script(command: &[String]) -> String {
+    if let [shell, flag, script] = command
+        && matches!(flag.as_str(), "-c" | "-lc")
+        && let Some(name) = Path::new(shell).file_name().and_then(|n| n.to_str())
+        && matches!(name, "bash" | "sh" | "zsh" | "dash")
+    {
+        return script.clone();
+    }
+    command
+        .iter()
+        .map(|word| single_quote(word))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn single_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', r#"'\''"#))
+}
+
+fn parse_status_line(line: &str) -> Option<(i32, PathBuf)> {
+    let (code, cwd) = line.split_once(' ')?;
+    Some((code.trim().parse().ok()?, PathBuf::from(cwd)))
+}
+
+fn find_subslice(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::exec::ExecParams;
+    use pretty_assertions::assert_eq;
+    use std::collections::HashMap;
+    use tempfile::TempDir;
+
+    fn bash(script: &str) -> Vec<String> {
+        vec!["bash".to_string(), "-lc".to_string(), script.to_string()]
+    }
+
+    async fn run(
+        manager: &PersistentShellManager,
+        sandbox_cwd: &Path,
+        command: Vec<String>,
+        timeout_ms: Option<u64>,
+    ) -> Result<ExecToolCallOutput> {
+        let params = ExecParams {
+            command,
+            cwd: manager.cwd_for_turn(sandbox_cwd),
+            timeout_ms,
+            env: std::env::vars().collect::<HashMap<_, _>>(),
+            with_escalated_permissions: None,
+            justification: None,
+        };
+        manager
+            .run(ExecInvokeArgs {
+                params,
+                sandbox_type: SandboxType::None,
+                sandbox_policy: &SandboxPolicy::DangerFullAccess,
+                codex_linux_sandbox_exe: &None,
+                seccomp: &Seccomp::default(),
+                stdout_stream: None,
+                persistent_shell_sandbox_cwd: Some(sandbox_cwd),
+            })
+            .await
+    }
+
+    #[test]
+    fn command_to_script_unwraps_shell_invocations() {
+        assert_eq!(command_to_script(&bash("cd src && ls")), "cd src && ls");
+        assert_eq!(
+            command_to_script(&["echo".to_string(), "it's here".to_string()]),
+            r#"'echo' 'it'\''s here'"#
+        );
+    }
+
+    #[tokio::test]
+    async fn cwd_and_env_persist_between_calls() {
+        let dir = TempDir::new().expect("tempdir");
+        let root = dir.path().canonicalize().expect("canonicalize");
+        std::fs::create_dir(root.join("sub")).expect("mkdir");
+        let manager = PersistentShellManager::new(root.clone());
+
+        let first = run(&manager, &root, bash("cd sub && export GREETING=hi"), None)
+            .await
+            .expect("first command");
+        assert_eq!(first.exit_code, 0);
+        assert_eq!(manager.cwd_for_turn(&root), root.join("sub"));
+
+        let second = run(&manager, &root, bash("echo \"$GREETING from $PWD\""), None)
+            .await
+            .expect("second command");
+        assert_eq!(
+            second.aggregated_output.text,
+            format!("hi from {}\n", root.join("sub").display())
+        );
+    }
+
+    #[tokio::test]
+    async fn turn_cwd_change_resets_cwd() {
+        let dir = TempDir::new().expect("tempdir");
+        let root = dir.path().canonicalize().expect("canonicalize");
+        std::fs::create_dir(root.join("sub")).expect("mkdir");
+        std::fs::create_dir(root.join("other")).expect("mkdir");
+        let manager = PersistentShellManager::new(root.clone());
+
+        run(&manager, &root, bash("cd sub"), None)
+            .await
+            .expect("first command");
+        let other = root.join("other");
+        let moved = run(&manager, &other, bash("printf %s \"$PWD\""), None)
+            .await
+            .expect("second command");
+        assert!(
+            moved
+                .aggregated_output
+                .text
+                .ends_with(&format!("\n{}", other.display()))
+        );
+        assert_eq!(manager.cwd_for_turn(&other), other);
+    }
+
+    #[tokio::test]
+    async fn reports_exit_code_and_survives_syntax_errors() {
+        let dir = TempDir::new().expect("tempdir");
+        let root = dir.path().to_path_buf();
+        let manager = PersistentShellManager::new(root.clone());
+
+        let failed = run(&manager, &root, bash("echo oops; false"), None)
+            .await
+            .expect("failing command");
+        assert_eq!(failed.exit_code, 1);
+        assert_eq!(failed.aggregated_output.text, "oops\n");
+
+        let syntax = run(&manager, &root, bash("echo \"unterminated"), None)
+            .await
+            .expect("syntax error");
+        assert_ne!(syntax.exit_code, 0);
+
+        let after = run(&manager, &root, bash("printf ok"), None)
+            .await
+            .expect("shell still alive");
+        assert_eq!(after.aggregated_output.text, "ok");
+    }
+
+    #[tokio::test]
+    async fn timeout_restarts_shell_and_keeps_cwd() {
+        let dir = TempDir::new().expect("tempdir");
+        let root = dir.path().canonicalize().expect("canonicalize");
+        std::fs::create_dir(root.join("sub")).expect("mkdir");
+        let manager = PersistentShellManager::new(root.clone());
+
+        run(&manager, &root, bash("cd sub && export KEEP=1"), None)
+            .await
+            .expect("cd");
+        let timed_out = run(&manager, &root, bash("sleep 5"), Some(200)).await;
+        assert!(matches!(
+            timed_out,
+            Err(CodexErr::Sandbox(SandboxErr::Timeout))
+        ));
+
+        let after = run(&manager, &root, bash("echo \"${KEEP:-unset} $PWD\""), None)
+            .await
+            .expect("respawned shell");
+        assert_eq!(
+            after.aggregated_output.text,
+            format!(
+                "[shell session restarted because the previous command timed out; exported variables and shell state were reset]\nunset {}\n",
+                root.join("sub").display()
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn exit_is_reported_and_next_call_respawns() {
+        let dir = TempDir::new().expect("tempdir");
+        let root = dir.path().to_path_buf();
+        let manager = PersistentShellManager::new(root.clone());
+
+        let exited = run(&manager, &root, bash("echo bye; exit 3"), None)
+            .await
+            .expect("exit");
+        assert_eq!(exited.exit_code, 3);
+        assert_eq!(exited.aggregated_output.text, "bye\n");
+
+        let after = run(&manager, &root, bash("printf alive"), None)
+            .await
+            .expect("respawned");
+        assert!(after.aggregated_output.text.ends_with("alive"));
+        assert!(
+            after
+                .aggregated_output
+                .text
+                .contains("exited with status 3")
+        );
+    }
+}
@@ -0,0 +1,314 @@
+//! Formatters and linters configured under `[formatters]` and `[linters]`
+//! that run on the files touched by `apply_patch`.
+//!
+//! Formatters rewrite files in place, so their edits show up in the turn diff
+//! like any other change. The files a formatter rewrote, linter output and any
+//! formatter failures are returned as a report that is appended to the patch
+//! output the model sees.
+
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::time::Duration;
+
+use tokio::process::Command;
+use wildmatch::WildMatchPattern;
+
+use crate::config_types::PostEditCommand;
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Lines of output kept per command; linters on a large file can be chatty.
+const MAX_OUTPUT_LINES: usize = 100;
+
+type FilePattern = WildMatchPattern<'*', '?'>;
+
+/// Glob patterns selecting files by name (`*.rs`) or, for patterns that
+/// contain a `/`, by their path relative to the working directory.
+pub(crate) struct FilePatterns {
+    /// Each pattern, and whether it matches the whole relative path rather
+    /// than only the file name.
+    patterns: Vec<(bool, FilePattern)>,
+}
+
+impl FilePatterns {
+    pub(crate) fn new(patterns: &[String]) -> Self {
+        Self {
+            patterns: patterns
+                .iter()
+                .map(|p| (p.contains('/'), FilePattern::new(p)))
+                .collect(),
+        }
+    }
+
+    pub(crate) fn matches(&self, relative: &Path) -> bool {
+        let path = relative.to_string_lossy();
+        let file_name = relative
+            .file_name()
+            .map(|name| name.to_string_lossy())
+            .unwrap_or_default();
+        self.patterns.iter().any(|(whole_path, pattern)| {
+            if *whole_path {
+                pattern.matches(&path)
+            } else {
+                pattern.matches(&file_name)
+            }
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Formatter,
+    Linter,
+}
+
+struct Tool {
+    kind: Kind,
+    name: String,
+    files: FilePatterns,
+    command: Vec<String>,
+    timeout: Duration,
+}
+
+/// The formatters and linters of a session.
+pub(crate) struct PostEditHooks {
+    /// Formatters first, so that linters see the formatted files.
+    tools: Vec<Tool>,
+}
+
+impl PostEditHooks {
+    pub(crate) fn new(
+        formatters: &BTreeMap<String, PostEditCommand>,
+        linters: &BTreeMap<String, PostEditCommand>,
+    ) -> Self {
+        let tools = [(Kind::Formatter, formatters), (Kind::Linter, linters)]
+            .into_iter()
+            .flat_map(|(kind, commands)| {
+                commands.iter().map(move |(name, command)| Tool {
+                    kind,
+                    name: name.clone(),
+                    files: FilePatterns::new(&command.files),
+                    command: command.command.clone(),
+                    timeout: command
+                        .timeout_ms
+                        .map(Duration::from_millis)
+                        .unwrap_or(DEFAULT_TIMEOUT),
+                })
+            })
+            .collect();
+        Self { tools }
+    }
+
+    /// Runs every formatter and then every linter on the matching `files`
+    /// that still exist. Returns the text to append to the patch output, if
+    /// there is anything to report.
+    pub(crate) async fn run(&self, cwd: &Path, files: &[PathBuf]) -> Option<String> {
+        let mut files: Vec<PathBuf> = files.iter().filter(|f| f.is_file()).cloned().collect();
+        files.sort();
+        files.dedup();
+
+        let mut formatted = BTreeSet::new();
+        let mut report = Vec::new();
+        for tool in &self.tools {
+            let args: Vec<&Path> = files
+                .iter()
+                .map(|file| file.strip_prefix(cwd).unwrap_or(file))
+                .filter(|relative| tool.files.matches(relative))
+                .collect();
+            if args.is_empty() {
+                continue;
+            }
+            let before = match tool.kind {
+                Kind::Formatter => read_all(cwd, &args).await,
+                Kind::Linter => Vec::new(),
+            };
+            if let Some(section) = tool.run(cwd, &args).await {
+                report.push(section);
+            }
+            for (file, contents) in args.iter().zip(before) {
+                if tokio::fs::read(cwd.join(file)).await.ok() != contents {
+                    formatted.insert(file.display().to_string());
+                }
+            }
+        }
+        if !formatted.is_empty() {
+            let files = formatted.into_iter().collect::<Vec<_>>().join(", ");
+            report.insert(0, format!("formatted: {files}\n"));
+        }
+        (!report.is_empty()).then(|| report.join("\n"))
+    }
+}
+
+impl Tool {
+    /// Runs the tool and describes the outcome if it needs the model's
+    /// attention: a failing formatter or a linter that found problems.
+    async fn run(&self, cwd: &Path, files: &[&Path]) -> Option<String> {
+        let label = match self.kind {
+            Kind::Formatter => "Formatter",
+            Kind::Linter => "Linter",
+        };
+        let name = &self.name;
+        let (program, args) = self.command.split_first()?;
+        let child = Command::new(program)
+            .args(args)
+            .args(files)
+            .current_dir(cwd)
+            .stdin(Stdio::null())
+            .kill_on_drop(true)
+            .output();
+        let output = match tokio::time::timeout(self.timeout, child).await {
+            Ok(Ok(output)) => output,
+            Ok(Err(err)) => return Some(format!("{label} `{name}` could not be started: {err}\n")),
+            Err(_) => {
+                let secs = self.timeout.as_secs_f32();
+                return Some(format!("{label} `{name}` timed out after {secs}s\n"));
+            }
+        };
+        if output.status.success() {
+            return None;
+        }
+
+        let mut text = String::from_utf8_lossy(&output.stdout).into_owned();
+        text.push_str(&String::from_utf8_lossy(&output.stderr));
+        let status = match output.status.code() {
+            Some(code) => format!("exit code {code}"),
+            None => "killed by a signal".to_string(),
+        };
+        let heading = match self.kind {
+            Kind::Formatter => format!("Formatter `{name}` failed ({status}):"),
+            Kind::Linter => format!("Linter `{name}` reported problems ({status}):"),
+        };
+        Some(format!("{heading}\n{}", truncate_lines(&text)))
+    }
+}
+
+async fn read_all(cwd: &Path, files: &[&Path]) -> Vec<Option<Vec<u8>>> {
+    let mut contents = Vec::with_capacity(files.len());
+    for file in files {
+        contents.push(tokio::fs::read(cwd.join(file)).await.ok());
+    }
+    contents
+}
+
+fn truncate_lines(text: &str) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut out = lines
+        .iter()
+        .take(MAX_OUTPUT_LINES)
+        .map(|line| format!("{line}\n"))
+        .collect::<String>();
+    if lines.len() > MAX_OUTPUT_LINES {
+        let omitted = lines.len() - MAX_OUTPUT_LINES;
+        out.push_str(&format!("[... omitted {omitted} more lines ...]\n"));
+    }
+    out
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    fn command(files: &[&str], script: &str) -> PostEditCommand {
+        PostEditCommand {
+            files: files.iter().map(ToString::to_string).collect(),
+            command: vec![
+                "sh".to_string(),
+                "-c".to_string(),
+                script.to_string(),
+                "sh".to_string(),
+            ],
+            timeout_ms: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn formatters_rewrite_matching_files_and_linters_report_problems() {
+        let dir = TempDir::new().unwrap();
+        let cwd = dir.path();
+        std::fs::create_dir(cwd.join("src")).unwrap();
+        std::fs::write(cwd.join("src/lib.rs"), "fn main() {}\n").unwrap();
+        std::fs::write(cwd.join("notes.txt"), "keep me\n").unwrap();
+
+        let formatters = BTreeMap::from([(
+            "upper".to_string(),
+            command(
+                &["*.rs"],
+                r#"for f; do tr a-z A-Z < "$f" > "$f.tmp" && mv "$f.tmp" "$f"; done"#,
+            ),
+        )]);
+        let linters = BTreeMap::from([
+            (
+                "nag".to_string(),
+                command(
+                    &["src/*"],
+                    r#"for f; do echo "$f:1: FN is shouting"; done; exit 1"#,
+                ),
+            ),
+            ("quiet".to_string(), command(&["*.rs"], "exit 0")),
+        ]);
+        let hooks = PostEditHooks::new(&formatters, &linters);
+
+        let files = vec![
+            cwd.join("src/lib.rs"),
+            cwd.join("notes.txt"),
+            cwd.join("deleted.rs"),
+        ];
+        let report = hooks.run(cwd, &files).await;
+
+        assert_eq!(
+            std::fs::read_to_string(cwd.join("src/lib.rs")).unwrap(),
+            "FN MAIN() {}\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(cwd.join("notes.txt")).unwrap(),
+            "keep me\n"
+        );
+        assert_eq!(
+            report.as_deref(),
+            Some(
+                "formatted: src/lib.rs\n\nLinter `nag` reported problems (exit code 1):\nsrc/lib.rs:1: FN is shouting\n"
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn reports_failing_and_slow_formatters() {
+        let dir = TempDir::new().unwrap();
+        let cwd = dir.path();
+        std::fs::write(cwd.join("a.py"), "x=1\n").unwrap();
+
+        let mut slow = command(&["*.py"], "sleep 5");
+        slow.timeout_ms = Some(100);
+        let formatters = BTreeMap::from([
+            (
+                "broken".to_string(),
+                command(&["*.py"], "echo 'cannot parse' >&2; exit 2"),
+            ),
+            ("slow".to_string(), slow),
+        ]);
+        let hooks = PostEditHooks::new(&formatters, &BTreeMap::new());
+
+        let report = hooks.run(cwd, &[cwd.join("a.py")]).await;
+        assert_eq!(
+            report.as_deref(),
+            Some(
+                "Formatter `broken` failed (exit code 2):\ncannot parse\n\nFormatter `slow` timed out after 0.1s\n"
+            )
+        );
+    }
+
+    #[test]
+    fn truncates_long_output() {
+        let text = (0..MAX_OUTPUT_LINES + 3)
+            .map(|i| format!("line {i}\n"))
+            .collect::<String>();
+        let truncated = truncate_lines(&text);
+        assert!(truncated.starts_with("line 0\n"));
+        assert!(truncated.ends_with("line 99\n[... omitted 3 more lines ...]\n"));
+    }
+}
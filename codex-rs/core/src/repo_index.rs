@@ -0,0 +1,1174 @@
+//! A local index of the repository the session works in: the symbols and a
+//! one-line summary of every source file, the graph of imports between them
+//! and identifier term frequencies for BM25 ranking. It backs the
+//! `repo_search` tool and the optional repository map in the initial context.
+//!
+//! The index is stored under `~/.codex/repo_index` and refreshed
+//! incrementally: only files whose size or modification time changed since
+//! the last walk are parsed again. A session starts refreshing it in the
+//! background; the repository map uses whatever is indexed at the time.
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+use std::time::UNIX_EPOCH;
+
+use serde::Deserialize;
+use serde::Serialize;
+use sha1::Digest;
+use sha1::Sha1;
+use tracing::warn;
+
+use crate::code_search;
+use crate::openai_tools::JsonSchema;
+use crate::openai_tools::OpenAiTool;
+use crate::openai_tools::ResponsesApiTool;
+
+pub(crate) const REPO_SEARCH_TOOL_NAME: &str = "repo_search";
+
+/// Bumped whenever the stored format or the extraction changes, so that old
+/// indexes are rebuilt rather than misread.
+const INDEX_VERSION: u32 = 1;
+/// Distinct terms kept per file, most frequent first.
+const MAX_TERMS_PER_FILE: usize = 300;
+/// Symbols kept per file.
+const MAX_SYMBOLS_PER_FILE: usize = 200;
+/// Terms from paths and symbol names count this many times over terms from
+/// the rest of the source.
+const NAME_TERM_WEIGHT: u32 = 3;
+const MAX_SUMMARY_CHARS: usize = 160;
+const DEFAULT_RESULTS: usize = 10;
+const MAX_RESULTS: usize = 50;
+/// Symbols listed per search result.
+const SYMBOLS_PER_RESULT: usize = 8;
+
+const BM25_K1: f64 = 1.2;
+const BM25_B: f64 = 0.75;
+/// How much import-graph centrality boosts a BM25 score: the most central
+/// file scores `1 + CENTRALITY_WEIGHT` times its text score.
+const CENTRALITY_WEIGHT: f64 = 0.25;
+const PAGERANK_DAMPING: f64 = 0.85;
+const PAGERANK_ITERATIONS: usize = 20;
+
+/// A reference from one file to another module, as written in the source.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "kind", content = "path")]
+enum Import {
+    /// A module path relative to the repository root, without extension.
+    /// Trailing segments may name items rather than modules.
+    Path(String),
+    /// Module path segments joined with `/`, matched against the end of the
+    /// indexed paths.
+    Module(String),
+    /// A Go import path, matched against the end of a directory.
+    Package(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct FileEntry {
+    modified_ms: u64,
+    size: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    summary: Option<String>,
+    /// `kind qualified_name`, in document order.
+    symbols: Vec<String>,
+    imports: Vec<Import>,
+    terms: BTreeMap<String, u32>,
+}
+
+/// The on-disk form of the index. Keys are `/`-separated paths relative to
+/// the repository root.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+struct StoredIndex {
+    version: u32,
+    files: BTreeMap<String, FileEntry>,
+}
+
+/// The stored index plus what is derived from it on load.
+#[derive(Debug, Default)]
+struct Index {
+    stored: StoredIndex,
+    /// Indexed paths, in the order of `stored.files`.
+    paths: Vec<String>,
+    /// How many files import each file, by position in `paths`.
+    imported_by: Vec<usize>,
+    /// PageRank over the import graph, scaled so the maximum is 1.
+    centrality: Vec<f64>,
+    document_frequency: HashMap<String, usize>,
+    average_length: f64,
+}
+
+/// The repository index of one session, keyed on disk by its root.
+pub(crate) struct RepoIndex {
+    root: PathBuf,
+    store: PathBuf,
+    /// Files listed in the repository map of the initial context.
+    context_files: usize,
+    index: Arc<Mutex<Option<Index>>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RepoSearchArgs {
+    query: String,
+    #[serde(default)]
+    limit: Option<usize>,
+}
+
+impl RepoIndex {
+    pub(crate) fn new(codex_home: &Path, root: PathBuf, context_files: usize) -> Self {
+        let mut hasher = Sha1::new();
+        hasher.update(root.to_string_lossy().as_bytes());
+        let key = format!("{:x}", hasher.finalize());
+        Self {
+            store: codex_home.join("repo_index").join(format!("{key}.json")),
+            root,
+            context_files,
+            index: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Handles a `repo_search` call.
+    pub(crate) async fn handle_repo_search(&self, arguments: &str) -> Result<String, String> {
+        let args: RepoSearchArgs = serde_json::from_str(arguments)
+            .map_err(|e| format!("failed to parse function arguments: {e}"))?;
+        let limit = args.limit.unwrap_or(DEFAULT_RESULTS).clamp(1, MAX_RESULTS);
+        self.with_index(move |index| index.search(&args.query, limit))
+            .await?
+    }
+
+    /// The most central files of the repository with their summaries, one
+    /// per line, or `None` if the map is disabled or nothing is indexed.
+    /// The index is not refreshed first, so the map may be stale, or absent
+    /// while a repository is indexed for the first time.
+    pub(crate) async fn repository_map(&self) -> Option<String> {
+        let limit = self.context_files;
+        if limit == 0 {
+            return None;
+        }
+        let store = self.store.clone();
+        let index = self.index.clone();
+        let map = tokio::task::spawn_blocking(move || {
+            let mut guard = index.lock().unwrap_or_else(PoisonError::into_inner);
+            guard
+                .get_or_insert_with(|| Index::new(load(&store)))
+                .map(limit)
+        });
+        match map.await {
+            Ok(map) => map,
+            Err(e) => {
+                warn!("failed to read the index of {}: {e}", self.root.display());
+                None
+            }
+        }
+    }
+
+    /// Brings the index up to date with the working tree without holding it
+    /// locked during the walk, so that the repository map can be read
+    /// meanwhile.
+    pub(crate) fn refresh_in_background(&self) {
+        let root = self.root.clone();
+        let store = self.store.clone();
+        let index = self.index.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut stored = {
+                let mut guard = index.lock().unwrap_or_else(PoisonError::into_inner);
+                guard
+                    .get_or_insert_with(|| Index::new(load(&store)))
+                    .stored
+                    .clone()
+            };
+            if refresh(&root, &mut stored) {
+                if let Err(e) = save(&store, &stored) {
+                    warn!("failed to save {}: {e}", store.display());
+                }
+                *index.lock().unwrap_or_else(PoisonError::into_inner) = Some(Index::new(stored));
+            }
+        });
+    }
+
+    /// Brings the index up to date with the working tree, then runs `f` on
+    /// it. Walking and parsing are blocking, so they run off the async
+    /// workers.
+    async fn with_index<T: Send + 'static>(
+        &self,
+        f: impl FnOnce(&Index) -> T + Send + 'static,
+    ) -> Result<T, String> {
+        let root = self.root.clone();
+        let store = self.store.clone();
+        let index = self.index.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut guard = index.lock().unwrap_or_else(PoisonError::into_inner);
+            let index = guard.get_or_insert_with(|| Index::new(load(&store)));
+            if refresh(&root, &mut index.stored) {
+                if let Err(e) = save(&store, &index.stored) {
+                    warn!("failed to save {}: {e}", store.display());
+                }
+                *index = Index::new(std::mem::take(&mut index.stored));
+            }
+            f(index)
+        })
+        .await
+        .map_err(|e| format!("repo_search failed: {e}"))
+    }
+}
+
+fn load(store: &Path) -> StoredIndex {
+    std::fs::read_to_string(store)
+        .ok()
+        .and_then(|contents| serde_json::from_str::<StoredIndex>(&contents).ok())
+        .filter(|stored| stored.version == INDEX_VERSION)
+        .unwrap_or_default()
+}
+
+fn save(store: &Path, stored: &StoredIndex) -> std::io::Result<()> {
+    if let Some(parent) = store.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    // Write to a sibling and rename so a concurrent session never reads a
+    // partial file.
+    let tmp = store.with_extension(format!("json.{}.tmp", std::process::id()));
+    std::fs::write(&tmp, serde_json::to_vec(stored)?)?;
+    std::fs::rename(&tmp, store)
+}
+
+/// Walks `root`, honouring `.gitignore`, and re-indexes the source files that
+/// are new or changed since the last walk. Returns whether anything changed.
+fn refresh(root: &Path, stored: &mut StoredIndex) -> bool {
+    let mut old_files = std::mem::take(&mut stored.files);
+    let mut changed = stored.version != INDEX_VERSION;
+    let mut files = BTreeMap::new();
+    for (path, metadata) in code_search::walk_source_files(root) {
+        let Some(key) = relative_key(root, &path) else {
+            continue;
+        };
+        let modified_ms = metadata
+            .modified()
+            .ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map_or(0, |duration| duration.as_millis() as u64);
+        let size = metadata.len();
+        let entry = match old_files.remove(&key) {
+            Some(old) if old.modified_ms == modified_ms && old.size == size => old,
+            _ => {
+                let Ok(source) = std::fs::read_to_string(&path) else {
+                    continue;
+                };
+                changed = true;
+                index_file(&key, &source, modified_ms, size)
+            }
+        };
+        files.insert(key, entry);
+    }
+    changed |= !old_files.is_empty();
+    stored.version = INDEX_VERSION;
+    stored.files = files;
+    changed
+}
+
+fn relative_key(root: &Path, path: &Path) -> Option<String> {
+    let relative = path.strip_prefix(root).ok()?;
+    let parts: Vec<String> = relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect();
+    (!parts.is_empty()).then(|| parts.join("/"))
+}
+
+fn index_file(key: &str, source: &str, modified_ms: u64, size: u64) -> FileEntry {
+    let mut symbols = code_search::symbol_outline(Path::new(key), source);
+    symbols.truncate(MAX_SYMBOLS_PER_FILE);
+
+    let mut counts: HashMap<String, u32> = HashMap::new();
+    identifier_terms(source, |term| *counts.entry(term).or_default() += 1);
+    let names = symbols
+        .iter()
+        .filter_map(|symbol| symbol.split_once(' ').map(|(_, name)| name));
+    for name in names.chain([key]) {
+        identifier_terms(name, |term| {
+            *counts.entry(term).or_default() += NAME_TERM_WEIGHT;
+        });
+    }
+    let mut terms: Vec<(String, u32)> = counts.into_iter().collect();
+    terms.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    terms.truncate(MAX_TERMS_PER_FILE);
+
+    FileEntry {
+        modified_ms,
+        size,
+        summary: summary(source),
+        symbols,
+        imports: imports(key, source),
+        terms: terms.into_iter().collect(),
+    }
+}
+
+/// Calls `add` with the lowercased identifier terms of `text`: every
+/// identifier and, for compound ones, their camelCase and snake_case parts.
+/// Single characters are dropped.
+fn identifier_terms(text: &str, mut add: impl FnMut(String)) {
+    let identifiers = text
+        .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .filter(|ident| ident.starts_with(|c: char| c.is_alphabetic() || c == '_'));
+    for ident in identifiers {
+        let parts = split_identifier(ident);
+        if parts.len() > 1 {
+            add(ident.to_lowercase());
+        }
+        for part in parts {
+            if part.chars().count() > 1 {
+                add(part);
+            }
+        }
+    }
+}
+
+/// `parseHTTPRequest_v2` -> `parse`, `http`, `request`, `v2`.
+fn split_identifier(ident: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    for word in ident.split('_').filter(|word| !word.is_empty()) {
+        let chars: Vec<char> = word.chars().collect();
+        let mut start = 0;
+        for i in 1..chars.len() {
+            let (prev, cur) = (chars[i - 1], chars[i]);
+            let next_is_lower = chars.get(i + 1).is_some_and(|c| c.is_lowercase());
+            let boundary = ((prev.is_lowercase() || prev.is_ascii_digit()) && cur.is_uppercase())
+                || (prev.is_uppercase() && cur.is_uppercase() && next_is_lower);
+            if boundary {
+                parts.push(chars[start..i].iter().collect::<String>().to_lowercase());
+                start = i;
+            }
+        }
+        parts.push(chars[start..].iter().collect::<String>().to_lowercase());
+    }
+    parts
+}
+
+/// The first sentence of the comment or docstring heading `source`, skipping
+/// license headers and tool directives.
+fn summary(source: &str) -> Option<String> {
+    let mut lines = source
+        .lines()
+        .map(str::trim)
+        .skip_while(|line| line.is_empty() || line.starts_with("#!"))
+        .peekable();
+    let first = *lines.peek()?;
+
+    let mut text: Vec<String> = Vec::new();
+    if let Some(quote) = ["\"\"\"", "'''"]
+        .into_iter()
+        .find(|quote| first.starts_with(quote))
+    {
+        // A Python docstring.
+        let mut first_line = true;
+        for line in lines {
+            let mut line = line;
+            if first_line {
+                line = &line[quote.len()..];
+                first_line = false;
+            }
+            match line.find(quote) {
+                Some(end) => {
+                    text.push(line[..end].to_string());
+                    break;
+                }
+                None => text.push(line.to_string()),
+            }
+        }
+    } else if first.starts_with("/*") {
+        for line in lines {
+            let end = line.find("*/");
+            let line = line[..end.unwrap_or(line.len())]
+                .trim_start_matches("/**")
+                .trim_start_matches("/*")
+                .trim_start_matches('*');
+            text.push(line.trim().to_string());
+            if end.is_some() {
+                break;
+            }
+        }
+    } else {
+        for line in lines {
+            let Some(comment) = ["//!", "///", "//", "#"]
+                .into_iter()
+                .find_map(|prefix| line.strip_prefix(prefix))
+            else {
+                break;
+            };
+            if line.starts_with("#[") || line.starts_with("#!") {
+                break;
+            }
+            text.push(comment.trim().to_string());
+        }
+    }
+
+    let text = text
+        .iter()
+        .filter(|line| !is_directive(line))
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .join(" ");
+    let lowercase = text.to_lowercase();
+    if lowercase.contains("copyright") || lowercase.contains("license") {
+        return None;
+    }
+    let sentence = match text.find(". ") {
+        Some(end) => &text[..=end],
+        None => text.as_str(),
+    };
+    let sentence = sentence.split_whitespace().collect::<Vec<_>>().join(" ");
+    if sentence.is_empty() {
+        return None;
+    }
+    if sentence.chars().count() > MAX_SUMMARY_CHARS {
+        let truncated: String = sentence.chars().take(MAX_SUMMARY_CHARS - 3).collect();
+        return Some(format!("{}...", truncated.trim_end()));
+    }
+    Some(sentence)
+}
+
+/// Comment lines addressed to tools rather than readers.
+fn is_directive(line: &str) -> bool {
+    [
+        "go:",
+        "+build",
+        "eslint",
+        "@ts-",
+        "-*-",
+        "prettier-",
+        "type:",
+        "noqa",
+        "pylint:",
+    ]
+    .iter()
+    .any(|prefix| line.starts_with(prefix))
+}
+
+/// The imports of the file at `key`, resolved as far as the source alone
+/// allows.
+fn imports(key: &str, source: &str) -> Vec<Import> {
+    let extension = key.rsplit_once('.').map_or("", |(_, extension)| extension);
+    let dir = key.rsplit_once('/').map_or("", |(dir, _)| dir);
+    let mut imports = match extension {
+        "rs" => rust_imports(key, dir, source),
+        "py" | "pyi" => python_imports(dir, source),
+        "go" => go_imports(source),
+        _ => script_imports(dir, source),
+    };
+    let mut seen = HashSet::new();
+    imports.retain(|import| seen.insert(import.clone()));
+    imports
+}
+
+/// `mod` declarations and `use` paths starting with `crate`, `self` or
+/// `super`. Paths into other crates cannot be told apart from the standard
+/// library without the manifest, so they are ignored.
+fn rust_imports(key: &str, dir: &str, source: &str) -> Vec<Import> {
+    // The directory holding this module's submodules.
+    let stem = key
+        .rsplit('/')
+        .next()
+        .unwrap_or(key)
+        .trim_end_matches(".rs");
+    let module_dir = if matches!(stem, "lib" | "main" | "mod") {
+        dir.to_string()
+    } else {
+        join_path(dir, stem)
+    };
+
+    let mut imports = Vec::new();
+    for line in source.lines().map(str::trim) {
+        let line = strip_visibility(line);
+        if let Some(name) = line
+            .strip_prefix("mod ")
+            .and_then(|rest| rest.strip_suffix(';'))
+        {
+            imports.push(Import::Path(join_path(&module_dir, name.trim())));
+        } else if let Some(path) = line.strip_prefix("use ") {
+            let path = path.split(['{', ';', ' ']).next().unwrap_or_default();
+            let mut segments = path.split("::").filter(|s| !s.is_empty() && *s != "*");
+            let mut base = match segments.next() {
+                Some("crate") => None,
+                Some("self") => Some(module_dir.clone()),
+                Some("super") => Some(parent_dir(&module_dir)),
+                _ => continue,
+            };
+            let mut rest = Vec::new();
+            for segment in segments {
+                match (segment, &base) {
+                    ("super", Some(dir)) if rest.is_empty() => base = Some(parent_dir(dir)),
+                    _ => rest.push(segment),
+                }
+            }
+            if rest.is_empty() {
+                continue;
+            }
+            imports.push(match base {
+                Some(base) => Import::Path(join_path(&base, &rest.join("/"))),
+                None => Import::Module(rest.join("/")),
+            });
+        }
+    }
+    imports
+}
+
+fn strip_visibility(line: &str) -> &str {
+    let Some(rest) = line.strip_prefix("pub") else {
+        return line;
+    };
+    match rest.strip_prefix('(') {
+        Some(rest) => rest
+            .split_once(')')
+            .map_or(line, |(_, rest)| rest.trim_start()),
+        None => rest.trim_start(),
+    }
+}
+
+/// `import a.b` and `from a.b import c`, including relative imports.
+fn python_imports(dir: &str, source: &str) -> Vec<Import> {
+    let mut imports = Vec::new();
+    for line in source.lines().map(str::trim) {
+        if let Some(modules) = line.strip_prefix("import ") {
+            for module in modules.split(',') {
+                let module = module.split_whitespace().next().unwrap_or_default();
+                if !module.is_empty() {
+                    imports.push(Import::Module(module.replace('.', "/")));
+                }
+            }
+        } else if let Some(rest) = line.strip_prefix("from ") {
+            let Some((module, names)) = rest.split_once(" import ") else {
+                continue;
+            };
+            let module = module.trim();
+            let dots = module.chars().take_while(|c| *c == '.').count();
+            if dots == 0 {
+                imports.push(Import::Module(module.replace('.', "/")));
+                continue;
+            }
+            let mut base = dir.to_string();
+            for _ in 1..dots {
+                base = parent_dir(&base);
+            }
+            let module = &module[dots..];
+            if module.is_empty() {
+                // `from . import a, b` imports sibling modules.
+                let names = names.trim_matches(|c: char| c == '(' || c == ')' || c.is_whitespace());
+                for name in names.split(',') {
+                    let name = name.split_whitespace().next().unwrap_or_default();
+                    if !name.is_empty() {
+                        imports.push(Import::Path(join_path(&base, name)));
+                    }
+                }
+            } else {
+                imports.push(Import::Path(join_path(&base, &module.replace('.', "/"))));
+            }
+        }
+    }
+    imports
+}
+
+/// Relative `import`, `export ... from` and `require` specifiers.
+fn script_imports(dir: &str, source: &str) -> Vec<Import> {
+    let mut imports = Vec::new();
+    for pattern in ["from ", "import ", "import(", "require("] {
+        for (start, _) in source.match_indices(pattern) {
+            let rest = source[start + pattern.len()..].trim_start();
+            let Some(quote) = rest
+                .chars()
+                .next()
+                .filter(|c| matches!(c, '\'' | '"' | '`'))
+            else {
+                continue;
+            };
+            let Some(specifier) = rest[1..].split(quote).next() else {
+                continue;
+            };
+            if specifier.starts_with("./") || specifier.starts_with("../") {
+                let path = join_path(dir, specifier);
+                imports.push(Import::Path(strip_script_extension(&path).to_string()));
+            }
+        }
+    }
+    imports
+}
+
+fn strip_script_extension(path: &str) -> &str {
+    [".js", ".mjs", ".cjs", ".jsx", ".ts", ".mts", ".cts", ".tsx"]
+        .iter()
+        .find_map(|extension| path.strip_suffix(extension))
+        .unwrap_or(path)
+}
+
+/// Import paths from `import "a/b"` and `import ( ... )` blocks.
+fn go_imports(source: &str) -> Vec<Import> {
+    let mut imports = Vec::new();
+    let mut in_block = false;
+    for line in source.lines().map(str::trim) {
+        let spec = if in_block {
+            if line.starts_with(')') {
+                in_block = false;
+                continue;
+            }
+            line
+        } else if let Some(rest) = line.strip_prefix("import") {
+            let rest = rest.trim_start();
+            if rest.starts_with('(') {
+                in_block = true;
+                continue;
+            }
+            rest
+        } else {
+            continue;
+        };
+        if let Some(path) = spec.split('"').nth(1)
+            && path.contains('/')
+        {
+            imports.push(Import::Package(path.to_string()));
+        }
+    }
+    imports
+}
+
+/// Joins `/`-separated paths, resolving `.` and `..`.
+fn join_path(base: &str, relative: &str) -> String {
+    let mut parts: Vec<&str> = base.split('/').filter(|p| !p.is_empty()).collect();
+    for part in relative.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop();
+            }
+            part => parts.push(part),
+        }
+    }
+    parts.join("/")
+}
+
+fn parent_dir(dir: &str) -> String {
+    dir.rsplit_once('/')
+        .map_or_else(String::new, |(parent, _)| parent.to_string())
+}
+
+impl Index {
+    fn new(stored: StoredIndex) -> Self {
+        let paths: Vec<String> = stored.files.keys().cloned().collect();
+        let edges = resolve_imports(&stored, &paths);
+        let mut imported_by = vec![0; paths.len()];
+        for targets in &edges {
+            for &target in targets {
+                imported_by[target] += 1;
+            }
+        }
+        let centrality = pagerank(&edges);
+
+        let mut document_frequency: HashMap<String, usize> = HashMap::new();
+        let mut total_length = 0u64;
+        for entry in stored.files.values() {
+            for (term, count) in &entry.terms {
+                *document_frequency.entry(term.clone()).or_default() += 1;
+                total_length += u64::from(*count);
+            }
+        }
+        let average_length = if paths.is_empty() {
+            0.0
+        } else {
+            total_length as f64 / paths.len() as f64
+        };
+
+        Self {
+            stored,
+            paths,
+            imported_by,
+            centrality,
+            document_frequency,
+            average_length,
+        }
+    }
+
+    fn entry(&self, file: usize) -> Option<&FileEntry> {
+        self.stored.files.get(&self.paths[file])
+    }
+
+    /// Ranks files by BM25 over `query`'s identifier terms, boosted by their
+    /// centrality in the import graph.
+    fn search(&self, query: &str, limit: usize) -> Result<String, String> {
+        let mut query_terms = Vec::new();
+        identifier_terms(query, |term| {
+            if !query_terms.contains(&term) {
+                query_terms.push(term);
+            }
+        });
+        if query_terms.is_empty() {
+            return Err("`query` must contain at least one identifier or word".to_string());
+        }
+
+        let documents = self.paths.len() as f64;
+        let mut scored: Vec<(f64, usize)> = Vec::new();
+        for (file, entry) in self.stored.files.values().enumerate() {
+            let length = entry
+                .terms
+                .values()
+                .map(|count| f64::from(*count))
+                .sum::<f64>();
+            let normalization = 1.0 - BM25_B + BM25_B * length / self.average_length.max(1.0);
+            let mut score = 0.0;
+            for term in &query_terms {
+                let Some(&frequency) = entry.terms.get(term) else {
+                    continue;
+                };
+                let frequency = f64::from(frequency);
+                let containing = self.document_frequency.get(term).copied().unwrap_or(0) as f64;
+                let idf = (1.0 + (documents - containing + 0.5) / (containing + 0.5)).ln();
+                score += idf * frequency * (BM25_K1 + 1.0) / (frequency + BM25_K1 * normalization);
+            }
+            if score > 0.0 {
+                scored.push((
+                    score * (1.0 + CENTRALITY_WEIGHT * self.centrality[file]),
+                    file,
+                ));
+            }
+        }
+        if scored.is_empty() {
+            return Ok(format!("No indexed files match `{query}`."));
+        }
+        scored.sort_by(|a, b| b.0.total_cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+
+        let total = scored.len();
+        let mut out = String::new();
+        for &(score, file) in scored.iter().take(limit) {
+            out.push_str(&self.describe(file, score, &query_terms));
+        }
+        if total > limit {
+            let more = total - limit;
+            out.push_str(&format!("[... {more} more matching files ...]\n"));
+        }
+        Ok(out)
+    }
+
+    /// A search result: the path, the summary and the symbols, those
+    /// matching `query_terms` first.
+    fn describe(&self, file: usize, score: f64, query_terms: &[String]) -> String {
+        let mut out = format!("{} (score {score:.2}", self.paths[file]);
+        match self.imported_by[file] {
+            0 => out.push_str(")\n"),
+            1 => out.push_str(", imported by 1 file)\n"),
+            n => out.push_str(&format!(", imported by {n} files)\n")),
+        }
+
+        let Some(entry) = self.entry(file) else {
+            return out;
+        };
+        if let Some(summary) = &entry.summary {
+            out.push_str(&format!("  {summary}\n"));
+        }
+        let matches_query = |symbol: &&String| {
+            let mut matched = false;
+            identifier_terms(symbol, |term| matched |= query_terms.contains(&term));
+            matched
+        };
+        let (mut symbols, others): (Vec<&String>, Vec<&String>) =
+            entry.symbols.iter().partition(matches_query);
+        symbols.extend(others);
+        if !symbols.is_empty() {
+            let shown: Vec<&str> = symbols
+                .iter()
+                .take(SYMBOLS_PER_RESULT)
+                .map(|symbol| symbol.as_str())
+                .collect();
+            out.push_str(&format!("  symbols: {}", shown.join(", ")));
+            if symbols.len() > SYMBOLS_PER_RESULT {
+                out.push_str(&format!(" (+{} more)", symbols.len() - SYMBOLS_PER_RESULT));
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    /// The `limit` files with the highest centrality, most imported first on
+    /// ties, as `path: summary` lines.
+    fn map(&self, limit: usize) -> Option<String> {
+        let mut files: Vec<usize> = (0..self.paths.len()).collect();
+        files.sort_by(|&a, &b| {
+            self.centrality[b]
+                .total_cmp(&self.centrality[a])
+                .then_with(|| self.imported_by[b].cmp(&self.imported_by[a]))
+                .then_with(|| a.cmp(&b))
+        });
+        let lines: Vec<String> = files
+            .into_iter()
+            .take(limit)
+            .map(|file| {
+                let path = &self.paths[file];
+                match self.entry(file).and_then(|entry| entry.summary.as_deref()) {
+                    Some(summary) => format!("{path}: {summary}"),
+                    None => path.clone(),
+                }
+            })
+            .collect();
+        (!lines.is_empty()).then(|| lines.join("\n"))
+    }
+}
+
+/// Resolves every file's imports to indexed files.
+fn resolve_imports(stored: &StoredIndex, paths: &[String]) -> Vec<Vec<usize>> {
+    // Module paths: the path without extension and, for `mod.rs`,
+    // `index.ts`, `__init__.py` and the like, the directory.
+    let mut modules: HashMap<&str, Vec<usize>> = HashMap::new();
+    let mut module_suffixes: HashMap<&str, Vec<usize>> = HashMap::new();
+    let mut dir_suffixes: HashMap<&str, Vec<usize>> = HashMap::new();
+    for (file, path) in paths.iter().enumerate() {
+        let stem = path
+            .rsplit_once('.')
+            .map_or(path.as_str(), |(stem, _)| stem);
+        let (dir, name) = stem.rsplit_once('/').unwrap_or(("", stem));
+        let mut keys = vec![stem];
+        if matches!(name, "mod" | "index" | "__init__") && !dir.is_empty() {
+            keys.push(dir);
+        }
+        for key in keys {
+            modules.entry(key).or_default().push(file);
+            for suffix in suffixes(key) {
+                module_suffixes.entry(suffix).or_default().push(file);
+            }
+        }
+        if !dir.is_empty() {
+            for suffix in suffixes(dir) {
+                dir_suffixes.entry(suffix).or_default().push(file);
+            }
+        }
+    }
+
+    paths
+        .iter()
+        .enumerate()
+        .map(|(file, path)| {
+            let Some(entry) = stored.files.get(path) else {
+                return Vec::new();
+            };
+            let mut targets = Vec::new();
+            for import in &entry.imports {
+                let found = match import {
+                    // Trailing segments may be items: drop them until a
+                    // module matches.
+                    Import::Path(target) => prefixes(target)
+                        .find_map(|prefix| modules.get(prefix))
+                        .map(|files| vec![closest(path, paths, files)]),
+                    Import::Module(target) => prefixes(target)
+                        .find_map(|prefix| module_suffixes.get(prefix))
+                        .map(|files| vec![closest(path, paths, files)]),
+                    // Leading segments name the module: drop them until a
+                    // directory matches, keeping at least two.
+                    Import::Package(target) => suffixes(target)
+                        .take_while(|suffix| suffix.contains('/'))
+                        .find_map(|suffix| dir_suffixes.get(suffix))
+                        .cloned(),
+                };
+                for target in found.unwrap_or_default() {
+                    if target != file && !targets.contains(&target) {
+                        targets.push(target);
+                    }
+                }
+            }
+            targets
+        })
+        .collect()
+}
+
+/// `a/b/c`, `b/c`, `c`.
+fn suffixes(path: &str) -> impl Iterator<Item = &str> {
+    std::iter::once(path).chain(path.match_indices('/').map(|(i, _)| &path[i + 1..]))
+}
+
+/// `a/b/c`, `a/b`, `a`.
+fn prefixes(path: &str) -> impl Iterator<Item = &str> {
+    std::iter::once(path).chain(path.rmatch_indices('/').map(|(i, _)| &path[..i]))
+}
+
+/// Of several candidate files, the one sharing the longest directory prefix
+/// with `from`.
+fn closest(from: &str, paths: &[String], candidates: &[usize]) -> usize {
+    let shared = |candidate: &usize| {
+        paths[*candidate]
+            .split('/')
+            .zip(from.split('/'))
+            .take_while(|(a, b)| a == b)
+            .count()
+    };
+    candidates
+        .iter()
+        .copied()
+        .max_by(|a, b| shared(a).cmp(&shared(b)).then_with(|| b.cmp(a)))
+        .unwrap_or_default()
+}
+
+/// PageRank over the import graph, where importing a file votes for it,
+/// scaled so that the most central file scores 1.
+fn pagerank(edges: &[Vec<usize>]) -> Vec<f64> {
+    let n = edges.len();
+    if n == 0 {
+        return Vec::new();
+    }
+    let base = (1.0 - PAGERANK_DAMPING) / n as f64;
+    let mut rank = vec![1.0 / n as f64; n];
+    for _ in 0..PAGERANK_ITERATIONS {
+        // Files importing nothing spread their rank evenly.
+        let dangling: f64 = edges
+            .iter()
+            .zip(&rank)
+            .filter(|(targets, _)| targets.is_empty())
+            .map(|(_, rank)| rank)
+            .sum();
+        let mut next = vec![base + PAGERANK_DAMPING * dangling / n as f64; n];
+        for (file, targets) in edges.iter().enumerate() {
+            let share = PAGERANK_DAMPING * rank[file] / targets.len().max(1) as f64;
+            for &target in targets {
+                next[target] += share;
+            }
+        }
+        rank = next;
+    }
+    let max = rank.iter().copied().fold(0.0, f64::max);
+    if max > 0.0 {
+        rank.iter_mut().for_each(|rank| *rank /= max);
+    }
+    rank
+}
+
+pub(crate) fn create_repo_search_tool() -> OpenAiTool {
+    let mut properties = BTreeMap::new();
+    properties.insert(
+        "query".to_string(),
+        JsonSchema::String {
+            description: Some(
+                "Identifiers or words to look for, e.g. `session config loading` or `parseRequest`.".to_string(),
+            ),
+        },
+    );
+    properties.insert(
+        "limit".to_string(),
+        JsonSchema::Number {
+            description: Some(format!(
+                "Maximum number of files to return (default {DEFAULT_RESULTS}, at most {MAX_RESULTS})."
+            )),
+        },
+    );
+    OpenAiTool::Function(ResponsesApiTool {
+        name: REPO_SEARCH_TOOL_NAME.to_string(),
+        description: "Ranks the source files of the repository by how well their identifiers, paths and symbol names match a query, favouring files many others import. Returns each file's summary and symbols. Use it to find where to start reading.".to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: Some(vec!["query".to_string()]),
+            additional_properties: Some(false),
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    fn write(dir: &Path, name: &str, contents: &str) {
+        let path = dir.join(name);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).unwrap();
+        }
+        std::fs::write(&path, contents).unwrap();
+    }
+
+    #[test]
+    fn splits_identifiers_into_terms() {
+        let mut terms = Vec::new();
+        identifier_terms("parseHTTPRequest_v2(x, load_config)", |term| {
+            terms.push(term)
+        });
+        assert_eq!(
+            terms,
+            vec![
+                "parsehttprequest_v2",
+                "parse",
+                "http",
+                "request",
+                "v2",
+                "load_config",
+                "load",
+                "config",
+            ]
+        );
+    }
+
+    #[test]
+    fn extracts_summaries_and_imports() {
+        assert_eq!(
+            summary("// Copyright 2024 Example\n\n//! Parses config files. More text.\n"),
+            None
+        );
+        assert_eq!(
+            summary("#!/usr/bin/env python\n\"\"\"Loads the config.\n\nDetails.\n\"\"\"\n"),
+            Some("Loads the config.".to_string())
+        );
+        assert_eq!(
+            summary("//! Parses config\n//! files. Then more.\nuse std::fs;\n"),
+            Some("Parses config files.".to_string())
+        );
+
+        assert_eq!(
+            imports(
+                "src/config/mod.rs",
+                "pub(crate) mod loader;\nuse crate::util::{a, b};\nuse super::Thing;\nuse std::fs;\n",
+            ),
+            vec![
+                Import::Path("src/config/loader".to_string()),
+                Import::Module("util".to_string()),
+                Import::Path("src/Thing".to_string()),
+            ]
+        );
+        assert_eq!(
+            imports(
+                "pkg/app/main.py",
+                "import os, pkg.util as u\nfrom . import models\nfrom ..core.db import connect\n",
+            ),
+            vec![
+                Import::Module("os".to_string()),
+                Import::Module("pkg/util".to_string()),
+                Import::Path("pkg/app/models".to_string()),
+                Import::Path("pkg/core/db".to_string()),
+            ]
+        );
+        assert_eq!(
+            imports(
+                "web/src/app.ts",
+                "import { a } from './lib/a.js';\nimport React from 'react';\nconst b = require(\"../b\");\n",
+            ),
+            vec![
+                Import::Path("web/src/lib/a".to_string()),
+                Import::Path("web/b".to_string()),
+            ]
+        );
+        assert_eq!(
+            imports(
+                "cmd/main.go",
+                "package main\n\nimport (\n\t\"fmt\"\n\tdb \"example.com/app/internal/db\"\n)\n",
+            ),
+            vec![Import::Package("example.com/app/internal/db".to_string())]
+        );
+    }
+
+    #[test]
+    fn search_ranks_by_identifiers_and_centrality() {
+        let repo = TempDir::new().unwrap();
+        let root = repo.path();
+        write(
+            root,
+            "src/lib.rs",
+            "//! The crate root.\nmod config;\nmod session;\n",
+        );
+        write(
+            root,
+            "src/config.rs",
+            "//! Loads the user configuration.\npub struct Config;\nimpl Config {\n    pub fn load() -> Self { Config }\n}\n",
+        );
+        write(
+            root,
+            "src/session.rs",
+            "use crate::config::Config;\npub fn start(config: Config) {}\n",
+        );
+        let mut stored = StoredIndex::default();
+        refresh(root, &mut stored);
+        let index = Index::new(stored);
+
+        let results = index.search("load config", 10).unwrap();
+        assert_eq!(
+            results.lines().next(),
+            Some("src/config.rs (score 2.29, imported by 2 files)")
+        );
+        assert_eq!(
+            results.lines().nth(1),
+            Some("  Loads the user configuration.")
+        );
+        assert_eq!(
+            results.lines().nth(2),
+            Some("  symbols: struct Config, impl Config, fn Config::load")
+        );
+        assert_eq!(
+            index.map(2),
+            Some("src/config.rs: Loads the user configuration.\nsrc/session.rs".to_string())
+        );
+        assert_eq!(
+            index.search("zebra", 10),
+            Ok("No indexed files match `zebra`.".to_string())
+        );
+    }
+
+    #[test]
+    fn refresh_only_reparses_changed_files() {
+        let repo = TempDir::new().unwrap();
+        let root = repo.path();
+        write(root, "a.py", "def alpha():\n    pass\n");
+        write(root, "b.py", "def beta():\n    pass\n");
+        let mut stored = StoredIndex::default();
+        assert!(refresh(root, &mut stored));
+        assert!(!refresh(root, &mut stored));
+
+        // An entry whose file kept its size and mtime is not parsed again.
+        if let Some(entry) = stored.files.get_mut("a.py") {
+            entry.symbols = vec!["def stale".to_string()];
+        }
+        write(root, "b.py", "def gamma_function():\n    pass\n");
+        write(root, "c.py", "def delta():\n    pass\n");
+        assert!(refresh(root, &mut stored));
+        assert_eq!(stored.files["a.py"].symbols, vec!["def stale"]);
+        assert_eq!(stored.files["b.py"].symbols, vec!["def gamma_function"]);
+        assert_eq!(stored.files["c.py"].symbols, vec!["def delta"]);
+
+        std::fs::remove_file(root.join("a.py")).unwrap();
+        assert!(refresh(root, &mut stored));
+        assert_eq!(
+            stored.files.keys().collect::<Vec<_>>(),
+            vec!["b.py", "c.py"]
+        );
+
+        let store = root.join("index.json");
+        save(&store, &stored).unwrap();
+        assert_eq!(load(&store).files, stored.files);
+    }
+
+    #[tokio::test]
+    async fn repository_map_does_not_wait_for_the_walk() {
+        let home = TempDir::new().unwrap();
+        let repo = TempDir::new().unwrap();
+        write(
+            repo.path(),
+            "a.py",
+            "\"\"\"Alpha helpers.\"\"\"\ndef alpha():\n    pass\n",
+        );
+        let index = RepoIndex::new(home.path(), repo.path().to_path_buf(), 5);
+
+        // Nothing is indexed yet, and asking for the map does not index.
+        assert_eq!(index.repository_map().await, None);
+
+        index.refresh_in_background();
+        let mut map = None;
+        for _ in 0..200 {
+            map = index.repository_map().await;
+            if map.is_some() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert_eq!(map, Some("a.py: Alpha helpers.".to_string()));
+
+        // A later session reads the stored index before refreshing it.
+        let later = RepoIndex::new(home.path(), repo.path().to_path_buf(), 5);
+        assert_eq!(
+            later.repository_map().await,
+            Some("a.py: Alpha helpers.".to_string())
+        );
+    }
+}
@@ -1,8 +1,12 @@
 use std::cmp::Reverse;
+use std::io::SeekFrom;
 use std::io::{self};
 use std::path::Path;
 use std::path::PathBuf;
 
+use tokio::io::AsyncReadExt;
+use tokio::io::AsyncSeekExt;
+
 use time::OffsetDateTime;
 use time::PrimitiveDateTime;
 use time::format_description::FormatItem;
@@ -13,6 +17,8 @@ use super::SESSIONS_SUBDIR;
 use super::recorder::RolloutItem;
 use super::recorder::RolloutLine;
 use crate::protocol::EventMsg;
+use crate::protocol::InputMessageKind;
+use crate::protocol::USER_MESSAGE_BEGIN;
 
 /// Returned page of conversation summaries.
 #[derive(Debug, Default, PartialEq)]
@@ -36,10 +42,42 @@ pub struct ConversationItem {
     pub head: Vec<serde_json::Value>,
 }
 
+/// A conversation's metadata and messages, read from its rollout file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConversationSummary {
+    /// Absolute path to the rollout file.
+    pub path: PathBuf,
+    /// Session start time from the session meta line.
+    pub timestamp: String,
+    pub cwd: PathBuf,
+    /// Remote URL of the git repository the session ran in, if any.
+    pub git_repository_url: Option<String>,
+    pub git_branch: Option<String>,
+    /// The user's messages and the assistant's replies, oldest first.
+    pub messages: Vec<ConversationMessage>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConversationMessage {
+    pub role: MessageRole,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageRole {
+    User,
+    Assistant,
+}
+
 /// Hard cap to bound worst‑case work per request.
 const MAX_SCAN_FILES: usize = 100;
 const HEAD_RECORD_LIMIT: usize = 10;
 
+/// Bytes read from the start and from the end of a rollout file when
+/// summarizing it; long sessions with large tool outputs can be many MiB.
+pub(crate) const SUMMARY_HEAD_BYTES: u64 = 256 * 1024;
+pub(crate) const SUMMARY_TAIL_BYTES: u64 = 256 * 1024;
+
 /// Pagination cursor identifying a file by timestamp and UUID.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Cursor {
@@ -104,6 +142,140 @@ pub(crate) async fn get_conversations(
     Ok(result)
 }
 
+/// Paths of all rollout files, ordered like [`get_conversations`]. Only the
+/// directory tree is read, so unlike [`get_conversations`] this is not capped.
+pub(crate) async fn get_conversation_paths(codex_home: &Path) -> io::Result<Vec<PathBuf>> {
+    let root = codex_home.join(SESSIONS_SUBDIR);
+    if !root.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut paths = Vec::new();
+    for (_year, year_path) in collect_dirs_desc(&root, |s| s.parse::<u16>().ok()).await? {
+        for (_month, month_path) in collect_dirs_desc(&year_path, |s| s.parse::<u8>().ok()).await? {
+            for (_day, day_path) in collect_dirs_desc(&month_path, |s| s.parse::<u8>().ok()).await?
+            {
+                let mut day_files = collect_files(&day_path, |name_str, path| {
+                    parse_timestamp_uuid_from_filename(name_str)
+                        .map(|(ts, id)| (ts, id, path.to_path_buf()))
+                })
+                .await?;
+                day_files.sort_by_key(|(ts, sid, _path)| (Reverse(*ts), Reverse(*sid)));
+                paths.extend(day_files.into_iter().map(|(_ts, _sid, path)| path));
+            }
+        }
+    }
+    Ok(paths)
+}
+
+/// Read the session meta and the user and assistant messages of the rollout
+/// file at `path`. Like [`get_conversations`], files without session meta or
+/// a user message are skipped by returning `None`. Only the first and last
+/// [`SUMMARY_HEAD_BYTES`] and [`SUMMARY_TAIL_BYTES`] of a large file are read,
+/// so messages from the middle of a long session are left out.
+pub(crate) async fn get_conversation_summary(
+    path: &Path,
+) -> io::Result<Option<ConversationSummary>> {
+    let contents = read_head_and_tail(path).await?;
+    let mut summary: Option<ConversationSummary> = None;
+    let mut messages: Vec<ConversationMessage> = Vec::new();
+
+    for line in contents.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+        let Ok(rollout_line) = serde_json::from_str::<RolloutLine>(trimmed) else {
+            continue;
+        };
+        match rollout_line.item {
+            // A resumed session records its meta again; the first one wins.
+            RolloutItem::SessionMeta(session_meta_line) if summary.is_none() => {
+                let (git_repository_url, git_branch) = match session_meta_line.git {
+                    Some(git) => (git.repository_url, git.branch),
+                    None => (None, None),
+                };
+                summary = Some(ConversationSummary {
+                    path: path.to_path_buf(),
+                    timestamp: session_meta_line.meta.timestamp,
+                    cwd: session_meta_line.meta.cwd,
+                    git_repository_url,
+                    git_branch,
+                    messages: Vec::new(),
+                });
+            }
+            RolloutItem::EventMsg(EventMsg::UserMessage(ev))
+                if matches!(ev.kind, None | Some(InputMessageKind::Plain)) =>
+            {
+                // Strip ide context.
+                let text = match ev.message.find(USER_MESSAGE_BEGIN) {
+                    Some(idx) => &ev.message[idx + USER_MESSAGE_BEGIN.len()..],
+                    None => ev.message.as_str(),
+                };
+                if !text.trim().is_empty() {
+                    messages.push(ConversationMessage {
+                        role: MessageRole::User,
+                        text: text.trim().to_string(),
+                    });
+                }
+            }
+            RolloutItem::EventMsg(EventMsg::AgentMessage(ev)) => {
+                messages.push(ConversationMessage {
+                    role: MessageRole::Assistant,
+                    text: ev.message,
+                });
+            }
+            _ => {}
+        }
+    }
+
+    if !messages.iter().any(|m| m.role == MessageRole::User) {
+        return Ok(None);
+    }
+    Ok(summary.map(|summary| ConversationSummary {
+        messages,
+        ..summary
+    }))
+}
+
+/// Reads the whole file at `path` if it is small, and otherwise only its
+/// first [`SUMMARY_HEAD_BYTES`] and last [`SUMMARY_TAIL_BYTES`], dropping the
+/// partial lines at each cut.
+async fn read_head_and_tail(path: &Path) -> io::Result<String> {
+    let mut file = tokio::fs::File::open(path).await?;
+    let len = file.metadata().await?.len();
+    let mut contents = Vec::new();
+    if len <= SUMMARY_HEAD_BYTES + SUMMARY_TAIL_BYTES {
+        file.read_to_end(&mut contents).await?;
+        return Ok(String::from_utf8_lossy(&contents).into_owned());
+    }
+
+    (&mut file)
+        .take(SUMMARY_HEAD_BYTES)
+        .read_to_end(&mut contents)
+        .await?;
+    let head_end = contents
+        .iter()
+        .rposition(|b| *b == b'\n')
+        .map_or(0, |i| i + 1);
+    contents.truncate(head_end);
+
+    // Start one byte early so that a tail beginning exactly at a line start
+    // keeps that line.
+    let mut tail = Vec::new();
+    file.seek(SeekFrom::Start(len - SUMMARY_TAIL_BYTES - 1))
+        .await?;
+    file.take(SUMMARY_TAIL_BYTES + 1)
+        .read_to_end(&mut tail)
+        .await?;
+    let tail_start = tail
+        .iter()
+        .position(|b| *b == b'\n')
+        .map_or(tail.len(), |i| i + 1);
+    contents.extend_from_slice(&tail[tail_start..]);
+    Ok(String::from_utf8_lossy(&contents).into_owned())
+}
+
 /// Load the full contents of a single conversation session file at `path`.
 /// Returns the entire file contents as a String.
 #[allow(dead_code)]
@@ -323,6 +495,7 @@ async fn read_head_and_flags(
                     saw_user_event = true;
                 }
             }
+            RolloutItem::ExecEnv(_) => {}
         }
     }
 
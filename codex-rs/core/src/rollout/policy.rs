@@ -10,6 +10,8 @@ pub(crate) fn is_persisted_response_item(item: &RolloutItem) -> bool {
         RolloutItem::EventMsg(ev) => should_persist_event_msg(ev),
         // Always persist session meta
         RolloutItem::SessionMeta(_) => true,
+        // Only emitted when the user opted in.
+        RolloutItem::ExecEnv(_) => true,
     }
 }
 
@@ -59,6 +61,7 @@ pub(crate) fn should_persist_event_msg(ev: &EventMsg) -> bool {
         | EventMsg::PatchApplyBegin(_)
         | EventMsg::PatchApplyEnd(_)
         | EventMsg::TurnDiff(_)
+        | EventMsg::BackgroundProcessesUpdate(_)
         | EventMsg::GetHistoryEntryResponse(_)
         | EventMsg::McpListToolsResponse(_)
         | EventMsg::ListCustomPromptsResponse(_)
@@ -21,8 +21,11 @@ use tracing::info;
 use tracing::warn;
 
 use super::SESSIONS_SUBDIR;
+use super::list::ConversationSummary;
 use super::list::ConversationsPage;
 use super::list::Cursor;
+use super::list::get_conversation_paths;
+use super::list::get_conversation_summary;
 use super::list::get_conversations;
 use super::policy::is_persisted_response_item;
 use crate::config::Config;
@@ -32,6 +35,7 @@ use crate::default_client::ORIGINATOR;
 use crate::git_info::GitInfo;
 use crate::git_info::collect_git_info;
 use crate::protocol::EventMsg;
+use crate::redact::Redactor;
 use codex_protocol::models::ResponseItem;
 
 #[derive(Serialize, Deserialize, Clone, Default, Debug)]
@@ -47,9 +51,9 @@ pub struct SessionMeta {
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct SessionMetaLine {
     #[serde(flatten)]
-    meta: SessionMeta,
+    pub(crate) meta: SessionMeta,
     #[serde(skip_serializing_if = "Option::is_none")]
-    git: Option<GitInfo>,
+    pub(crate) git: Option<GitInfo>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -58,6 +62,21 @@ pub enum RolloutItem {
     SessionMeta(SessionMetaLine),
     ResponseItem(ResponseItem),
     EventMsg(EventMsg),
+    ExecEnv(ExecEnvRecord),
+}
+
+/// Names of the environment variables passed to a single tool call. Only
+/// recorded when `shell_environment_policy.record_in_rollout` is set; values
+/// are never written.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ExecEnvRecord {
+    pub call_id: String,
+    pub variables: Vec<String>,
+    /// The call ran in the persistent shell, so `variables` is only what a
+    /// fresh process would have received: the shell keeps the environment it
+    /// was started with, as changed by the commands run in it since.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub inexact: bool,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -135,6 +154,21 @@ impl RolloutRecorder {
         get_conversations(codex_home, page_size, cursor).await
     }
 
+    /// List the paths of all recorded conversations, newest first. Unlike
+    /// [`Self::list_conversations`] this does not open the files, so it is not
+    /// capped.
+    pub async fn list_conversation_paths(codex_home: &Path) -> std::io::Result<Vec<PathBuf>> {
+        get_conversation_paths(codex_home).await
+    }
+
+    /// Read the metadata and messages of the conversation recorded at `path`.
+    /// Returns `None` for files that [`Self::list_conversations`] would skip.
+    pub async fn read_conversation_summary(
+        path: &Path,
+    ) -> std::io::Result<Option<ConversationSummary>> {
+        get_conversation_summary(path).await
+    }
+
     /// Attempt to create a new [`RolloutRecorder`]. If the sessions directory
     /// cannot be created or the rollout file cannot be opened we return the
     /// error so the caller can decide whether to disable persistence.
@@ -185,6 +219,9 @@ impl RolloutRecorder {
         // Clone the cwd for the spawned task to collect git info asynchronously
         let cwd = config.cwd.clone();
 
+        let redactor = Redactor::new(&config.redaction)
+            .map_err(|e| IoError::other(format!("invalid redaction pattern: {e}")))?;
+
         // A reasonably-sized bounded channel. If the buffer fills up the send
         // future will yield, which is fine – we only need to ensure we do not
         // perform *blocking* I/O on the caller's thread.
@@ -193,7 +230,7 @@ impl RolloutRecorder {
         // Spawn a Tokio task that owns the file handle and performs async
         // writes. Using `tokio::fs::File` keeps everything on the async I/O
         // driver instead of blocking the runtime.
-        tokio::task::spawn(rollout_writer(file, rx, meta, cwd));
+        tokio::task::spawn(rollout_writer(file, rx, meta, cwd, redactor));
 
         Ok(Self { tx, rollout_path })
     }
@@ -256,6 +293,9 @@ impl RolloutRecorder {
                     RolloutItem::EventMsg(_ev) => {
                         items.push(RolloutItem::EventMsg(_ev));
                     }
+                    RolloutItem::ExecEnv(record) => {
+                        items.push(RolloutItem::ExecEnv(record));
+                    }
                 },
                 Err(e) => {
                     warn!("failed to parse rollout line: {v:?}, error: {e}");
@@ -356,8 +396,9 @@ async fn rollout_writer(
     mut rx: mpsc::Receiver<RolloutCmd>,
     mut meta: Option<SessionMeta>,
     cwd: std::path::PathBuf,
+    redactor: Redactor,
 ) -> std::io::Result<()> {
-    let mut writer = JsonlWriter { file };
+    let mut writer = JsonlWriter { file, redactor };
 
     // If we have a meta, collect git info asynchronously and write meta first
     if let Some(session_meta) = meta.take() {
@@ -394,6 +435,7 @@ async fn rollout_writer(
 
 struct JsonlWriter {
     file: tokio::fs::File,
+    redactor: Redactor,
 }
 
 impl JsonlWriter {
@@ -412,7 +454,11 @@ impl JsonlWriter {
         self.write_line(&line).await
     }
     async fn write_line(&mut self, item: &impl serde::Serialize) -> std::io::Result<()> {
-        let mut json = serde_json::to_string(item)?;
+        // Round-trip through `Value` so every string in the line is scrubbed,
+        // whatever item type it belongs to.
+        let mut value = serde_json::to_value(item)?;
+        self.redactor.redact_json(&mut value);
+        let mut json = serde_json::to_string(&value)?;
         json.push('\n');
         self.file.write_all(json.as_bytes()).await?;
         self.file.flush().await?;
@@ -13,10 +13,18 @@ use time::macros::format_description;
 use uuid::Uuid;
 
 use crate::rollout::list::ConversationItem;
+use crate::rollout::list::ConversationMessage;
+use crate::rollout::list::ConversationSummary;
 use crate::rollout::list::ConversationsPage;
 use crate::rollout::list::Cursor;
+use crate::rollout::list::MessageRole;
+use crate::rollout::list::SUMMARY_HEAD_BYTES;
+use crate::rollout::list::SUMMARY_TAIL_BYTES;
 use crate::rollout::list::get_conversation;
+use crate::rollout::list::get_conversation_paths;
+use crate::rollout::list::get_conversation_summary;
 use crate::rollout::list::get_conversations;
+use crate::rollout::recorder::ExecEnvRecord;
 
 fn write_session_file(
     root: &Path,
@@ -366,6 +374,132 @@ async fn test_get_conversation_contents() {
     assert_eq!(content, expected_content);
 }
 
+#[tokio::test]
+async fn test_conversation_paths_are_not_capped() {
+    let temp = TempDir::new().unwrap();
+    let home = temp.path();
+
+    for i in 0..120u128 {
+        let ts = format!("2025-05-{:02}T10-00-{:02}", 1 + i / 60, i % 60);
+        write_session_file(home, &ts, Uuid::from_u128(i), 0).unwrap();
+    }
+
+    let paths = get_conversation_paths(home).await.unwrap();
+
+    assert_eq!(paths.len(), 120);
+    let newest = format!("rollout-2025-05-02T10-00-59-{}.jsonl", Uuid::from_u128(119));
+    assert_eq!(
+        paths[0].file_name().unwrap().to_str(),
+        Some(newest.as_str())
+    );
+}
+
+#[tokio::test]
+async fn test_conversation_summary_reads_meta_and_messages() {
+    let temp = TempDir::new().unwrap();
+    let home = temp.path();
+
+    let uuid = Uuid::from_u128(7);
+    let ts = "2025-06-01T08-00-00";
+    write_session_file(home, ts, uuid, 0).unwrap();
+    let path = get_conversation_paths(home).await.unwrap().remove(0);
+    let mut file = fs::OpenOptions::new().append(true).open(&path).unwrap();
+    let records = [
+        serde_json::json!({"timestamp": ts, "type": "event_msg", "payload": {"type": "user_message", "message": "<user_instructions>be brief</user_instructions>", "kind": "user_instructions"}}),
+        serde_json::json!({"timestamp": ts, "type": "event_msg", "payload": {"type": "agent_message", "message": "Hi! What can I do?"}}),
+        serde_json::json!({"timestamp": ts, "type": "event_msg", "payload": {"type": "user_message", "message": "fix the build", "kind": "plain"}}),
+    ];
+    for record in records {
+        writeln!(file, "{record}").unwrap();
+    }
+
+    let summary = get_conversation_summary(&path).await.unwrap();
+
+    let message = |role, text: &str| ConversationMessage {
+        role,
+        text: text.to_string(),
+    };
+    assert_eq!(
+        summary,
+        Some(ConversationSummary {
+            path,
+            timestamp: ts.to_string(),
+            cwd: ".".into(),
+            git_repository_url: None,
+            git_branch: None,
+            messages: vec![
+                message(MessageRole::User, "Hello from user"),
+                message(MessageRole::Assistant, "Hi! What can I do?"),
+                message(MessageRole::User, "fix the build"),
+            ],
+        })
+    );
+}
+
+#[tokio::test]
+async fn test_conversation_summary_reads_only_head_and_tail_of_large_files() {
+    let temp = TempDir::new().unwrap();
+    let home = temp.path();
+
+    let ts = "2025-06-02T08-00-00";
+    write_session_file(home, ts, Uuid::from_u128(8), 0).unwrap();
+    let path = get_conversation_paths(home).await.unwrap().remove(0);
+    let mut file = fs::OpenOptions::new().append(true).open(&path).unwrap();
+    let message = |role: &str, text: String| serde_json::json!({"timestamp": ts, "type": "event_msg", "payload": {"type": role, "message": text, "kind": "plain"}});
+    let filler = "x".repeat(1024);
+    let middle = (SUMMARY_HEAD_BYTES + SUMMARY_TAIL_BYTES) / 1024;
+    for i in 0..middle {
+        writeln!(
+            file,
+            "{}",
+            message("agent_message", format!("{i} {filler}"))
+        )
+        .unwrap();
+    }
+    writeln!(
+        file,
+        "{}",
+        message("user_message", "last request".to_string())
+    )
+    .unwrap();
+    drop(file);
+
+    let summary = get_conversation_summary(&path).await.unwrap().unwrap();
+
+    let texts: Vec<&str> = summary.messages.iter().map(|m| m.text.as_str()).collect();
+    assert_eq!(texts.first(), Some(&"Hello from user"));
+    assert_eq!(texts.last(), Some(&"last request"));
+    assert!(texts.len() < middle as usize);
+    assert!(texts.iter().all(|text| text.len() < 2 * 1024));
+}
+
+#[test]
+fn test_exec_env_record_marks_only_inexact_records() {
+    let exact = ExecEnvRecord {
+        call_id: "call-1".to_string(),
+        variables: vec!["PATH".to_string()],
+        inexact: false,
+    };
+    let json = serde_json::to_value(&exact).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({"call_id": "call-1", "variables": ["PATH"]})
+    );
+    assert_eq!(
+        serde_json::from_value::<ExecEnvRecord>(json).unwrap(),
+        exact
+    );
+
+    let inexact = ExecEnvRecord {
+        inexact: true,
+        ..exact
+    };
+    assert_eq!(
+        serde_json::to_value(&inexact).unwrap()["inexact"],
+        serde_json::json!(true)
+    );
+}
+
 #[tokio::test]
 async fn test_stable_ordering_same_second_pagination() {
     let temp = TempDir::new().unwrap();
@@ -1,15 +1,18 @@
+use std::collections::HashMap;
 use std::collections::HashSet;
 use std::path::Component;
 use std::path::Path;
 use std::path::PathBuf;
 
 use codex_apply_patch::ApplyPatchAction;
-use codex_apply_patch::ApplyPatchFileChange;
 
+use crate::apply_patch::convert_apply_patch_to_protocol;
 use crate::exec::SandboxType;
 use crate::is_safe_command::is_known_safe_command;
 use crate::protocol::AskForApproval;
+use crate::protocol::FileChange;
 use crate::protocol::SandboxPolicy;
+use crate::turn_diff_tracker::move_destination;
 
 #[derive(Debug, PartialEq)]
 pub enum SafetyCheck {
@@ -192,22 +195,6 @@ fn is_write_patch_constrained_to_writable_paths(
         SandboxPolicy::WorkspaceWrite { .. } => sandbox_policy.get_writable_roots_with_cwd(cwd),
     };
 
-    // Normalize a path by removing `.` and resolving `..` without touching the
-    // filesystem (works even if the file does not exist).
-    fn normalize(path: &Path) -> Option<PathBuf> {
-        let mut out = PathBuf::new();
-        for comp in path.components() {
-            match comp {
-                Component::ParentDir => {
-                    out.pop();
-                }
-                Component::CurDir => { /* skip */ }
-                other => out.push(other.as_os_str()),
-            }
-        }
-        Some(out)
-    }
-
     // Determine whether `path` is inside **any** writable root. Both `path`
     // and roots are converted to absolute, normalized forms before the
     // prefix check.
@@ -227,29 +214,103 @@ fn is_write_patch_constrained_to_writable_paths(
             .any(|writable_root| writable_root.is_path_writable(&abs))
     };
 
-    for (path, change) in action.changes() {
-        match change {
-            ApplyPatchFileChange::Add { .. } | ApplyPatchFileChange::Delete { .. } => {
-                if !is_path_writable(path) {
-                    return false;
-                }
-            }
-            ApplyPatchFileChange::Update { move_path, .. } => {
-                if !is_path_writable(path) {
-                    return false;
-                }
-                if let Some(dest) = move_path
-                    && !is_path_writable(dest)
-                {
-                    return false;
-                }
-            }
+    let changes = convert_apply_patch_to_protocol(action);
+    // Symlinks added by the patch, by normalized path, since later writes
+    // below them go wherever they point.
+    let created_links: HashMap<PathBuf, PathBuf> = changes
+        .iter()
+        .filter_map(|(path, change)| match change {
+            FileChange::Symlink { target } => Some((normalize(&cwd.join(path))?, target.clone())),
+            _ => None,
+        })
+        .collect();
+    let roots: Vec<PathBuf> = writable_roots.iter().map(|r| r.root.clone()).collect();
+    // The file a write to `path` actually reaches once symlinked ancestor
+    // directories are followed, or `None` if that cannot be determined.
+    let landing_path = |path: &PathBuf| {
+        let abs = normalize(&cwd.join(path))?;
+        resolve_ancestor_links(&abs, &created_links, &roots, 0)
+    };
+
+    for (path, change) in &changes {
+        let Some(landing) = landing_path(path) else {
+            return false;
+        };
+        if !is_path_writable(path) || !is_path_writable(&landing) {
+            return false;
+        }
+        if let Some(dest) = move_destination(change)
+            && !(is_path_writable(dest) && landing_path(dest).is_some_and(|d| is_path_writable(&d)))
+        {
+            return false;
+        }
+        // Edits to an existing symlink are written to the file it points to.
+        if !matches!(
+            change,
+            FileChange::Add { .. } | FileChange::Delete { .. } | FileChange::Symlink { .. }
+        ) && std::fs::symlink_metadata(&landing).is_ok_and(|m| m.file_type().is_symlink())
+            && !std::fs::canonicalize(&landing).is_ok_and(|target| is_path_writable(&target))
+        {
+            return false;
         }
     }
 
     true
 }
 
+/// Normalize a path by removing `.` and resolving `..` without touching the
+/// filesystem (works even if the file does not exist).
+fn normalize(path: &Path) -> Option<PathBuf> {
+    let mut out = PathBuf::new();
+    for comp in path.components() {
+        match comp {
+            Component::ParentDir => {
+                out.pop();
+            }
+            Component::CurDir => { /* skip */ }
+            other => out.push(other.as_os_str()),
+        }
+    }
+    Some(out)
+}
+
+/// Follows the symlinks among the ancestor directories of the absolute,
+/// normalized `path`: those on disk and those in `created_links`, which the
+/// patch adds. Directories leading up to one of `roots` are taken as they
+/// are, so a writable root that is itself reached through a symlink (such as
+/// `/tmp` on macOS) still matches. Returns `None` for dangling or looping
+/// links.
+fn resolve_ancestor_links(
+    path: &Path,
+    created_links: &HashMap<PathBuf, PathBuf>,
+    roots: &[PathBuf],
+    depth: usize,
+) -> Option<PathBuf> {
+    const MAX_LINKS: usize = 40;
+    let (Some(parent), Some(file_name)) = (path.parent(), path.file_name()) else {
+        return Some(path.to_path_buf());
+    };
+    let mut resolved = PathBuf::new();
+    for comp in parent.components() {
+        resolved.push(comp);
+        if roots.iter().any(|root| root.starts_with(&resolved)) {
+            continue;
+        }
+        if let Some(target) = created_links.get(&resolved) {
+            if depth >= MAX_LINKS {
+                return None;
+            }
+            let link_dir = resolved.parent().unwrap_or(Path::new("/"));
+            let target = normalize(&link_dir.join(target))?;
+            // The target's own ancestors may be links too.
+            resolved = resolve_ancestor_links(&target, created_links, roots, depth + 1)?;
+        } else if std::fs::symlink_metadata(&resolved).is_ok_and(|m| m.file_type().is_symlink()) {
+            resolved = std::fs::canonicalize(&resolved).ok()?;
+        }
+    }
+    Some(resolved.join(file_name))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -305,6 +366,152 @@ mod tests {
         ));
     }
 
+    /// Parses `patch` as an `apply_patch` call made in `cwd`.
+    fn parse_patch(patch: &str, cwd: &Path) -> ApplyPatchAction {
+        let argv = vec!["apply_patch".to_string(), patch.to_string()];
+        match codex_apply_patch::maybe_parse_apply_patch_verified(
+            &argv,
+            cwd,
+            codex_apply_patch::MatchOptions::default(),
+        ) {
+            codex_apply_patch::MaybeApplyPatchVerified::Body(action) => action,
+            other => panic!("not a valid patch: {other:?}"),
+        }
+    }
+
+    /// Workspace-write confined to `cwd`, so that the system temp dirs are
+    /// not writable.
+    fn workspace_only() -> SandboxPolicy {
+        SandboxPolicy::WorkspaceWrite {
+            writable_roots: vec![],
+            network_access: false,
+            exclude_tmpdir_env_var: true,
+            exclude_slash_tmp: true,
+        }
+    }
+
+    fn approved() -> SafetyCheck {
+        match get_platform_sandbox() {
+            Some(sandbox_type) => SafetyCheck::AutoApprove { sandbox_type },
+            None => SafetyCheck::AskUser,
+        }
+    }
+
+    fn rejected() -> SafetyCheck {
+        SafetyCheck::Reject {
+            reason: "writing outside of the project; rejected by user approval settings"
+                .to_string(),
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn edits_through_a_symlink_to_outside_the_writable_roots_are_rejected() {
+        let workspace = TempDir::new().unwrap();
+        let outside = TempDir::new().unwrap();
+        let cwd = workspace.path();
+        std::fs::write(outside.path().join("target.txt"), "old\n").unwrap();
+        std::os::unix::fs::symlink(outside.path().join("target.txt"), cwd.join("link.txt"))
+            .unwrap();
+        std::fs::write(cwd.join("inside.txt"), "old\n").unwrap();
+        std::os::unix::fs::symlink(cwd.join("inside.txt"), cwd.join("inside-link.txt")).unwrap();
+
+        let check = |patch: &str| {
+            assess_patch_safety(
+                &parse_patch(patch, cwd),
+                AskForApproval::Never,
+                &workspace_only(),
+                cwd,
+            )
+        };
+        assert_eq!(
+            check("*** Begin Patch\n*** Update File: link.txt\n@@\n-old\n+new\n*** End Patch"),
+            rejected()
+        );
+        assert_eq!(
+            check("*** Begin Patch\n*** Chmod File: link.txt\n*** Mode: 755\n*** End Patch"),
+            rejected()
+        );
+        // A directory symlink on disk leads out of the workspace too.
+        std::os::unix::fs::symlink(outside.path(), cwd.join("out")).unwrap();
+        assert_eq!(
+            check("*** Begin Patch\n*** Add File: out/new.txt\n+hi\n*** End Patch"),
+            rejected()
+        );
+        std::fs::create_dir(cwd.join("src")).unwrap();
+        std::os::unix::fs::symlink(cwd.join("src"), cwd.join("src-link")).unwrap();
+        assert_eq!(
+            check("*** Begin Patch\n*** Add File: src-link/new.txt\n+hi\n*** End Patch"),
+            approved()
+        );
+        // Deleting the link only removes the link itself.
+        assert_eq!(
+            check("*** Begin Patch\n*** Delete File: link.txt\n*** End Patch"),
+            approved()
+        );
+        assert_eq!(
+            check(
+                "*** Begin Patch\n*** Update File: inside-link.txt\n@@\n-old\n+new\n*** End Patch"
+            ),
+            approved()
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn binary_mode_and_symlink_changes_are_confined_to_writable_roots() {
+        let workspace = TempDir::new().unwrap();
+        let cwd = workspace.path().join("repo");
+        std::fs::create_dir(&cwd).unwrap();
+        std::fs::write(cwd.join("run.sh"), "echo hi\n").unwrap();
+
+        let check = |patch: &str| {
+            assess_patch_safety(
+                &parse_patch(patch, &cwd),
+                AskForApproval::Never,
+                &workspace_only(),
+                &cwd,
+            )
+        };
+        let cases = [
+            ("*** Add Binary File: dot.bin\niVBORw0K\n", approved()),
+            ("*** Add Binary File: ../dot.bin\niVBORw0K\n", rejected()),
+            ("*** Chmod File: run.sh\n*** Mode: 755\n", approved()),
+            ("*** Chmod File: ../run.sh\n*** Mode: 755\n", rejected()),
+            // A symlink may point anywhere; only the link is written.
+            (
+                "*** Add Symlink: latest\n*** Target: /etc/passwd\n",
+                approved(),
+            ),
+            (
+                "*** Add Symlink: ../latest\n*** Target: repo/run.sh\n",
+                rejected(),
+            ),
+            // Later hunks write through a link added earlier in the patch.
+            (
+                "*** Add Symlink: evil\n*** Target: /etc\n*** Add File: evil/passwd\n+root\n",
+                rejected(),
+            ),
+            (
+                "*** Add Symlink: up\n*** Target: ..\n*** Add File: up/escaped.txt\n+hi\n",
+                rejected(),
+            ),
+            (
+                "*** Add Symlink: docs\n*** Target: .\n*** Add File: docs/notes.txt\n+hi\n",
+                approved(),
+            ),
+            // The mode change wraps a move out of the workspace.
+            (
+                "*** Update File: run.sh\n*** Move to: ../run.sh\n@@\n-echo hi\n+echo bye\n*** Chmod File: ../run.sh\n*** Mode: 755\n",
+                rejected(),
+            ),
+        ];
+        for (hunks, expected) in cases {
+            let patch = format!("*** Begin Patch\n{hunks}*** End Patch");
+            assert_eq!(check(&patch), expected, "{hunks}");
+        }
+    }
+
     #[test]
     fn test_request_escalated_privileges() {
         // Should not be a trusted command
@@ -437,6 +437,7 @@ mod tests {
         for (input, expected_cmd, expected_output) in cases {
             use std::collections::HashMap;
 
+            use crate::config_types::Seccomp;
             use crate::exec::ExecParams;
             use crate::exec::SandboxType;
             use crate::exec::process_exec_tool_call;
@@ -487,6 +488,7 @@ mod tests {
                 SandboxType::None,
                 &SandboxPolicy::DangerFullAccess,
                 &None,
+                &Seccomp::default(),
                 None,
             )
             .await
@@ -630,6 +632,7 @@ mod macos_tests {
             use std::collections::HashMap;
             use std::path::PathBuf;
 
+            use crate::config_types::Seccomp;
             use crate::exec::ExecParams;
             use crate::exec::SandboxType;
             use crate::exec::process_exec_tool_call;
@@ -681,6 +684,7 @@ mod macos_tests {
                 SandboxType::None,
                 &SandboxPolicy::DangerFullAccess,
                 &None,
+                &Seccomp::default(),
                 None,
             )
             .await
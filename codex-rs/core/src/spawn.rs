@@ -25,6 +25,12 @@ pub const CODEX_SANDBOX_ENV_VAR: &str = "CODEX_SANDBOX";
 #[derive(Debug, Clone, Copy)]
 pub enum StdioPolicy {
     RedirectForShellTool,
+    /// Like `RedirectForShellTool`, but also pipes stdin so that commands can
+    /// be written to a long-lived shell process.
+    RedirectForPersistentShell,
+    /// Like `RedirectForShellTool`, but the child leads a new process group so
+    /// that stopping it also stops everything it spawned (e.g. `npm` -> `node`).
+    RedirectForBackgroundProcess,
     Inherit,
 }
 
@@ -86,7 +92,7 @@ pub(crate) async fn spawn_child_async(
     }
 
     match stdio_policy {
-        StdioPolicy::RedirectForShellTool => {
+        StdioPolicy::RedirectForShellTool | StdioPolicy::RedirectForBackgroundProcess => {
             // Do not create a file descriptor for stdin because otherwise some
             // commands may hang forever waiting for input. For example, ripgrep has
             // a heuristic where it may try to read from stdin as explained here:
@@ -95,6 +101,11 @@ pub(crate) async fn spawn_child_async(
 
             cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
         }
+        StdioPolicy::RedirectForPersistentShell => {
+            cmd.stdin(Stdio::piped())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped());
+        }
         StdioPolicy::Inherit => {
             // Inherit stdin, stdout, and stderr from the parent process.
             cmd.stdin(Stdio::inherit())
@@ -103,5 +114,10 @@ pub(crate) async fn spawn_child_async(
         }
     }
 
+    #[cfg(unix)]
+    if matches!(stdio_policy, StdioPolicy::RedirectForBackgroundProcess) {
+        cmd.process_group(0);
+    }
+
     cmd.kill_on_drop(true).spawn()
 }
@@ -63,6 +63,12 @@ Each operation starts with one of three headers:
 *** Delete File: <path> - remove an existing file. Nothing follows.
 *** Update File: <path> - patch an existing file in place (optionally with a rename).
 
+For files that are not plain text, use one of these instead:
+
+*** Add Binary File: <path> / *** Replace Binary File: <path> - write the file's bytes, given as base64 on the following lines.
+*** Chmod File: <path> - followed by *** Mode: <octal bits>, e.g. 755 to make a script executable.
+*** Add Symlink: <path> - followed by *** Target: <target> to create a symbolic link.
+
 May be immediately followed by *** Move to: <new path> if you want to rename the file.
 Then one or more “hunks”, each introduced by @@ (optionally followed by a hunk header).
 Within a hunk each line starts with:
@@ -89,13 +95,16 @@ The full grammar definition is below:
 Patch := Begin { FileOp } End
 Begin := "*** Begin Patch" NEWLINE
 End := "*** End Patch" NEWLINE
-FileOp := AddFile | DeleteFile | UpdateFile
+FileOp := AddFile | DeleteFile | UpdateFile | BinaryFile | ChmodFile | AddSymlink
 AddFile := "*** Add File: " path NEWLINE { "+" line NEWLINE }
 DeleteFile := "*** Delete File: " path NEWLINE
 UpdateFile := "*** Update File: " path NEWLINE [ MoveTo ] { Hunk }
 MoveTo := "*** Move to: " newPath NEWLINE
 Hunk := "@@" [ header ] NEWLINE { HunkLine } [ "*** End of File" NEWLINE ]
 HunkLine := (" " | "-" | "+") text NEWLINE
+BinaryFile := ("*** Add Binary File: " | "*** Replace Binary File: ") path NEWLINE { base64 NEWLINE }
+ChmodFile := "*** Chmod File: " path NEWLINE "*** Mode: " octal NEWLINE
+AddSymlink := "*** Add Symlink: " path NEWLINE "*** Target: " target NEWLINE
 
 A full patch can combine several operations:
 
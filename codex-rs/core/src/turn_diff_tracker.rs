@@ -62,7 +62,7 @@ impl TurnDiffTracker {
                     .insert(internal.clone(), path.clone());
 
                 // If the file exists on disk now, snapshot as baseline; else leave missing to represent /dev/null.
-                let baseline_file_info = if path.exists() {
+                let baseline_file_info = if exists_on_disk(path) {
                     let mode = file_mode_for_path(path);
                     let mode_val = mode.unwrap_or(FileMode::Regular);
                     let content = blob_bytes(path, &mode_val).unwrap_or_default();
@@ -94,11 +94,7 @@ impl TurnDiffTracker {
             }
 
             // Track rename/move in current mapping if provided in an Update.
-            if let FileChange::Update {
-                move_path: Some(dest),
-                ..
-            } = change
-            {
+            if let Some(dest) = move_destination(change) {
                 let uuid_filename = match self.external_to_temp_name.get(path) {
                     Some(i) => i.clone(),
                     None => {
@@ -294,8 +290,9 @@ impl TurnDiffTracker {
             None
         };
 
-        // Fast path: identical bytes or both missing.
-        if left_bytes == right_bytes.as_deref() {
+        // Fast path: identical bytes and mode, or both missing.
+        let same_bytes = left_bytes == right_bytes.as_deref();
+        if same_bytes && (!left_present || baseline_mode == current_mode) {
             return aggregated;
         }
 
@@ -311,6 +308,10 @@ impl TurnDiffTracker {
         } else if baseline_mode != current_mode {
             aggregated.push_str(&format!("old mode {baseline_mode}\n"));
             aggregated.push_str(&format!("new mode {current_mode}\n"));
+            if same_bytes {
+                // Mode-only change: git prints no index line or hunks.
+                return aggregated;
+            }
         }
 
         let left_text = left_bytes.and_then(|b| std::str::from_utf8(b).ok());
@@ -368,6 +369,23 @@ impl TurnDiffTracker {
     }
 }
 
+/// Destination of a move, looking through a mode change wrapping it.
+pub(crate) fn move_destination(change: &FileChange) -> Option<&PathBuf> {
+    match change {
+        FileChange::Update { move_path, .. } => move_path.as_ref(),
+        FileChange::Mode {
+            contents: Some(contents),
+            ..
+        } => move_destination(contents),
+        _ => None,
+    }
+}
+
+/// Like `Path::exists`, but true for dangling symlinks too.
+fn exists_on_disk(path: &Path) -> bool {
+    fs::symlink_metadata(path).is_ok()
+}
+
 /// Compute the Git SHA-1 blob object ID for the given content (bytes).
 fn git_blob_sha1_hex_bytes(data: &[u8]) -> Output<sha1::Sha1> {
     // Git blob hash is sha1 of: "blob <len>\0<data>"
@@ -428,7 +446,7 @@ fn file_mode_for_path(_path: &Path) -> Option<FileMode> {
 }
 
 fn blob_bytes(path: &Path, mode: &FileMode) -> Option<Vec<u8>> {
-    if path.exists() {
+    if exists_on_disk(path) {
         let contents = if *mode == FileMode::Symlink {
             symlink_blob_bytes(path)
                 .ok_or_else(|| anyhow!("failed to read symlink target for {}", path.display()))
@@ -571,6 +589,69 @@ index {ZERO_OID}..{right_oid}
         assert_eq!(combined, expected_combined);
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn mode_only_change_is_reported() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("run.sh");
+        fs::write(&file, "echo hi\n").unwrap();
+        fs::set_permissions(&file, fs::Permissions::from_mode(0o644)).unwrap();
+
+        let mut acc = TurnDiffTracker::new();
+        acc.on_patch_begin(&HashMap::from([(
+            file.clone(),
+            FileChange::Mode {
+                old_mode: Some(0o644),
+                new_mode: 0o755,
+                contents: None,
+            },
+        )]));
+
+        // Simulate apply: make the script executable.
+        fs::set_permissions(&file, fs::Permissions::from_mode(0o755)).unwrap();
+        let diff = acc.get_unified_diff().unwrap().unwrap();
+        let diff = normalize_diff_for_test(&diff, dir.path());
+        assert_eq!(
+            diff,
+            "diff --git a/<TMP>/run.sh b/<TMP>/run.sh\nold mode 100644\nnew mode 100755\n"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn dangling_symlink_add_is_reported() {
+        let dir = tempdir().unwrap();
+        let link = dir.path().join("latest");
+
+        let mut acc = TurnDiffTracker::new();
+        acc.on_patch_begin(&HashMap::from([(
+            link.clone(),
+            FileChange::Symlink {
+                target: PathBuf::from("releases/v2"),
+            },
+        )]));
+
+        // Simulate apply: create a symlink whose target does not exist.
+        std::os::unix::fs::symlink("releases/v2", &link).unwrap();
+        let diff = acc.get_unified_diff().unwrap().unwrap();
+        let diff = normalize_diff_for_test(&diff, dir.path());
+        let right_oid = git_blob_sha1_hex("releases/v2");
+        let expected = format!(
+            r#"diff --git a/<TMP>/latest b/<TMP>/latest
+new file mode 120000
+index {ZERO_OID}..{right_oid}
+--- {DEV_NULL}
++++ b/<TMP>/latest
+@@ -0,0 +1 @@
++releases/v2
+\ No newline at end of file
+"#,
+        );
+        assert_eq!(diff, expected);
+    }
+
     #[test]
     fn accumulates_delete() {
         let dir = tempdir().unwrap();
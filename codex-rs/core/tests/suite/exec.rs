@@ -2,6 +2,7 @@
 
 use std::collections::HashMap;
 
+use codex_core::config_types::Seccomp;
 use codex_core::exec::ExecParams;
 use codex_core::exec::ExecToolCallOutput;
 use codex_core::exec::SandboxType;
@@ -39,7 +40,15 @@ async fn run_test_cmd(tmp: TempDir, cmd: Vec<&str>) -> Result<ExecToolCallOutput
 
     let policy = SandboxPolicy::new_read_only_policy();
 
-    process_exec_tool_call(params, sandbox_type, &policy, &None, None).await
+    process_exec_tool_call(
+        params,
+        sandbox_type,
+        &policy,
+        &None,
+        &Seccomp::default(),
+        None,
+    )
+    .await
 }
 
 /// Command succeeds with exit code 0 normally
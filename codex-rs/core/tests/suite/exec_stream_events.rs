@@ -4,6 +4,7 @@ use std::collections::HashMap;
 use std::path::PathBuf;
 
 use async_channel::Receiver;
+use codex_core::config_types::Seccomp;
 use codex_core::exec::ExecParams;
 use codex_core::exec::SandboxType;
 use codex_core::exec::StdoutStream;
@@ -62,6 +63,7 @@ async fn test_exec_stdout_stream_events_echo() {
         SandboxType::None,
         &policy,
         &None,
+        &Seccomp::default(),
         Some(stdout_stream),
     )
     .await;
@@ -112,6 +114,7 @@ async fn test_exec_stderr_stream_events_echo() {
         SandboxType::None,
         &policy,
         &None,
+        &Seccomp::default(),
         Some(stdout_stream),
     )
     .await;
@@ -160,9 +163,16 @@ async fn test_aggregated_output_interleaves_in_order() {
 
     let policy = SandboxPolicy::new_read_only_policy();
 
-    let result = process_exec_tool_call(params, SandboxType::None, &policy, &None, None)
-        .await
-        .expect("process_exec_tool_call");
+    let result = process_exec_tool_call(
+        params,
+        SandboxType::None,
+        &policy,
+        &None,
+        &Seccomp::default(),
+        None,
+    )
+    .await
+    .expect("process_exec_tool_call");
 
     assert_eq!(result.exit_code, 0);
     assert_eq!(result.stdout.text, "O1\nO2\n");
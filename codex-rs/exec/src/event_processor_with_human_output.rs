@@ -32,6 +32,7 @@ use owo_colors::Style;
 use shlex::try_join;
 use std::collections::HashMap;
 use std::io::Write;
+use std::path::Path;
 use std::path::PathBuf;
 use std::time::Instant;
 
@@ -115,6 +116,95 @@ impl EventProcessorWithHumanOutput {
             }
         }
     }
+
+    fn print_file_change(&self, path: &Path, change: &FileChange) {
+        match change {
+            FileChange::Add { content } => {
+                let header = format!("{} {}", format_file_change(change), path.to_string_lossy());
+                println!("{}", header.style(self.magenta));
+                for line in content.lines() {
+                    println!("{}", line.style(self.green));
+                }
+            }
+            FileChange::Delete { content } => {
+                let header = format!("{} {}", format_file_change(change), path.to_string_lossy());
+                println!("{}", header.style(self.magenta));
+                for line in content.lines() {
+                    println!("{}", line.style(self.red));
+                }
+            }
+            FileChange::Update {
+                unified_diff,
+                move_path,
+            } => {
+                let header = if let Some(dest) = move_path {
+                    format!(
+                        "{} {} -> {}",
+                        format_file_change(change),
+                        path.to_string_lossy(),
+                        dest.to_string_lossy()
+                    )
+                } else {
+                    format!("{} {}", format_file_change(change), path.to_string_lossy())
+                };
+                println!("{}", header.style(self.magenta));
+
+                // Colorize diff lines. We keep file header lines
+                // (--- / +++) without extra coloring so they are
+                // still readable.
+                for diff_line in unified_diff.lines() {
+                    if diff_line.starts_with('+') && !diff_line.starts_with("+++") {
+                        println!("{}", diff_line.style(self.green));
+                    } else if diff_line.starts_with('-') && !diff_line.starts_with("---") {
+                        println!("{}", diff_line.style(self.red));
+                    } else {
+                        println!("{diff_line}");
+                    }
+                }
+            }
+            FileChange::Binary { old_size, new_size } => {
+                let header = format!("{} {}", format_file_change(change), path.to_string_lossy());
+                println!("{}", header.style(self.magenta));
+                let size = match old_size {
+                    Some(old_size) => format!(
+                        "{} -> {} bytes",
+                        format_with_separators(*old_size),
+                        format_with_separators(*new_size)
+                    ),
+                    None => format!("{} bytes", format_with_separators(*new_size)),
+                };
+                println!("{}", format!("Binary file, {size}").style(self.dimmed));
+            }
+            FileChange::Mode {
+                old_mode,
+                new_mode,
+                contents,
+            } => {
+                match contents {
+                    Some(contents) => self.print_file_change(path, contents),
+                    None => {
+                        let header =
+                            format!("{} {}", format_file_change(change), path.to_string_lossy());
+                        println!("{}", header.style(self.magenta));
+                    }
+                }
+                let mode = match old_mode {
+                    Some(old_mode) => format!("mode {old_mode:o} -> {new_mode:o}"),
+                    None => format!("mode {new_mode:o}"),
+                };
+                println!("{}", mode.style(self.dimmed));
+            }
+            FileChange::Symlink { target } => {
+                let header = format!(
+                    "{} {} -> {}",
+                    format_file_change(change),
+                    path.to_string_lossy(),
+                    target.to_string_lossy()
+                );
+                println!("{}", header.style(self.magenta));
+            }
+        }
+    }
 }
 
 struct ExecCommandBegin {
@@ -399,61 +489,7 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                 // Pretty-print the patch summary with colored diff markers so
                 // it's easy to scan in the terminal output.
                 for (path, change) in changes.iter() {
-                    match change {
-                        FileChange::Add { content } => {
-                            let header = format!(
-                                "{} {}",
-                                format_file_change(change),
-                                path.to_string_lossy()
-                            );
-                            println!("{}", header.style(self.magenta));
-                            for line in content.lines() {
-                                println!("{}", line.style(self.green));
-                            }
-                        }
-                        FileChange::Delete { content } => {
-                            let header = format!(
-                                "{} {}",
-                                format_file_change(change),
-                                path.to_string_lossy()
-                            );
-                            println!("{}", header.style(self.magenta));
-                            for line in content.lines() {
-                                println!("{}", line.style(self.red));
-                            }
-                        }
-                        FileChange::Update {
-                            unified_diff,
-                            move_path,
-                        } => {
-                            let header = if let Some(dest) = move_path {
-                                format!(
-                                    "{} {} -> {}",
-                                    format_file_change(change),
-                                    path.to_string_lossy(),
-                                    dest.to_string_lossy()
-                                )
-                            } else {
-                                format!("{} {}", format_file_change(change), path.to_string_lossy())
-                            };
-                            println!("{}", header.style(self.magenta));
-
-                            // Colorize diff lines. We keep file header lines
-                            // (--- / +++) without extra coloring so they are
-                            // still readable.
-                            for diff_line in unified_diff.lines() {
-                                if diff_line.starts_with('+') && !diff_line.starts_with("+++") {
-                                    println!("{}", diff_line.style(self.green));
-                                } else if diff_line.starts_with('-')
-                                    && !diff_line.starts_with("---")
-                                {
-                                    println!("{}", diff_line.style(self.red));
-                                } else {
-                                    println!("{diff_line}");
-                                }
-                            }
-                        }
-                    }
+                    self.print_file_change(path, change);
                 }
             }
             EventMsg::PatchApplyEnd(PatchApplyEndEvent {
@@ -550,6 +586,9 @@ impl EventProcessor for EventProcessorWithHumanOutput {
             EventMsg::ListCustomPromptsResponse(_) => {
                 // Currently ignored in exec output.
             }
+            EventMsg::BackgroundProcessesUpdate(_) => {
+                // Currently ignored in exec output.
+            }
             EventMsg::TurnAborted(abort_reason) => match abort_reason.reason {
                 TurnAbortReason::Interrupted => {
                     ts_println!(self, "task interrupted");
@@ -580,6 +619,16 @@ fn format_file_change(change: &FileChange) -> &'static str {
         FileChange::Update {
             move_path: None, ..
         } => "M",
+        FileChange::Binary { old_size: None, .. } => "A",
+        FileChange::Binary {
+            old_size: Some(_), ..
+        } => "M",
+        FileChange::Mode {
+            contents: Some(contents),
+            ..
+        } => format_file_change(contents),
+        FileChange::Mode { contents: None, .. } => "M",
+        FileChange::Symlink { .. } => "A",
     }
 }
 
@@ -28,12 +28,14 @@ async fn spawn_command_under_sandbox(
     stdio_policy: StdioPolicy,
     env: HashMap<String, String>,
 ) -> std::io::Result<Child> {
+    use codex_core::config_types::Seccomp;
     use codex_core::landlock::spawn_command_under_linux_sandbox;
     let codex_linux_sandbox_exe = assert_cmd::cargo::cargo_bin("codex-exec");
     spawn_command_under_linux_sandbox(
         codex_linux_sandbox_exe,
         command,
         sandbox_policy,
+        &Seccomp::default(),
         cwd,
         stdio_policy,
         env,
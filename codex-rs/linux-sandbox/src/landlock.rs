@@ -30,9 +30,11 @@ use seccompiler::apply_filter;
 pub(crate) fn apply_sandbox_policy_to_current_thread(
     sandbox_policy: &SandboxPolicy,
     cwd: &Path,
+    denied_syscalls: &[DeniedSyscall],
 ) -> Result<()> {
-    if !sandbox_policy.has_full_network_access() {
-        install_network_seccomp_filter_on_current_thread()?;
+    let rules = seccomp_rules(sandbox_policy, denied_syscalls);
+    if !rules.is_empty() {
+        install_seccomp_filter_on_current_thread(&rules)?;
     }
 
     if !sandbox_policy.has_full_disk_write_access() {
@@ -50,6 +52,47 @@ pub(crate) fn apply_sandbox_policy_to_current_thread(
     Ok(())
 }
 
+/// Describes the Landlock rules and seccomp filter that
+/// [`apply_sandbox_policy_to_current_thread`] would install, for
+/// `codex debug landlock --print-policy`.
+pub(crate) fn describe_sandbox_policy(
+    sandbox_policy: &SandboxPolicy,
+    cwd: &Path,
+    denied_syscalls: &[DeniedSyscall],
+) -> std::result::Result<String, SandboxErr> {
+    let mut out = String::new();
+
+    if sandbox_policy.has_full_disk_write_access() {
+        out.push_str("landlock: not applied (full disk write access)\n");
+    } else {
+        out.push_str("landlock: read-only file system, except:\n");
+        out.push_str("  /dev/null\n");
+        for writable_root in sandbox_policy.get_writable_roots_with_cwd(cwd) {
+            out.push_str(&format!("  {}\n", writable_root.root.display()));
+        }
+    }
+
+    let mut rules = seccomp_rules(sandbox_policy, denied_syscalls);
+    if rules.is_empty() {
+        out.push_str("seccomp: not applied\n");
+        return Ok(out);
+    }
+    let instructions = build_seccomp_filter(&rules)?.len();
+    out.push_str(&format!(
+        "seccomp: allow by default; listed syscalls fail with EPERM ({instructions} BPF instructions)\n"
+    ));
+    rules.sort_by_key(|rule| rule.name);
+    for rule in rules {
+        let condition = if rule.allow_unix_domain {
+            " unless domain is AF_UNIX"
+        } else {
+            ""
+        };
+        out.push_str(&format!("  {} ({}){condition}\n", rule.name, rule.nr));
+    }
+    Ok(out)
+}
+
 /// Installs Landlock file-system rules on the current thread allowing read
 /// access to the entire file-system while restricting write access to
 /// `/dev/null` and the provided list of `writable_roots`.
@@ -82,50 +125,131 @@ fn install_filesystem_landlock_rules_on_current_thread(writable_roots: Vec<PathB
     Ok(())
 }
 
-/// Installs a seccomp filter that blocks outbound network access except for
-/// AF_UNIX domain sockets.
-fn install_network_seccomp_filter_on_current_thread() -> std::result::Result<(), SandboxErr> {
-    // Build rule map.
-    let mut rules: BTreeMap<i64, Vec<SeccompRule>> = BTreeMap::new();
+/// A syscall that the seccomp filter makes fail with `EPERM`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct DeniedSyscall {
+    pub(crate) name: &'static str,
+    pub(crate) nr: i64,
+    /// Only deny the call when its first argument (the socket domain) is not
+    /// `AF_UNIX`.
+    pub(crate) allow_unix_domain: bool,
+}
 
-    // Helper – insert unconditional deny rule for syscall number.
-    let mut deny_syscall = |nr: i64| {
-        rules.insert(nr, vec![]); // empty rule vec = unconditional match
-    };
+impl DeniedSyscall {
+    const fn always(name: &'static str, nr: i64) -> Self {
+        Self {
+            name,
+            nr,
+            allow_unix_domain: false,
+        }
+    }
+}
 
-    deny_syscall(libc::SYS_connect);
-    deny_syscall(libc::SYS_accept);
-    deny_syscall(libc::SYS_accept4);
-    deny_syscall(libc::SYS_bind);
-    deny_syscall(libc::SYS_listen);
-    deny_syscall(libc::SYS_getpeername);
-    deny_syscall(libc::SYS_getsockname);
-    deny_syscall(libc::SYS_shutdown);
-    deny_syscall(libc::SYS_sendto);
-    deny_syscall(libc::SYS_sendmsg);
-    deny_syscall(libc::SYS_sendmmsg);
+/// Syscalls that make up the network filter, applied unless the policy grants
+/// full network access.
+const NETWORK_DENIED_SYSCALLS: &[DeniedSyscall] = &[
+    DeniedSyscall::always("connect", libc::SYS_connect),
+    DeniedSyscall::always("accept", libc::SYS_accept),
+    DeniedSyscall::always("accept4", libc::SYS_accept4),
+    DeniedSyscall::always("bind", libc::SYS_bind),
+    DeniedSyscall::always("listen", libc::SYS_listen),
+    DeniedSyscall::always("getpeername", libc::SYS_getpeername),
+    DeniedSyscall::always("getsockname", libc::SYS_getsockname),
+    DeniedSyscall::always("shutdown", libc::SYS_shutdown),
+    DeniedSyscall::always("sendto", libc::SYS_sendto),
+    DeniedSyscall::always("sendmsg", libc::SYS_sendmsg),
+    DeniedSyscall::always("sendmmsg", libc::SYS_sendmmsg),
     // NOTE: allowing recvfrom allows some tools like: `cargo clippy` to run
     // with their socketpair + child processes for sub-proc management
-    // deny_syscall(libc::SYS_recvfrom);
-    deny_syscall(libc::SYS_recvmsg);
-    deny_syscall(libc::SYS_recvmmsg);
-    deny_syscall(libc::SYS_getsockopt);
-    deny_syscall(libc::SYS_setsockopt);
-    deny_syscall(libc::SYS_ptrace);
-
-    // For `socket` we allow AF_UNIX (arg0 == AF_UNIX) and deny everything else.
-    let unix_only_rule = SeccompRule::new(vec![SeccompCondition::new(
-        0, // first argument (domain)
-        SeccompCmpArgLen::Dword,
-        SeccompCmpOp::Ne,
-        libc::AF_UNIX as u64,
-    )?])?;
-
-    rules.insert(libc::SYS_socket, vec![unix_only_rule.clone()]);
-    rules.insert(libc::SYS_socketpair, vec![unix_only_rule]); // always deny (Unix can use socketpair but fine, keep open?)
+    // DeniedSyscall::always("recvfrom", libc::SYS_recvfrom),
+    DeniedSyscall::always("recvmsg", libc::SYS_recvmsg),
+    DeniedSyscall::always("recvmmsg", libc::SYS_recvmmsg),
+    DeniedSyscall::always("getsockopt", libc::SYS_getsockopt),
+    DeniedSyscall::always("setsockopt", libc::SYS_setsockopt),
+    DeniedSyscall::always("ptrace", libc::SYS_ptrace),
+    // For `socket` we allow AF_UNIX and deny everything else.
+    DeniedSyscall {
+        name: "socket",
+        nr: libc::SYS_socket,
+        allow_unix_domain: true,
+    },
+    DeniedSyscall {
+        name: "socketpair",
+        nr: libc::SYS_socketpair,
+        allow_unix_domain: true,
+    },
+];
+
+/// Resolves the names passed with `--deny-syscall`. The accepted names are
+/// those in [`codex_core::landlock::DENYABLE_SYSCALLS`].
+pub(crate) fn resolve_denied_syscall(name: &str) -> Option<DeniedSyscall> {
+    let (name, nr) = match name {
+        "mount" => ("mount", libc::SYS_mount),
+        "umount2" => ("umount2", libc::SYS_umount2),
+        "pivot_root" => ("pivot_root", libc::SYS_pivot_root),
+        "chroot" => ("chroot", libc::SYS_chroot),
+        "unshare" => ("unshare", libc::SYS_unshare),
+        "setns" => ("setns", libc::SYS_setns),
+        "keyctl" => ("keyctl", libc::SYS_keyctl),
+        "add_key" => ("add_key", libc::SYS_add_key),
+        "request_key" => ("request_key", libc::SYS_request_key),
+        "bpf" => ("bpf", libc::SYS_bpf),
+        "perf_event_open" => ("perf_event_open", libc::SYS_perf_event_open),
+        "io_uring_setup" => ("io_uring_setup", libc::SYS_io_uring_setup),
+        "io_uring_enter" => ("io_uring_enter", libc::SYS_io_uring_enter),
+        "io_uring_register" => ("io_uring_register", libc::SYS_io_uring_register),
+        "personality" => ("personality", libc::SYS_personality),
+        "userfaultfd" => ("userfaultfd", libc::SYS_userfaultfd),
+        "process_vm_readv" => ("process_vm_readv", libc::SYS_process_vm_readv),
+        "process_vm_writev" => ("process_vm_writev", libc::SYS_process_vm_writev),
+        "kexec_load" => ("kexec_load", libc::SYS_kexec_load),
+        "init_module" => ("init_module", libc::SYS_init_module),
+        "finit_module" => ("finit_module", libc::SYS_finit_module),
+        "delete_module" => ("delete_module", libc::SYS_delete_module),
+        "swapon" => ("swapon", libc::SYS_swapon),
+        "swapoff" => ("swapoff", libc::SYS_swapoff),
+        "reboot" => ("reboot", libc::SYS_reboot),
+        _ => return None,
+    };
+    Some(DeniedSyscall::always(name, nr))
+}
+
+/// Combines the network filter (unless the policy allows network access) with
+/// the explicitly denied syscalls.
+fn seccomp_rules(
+    sandbox_policy: &SandboxPolicy,
+    denied_syscalls: &[DeniedSyscall],
+) -> Vec<DeniedSyscall> {
+    let mut rules: Vec<DeniedSyscall> = Vec::new();
+    if !sandbox_policy.has_full_network_access() {
+        rules.extend_from_slice(NETWORK_DENIED_SYSCALLS);
+    }
+    for denied in denied_syscalls {
+        if !rules.iter().any(|rule| rule.nr == denied.nr) {
+            rules.push(*denied);
+        }
+    }
+    rules
+}
+
+fn build_seccomp_filter(rules: &[DeniedSyscall]) -> std::result::Result<BpfProgram, SandboxErr> {
+    let mut rule_map: BTreeMap<i64, Vec<SeccompRule>> = BTreeMap::new();
+    for rule in rules {
+        let conditions = if rule.allow_unix_domain {
+            vec![SeccompRule::new(vec![SeccompCondition::new(
+                0, // first argument (domain)
+                SeccompCmpArgLen::Dword,
+                SeccompCmpOp::Ne,
+                libc::AF_UNIX as u64,
+            )?])?]
+        } else {
+            vec![] // empty rule vec = unconditional match
+        };
+        rule_map.insert(rule.nr, conditions);
+    }
 
     let filter = SeccompFilter::new(
-        rules,
+        rule_map,
         SeccompAction::Allow,                     // default – allow
         SeccompAction::Errno(libc::EPERM as u32), // when rule matches – return EPERM
         if cfg!(target_arch = "x86_64") {
@@ -137,9 +261,135 @@ fn install_network_seccomp_filter_on_current_thread() -> std::result::Result<(),
         },
     )?;
 
-    let prog: BpfProgram = filter.try_into()?;
+    Ok(filter.try_into()?)
+}
+
+/// Installs a seccomp filter that makes each of `rules` fail with `EPERM`.
+fn install_seccomp_filter_on_current_thread(
+    rules: &[DeniedSyscall],
+) -> std::result::Result<(), SandboxErr> {
+    let prog = build_seccomp_filter(rules)?;
 
     apply_filter(&prog)?;
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Installs a filter denying `denied` on a fresh thread, verifies the
+    /// filter is active, and returns the errno of calling `denied` there, or 0
+    /// if the call succeeded.
+    fn errno_under_filter(denied: DeniedSyscall) -> i32 {
+        std::thread::spawn(move || {
+            install_seccomp_filter_on_current_thread(&[denied]).expect("install filter");
+            let status = std::fs::read_to_string("/proc/thread-self/status").expect("status");
+            assert!(
+                status.lines().any(|line| line == "Seccomp:\t2"),
+                "seccomp filter not active: {status}"
+            );
+            // Zero arguments are not invalid for every syscall: `unshare(0)`
+            // is a no-op that succeeds, and so would `personality(0)` after
+            // switching this thread's personality. Most others fail with
+            // EFAULT, EBADF or EINVAL, so only EPERM shows that the filter
+            // matched. `personality(0xffffffff)` only queries the personality.
+            let first_arg: libc::c_long = if denied.nr == libc::SYS_personality {
+                0xffff_ffff
+            } else {
+                0
+            };
+            // SAFETY: the arguments are plain integers; every pointer argument
+            // is null, which the kernel rejects with EFAULT rather than
+            // dereferencing, and the calls that could succeed only affect this
+            // throwaway thread.
+            let ret = unsafe {
+                libc::syscall(
+                    denied.nr,
+                    first_arg,
+                    0 as libc::c_long,
+                    0 as libc::c_long,
+                    0 as libc::c_long,
+                    0 as libc::c_long,
+                )
+            };
+            if ret == -1 {
+                std::io::Error::last_os_error()
+                    .raw_os_error()
+                    .expect("errno")
+            } else {
+                0
+            }
+        })
+        .join()
+        .expect("filter thread panicked")
+    }
+
+    #[test]
+    fn every_denyable_syscall_is_known() {
+        for name in codex_core::landlock::DENYABLE_SYSCALLS {
+            let denied = resolve_denied_syscall(name).expect("syscall number");
+            assert_eq!(denied.name, *name);
+        }
+        assert_eq!(resolve_denied_syscall("clone3"), None);
+    }
+
+    #[test]
+    fn hardened_syscalls_fail_with_eperm() {
+        for name in codex_core::landlock::HARDENED_SECCOMP_SYSCALLS {
+            let denied = resolve_denied_syscall(name).expect("syscall number");
+            assert_eq!(errno_under_filter(denied), libc::EPERM, "{name}");
+        }
+    }
+
+    #[test]
+    fn other_denyable_syscalls_fail_with_eperm() {
+        for name in codex_core::landlock::DENYABLE_SYSCALLS
+            .iter()
+            .filter(|name| !codex_core::landlock::HARDENED_SECCOMP_SYSCALLS.contains(name))
+        {
+            let denied = resolve_denied_syscall(name).expect("syscall number");
+            assert_eq!(errno_under_filter(denied), libc::EPERM, "{name}");
+        }
+    }
+
+    #[test]
+    fn unrelated_syscalls_are_still_allowed() {
+        let denied = resolve_denied_syscall("personality").expect("syscall number");
+        let pid = std::thread::spawn(move || {
+            install_seccomp_filter_on_current_thread(&[denied]).expect("install filter");
+            // SAFETY: getpid takes no arguments and cannot fail.
+            unsafe { libc::getpid() }
+        })
+        .join()
+        .expect("filter thread panicked");
+        assert_eq!(pid, std::process::id() as i32);
+    }
+
+    #[test]
+    fn full_network_access_only_applies_denied_syscalls() {
+        let bpf = resolve_denied_syscall("bpf").expect("syscall number");
+        assert_eq!(
+            seccomp_rules(&SandboxPolicy::DangerFullAccess, &[bpf]),
+            vec![bpf]
+        );
+        assert_eq!(
+            seccomp_rules(&SandboxPolicy::DangerFullAccess, &[]),
+            Vec::new()
+        );
+        let read_only = seccomp_rules(&SandboxPolicy::ReadOnly, &[bpf]);
+        assert_eq!(read_only.len(), NETWORK_DENIED_SYSCALLS.len() + 1);
+    }
+
+    #[test]
+    fn describes_policy() {
+        let bpf = resolve_denied_syscall("bpf").expect("syscall number");
+        let description =
+            describe_sandbox_policy(&SandboxPolicy::DangerFullAccess, Path::new("/repo"), &[bpf])
+                .expect("describe");
+        assert!(description.starts_with("landlock: not applied (full disk write access)\n"));
+        assert!(description.contains("seccomp: allow by default"));
+        assert!(description.ends_with(&format!("  bpf ({})\n", libc::SYS_bpf)));
+    }
+}
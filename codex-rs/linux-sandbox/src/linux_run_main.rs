@@ -2,10 +2,22 @@ use clap::Parser;
 use std::ffi::CString;
 use std::path::PathBuf;
 
+use crate::landlock::DeniedSyscall;
 use crate::landlock::apply_sandbox_policy_to_current_thread;
+use crate::landlock::describe_sandbox_policy;
+use crate::landlock::resolve_denied_syscall;
 
 #[derive(Debug, Parser)]
 pub struct LandlockCommand {
+    /// Print the Landlock rules and seccomp filter that would be installed
+    /// instead of running a command.
+    #[arg(long = "print-policy", default_value_t = false)]
+    pub print_policy: bool,
+
+    /// Additional syscall to make fail with EPERM. May be repeated.
+    #[arg(long = "deny-syscall", value_name = "NAME")]
+    pub deny_syscalls: Vec<String>,
+
     /// It is possible that the cwd used in the context of the sandbox policy
     /// is different from the cwd of the process to spawn.
     pub sandbox_policy_cwd: PathBuf,
@@ -19,12 +31,36 @@ pub struct LandlockCommand {
 
 pub fn run_main() -> ! {
     let LandlockCommand {
+        print_policy,
+        deny_syscalls,
         sandbox_policy_cwd,
         sandbox_policy,
         command,
     } = LandlockCommand::parse();
 
-    if let Err(e) = apply_sandbox_policy_to_current_thread(&sandbox_policy, &sandbox_policy_cwd) {
+    let denied_syscalls: Vec<DeniedSyscall> = deny_syscalls
+        .iter()
+        .map(|name| {
+            resolve_denied_syscall(name)
+                .unwrap_or_else(|| panic!("unsupported syscall in --deny-syscall: {name}"))
+        })
+        .collect();
+
+    if print_policy {
+        match describe_sandbox_policy(&sandbox_policy, &sandbox_policy_cwd, &denied_syscalls) {
+            Ok(description) => {
+                print!("{description}");
+                std::process::exit(0);
+            }
+            Err(e) => panic!("error building seccomp filter: {e:?}"),
+        }
+    }
+
+    if let Err(e) = apply_sandbox_policy_to_current_thread(
+        &sandbox_policy,
+        &sandbox_policy_cwd,
+        &denied_syscalls,
+    ) {
         panic!("error running landlock: {e:?}");
     }
 
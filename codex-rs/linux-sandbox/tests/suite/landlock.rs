@@ -1,4 +1,5 @@
 #![cfg(target_os = "linux")]
+use codex_core::config_types::Seccomp;
 use codex_core::config_types::ShellEnvironmentPolicy;
 use codex_core::error::CodexErr;
 use codex_core::error::SandboxErr;
@@ -60,6 +61,7 @@ async fn run_cmd(cmd: &[&str], writable_roots: &[PathBuf], timeout_ms: u64) {
         SandboxType::LinuxSeccomp,
         &sandbox_policy,
         &codex_linux_sandbox_exe,
+        &Seccomp::default(),
         None,
     )
     .await
@@ -152,6 +154,7 @@ async fn assert_network_blocked(cmd: &[&str]) {
         SandboxType::LinuxSeccomp,
         &sandbox_policy,
         &codex_linux_sandbox_exe,
+        &Seccomp::default(),
         None,
     )
     .await;
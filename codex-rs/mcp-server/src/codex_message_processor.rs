@@ -476,6 +476,7 @@ impl CodexMessageProcessor {
         };
         tracing::debug!("Sandbox type: {sandbox_type:?}");
         let codex_linux_sandbox_exe = self.config.codex_linux_sandbox_exe.clone();
+        let seccomp = self.config.seccomp.clone();
         let outgoing = self.outgoing.clone();
         let req_id = request_id;
 
@@ -485,6 +486,7 @@ impl CodexMessageProcessor {
                 sandbox_type,
                 &effective_policy,
                 &codex_linux_sandbox_exe,
+                &seccomp,
                 None,
             )
             .await
@@ -99,6 +99,9 @@ async fn get_config_toml_parses_all_fields() {
             tools: Some(Tools {
                 web_search: Some(false),
                 view_image: Some(true),
+                persistent_shell: None,
+                background_processes: None,
+                code_search: None,
             }),
             profile: Some("test".to_string()),
             profiles: HashMap::from([(
@@ -437,6 +437,12 @@ pub struct Tools {
     pub web_search: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub view_image: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub persistent_shell: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub background_processes: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code_search: Option<bool>,
 }
 
 /// MCP representation of a [`codex_core::config_types::SandboxWorkspaceWrite`].
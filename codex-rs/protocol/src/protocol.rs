@@ -483,6 +483,10 @@ pub enum EventMsg {
 
     TurnDiff(TurnDiffEvent),
 
+    /// Snapshot of the agent's background processes, sent whenever one is
+    /// started, becomes ready, exits or is stopped.
+    BackgroundProcessesUpdate(BackgroundProcessesUpdateEvent),
+
     /// Response to GetHistoryEntryRequest.
     GetHistoryEntryResponse(GetHistoryEntryResponseEvent),
 
@@ -918,6 +922,34 @@ pub struct TurnDiffEvent {
     pub unified_diff: String,
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+pub struct BackgroundProcessesUpdateEvent {
+    /// All known background processes, ordered by name. Exited processes are
+    /// kept until they are stopped or replaced so their logs stay available.
+    pub processes: Vec<BackgroundProcessInfo>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, TS)]
+pub struct BackgroundProcessInfo {
+    pub name: String,
+    pub command: Vec<String>,
+    pub cwd: PathBuf,
+    pub pid: Option<u32>,
+    pub status: BackgroundProcessStatus,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum BackgroundProcessStatus {
+    /// Running, but the readiness check has not passed yet.
+    Starting,
+    /// Running, and ready if a readiness check was requested.
+    Running,
+    Exited {
+        exit_code: i32,
+    },
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize, TS)]
 pub struct GetHistoryEntryResponseEvent {
     pub offset: usize,
@@ -997,6 +1029,22 @@ pub enum FileChange {
         unified_diff: String,
         move_path: Option<PathBuf>,
     },
+    /// A file whose contents are not shown, e.g. an image.
+    Binary {
+        /// Size in bytes of the file being replaced, if any.
+        old_size: Option<u64>,
+        new_size: u64,
+    },
+    /// The permission bits of the file change. `contents` is the change to
+    /// the file's contents or path made by the same patch, if any.
+    Mode {
+        old_mode: Option<u32>,
+        new_mode: u32,
+        contents: Option<Box<FileChange>>,
+    },
+    Symlink {
+        target: PathBuf,
+    },
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, TS)]
@@ -1,16 +1,31 @@
 use crate::app_backtrack::BacktrackState;
 use crate::app_event::AppEvent;
 use crate::app_event_sender::AppEventSender;
+use crate::bottom_pane::SelectionAction;
+use crate::bottom_pane::SelectionItem;
 use crate::chatwidget::ChatWidget;
+use crate::diff_render::parse_git_diff;
+use crate::external_editor;
 use crate::file_search::FileSearchManager;
+use crate::history_cell::HistoryCell;
+use crate::keymap::keymap;
 use crate::pager_overlay::Overlay;
+use crate::pager_overlay::TranscriptOutput;
 use crate::resume_picker::ResumeSelection;
+use crate::tabs::Tab;
+use crate::tabs::TabActivity;
+use crate::tabs::TabId;
+use crate::tabs::TabState;
+use crate::tabs::UnseenHistory;
+use crate::tabs::tab_bar_line;
 use crate::tui;
 use crate::tui::TuiEvent;
 use codex_ansi_escape::ansi_escape_line;
 use codex_core::AuthManager;
 use codex_core::ConversationManager;
 use codex_core::config::Config;
+use codex_core::config_types::KeyAction;
+use codex_core::protocol::Op;
 use codex_core::protocol::TokenUsage;
 use color_eyre::eyre::Result;
 use color_eyre::eyre::WrapErr;
@@ -18,8 +33,11 @@ use crossterm::event::KeyCode;
 use crossterm::event::KeyEvent;
 use crossterm::event::KeyEventKind;
 use crossterm::terminal::supports_keyboard_enhancement;
+use ratatui::layout::Rect;
 use ratatui::style::Stylize;
 use ratatui::text::Line;
+use ratatui::widgets::Paragraph;
+use std::collections::HashSet;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::atomic::AtomicBool;
@@ -41,16 +59,26 @@ pub(crate) struct App {
     pub(crate) file_search: FileSearchManager,
 
     pub(crate) transcript_lines: Vec<Line<'static>>,
+    /// Full command and tool call outputs, keyed by their `transcript_lines` range.
+    pub(crate) transcript_outputs: Vec<TranscriptOutput>,
 
     // Pager overlay state (Transcript or Static like Diff)
     pub(crate) overlay: Option<Overlay>,
     pub(crate) deferred_history_lines: Vec<Line<'static>>,
     has_emitted_history_lines: bool,
 
+    /// Open conversations. The one at `active_tab` is on screen and its state
+    /// lives in the fields above.
+    pub(crate) tabs: Vec<Tab>,
+    pub(crate) active_tab: usize,
+    next_tab_id: TabId,
+
     pub(crate) enhanced_keys_supported: bool,
 
     /// Controls the animation thread that sends CommitTick events.
     pub(crate) commit_anim_running: Arc<AtomicBool>,
+    /// Tabs whose streams are waiting on commit ticks.
+    commit_anim_tabs: HashSet<TabId>,
 
     // Esc-backtracking state grouped
     pub(crate) backtrack: crate::app_backtrack::BacktrackState,
@@ -78,7 +106,7 @@ impl App {
                 let init = crate::chatwidget::ChatWidgetInit {
                     config: config.clone(),
                     frame_requester: tui.frame_requester(),
-                    app_event_tx: app_event_tx.clone(),
+                    app_event_tx: app_event_tx.for_tab(0),
                     initial_prompt: initial_prompt.clone(),
                     initial_images: initial_images.clone(),
                     enhanced_keys_supported,
@@ -99,7 +127,7 @@ impl App {
                 let init = crate::chatwidget::ChatWidgetInit {
                     config: config.clone(),
                     frame_requester: tui.frame_requester(),
-                    app_event_tx: app_event_tx.clone(),
+                    app_event_tx: app_event_tx.for_tab(0),
                     initial_prompt: initial_prompt.clone(),
                     initial_images: initial_images.clone(),
                     enhanced_keys_supported,
@@ -112,7 +140,7 @@ impl App {
             }
         };
 
-        let file_search = FileSearchManager::new(config.cwd.clone(), app_event_tx.clone());
+        let file_search = FileSearchManager::new(config.cwd.clone(), app_event_tx.for_tab(0));
 
         let mut app = Self {
             server: conversation_manager,
@@ -122,21 +150,42 @@ impl App {
             file_search,
             enhanced_keys_supported,
             transcript_lines: Vec::new(),
+            transcript_outputs: Vec::new(),
             overlay: None,
             deferred_history_lines: Vec::new(),
             has_emitted_history_lines: false,
+            tabs: vec![Tab::new(0, None)],
+            active_tab: 0,
+            next_tab_id: 1,
             commit_anim_running: Arc::new(AtomicBool::new(false)),
+            commit_anim_tabs: HashSet::new(),
             backtrack: BacktrackState::default(),
         };
 
-        let tui_events = tui.event_stream();
-        tokio::pin!(tui_events);
+        let mut tui_events = tui.event_stream();
 
         tui.frame_requester().schedule_frame();
 
         while select! {
             Some(event) = app_event_rx.recv() => {
-                app.handle_event(tui, event).await?
+                // Background tabs cannot open the editor.
+                let opens_editor = match &event {
+                    AppEvent::TabEvent { tab, event } => {
+                        *tab == app.active_tab_id()
+                            && matches!(**event, AppEvent::OpenExternalEditor)
+                    }
+                    event => matches!(event, AppEvent::OpenExternalEditor),
+                };
+                if opens_editor {
+                    // The editor reads from the terminal, so stop listening for
+                    // terminal events until it exits.
+                    drop(tui_events);
+                    let result = app.handle_event(tui, event).await;
+                    tui_events = tui.event_stream();
+                    result?
+                } else {
+                    app.handle_event(tui, event).await?
+                }
             }
             Some(event) = tui_events.next() => {
                 app.handle_tui_event(tui, event).await?
@@ -173,11 +222,26 @@ impl App {
                     {
                         return Ok(true);
                     }
+                    let tab_bar = self.tab_bar();
+                    let tab_bar_height = u16::from(tab_bar.is_some());
                     tui.draw(
-                        self.chat_widget.desired_height(tui.terminal.size()?.width),
+                        self.chat_widget.desired_height(tui.terminal.size()?.width)
+                            + tab_bar_height,
                         |frame| {
-                            frame.render_widget_ref(&self.chat_widget, frame.area());
-                            if let Some((x, y)) = self.chat_widget.cursor_pos(frame.area()) {
+                            let area = frame.area();
+                            if let Some(line) = tab_bar {
+                                frame.render_widget(
+                                    Paragraph::new(line),
+                                    Rect { height: 1, ..area },
+                                );
+                            }
+                            let area = Rect {
+                                y: area.y + tab_bar_height,
+                                height: area.height.saturating_sub(tab_bar_height),
+                                ..area
+                            };
+                            frame.render_widget_ref(&self.chat_widget, area);
+                            if let Some((x, y)) = self.chat_widget.cursor_pos(area) {
                                 frame.set_cursor_position((x, y));
                             }
                         },
@@ -189,12 +253,23 @@ impl App {
     }
 
     async fn handle_event(&mut self, tui: &mut tui::Tui, event: AppEvent) -> Result<bool> {
+        let active_id = self.active_tab_id();
+        let (tab, event) = match event {
+            AppEvent::TabEvent { tab, event } => (tab, *event),
+            event => (active_id, event),
+        };
+        if tab != active_id {
+            self.handle_background_tab_event(tui, tab, event);
+            return Ok(true);
+        }
         match event {
+            // Unwrapped above.
+            AppEvent::TabEvent { .. } => {}
             AppEvent::NewSession => {
                 let init = crate::chatwidget::ChatWidgetInit {
                     config: self.config.clone(),
                     frame_requester: tui.frame_requester(),
-                    app_event_tx: self.app_event_tx.clone(),
+                    app_event_tx: self.app_event_tx.for_tab(active_id),
                     initial_prompt: None,
                     initial_images: Vec::new(),
                     enhanced_keys_supported: self.enhanced_keys_supported,
@@ -203,27 +278,23 @@ impl App {
                 tui.frame_requester().schedule_frame();
             }
             AppEvent::InsertHistoryCell(cell) => {
-                let mut cell_transcript = cell.transcript_lines();
-                if !cell.is_stream_continuation() && !self.transcript_lines.is_empty() {
-                    cell_transcript.insert(0, Line::from(""));
-                }
+                let (cell_transcript, output) =
+                    transcript_entry(cell.as_ref(), self.transcript_lines.len());
                 if let Some(Overlay::Transcript(t)) = &mut self.overlay {
                     t.insert_lines(cell_transcript.clone());
+                    if let Some(output) = &output {
+                        t.insert_output(output.clone());
+                    }
                     tui.frame_requester().schedule_frame();
                 }
                 self.transcript_lines.extend(cell_transcript.clone());
-                let mut display = cell.display_lines(tui.terminal.last_known_screen_size.width);
+                self.transcript_outputs.extend(output);
+                let display = history_entry(
+                    cell.as_ref(),
+                    tui.terminal.last_known_screen_size.width,
+                    &mut self.has_emitted_history_lines,
+                );
                 if !display.is_empty() {
-                    // Only insert a separating blank line for new cells that are not
-                    // part of an ongoing stream. Streaming continuations should not
-                    // accrue extra blank lines between chunks.
-                    if !cell.is_stream_continuation() {
-                        if self.has_emitted_history_lines {
-                            display.insert(0, Line::from(""));
-                        } else {
-                            self.has_emitted_history_lines = true;
-                        }
-                    }
                     if self.overlay.is_some() {
                         self.deferred_history_lines.extend(display);
                     } else {
@@ -231,27 +302,13 @@ impl App {
                     }
                 }
             }
-            AppEvent::StartCommitAnimation => {
-                if self
-                    .commit_anim_running
-                    .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
-                    .is_ok()
-                {
-                    let tx = self.app_event_tx.clone();
-                    let running = self.commit_anim_running.clone();
-                    thread::spawn(move || {
-                        while running.load(Ordering::Relaxed) {
-                            thread::sleep(Duration::from_millis(50));
-                            tx.send(AppEvent::CommitTick);
-                        }
-                    });
-                }
-            }
-            AppEvent::StopCommitAnimation => {
-                self.commit_anim_running.store(false, Ordering::Release);
-            }
+            AppEvent::StartCommitAnimation => self.set_commit_animation(tab, true),
+            AppEvent::StopCommitAnimation => self.set_commit_animation(tab, false),
             AppEvent::CommitTick => {
                 self.chat_widget.on_commit_tick();
+                for state in self.tabs.iter_mut().filter_map(|tab| tab.parked.as_mut()) {
+                    state.chat_widget.on_commit_tick();
+                }
             }
             AppEvent::CodexEvent(event) => {
                 self.chat_widget.handle_codex_event(event);
@@ -268,17 +325,36 @@ impl App {
                 self.chat_widget.on_diff_complete();
                 // Enter alternate screen using TUI helper and build pager lines
                 let _ = tui.enter_alt_screen();
-                let pager_lines: Vec<ratatui::text::Line<'static>> = if text.trim().is_empty() {
-                    vec!["No changes detected.".italic().into()]
+                let files = parse_git_diff(&text);
+                self.overlay = Some(if files.is_empty() {
+                    // Not a diff: either no changes or a message explaining why
+                    // there is no diff to show.
+                    let pager_lines: Vec<ratatui::text::Line<'static>> = if text.trim().is_empty() {
+                        vec!["No changes detected.".italic().into()]
+                    } else {
+                        text.lines().map(ansi_escape_line).collect()
+                    };
+                    Overlay::new_static_with_title(pager_lines, "D I F F".to_string())
                 } else {
-                    text.lines().map(ansi_escape_line).collect()
-                };
-                self.overlay = Some(Overlay::new_static_with_title(
-                    pager_lines,
-                    "D I F F".to_string(),
-                ));
+                    Overlay::new_diff(files, self.config.tui.diff_layout)
+                });
                 tui.frame_requester().schedule_frame();
             }
+            AppEvent::OpenExternalEditor => {
+                let text = self.chat_widget.composer_text();
+                let edited = match external_editor::editor_command() {
+                    Ok(command) => {
+                        tui.with_terminal_released(|| external_editor::edit(&command, &text))?
+                    }
+                    Err(err) => Err(err),
+                };
+                match edited {
+                    Ok(text) => self.chat_widget.apply_external_edit(text),
+                    Err(err) => self
+                        .chat_widget
+                        .add_error_message(format!("Failed to edit message: {err}")),
+                }
+            }
             AppEvent::StartFileSearch(query) => {
                 if !query.is_empty() {
                     self.file_search.on_user_query(query);
@@ -299,36 +375,303 @@ impl App {
             AppEvent::UpdateSandboxPolicy(policy) => {
                 self.chat_widget.set_sandbox_policy(policy);
             }
+            AppEvent::NewTab => self.open_tab(tui),
+            AppEvent::ShowTabs => self.show_tabs(),
+            AppEvent::SwitchTab(id) => {
+                if let Some(idx) = self.tabs.iter().position(|tab| tab.id == id) {
+                    self.switch_tab(tui, idx);
+                }
+            }
+            AppEvent::RenameTab(name) => {
+                let name = name.trim();
+                self.tabs[self.active_tab].name = (!name.is_empty()).then(|| name.to_string());
+                tui.frame_requester().schedule_frame();
+            }
+            // Closing the last tab exits, like `/quit`.
+            AppEvent::CloseTab if self.tabs.len() == 1 => return Ok(false),
+            AppEvent::CloseTab => self.close_tab(tui),
         }
         Ok(true)
     }
 
+    /// Handle an event from a conversation that is not on screen.
+    fn handle_background_tab_event(&mut self, tui: &mut tui::Tui, tab: TabId, event: AppEvent) {
+        match event {
+            AppEvent::StartCommitAnimation => return self.set_commit_animation(tab, true),
+            AppEvent::StopCommitAnimation => return self.set_commit_animation(tab, false),
+            _ => {}
+        }
+        // Events from a closed tab's conversation are dropped.
+        let Some(tab) = self.tabs.iter_mut().find(|t| t.id == tab) else {
+            return;
+        };
+        let Some(state) = tab.parked.as_mut() else {
+            return;
+        };
+        match event {
+            AppEvent::CodexEvent(event) => {
+                tab.activity = tab.activity.after_event(&event.msg);
+                state.chat_widget.handle_codex_event(event);
+            }
+            AppEvent::InsertHistoryCell(cell) => {
+                let (cell_transcript, output) =
+                    transcript_entry(cell.as_ref(), state.transcript_lines.len());
+                state.transcript_lines.extend(cell_transcript);
+                state.transcript_outputs.extend(output);
+                state.unseen.push(cell);
+            }
+            AppEvent::CodexOp(op) => state.chat_widget.submit_op(op),
+            AppEvent::DiffResult(_) => state.chat_widget.on_diff_complete(),
+            _ => {}
+        }
+        tui.frame_requester().schedule_frame();
+    }
+
+    /// Keep the commit animation running while any tab is streaming.
+    fn set_commit_animation(&mut self, tab: TabId, running: bool) {
+        if !running {
+            self.commit_anim_tabs.remove(&tab);
+            if self.commit_anim_tabs.is_empty() {
+                self.commit_anim_running.store(false, Ordering::Release);
+            }
+            return;
+        }
+        self.commit_anim_tabs.insert(tab);
+        if self
+            .commit_anim_running
+            .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_ok()
+        {
+            let tx = self.app_event_tx.clone();
+            let running = self.commit_anim_running.clone();
+            thread::spawn(move || {
+                while running.load(Ordering::Relaxed) {
+                    thread::sleep(Duration::from_millis(50));
+                    tx.send(AppEvent::CommitTick);
+                }
+            });
+        }
+    }
+
+    pub(crate) fn active_tab_id(&self) -> TabId {
+        self.tabs[self.active_tab].id
+    }
+
+    fn tab_titles(&self) -> Vec<(String, TabActivity)> {
+        self.tabs
+            .iter()
+            .map(|tab| {
+                let transcript = match &tab.parked {
+                    Some(state) => &state.transcript_lines,
+                    None => &self.transcript_lines,
+                };
+                (tab.title(transcript), tab.activity)
+            })
+            .collect()
+    }
+
+    /// The tab bar, shown once there is more than one tab or the tab is named.
+    fn tab_bar(&self) -> Option<Line<'static>> {
+        if self.tabs.len() == 1 && self.tabs[0].name.is_none() {
+            return None;
+        }
+        Some(tab_bar_line(&self.tab_titles(), self.active_tab))
+    }
+
+    fn open_tab(&mut self, tui: &mut tui::Tui) {
+        let id = self.next_tab_id;
+        self.next_tab_id += 1;
+        let init = crate::chatwidget::ChatWidgetInit {
+            config: self.config.clone(),
+            frame_requester: tui.frame_requester(),
+            app_event_tx: self.app_event_tx.for_tab(id),
+            initial_prompt: None,
+            initial_images: Vec::new(),
+            enhanced_keys_supported: self.enhanced_keys_supported,
+        };
+        let chat_widget = ChatWidget::new(init, self.server.clone());
+        let file_search =
+            FileSearchManager::new(self.config.cwd.clone(), self.app_event_tx.for_tab(id));
+        self.tabs
+            .push(Tab::new(id, Some(TabState::new(chat_widget, file_search))));
+        self.switch_tab(tui, self.tabs.len() - 1);
+    }
+
+    fn switch_tab(&mut self, tui: &mut tui::Tui, idx: usize) {
+        if let Some(unseen) = self.activate_tab(idx) {
+            self.show_active_tab(tui, unseen);
+        }
+    }
+
+    /// Park the conversation on screen in its tab and bring in the one at
+    /// `idx`, returning the history it produced in the background. Returns
+    /// `None` if that is already on screen.
+    fn activate_tab(&mut self, idx: usize) -> Option<UnseenHistory> {
+        if idx == self.active_tab {
+            return None;
+        }
+        let mut state = self.tabs.get_mut(idx)?.parked.take()?;
+        let unseen = std::mem::take(&mut state.unseen);
+        self.reset_backtrack_state();
+        self.backtrack.pending = None;
+        std::mem::swap(&mut self.chat_widget, &mut state.chat_widget);
+        std::mem::swap(&mut self.file_search, &mut state.file_search);
+        std::mem::swap(&mut self.transcript_lines, &mut state.transcript_lines);
+        std::mem::swap(&mut self.transcript_outputs, &mut state.transcript_outputs);
+        std::mem::swap(
+            &mut self.has_emitted_history_lines,
+            &mut state.has_emitted_history_lines,
+        );
+        let previous = &mut self.tabs[self.active_tab];
+        previous.activity = if state.chat_widget.is_task_running() {
+            TabActivity::Working
+        } else {
+            TabActivity::Idle
+        };
+        previous.parked = Some(state);
+        self.active_tab = idx;
+        self.tabs[idx].activity = TabActivity::Idle;
+        Some(unseen)
+    }
+
+    /// Add a header naming the active tab to the terminal history, followed
+    /// by what the conversation produced since it was last on screen. Its
+    /// earlier history is already in the terminal above.
+    fn show_active_tab(&mut self, tui: &mut tui::Tui, unseen: UnseenHistory) {
+        let title = self.tabs[self.active_tab].title(&self.transcript_lines);
+        let mut lines = vec![
+            Line::from(""),
+            Line::from(format!("── {} {title} ──", self.active_tab + 1).secondary()),
+        ];
+        if unseen.omitted > 0 {
+            lines.push(Line::from(
+                format!(
+                    "… {} earlier entries are only in the transcript",
+                    unseen.omitted
+                )
+                .secondary(),
+            ));
+        }
+        let width = tui.terminal.last_known_screen_size.width;
+        for cell in unseen.into_cells() {
+            lines.extend(history_entry(
+                cell.as_ref(),
+                width,
+                &mut self.has_emitted_history_lines,
+            ));
+        }
+        tui.insert_history_lines(lines);
+        tui.frame_requester().schedule_frame();
+    }
+
+    fn close_tab(&mut self, tui: &mut tui::Tui) {
+        let closing = self.active_tab;
+        let next = if closing + 1 < self.tabs.len() {
+            closing + 1
+        } else {
+            closing.saturating_sub(1)
+        };
+        let Some(unseen) = self.activate_tab(next) else {
+            return;
+        };
+        let tab = self.tabs.remove(closing);
+        if self.active_tab > closing {
+            self.active_tab -= 1;
+        }
+        self.set_commit_animation(tab.id, false);
+        if let Some(state) = tab.parked {
+            state.chat_widget.submit_op(Op::Shutdown);
+            if let Some(id) = state.chat_widget.conversation_id() {
+                let server = self.server.clone();
+                tokio::spawn(async move {
+                    server.remove_conversation(&id).await;
+                });
+            }
+        }
+        self.show_active_tab(tui, unseen);
+    }
+
+    fn show_tabs(&mut self) {
+        let mut items: Vec<SelectionItem> = self
+            .tab_titles()
+            .into_iter()
+            .zip(self.tabs.iter().map(|tab| tab.id))
+            .enumerate()
+            .map(|(idx, ((title, activity), id))| {
+                let actions: Vec<SelectionAction> = vec![Box::new(move |tx| {
+                    tx.send(AppEvent::SwitchTab(id));
+                })];
+                SelectionItem {
+                    name: format!("{} {title}", idx + 1),
+                    description: activity.description().map(str::to_string),
+                    is_current: idx == self.active_tab,
+                    actions,
+                }
+            })
+            .collect();
+        let actions: Vec<SelectionAction> = vec![Box::new(|tx| tx.send(AppEvent::NewTab))];
+        items.push(SelectionItem {
+            name: "New tab".to_string(),
+            description: None,
+            is_current: false,
+            actions,
+        });
+        self.chat_widget.show_selection_view(
+            "Open chats".to_string(),
+            Some("Press Enter to switch to a chat or Esc to go back".to_string()),
+            items,
+        );
+    }
+
     pub(crate) fn token_usage(&self) -> codex_core::protocol::TokenUsage {
         self.chat_widget.token_usage().clone()
     }
 
     async fn handle_key_event(&mut self, tui: &mut tui::Tui, key_event: KeyEvent) {
+        let action = keymap().action(
+            &[
+                KeyAction::Transcript,
+                KeyAction::Backtrack,
+                KeyAction::NextTab,
+                KeyAction::PreviousTab,
+            ],
+            &key_event,
+        );
         match key_event {
             KeyEvent {
-                code: KeyCode::Char('t'),
-                modifiers: crossterm::event::KeyModifiers::CONTROL,
                 kind: KeyEventKind::Press,
                 ..
-            } => {
+            } if action == Some(KeyAction::Transcript) => {
                 // Enter alternate screen and set viewport to full size.
                 let _ = tui.enter_alt_screen();
-                self.overlay = Some(Overlay::new_transcript(self.transcript_lines.clone()));
+                self.overlay = Some(Overlay::new_transcript(
+                    self.transcript_lines.clone(),
+                    self.transcript_outputs.clone(),
+                ));
                 tui.frame_requester().schedule_frame();
             }
+            KeyEvent {
+                kind: KeyEventKind::Press | KeyEventKind::Repeat,
+                ..
+            } if matches!(action, Some(KeyAction::NextTab | KeyAction::PreviousTab)) => {
+                let len = self.tabs.len();
+                let idx = if action == Some(KeyAction::NextTab) {
+                    (self.active_tab + 1) % len
+                } else {
+                    (self.active_tab + len - 1) % len
+                };
+                self.switch_tab(tui, idx);
+            }
             // Esc primes/advances backtracking only in normal (not working) mode
             // with an empty composer. In any other state, forward Esc so the
-            // active UI (e.g. status indicator, modals, popups) handles it.
+            // active UI (e.g. status indicator, modals, popups) handles it. A vi
+            // composer in insert mode always gets Esc first to leave insert mode.
             KeyEvent {
-                code: KeyCode::Esc,
                 kind: KeyEventKind::Press | KeyEventKind::Repeat,
                 ..
-            } => {
-                if self.chat_widget.is_normal_backtrack_mode()
+            } if action == Some(KeyAction::Backtrack) => {
+                if !self.chat_widget.is_vi_insert_mode()
+                    && self.chat_widget.is_normal_backtrack_mode()
                     && self.chat_widget.composer_is_empty()
                 {
                     self.handle_backtrack_esc_key(tui);
@@ -355,7 +698,7 @@ impl App {
                 // Any non-Esc key press should cancel a primed backtrack.
                 // This avoids stale "Esc-primed" state after the user starts typing
                 // (even if they later backspace to empty).
-                if key_event.code != KeyCode::Esc && self.backtrack.primed {
+                if action != Some(KeyAction::Backtrack) && self.backtrack.primed {
                     self.reset_backtrack_state();
                 }
                 self.chat_widget.handle_key_event(key_event);
@@ -366,3 +709,43 @@ impl App {
         };
     }
 }
+
+/// A cell's transcript lines, preceded by a separating blank line, and its
+/// full output keyed by where the lines land in a transcript of
+/// `transcript_len` lines.
+fn transcript_entry(
+    cell: &dyn HistoryCell,
+    transcript_len: usize,
+) -> (Vec<Line<'static>>, Option<TranscriptOutput>) {
+    let mut lines = cell.transcript_lines();
+    let mut start = transcript_len;
+    if !cell.is_stream_continuation() && transcript_len > 0 {
+        lines.insert(0, Line::from(""));
+        start += 1;
+    }
+    let output = cell.full_output().map(|text| TranscriptOutput {
+        lines: start..transcript_len + lines.len(),
+        text,
+    });
+    (lines, output)
+}
+
+/// A cell's lines for the terminal history.
+fn history_entry(
+    cell: &dyn HistoryCell,
+    width: u16,
+    has_emitted_history_lines: &mut bool,
+) -> Vec<Line<'static>> {
+    let mut display = cell.display_lines(width);
+    // Only insert a separating blank line for new cells that are not
+    // part of an ongoing stream. Streaming continuations should not
+    // accrue extra blank lines between chunks.
+    if !display.is_empty() && !cell.is_stream_continuation() {
+        if *has_emitted_history_lines {
+            display.insert(0, Line::from(""));
+        } else {
+            *has_emitted_history_lines = true;
+        }
+    }
+    display
+}
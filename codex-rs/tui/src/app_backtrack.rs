@@ -1,8 +1,10 @@
 use crate::app::App;
 use crate::backtrack_helpers;
+use crate::keymap::keymap;
 use crate::pager_overlay::Overlay;
 use crate::tui;
 use crate::tui::TuiEvent;
+use codex_core::config_types::KeyAction;
 use codex_core::protocol::ConversationHistoryResponseEvent;
 use codex_protocol::mcp_protocol::ConversationId;
 use color_eyre::eyre::Result;
@@ -35,13 +37,22 @@ impl App {
         tui: &mut tui::Tui,
         event: TuiEvent,
     ) -> Result<bool> {
+        // Keys typed into the pager's search prompt or an expanded output
+        // are not backtrack keys.
+        if self.overlay.as_ref().is_some_and(Overlay::captures_keys) {
+            self.overlay_forward_event(tui, event)?;
+            return Ok(true);
+        }
+        let is_backtrack_key = matches!(
+            &event,
+            TuiEvent::Key(key_event @ KeyEvent {
+                kind: KeyEventKind::Press | KeyEventKind::Repeat,
+                ..
+            }) if keymap().matches(KeyAction::Backtrack, key_event)
+        );
         if self.backtrack.overlay_preview_active {
             match event {
-                TuiEvent::Key(KeyEvent {
-                    code: KeyCode::Esc,
-                    kind: KeyEventKind::Press | KeyEventKind::Repeat,
-                    ..
-                }) => {
+                _ if is_backtrack_key => {
                     self.overlay_step_backtrack(tui, event)?;
                     Ok(true)
                 }
@@ -59,12 +70,7 @@ impl App {
                     Ok(true)
                 }
             }
-        } else if let TuiEvent::Key(KeyEvent {
-            code: KeyCode::Esc,
-            kind: KeyEventKind::Press | KeyEventKind::Repeat,
-            ..
-        }) = event
-        {
+        } else if is_backtrack_key {
             // First Esc in transcript overlay: begin backtrack preview at latest user message.
             self.begin_overlay_backtrack_preview(tui);
             Ok(true)
@@ -105,7 +111,10 @@ impl App {
     /// Open transcript overlay (enters alternate screen and shows full transcript).
     pub(crate) fn open_transcript_overlay(&mut self, tui: &mut tui::Tui) {
         let _ = tui.enter_alt_screen();
-        self.overlay = Some(Overlay::new_transcript(self.transcript_lines.clone()));
+        self.overlay = Some(Overlay::new_transcript(
+            self.transcript_lines.clone(),
+            self.transcript_outputs.clone(),
+        ));
         tui.frame_requester().schedule_frame();
     }
 
@@ -210,11 +219,27 @@ impl App {
             if overlay.is_done() {
                 self.close_transcript_overlay(tui);
                 tui.frame_requester().schedule_frame();
+            } else if let Overlay::Transcript(t) = overlay
+                && let Some(line) = t.take_search_jump()
+                && self.backtrack.overlay_preview_active
+            {
+                self.select_backtrack_at_line(line);
             }
         }
         Ok(())
     }
 
+    /// During a backtrack preview, select the user message containing a
+    /// search match so that Enter edits it and Esc steps back from it.
+    fn select_backtrack_at_line(&mut self, line: usize) {
+        let nth = backtrack_helpers::nth_last_user_for_line(&self.transcript_lines, line);
+        if nth > 0 {
+            let hl =
+                backtrack_helpers::highlight_range_for_nth_last_user(&self.transcript_lines, nth);
+            self.apply_backtrack_selection((nth, None, hl));
+        }
+    }
+
     /// Handle Enter in overlay backtrack preview: confirm selection and reset state.
     fn overlay_confirm_backtrack(&mut self, tui: &mut tui::Tui) {
         if let Some(base_id) = self.backtrack.base_id {
@@ -324,7 +349,7 @@ impl App {
         let init = crate::chatwidget::ChatWidgetInit {
             config: cfg,
             frame_requester: tui.frame_requester(),
-            app_event_tx: self.app_event_tx.clone(),
+            app_event_tx: self.app_event_tx.for_tab(self.active_tab_id()),
             initial_prompt: None,
             initial_images: Vec::new(),
             enhanced_keys_supported: self.enhanced_keys_supported,
@@ -346,8 +371,11 @@ impl App {
             backtrack_helpers::find_nth_last_user_header_index(&self.transcript_lines, drop_count)
         {
             self.transcript_lines.truncate(cut_idx);
+            self.transcript_outputs
+                .retain(|output| output.lines.end <= cut_idx);
         } else {
             self.transcript_lines.clear();
+            self.transcript_outputs.clear();
         }
     }
 }
@@ -3,6 +3,7 @@ use codex_core::protocol::Event;
 use codex_file_search::FileMatch;
 
 use crate::history_cell::HistoryCell;
+use crate::tabs::TabId;
 
 use codex_core::protocol::AskForApproval;
 use codex_core::protocol::SandboxPolicy;
@@ -13,9 +14,30 @@ use codex_core::protocol_config_types::ReasoningEffort;
 pub(crate) enum AppEvent {
     CodexEvent(Event),
 
+    /// An event from the conversation in tab `tab`.
+    TabEvent {
+        tab: TabId,
+        event: Box<AppEvent>,
+    },
+
     /// Start a new session.
     NewSession,
 
+    /// Start a new session in another tab and switch to it.
+    NewTab,
+
+    /// List the open tabs to switch between.
+    ShowTabs,
+
+    SwitchTab(TabId),
+
+    /// Name the current tab, or name it after its first message again when
+    /// the name is empty.
+    RenameTab(String),
+
+    /// End the current tab's conversation and close the tab.
+    CloseTab,
+
     /// Request to exit the application gracefully.
     ExitRequest,
 
@@ -39,6 +61,9 @@ pub(crate) enum AppEvent {
     /// Result of computing a `/diff` command.
     DiffResult(String),
 
+    /// Open the composer text in `$VISUAL` or `$EDITOR`.
+    OpenExternalEditor,
+
     InsertHistoryCell(Box<dyn HistoryCell>),
 
     StartCommitAnimation,
@@ -2,15 +2,29 @@ use tokio::sync::mpsc::UnboundedSender;
 
 use crate::app_event::AppEvent;
 use crate::session_log;
+use crate::tabs::TabId;
 
 #[derive(Clone, Debug)]
 pub(crate) struct AppEventSender {
     pub app_event_tx: UnboundedSender<AppEvent>,
+    /// Tab whose events are sent through this sender, if any.
+    tab: Option<TabId>,
 }
 
 impl AppEventSender {
     pub(crate) fn new(app_event_tx: UnboundedSender<AppEvent>) -> Self {
-        Self { app_event_tx }
+        Self {
+            app_event_tx,
+            tab: None,
+        }
+    }
+
+    /// A sender that marks its events as coming from `tab`.
+    pub(crate) fn for_tab(&self, tab: TabId) -> Self {
+        Self {
+            app_event_tx: self.app_event_tx.clone(),
+            tab: Some(tab),
+        }
     }
 
     /// Send an event to the app event channel. If it fails, we swallow the
@@ -21,6 +35,13 @@ impl AppEventSender {
         if !matches!(event, AppEvent::CodexOp(_)) {
             session_log::log_inbound_app_event(&event);
         }
+        let event = match self.tab {
+            Some(tab) => AppEvent::TabEvent {
+                tab,
+                event: Box::new(event),
+            },
+            None => event,
+        };
         if let Err(e) = self.app_event_tx.send(event) {
             tracing::error!("failed to send event: {e}");
         }
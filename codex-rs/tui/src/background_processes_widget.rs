@@ -0,0 +1,156 @@
+//! Lists the background processes the agent has started (dev servers,
+//! databases, watchers) above the composer.
+
+use codex_core::protocol::BackgroundProcessInfo;
+use codex_core::protocol::BackgroundProcessStatus;
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::Stylize;
+use ratatui::text::Line;
+use ratatui::text::Span;
+use ratatui::widgets::Paragraph;
+use ratatui::widgets::WidgetRef;
+
+use crate::exec_command::strip_bash_lc_and_escape;
+use crate::theme::ThemeStylize;
+
+/// Processes beyond this many are summarized as "… N more".
+const MAX_VISIBLE_PROCESSES: usize = 4;
+
+#[derive(Default)]
+pub(crate) struct BackgroundProcessesWidget {
+    processes: Vec<BackgroundProcessInfo>,
+}
+
+impl BackgroundProcessesWidget {
+    pub(crate) fn set_processes(&mut self, processes: Vec<BackgroundProcessInfo>) {
+        self.processes = processes;
+    }
+
+    /// Zero when there is nothing to show, so the panel takes no space.
+    pub(crate) fn desired_height(&self) -> u16 {
+        if self.processes.is_empty() {
+            return 0;
+        }
+        let visible = self.processes.len().min(MAX_VISIBLE_PROCESSES);
+        let overflow = usize::from(self.processes.len() > MAX_VISIBLE_PROCESSES);
+        // Header + one line per process + optional overflow line.
+        (1 + visible + overflow) as u16
+    }
+
+    fn lines(&self) -> Vec<Line<'static>> {
+        if self.processes.is_empty() {
+            return Vec::new();
+        }
+        let mut lines = vec![Line::from(" Background processes".secondary().bold())];
+        for process in self.processes.iter().take(MAX_VISIBLE_PROCESSES) {
+            let status: Span<'static> = match &process.status {
+                BackgroundProcessStatus::Starting => "starting".accent(),
+                BackgroundProcessStatus::Running => "running".success(),
+                BackgroundProcessStatus::Exited { exit_code } => {
+                    format!("exited ({exit_code})").error()
+                }
+            };
+            let mut spans = vec![
+                "   • ".secondary(),
+                process.name.clone().bold(),
+                " ".into(),
+                status,
+            ];
+            if let Some(pid) = process.pid
+                && !matches!(process.status, BackgroundProcessStatus::Exited { .. })
+            {
+                spans.push(format!(" pid {pid}").secondary());
+            }
+            spans.push(" · ".secondary());
+            spans.push(strip_bash_lc_and_escape(&process.command).secondary());
+            lines.push(Line::from(spans));
+        }
+        if self.processes.len() > MAX_VISIBLE_PROCESSES {
+            let hidden = self.processes.len() - MAX_VISIBLE_PROCESSES;
+            lines.push(Line::from(format!("   … {hidden} more").secondary()));
+        }
+        lines
+    }
+}
+
+impl WidgetRef for BackgroundProcessesWidget {
+    fn render_ref(&self, area: Rect, buf: &mut Buffer) {
+        if area.is_empty() {
+            return;
+        }
+        Paragraph::new(self.lines()).render_ref(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::path::PathBuf;
+
+    fn process(name: &str, status: BackgroundProcessStatus) -> BackgroundProcessInfo {
+        BackgroundProcessInfo {
+            name: name.to_string(),
+            command: vec!["bash".into(), "-lc".into(), "npm run dev".into()],
+            cwd: PathBuf::from("/repo"),
+            pid: Some(42),
+            status,
+        }
+    }
+
+    fn render_rows(widget: &BackgroundProcessesWidget, width: u16) -> Vec<String> {
+        let area = Rect::new(0, 0, width, widget.desired_height());
+        let mut buf = Buffer::empty(area);
+        widget.render_ref(area, &mut buf);
+        (0..area.height)
+            .map(|y| {
+                (0..area.width)
+                    .map(|x| buf[(x, y)].symbol())
+                    .collect::<String>()
+                    .trim_end()
+                    .to_string()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn empty_panel_takes_no_space() {
+        let widget = BackgroundProcessesWidget::default();
+        assert_eq!(widget.desired_height(), 0);
+    }
+
+    #[test]
+    fn renders_one_line_per_process() {
+        let mut widget = BackgroundProcessesWidget::default();
+        widget.set_processes(vec![
+            process("db", BackgroundProcessStatus::Starting),
+            process("web", BackgroundProcessStatus::Running),
+            process("worker", BackgroundProcessStatus::Exited { exit_code: 1 }),
+        ]);
+
+        assert_eq!(
+            render_rows(&widget, 60),
+            vec![
+                " Background processes",
+                "   • db starting pid 42 · npm run dev",
+                "   • web running pid 42 · npm run dev",
+                "   • worker exited (1) · npm run dev",
+            ]
+        );
+    }
+
+    #[test]
+    fn summarizes_processes_beyond_limit() {
+        let mut widget = BackgroundProcessesWidget::default();
+        widget.set_processes(
+            (0..6)
+                .map(|i| process(&format!("p{i}"), BackgroundProcessStatus::Running))
+                .collect(),
+        );
+
+        let rows = render_rows(&widget, 60);
+        assert_eq!(rows.len(), 6);
+        assert_eq!(rows[5], "   … 2 more");
+    }
+}
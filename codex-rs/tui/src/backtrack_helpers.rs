@@ -23,13 +23,7 @@ pub(crate) fn find_nth_last_user_header_index(lines: &[Line<'_>], n: usize) -> O
     }
     let mut found = 0usize;
     for (idx, line) in lines.iter().enumerate().rev() {
-        let content: String = line
-            .spans
-            .iter()
-            .map(|s| s.content.as_ref())
-            .collect::<Vec<_>>()
-            .join("");
-        if content.trim() == "user" {
+        if is_user_header(line) {
             found += 1;
             if found == n {
                 return Some(idx);
@@ -39,6 +33,33 @@ pub(crate) fn find_nth_last_user_header_index(lines: &[Line<'_>], n: usize) -> O
     None
 }
 
+/// The step `n` that selects the user message at or before `line_idx`, i.e.
+/// the message a line of the transcript belongs to. Returns `0` if no user
+/// message precedes the line.
+pub(crate) fn nth_last_user_for_line(lines: &[Line<'_>], line_idx: usize) -> usize {
+    let Some(header_idx) = lines
+        .iter()
+        .take(line_idx.saturating_add(1))
+        .rposition(is_user_header)
+    else {
+        return 0;
+    };
+    lines[header_idx..]
+        .iter()
+        .filter(|line| is_user_header(line))
+        .count()
+}
+
+fn is_user_header(line: &Line<'_>) -> bool {
+    let content: String = line
+        .spans
+        .iter()
+        .map(|s| s.content.as_ref())
+        .collect::<Vec<_>>()
+        .join("");
+    content.trim() == "user"
+}
+
 /// Normalize a requested backtrack step `n` against the available user messages.
 /// - Returns `0` if there are no user messages.
 /// - Returns `n` if the Nth last user message exists.
@@ -65,6 +86,12 @@ pub(crate) fn nth_last_user_text(lines: &[Line<'_>], n: usize) -> Option<String>
     extract_message_text_after_header(lines, header_idx)
 }
 
+/// Extract the text content of the first user message.
+pub(crate) fn first_user_text(lines: &[Line<'_>]) -> Option<String> {
+    let header_idx = lines.iter().position(is_user_header)?;
+    extract_message_text_after_header(lines, header_idx)
+}
+
 /// Extract message text starting after `header_idx` until the first blank line.
 fn extract_message_text_after_header(lines: &[Line<'_>], header_idx: usize) -> Option<String> {
     let start = header_idx + 1;
@@ -150,4 +177,17 @@ mod tests {
         let lines = transcript_with_users(3);
         assert_eq!(normalize_backtrack_n(&lines, 2), 2);
     }
+
+    #[test]
+    fn nth_last_user_for_line_finds_enclosing_message() {
+        let mut lines = vec![line("intro")];
+        lines.extend(transcript_with_users(3));
+        assert_eq!(nth_last_user_for_line(&lines, 0), 0);
+        // The first message's header and body.
+        assert_eq!(nth_last_user_for_line(&lines, 1), 3);
+        assert_eq!(nth_last_user_for_line(&lines, 2), 3);
+        // The blank line after the second message.
+        assert_eq!(nth_last_user_for_line(&lines, 6), 2);
+        assert_eq!(nth_last_user_for_line(&lines, 8), 1);
+    }
 }
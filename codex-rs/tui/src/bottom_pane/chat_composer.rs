@@ -1,3 +1,4 @@
+use codex_core::config_types::KeyAction;
 use codex_core::protocol::TokenUsageInfo;
 use codex_protocol::num_format::format_si_suffix;
 use crossterm::event::KeyCode;
@@ -12,7 +13,6 @@ use ratatui::layout::Rect;
 use ratatui::style::Color;
 use ratatui::style::Modifier;
 use ratatui::style::Style;
-use ratatui::style::Stylize;
 use ratatui::text::Line;
 use ratatui::text::Span;
 use ratatui::widgets::Block;
@@ -35,9 +35,12 @@ use crate::app_event::AppEvent;
 use crate::app_event_sender::AppEventSender;
 use crate::bottom_pane::textarea::TextArea;
 use crate::bottom_pane::textarea::TextAreaState;
+use crate::bottom_pane::textarea::ViMode;
 use crate::clipboard_paste::normalize_pasted_path;
 use crate::clipboard_paste::pasted_image_format;
 use crate::key_hint;
+use crate::keymap::keymap;
+use crate::theme::theme;
 use codex_file_search::FileMatch;
 use std::cell::RefCell;
 use std::collections::HashMap;
@@ -55,6 +58,8 @@ const LARGE_PASTE_CHAR_THRESHOLD: usize = 1000;
 pub enum InputResult {
     Submitted(String),
     Command(SlashCommand),
+    /// A command that takes an argument, with the text typed after it.
+    CommandWithArgument(SlashCommand, String),
     None,
 }
 
@@ -72,7 +77,7 @@ pub(crate) struct ChatComposer {
     history: ChatComposerHistory,
     ctrl_c_quit_hint: bool,
     esc_backtrack_hint: bool,
-    use_shift_enter_hint: bool,
+    enhanced_keys_supported: bool,
     dismissed_file_popup_token: Option<String>,
     current_file_query: Option<String>,
     pending_pastes: Vec<(String, String)>,
@@ -103,8 +108,6 @@ impl ChatComposer {
         placeholder_text: String,
         disable_paste_burst: bool,
     ) -> Self {
-        let use_shift_enter_hint = enhanced_keys_supported;
-
         let mut this = Self {
             textarea: TextArea::new(),
             textarea_state: RefCell::new(TextAreaState::default()),
@@ -113,7 +116,7 @@ impl ChatComposer {
             history: ChatComposerHistory::new(),
             ctrl_c_quit_hint: false,
             esc_backtrack_hint: false,
-            use_shift_enter_hint,
+            enhanced_keys_supported,
             dismissed_file_popup_token: None,
             current_file_query: None,
             pending_pastes: Vec::new(),
@@ -233,6 +236,15 @@ impl ChatComposer {
         }
     }
 
+    pub(crate) fn enable_vi_mode(&mut self) {
+        self.textarea.enable_vi_mode();
+    }
+
+    /// True when vi editing is enabled and keys typed are inserted as text.
+    pub(crate) fn is_vi_insert_mode(&self) -> bool {
+        self.textarea.vi_mode() == Some(ViMode::Insert)
+    }
+
     pub(crate) fn set_disable_paste_burst(&mut self, disabled: bool) {
         let was_disabled = self.disable_paste_burst;
         self.disable_paste_burst = disabled;
@@ -249,6 +261,39 @@ impl ChatComposer {
         self.sync_file_search_popup();
     }
 
+    /// Replace the composer content with text edited outside the TUI. Paste
+    /// and image placeholders that survived the edit are restored as
+    /// elements so they still expand on submit; the rest are dropped.
+    pub(crate) fn apply_external_edit(&mut self, text: String) {
+        let placeholders: Vec<String> = self
+            .pending_pastes
+            .iter()
+            .map(|(placeholder, _)| placeholder.clone())
+            .chain(
+                self.attached_images
+                    .iter()
+                    .map(|img| img.placeholder.clone()),
+            )
+            .collect();
+
+        self.textarea.set_text("");
+        let mut rest = text.as_str();
+        while let Some((idx, placeholder)) = placeholders
+            .iter()
+            .filter_map(|p| rest.find(p.as_str()).map(|idx| (idx, p)))
+            .min_by_key(|(idx, _)| *idx)
+        {
+            self.textarea.insert_str(&rest[..idx]);
+            self.textarea.insert_element(placeholder);
+            rest = &rest[idx + placeholder.len()..];
+        }
+        self.textarea.insert_str(rest);
+
+        self.prune_attachments();
+        self.sync_command_popup();
+        self.sync_file_search_popup();
+    }
+
     /// Get the current composer text.
     #[cfg(test)]
     pub(crate) fn current_text(&self) -> String {
@@ -402,6 +447,14 @@ impl ChatComposer {
                 ..
             } => {
                 if let Some(sel) = popup.selected_item() {
+                    let argument = self
+                        .textarea
+                        .text()
+                        .lines()
+                        .next()
+                        .and_then(|line| line.trim_start().split_once(char::is_whitespace))
+                        .map(|(_, rest)| rest.trim().to_string())
+                        .unwrap_or_default();
                     // Clear textarea so no residual text remains.
                     self.textarea.set_text("");
                     // Capture any needed data from popup before clearing it.
@@ -415,6 +468,9 @@ impl ChatComposer {
                     self.active_popup = ActivePopup::None;
 
                     match sel {
+                        CommandItem::Builtin(cmd) if cmd.takes_argument() => {
+                            return (InputResult::CommandWithArgument(cmd, argument), true);
+                        }
                         CommandItem::Builtin(cmd) => {
                             return (InputResult::Command(cmd), true);
                         }
@@ -711,33 +767,44 @@ impl ChatComposer {
 
     /// Handle key event when no popup is visible.
     fn handle_key_event_without_popup(&mut self, key_event: KeyEvent) -> (InputResult, bool) {
-        match key_event {
-            KeyEvent {
-                code: KeyCode::Char('d'),
-                modifiers: crossterm::event::KeyModifiers::CONTROL,
-                kind: KeyEventKind::Press,
-                ..
-            } if self.is_empty() => {
+        // Newline is looked up only so that its keys are not mistaken for
+        // Submit; inserting the newline is left to the textarea.
+        let action = keymap().action(
+            &[
+                KeyAction::Quit,
+                KeyAction::HistoryPrevious,
+                KeyAction::HistoryNext,
+                KeyAction::Newline,
+                KeyAction::Submit,
+                KeyAction::ExternalEditor,
+            ],
+            &key_event,
+        );
+        match action {
+            Some(KeyAction::Quit) if key_event.kind == KeyEventKind::Press && self.is_empty() => {
                 self.app_event_tx.send(AppEvent::ExitRequest);
                 (InputResult::None, true)
             }
+            Some(KeyAction::ExternalEditor) => {
+                if key_event.kind == KeyEventKind::Press {
+                    self.app_event_tx.send(AppEvent::OpenExternalEditor);
+                }
+                (InputResult::None, true)
+            }
             // -------------------------------------------------------------
-            // History navigation (Up / Down) – only when the composer is not
-            // empty or when the cursor is at the correct position, to avoid
+            // History navigation – only when the composer is not empty or
+            // when the cursor is at the correct position, to avoid
             // interfering with normal cursor movement.
             // -------------------------------------------------------------
-            KeyEvent {
-                code: KeyCode::Up | KeyCode::Down,
-                ..
-            } => {
+            Some(action @ (KeyAction::HistoryPrevious | KeyAction::HistoryNext)) => {
                 if self
                     .history
                     .should_handle_navigation(self.textarea.text(), self.textarea.cursor())
                 {
-                    let replace_text = match key_event.code {
-                        KeyCode::Up => self.history.navigate_up(&self.app_event_tx),
-                        KeyCode::Down => self.history.navigate_down(&self.app_event_tx),
-                        _ => unreachable!(),
+                    let replace_text = if action == KeyAction::HistoryPrevious {
+                        self.history.navigate_up(&self.app_event_tx)
+                    } else {
+                        self.history.navigate_down(&self.app_event_tx)
                     };
                     if let Some(text) = replace_text {
                         self.textarea.set_text(&text);
@@ -747,11 +814,7 @@ impl ChatComposer {
                 }
                 self.handle_input_basic(key_event)
             }
-            KeyEvent {
-                code: KeyCode::Enter,
-                modifiers: KeyModifiers::NONE,
-                ..
-            } => {
+            Some(KeyAction::Submit) => {
                 // If we're in a paste-like burst capture, treat Enter as part of the burst
                 // and accumulate it rather than submitting or inserting immediately.
                 // Do not treat Enter as paste inside a slash-command context.
@@ -769,10 +832,13 @@ impl ChatComposer {
                         return (InputResult::None, true);
                     }
                 }
+                // Vi mode keeps attachments for undo until the message is sent.
+                self.drop_unused_attachments();
                 // If we have pending placeholder pastes, submit immediately to expand them.
                 if !self.pending_pastes.is_empty() {
                     let mut text = self.textarea.text().to_string();
                     self.textarea.set_text("");
+                    self.textarea.reset_vi_mode();
                     for (placeholder, actual) in &self.pending_pastes {
                         if text.contains(placeholder) {
                             text = text.replace(placeholder, actual);
@@ -798,6 +864,7 @@ impl ChatComposer {
                 }
                 let mut text = self.textarea.text().to_string();
                 self.textarea.set_text("");
+                self.textarea.reset_vi_mode();
 
                 // Replace all pending pastes in the text
                 for (placeholder, actual) in &self.pending_pastes {
@@ -819,7 +886,7 @@ impl ChatComposer {
                 // Do not clear attached_images here; ChatWidget drains them via take_recent_submission_images().
                 (InputResult::Submitted(text), true)
             }
-            input => self.handle_input_basic(input),
+            _ => self.handle_input_basic(key_event),
         }
     }
 
@@ -862,12 +929,20 @@ impl ChatComposer {
             return (InputResult::None, true);
         }
 
+        // In vi normal and visual mode characters are commands, so they are
+        // neither buffered as a paste nor special-cased below.
+        let vi_command_mode = self
+            .textarea
+            .vi_mode()
+            .is_some_and(|mode| mode != ViMode::Insert);
+
         // Intercept plain Char inputs to optionally accumulate into a burst buffer.
-        if let KeyEvent {
-            code: KeyCode::Char(ch),
-            modifiers,
-            ..
-        } = input
+        if !vi_command_mode
+            && let KeyEvent {
+                code: KeyCode::Char(ch),
+                modifiers,
+                ..
+            } = input
         {
             let has_ctrl_or_alt =
                 modifiers.contains(KeyModifiers::CONTROL) || modifiers.contains(KeyModifiers::ALT);
@@ -920,11 +995,13 @@ impl ChatComposer {
         }
 
         // For non-char inputs (or after flushing), handle normally.
-        // Special handling for backspace on placeholders
-        if let KeyEvent {
-            code: KeyCode::Backspace,
-            ..
-        } = input
+        // Special handling for backspace on placeholders. In vi mode the
+        // textarea deletes them atomically and the attachment stays for undo.
+        if self.textarea.vi_mode().is_none()
+            && let KeyEvent {
+                code: KeyCode::Backspace,
+                ..
+            } = input
             && self.try_remove_any_placeholder_at_cursor()
         {
             return (InputResult::None, true);
@@ -932,7 +1009,6 @@ impl ChatComposer {
 
         // Normal input handling
         self.textarea.input(input);
-        let text_after = self.textarea.text();
 
         // Update paste-burst heuristic for plain Char (no Ctrl/Alt) events.
         let crossterm::event::KeyEvent {
@@ -955,6 +1031,22 @@ impl ChatComposer {
             }
         }
 
+        self.prune_attachments();
+
+        (InputResult::None, true)
+    }
+
+    /// Drops pending pastes and attached images whose placeholders are no
+    /// longer in the text. In vi mode they are kept until submit instead,
+    /// since undo can bring a deleted placeholder back.
+    fn prune_attachments(&mut self) {
+        if self.textarea.vi_mode().is_none() {
+            self.drop_unused_attachments();
+        }
+    }
+
+    fn drop_unused_attachments(&mut self) {
+        let text_after = self.textarea.text();
         // Check if any placeholders were removed and remove their corresponding pending pastes
         self.pending_pastes
             .retain(|(placeholder, _)| text_after.contains(placeholder));
@@ -981,8 +1073,6 @@ impl ChatComposer {
             }
             self.attached_images = kept;
         }
-
-        (InputResult::None, true)
     }
 
     /// Attempts to remove an image or paste placeholder if the cursor is at the end of one.
@@ -1248,27 +1338,36 @@ impl WidgetRef for ChatComposer {
                         ctrl_c_followup.into(),
                     ]
                 } else {
-                    let newline_hint_key = if self.use_shift_enter_hint {
-                        key_hint::shift('⏎')
-                    } else {
-                        key_hint::ctrl('J')
-                    };
-                    vec![
-                        " ".into(),
-                        key_hint::plain('⏎'),
-                        " send   ".into(),
-                        newline_hint_key,
-                        " newline   ".into(),
-                        key_hint::ctrl('T'),
-                        " transcript   ".into(),
-                        key_hint::ctrl('C'),
-                        " quit".into(),
-                    ]
+                    let mut hint = vec![" ".into()];
+                    if let Some(mode) = self.textarea.vi_mode() {
+                        hint.push(Span::styled(
+                            format!("-- {} --", mode.label()),
+                            theme().accent.add_modifier(Modifier::BOLD),
+                        ));
+                        hint.push("   ".into());
+                    }
+                    for (action, description) in [
+                        (KeyAction::Submit, " send   "),
+                        (KeyAction::Newline, " newline   "),
+                        (KeyAction::Transcript, " transcript   "),
+                    ] {
+                        if let Some(key) = key_hint::action(action, self.enhanced_keys_supported) {
+                            hint.push(key);
+                            hint.push(description.into());
+                        }
+                    }
+                    hint.push(key_hint::ctrl('C'));
+                    hint.push(" quit".into());
+                    hint
                 };
 
-                if !self.ctrl_c_quit_hint && self.esc_backtrack_hint {
+                if !self.ctrl_c_quit_hint
+                    && self.esc_backtrack_hint
+                    && let Some(key) =
+                        key_hint::action(KeyAction::Backtrack, self.enhanced_keys_supported)
+                {
                     hint.push("   ".into());
-                    hint.push(key_hint::plain("Esc"));
+                    hint.push(key);
                     hint.push(" edit prev".into());
                 }
 
@@ -1281,7 +1380,7 @@ impl WidgetRef for ChatComposer {
                             "{} tokens used",
                             format_si_suffix(token_usage.blended_total())
                         ))
-                        .style(Style::default().add_modifier(Modifier::DIM)),
+                        .style(theme().secondary),
                     );
                     let last_token_usage = &token_usage_info.last_token_usage;
                     if let Some(context_window) = token_usage_info.model_context_window {
@@ -1293,7 +1392,7 @@ impl WidgetRef for ChatComposer {
                         let context_style = if percent_remaining < 20 {
                             Style::default().fg(Color::Yellow)
                         } else {
-                            Style::default().add_modifier(Modifier::DIM)
+                            theme().secondary
                         };
                         hint.push("   ".into());
                         hint.push(Span::styled(
@@ -1304,14 +1403,14 @@ impl WidgetRef for ChatComposer {
                 }
 
                 Line::from(hint)
-                    .style(Style::default().dim())
+                    .style(theme().secondary)
                     .render_ref(bottom_line_rect, buf);
             }
         }
         let border_style = if self.has_focus {
-            Style::default().fg(Color::Cyan)
+            theme().accent
         } else {
-            Style::default().add_modifier(Modifier::DIM)
+            theme().secondary
         };
         Block::default()
             .borders(Borders::LEFT)
@@ -1329,7 +1428,7 @@ impl WidgetRef for ChatComposer {
         StatefulWidgetRef::render_ref(&(&self.textarea), textarea_rect, buf, &mut state);
         if self.textarea.text().is_empty() {
             Line::from(self.placeholder_text.as_str())
-                .style(Style::default().dim())
+                .style(theme().secondary)
                 .render_ref(textarea_rect.inner(Margin::new(1, 0)), buf);
         }
     }
@@ -1557,6 +1656,85 @@ mod tests {
         }
     }
 
+    #[test]
+    fn vi_normal_mode_keys_are_commands_and_submit_returns_to_insert_mode() {
+        use crossterm::event::KeyCode;
+        use crossterm::event::KeyEvent;
+        use crossterm::event::KeyModifiers;
+
+        let (tx, _rx) = unbounded_channel::<AppEvent>();
+        let sender = AppEventSender::new(tx);
+        let mut composer = ChatComposer::new(
+            true,
+            sender,
+            false,
+            "Ask Codex to do anything".to_string(),
+            false,
+        );
+        composer.enable_vi_mode();
+        assert!(composer.is_vi_insert_mode());
+
+        composer.handle_paste("hello world".to_string());
+        composer.handle_key_event(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE));
+        assert!(!composer.is_vi_insert_mode());
+        // Typed in quick succession, these are still commands rather than a paste.
+        for c in "0dw".chars() {
+            composer.handle_key_event(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE));
+        }
+        assert_eq!(composer.textarea.text(), "world");
+
+        let (result, _) =
+            composer.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        match result {
+            InputResult::Submitted(text) => assert_eq!(text, "world"),
+            other => panic!("expected Submitted, got: {other:?}"),
+        }
+        assert!(composer.is_vi_insert_mode());
+    }
+
+    #[test]
+    fn vi_undo_restores_a_deleted_paste_placeholder() {
+        use crossterm::event::KeyCode;
+        use crossterm::event::KeyEvent;
+        use crossterm::event::KeyModifiers;
+
+        let (tx, _rx) = unbounded_channel::<AppEvent>();
+        let sender = AppEventSender::new(tx);
+        let mut composer = ChatComposer::new(
+            true,
+            sender,
+            false,
+            "Ask Codex to do anything".to_string(),
+            false,
+        );
+        composer.enable_vi_mode();
+
+        let large = "x".repeat(LARGE_PASTE_CHAR_THRESHOLD + 10);
+        composer.handle_paste(large.clone());
+        let placeholder = composer.textarea.text().to_string();
+        composer.handle_key_event(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE));
+        for c in "ddu".chars() {
+            composer.handle_key_event(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE));
+        }
+        assert_eq!(composer.textarea.text(), placeholder);
+
+        let (result, _) =
+            composer.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        match result {
+            InputResult::Submitted(text) => assert_eq!(text, large),
+            other => panic!("expected Submitted, got: {other:?}"),
+        }
+
+        // A placeholder that is still deleted on submit drops its paste.
+        composer.handle_paste(large);
+        composer.handle_key_event(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE));
+        composer.handle_key_event(KeyEvent::new(KeyCode::Char('x'), KeyModifiers::NONE));
+        let (result, _) =
+            composer.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        assert_eq!(result, InputResult::None);
+        assert!(composer.pending_pastes.is_empty());
+    }
+
     #[test]
     fn handle_paste_large_uses_placeholder_and_replaces_on_submit() {
         use crossterm::event::KeyCode;
@@ -1616,6 +1794,39 @@ mod tests {
         assert!(composer.pending_pastes.is_empty());
     }
 
+    #[test]
+    fn external_edit_keeps_surviving_placeholders() {
+        use crossterm::event::KeyCode;
+        use crossterm::event::KeyEvent;
+        use crossterm::event::KeyModifiers;
+
+        let (tx, _rx) = unbounded_channel::<AppEvent>();
+        let sender = AppEventSender::new(tx);
+        let mut composer = ChatComposer::new(
+            true,
+            sender,
+            false,
+            "Ask Codex to do anything".to_string(),
+            false,
+        );
+
+        let large = "z".repeat(LARGE_PASTE_CHAR_THRESHOLD + 5);
+        composer.handle_paste(large.clone());
+        let paste = composer.pending_pastes[0].0.clone();
+        composer.attach_image(PathBuf::from("/tmp/a.png"), 4, 4, "PNG");
+
+        composer.apply_external_edit(format!("see {paste} and more"));
+        assert_eq!(composer.pending_pastes.len(), 1);
+        assert!(composer.attached_images.is_empty());
+
+        let (result, _) =
+            composer.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        match result {
+            InputResult::Submitted(text) => assert_eq!(text, format!("see {large} and more")),
+            other => panic!("expected Submitted, got: {other:?}"),
+        }
+    }
+
     #[test]
     fn ui_snapshots() {
         use crossterm::event::KeyCode;
@@ -1779,11 +1990,47 @@ mod tests {
             InputResult::Submitted(text) => {
                 panic!("expected command dispatch, but composer submitted literal text: {text}")
             }
+            InputResult::CommandWithArgument(cmd, _) => {
+                panic!(
+                    "expected Command result for '/init', got '/{}'",
+                    cmd.command()
+                )
+            }
             InputResult::None => panic!("expected Command result for '/init'"),
         }
         assert!(composer.textarea.is_empty(), "composer should be cleared");
     }
 
+    #[test]
+    fn slash_rename_passes_the_text_after_the_command() {
+        use crossterm::event::KeyCode;
+        use crossterm::event::KeyEvent;
+        use crossterm::event::KeyModifiers;
+
+        let (tx, _rx) = unbounded_channel::<AppEvent>();
+        let sender = AppEventSender::new(tx);
+        let mut composer = ChatComposer::new(
+            true,
+            sender,
+            false,
+            "Ask Codex to do anything".to_string(),
+            false,
+        );
+
+        composer.handle_paste("/rename  flaky tests ".to_string());
+        let (result, _needs_redraw) =
+            composer.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+
+        match result {
+            InputResult::CommandWithArgument(cmd, argument) => {
+                assert_eq!(cmd, SlashCommand::Rename);
+                assert_eq!(argument, "flaky tests");
+            }
+            _ => panic!("expected '/rename' with its argument"),
+        }
+        assert!(composer.textarea.is_empty(), "composer should be cleared");
+    }
+
     #[test]
     fn slash_tab_completion_moves_cursor_to_end() {
         use crossterm::event::KeyCode;
@@ -1837,6 +2084,12 @@ mod tests {
             InputResult::Submitted(text) => {
                 panic!("expected command dispatch, but composer submitted literal text: {text}")
             }
+            InputResult::CommandWithArgument(cmd, _) => {
+                panic!(
+                    "expected Command result for '/mention', got '/{}'",
+                    cmd.command()
+                )
+            }
             InputResult::None => panic!("expected Command result for '/mention'"),
         }
         assert!(composer.textarea.is_empty(), "composer should be cleared");
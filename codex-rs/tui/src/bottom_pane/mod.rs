@@ -2,9 +2,11 @@
 use std::path::PathBuf;
 
 use crate::app_event_sender::AppEventSender;
+use crate::background_processes_widget::BackgroundProcessesWidget;
 use crate::tui::FrameRequester;
 use crate::user_approval_widget::ApprovalRequest;
 use bottom_pane_view::BottomPaneView;
+use codex_core::protocol::BackgroundProcessInfo;
 use codex_core::protocol::TokenUsageInfo;
 use codex_file_search::FileMatch;
 use crossterm::event::KeyEvent;
@@ -64,6 +66,8 @@ pub(crate) struct BottomPane {
     status: Option<StatusIndicatorWidget>,
     /// Queued user messages to show under the status indicator.
     queued_user_messages: Vec<String>,
+    /// Background processes started by the agent, listed above the composer.
+    background_processes: BackgroundProcessesWidget,
 }
 
 pub(crate) struct BottomPaneParams {
@@ -73,20 +77,26 @@ pub(crate) struct BottomPaneParams {
     pub(crate) enhanced_keys_supported: bool,
     pub(crate) placeholder_text: String,
     pub(crate) disable_paste_burst: bool,
+    /// Edit the composer with vi-style modal key bindings.
+    pub(crate) vi_mode: bool,
 }
 
 impl BottomPane {
     const BOTTOM_PAD_LINES: u16 = 1;
     pub fn new(params: BottomPaneParams) -> Self {
         let enhanced_keys_supported = params.enhanced_keys_supported;
+        let mut composer = ChatComposer::new(
+            params.has_input_focus,
+            params.app_event_tx.clone(),
+            enhanced_keys_supported,
+            params.placeholder_text,
+            params.disable_paste_burst,
+        );
+        if params.vi_mode {
+            composer.enable_vi_mode();
+        }
         Self {
-            composer: ChatComposer::new(
-                params.has_input_focus,
-                params.app_event_tx.clone(),
-                enhanced_keys_supported,
-                params.placeholder_text,
-                params.disable_paste_burst,
-            ),
+            composer,
             active_view: None,
             app_event_tx: params.app_event_tx,
             frame_requester: params.frame_requester,
@@ -95,6 +105,7 @@ impl BottomPane {
             ctrl_c_quit_hint: false,
             status: None,
             queued_user_messages: Vec::new(),
+            background_processes: BackgroundProcessesWidget::default(),
             esc_backtrack_hint: false,
         }
     }
@@ -106,18 +117,22 @@ impl BottomPane {
         // Base height depends on whether a modal/overlay is active.
         let base = match self.active_view.as_ref() {
             Some(view) => view.desired_height(width),
-            None => self.composer.desired_height(width).saturating_add(
-                self.status
-                    .as_ref()
-                    .map_or(0, |status| status.desired_height(width)),
-            ),
+            None => self
+                .composer
+                .desired_height(width)
+                .saturating_add(
+                    self.status
+                        .as_ref()
+                        .map_or(0, |status| status.desired_height(width)),
+                )
+                .saturating_add(self.background_processes.desired_height()),
         };
         // Account for bottom padding rows. Top spacing is handled in layout().
         base.saturating_add(Self::BOTTOM_PAD_LINES)
             .saturating_add(top_margin)
     }
 
-    fn layout(&self, area: Rect) -> [Rect; 2] {
+    fn layout(&self, area: Rect) -> [Rect; 3] {
         // At small heights, bottom pane takes the entire height.
         let (top_margin, bottom_margin) = if area.height <= BottomPane::BOTTOM_PAD_LINES + 1 {
             (0, 0)
@@ -132,13 +147,18 @@ impl BottomPane {
             height: area.height - top_margin - bottom_margin,
         };
         match self.active_view.as_ref() {
-            Some(_) => [Rect::ZERO, area],
+            Some(_) => [Rect::ZERO, Rect::ZERO, area],
             None => {
                 let status_height = self
                     .status
                     .as_ref()
                     .map_or(0, |status| status.desired_height(area.width));
-                Layout::vertical([Constraint::Max(status_height), Constraint::Min(1)]).areas(area)
+                Layout::vertical([
+                    Constraint::Max(status_height),
+                    Constraint::Max(self.background_processes.desired_height()),
+                    Constraint::Min(1),
+                ])
+                .areas(area)
             }
         }
     }
@@ -151,7 +171,7 @@ impl BottomPane {
         if self.active_view.is_some() {
             None
         } else {
-            let [_, content] = self.layout(area);
+            let [_, _, content] = self.layout(area);
             self.composer.cursor_pos(content)
         }
     }
@@ -169,9 +189,11 @@ impl BottomPane {
             InputResult::None
         } else {
             // If a task is running and a status line is visible, allow Esc to
-            // send an interrupt even while the composer has focus.
+            // send an interrupt even while the composer has focus. In vi insert
+            // mode, Esc first switches the composer to normal mode.
             if matches!(key_event.code, crossterm::event::KeyCode::Esc)
                 && self.is_task_running
+                && !self.composer.is_vi_insert_mode()
                 && let Some(status) = &self.status
             {
                 // Send Op::Interrupt
@@ -243,12 +265,17 @@ impl BottomPane {
         self.request_redraw();
     }
 
-    /// Get the current composer text (for tests and programmatic checks).
-    #[cfg(test)]
+    /// Get the current composer text.
     pub(crate) fn composer_text(&self) -> String {
         self.composer.current_text()
     }
 
+    /// Replace the composer text with `text` as edited in an external editor.
+    pub(crate) fn apply_external_edit(&mut self, text: String) {
+        self.composer.apply_external_edit(text);
+        self.request_redraw();
+    }
+
     /// Update the animated header shown to the left of the brackets in the
     /// status indicator (defaults to "Working"). No-ops if the status
     /// indicator is not active.
@@ -345,6 +372,12 @@ impl BottomPane {
         self.request_redraw();
     }
 
+    /// Update the list of background processes shown above the composer.
+    pub(crate) fn set_background_processes(&mut self, processes: Vec<BackgroundProcessInfo>) {
+        self.background_processes.set_processes(processes);
+        self.request_redraw();
+    }
+
     /// Update custom prompts available for the slash popup.
     pub(crate) fn set_custom_prompts(&mut self, prompts: Vec<CustomPrompt>) {
         self.composer.set_custom_prompts(prompts);
@@ -366,6 +399,12 @@ impl BottomPane {
         !self.is_task_running && self.active_view.is_none() && !self.composer.popup_active()
     }
 
+    /// True when the composer edits in vi mode and is in insert mode, where
+    /// Esc switches it to normal mode before it means anything else.
+    pub(crate) fn is_vi_insert_mode(&self) -> bool {
+        self.composer.is_vi_insert_mode()
+    }
+
     /// Update the *context-window remaining* indicator in the composer. This
     /// is forwarded directly to the underlying `ChatComposer`.
     pub(crate) fn set_token_usage(&mut self, token_info: Option<TokenUsageInfo>) {
@@ -474,7 +513,7 @@ impl BottomPane {
 
 impl WidgetRef for &BottomPane {
     fn render_ref(&self, area: Rect, buf: &mut Buffer) {
-        let [status_area, content] = self.layout(area);
+        let [status_area, background_processes_area, content] = self.layout(area);
 
         // When a modal view is active, it owns the whole content area.
         if let Some(view) = &self.active_view {
@@ -485,6 +524,8 @@ impl WidgetRef for &BottomPane {
             if let Some(status) = &self.status {
                 status.render_ref(status_area, buf);
             }
+            self.background_processes
+                .render_ref(background_processes_area, buf);
 
             // Render the composer in the remaining area.
             self.composer.render_ref(content, buf);
@@ -519,6 +560,7 @@ mod tests {
             enhanced_keys_supported: false,
             placeholder_text: "Ask Codex to do anything".to_string(),
             disable_paste_burst: false,
+            vi_mode: false,
         });
         pane.push_approval_request(exec_request());
         assert_eq!(CancellationEvent::Handled, pane.on_ctrl_c());
@@ -539,6 +581,7 @@ mod tests {
             enhanced_keys_supported: false,
             placeholder_text: "Ask Codex to do anything".to_string(),
             disable_paste_burst: false,
+            vi_mode: false,
         });
 
         // Create an approval modal (active view).
@@ -570,6 +613,7 @@ mod tests {
             enhanced_keys_supported: false,
             placeholder_text: "Ask Codex to do anything".to_string(),
             disable_paste_burst: false,
+            vi_mode: false,
         });
 
         // Start a running task so the status indicator is active above the composer.
@@ -638,6 +682,7 @@ mod tests {
             enhanced_keys_supported: false,
             placeholder_text: "Ask Codex to do anything".to_string(),
             disable_paste_burst: false,
+            vi_mode: false,
         });
 
         // Begin a task: show initial status.
@@ -669,6 +714,7 @@ mod tests {
             enhanced_keys_supported: false,
             placeholder_text: "Ask Codex to do anything".to_string(),
             disable_paste_burst: false,
+            vi_mode: false,
         });
 
         // Activate spinner (status view replaces composer) with no live ring.
@@ -720,6 +766,7 @@ mod tests {
             enhanced_keys_supported: false,
             placeholder_text: "Ask Codex to do anything".to_string(),
             disable_paste_burst: false,
+            vi_mode: false,
         });
 
         pane.set_task_running(true);
@@ -757,4 +804,50 @@ mod tests {
             "expected composer with no padding: {only:?}"
         );
     }
+
+    #[test]
+    fn esc_in_vi_insert_mode_goes_to_the_composer_first() {
+        use codex_core::protocol::Op;
+        use crossterm::event::KeyCode;
+        use crossterm::event::KeyEvent;
+        use crossterm::event::KeyModifiers;
+
+        let (tx_raw, mut rx) = unbounded_channel::<AppEvent>();
+        let tx = AppEventSender::new(tx_raw);
+        let mut pane = BottomPane::new(BottomPaneParams {
+            app_event_tx: tx,
+            frame_requester: FrameRequester::test_dummy(),
+            has_input_focus: true,
+            enhanced_keys_supported: false,
+            placeholder_text: "Ask Codex to do anything".to_string(),
+            disable_paste_burst: false,
+            vi_mode: true,
+        });
+        assert!(pane.composer_is_empty());
+        assert!(pane.is_vi_insert_mode());
+
+        // With a task running, the first Esc leaves insert mode instead of
+        // interrupting; the second one interrupts.
+        pane.set_task_running(true);
+        pane.handle_key_event(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE));
+        assert!(!pane.is_vi_insert_mode());
+        while let Ok(ev) = rx.try_recv() {
+            assert!(
+                !matches!(ev, AppEvent::CodexOp(Op::Interrupt)),
+                "Esc in insert mode must not interrupt"
+            );
+        }
+        pane.handle_key_event(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE));
+        let mut interrupted = false;
+        while let Ok(ev) = rx.try_recv() {
+            interrupted |= matches!(ev, AppEvent::CodexOp(Op::Interrupt));
+        }
+        assert!(interrupted, "Esc in normal mode interrupts the task");
+
+        // Once idle, Esc in normal mode is left to backtracking.
+        pane.set_task_running(false);
+        assert!(pane.is_normal_backtrack_mode());
+        pane.handle_key_event(KeyEvent::new(KeyCode::Char('i'), KeyModifiers::NONE));
+        assert!(pane.is_vi_insert_mode());
+    }
 }
@@ -1,9 +1,7 @@
 use ratatui::buffer::Buffer;
 use ratatui::layout::Rect;
 use ratatui::prelude::Constraint;
-use ratatui::style::Color;
 use ratatui::style::Modifier;
-use ratatui::style::Style;
 use ratatui::style::Stylize;
 use ratatui::text::Line;
 use ratatui::text::Span;
@@ -16,6 +14,8 @@ use ratatui::widgets::Table;
 use ratatui::widgets::Widget;
 
 use super::scroll_state::ScrollState;
+use crate::theme::ThemeStylize;
+use crate::theme::theme;
 
 /// A generic representation of a display row for selection popups.
 pub(crate) struct GenericDisplayRow {
@@ -41,7 +41,7 @@ pub(crate) fn render_rows(
     let mut rows: Vec<Row> = Vec::new();
     if rows_all.is_empty() {
         rows.push(Row::new(vec![Cell::from(Line::from(
-            empty_message.dim().italic(),
+            empty_message.secondary().italic(),
         ))]));
     } else {
         let max_rows_from_area = area.height as usize;
@@ -93,16 +93,12 @@ pub(crate) fn render_rows(
 
             if let Some(desc) = description.as_ref() {
                 spans.push("  ".into());
-                spans.push(desc.clone().dim());
+                spans.push(desc.clone().secondary());
             }
 
             let mut cell = Cell::from(Line::from(spans));
             if Some(i) == state.selected_idx {
-                cell = cell.style(
-                    Style::default()
-                        .fg(Color::Cyan)
-                        .add_modifier(Modifier::BOLD),
-                );
+                cell = cell.style(theme().accent.add_modifier(Modifier::BOLD));
             }
             rows.push(Row::new(vec![cell]));
         }
@@ -113,7 +109,7 @@ pub(crate) fn render_rows(
             Block::default()
                 .borders(Borders::LEFT)
                 .border_type(BorderType::QuadrantOutside)
-                .border_style(Style::default().add_modifier(Modifier::DIM)),
+                .border_style(theme().secondary),
         )
         .widths([Constraint::Percentage(100)]);
 
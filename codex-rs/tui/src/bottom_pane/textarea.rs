@@ -1,9 +1,12 @@
+use crate::keymap::keymap;
+use crate::theme::theme;
+use codex_core::config_types::KeyAction;
 use crossterm::event::KeyCode;
 use crossterm::event::KeyEvent;
 use crossterm::event::KeyModifiers;
 use ratatui::buffer::Buffer;
 use ratatui::layout::Rect;
-use ratatui::style::Color;
+use ratatui::style::Modifier;
 use ratatui::style::Style;
 use ratatui::widgets::StatefulWidgetRef;
 use ratatui::widgets::WidgetRef;
@@ -14,6 +17,29 @@ use textwrap::Options;
 use unicode_segmentation::UnicodeSegmentation;
 use unicode_width::UnicodeWidthStr;
 
+mod vi;
+
+pub(crate) use vi::ViMode;
+
+/// Actions handled by the textarea, in order of precedence.
+const EDITOR_ACTIONS: [KeyAction; 15] = [
+    KeyAction::Newline,
+    KeyAction::CursorLeft,
+    KeyAction::CursorRight,
+    KeyAction::CursorUp,
+    KeyAction::CursorDown,
+    KeyAction::WordLeft,
+    KeyAction::WordRight,
+    KeyAction::LineStart,
+    KeyAction::LineEnd,
+    KeyAction::DeleteBackward,
+    KeyAction::DeleteForward,
+    KeyAction::DeleteWordBackward,
+    KeyAction::DeleteWordForward,
+    KeyAction::KillLineStart,
+    KeyAction::KillLineEnd,
+];
+
 #[derive(Debug, Clone)]
 struct TextElement {
     range: Range<usize>,
@@ -26,6 +52,8 @@ pub(crate) struct TextArea {
     wrap_cache: RefCell<Option<WrapCache>>,
     preferred_col: Option<usize>,
     elements: Vec<TextElement>,
+    /// Modal editing state, present when vi editing is enabled.
+    vi: Option<vi::ViState>,
 }
 
 #[derive(Debug, Clone)]
@@ -48,6 +76,7 @@ impl TextArea {
             wrap_cache: RefCell::new(None),
             preferred_col: None,
             elements: Vec::new(),
+            vi: None,
         }
     }
 
@@ -203,6 +232,12 @@ impl TextArea {
     }
 
     pub fn input(&mut self, event: KeyEvent) {
+        if let Some(mode) = self.vi_mode()
+            && (mode != ViMode::Insert || event.code == KeyCode::Esc)
+        {
+            self.vi_input(event);
+            return;
+        }
         match event {
             // Some terminals (or configurations) send Control key chords as
             // C0 control characters without reporting the CONTROL modifier.
@@ -218,191 +253,56 @@ impl TextArea {
                 code: KeyCode::Char(c),
                 // Insert plain characters (and Shift-modified). Do NOT insert when ALT is held,
                 // because many terminals map Option/Meta combos to ALT+<char> (e.g. ESC f/ESC b)
-                // for word navigation. Those are handled explicitly below.
+                // for word navigation. Those are handled by the key bindings below.
                 modifiers: KeyModifiers::NONE | KeyModifiers::SHIFT,
                 ..
             } => self.insert_str(&c.to_string()),
-            KeyEvent {
-                code: KeyCode::Char('j' | 'm'),
-                modifiers: KeyModifiers::CONTROL,
-                ..
-            }
-            | KeyEvent {
-                code: KeyCode::Enter,
-                ..
-            } => self.insert_str("\n"),
-            KeyEvent {
-                code: KeyCode::Char('h'),
-                modifiers,
-                ..
-            } if modifiers == (KeyModifiers::CONTROL | KeyModifiers::ALT) => {
-                self.delete_backward_word()
+            _ => match keymap().action(&EDITOR_ACTIONS, &event) {
+                Some(action) => self.perform(action, event.code),
+                // Enter that the composer did not take as Submit, with any
+                // modifiers, and Ctrl-M, which some terminals send for Enter.
+                None if matches!(
+                    event,
+                    KeyEvent {
+                        code: KeyCode::Enter,
+                        ..
+                    } | KeyEvent {
+                        code: KeyCode::Char('m'),
+                        modifiers: KeyModifiers::CONTROL,
+                        ..
+                    }
+                ) =>
+                {
+                    self.insert_str("\n")
+                }
+                None => {
+                    #[cfg(feature = "debug-logs")]
+                    tracing::debug!("Unhandled key event in TextArea: {:?}", event);
+                }
             },
-            KeyEvent {
-                code: KeyCode::Backspace,
-                modifiers: KeyModifiers::ALT,
-                ..
-            } => self.delete_backward_word(),
-            KeyEvent {
-                code: KeyCode::Backspace,
-                ..
-            }
-            | KeyEvent {
-                code: KeyCode::Char('h'),
-                modifiers: KeyModifiers::CONTROL,
-                ..
-            } => self.delete_backward(1),
-            KeyEvent {
-                code: KeyCode::Delete,
-                modifiers: KeyModifiers::ALT,
-                ..
-            }  => self.delete_forward_word(),
-            KeyEvent {
-                code: KeyCode::Delete,
-                ..
-            }
-            | KeyEvent {
-                code: KeyCode::Char('d'),
-                modifiers: KeyModifiers::CONTROL,
-                ..
-            } => self.delete_forward(1),
-
-            KeyEvent {
-                code: KeyCode::Char('w'),
-                modifiers: KeyModifiers::CONTROL,
-                ..
-            } => {
-                self.delete_backward_word();
-            }
-            // Meta-b -> move to beginning of previous word
-            // Meta-f -> move to end of next word
-            // Many terminals map Option (macOS) to Alt. Some send Alt|Shift, so match contains(ALT).
-            KeyEvent {
-                code: KeyCode::Char('b'),
-                modifiers: KeyModifiers::ALT,
-                ..
-            } => {
-                self.set_cursor(self.beginning_of_previous_word());
-            }
-            KeyEvent {
-                code: KeyCode::Char('f'),
-                modifiers: KeyModifiers::ALT,
-                ..
-            } => {
-                self.set_cursor(self.end_of_next_word());
-            }
-            KeyEvent {
-                code: KeyCode::Char('u'),
-                modifiers: KeyModifiers::CONTROL,
-                ..
-            } => {
-                self.kill_to_beginning_of_line();
-            }
-            KeyEvent {
-                code: KeyCode::Char('k'),
-                modifiers: KeyModifiers::CONTROL,
-                ..
-            } => {
-                self.kill_to_end_of_line();
-            }
-
-            // Cursor movement
-            KeyEvent {
-                code: KeyCode::Left,
-                modifiers: KeyModifiers::NONE,
-                ..
-            } => {
-                self.move_cursor_left();
-            }
-            KeyEvent {
-                code: KeyCode::Right,
-                modifiers: KeyModifiers::NONE,
-                ..
-            } => {
-                self.move_cursor_right();
-            }
-            KeyEvent {
-                code: KeyCode::Char('b'),
-                modifiers: KeyModifiers::CONTROL,
-                ..
-            } => {
-                self.move_cursor_left();
-            }
-            KeyEvent {
-                code: KeyCode::Char('f'),
-                modifiers: KeyModifiers::CONTROL,
-                ..
-            } => {
-                self.move_cursor_right();
-            }
-            // Some terminals send Alt+Arrow for word-wise movement:
-            // Option/Left -> Alt+Left (previous word start)
-            // Option/Right -> Alt+Right (next word end)
-            KeyEvent {
-                code: KeyCode::Left,
-                modifiers: KeyModifiers::ALT,
-                ..
-            }
-            | KeyEvent {
-                code: KeyCode::Left,
-                modifiers: KeyModifiers::CONTROL,
-                ..
-            } => {
-                self.set_cursor(self.beginning_of_previous_word());
-            }
-            KeyEvent {
-                code: KeyCode::Right,
-                modifiers: KeyModifiers::ALT,
-                ..
-            }
-            | KeyEvent {
-                code: KeyCode::Right,
-                modifiers: KeyModifiers::CONTROL,
-                ..
-            } => {
-                self.set_cursor(self.end_of_next_word());
-            }
-            KeyEvent {
-                code: KeyCode::Up, ..
-            } => {
-                self.move_cursor_up();
-            }
-            KeyEvent {
-                code: KeyCode::Down,
-                ..
-            } => {
-                self.move_cursor_down();
-            }
-            KeyEvent {
-                code: KeyCode::Home,
-                ..
-            } => {
-                self.move_cursor_to_beginning_of_line(false);
-            }
-            KeyEvent {
-                code: KeyCode::Char('a'),
-                modifiers: KeyModifiers::CONTROL,
-                ..
-            } => {
-                self.move_cursor_to_beginning_of_line(true);
-            }
+        }
+    }
 
-            KeyEvent {
-                code: KeyCode::End, ..
-            } => {
-                self.move_cursor_to_end_of_line(false);
-            }
-            KeyEvent {
-                code: KeyCode::Char('e'),
-                modifiers: KeyModifiers::CONTROL,
-                ..
-            } => {
-                self.move_cursor_to_end_of_line(true);
-            }
-            _o => {
-                #[cfg(feature = "debug-logs")]
-                tracing::debug!("Unhandled key event in TextArea: {:?}", _o);
-            }
+    fn perform(&mut self, action: KeyAction, code: KeyCode) {
+        match action {
+            KeyAction::Newline => self.insert_str("\n"),
+            KeyAction::CursorLeft => self.move_cursor_left(),
+            KeyAction::CursorRight => self.move_cursor_right(),
+            KeyAction::CursorUp => self.move_cursor_up(),
+            KeyAction::CursorDown => self.move_cursor_down(),
+            KeyAction::WordLeft => self.set_cursor(self.beginning_of_previous_word()),
+            KeyAction::WordRight => self.set_cursor(self.end_of_next_word()),
+            // Home and End stop at the line boundary; other keys continue to
+            // the previous or next line when pressed again, as in Emacs.
+            KeyAction::LineStart => self.move_cursor_to_beginning_of_line(code != KeyCode::Home),
+            KeyAction::LineEnd => self.move_cursor_to_end_of_line(code != KeyCode::End),
+            KeyAction::DeleteBackward => self.delete_backward(1),
+            KeyAction::DeleteForward => self.delete_forward(1),
+            KeyAction::DeleteWordBackward => self.delete_backward_word(),
+            KeyAction::DeleteWordForward => self.delete_forward_word(),
+            KeyAction::KillLineStart => self.kill_to_beginning_of_line(),
+            KeyAction::KillLineEnd => self.kill_to_end_of_line(),
+            _ => {}
         }
     }
 
@@ -941,9 +841,23 @@ impl TextArea {
                 }
                 let styled = &self.text[overlap_start..overlap_end];
                 let x_off = self.text[line_range.start..overlap_start].width() as u16;
-                let style = Style::default().fg(Color::Cyan);
+                let style = theme().accent;
                 buf.set_string(area.x + x_off, y, styled, style);
             }
+
+            // Highlight the vi visual selection.
+            if let Some(selection) = self.vi_selection() {
+                let start = selection.start.max(line_range.start);
+                let end = selection.end.min(line_range.end);
+                if start < end {
+                    let x_off = self.text[line_range.start..start].width() as u16;
+                    let width = self.text[start..end].width() as u16;
+                    buf.set_style(
+                        Rect::new(area.x + x_off, y, width, 1),
+                        Style::default().add_modifier(Modifier::REVERSED),
+                    );
+                }
+            }
         }
     }
 }
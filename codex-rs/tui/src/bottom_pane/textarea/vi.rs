@@ -0,0 +1,1228 @@
+//! Modal vi editing for [`TextArea`], enabled with `editing_mode = "vi"`.
+//!
+//! Keys typed in insert mode edit the text as usual. Esc switches to normal
+//! mode, where keys are commands: motions (`h`, `w`, `$`, `gg`, ...),
+//! operators applied to motions (`dw`, `c$`, `yy`), counts, undo and redo,
+//! and visual selections.
+
+use super::TextArea;
+use super::TextElement;
+use crossterm::event::KeyCode;
+use crossterm::event::KeyEvent;
+use crossterm::event::KeyEventKind;
+use crossterm::event::KeyModifiers;
+use std::ops::Range;
+
+/// Largest count a command accepts; bigger counts are clamped to it so that
+/// `99999999p` cannot allocate without bound.
+const MAX_COUNT: usize = 999;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ViMode {
+    Insert,
+    Normal,
+    /// Characters between the anchor and the cursor are selected.
+    Visual,
+    /// Whole lines between the anchor and the cursor are selected.
+    VisualLine,
+}
+
+impl ViMode {
+    /// Name of the mode as shown in the composer footer.
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            ViMode::Insert => "INSERT",
+            ViMode::Normal => "NORMAL",
+            ViMode::Visual => "VISUAL",
+            ViMode::VisualLine => "VISUAL LINE",
+        }
+    }
+
+    fn is_visual(self) -> bool {
+        matches!(self, ViMode::Visual | ViMode::VisualLine)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operator {
+    Delete,
+    Change,
+    Yank,
+}
+
+impl Operator {
+    fn from_key(key: char) -> Option<Self> {
+        match key {
+            'd' => Some(Operator::Delete),
+            'c' => Some(Operator::Change),
+            'y' => Some(Operator::Yank),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Motion {
+    Left,
+    Right,
+    Up,
+    Down,
+    WordForward,
+    WordBackward,
+    WordEnd,
+    LineStart,
+    FirstNonBlank,
+    LineEnd,
+    FirstLine,
+    LastLine,
+}
+
+/// How much text an operator applied to a motion covers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MotionKind {
+    /// Up to, but not including, the character the motion lands on.
+    Exclusive,
+    /// Up to and including the character the motion lands on.
+    Inclusive,
+    /// Every line from the cursor's line to the line the motion lands on.
+    Linewise,
+}
+
+impl Motion {
+    fn from_key(key: char) -> Option<Self> {
+        match key {
+            'h' => Some(Motion::Left),
+            'l' | ' ' => Some(Motion::Right),
+            'k' => Some(Motion::Up),
+            'j' => Some(Motion::Down),
+            'w' => Some(Motion::WordForward),
+            'b' => Some(Motion::WordBackward),
+            'e' => Some(Motion::WordEnd),
+            '0' => Some(Motion::LineStart),
+            '^' => Some(Motion::FirstNonBlank),
+            '$' => Some(Motion::LineEnd),
+            'G' => Some(Motion::LastLine),
+            _ => None,
+        }
+    }
+
+    fn kind(self) -> MotionKind {
+        match self {
+            Motion::Left
+            | Motion::Right
+            | Motion::WordForward
+            | Motion::WordBackward
+            | Motion::LineStart
+            | Motion::FirstNonBlank => MotionKind::Exclusive,
+            Motion::WordEnd | Motion::LineEnd => MotionKind::Inclusive,
+            Motion::Up | Motion::Down | Motion::FirstLine | Motion::LastLine => {
+                MotionKind::Linewise
+            }
+        }
+    }
+}
+
+/// Character classes that separate words: a word is a run of letters,
+/// digits and underscores, or a run of other non-blank characters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CharClass {
+    Blank,
+    Punctuation,
+    Word,
+}
+
+#[derive(Debug, Clone)]
+struct Snapshot {
+    text: String,
+    cursor: usize,
+    elements: Vec<TextElement>,
+}
+
+/// Text most recently deleted or yanked, put back with `p` and `P`.
+#[derive(Debug, Clone, Default)]
+struct Register {
+    text: String,
+    linewise: bool,
+}
+
+#[derive(Debug)]
+pub(super) struct ViState {
+    mode: ViMode,
+    /// Count typed before a command, operator or motion.
+    count: Option<usize>,
+    /// Operator waiting for its motion, with the count typed before it.
+    operator: Option<(Operator, Option<usize>)>,
+    /// `g` was typed and waits for the second key of the command.
+    pending_g: bool,
+    /// Start of the visual selection; the cursor is its other end.
+    anchor: usize,
+    register: Register,
+    undo: Vec<Snapshot>,
+    redo: Vec<Snapshot>,
+    /// The text before the current insert session. Everything typed in one
+    /// session is undone at once.
+    insert_start: Option<Snapshot>,
+}
+
+impl ViState {
+    fn clear_pending(&mut self) {
+        self.count = None;
+        self.operator = None;
+        self.pending_g = false;
+    }
+
+    fn record_undo(&mut self, snapshot: Snapshot) {
+        self.undo.push(snapshot);
+        self.redo.clear();
+    }
+}
+
+/// Product of the counts typed before an operator and before its motion.
+fn combine_counts(a: Option<usize>, b: Option<usize>) -> Option<usize> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.saturating_mul(b).min(MAX_COUNT)),
+        (a, b) => a.or(b),
+    }
+}
+
+impl TextArea {
+    /// Switches to modal vi editing, starting in insert mode.
+    pub(crate) fn enable_vi_mode(&mut self) {
+        self.vi = Some(ViState {
+            mode: ViMode::Insert,
+            count: None,
+            operator: None,
+            pending_g: false,
+            anchor: 0,
+            register: Register::default(),
+            undo: Vec::new(),
+            redo: Vec::new(),
+            insert_start: Some(self.snapshot()),
+        });
+    }
+
+    /// The current vi mode, or `None` when vi editing is not enabled.
+    pub(crate) fn vi_mode(&self) -> Option<ViMode> {
+        self.vi.as_ref().map(|vi| vi.mode)
+    }
+
+    /// Returns to insert mode and forgets the undo history, e.g. after the
+    /// message was submitted.
+    pub(crate) fn reset_vi_mode(&mut self) {
+        if self.vi.is_some() {
+            let register = self.vi.take().map(|vi| vi.register).unwrap_or_default();
+            self.enable_vi_mode();
+            if let Some(vi) = self.vi.as_mut() {
+                vi.register = register;
+            }
+        }
+    }
+
+    /// The selected range of text in visual modes.
+    pub(super) fn vi_selection(&self) -> Option<Range<usize>> {
+        let vi = self.vi.as_ref()?;
+        self.selection(vi)
+    }
+
+    fn selection(&self, vi: &ViState) -> Option<Range<usize>> {
+        let start = vi.anchor.min(self.cursor_pos);
+        let end = vi.anchor.max(self.cursor_pos);
+        match vi.mode {
+            ViMode::Visual => Some(start..self.next_atomic_boundary(end)),
+            ViMode::VisualLine => Some(self.beginning_of_line(start)..self.end_of_line(end)),
+            ViMode::Insert | ViMode::Normal => None,
+        }
+    }
+
+    pub(super) fn vi_input(&mut self, event: KeyEvent) {
+        if event.kind == KeyEventKind::Release {
+            return;
+        }
+        let Some(mut vi) = self.vi.take() else {
+            return;
+        };
+        match vi.mode {
+            ViMode::Insert => {
+                if event.code == KeyCode::Esc {
+                    self.vi_leave_insert(&mut vi);
+                }
+            }
+            ViMode::Normal | ViMode::Visual | ViMode::VisualLine => {
+                self.vi_command(&mut vi, event);
+                if vi.mode != ViMode::Insert {
+                    self.vi_clamp_cursor();
+                }
+            }
+        }
+        self.vi = Some(vi);
+    }
+
+    fn vi_command(&mut self, vi: &mut ViState, event: KeyEvent) {
+        if event.modifiers.contains(KeyModifiers::CONTROL) {
+            if event.code == KeyCode::Char('r') {
+                let count = vi.count.unwrap_or(1);
+                self.vi_redo(vi, count);
+            }
+            vi.clear_pending();
+            return;
+        }
+        if event.modifiers.contains(KeyModifiers::ALT) {
+            return;
+        }
+        let key = match event.code {
+            KeyCode::Char(c) => c,
+            KeyCode::Left | KeyCode::Backspace => 'h',
+            KeyCode::Right => 'l',
+            KeyCode::Up => 'k',
+            KeyCode::Down => 'j',
+            KeyCode::Home => '0',
+            KeyCode::End => '$',
+            KeyCode::Delete => 'x',
+            KeyCode::Esc => {
+                if vi.mode.is_visual() {
+                    vi.mode = ViMode::Normal;
+                }
+                vi.clear_pending();
+                return;
+            }
+            _ => return,
+        };
+
+        if vi.pending_g {
+            vi.pending_g = false;
+            if key == 'g' {
+                self.vi_motion(vi, Motion::FirstLine);
+            } else {
+                vi.clear_pending();
+            }
+            return;
+        }
+        if let Some(digit) = key.to_digit(10)
+            && (digit != 0 || vi.count.is_some())
+        {
+            let count = vi.count.unwrap_or(0);
+            vi.count = Some((count * 10 + digit as usize).min(MAX_COUNT));
+            return;
+        }
+        if let Some(motion) = Motion::from_key(key) {
+            self.vi_motion(vi, motion);
+            return;
+        }
+        if key == 'g' {
+            vi.pending_g = true;
+            return;
+        }
+
+        if vi.mode.is_visual() {
+            self.vi_visual_command(vi, key);
+            return;
+        }
+
+        if let Some(op) = Operator::from_key(key) {
+            match vi.operator.take() {
+                // A doubled operator (`dd`, `cc`, `yy`) acts on whole lines.
+                Some((pending, count)) if pending == op => {
+                    let count = combine_counts(count, vi.count.take());
+                    self.vi_operate_lines(vi, op, count.unwrap_or(1));
+                }
+                Some(_) => vi.clear_pending(),
+                None => vi.operator = Some((op, vi.count.take())),
+            }
+            return;
+        }
+        if vi.operator.is_some() {
+            vi.clear_pending();
+            return;
+        }
+
+        let count = vi.count.take();
+        let bol = self.beginning_of_current_line();
+        let eol = self.end_of_current_line();
+        match key {
+            'i' => self.vi_enter_insert(vi, self.snapshot()),
+            'a' => {
+                let snapshot = self.snapshot();
+                if self.cursor_pos < eol {
+                    self.cursor_pos = self.next_atomic_boundary(self.cursor_pos);
+                }
+                self.vi_enter_insert(vi, snapshot);
+            }
+            'I' => {
+                let snapshot = self.snapshot();
+                self.set_cursor(self.first_non_blank(bol));
+                self.vi_enter_insert(vi, snapshot);
+            }
+            'A' => {
+                let snapshot = self.snapshot();
+                self.set_cursor(eol);
+                self.vi_enter_insert(vi, snapshot);
+            }
+            'o' => {
+                let snapshot = self.snapshot();
+                self.insert_str_at(eol, "\n");
+                self.set_cursor(eol + 1);
+                self.vi_enter_insert(vi, snapshot);
+            }
+            'O' => {
+                let snapshot = self.snapshot();
+                self.insert_str_at(bol, "\n");
+                self.set_cursor(bol);
+                self.vi_enter_insert(vi, snapshot);
+            }
+            'x' => self.vi_apply(vi, Some(Operator::Delete), Motion::Right, count),
+            'X' => self.vi_apply(vi, Some(Operator::Delete), Motion::Left, count),
+            's' => self.vi_apply(vi, Some(Operator::Change), Motion::Right, count),
+            'D' => self.vi_apply(vi, Some(Operator::Delete), Motion::LineEnd, count),
+            'C' => self.vi_apply(vi, Some(Operator::Change), Motion::LineEnd, count),
+            'S' => self.vi_operate_lines(vi, Operator::Change, count.unwrap_or(1)),
+            'Y' => self.vi_operate_lines(vi, Operator::Yank, count.unwrap_or(1)),
+            'p' => self.vi_put(vi, true, count.unwrap_or(1)),
+            'P' => self.vi_put(vi, false, count.unwrap_or(1)),
+            'u' => self.vi_undo(vi, count.unwrap_or(1)),
+            'v' => {
+                vi.anchor = self.cursor_pos;
+                vi.mode = ViMode::Visual;
+            }
+            'V' => {
+                vi.anchor = self.cursor_pos;
+                vi.mode = ViMode::VisualLine;
+            }
+            _ => {}
+        }
+    }
+
+    fn vi_visual_command(&mut self, vi: &mut ViState, key: char) {
+        vi.count = None;
+        let op = match key {
+            'd' | 'x' => Operator::Delete,
+            'c' | 's' => Operator::Change,
+            'y' => Operator::Yank,
+            'o' => {
+                std::mem::swap(&mut vi.anchor, &mut self.cursor_pos);
+                return;
+            }
+            'v' | 'V' => {
+                let mode = if key == 'v' {
+                    ViMode::Visual
+                } else {
+                    ViMode::VisualLine
+                };
+                vi.mode = if vi.mode == mode {
+                    ViMode::Normal
+                } else {
+                    mode
+                };
+                return;
+            }
+            _ => return,
+        };
+        let Some(range) = self.selection(vi) else {
+            return;
+        };
+        let linewise = vi.mode == ViMode::VisualLine;
+        vi.mode = ViMode::Normal;
+        self.vi_operate(vi, op, range, linewise);
+    }
+
+    /// Moves the cursor by `motion`, or applies the pending operator to the
+    /// text it covers.
+    fn vi_motion(&mut self, vi: &mut ViState, motion: Motion) {
+        let (op, op_count) = match vi.operator.take() {
+            Some((op, count)) => (Some(op), count),
+            None => (None, None),
+        };
+        let count = combine_counts(op_count, vi.count.take());
+        self.vi_apply(vi, op, motion, count);
+    }
+
+    fn vi_apply(
+        &mut self,
+        vi: &mut ViState,
+        op: Option<Operator>,
+        motion: Motion,
+        count: Option<usize>,
+    ) {
+        let from = self.cursor_pos;
+        // `cw` on a word changes only up to the end of that word, like `ce`.
+        if op == Some(Operator::Change)
+            && motion == Motion::WordForward
+            && self.char_class_at(from) != CharClass::Blank
+        {
+            let mut to = self.word_end(from, true);
+            for _ in 1..count.unwrap_or(1) {
+                let next = self.word_end(to, false);
+                if next == to {
+                    break;
+                }
+                to = next;
+            }
+            let range = from..self.next_atomic_boundary(to);
+            self.vi_operate(vi, Operator::Change, range, false);
+            return;
+        }
+        self.vi_move(motion, count);
+        let Some(op) = op else {
+            return;
+        };
+        let to = self.cursor_pos;
+        self.cursor_pos = from;
+        let start = from.min(to);
+        let end = from.max(to);
+        match motion.kind() {
+            MotionKind::Linewise => {
+                let range = self.beginning_of_line(start)..self.end_of_line(end);
+                self.vi_operate(vi, op, range, true);
+            }
+            MotionKind::Inclusive => {
+                let end = if end < self.end_of_line(end) {
+                    self.next_atomic_boundary(end)
+                } else {
+                    end
+                };
+                self.vi_operate(vi, op, start..end, false);
+            }
+            MotionKind::Exclusive => {
+                let mut end = end;
+                // `dw` on the last word of a line stops at the end of the line
+                // instead of joining it with the next one.
+                let end_bol = self.beginning_of_line(end);
+                if motion == Motion::WordForward
+                    && end_bol > start
+                    && self.text[end_bol..end].trim().is_empty()
+                {
+                    end = end_bol - 1;
+                }
+                self.vi_operate(vi, op, start..end, false);
+            }
+        }
+    }
+
+    fn vi_move(&mut self, motion: Motion, count: Option<usize>) {
+        let n = count.unwrap_or(1);
+        let bol = self.beginning_of_current_line();
+        let eol = self.end_of_current_line();
+        let mut pos = self.cursor_pos;
+        match motion {
+            Motion::Left => {
+                for _ in 0..n {
+                    if pos <= bol {
+                        break;
+                    }
+                    pos = self.prev_atomic_boundary(pos);
+                }
+            }
+            Motion::Right => {
+                for _ in 0..n {
+                    if pos >= eol {
+                        break;
+                    }
+                    pos = self.next_atomic_boundary(pos);
+                }
+            }
+            Motion::Up | Motion::Down => {
+                let col = self
+                    .preferred_col
+                    .unwrap_or_else(|| self.current_display_col());
+                let mut line = bol;
+                for _ in 0..n {
+                    if motion == Motion::Down {
+                        let end = self.end_of_line(line);
+                        if end >= self.text.len() {
+                            break;
+                        }
+                        line = end + 1;
+                    } else {
+                        if line == 0 {
+                            break;
+                        }
+                        line = self.beginning_of_line(line - 1);
+                    }
+                }
+                let line_end = self.end_of_line(line);
+                self.move_to_display_col_on_line(line, line_end, col);
+                self.preferred_col = Some(col);
+                return;
+            }
+            Motion::WordForward => {
+                for _ in 0..n {
+                    let next = self.word_forward(pos);
+                    if next == pos {
+                        break;
+                    }
+                    pos = next;
+                }
+            }
+            Motion::WordBackward => {
+                for _ in 0..n {
+                    let next = self.word_backward(pos);
+                    if next == pos {
+                        break;
+                    }
+                    pos = next;
+                }
+            }
+            Motion::WordEnd => {
+                for _ in 0..n {
+                    let next = self.word_end(pos, false);
+                    if next == pos {
+                        break;
+                    }
+                    pos = next;
+                }
+            }
+            Motion::LineStart => pos = bol,
+            Motion::FirstNonBlank => pos = self.first_non_blank(bol),
+            Motion::LineEnd => {
+                let mut end = eol;
+                for _ in 1..n {
+                    if end >= self.text.len() {
+                        break;
+                    }
+                    end = self.end_of_line(end + 1);
+                }
+                self.set_cursor(end);
+                // Stay at the end of lines when moving up or down from here.
+                self.preferred_col = Some(usize::MAX);
+                return;
+            }
+            Motion::FirstLine | Motion::LastLine => {
+                let line = match (motion, count) {
+                    (_, Some(line)) => line,
+                    (Motion::FirstLine, None) => 1,
+                    _ => usize::MAX,
+                };
+                let mut start = 0;
+                for _ in 1..line {
+                    let end = self.end_of_line(start);
+                    if end >= self.text.len() {
+                        break;
+                    }
+                    start = end + 1;
+                }
+                pos = self.first_non_blank(start);
+            }
+        }
+        self.set_cursor(pos);
+    }
+
+    /// Applies `op` to `range`, which spans the contents of whole lines when
+    /// `linewise` is set.
+    fn vi_operate(&mut self, vi: &mut ViState, op: Operator, range: Range<usize>, linewise: bool) {
+        let range = self.expand_range_to_element_boundaries(range);
+        vi.register = Register {
+            text: self.text[range.clone()].to_string(),
+            linewise,
+        };
+        match op {
+            Operator::Yank => {
+                if !linewise {
+                    self.set_cursor(range.start);
+                }
+            }
+            Operator::Delete => {
+                if range.is_empty() && !linewise {
+                    return;
+                }
+                vi.record_undo(self.snapshot());
+                if linewise {
+                    // Remove the line break after the lines, or before them
+                    // when they are at the end of the text.
+                    let range = if range.end < self.text.len() {
+                        range.start..range.end + 1
+                    } else {
+                        range.start.saturating_sub(1)..range.end
+                    };
+                    self.replace_range(range.clone(), "");
+                    let bol = self.beginning_of_line(range.start.min(self.text.len()));
+                    self.set_cursor(self.first_non_blank(bol));
+                } else {
+                    self.replace_range(range.clone(), "");
+                    self.set_cursor(range.start);
+                }
+            }
+            Operator::Change => {
+                let snapshot = self.snapshot();
+                self.replace_range(range.clone(), "");
+                self.set_cursor(range.start);
+                self.vi_enter_insert(vi, snapshot);
+            }
+        }
+    }
+
+    /// Applies `op` to `count` lines starting at the cursor's line.
+    fn vi_operate_lines(&mut self, vi: &mut ViState, op: Operator, count: usize) {
+        let start = self.beginning_of_current_line();
+        let mut end = self.end_of_current_line();
+        for _ in 1..count {
+            if end >= self.text.len() {
+                break;
+            }
+            end = self.end_of_line(end + 1);
+        }
+        self.vi_operate(vi, op, start..end, true);
+    }
+
+    /// Puts the register after (`p`) or before (`P`) the cursor.
+    fn vi_put(&mut self, vi: &mut ViState, after: bool, count: usize) {
+        let Register { text, linewise } = vi.register.clone();
+        if text.is_empty() {
+            return;
+        }
+        vi.record_undo(self.snapshot());
+        if linewise {
+            let lines = vec![text; count].join("\n");
+            let line_start = if after {
+                let eol = self.end_of_current_line();
+                if eol < self.text.len() {
+                    self.insert_str_at(eol + 1, &format!("{lines}\n"));
+                } else {
+                    self.insert_str_at(eol, &format!("\n{lines}"));
+                }
+                eol + 1
+            } else {
+                let bol = self.beginning_of_current_line();
+                self.insert_str_at(bol, &format!("{lines}\n"));
+                bol
+            };
+            self.set_cursor(self.first_non_blank(line_start));
+        } else {
+            let text = text.repeat(count);
+            let pos = if after && self.cursor_pos < self.end_of_current_line() {
+                self.next_atomic_boundary(self.cursor_pos)
+            } else {
+                self.cursor_pos
+            };
+            let pos = self.clamp_pos_for_insertion(pos);
+            self.insert_str_at(pos, &text);
+            // The cursor ends on the last character that was put.
+            self.set_cursor(self.prev_atomic_boundary(pos + text.len()));
+        }
+    }
+
+    fn vi_enter_insert(&mut self, vi: &mut ViState, snapshot: Snapshot) {
+        vi.mode = ViMode::Insert;
+        vi.insert_start = Some(snapshot);
+        vi.clear_pending();
+    }
+
+    fn vi_leave_insert(&mut self, vi: &mut ViState) {
+        vi.mode = ViMode::Normal;
+        if let Some(start) = vi.insert_start.take()
+            && start.text != self.text
+        {
+            vi.record_undo(start);
+        }
+        // Leaving insert mode puts the cursor on the last inserted character.
+        if self.cursor_pos > self.beginning_of_current_line() {
+            self.cursor_pos = self.prev_atomic_boundary(self.cursor_pos);
+        }
+        self.preferred_col = None;
+    }
+
+    fn vi_undo(&mut self, vi: &mut ViState, count: usize) {
+        for _ in 0..count {
+            let Some(snapshot) = vi.undo.pop() else {
+                break;
+            };
+            vi.redo.push(self.snapshot());
+            self.restore(snapshot);
+        }
+    }
+
+    fn vi_redo(&mut self, vi: &mut ViState, count: usize) {
+        for _ in 0..count {
+            let Some(snapshot) = vi.redo.pop() else {
+                break;
+            };
+            vi.undo.push(self.snapshot());
+            self.restore(snapshot);
+        }
+    }
+
+    /// Keeps the cursor on a character in normal and visual mode: it may
+    /// only sit past the end of a line when the line is empty.
+    fn vi_clamp_cursor(&mut self) {
+        let bol = self.beginning_of_current_line();
+        let eol = self.end_of_current_line();
+        if self.cursor_pos >= eol && eol > bol {
+            self.cursor_pos = self.prev_atomic_boundary(eol);
+        }
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            text: self.text.clone(),
+            cursor: self.cursor_pos,
+            elements: self.elements.clone(),
+        }
+    }
+
+    fn restore(&mut self, snapshot: Snapshot) {
+        self.text = snapshot.text;
+        self.elements = snapshot.elements;
+        self.wrap_cache.replace(None);
+        self.set_cursor(snapshot.cursor);
+    }
+
+    fn first_non_blank(&self, bol: usize) -> usize {
+        let eol = self.end_of_line(bol);
+        self.text[bol..eol]
+            .find(|c: char| !c.is_whitespace())
+            .map_or(eol, |i| bol + i)
+    }
+
+    fn char_class_at(&self, pos: usize) -> CharClass {
+        if self.elements.iter().any(|e| e.range.start == pos) {
+            return CharClass::Word;
+        }
+        match self.text[pos..].chars().next() {
+            None => CharClass::Blank,
+            Some(c) if c.is_whitespace() => CharClass::Blank,
+            Some(c) if c.is_alphanumeric() || c == '_' => CharClass::Word,
+            Some(_) => CharClass::Punctuation,
+        }
+    }
+
+    /// An empty line counts as a word of its own.
+    fn is_empty_line(&self, pos: usize) -> bool {
+        self.text[pos..].starts_with('\n') && pos == self.beginning_of_line(pos)
+    }
+
+    /// Start of the next word after `pos` (`w`).
+    fn word_forward(&self, pos: usize) -> usize {
+        let len = self.text.len();
+        let class = self.char_class_at(pos);
+        let mut p = pos;
+        if class != CharClass::Blank {
+            while p < len && self.char_class_at(p) == class {
+                p = self.next_atomic_boundary(p);
+            }
+        }
+        while p < len && self.char_class_at(p) == CharClass::Blank {
+            if p > pos && self.is_empty_line(p) {
+                break;
+            }
+            p = self.next_atomic_boundary(p);
+        }
+        p
+    }
+
+    /// Start of the word before `pos` (`b`).
+    fn word_backward(&self, pos: usize) -> usize {
+        if pos == 0 {
+            return 0;
+        }
+        let mut p = self.prev_atomic_boundary(pos);
+        while p > 0 && self.char_class_at(p) == CharClass::Blank && !self.is_empty_line(p) {
+            p = self.prev_atomic_boundary(p);
+        }
+        let class = self.char_class_at(p);
+        if class == CharClass::Blank {
+            return p;
+        }
+        while p > 0 {
+            let prev = self.prev_atomic_boundary(p);
+            if self.char_class_at(prev) != class {
+                break;
+            }
+            p = prev;
+        }
+        p
+    }
+
+    /// Last character of the word at (`stay` set) or after `pos` (`e`).
+    fn word_end(&self, pos: usize, stay: bool) -> usize {
+        let len = self.text.len();
+        let mut p = if stay {
+            pos
+        } else {
+            self.next_atomic_boundary(pos)
+        };
+        while p < len && self.char_class_at(p) == CharClass::Blank {
+            p = self.next_atomic_boundary(p);
+        }
+        if p >= len {
+            return self.prev_atomic_boundary(len);
+        }
+        let class = self.char_class_at(p);
+        loop {
+            let next = self.next_atomic_boundary(p);
+            if next >= len || self.char_class_at(next) != class {
+                return p;
+            }
+            p = next;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    /// A textarea in normal mode with the cursor at the `|` in `text`.
+    fn normal(text: &str) -> TextArea {
+        let cursor = text.find('|').unwrap_or(0);
+        let mut t = TextArea::new();
+        t.insert_str(&text.replacen('|', "", 1));
+        t.set_cursor(cursor);
+        t.enable_vi_mode();
+        if let Some(vi) = t.vi.as_mut() {
+            vi.mode = ViMode::Normal;
+        }
+        t
+    }
+
+    /// The text with a `|` at the cursor.
+    fn state(t: &TextArea) -> String {
+        let mut text = t.text().to_string();
+        text.insert(t.cursor(), '|');
+        text
+    }
+
+    fn feed(t: &mut TextArea, keys: &str) {
+        for c in keys.chars() {
+            t.input(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE));
+        }
+    }
+
+    fn esc(t: &mut TextArea) {
+        t.input(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE));
+    }
+
+    fn after(text: &str, keys: &str) -> String {
+        let mut t = normal(text);
+        feed(&mut t, keys);
+        state(&t)
+    }
+
+    #[test]
+    fn starts_in_insert_mode_and_esc_enters_normal_mode() {
+        let mut t = TextArea::new();
+        assert_eq!(t.vi_mode(), None);
+        t.enable_vi_mode();
+        assert_eq!(t.vi_mode(), Some(ViMode::Insert));
+        feed(&mut t, "hello");
+        assert_eq!(state(&t), "hello|");
+        esc(&mut t);
+        assert_eq!(t.vi_mode(), Some(ViMode::Normal));
+        assert_eq!(state(&t), "hell|o");
+        // Keys are commands now and do not insert text.
+        feed(&mut t, "hx");
+        assert_eq!(state(&t), "hel|o");
+    }
+
+    #[test]
+    fn character_and_line_motions() {
+        assert_eq!(after("h|ello", "l"), "he|llo");
+        assert_eq!(after("h|ello", "3l"), "hell|o");
+        assert_eq!(after("h|ello", "9l"), "hell|o");
+        assert_eq!(after("hel|lo", "2h"), "h|ello");
+        assert_eq!(after("ab\ncd|e", "9h"), "ab\n|cde");
+        assert_eq!(after("  he|llo", "0"), "|  hello");
+        assert_eq!(after("  he|llo", "^"), "  |hello");
+        assert_eq!(after("|hello\nworld", "$"), "hell|o\nworld");
+        assert_eq!(after("|hello\nworld", "2$"), "hello\nworl|d");
+    }
+
+    #[test]
+    fn vertical_motions_keep_the_column() {
+        assert_eq!(after("ab|cd\nx\nefgh", "j"), "abcd\n|x\nefgh");
+        assert_eq!(after("ab|cd\nx\nefgh", "jj"), "abcd\nx\nef|gh");
+        assert_eq!(after("ab|cd\nx\nefgh", "2j"), "abcd\nx\nef|gh");
+        assert_eq!(after("abcd\nx\nef|gh", "2k"), "ab|cd\nx\nefgh");
+        assert_eq!(after("ab|cd\nefgh", "k"), "ab|cd\nefgh");
+        assert_eq!(after("a|bcd\nx\nefgh", "$jj"), "abcd\nx\nefg|h");
+    }
+
+    #[test]
+    fn word_motions() {
+        assert_eq!(after("|foo bar.baz", "w"), "foo |bar.baz");
+        assert_eq!(after("|foo bar.baz", "2w"), "foo bar|.baz");
+        assert_eq!(after("|foo bar.baz", "3w"), "foo bar.|baz");
+        assert_eq!(after("foo |bar\n  baz", "w"), "foo bar\n  |baz");
+        assert_eq!(after("foo |bar\n\nbaz", "w"), "foo bar\n|\nbaz");
+        assert_eq!(after("foo bar.ba|z", "b"), "foo bar.|baz");
+        assert_eq!(after("foo bar.ba|z", "3b"), "foo |bar.baz");
+        assert_eq!(after("foo\n  |bar", "b"), "|foo\n  bar");
+        assert_eq!(after("|foo bar", "e"), "fo|o bar");
+        assert_eq!(after("fo|o bar", "e"), "foo ba|r");
+        assert_eq!(after("|a_1-b c", "2e"), "a_1|-b c");
+        assert_eq!(after("foo |bar", "w"), "foo ba|r");
+    }
+
+    #[test]
+    fn file_motions_take_line_numbers() {
+        let text = "one\n  two\nthree|";
+        assert_eq!(after(text, "gg"), "|one\n  two\nthree");
+        assert_eq!(after("|one\n  two\nthree", "G"), "one\n  two\n|three");
+        assert_eq!(after("|one\n  two\nthree", "2G"), "one\n  |two\nthree");
+        assert_eq!(after(text, "2gg"), "one\n  |two\nthree");
+        // An unknown key after `g` cancels it.
+        assert_eq!(after(text, "gxgg"), "|one\n  two\nthree");
+    }
+
+    #[test]
+    fn delete_with_motions() {
+        assert_eq!(after("|foo bar baz", "dw"), "|bar baz");
+        assert_eq!(after("|foo bar baz", "d2w"), "|baz");
+        assert_eq!(after("|foo bar baz", "2dw"), "|baz");
+        assert_eq!(after("foo |bar\nbaz", "dw"), "foo| \nbaz");
+        assert_eq!(after("|foo bar", "de"), "| bar");
+        assert_eq!(after("foo b|ar", "db"), "foo |ar");
+        assert_eq!(after("foo b|ar", "d$"), "foo |b");
+        assert_eq!(after("foo b|ar", "D"), "foo |b");
+        assert_eq!(after("foo b|ar", "d0"), "|ar");
+        assert_eq!(after("|abc", "x"), "|bc");
+        assert_eq!(after("a|bcd", "2x"), "a|d");
+        assert_eq!(after("ab|c", "x"), "a|b");
+        assert_eq!(after("ab|c", "X"), "a|c");
+        // An operator followed by something that is not a motion is cancelled.
+        assert_eq!(after("|foo bar", "dzw"), "foo |bar");
+        let mut t = normal("|foo bar");
+        feed(&mut t, "d");
+        esc(&mut t);
+        feed(&mut t, "w");
+        assert_eq!(state(&t), "foo |bar");
+    }
+
+    #[test]
+    fn linewise_delete() {
+        assert_eq!(after("one\nt|wo\nthree", "dd"), "one\n|three");
+        assert_eq!(after("one\ntwo\nthr|ee", "dd"), "one\n|two");
+        assert_eq!(after("|one\ntwo\nthree", "2dd"), "|three");
+        assert_eq!(after("|one\ntwo\nthree", "d2d"), "|three");
+        assert_eq!(after("one\n  t|wo\nthree", "dj"), "|one");
+        assert_eq!(after("one\n  t|wo\nthree", "dk"), "|three");
+        assert_eq!(after("one\ntwo\nth|ree", "dgg"), "|");
+        assert_eq!(after("one\nt|wo\nthree", "dG"), "|one");
+    }
+
+    #[test]
+    fn change_enters_insert_mode() {
+        let mut t = normal("|foo bar");
+        feed(&mut t, "cw");
+        assert_eq!(t.vi_mode(), Some(ViMode::Insert));
+        assert_eq!(state(&t), "| bar");
+        feed(&mut t, "baz");
+        esc(&mut t);
+        assert_eq!(state(&t), "ba|z bar");
+
+        assert_eq!(after("|a bar", "cw"), "| bar");
+        assert_eq!(after("|foo bar baz", "c2w"), "| baz");
+        assert_eq!(after("foo b|ar", "C"), "foo b|");
+        assert_eq!(after("foo b|ar", "c$"), "foo b|");
+        assert_eq!(after("one\n  t|wo\nthree", "cc"), "one\n|\nthree");
+        assert_eq!(after("one\nt|wo\nthree", "S"), "one\n|\nthree");
+        assert_eq!(after("|abc", "s"), "|bc");
+    }
+
+    #[test]
+    fn insert_commands() {
+        let cases = [
+            ("a|bc", "i", "a|bc"),
+            ("a|bc", "a", "ab|c"),
+            ("  a|bc", "I", "  |abc"),
+            ("a|bc\nd", "A", "abc|\nd"),
+            ("a|bc\nd", "o", "abc\n|\nd"),
+            ("x\na|bc", "O", "x\n|\nabc"),
+        ];
+        for (text, keys, expected) in cases {
+            let mut t = normal(text);
+            feed(&mut t, keys);
+            assert_eq!(t.vi_mode(), Some(ViMode::Insert), "{keys}");
+            assert_eq!(state(&t), expected, "{keys}");
+        }
+    }
+
+    #[test]
+    fn yank_and_put() {
+        assert_eq!(after("|foo bar", "ywP"), "foo| foo bar");
+        assert_eq!(after("|foo bar", "yw$p"), "foo barfoo| ");
+        assert_eq!(after("foo |bar", "ybp"), "ffoo| oo bar");
+        assert_eq!(after("|ab", "x2p"), "ba|a");
+        assert_eq!(after("o|ne\ntwo", "yyjp"), "one\ntwo\n|one");
+        assert_eq!(after("o|ne\ntwo", "yyjP"), "one\n|one\ntwo");
+        assert_eq!(after("o|ne\ntwo", "ddp"), "two\n|one");
+        assert_eq!(after("|one\ntwo", "Yp"), "one\n|one\ntwo");
+        assert_eq!(after("o|ne\ntwo", "yj2P"), "|one\ntwo\none\ntwo\none\ntwo");
+        // Nothing is put before anything was yanked.
+        assert_eq!(after("a|b", "p"), "a|b");
+    }
+
+    #[test]
+    fn yank_keeps_the_text() {
+        let mut t = normal("foo |bar");
+        feed(&mut t, "yb");
+        assert_eq!(state(&t), "|foo bar");
+        assert_eq!(t.vi_mode(), Some(ViMode::Normal));
+    }
+
+    #[test]
+    fn undo_and_redo() {
+        let mut t = normal("|one two three");
+        feed(&mut t, "dwdw");
+        assert_eq!(state(&t), "|three");
+        feed(&mut t, "u");
+        assert_eq!(state(&t), "|two three");
+        feed(&mut t, "u");
+        assert_eq!(state(&t), "|one two three");
+        feed(&mut t, "u");
+        assert_eq!(state(&t), "|one two three");
+        t.input(KeyEvent::new(KeyCode::Char('r'), KeyModifiers::CONTROL));
+        assert_eq!(state(&t), "|two three");
+        t.input(KeyEvent::new(KeyCode::Char('r'), KeyModifiers::CONTROL));
+        assert_eq!(state(&t), "|three");
+
+        feed(&mut t, "2u");
+        assert_eq!(state(&t), "|one two three");
+        // A new change forgets what could be redone.
+        feed(&mut t, "x");
+        t.input(KeyEvent::new(KeyCode::Char('r'), KeyModifiers::CONTROL));
+        assert_eq!(state(&t), "|ne two three");
+    }
+
+    #[test]
+    fn undo_treats_an_insert_session_as_one_change() {
+        let mut t = TextArea::new();
+        t.enable_vi_mode();
+        feed(&mut t, "hello");
+        esc(&mut t);
+        feed(&mut t, "A world");
+        esc(&mut t);
+        assert_eq!(state(&t), "hello worl|d");
+        feed(&mut t, "u");
+        assert_eq!(state(&t), "hell|o");
+        feed(&mut t, "u");
+        assert_eq!(state(&t), "|");
+
+        // Entering and leaving insert mode without typing is not a change.
+        let mut t = normal("a|b");
+        feed(&mut t, "x");
+        feed(&mut t, "i");
+        esc(&mut t);
+        feed(&mut t, "u");
+        assert_eq!(state(&t), "a|b");
+    }
+
+    #[test]
+    fn undo_restores_elements() {
+        let mut t = TextArea::new();
+        t.enable_vi_mode();
+        t.insert_element("[Pasted Content 100 chars]");
+        esc(&mut t);
+        feed(&mut t, "0dw");
+        assert_eq!(t.text(), "");
+        feed(&mut t, "u");
+        assert_eq!(t.text(), "[Pasted Content 100 chars]");
+        assert_eq!(t.elements.len(), 1);
+    }
+
+    #[test]
+    fn counts() {
+        assert_eq!(after("|abcdefghijklm", "10l"), "abcdefghij|klm");
+        assert_eq!(after("|a b c d e f", "2d2w"), "|e f");
+        // `0` is a motion unless it continues a count.
+        assert_eq!(after("ab|c", "0"), "|abc");
+    }
+
+    #[test]
+    fn huge_counts_are_capped() {
+        let mut t = normal("|x");
+        feed(&mut t, "yl99999999999999999999p");
+        assert_eq!(t.text().len(), 1 + MAX_COUNT);
+        let mut t = normal("|x");
+        feed(&mut t, "yy99999999999999999999P");
+        assert_eq!(t.text().lines().count(), 1 + MAX_COUNT);
+        assert_eq!(after("|a", "99999d99999l"), "|");
+    }
+
+    #[test]
+    fn word_motions_stop_when_the_cursor_stops_moving() {
+        assert_eq!(after("|foo bar", "99999999999999999999w"), "foo ba|r");
+        assert_eq!(after("foo b|ar", "99999999999999999999b"), "|foo bar");
+        assert_eq!(after("|foo bar", "99999999999999999999e"), "foo ba|r");
+        assert_eq!(after("|foo bar", "99999999999999999999cw"), "|");
+    }
+
+    #[test]
+    fn visual_mode_selects_characters() {
+        let mut t = normal("foo |bar baz");
+        feed(&mut t, "v");
+        assert_eq!(t.vi_mode(), Some(ViMode::Visual));
+        feed(&mut t, "e");
+        assert_eq!(t.vi_selection(), Some(4..7));
+        feed(&mut t, "d");
+        assert_eq!(t.vi_mode(), Some(ViMode::Normal));
+        assert_eq!(state(&t), "foo | baz");
+
+        assert_eq!(after("foo |bar baz", "vey$p"), "foo bar bazba|r");
+        assert_eq!(after("foo ba|r baz", "vbx"), "foo | baz");
+        assert_eq!(after("foo ba|r baz", "vbo"), "foo ba|r baz");
+        assert_eq!(after("foo |bar baz", "vwc"), "foo |az");
+
+        let mut t = normal("a|bc");
+        feed(&mut t, "vl");
+        esc(&mut t);
+        assert_eq!(t.vi_mode(), Some(ViMode::Normal));
+        assert_eq!(t.vi_selection(), None);
+        assert_eq!(state(&t), "ab|c");
+    }
+
+    #[test]
+    fn visual_line_mode_selects_lines() {
+        let mut t = normal("one\nt|wo\nthree\nfour");
+        feed(&mut t, "Vj");
+        assert_eq!(t.vi_mode(), Some(ViMode::VisualLine));
+        assert_eq!(t.vi_selection(), Some(4..13));
+        feed(&mut t, "d");
+        assert_eq!(state(&t), "one\n|four");
+
+        assert_eq!(after("o|ne\ntwo", "VyjP"), "one\n|one\ntwo");
+        // `v` and `V` switch between the visual modes or leave them.
+        let mut t = normal("o|ne");
+        feed(&mut t, "Vv");
+        assert_eq!(t.vi_mode(), Some(ViMode::Visual));
+        feed(&mut t, "v");
+        assert_eq!(t.vi_mode(), Some(ViMode::Normal));
+    }
+
+    #[test]
+    fn cursor_stays_on_a_character_in_normal_mode() {
+        let mut t = TextArea::new();
+        t.enable_vi_mode();
+        feed(&mut t, "ab");
+        esc(&mut t);
+        feed(&mut t, "$l");
+        assert_eq!(state(&t), "a|b");
+        feed(&mut t, "o");
+        esc(&mut t);
+        assert_eq!(state(&t), "ab\n|");
+    }
+
+    #[test]
+    fn reset_returns_to_insert_mode_and_keeps_the_register() {
+        let mut t = normal("|foo");
+        feed(&mut t, "yw");
+        t.set_text("");
+        t.reset_vi_mode();
+        assert_eq!(t.vi_mode(), Some(ViMode::Insert));
+        esc(&mut t);
+        feed(&mut t, "p");
+        assert_eq!(t.text(), "foo");
+        feed(&mut t, "u");
+        assert_eq!(t.text(), "");
+        feed(&mut t, "u");
+        assert_eq!(t.text(), "");
+    }
+
+    #[test]
+    fn arrow_keys_move_in_normal_mode() {
+        let mut t = normal("|abc\ndef");
+        t.input(KeyEvent::new(KeyCode::Right, KeyModifiers::NONE));
+        t.input(KeyEvent::new(KeyCode::Down, KeyModifiers::NONE));
+        assert_eq!(state(&t), "abc\nd|ef");
+        t.input(KeyEvent::new(KeyCode::End, KeyModifiers::NONE));
+        assert_eq!(state(&t), "abc\nde|f");
+    }
+}
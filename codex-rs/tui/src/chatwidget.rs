@@ -4,6 +4,7 @@ use std::path::PathBuf;
 use std::sync::Arc;
 
 use codex_core::config::Config;
+use codex_core::config_types::EditingMode;
 use codex_core::protocol::AgentMessageDeltaEvent;
 use codex_core::protocol::AgentMessageEvent;
 use codex_core::protocol::AgentReasoningDeltaEvent;
@@ -65,6 +66,7 @@ use crate::history_cell::CommandOutput;
 use crate::history_cell::ExecCell;
 use crate::history_cell::HistoryCell;
 use crate::history_cell::PatchEventType;
+use crate::history_cell::bounded_output;
 use crate::slash_command::SlashCommand;
 use crate::tui::FrameRequester;
 // streaming internals are provided by crate::streaming and crate::markdown_stream
@@ -350,6 +352,7 @@ impl ChatWidget {
             },
             event.changes,
             &self.config.cwd,
+            self.config.tui.diff_layout,
         ));
     }
 
@@ -497,6 +500,7 @@ impl ChatWidget {
                     stdout: ev.stdout.clone(),
                     stderr: ev.stderr.clone(),
                     formatted_output: ev.formatted_output.clone(),
+                    aggregated_output: bounded_output(&ev.aggregated_output),
                 },
                 ev.duration,
             );
@@ -541,6 +545,7 @@ impl ChatWidget {
             PatchEventType::ApprovalRequest,
             ev.changes.clone(),
             &self.config.cwd,
+            self.config.tui.diff_layout,
         ));
 
         let request = ApprovalRequest::ApplyPatch {
@@ -646,6 +651,7 @@ impl ChatWidget {
                 enhanced_keys_supported,
                 placeholder_text: placeholder,
                 disable_paste_burst: config.disable_paste_burst,
+                vi_mode: config.tui.editing_mode == EditingMode::Vi,
             }),
             active_exec_cell: None,
             config: config.clone(),
@@ -698,6 +704,7 @@ impl ChatWidget {
                 enhanced_keys_supported,
                 placeholder_text: placeholder,
                 disable_paste_burst: config.disable_paste_burst,
+                vi_mode: config.tui.editing_mode == EditingMode::Vi,
             }),
             active_exec_cell: None,
             config: config.clone(),
@@ -787,12 +794,24 @@ impl ChatWidget {
                     InputResult::Command(cmd) => {
                         self.dispatch_command(cmd);
                     }
+                    InputResult::CommandWithArgument(cmd, argument) => {
+                        self.dispatch_command_with_argument(cmd, argument);
+                    }
                     InputResult::None => {}
                 }
             }
         }
     }
 
+    fn dispatch_command_with_argument(&mut self, cmd: SlashCommand, argument: String) {
+        match cmd {
+            SlashCommand::Rename => {
+                self.app_event_tx.send(AppEvent::RenameTab(argument));
+            }
+            _ => self.dispatch_command(cmd),
+        }
+    }
+
     pub(crate) fn attach_image(
         &mut self,
         path: PathBuf,
@@ -822,6 +841,18 @@ impl ChatWidget {
             SlashCommand::New => {
                 self.app_event_tx.send(AppEvent::NewSession);
             }
+            SlashCommand::NewTab => {
+                self.app_event_tx.send(AppEvent::NewTab);
+            }
+            SlashCommand::Tabs => {
+                self.app_event_tx.send(AppEvent::ShowTabs);
+            }
+            SlashCommand::Rename => {
+                self.app_event_tx.send(AppEvent::RenameTab(String::new()));
+            }
+            SlashCommand::QuitTab => {
+                self.app_event_tx.send(AppEvent::CloseTab);
+            }
             SlashCommand::Init => {
                 const INIT_PROMPT: &str = include_str!("../prompt_for_init_command.md");
                 self.submit_text_message(INIT_PROMPT.to_string());
@@ -865,6 +896,9 @@ impl ChatWidget {
             SlashCommand::Mention => {
                 self.insert_str("@");
             }
+            SlashCommand::Edit => {
+                self.app_event_tx.send(AppEvent::OpenExternalEditor);
+            }
             SlashCommand::Status => {
                 self.add_status_output();
             }
@@ -1074,6 +1108,9 @@ impl ChatWidget {
             EventMsg::ListCustomPromptsResponse(ev) => self.on_list_custom_prompts(ev),
             EventMsg::ShutdownComplete => self.on_shutdown_complete(),
             EventMsg::TurnDiff(TurnDiffEvent { unified_diff }) => self.on_turn_diff(unified_diff),
+            EventMsg::BackgroundProcessesUpdate(ev) => {
+                self.bottom_pane.set_background_processes(ev.processes)
+            }
             EventMsg::BackgroundEvent(BackgroundEventEvent { message }) => {
                 self.on_background_event(message)
             }
@@ -1301,6 +1338,21 @@ impl ChatWidget {
         self.submit_op(Op::Shutdown);
     }
 
+    pub(crate) fn is_task_running(&self) -> bool {
+        self.bottom_pane.is_task_running()
+    }
+
+    pub(crate) fn show_selection_view(
+        &mut self,
+        title: String,
+        footer_hint: Option<String>,
+        items: Vec<SelectionItem>,
+    ) {
+        self.bottom_pane
+            .show_selection_view(title, None, footer_hint, items);
+        self.request_redraw();
+    }
+
     pub(crate) fn composer_is_empty(&self) -> bool {
         self.bottom_pane.composer_is_empty()
     }
@@ -1312,10 +1364,27 @@ impl ChatWidget {
         self.bottom_pane.is_normal_backtrack_mode()
     }
 
+    pub(crate) fn is_vi_insert_mode(&self) -> bool {
+        self.bottom_pane.is_vi_insert_mode()
+    }
+
     pub(crate) fn insert_str(&mut self, text: &str) {
         self.bottom_pane.insert_str(text);
     }
 
+    pub(crate) fn composer_text(&self) -> String {
+        self.bottom_pane.composer_text()
+    }
+
+    pub(crate) fn apply_external_edit(&mut self, text: String) {
+        self.bottom_pane.apply_external_edit(text);
+    }
+
+    pub(crate) fn add_error_message(&mut self, message: String) {
+        self.add_to_history(history_cell::new_error_event(message));
+        self.request_redraw();
+    }
+
     pub(crate) fn show_esc_backtrack_hint(&mut self) {
         self.bottom_pane.show_esc_backtrack_hint();
     }
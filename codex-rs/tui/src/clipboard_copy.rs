@@ -0,0 +1,89 @@
+use std::io::Write;
+
+use base64::Engine;
+
+#[derive(Debug)]
+pub enum CopyTextError {
+    ClipboardUnavailable(String),
+    IoError(String),
+}
+
+impl std::fmt::Display for CopyTextError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CopyTextError::ClipboardUnavailable(msg) => write!(f, "clipboard unavailable: {msg}"),
+            CopyTextError::IoError(msg) => write!(f, "io error: {msg}"),
+        }
+    }
+}
+impl std::error::Error for CopyTextError {}
+
+/// Copy `text` to the system clipboard, or ask the terminal to copy it with
+/// an OSC 52 escape sequence when there is no system clipboard (e.g. over
+/// SSH).
+pub fn copy_text(text: &str) -> Result<(), CopyTextError> {
+    match copy_text_to_system_clipboard(text) {
+        Ok(()) => Ok(()),
+        Err(err) => {
+            tracing::debug!("falling back to OSC 52 copy: {err}");
+            copy_text_with_osc52(text)
+        }
+    }
+}
+
+#[cfg(not(target_os = "android"))]
+fn copy_text_to_system_clipboard(text: &str) -> Result<(), CopyTextError> {
+    use std::sync::Mutex;
+
+    // On X11 and Wayland the copied text is served by its owner, so the
+    // clipboard is kept open for the life of the process.
+    static CLIPBOARD: Mutex<Option<arboard::Clipboard>> = Mutex::new(None);
+
+    let mut clipboard = CLIPBOARD
+        .lock()
+        .map_err(|e| CopyTextError::ClipboardUnavailable(e.to_string()))?;
+    if clipboard.is_none() {
+        *clipboard = Some(
+            arboard::Clipboard::new()
+                .map_err(|e| CopyTextError::ClipboardUnavailable(e.to_string()))?,
+        );
+    }
+    match clipboard.as_mut() {
+        Some(cb) => cb
+            .set_text(text)
+            .map_err(|e| CopyTextError::ClipboardUnavailable(e.to_string())),
+        None => Err(CopyTextError::ClipboardUnavailable(
+            "clipboard not initialized".to_string(),
+        )),
+    }
+}
+
+#[cfg(target_os = "android")]
+fn copy_text_to_system_clipboard(_text: &str) -> Result<(), CopyTextError> {
+    Err(CopyTextError::ClipboardUnavailable(
+        "clipboard text copy is not supported on Android".into(),
+    ))
+}
+
+fn copy_text_with_osc52(text: &str) -> Result<(), CopyTextError> {
+    let mut stdout = std::io::stdout();
+    stdout
+        .write_all(osc52_sequence(text).as_bytes())
+        .and_then(|()| stdout.flush())
+        .map_err(|e| CopyTextError::IoError(e.to_string()))
+}
+
+fn osc52_sequence(text: &str) -> String {
+    let encoded = base64::engine::general_purpose::STANDARD.encode(text);
+    format!("\x1b]52;c;{encoded}\x07")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn osc52_sequence_encodes_text_as_base64() {
+        assert_eq!(osc52_sequence("hi\n"), "\x1b]52;c;aGkK\x07");
+    }
+}
@@ -1,21 +1,35 @@
-use ratatui::style::Color;
 use ratatui::style::Modifier;
 use ratatui::style::Style;
+use ratatui::style::Styled;
 use ratatui::style::Stylize;
 use ratatui::text::Line as RtLine;
 use ratatui::text::Span as RtSpan;
 use std::collections::HashMap;
+use std::ops::Range;
 use std::path::Path;
 use std::path::PathBuf;
 
 use crate::exec_command::relativize_to_home;
 use crate::history_cell::PatchEventType;
+use crate::render::highlight::highlight_file_to_lines;
+use crate::theme::ThemeStylize;
+use crate::theme::theme;
+use codex_core::config_types::DiffLayout;
 use codex_core::git_info::get_git_repo_root;
 use codex_core::protocol::FileChange;
+use codex_protocol::num_format::format_with_separators;
 
 const SPACES_AFTER_LINE_NUMBER: usize = 6;
 
+/// Narrowest terminal on which hunks are laid out side by side.
+const MIN_SIDE_BY_SIDE_COLS: usize = 120;
+
+/// Pairs of changed lines whose token counts multiply to more than this are
+/// not diffed word by word.
+const MAX_WORD_DIFF_CELLS: usize = 40_000;
+
 // Internal representation for diff line rendering
+#[derive(Clone, Copy, PartialEq, Eq)]
 enum DiffLineType {
     Insert,
     Delete,
@@ -27,6 +41,7 @@ pub(crate) fn create_diff_summary(
     event_type: PatchEventType,
     cwd: &Path,
     wrap_cols: usize,
+    layout: DiffLayout,
 ) -> Vec<RtLine<'static>> {
     let rows = collect_rows(changes);
     let header_kind = match event_type {
@@ -39,7 +54,7 @@ pub(crate) fn create_diff_summary(
         }
         PatchEventType::ApprovalRequest => HeaderKind::ProposedChange,
     };
-    render_changes_block(rows, wrap_cols, header_kind, cwd)
+    render_changes_block(rows, wrap_cols, header_kind, cwd, layout)
 }
 
 // Shared row for per-file presentation
@@ -56,18 +71,8 @@ struct Row {
 fn collect_rows(changes: &HashMap<PathBuf, FileChange>) -> Vec<Row> {
     let mut rows: Vec<Row> = Vec::new();
     for (path, change) in changes.iter() {
-        let (added, removed) = match change {
-            FileChange::Add { content } => (content.lines().count(), 0),
-            FileChange::Delete { content } => (0, content.lines().count()),
-            FileChange::Update { unified_diff, .. } => calculate_add_remove_from_diff(unified_diff),
-        };
-        let move_path = match change {
-            FileChange::Update {
-                move_path: Some(new),
-                ..
-            } => Some(new.clone()),
-            _ => None,
-        };
+        let (added, removed) = line_counts(change);
+        let move_path = move_path_of(change).cloned();
         rows.push(Row {
             path: path.clone(),
             move_path,
@@ -80,6 +85,44 @@ fn collect_rows(changes: &HashMap<PathBuf, FileChange>) -> Vec<Row> {
     rows
 }
 
+fn line_counts(change: &FileChange) -> (usize, usize) {
+    match change {
+        FileChange::Add { content } => (content.lines().count(), 0),
+        FileChange::Delete { content } => (0, content.lines().count()),
+        FileChange::Update { unified_diff, .. } => calculate_add_remove_from_diff(unified_diff),
+        FileChange::Mode {
+            contents: Some(contents),
+            ..
+        } => line_counts(contents),
+        FileChange::Binary { .. } | FileChange::Mode { .. } | FileChange::Symlink { .. } => (0, 0),
+    }
+}
+
+fn move_path_of(change: &FileChange) -> Option<&PathBuf> {
+    match change {
+        FileChange::Update { move_path, .. } => move_path.as_ref(),
+        FileChange::Mode {
+            contents: Some(contents),
+            ..
+        } => move_path_of(contents),
+        _ => None,
+    }
+}
+
+fn edited_verb(change: &FileChange) -> &'static str {
+    match change {
+        FileChange::Add { .. }
+        | FileChange::Symlink { .. }
+        | FileChange::Binary { old_size: None, .. } => "Added",
+        FileChange::Delete { .. } => "Deleted",
+        FileChange::Mode {
+            contents: Some(contents),
+            ..
+        } => edited_verb(contents),
+        _ => "Edited",
+    }
+}
+
 enum HeaderKind {
     ProposedChange,
     Edited,
@@ -91,6 +134,7 @@ fn render_changes_block(
     wrap_cols: usize,
     header_kind: HeaderKind,
     cwd: &Path,
+    layout: DiffLayout,
 ) -> Vec<RtLine<'static>> {
     let mut out: Vec<RtLine<'static>> = Vec::new();
     let term_cols = wrap_cols;
@@ -98,9 +142,9 @@ fn render_changes_block(
     fn render_line_count_summary(added: usize, removed: usize) -> Vec<RtSpan<'static>> {
         let mut spans = Vec::new();
         spans.push("(".into());
-        spans.push(format!("+{added}").green());
+        spans.push(format!("+{added}").set_style(style_add()));
         spans.push(" ".into());
-        spans.push(format!("-{removed}").red());
+        spans.push(format!("-{removed}").set_style(style_del()));
         spans.push(")".into());
         spans
     }
@@ -135,12 +179,7 @@ fn render_changes_block(
         }
         HeaderKind::Edited => {
             if let [row] = &rows[..] {
-                let verb = match &row.change {
-                    FileChange::Add { .. } => "Added",
-                    FileChange::Delete { .. } => "Deleted",
-                    _ => "Edited",
-                };
-                header_spans.push(verb.bold());
+                header_spans.push(edited_verb(&row.change).bold());
                 header_spans.push(" ".into());
                 header_spans.extend(render_path(row));
                 header_spans.push(" ".into());
@@ -182,86 +221,601 @@ fn render_changes_block(
                 && file_count == 1;
         if !skip_file_header {
             let mut header: Vec<RtSpan<'static>> = Vec::new();
-            header.push("  └ ".dim());
+            header.push("  └ ".secondary());
             header.extend(render_path(&r));
             header.push(" ".into());
             header.extend(render_line_count_summary(r.added, r.removed));
             out.push(RtLine::from(header));
         }
 
-        match r.change {
-            FileChange::Add { content } => {
-                for (i, raw) in content.lines().enumerate() {
-                    out.extend(push_wrapped_diff_line(
-                        i + 1,
-                        DiffLineType::Insert,
-                        raw,
-                        term_cols,
-                    ));
-                }
+        // Syntax coloring follows the name the file ends up with.
+        let path = r.move_path.as_ref().unwrap_or(&r.path);
+        render_change_body(&r.change, path, term_cols, layout, &mut out);
+    }
+
+    out
+}
+
+fn render_change_body(
+    change: &FileChange,
+    path: &Path,
+    term_cols: usize,
+    layout: DiffLayout,
+    out: &mut Vec<RtLine<'static>>,
+) {
+    match change {
+        FileChange::Add { content } => {
+            for (i, raw) in content.lines().enumerate() {
+                out.extend(push_wrapped_diff_line(
+                    i + 1,
+                    DiffLineType::Insert,
+                    raw,
+                    term_cols,
+                ));
             }
-            FileChange::Delete { content } => {
-                for (i, raw) in content.lines().enumerate() {
-                    out.extend(push_wrapped_diff_line(
-                        i + 1,
-                        DiffLineType::Delete,
-                        raw,
-                        term_cols,
-                    ));
-                }
+        }
+        FileChange::Delete { content } => {
+            for (i, raw) in content.lines().enumerate() {
+                out.extend(push_wrapped_diff_line(
+                    i + 1,
+                    DiffLineType::Delete,
+                    raw,
+                    term_cols,
+                ));
             }
-            FileChange::Update { unified_diff, .. } => {
-                if let Ok(patch) = diffy::Patch::from_str(&unified_diff) {
-                    let mut is_first_hunk = true;
-                    for h in patch.hunks() {
-                        if !is_first_hunk {
-                            out.push(RtLine::from(vec!["    ".into(), "⋮".dim()]));
-                        }
-                        is_first_hunk = false;
-
-                        let mut old_ln = h.old_range().start();
-                        let mut new_ln = h.new_range().start();
-                        for l in h.lines() {
-                            match l {
-                                diffy::Line::Insert(text) => {
-                                    let s = text.trim_end_matches('\n');
-                                    out.extend(push_wrapped_diff_line(
-                                        new_ln,
-                                        DiffLineType::Insert,
-                                        s,
-                                        term_cols,
-                                    ));
-                                    new_ln += 1;
-                                }
-                                diffy::Line::Delete(text) => {
-                                    let s = text.trim_end_matches('\n');
-                                    out.extend(push_wrapped_diff_line(
-                                        old_ln,
-                                        DiffLineType::Delete,
-                                        s,
-                                        term_cols,
-                                    ));
-                                    old_ln += 1;
-                                }
-                                diffy::Line::Context(text) => {
-                                    let s = text.trim_end_matches('\n');
-                                    out.extend(push_wrapped_diff_line(
-                                        new_ln,
-                                        DiffLineType::Context,
-                                        s,
-                                        term_cols,
-                                    ));
-                                    old_ln += 1;
-                                    new_ln += 1;
-                                }
-                            }
-                        }
-                    }
-                }
+        }
+        FileChange::Update { unified_diff, .. } => {
+            render_unified_diff(unified_diff, path, term_cols, layout, out);
+        }
+        FileChange::Binary { old_size, new_size } => {
+            let size = match old_size {
+                Some(old_size) => format!(
+                    "{} → {} bytes",
+                    format_with_separators(*old_size),
+                    format_with_separators(*new_size)
+                ),
+                None => format!("{} bytes", format_with_separators(*new_size)),
+            };
+            out.push(RtLine::from(vec![
+                "    ".into(),
+                format!("binary file, {size}").secondary(),
+            ]));
+        }
+        FileChange::Mode {
+            old_mode,
+            new_mode,
+            contents,
+        } => {
+            if let Some(contents) = contents {
+                render_change_body(contents, path, term_cols, layout, out);
             }
+            let mode = match old_mode {
+                Some(old_mode) => format!("mode {old_mode:o} → {new_mode:o}"),
+                None => format!("mode {new_mode:o}"),
+            };
+            out.push(RtLine::from(vec!["    ".into(), mode.secondary()]));
+        }
+        FileChange::Symlink { target } => {
+            out.push(RtLine::from(vec![
+                "    ".into(),
+                format!("symlink → {}", target.display()).secondary(),
+            ]));
         }
     }
+}
 
+fn render_unified_diff(
+    unified_diff: &str,
+    path: &Path,
+    term_cols: usize,
+    layout: DiffLayout,
+    out: &mut Vec<RtLine<'static>>,
+) {
+    let Ok(patch) = diffy::Patch::from_str(unified_diff) else {
+        return;
+    };
+    let side_by_side =
+        matches!(layout, DiffLayout::SideBySide) && term_cols >= MIN_SIDE_BY_SIDE_COLS;
+    for (idx, hunk) in patch.hunks().iter().enumerate() {
+        if idx > 0 {
+            out.push(RtLine::from(vec!["    ".into(), "⋮".secondary()]));
+        }
+        let lines = hunk_lines(hunk, path);
+        if side_by_side {
+            render_hunk_side_by_side(lines, term_cols, out);
+        } else {
+            for line in lines {
+                let line_number = match line.kind {
+                    DiffLineType::Delete => line.old_line,
+                    DiffLineType::Insert | DiffLineType::Context => line.new_line,
+                };
+                out.extend(push_wrapped_diff_spans(
+                    line_number,
+                    line.kind,
+                    line.content,
+                    term_cols,
+                ));
+            }
+        }
+    }
+}
+
+/// A line of a hunk with its styled content, ready to be laid out.
+#[derive(Clone)]
+struct HunkLine {
+    kind: DiffLineType,
+    old_line: usize,
+    new_line: usize,
+    content: Vec<RtSpan<'static>>,
+}
+
+/// Styles the lines of `hunk`: context lines are syntax colored by the
+/// extension of `path`, and the words that differ between a removed line and
+/// the line added in its place are emphasized.
+fn hunk_lines(hunk: &diffy::Hunk<'_, str>, path: &Path) -> Vec<HunkLine> {
+    let mut raw: Vec<(DiffLineType, &str)> = Vec::new();
+    for l in hunk.lines() {
+        raw.push(match l {
+            diffy::Line::Insert(text) => (DiffLineType::Insert, text.trim_end_matches('\n')),
+            diffy::Line::Delete(text) => (DiffLineType::Delete, text.trim_end_matches('\n')),
+            diffy::Line::Context(text) => (DiffLineType::Context, text.trim_end_matches('\n')),
+        });
+    }
+
+    // Highlight the new version of the hunk as a whole so that constructs
+    // spanning several lines (strings, comments) are recognized.
+    let has_context = raw.iter().any(|(kind, _)| *kind == DiffLineType::Context);
+    let mut highlighted = if has_context {
+        let new_side: Vec<&str> = raw
+            .iter()
+            .filter(|(kind, _)| *kind != DiffLineType::Delete)
+            .map(|(_, text)| *text)
+            .collect();
+        highlight_file_to_lines(path, &new_side.join("\n")).map(Vec::into_iter)
+    } else {
+        None
+    };
+
+    let kinds: Vec<DiffLineType> = raw.iter().map(|(kind, _)| *kind).collect();
+    let mut emphasis: Vec<Vec<Range<usize>>> = vec![Vec::new(); raw.len()];
+    let mut i = 0;
+    while i < raw.len() {
+        if kinds[i] == DiffLineType::Context {
+            i += 1;
+            continue;
+        }
+        let (deletes_end, end) = change_block(&kinds, i);
+        for (old, new) in (i..deletes_end).zip(deletes_end..end) {
+            if let Some((old_ranges, new_ranges)) = changed_ranges(raw[old].1, raw[new].1) {
+                emphasis[old] = old_ranges;
+                emphasis[new] = new_ranges;
+            }
+        }
+        i = end;
+    }
+
+    let mut old_line = hunk.old_range().start();
+    let mut new_line = hunk.new_range().start();
+    let mut lines = Vec::with_capacity(raw.len());
+    for ((kind, text), emphasized) in raw.into_iter().zip(emphasis) {
+        let syntax = match kind {
+            DiffLineType::Delete => None,
+            DiffLineType::Insert | DiffLineType::Context => {
+                highlighted.as_mut().and_then(Iterator::next)
+            }
+        };
+        let content = match (kind, syntax) {
+            (DiffLineType::Context, Some(line)) => line.spans,
+            (DiffLineType::Context, None) => {
+                vec![RtSpan::styled(text.to_string(), style_context())]
+            }
+            (DiffLineType::Insert, _) => emphasized_spans(text, &emphasized, style_add()),
+            (DiffLineType::Delete, _) => emphasized_spans(text, &emphasized, style_del()),
+        };
+        lines.push(HunkLine {
+            kind,
+            old_line,
+            new_line,
+            content,
+        });
+        match kind {
+            DiffLineType::Insert => new_line += 1,
+            DiffLineType::Delete => old_line += 1,
+            DiffLineType::Context => {
+                old_line += 1;
+                new_line += 1;
+            }
+        }
+    }
+    lines
+}
+
+/// For the block of changed lines starting at `start`, returns the end of its
+/// removed lines and the end of the added lines that follow them.
+fn change_block(kinds: &[DiffLineType], start: usize) -> (usize, usize) {
+    let run_end = |from: usize, kind: DiffLineType| {
+        kinds[from..]
+            .iter()
+            .position(|k| *k != kind)
+            .map_or(kinds.len(), |p| from + p)
+    };
+    let deletes_end = run_end(start, DiffLineType::Delete);
+    (deletes_end, run_end(deletes_end, DiffLineType::Insert))
+}
+
+/// Lays out a hunk with the old version on the left and the new version on
+/// the right. Removed lines are lined up with the lines added in their place.
+fn render_hunk_side_by_side(
+    lines: Vec<HunkLine>,
+    term_cols: usize,
+    out: &mut Vec<RtLine<'static>>,
+) {
+    let indent = "    ";
+    let separator = " │ ";
+    let cell_cols = term_cols.saturating_sub(indent.len() + separator.chars().count()) / 2;
+
+    let kinds: Vec<DiffLineType> = lines.iter().map(|l| l.kind).collect();
+    let mut rows: Vec<(Option<&HunkLine>, Option<&HunkLine>)> = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        if kinds[i] == DiffLineType::Context {
+            rows.push((Some(&lines[i]), Some(&lines[i])));
+            i += 1;
+            continue;
+        }
+        let (deletes_end, end) = change_block(&kinds, i);
+        let old = &lines[i..deletes_end];
+        let new = &lines[deletes_end..end];
+        for k in 0..old.len().max(new.len()) {
+            rows.push((old.get(k), new.get(k)));
+        }
+        i = end;
+    }
+
+    for (old, new) in rows {
+        let left = old
+            .map(|l| wrap_diff_cell(l.old_line, l, cell_cols, true))
+            .unwrap_or_default();
+        let right = new
+            .map(|l| wrap_diff_cell(l.new_line, l, cell_cols, false))
+            .unwrap_or_default();
+        for row in 0..left.len().max(right.len()) {
+            let mut spans: Vec<RtSpan<'static>> = vec![indent.into()];
+            match left.get(row) {
+                Some(cell) => spans.extend(cell.iter().cloned()),
+                None => spans.push(" ".repeat(cell_cols).into()),
+            }
+            spans.push(separator.secondary());
+            if let Some(cell) = right.get(row) {
+                spans.extend(cell.iter().cloned());
+            }
+            out.push(RtLine::from(spans));
+        }
+    }
+}
+
+/// Wraps one side of a side-by-side row into rows of at most `cols` columns,
+/// padded to exactly `cols` when `pad` is set.
+fn wrap_diff_cell(
+    line_number: usize,
+    line: &HunkLine,
+    cols: usize,
+    pad: bool,
+) -> Vec<Vec<RtSpan<'static>>> {
+    let ln_str = line_number.to_string();
+    let gap_after_ln = SPACES_AFTER_LINE_NUMBER.saturating_sub(ln_str.len());
+    let prefix_cols = ln_str.len() + gap_after_ln + 1;
+    let content_cols = cols.saturating_sub(prefix_cols).max(1);
+    let (sign_char, line_style) = sign_and_style(line.kind);
+
+    let mut rows = Vec::new();
+    let mut remaining = line.content.clone();
+    loop {
+        let (chunk, rest) = split_spans(remaining, content_cols);
+        remaining = rest;
+        let mut row: Vec<RtSpan<'static>> = if rows.is_empty() {
+            vec![
+                RtSpan::styled(
+                    format!("{ln_str}{}", " ".repeat(gap_after_ln)),
+                    style_gutter(),
+                ),
+                RtSpan::styled(sign_char.to_string(), line_style),
+            ]
+        } else {
+            vec![RtSpan::styled(" ".repeat(prefix_cols), style_gutter())]
+        };
+        let used: usize = chunk.iter().map(|s| s.content.chars().count()).sum();
+        row.extend(chunk);
+        if pad {
+            let width = prefix_cols + used;
+            row.push(" ".repeat(cols.saturating_sub(width)).into());
+        }
+        rows.push(row);
+        if remaining.is_empty() {
+            break;
+        }
+    }
+    rows
+}
+
+/// Splits `text` into runs of word characters, runs of whitespace and single
+/// other characters, returned as byte ranges.
+fn word_tokens(text: &str) -> Vec<Range<usize>> {
+    #[derive(PartialEq)]
+    enum Class {
+        Word,
+        Space,
+        Other,
+    }
+    let class_of = |c: char| {
+        if c.is_alphanumeric() || c == '_' {
+            Class::Word
+        } else if c.is_whitespace() {
+            Class::Space
+        } else {
+            Class::Other
+        }
+    };
+
+    let mut tokens = Vec::new();
+    let mut start = 0;
+    let mut prev = Class::Other;
+    for (i, c) in text.char_indices() {
+        let class = class_of(c);
+        if i > start && (class != prev || class == Class::Other) {
+            tokens.push(start..i);
+            start = i;
+        }
+        prev = class;
+    }
+    if start < text.len() {
+        tokens.push(start..text.len());
+    }
+    tokens
+}
+
+type ByteRanges = Vec<Range<usize>>;
+
+/// Byte ranges of `old` and `new` that fall outside their longest common
+/// subsequence of tokens, or `None` when the lines have too little in common
+/// for emphasizing the difference to help.
+fn changed_ranges(old: &str, new: &str) -> Option<(ByteRanges, ByteRanges)> {
+    let a = word_tokens(old);
+    let b = word_tokens(new);
+    if a.is_empty() || b.is_empty() || a.len() * b.len() > MAX_WORD_DIFF_CELLS {
+        return None;
+    }
+
+    // lcs[i * w + j] is the length of the LCS of a[i..] and b[j..].
+    let w = b.len() + 1;
+    let mut lcs = vec![0u32; (a.len() + 1) * w];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i * w + j] = if old[a[i].clone()] == new[b[j].clone()] {
+                lcs[(i + 1) * w + j + 1] + 1
+            } else {
+                lcs[(i + 1) * w + j].max(lcs[i * w + j + 1])
+            };
+        }
+    }
+    let mut old_same = vec![false; a.len()];
+    let mut new_same = vec![false; b.len()];
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        if old[a[i].clone()] == new[b[j].clone()] {
+            old_same[i] = true;
+            new_same[j] = true;
+            i += 1;
+            j += 1;
+        } else if lcs[(i + 1) * w + j] >= lcs[i * w + j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+
+    // Only count visible text: shared indentation says little about how
+    // similar two lines are.
+    let visible_len = |text: &str, range: &Range<usize>| {
+        if text[range.clone()].trim().is_empty() {
+            0
+        } else {
+            range.len()
+        }
+    };
+    let common: usize = a
+        .iter()
+        .zip(&old_same)
+        .filter(|(_, same)| **same)
+        .map(|(range, _)| visible_len(old, range))
+        .sum();
+    let old_len: usize = a.iter().map(|range| visible_len(old, range)).sum();
+    let new_len: usize = b.iter().map(|range| visible_len(new, range)).sum();
+    if common == 0 || common * 2 < old_len.min(new_len) {
+        return None;
+    }
+    Some((
+        changed_token_ranges(old, &a, &old_same),
+        changed_token_ranges(new, &b, &new_same),
+    ))
+}
+
+/// Merges the tokens not in the common subsequence into byte ranges.
+/// Whitespace between two changed tokens is treated as changed so that a
+/// rewritten phrase is emphasized as a whole, while whitespace at the edges
+/// of a range is left out unless the range is only whitespace.
+fn changed_token_ranges(text: &str, tokens: &[Range<usize>], same: &[bool]) -> Vec<Range<usize>> {
+    let changed: Vec<bool> = (0..tokens.len())
+        .map(|k| {
+            !same[k]
+                || (text[tokens[k].clone()].trim().is_empty()
+                    && k > 0
+                    && k + 1 < tokens.len()
+                    && !same[k - 1]
+                    && !same[k + 1])
+        })
+        .collect();
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+    for (range, changed) in tokens.iter().zip(changed) {
+        if !changed {
+            continue;
+        }
+        match ranges.last_mut() {
+            Some(last) if last.end == range.start => last.end = range.end,
+            _ => ranges.push(range.clone()),
+        }
+    }
+    for range in &mut ranges {
+        let changed = &text[range.clone()];
+        let trimmed = changed.trim();
+        if !trimmed.is_empty() {
+            range.start += changed.len() - changed.trim_start().len();
+            range.end = range.start + trimmed.len();
+        }
+    }
+    ranges
+}
+
+/// Styles `text` with `style`, reversing the `emphasized` byte ranges.
+fn emphasized_spans(text: &str, emphasized: &[Range<usize>], style: Style) -> Vec<RtSpan<'static>> {
+    let mut spans = Vec::new();
+    let mut pos = 0;
+    for range in emphasized {
+        if range.start > pos {
+            spans.push(RtSpan::styled(text[pos..range.start].to_string(), style));
+        }
+        spans.push(RtSpan::styled(
+            text[range.clone()].to_string(),
+            style.add_modifier(Modifier::REVERSED),
+        ));
+        pos = range.end;
+    }
+    if pos < text.len() {
+        spans.push(RtSpan::styled(text[pos..].to_string(), style));
+    }
+    spans
+}
+
+/// Splits `spans` after `cols` characters. Empty spans are dropped.
+fn split_spans(
+    spans: Vec<RtSpan<'static>>,
+    cols: usize,
+) -> (Vec<RtSpan<'static>>, Vec<RtSpan<'static>>) {
+    let mut head = Vec::new();
+    let mut tail = Vec::new();
+    let mut remaining = cols;
+    for span in spans {
+        if span.content.is_empty() {
+            continue;
+        }
+        if remaining == 0 {
+            tail.push(span);
+            continue;
+        }
+        match span.content.char_indices().nth(remaining) {
+            Some((split_at, _)) => {
+                let (first, rest) = span.content.split_at(split_at);
+                head.push(RtSpan::styled(first.to_string(), span.style));
+                tail.push(RtSpan::styled(rest.to_string(), span.style));
+                remaining = 0;
+            }
+            None => {
+                remaining -= span.content.chars().count();
+                head.push(span);
+            }
+        }
+    }
+    (head, tail)
+}
+
+/// One file of `git diff` output.
+pub(crate) struct GitDiffFile {
+    path: PathBuf,
+    /// The file's diff from its `---` line on. Empty for binary files and
+    /// changes to the mode only.
+    unified_diff: String,
+    /// Extended header lines such as `new file mode 100644` or `Binary files
+    /// a/x and b/x differ`.
+    notes: Vec<String>,
+    added: usize,
+    removed: usize,
+}
+
+/// Splits the output of `git diff` into files. Returns an empty list when
+/// `text` is not a diff.
+pub(crate) fn parse_git_diff(text: &str) -> Vec<GitDiffFile> {
+    let mut files: Vec<GitDiffFile> = Vec::new();
+    let mut in_body = false;
+    for line in text.split_inclusive('\n') {
+        if let Some(paths) = line.strip_prefix("diff --git ") {
+            let path = paths
+                .trim_end()
+                .rsplit_once(" b/")
+                .map(|(_, new)| new)
+                .unwrap_or(paths.trim_end());
+            files.push(GitDiffFile {
+                path: PathBuf::from(path),
+                unified_diff: String::new(),
+                notes: Vec::new(),
+                added: 0,
+                removed: 0,
+            });
+            in_body = false;
+            continue;
+        }
+        let Some(file) = files.last_mut() else {
+            continue;
+        };
+        if in_body || line.starts_with("--- ") {
+            in_body = true;
+            file.unified_diff.push_str(line);
+        } else if !line.starts_with("index ") {
+            file.notes.push(line.trim_end().to_string());
+        }
+    }
+    for file in &mut files {
+        (file.added, file.removed) = calculate_add_remove_from_diff(&file.unified_diff);
+    }
+    files
+}
+
+/// The header line of a file in the diff pager: a fold marker, the path and
+/// the line counts.
+pub(crate) fn git_diff_file_header(
+    file: &GitDiffFile,
+    collapsed: bool,
+    selected: bool,
+) -> RtLine<'static> {
+    let marker = if collapsed { "▸ " } else { "▾ " };
+    let path = file.path.display().to_string();
+    let (marker, path) = if selected {
+        (marker.accent(), path.accent().bold())
+    } else {
+        (marker.secondary(), path.bold())
+    };
+    RtLine::from(vec![
+        marker,
+        path,
+        " (".into(),
+        format!("+{}", file.added).set_style(style_add()),
+        " ".into(),
+        format!("-{}", file.removed).set_style(style_del()),
+        ")".into(),
+    ])
+}
+
+/// The hunks of a file in the diff pager, preceded by its extended header.
+pub(crate) fn render_git_diff_file_body(
+    file: &GitDiffFile,
+    wrap_cols: usize,
+    layout: DiffLayout,
+) -> Vec<RtLine<'static>> {
+    let mut out: Vec<RtLine<'static>> = file
+        .notes
+        .iter()
+        .map(|note| RtLine::from(vec!["    ".into(), note.clone().secondary()]))
+        .collect();
+    render_unified_diff(&file.unified_diff, &file.path, wrap_cols, layout, &mut out);
     out
 }
 
@@ -300,10 +854,25 @@ fn push_wrapped_diff_line(
     kind: DiffLineType,
     text: &str,
     term_cols: usize,
+) -> Vec<RtLine<'static>> {
+    let (_, line_style) = sign_and_style(kind);
+    push_wrapped_diff_spans(
+        line_number,
+        kind,
+        vec![RtSpan::styled(text.to_string(), line_style)],
+        term_cols,
+    )
+}
+
+fn push_wrapped_diff_spans(
+    line_number: usize,
+    kind: DiffLineType,
+    content: Vec<RtSpan<'static>>,
+    term_cols: usize,
 ) -> Vec<RtLine<'static>> {
     let indent = "    ";
     let ln_str = line_number.to_string();
-    let mut remaining_text: &str = text;
+    let mut remaining = content;
 
     // Reserve a fixed number of spaces after the line number so that content starts
     // at a consistent column. Content includes a 1-character diff sign prefix
@@ -313,53 +882,49 @@ fn push_wrapped_diff_line(
     let prefix_cols = indent.len() + ln_str.len() + gap_after_ln;
 
     let mut first = true;
-    let (sign_char, line_style) = match kind {
-        DiffLineType::Insert => ('+', style_add()),
-        DiffLineType::Delete => ('-', style_del()),
-        DiffLineType::Context => (' ', style_context()),
-    };
+    let (sign_char, line_style) = sign_and_style(kind);
     let mut lines: Vec<RtLine<'static>> = Vec::new();
 
     loop {
-        // Fit the content for the current terminal row:
-        // compute how many columns are available after the prefix, then split
-        // at a UTF-8 character boundary so this row's chunk fits exactly.
+        // Fit the content for the current terminal row: compute how many
+        // columns are available after the prefix and split the spans there.
         let available_content_cols = term_cols.saturating_sub(prefix_cols + 1).max(1);
-        let split_at_byte_index = remaining_text
-            .char_indices()
-            .nth(available_content_cols)
-            .map(|(i, _)| i)
-            .unwrap_or_else(|| remaining_text.len());
-        let (chunk, rest) = remaining_text.split_at(split_at_byte_index);
-        remaining_text = rest;
-
-        if first {
-            // Build gutter (indent + line number + spacing) as a dimmed span
+        let (chunk, rest) = split_spans(remaining, available_content_cols);
+        remaining = rest;
+
+        let mut spans = if first {
+            // Gutter (indent + line number + spacing) dimmed, then the sign
+            // ('+'/'-'/' ') styled per diff kind
             let gutter = format!("{indent}{ln_str}{}", " ".repeat(gap_after_ln));
-            // Content with a sign ('+'/'-'/' ') styled per diff kind
-            let content = format!("{sign_char}{chunk}");
-            lines.push(RtLine::from(vec![
-                RtSpan::styled(gutter, style_gutter()),
-                RtSpan::styled(content, line_style),
-            ]));
             first = false;
+            vec![
+                RtSpan::styled(gutter, style_gutter()),
+                RtSpan::styled(sign_char.to_string(), line_style),
+            ]
         } else {
             // Continuation lines keep a space for the sign column so content aligns
             let gutter = format!("{indent}{} ", " ".repeat(ln_str.len() + gap_after_ln));
-            lines.push(RtLine::from(vec![
-                RtSpan::styled(gutter, style_gutter()),
-                RtSpan::styled(chunk.to_string(), line_style),
-            ]));
-        }
-        if remaining_text.is_empty() {
+            vec![RtSpan::styled(gutter, style_gutter())]
+        };
+        spans.extend(chunk);
+        lines.push(RtLine::from(spans));
+        if remaining.is_empty() {
             break;
         }
     }
     lines
 }
 
+fn sign_and_style(kind: DiffLineType) -> (char, Style) {
+    match kind {
+        DiffLineType::Insert => ('+', style_add()),
+        DiffLineType::Delete => ('-', style_del()),
+        DiffLineType::Context => (' ', style_context()),
+    }
+}
+
 fn style_gutter() -> Style {
-    Style::default().add_modifier(Modifier::DIM)
+    theme().diff_gutter
 }
 
 fn style_context() -> Style {
@@ -367,11 +932,11 @@ fn style_context() -> Style {
 }
 
 fn style_add() -> Style {
-    Style::default().fg(Color::Green)
+    theme().diff_add
 }
 
 fn style_del() -> Style {
-    Style::default().fg(Color::Red)
+    theme().diff_delete
 }
 
 #[cfg(test)]
@@ -380,6 +945,7 @@ mod tests {
     use insta::assert_snapshot;
     use ratatui::Terminal;
     use ratatui::backend::TestBackend;
+    use ratatui::style::Color;
     use ratatui::text::Text;
     use ratatui::widgets::Paragraph;
     use ratatui::widgets::WidgetRef;
@@ -388,7 +954,13 @@ mod tests {
         changes: &HashMap<PathBuf, FileChange>,
         event_type: PatchEventType,
     ) -> Vec<RtLine<'static>> {
-        create_diff_summary(changes, event_type, &PathBuf::from("/"), 80)
+        create_diff_summary(
+            changes,
+            event_type,
+            &PathBuf::from("/"),
+            80,
+            DiffLayout::Unified,
+        )
     }
 
     fn snapshot_lines(name: &str, lines: Vec<RtLine<'static>>, width: u16, height: u16) {
@@ -637,6 +1209,42 @@ mod tests {
         snapshot_lines("apply_add_block", lines, 80, 10);
     }
 
+    #[test]
+    fn ui_snapshot_apply_binary_mode_and_symlink_block() {
+        let changes: HashMap<PathBuf, FileChange> = HashMap::from([
+            (
+                PathBuf::from("assets/logo.png"),
+                FileChange::Binary {
+                    old_size: Some(512),
+                    new_size: 768,
+                },
+            ),
+            (
+                PathBuf::from("run.sh"),
+                FileChange::Mode {
+                    old_mode: Some(0o644),
+                    new_mode: 0o755,
+                    contents: None,
+                },
+            ),
+            (
+                PathBuf::from("latest"),
+                FileChange::Symlink {
+                    target: PathBuf::from("releases/v2"),
+                },
+            ),
+        ]);
+
+        let lines = diff_summary_for_tests(
+            &changes,
+            PatchEventType::ApplyBegin {
+                auto_approved: true,
+            },
+        );
+
+        snapshot_lines("apply_binary_mode_and_symlink_block", lines, 80, 10);
+    }
+
     #[test]
     fn ui_snapshot_apply_delete_block() {
         // Write a temporary file so the delete renderer can read original content
@@ -687,6 +1295,7 @@ mod tests {
             },
             &PathBuf::from("/"),
             72,
+            DiffLayout::Unified,
         );
 
         // Render with backend width wider than wrap width to avoid Paragraph auto-wrap.
@@ -717,6 +1326,7 @@ mod tests {
             },
             &PathBuf::from("/"),
             28,
+            DiffLayout::Unified,
         );
         // Drop the combined header for this text-only snapshot
         if !lines.is_empty() {
@@ -751,8 +1361,142 @@ mod tests {
             },
             &cwd,
             80,
+            DiffLayout::Unified,
         );
 
         snapshot_lines("apply_update_block_relativizes_path", lines, 80, 10);
     }
+
+    fn update_changes(path: &str, original: &str, modified: &str) -> HashMap<PathBuf, FileChange> {
+        HashMap::from([(
+            PathBuf::from(path),
+            FileChange::Update {
+                unified_diff: diffy::create_patch(original, modified).to_string(),
+                move_path: None,
+            },
+        )])
+    }
+
+    fn emphasized(lines: &[RtLine<'static>]) -> Vec<String> {
+        lines
+            .iter()
+            .flat_map(|l| l.spans.iter())
+            .filter(|s| s.style.add_modifier.contains(Modifier::REVERSED))
+            .map(|s| s.content.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn changed_words_are_emphasized() {
+        let changes = update_changes(
+            "example.txt",
+            "let total = compute(1, 2);\n",
+            "let total = compute(1, 3);\n",
+        );
+        let lines = diff_summary_for_tests(&changes, PatchEventType::ApprovalRequest);
+        assert_eq!(emphasized(&lines), vec!["2", "3"]);
+    }
+
+    #[test]
+    fn rewritten_lines_are_not_emphasized() {
+        let changes = update_changes("example.txt", "return x;\n", "let y = 3;\n");
+        let lines = diff_summary_for_tests(&changes, PatchEventType::ApprovalRequest);
+        assert!(emphasized(&lines).is_empty());
+    }
+
+    #[test]
+    fn context_lines_are_syntax_highlighted() {
+        let changes = update_changes(
+            "src/main.rs",
+            "fn main() {\n    old();\n}\n",
+            "fn main() {\n    new();\n}\n",
+        );
+        let lines = diff_summary_for_tests(&changes, PatchEventType::ApprovalRequest);
+        let keyword = lines
+            .iter()
+            .flat_map(|l| l.spans.iter())
+            .find(|s| s.content == "fn")
+            .expect("fn keyword span");
+        assert_eq!(keyword.style.fg, Some(Color::Magenta));
+
+        // Changed lines keep the add/delete colors.
+        let inserted = lines
+            .iter()
+            .flat_map(|l| l.spans.iter())
+            .find(|s| s.content.contains("new"))
+            .expect("inserted span");
+        assert_eq!(inserted.style.fg, Some(Color::Green));
+    }
+
+    #[test]
+    fn ui_snapshot_side_by_side_update_block() {
+        let changes = update_changes(
+            "example.txt",
+            "line one\nline two\nline three\n",
+            "line one\nline two changed\nline three\n",
+        );
+        let lines = create_diff_summary(
+            &changes,
+            PatchEventType::ApprovalRequest,
+            &PathBuf::from("/"),
+            120,
+            DiffLayout::SideBySide,
+        );
+        snapshot_lines("side_by_side_update_block", lines, 120, 6);
+    }
+
+    #[test]
+    fn side_by_side_falls_back_to_unified_when_narrow() {
+        let changes = update_changes(
+            "example.txt",
+            "line one\nline two\nline three\n",
+            "line one\nline two changed\nline three\n",
+        );
+        let render = |layout| {
+            create_diff_summary(
+                &changes,
+                PatchEventType::ApprovalRequest,
+                &PathBuf::from("/"),
+                80,
+                layout,
+            )
+        };
+        assert_eq!(render(DiffLayout::SideBySide), render(DiffLayout::Unified));
+    }
+
+    #[test]
+    fn parses_git_diff_into_files() {
+        let diff = "\
+diff --git a/src/x.rs b/src/x.rs
+index 1111111..2222222 100644
+--- a/src/x.rs
++++ b/src/x.rs
+@@ -1,2 +1,2 @@
+-a
++b
+ c
+diff --git a/logo.png b/logo.png
+index 3333333..4444444 100644
+Binary files a/logo.png and b/logo.png differ
+";
+        let files = parse_git_diff(diff);
+        let summary: Vec<(PathBuf, usize, usize, Vec<String>)> = files
+            .into_iter()
+            .map(|f| (f.path, f.added, f.removed, f.notes))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (PathBuf::from("src/x.rs"), 1, 1, Vec::new()),
+                (
+                    PathBuf::from("logo.png"),
+                    0,
+                    0,
+                    vec!["Binary files a/logo.png and b/logo.png differ".to_string()]
+                ),
+            ]
+        );
+
+        assert!(parse_git_diff("`/diff` — _not inside a git repository_").is_empty());
+    }
 }
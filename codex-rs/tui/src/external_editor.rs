@@ -0,0 +1,133 @@
+//! Editing the composer text in the user's `$VISUAL` or `$EDITOR`.
+
+use std::process::Command;
+use tempfile::Builder;
+
+#[derive(Debug)]
+pub enum ExternalEditorError {
+    NotConfigured,
+    InvalidCommand(String),
+    LaunchFailed(String),
+    EditorFailed(String),
+    IoError(String),
+}
+
+impl std::fmt::Display for ExternalEditorError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExternalEditorError::NotConfigured => {
+                write!(f, "set $VISUAL or $EDITOR to edit messages in an editor")
+            }
+            ExternalEditorError::InvalidCommand(cmd) => write!(f, "invalid editor command: {cmd}"),
+            ExternalEditorError::LaunchFailed(msg) => write!(f, "could not start editor: {msg}"),
+            ExternalEditorError::EditorFailed(msg) => write!(f, "editor exited with {msg}"),
+            ExternalEditorError::IoError(msg) => write!(f, "io error: {msg}"),
+        }
+    }
+}
+impl std::error::Error for ExternalEditorError {}
+
+/// The editor command line from `$VISUAL`, falling back to `$EDITOR`.
+pub fn editor_command() -> Result<Vec<String>, ExternalEditorError> {
+    editor_command_from(std::env::var("VISUAL").ok(), std::env::var("EDITOR").ok())
+}
+
+fn editor_command_from(
+    visual: Option<String>,
+    editor: Option<String>,
+) -> Result<Vec<String>, ExternalEditorError> {
+    let cmd = [visual, editor]
+        .into_iter()
+        .flatten()
+        .find(|cmd| !cmd.trim().is_empty())
+        .ok_or(ExternalEditorError::NotConfigured)?;
+    match shlex::split(&cmd) {
+        Some(argv) if !argv.is_empty() => Ok(argv),
+        _ => Err(ExternalEditorError::InvalidCommand(cmd)),
+    }
+}
+
+/// Opens `text` in the editor `command` and returns the edited text once the
+/// editor exits. The editor runs in the foreground with the terminal as-is,
+/// so the caller must hand the terminal over first.
+pub fn edit(command: &[String], text: &str) -> Result<String, ExternalEditorError> {
+    let Some((program, args)) = command.split_first() else {
+        return Err(ExternalEditorError::NotConfigured);
+    };
+    let file = Builder::new()
+        .prefix("codex-message-")
+        .suffix(".md")
+        .tempfile()
+        .map_err(|e| ExternalEditorError::IoError(e.to_string()))?;
+    std::fs::write(file.path(), text).map_err(|e| ExternalEditorError::IoError(e.to_string()))?;
+
+    let status = Command::new(program)
+        .args(args)
+        .arg(file.path())
+        .status()
+        .map_err(|e| ExternalEditorError::LaunchFailed(format!("{program}: {e}")))?;
+    if !status.success() {
+        return Err(ExternalEditorError::EditorFailed(status.to_string()));
+    }
+
+    let edited = std::fs::read_to_string(file.path())
+        .map_err(|e| ExternalEditorError::IoError(e.to_string()))?;
+    // Most editors end the file with a newline that was not part of the message.
+    Ok(edited
+        .strip_suffix('\n')
+        .map(|s| s.strip_suffix('\r').unwrap_or(s))
+        .unwrap_or(&edited)
+        .to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn visual_takes_precedence_over_editor() {
+        let cmd = editor_command_from(Some("code --wait".into()), Some("vim".into()));
+        assert_eq!(
+            cmd.ok(),
+            Some(vec!["code".to_string(), "--wait".to_string()])
+        );
+
+        let cmd = editor_command_from(Some(" ".into()), Some("'my editor' -f".into()));
+        assert_eq!(
+            cmd.ok(),
+            Some(vec!["my editor".to_string(), "-f".to_string()])
+        );
+
+        assert!(matches!(
+            editor_command_from(None, Some(String::new())),
+            Err(ExternalEditorError::NotConfigured)
+        ));
+        assert!(matches!(
+            editor_command_from(Some("vim 'unterminated".into()), None),
+            Err(ExternalEditorError::InvalidCommand(_))
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn edit_returns_the_saved_text() {
+        let sh = |script: &str| {
+            vec![
+                "sh".to_string(),
+                "-c".to_string(),
+                script.to_string(),
+                "sh".to_string(),
+            ]
+        };
+
+        let edited = edit(
+            &sh(r#"printf '%s and more\n' "$(cat "$1")" > "$1""#),
+            "draft",
+        );
+        assert_eq!(edited.ok(), Some("draft and more".to_string()));
+
+        let failed = edit(&sh("exit 3"), "draft");
+        assert!(matches!(failed, Err(ExternalEditorError::EditorFailed(_))));
+    }
+}
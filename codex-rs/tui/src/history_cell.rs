@@ -7,6 +7,8 @@ use crate::render::line_utils::prefix_lines;
 use crate::render::line_utils::push_owned_lines;
 use crate::slash_command::SlashCommand;
 use crate::text_formatting::format_and_truncate_tool_result;
+use crate::theme::ThemeStylize;
+use crate::theme::theme;
 use crate::wrapping::RtOptions;
 use crate::wrapping::word_wrap_line;
 use crate::wrapping::word_wrap_lines;
@@ -17,6 +19,7 @@ use codex_common::elapsed::format_duration;
 use codex_core::auth::get_auth_file;
 use codex_core::auth::try_read_auth_json;
 use codex_core::config::Config;
+use codex_core::config_types::DiffLayout;
 use codex_core::config_types::ReasoningSummaryFormat;
 use codex_core::plan_tool::PlanItemArg;
 use codex_core::plan_tool::StepStatus;
@@ -36,7 +39,6 @@ use itertools::Itertools;
 use mcp_types::EmbeddedResourceResource;
 use mcp_types::ResourceLink;
 use ratatui::prelude::*;
-use ratatui::style::Modifier;
 use ratatui::style::Style;
 use ratatui::style::Styled;
 use ratatui::style::Stylize;
@@ -58,6 +60,38 @@ pub(crate) struct CommandOutput {
     pub(crate) stdout: String,
     pub(crate) stderr: String,
     pub(crate) formatted_output: String,
+    /// Interleaved stdout and stderr, cut down with [`bounded_output`].
+    pub(crate) aggregated_output: String,
+}
+
+/// Most bytes of a command or tool call's output that are kept for viewing
+/// in full.
+const FULL_OUTPUT_MAX_BYTES: usize = 256 * 1024;
+
+/// `output` cut down to about [`FULL_OUTPUT_MAX_BYTES`] by dropping whole
+/// lines from its middle.
+pub(crate) fn bounded_output(output: &str) -> String {
+    if output.len() <= FULL_OUTPUT_MAX_BYTES {
+        return output.to_string();
+    }
+    let mut head_end = FULL_OUTPUT_MAX_BYTES / 2;
+    while !output.is_char_boundary(head_end) {
+        head_end -= 1;
+    }
+    let mut tail_start = output.len() - FULL_OUTPUT_MAX_BYTES / 2;
+    while !output.is_char_boundary(tail_start) {
+        tail_start += 1;
+    }
+    let head_end = output[..head_end].rfind('\n').map_or(head_end, |i| i + 1);
+    let tail_start = output[tail_start..]
+        .find('\n')
+        .map_or(tail_start, |i| tail_start + i + 1);
+    let omitted = output[head_end..tail_start].lines().count();
+    format!(
+        "{}… +{omitted} lines\n{}",
+        &output[..head_end],
+        &output[tail_start..]
+    )
 }
 
 #[derive(Clone, Debug)]
@@ -87,6 +121,12 @@ pub(crate) trait HistoryCell: std::fmt::Debug + Send + Sync {
     fn is_stream_continuation(&self) -> bool {
         false
     }
+
+    /// The complete output of the commands or tool call shown by this cell,
+    /// which the transcript lets the user view in a pager and copy.
+    fn full_output(&self) -> Option<String> {
+        None
+    }
 }
 
 #[derive(Debug)]
@@ -107,14 +147,14 @@ impl HistoryCell for UserHistoryCell {
         );
 
         for line in wrapped {
-            lines.push(vec!["▌".cyan().dim(), line.to_string().dim()].into());
+            lines.push(vec!["▌".accent().secondary(), line.to_string().secondary()].into());
         }
         lines
     }
 
     fn transcript_lines(&self) -> Vec<Line<'static>> {
         let mut lines: Vec<Line<'static>> = Vec::new();
-        lines.push("user".cyan().bold().into());
+        lines.push("user".accent().bold().into());
         lines.extend(self.message.lines().map(|l| l.to_string().into()));
         lines
     }
@@ -152,7 +192,7 @@ impl HistoryCell for AgentMessageCell {
     fn transcript_lines(&self) -> Vec<Line<'static>> {
         let mut out: Vec<Line<'static>> = Vec::new();
         if self.is_first_line {
-            out.push("codex".magenta().bold().into());
+            out.push("codex".brand().bold().into());
         }
         out.extend(self.lines.clone());
         out
@@ -174,6 +214,23 @@ impl HistoryCell for PlainHistoryCell {
     }
 }
 
+/// A finished MCP tool call: a truncated summary plus the complete result.
+#[derive(Debug)]
+pub(crate) struct CompletedMcpToolCallCell {
+    lines: Vec<Line<'static>>,
+    output: String,
+}
+
+impl HistoryCell for CompletedMcpToolCallCell {
+    fn display_lines(&self, _width: u16) -> Vec<Line<'static>> {
+        self.lines.clone()
+    }
+
+    fn full_output(&self) -> Option<String> {
+        Some(self.output.clone())
+    }
+}
+
 #[derive(Debug)]
 pub(crate) struct TranscriptOnlyHistoryCell {
     lines: Vec<Line<'static>>,
@@ -194,6 +251,7 @@ pub(crate) struct PatchHistoryCell {
     event_type: PatchEventType,
     changes: HashMap<PathBuf, FileChange>,
     cwd: PathBuf,
+    diff_layout: DiffLayout,
 }
 
 impl HistoryCell for PatchHistoryCell {
@@ -203,6 +261,7 @@ impl HistoryCell for PatchHistoryCell {
             self.event_type.clone(),
             &self.cwd,
             width as usize,
+            self.diff_layout,
         )
     }
 }
@@ -236,7 +295,7 @@ impl HistoryCell for ExecCell {
             let cmd_display = strip_bash_lc_and_escape(&call.command);
             for (i, part) in cmd_display.lines().enumerate() {
                 if i == 0 {
-                    lines.push(vec!["$ ".magenta(), part.to_string().into()].into());
+                    lines.push(vec!["$ ".brand(), part.to_string().into()].into());
                 } else {
                     lines.push(vec!["    ".into(), part.to_string().into()].into());
                 }
@@ -249,20 +308,33 @@ impl HistoryCell for ExecCell {
                     .map(format_duration)
                     .unwrap_or_else(|| "unknown".to_string());
                 let mut result: Line = if output.exit_code == 0 {
-                    Line::from("✓".green().bold())
+                    Line::from("✓".success().bold())
                 } else {
                     Line::from(vec![
-                        "✗".red().bold(),
+                        "✗".error().bold(),
                         format!(" ({})", output.exit_code).into(),
                     ])
                 };
-                result.push_span(format!(" • {duration}").dim());
+                result.push_span(format!(" • {duration}").secondary());
                 lines.push(result);
             }
             lines.push("".into());
         }
         lines
     }
+
+    fn full_output(&self) -> Option<String> {
+        let outputs: Vec<String> = self
+            .calls
+            .iter()
+            .filter_map(|call| {
+                let output = call.output.as_ref()?;
+                let command = strip_bash_lc_and_escape(&call.command);
+                Some(format!("$ {command}\n{}", output.aggregated_output))
+            })
+            .collect();
+        (!outputs.is_empty()).then(|| outputs.join("\n"))
+    }
 }
 
 impl ExecCell {
@@ -331,7 +403,7 @@ impl ExecCell {
                     "Read",
                     itertools::Itertools::intersperse(
                         names.into_iter().map(|n| n.into()),
-                        ", ".dim(),
+                        ", ".secondary(),
                     )
                     .collect(),
                 )]
@@ -350,7 +422,7 @@ impl ExecCell {
                                 "Search",
                                 match (query, path) {
                                     (Some(q), Some(p)) => {
-                                        vec![q.into(), " in ".dim(), p.into()]
+                                        vec![q.into(), " in ".secondary(), p.into()]
                                     }
                                     (Some(q), None) => vec![q.into()],
                                     _ => vec![cmd.into()],
@@ -366,7 +438,7 @@ impl ExecCell {
             };
             for (title, line) in call_lines {
                 let line = Line::from(line);
-                let initial_indent = Line::from(vec![title.cyan(), " ".into()]);
+                let initial_indent = Line::from(vec![title.accent(), " ".into()]);
                 let subsequent_indent = " ".repeat(initial_indent.width()).into();
                 let wrapped = word_wrap_line(
                     &line,
@@ -377,7 +449,11 @@ impl ExecCell {
                 push_owned_lines(&wrapped, &mut out_indented);
             }
         }
-        out.extend(prefix_lines(out_indented, "  └ ".dim(), "    ".into()));
+        out.extend(prefix_lines(
+            out_indented,
+            "  └ ".secondary(),
+            "    ".into(),
+        ));
         out
     }
 
@@ -390,8 +466,8 @@ impl ExecCell {
         };
         let success = call.output.as_ref().map(|o| o.exit_code == 0);
         let bullet = match success {
-            Some(true) => "•".green().bold(),
-            Some(false) => "•".red().bold(),
+            Some(true) => "•".success().bold(),
+            Some(false) => "•".error().bold(),
             None => spinner(call.start_time),
         };
         let title = if self.is_active() { "Running" } else { "Ran" };
@@ -436,11 +512,15 @@ impl ExecCell {
                 // Wrap the output.
                 for line in out.lines() {
                     let wrapped = textwrap::wrap(line, TwOptions::new(width as usize - 4));
-                    body_lines.extend(wrapped.into_iter().map(|l| Line::from(l.to_string().dim())));
+                    body_lines.extend(
+                        wrapped
+                            .into_iter()
+                            .map(|l| Line::from(l.to_string().secondary())),
+                    );
                 }
             }
         }
-        lines.extend(prefix_lines(body_lines, "  └ ".dim(), "    ".into()));
+        lines.extend(prefix_lines(body_lines, "  └ ".secondary(), "    ".into()));
         lines
     }
 }
@@ -488,6 +568,7 @@ impl ExecCell {
                     stdout: String::new(),
                     stderr: String::new(),
                     formatted_output: String::new(),
+                    aggregated_output: String::new(),
                 });
             }
         }
@@ -623,39 +704,39 @@ pub(crate) fn new_session_info(
 
         let mut lines: Vec<Line<'static>> = Vec::new();
         lines.push(Line::from(vec![
-            ">_ ".dim(),
+            ">_ ".secondary(),
             "You are using OpenAI Codex in".bold(),
-            format!(" {cwd_str}").dim(),
+            format!(" {cwd_str}").secondary(),
         ]));
-        lines.push(Line::from("".dim()));
+        lines.push(Line::from("".secondary()));
         lines.push(Line::from(
-            " To get started, describe a task or try one of these commands:".dim(),
+            " To get started, describe a task or try one of these commands:".secondary(),
         ));
-        lines.push(Line::from("".dim()));
+        lines.push(Line::from("".secondary()));
         if !has_agents_md {
             lines.push(Line::from(vec![
                 " /init".bold(),
-                format!(" - {}", SlashCommand::Init.description()).dim(),
+                format!(" - {}", SlashCommand::Init.description()).secondary(),
             ]));
         }
         lines.push(Line::from(vec![
             " /status".bold(),
-            format!(" - {}", SlashCommand::Status.description()).dim(),
+            format!(" - {}", SlashCommand::Status.description()).secondary(),
         ]));
         lines.push(Line::from(vec![
             " /approvals".bold(),
-            format!(" - {}", SlashCommand::Approvals.description()).dim(),
+            format!(" - {}", SlashCommand::Approvals.description()).secondary(),
         ]));
         lines.push(Line::from(vec![
             " /model".bold(),
-            format!(" - {}", SlashCommand::Model.description()).dim(),
+            format!(" - {}", SlashCommand::Model.description()).secondary(),
         ]));
         PlainHistoryCell { lines }
     } else if config.model == model {
         PlainHistoryCell { lines: Vec::new() }
     } else {
         let lines = vec![
-            "model changed:".magenta().bold().into(),
+            "model changed:".brand().bold().into(),
             format!("requested: {}", config.model).into(),
             format!("used: {model}").into(),
         ];
@@ -696,7 +777,7 @@ fn spinner(start_time: Option<Instant>) -> Span<'static> {
 }
 
 pub(crate) fn new_active_mcp_tool_call(invocation: McpInvocation) -> PlainHistoryCell {
-    let title_line = Line::from(vec!["tool".magenta(), " running...".dim()]);
+    let title_line = Line::from(vec!["tool".brand(), " running...".secondary()]);
     let lines: Vec<Line> = vec![title_line, format_mcp_invocation(invocation.clone())];
 
     PlainHistoryCell { lines }
@@ -761,19 +842,20 @@ pub(crate) fn new_completed_mcp_tool_call(
     let duration = format_duration(duration);
     let status_str = if success { "success" } else { "failed" };
     let title_line = Line::from(vec![
-        "tool".magenta(),
+        "tool".brand(),
         " ".into(),
         if success {
-            status_str.green()
+            status_str.success()
         } else {
-            status_str.red()
+            status_str.error()
         },
-        format!(", duration: {duration}").dim(),
+        format!(", duration: {duration}").secondary(),
     ]);
 
     let mut lines: Vec<Line<'static>> = Vec::new();
     lines.push(title_line);
     lines.push(format_mcp_invocation(invocation));
+    let mut output: Vec<String> = Vec::new();
 
     match result {
         Ok(mcp_types::CallToolResult { content, .. }) => {
@@ -783,11 +865,16 @@ pub(crate) fn new_completed_mcp_tool_call(
                 for tool_call_result in content {
                     let line_text = match tool_call_result {
                         mcp_types::ContentBlock::TextContent(text) => {
-                            format_and_truncate_tool_result(
-                                &text.text,
-                                TOOL_CALL_MAX_LINES,
-                                num_cols,
-                            )
+                            output.push(bounded_output(&text.text));
+                            lines.push(Line::styled(
+                                format_and_truncate_tool_result(
+                                    &text.text,
+                                    TOOL_CALL_MAX_LINES,
+                                    num_cols,
+                                ),
+                                theme().secondary,
+                            ));
+                            continue;
                         }
                         mcp_types::ContentBlock::ImageContent(_) => {
                             // TODO show images even if they're not the first result, will require a refactor of `CompletedMcpToolCall`
@@ -805,19 +892,21 @@ pub(crate) fn new_completed_mcp_tool_call(
                             format!("link: {uri}")
                         }
                     };
-                    lines.push(Line::styled(
-                        line_text,
-                        Style::default().add_modifier(Modifier::DIM),
-                    ));
+                    output.push(line_text.clone());
+                    lines.push(Line::styled(line_text, theme().secondary));
                 }
             }
         }
         Err(e) => {
-            lines.push(vec!["Error: ".red().bold(), e.into()].into());
+            output.push(format!("Error: {e}"));
+            lines.push(vec!["Error: ".error().bold(), e.into()].into());
         }
     };
 
-    Box::new(PlainHistoryCell { lines })
+    Box::new(CompletedMcpToolCallCell {
+        lines,
+        output: output.join("\n"),
+    })
 }
 
 pub(crate) fn new_status_output(
@@ -826,7 +915,7 @@ pub(crate) fn new_status_output(
     session_id: &Option<ConversationId>,
 ) -> PlainHistoryCell {
     let mut lines: Vec<Line<'static>> = Vec::new();
-    lines.push("/status".magenta().into());
+    lines.push("/status".brand().into());
 
     let config_entries = create_config_summary_entries(config);
     let lookup = |k: &str| -> String {
@@ -990,7 +1079,7 @@ pub(crate) fn new_status_output(
 /// Render a summary of configured MCP servers from the current `Config`.
 pub(crate) fn empty_mcp_output() -> PlainHistoryCell {
     let lines: Vec<Line<'static>> = vec![
-        "/mcp".magenta().into(),
+        "/mcp".brand().into(),
         "".into(),
         vec!["🔌  ".into(), "MCP Tools".bold()].into(),
         "".into(),
@@ -1000,7 +1089,7 @@ pub(crate) fn empty_mcp_output() -> PlainHistoryCell {
             "\u{1b}]8;;https://github.com/openai/codex/blob/main/docs/config.md#mcp_servers\u{7}MCP docs\u{1b}]8;;\u{7}".underlined(),
             " to configure them.".into(),
         ])
-        .style(Style::default().add_modifier(Modifier::DIM)),
+        .style(theme().secondary),
     ];
 
     PlainHistoryCell { lines }
@@ -1012,7 +1101,7 @@ pub(crate) fn new_mcp_tools_output(
     tools: std::collections::HashMap<String, mcp_types::Tool>,
 ) -> PlainHistoryCell {
     let mut lines: Vec<Line<'static>> = vec![
-        "/mcp".magenta().into(),
+        "/mcp".brand().into(),
         "".into(),
         vec!["🔌  ".into(), "MCP Tools".bold()].into(),
         "".into(),
@@ -1057,12 +1146,13 @@ pub(crate) fn new_error_event(message: String) -> PlainHistoryCell {
     // before the text. VS16 is intentionally omitted to keep spacing tighter
     // in terminals like Ghostty.
     let lines: Vec<Line<'static>> =
-        vec![vec![padded_emoji("🖐").red().bold(), " ".into(), message.into()].into()];
+        vec![vec![padded_emoji("🖐").error().bold(), " ".into(), message.into()].into()];
     PlainHistoryCell { lines }
 }
 
 pub(crate) fn new_stream_error_event(message: String) -> PlainHistoryCell {
-    let lines: Vec<Line<'static>> = vec![vec![padded_emoji("⚠️").into(), message.dim()].into()];
+    let lines: Vec<Line<'static>> =
+        vec![vec![padded_emoji("⚠️").into(), message.secondary()].into()];
     PlainHistoryCell { lines }
 }
 
@@ -1084,15 +1174,15 @@ impl HistoryCell for PlanUpdateCell {
             let wrap_width = width.saturating_sub(4).max(1) as usize;
             textwrap::wrap(text, wrap_width)
                 .into_iter()
-                .map(|s| s.to_string().dim().italic().into())
+                .map(|s| s.to_string().secondary().italic().into())
                 .collect()
         };
 
         let render_step = |status: &StepStatus, text: &str| -> Vec<Line<'static>> {
             let (box_str, step_style) = match status {
-                StepStatus::Completed => ("✔ ", Style::default().crossed_out().dim()),
-                StepStatus::InProgress => ("□ ", Style::default().cyan().bold()),
-                StepStatus::Pending => ("□ ", Style::default().dim()),
+                StepStatus::Completed => ("✔ ", Style::default().crossed_out().secondary()),
+                StepStatus::InProgress => ("□ ", theme().accent.bold()),
+                StepStatus::Pending => ("□ ", theme().secondary),
             };
             let wrap_width = (width as usize)
                 .saturating_sub(4)
@@ -1120,7 +1210,7 @@ impl HistoryCell for PlanUpdateCell {
         };
 
         if self.plan.is_empty() {
-            indented_lines.push(Line::from("(no steps provided)".dim().italic()));
+            indented_lines.push(Line::from("(no steps provided)".secondary().italic()));
         } else {
             for PlanItemArg { step, status } in self.plan.iter() {
                 indented_lines.extend(render_step(status, step));
@@ -1139,11 +1229,13 @@ pub(crate) fn new_patch_event(
     event_type: PatchEventType,
     changes: HashMap<PathBuf, FileChange>,
     cwd: &Path,
+    diff_layout: DiffLayout,
 ) -> PatchHistoryCell {
     PatchHistoryCell {
         event_type,
         changes,
         cwd: cwd.to_path_buf(),
+        diff_layout,
     }
 }
 
@@ -1151,7 +1243,7 @@ pub(crate) fn new_patch_apply_failure(stderr: String) -> PlainHistoryCell {
     let mut lines: Vec<Line<'static>> = Vec::new();
 
     // Failure title
-    lines.push(Line::from("✘ Failed to apply patch".magenta().bold()));
+    lines.push(Line::from("✘ Failed to apply patch".brand().bold()));
 
     if !stderr.trim().is_empty() {
         lines.extend(output_lines(
@@ -1160,6 +1252,7 @@ pub(crate) fn new_patch_apply_failure(stderr: String) -> PlainHistoryCell {
                 stdout: String::new(),
                 stderr,
                 formatted_output: String::new(),
+                aggregated_output: String::new(),
             }),
             true,
             true,
@@ -1180,7 +1273,7 @@ pub(crate) fn new_proposed_command(command: &[String]) -> PlainHistoryCell {
     lines.push(Line::from(vec!["• ".into(), "Proposed Command".bold()]));
 
     let highlighted_lines = crate::render::highlight::highlight_bash_to_lines(&cmd);
-    let initial_prefix: Span<'static> = "  └ ".dim();
+    let initial_prefix: Span<'static> = "  └ ".secondary();
     let subsequent_prefix: Span<'static> = "    ".into();
     lines.extend(prefix_lines(
         highlighted_lines,
@@ -1196,7 +1289,7 @@ pub(crate) fn new_reasoning_block(
     config: &Config,
 ) -> TranscriptOnlyHistoryCell {
     let mut lines: Vec<Line<'static>> = Vec::new();
-    lines.push(Line::from("thinking".magenta().italic()));
+    lines.push(Line::from("thinking".brand().italic()));
     append_markdown(&full_reasoning_buffer, &mut lines, config);
     TranscriptOnlyHistoryCell { lines }
 }
@@ -1224,11 +1317,11 @@ pub(crate) fn new_reasoning_summary_block(
                     let summary_buffer = full_reasoning_buffer[after_close_idx..].to_string();
 
                     let mut header_lines: Vec<Line<'static>> = Vec::new();
-                    header_lines.push(Line::from("Thinking".magenta().italic()));
+                    header_lines.push(Line::from("Thinking".brand().italic()));
                     append_markdown(&header_buffer, &mut header_lines, config);
 
                     let mut summary_lines: Vec<Line<'static>> = Vec::new();
-                    summary_lines.push(Line::from("Thinking".magenta().bold()));
+                    summary_lines.push(Line::from("Thinking".brand().bold()));
                     append_markdown(&summary_buffer, &mut summary_lines, config);
 
                     return vec![
@@ -1280,7 +1373,7 @@ fn output_lines(
         };
         line.spans.insert(0, prefix.into());
         line.spans.iter_mut().for_each(|span| {
-            span.style = span.style.add_modifier(Modifier::DIM);
+            span.style = span.style.patch(theme().secondary);
         });
         out.push(line);
     }
@@ -1303,7 +1396,7 @@ fn output_lines(
             line.spans.insert(0, "    ".into());
         }
         line.spans.iter_mut().for_each(|span| {
-            span.style = span.style.add_modifier(Modifier::DIM);
+            span.style = span.style.patch(theme().secondary);
         });
         out.push(line);
     }
@@ -1322,11 +1415,11 @@ fn format_mcp_invocation<'a>(invocation: McpInvocation) -> Line<'a> {
         .unwrap_or_default();
 
     let invocation_spans = vec![
-        invocation.server.clone().cyan(),
+        invocation.server.clone().accent(),
         ".".into(),
-        invocation.tool.clone().cyan(),
+        invocation.tool.clone().accent(),
         "(".into(),
-        args_str.dim(),
+        args_str.secondary(),
         ")".into(),
     ];
     invocation_spans.into()
@@ -1398,6 +1491,7 @@ mod tests {
                 stdout: String::new(),
                 stderr: String::new(),
                 formatted_output: String::new(),
+                aggregated_output: String::new(),
             },
             Duration::from_millis(1),
         );
@@ -1429,6 +1523,7 @@ mod tests {
                 stdout: String::new(),
                 stderr: String::new(),
                 formatted_output: String::new(),
+                aggregated_output: String::new(),
             },
             Duration::from_millis(1),
         );
@@ -1450,6 +1545,7 @@ mod tests {
                 stdout: String::new(),
                 stderr: String::new(),
                 formatted_output: String::new(),
+                aggregated_output: String::new(),
             },
             Duration::from_millis(1),
         );
@@ -1471,6 +1567,7 @@ mod tests {
                 stdout: String::new(),
                 stderr: String::new(),
                 formatted_output: String::new(),
+                aggregated_output: String::new(),
             },
             Duration::from_millis(1),
         );
@@ -1480,6 +1577,62 @@ mod tests {
         insta::assert_snapshot!(rendered);
     }
 
+    #[test]
+    fn exec_cell_full_output_keeps_untruncated_output() {
+        let mut cell = ExecCell::new(ExecCall {
+            call_id: "c1".to_string(),
+            command: vec!["bash".into(), "-lc".into(), "seq 1 100".into()],
+            parsed: Vec::new(),
+            output: None,
+            start_time: Some(Instant::now()),
+            duration: None,
+        });
+        assert_eq!(cell.full_output(), None);
+
+        let aggregated: String = (1..=100).map(|i| format!("{i}\n")).collect();
+        cell.complete_call(
+            "c1",
+            CommandOutput {
+                exit_code: 0,
+                stdout: aggregated.clone(),
+                stderr: String::new(),
+                formatted_output: aggregated.clone(),
+                aggregated_output: bounded_output(&aggregated),
+            },
+            Duration::from_millis(1),
+        );
+
+        assert_eq!(
+            cell.full_output(),
+            Some(format!("$ seq 1 100\n{aggregated}"))
+        );
+    }
+
+    #[test]
+    fn bounded_output_drops_whole_lines_from_the_middle() {
+        let line = "x".repeat(99);
+        let output: String = (0..FULL_OUTPUT_MAX_BYTES / 50)
+            .map(|_| format!("{line}\n"))
+            .collect();
+
+        let bounded = bounded_output(&output);
+
+        assert!(bounded.len() <= FULL_OUTPUT_MAX_BYTES + 32);
+        let omitted = bounded
+            .lines()
+            .find(|l| l.starts_with("… +"))
+            .expect("omission marker");
+        let kept = bounded.lines().filter(|l| *l == line).count();
+        let omitted: usize = omitted
+            .trim_start_matches("… +")
+            .trim_end_matches(" lines")
+            .parse()
+            .expect("count");
+        assert_eq!(kept + omitted, output.lines().count());
+        assert!(bounded.lines().all(|l| l == line || l.starts_with("… +")));
+        assert_eq!(bounded_output("short\n"), "short\n");
+    }
+
     #[test]
     fn coalesced_reads_dedupe_names() {
         let mut cell = ExecCell::new(ExecCall {
@@ -1510,6 +1663,7 @@ mod tests {
                 stdout: String::new(),
                 stderr: String::new(),
                 formatted_output: String::new(),
+                aggregated_output: String::new(),
             },
             Duration::from_millis(1),
         );
@@ -1539,6 +1693,7 @@ mod tests {
                 stdout: String::new(),
                 stderr: String::new(),
                 formatted_output: String::new(),
+                aggregated_output: String::new(),
             },
             Duration::from_millis(1),
         );
@@ -1568,6 +1723,7 @@ mod tests {
                 stdout: String::new(),
                 stderr: String::new(),
                 formatted_output: String::new(),
+                aggregated_output: String::new(),
             },
             Duration::from_millis(1),
         );
@@ -1596,6 +1752,7 @@ mod tests {
                 stdout: String::new(),
                 stderr: String::new(),
                 formatted_output: String::new(),
+                aggregated_output: String::new(),
             },
             Duration::from_millis(1),
         );
@@ -1623,6 +1780,7 @@ mod tests {
                 stdout: String::new(),
                 stderr: String::new(),
                 formatted_output: String::new(),
+                aggregated_output: String::new(),
             },
             Duration::from_millis(1),
         );
@@ -1651,6 +1809,7 @@ mod tests {
                 stdout: String::new(),
                 stderr: String::new(),
                 formatted_output: String::new(),
+                aggregated_output: String::new(),
             },
             Duration::from_millis(1),
         );
@@ -1683,6 +1842,7 @@ mod tests {
                 stdout: String::new(),
                 stderr,
                 formatted_output: String::new(),
+                aggregated_output: String::new(),
             },
             Duration::from_millis(1),
         );
@@ -1727,6 +1887,7 @@ mod tests {
                 stdout: String::new(),
                 stderr,
                 formatted_output: String::new(),
+                aggregated_output: String::new(),
             },
             Duration::from_millis(5),
         );
@@ -1,4 +1,8 @@
-use ratatui::style::Color;
+use crate::keymap::keymap;
+use crate::theme::theme;
+use codex_core::config_types::KeyAction;
+use codex_core::config_types::KeyCodeSpec;
+use codex_core::config_types::KeySpec;
 use ratatui::style::Style;
 use ratatui::text::Span;
 use std::fmt::Display;
@@ -25,7 +29,7 @@ const SHIFT_PREFIX: &str = "⇧";
 const SHIFT_PREFIX: &str = "Shift+";
 
 fn key_hint_style() -> Style {
-    Style::default().fg(Color::Cyan)
+    theme().accent
 }
 
 fn modifier_span(prefix: &str, key: impl Display) -> Span<'static> {
@@ -47,3 +51,60 @@ pub(crate) fn shift(key: impl Display) -> Span<'static> {
 pub(crate) fn plain(key: impl Display) -> Span<'static> {
     Span::styled(format!("{key}"), key_hint_style())
 }
+
+/// Hint for the first key bound to `action`, or `None` if it is unbound.
+/// Unless `enhanced_keys_supported`, keys that only terminals with enhanced
+/// keyboard reporting can tell apart from plain Enter are skipped.
+pub(crate) fn action(action: KeyAction, enhanced_keys_supported: bool) -> Option<Span<'static>> {
+    keymap()
+        .keys(action)
+        .iter()
+        .find(|key| {
+            enhanced_keys_supported || key.code != KeyCodeSpec::Enter || !(key.ctrl || key.shift)
+        })
+        .map(|key| Span::styled(label(key), key_hint_style()))
+}
+
+/// Unstyled text of the first key bound to each of `actions`, joined by `/`.
+pub(crate) fn actions_label(actions: &[KeyAction]) -> String {
+    actions
+        .iter()
+        .filter_map(|action| keymap().keys(*action).first())
+        .map(label)
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn label(key: &KeySpec) -> String {
+    let mut label = String::new();
+    for (set, prefix) in [
+        (key.ctrl, CTRL_PREFIX),
+        (key.alt, ALT_PREFIX),
+        (key.shift, SHIFT_PREFIX),
+    ] {
+        if set {
+            label.push_str(prefix);
+        }
+    }
+    match key.code {
+        KeyCodeSpec::Char(' ') => label.push_str("Space"),
+        KeyCodeSpec::Char(c) if key.ctrl || key.alt => label.push(c.to_ascii_uppercase()),
+        KeyCodeSpec::Char(c) => label.push(c),
+        KeyCodeSpec::Enter => label.push('⏎'),
+        KeyCodeSpec::Esc => label.push_str("Esc"),
+        KeyCodeSpec::Tab => label.push_str("Tab"),
+        KeyCodeSpec::Backspace => label.push_str("Backspace"),
+        KeyCodeSpec::Delete => label.push_str("Del"),
+        KeyCodeSpec::Insert => label.push_str("Ins"),
+        KeyCodeSpec::Up => label.push('↑'),
+        KeyCodeSpec::Down => label.push('↓'),
+        KeyCodeSpec::Left => label.push('←'),
+        KeyCodeSpec::Right => label.push('→'),
+        KeyCodeSpec::Home => label.push_str("Home"),
+        KeyCodeSpec::End => label.push_str("End"),
+        KeyCodeSpec::PageUp => label.push_str("PgUp"),
+        KeyCodeSpec::PageDown => label.push_str("PgDn"),
+        KeyCodeSpec::F(n) => label.push_str(&format!("F{n}")),
+    }
+    label
+}
@@ -0,0 +1,193 @@
+//! Keys bound to TUI actions, chosen by `[tui.keybindings]`.
+//!
+//! Key handlers ask [`keymap()`] which action a key event triggers instead of
+//! matching on hardcoded keys, and key hints are rendered from the same
+//! bindings by [`crate::key_hint`].
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use codex_core::config_types::KeyAction;
+use codex_core::config_types::KeyBindings;
+use codex_core::config_types::KeyCodeSpec;
+use codex_core::config_types::KeySpec;
+use crossterm::event::KeyCode;
+use crossterm::event::KeyEvent;
+use crossterm::event::KeyModifiers;
+
+static KEYMAP: OnceLock<KeyMap> = OnceLock::new();
+
+/// Selects the key bindings for the rest of the process. Must be called
+/// before any key is handled; later calls have no effect.
+pub(crate) fn init(bindings: &KeyBindings) {
+    let _ = KEYMAP.set(KeyMap::new(bindings));
+}
+
+/// The key bindings selected with [`init`], or the defaults if none were.
+pub(crate) fn keymap() -> &'static KeyMap {
+    KEYMAP.get_or_init(|| KeyMap::new(&KeyBindings::default()))
+}
+
+#[derive(Debug)]
+pub(crate) struct KeyMap {
+    keys: HashMap<KeyAction, Vec<KeySpec>>,
+}
+
+impl KeyMap {
+    pub(crate) fn new(bindings: &KeyBindings) -> Self {
+        Self {
+            keys: KeyAction::ALL
+                .into_iter()
+                .map(|action| (action, bindings.keys(action)))
+                .collect(),
+        }
+    }
+
+    /// Keys bound to `action`, in the order they were configured.
+    pub(crate) fn keys(&self, action: KeyAction) -> &[KeySpec] {
+        self.keys
+            .get(&action)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn matches(&self, action: KeyAction, event: &KeyEvent) -> bool {
+        self.action(&[action], event).is_some()
+    }
+
+    /// The first of `actions` bound to `event`. A key bound without Shift
+    /// also matches the event with Shift held, unless another of `actions`
+    /// is bound to exactly that combination.
+    pub(crate) fn action(&self, actions: &[KeyAction], event: &KeyEvent) -> Option<KeyAction> {
+        let (code, modifiers) = normalize(event)?;
+        let find = |ignore_shift: bool| {
+            actions.iter().copied().find(|action| {
+                self.keys(*action)
+                    .iter()
+                    .any(|key| key_matches(key, code, modifiers, ignore_shift))
+            })
+        };
+        find(false).or_else(|| find(true))
+    }
+}
+
+/// The event as a key code and modifiers comparable with a [`KeySpec`].
+fn normalize(event: &KeyEvent) -> Option<(KeyCodeSpec, KeyModifiers)> {
+    let mut modifiers = event.modifiers;
+    let code = match event.code {
+        KeyCode::Char(c) => {
+            // Shift is already reflected in the character itself.
+            modifiers.remove(KeyModifiers::SHIFT);
+            if modifiers.contains(KeyModifiers::CONTROL) {
+                KeyCodeSpec::Char(c.to_ascii_lowercase())
+            } else {
+                KeyCodeSpec::Char(c)
+            }
+        }
+        KeyCode::Enter => KeyCodeSpec::Enter,
+        KeyCode::Esc => KeyCodeSpec::Esc,
+        KeyCode::Tab => KeyCodeSpec::Tab,
+        KeyCode::BackTab => {
+            modifiers.insert(KeyModifiers::SHIFT);
+            KeyCodeSpec::Tab
+        }
+        KeyCode::Backspace => KeyCodeSpec::Backspace,
+        KeyCode::Delete => KeyCodeSpec::Delete,
+        KeyCode::Insert => KeyCodeSpec::Insert,
+        KeyCode::Up => KeyCodeSpec::Up,
+        KeyCode::Down => KeyCodeSpec::Down,
+        KeyCode::Left => KeyCodeSpec::Left,
+        KeyCode::Right => KeyCodeSpec::Right,
+        KeyCode::Home => KeyCodeSpec::Home,
+        KeyCode::End => KeyCodeSpec::End,
+        KeyCode::PageUp => KeyCodeSpec::PageUp,
+        KeyCode::PageDown => KeyCodeSpec::PageDown,
+        KeyCode::F(n) => KeyCodeSpec::F(n),
+        _ => return None,
+    };
+    Some((code, modifiers))
+}
+
+fn key_matches(
+    key: &KeySpec,
+    code: KeyCodeSpec,
+    modifiers: KeyModifiers,
+    ignore_shift: bool,
+) -> bool {
+    let mut expected = KeyModifiers::NONE;
+    for (set, modifier) in [
+        (key.ctrl, KeyModifiers::CONTROL),
+        (key.alt, KeyModifiers::ALT),
+        (key.shift, KeyModifiers::SHIFT),
+    ] {
+        if set {
+            expected.insert(modifier);
+        }
+    }
+    let mut modifiers = modifiers;
+    if ignore_shift && !key.shift {
+        modifiers.remove(KeyModifiers::SHIFT);
+    }
+    key.code == code && modifiers == expected
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_core::config_types::SubmitMode;
+    use pretty_assertions::assert_eq;
+
+    fn press(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    #[test]
+    fn shifted_keys_fall_back_to_unshifted_bindings() {
+        let keymap = KeyMap::new(&KeyBindings::default());
+        let actions = [KeyAction::Submit, KeyAction::Newline];
+        assert_eq!(
+            keymap.action(&actions, &press(KeyCode::Enter, KeyModifiers::NONE)),
+            Some(KeyAction::Submit)
+        );
+        assert_eq!(
+            keymap.action(&actions, &press(KeyCode::Enter, KeyModifiers::SHIFT)),
+            Some(KeyAction::Newline)
+        );
+        assert!(keymap.matches(
+            KeyAction::DeleteBackward,
+            &press(KeyCode::Backspace, KeyModifiers::SHIFT)
+        ));
+        assert!(keymap.matches(
+            KeyAction::PreviousFile,
+            &press(KeyCode::BackTab, KeyModifiers::SHIFT)
+        ));
+        assert!(keymap.matches(
+            KeyAction::Transcript,
+            &press(
+                KeyCode::Char('T'),
+                KeyModifiers::CONTROL | KeyModifiers::SHIFT
+            )
+        ));
+        assert!(!keymap.matches(
+            KeyAction::Submit,
+            &press(KeyCode::Enter, KeyModifiers::CONTROL)
+        ));
+    }
+
+    #[test]
+    fn ctrl_enter_mode_swaps_submit_and_newline() {
+        let keymap = KeyMap::new(&KeyBindings {
+            submit_mode: SubmitMode::CtrlEnter,
+            ..KeyBindings::default()
+        });
+        let actions = [KeyAction::Submit, KeyAction::Newline];
+        assert_eq!(
+            keymap.action(&actions, &press(KeyCode::Enter, KeyModifiers::NONE)),
+            Some(KeyAction::Newline)
+        );
+        assert_eq!(
+            keymap.action(&actions, &press(KeyCode::Enter, KeyModifiers::CONTROL)),
+            Some(KeyAction::Submit)
+        );
+    }
+}
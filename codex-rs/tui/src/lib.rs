@@ -29,25 +29,30 @@ mod app;
 mod app_backtrack;
 mod app_event;
 mod app_event_sender;
+mod background_processes_widget;
 mod backtrack_helpers;
 mod bottom_pane;
 mod chatwidget;
 mod citation_regex;
 mod cli;
+mod clipboard_copy;
 mod clipboard_paste;
 pub mod custom_terminal;
 mod diff_render;
 mod exec_command;
+mod external_editor;
 mod file_search;
 mod get_git_diff;
 mod history_cell;
 pub mod insert_history;
 mod key_hint;
+mod keymap;
 pub mod live_wrap;
 mod markdown;
 mod markdown_stream;
 pub mod onboarding;
 mod pager_overlay;
+mod pager_search;
 mod render;
 mod resume_picker;
 mod session_log;
@@ -55,7 +60,9 @@ mod shimmer;
 mod slash_command;
 mod status_indicator_widget;
 mod streaming;
+mod tabs;
 mod text_formatting;
+mod theme;
 mod tui;
 mod user_approval_widget;
 mod version;
@@ -240,6 +247,8 @@ async fn run_ratatui_app(
 ) -> color_eyre::Result<codex_core::protocol::TokenUsage> {
     let mut config = config;
     color_eyre::install()?;
+    theme::init(&config.tui);
+    keymap::init(&config.tui.keybindings);
 
     // Forward panic reports through tracing so they appear in the UI status
     // line, but do not swallow the default/color-eyre panic handler.
@@ -259,6 +268,7 @@ async fn run_ratatui_app(
     // within the TUI scrollback. Building spans keeps styling consistent.
     #[cfg(not(debug_assertions))]
     if let Some(latest_version) = updates::get_upgrade_version(&config) {
+        use crate::theme::ThemeStylize as _;
         use ratatui::style::Stylize as _;
         use ratatui::text::Line;
 
@@ -268,7 +278,7 @@ async fn run_ratatui_app(
 
         let mut lines: Vec<Line<'static>> = Vec::new();
         lines.push(Line::from(vec![
-            "✨⬆️ Update available!".bold().cyan(),
+            "✨⬆️ Update available!".bold().accent(),
             " ".into(),
             format!("{current_version} -> {latest_version}.").into(),
         ]));
@@ -277,7 +287,7 @@ async fn run_ratatui_app(
             let npm_cmd = "npm install -g @openai/codex@latest";
             lines.push(Line::from(vec![
                 "Run ".into(),
-                npm_cmd.cyan(),
+                npm_cmd.accent(),
                 " to update.".into(),
             ]));
         } else if cfg!(target_os = "macos")
@@ -286,13 +296,13 @@ async fn run_ratatui_app(
             let brew_cmd = "brew upgrade codex";
             lines.push(Line::from(vec![
                 "Run ".into(),
-                brew_cmd.cyan(),
+                brew_cmd.accent(),
                 " to update.".into(),
             ]));
         } else {
             lines.push(Line::from(vec![
                 "See ".into(),
-                "https://github.com/openai/codex/releases/latest".cyan(),
+                "https://github.com/openai/codex/releases/latest".accent(),
                 " for the latest releases and installation options.".into(),
             ]));
         }
@@ -1,4 +1,5 @@
 use crate::citation_regex::CITATION_REGEX;
+use crate::render::highlight::highlight_code_to_lines;
 use codex_core::config::Config;
 use codex_core::config_types::UriBasedFileOpener;
 use ratatui::text::Line;
@@ -24,8 +25,8 @@ fn append_markdown_with_opener_and_cwd(
     // when formatted by the markdown renderer/highlighter. To preserve code
     // block content exactly, split the source into "text" and "code" segments:
     // - Render non-code text through `tui_markdown` (with citation rewrite).
-    // - Render code block content verbatim as plain lines without additional
-    //   formatting, preserving leading spaces.
+    // - Render code block content with its exact text, preserving leading
+    //   spaces, highlighted when the fence names a supported language.
     for seg in split_text_and_fences(markdown_source) {
         match seg {
             Segment::Text(s) => {
@@ -33,9 +34,17 @@ fn append_markdown_with_opener_and_cwd(
                 let rendered = tui_markdown::from_str(&processed);
                 crate::render::line_utils::push_owned_lines(&rendered.lines, lines);
             }
-            Segment::Code { content, .. } => {
+            Segment::Code { lang, content } => {
+                // The highlighter styles byte ranges of the source and never
+                // changes its text, so whitespace is preserved either way.
+                if let Some(highlighted) = lang
+                    .as_deref()
+                    .and_then(|lang| highlight_code_to_lines(lang, &content))
+                {
+                    lines.extend(highlighted);
+                    continue;
+                }
                 // Emit the code content exactly as-is, line by line.
-                // We don't attempt syntax highlighting to avoid whitespace bugs.
                 for line in content.split_inclusive('\n') {
                     // split_inclusive keeps the trailing \n; we want lines without it.
                     let line = if let Some(stripped) = line.strip_suffix('\n') {
@@ -101,7 +110,7 @@ fn rewrite_file_citations<'a>(
 
 // Minimal code block splitting.
 // - Recognizes fenced blocks opened by ``` or ~~~ (allowing leading whitespace).
-//   The opening fence may include a language string which we ignore.
+//   The opening fence may include a language string, used for highlighting.
 //   The closing fence must be on its own line (ignoring surrounding whitespace).
 // - Additionally recognizes indented code blocks that begin after a blank line
 //   with a line starting with at least 4 spaces or a tab, and continue for
@@ -109,7 +118,7 @@ fn rewrite_file_citations<'a>(
 enum Segment {
     Text(String),
     Code {
-        _lang: Option<String>,
+        lang: Option<String>,
         content: String,
     },
 }
@@ -201,7 +210,7 @@ fn split_text_and_fences(src: &str) -> Vec<Segment> {
                     if trimmed == fence_token {
                         // End code block: emit segment without fences
                         segments.push(Segment::Code {
-                            _lang: code_lang.take(),
+                            lang: code_lang.take(),
                             content: code_content.clone(),
                         });
                         code_content.clear();
@@ -226,7 +235,7 @@ fn split_text_and_fences(src: &str) -> Vec<Segment> {
                     } else {
                         // Close the indented code block and reprocess this line as normal text.
                         segments.push(Segment::Code {
-                            _lang: None,
+                            lang: None,
                             content: code_content.clone(),
                         });
                         code_content.clear();
@@ -243,7 +252,7 @@ fn split_text_and_fences(src: &str) -> Vec<Segment> {
     if code_mode != CodeMode::None {
         // Unterminated code fence: treat accumulated content as a code segment.
         segments.push(Segment::Code {
-            _lang: code_lang.take(),
+            lang: code_lang.take(),
             content: code_content.clone(),
         });
     } else if !curr_text.is_empty() {
@@ -257,6 +266,42 @@ fn split_text_and_fences(src: &str) -> Vec<Segment> {
 mod tests {
     use super::*;
     use pretty_assertions::assert_eq;
+    use ratatui::style::Modifier;
+    use ratatui::style::Style;
+
+    /// Renders `lines` as text with each styled span wrapped in
+    /// `[color,modifiers]...[/]`.
+    fn styled_text(lines: &[Line<'_>]) -> String {
+        fn tag(style: Style) -> Option<String> {
+            let mut parts = Vec::new();
+            if let Some(fg) = style.fg {
+                parts.push(format!("{fg:?}").to_lowercase());
+            }
+            for (modifier, name) in [
+                (Modifier::BOLD, "bold"),
+                (Modifier::DIM, "dim"),
+                (Modifier::ITALIC, "italic"),
+            ] {
+                if style.add_modifier.contains(modifier) {
+                    parts.push(name.to_string());
+                }
+            }
+            (!parts.is_empty()).then(|| parts.join(","))
+        }
+        lines
+            .iter()
+            .map(|line| {
+                line.spans
+                    .iter()
+                    .map(|span| match tag(span.style) {
+                        Some(tag) => format!("[{tag}]{}[/]", span.content),
+                        None => span.content.to_string(),
+                    })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
 
     #[test]
     fn citation_is_rewritten_with_absolute_path() {
@@ -578,4 +623,78 @@ Loose item"#;
             "did not expect a split into ['1.', 'Tight item']; got: {lines:?}"
         );
     }
+
+    #[test]
+    fn fenced_code_blocks_are_highlighted_by_language() {
+        let src = "```rust\n/// Doc.\n#[derive(Debug)]\npub struct Config {\n    name: String, // the name\n}\n```\n```json\n{\n  \"name\": \"codex\",\n  \"ok\": true\n}\n```\n```toml\n[tools]\nmodel = \"o3\"\n```\n```diff\n--- a/x\n+++ b/x\n@@ -1,2 +1,2 @@\n keep\n-old\n+new\n```\n";
+        let mut out = Vec::new();
+        append_markdown_with_opener_and_cwd(
+            src,
+            &mut out,
+            UriBasedFileOpener::None,
+            Path::new("/"),
+        );
+        insta::assert_snapshot!(styled_text(&out));
+    }
+
+    #[test]
+    fn highlighted_code_blocks_preserve_text_exactly() {
+        let samples = [
+            (
+                "rust",
+                "fn main() {\n\tlet s = \"  two  spaces \";\n\n    if s.is_empty() { return; }\n}\n",
+            ),
+            (
+                "python",
+                "class Foo:\n    def run(self, n=3):\n\t\treturn '\\t'\n",
+            ),
+            ("ts", "function g<T>(v: T): T {\n  return v;   \n}\n"),
+            ("tsx", "const App = () => <div className=\"x\">{1}</div>;\n"),
+            ("js", "const a = `t${x}`;\n"),
+            ("go", "func main() {\n\tfmt.Println(\"hi\", 1)\n}\n"),
+            ("json", "{\n    \"a\": [1, 2]\n}\n"),
+            ("toml", "[a]\n  b = 1\n"),
+            ("sh", "echo hi && ls   -la\n"),
+        ];
+        for (lang, code) in samples {
+            let src = format!("```{lang}\n{code}```\n");
+            let mut out = Vec::new();
+            append_markdown_with_opener_and_cwd(
+                &src,
+                &mut out,
+                UriBasedFileOpener::None,
+                Path::new("/"),
+            );
+            let rendered: Vec<String> = out
+                .iter()
+                .map(|l| {
+                    l.spans
+                        .iter()
+                        .map(|s| s.content.clone())
+                        .collect::<String>()
+                })
+                .collect();
+            let expected: Vec<&str> = code.lines().collect();
+            assert_eq!(rendered, expected, "text changed for {lang}");
+            assert!(
+                out.iter()
+                    .flat_map(|l| l.spans.iter())
+                    .any(|s| s.style != Style::default()),
+                "expected {lang} to be highlighted"
+            );
+        }
+    }
+
+    #[test]
+    fn unknown_fence_languages_render_plain() {
+        let src = "```brainfuck\n+[>.<]\n```\n";
+        let mut out = Vec::new();
+        append_markdown_with_opener_and_cwd(
+            src,
+            &mut out,
+            UriBasedFileOpener::None,
+            Path::new("/"),
+        );
+        assert_eq!(styled_text(&out), "+[>.<]");
+    }
 }
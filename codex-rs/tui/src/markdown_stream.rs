@@ -847,4 +847,55 @@ mod tests {
         let rendered_strs = lines_to_plain_strings(&rendered);
         assert_eq!(streamed_strs, rendered_strs);
     }
+
+    #[test]
+    fn streamed_fenced_code_is_highlighted_like_final_render() {
+        use ratatui::style::Color;
+
+        let cfg = test_config();
+        // Split mid-fence-info and mid-token to exercise the commit gating.
+        let deltas = vec![
+            "Code:\n```ru",
+            "st\nfn ma",
+            "in() {\n    let x",
+            " = 1;\n}\n",
+            "```\n",
+        ];
+        let streamed = simulate_stream_markdown_for_tests(&deltas, true, &cfg);
+        let full: String = deltas.iter().copied().collect();
+        let mut rendered: Vec<ratatui::text::Line<'static>> = Vec::new();
+        crate::markdown::append_markdown(&full, &mut rendered, &cfg);
+        // Compare spans, not just text, so styles must match too.
+        assert_eq!(streamed, rendered);
+
+        let keyword = streamed
+            .iter()
+            .flat_map(|l| l.spans.iter())
+            .find(|s| s.content == "fn")
+            .map(|s| s.style.fg);
+        assert_eq!(keyword, Some(Some(Color::Magenta)));
+        assert_eq!(
+            lines_to_plain_strings(&streamed),
+            vec!["Code:", "fn main() {", "    let x = 1;", "}"]
+        );
+    }
+
+    #[test]
+    fn unterminated_fence_is_highlighted_on_finalize() {
+        use ratatui::style::Color;
+
+        let cfg = test_config();
+        let deltas = vec!["```python\ndef f():\n", "    return 'x'"];
+        let streamed = simulate_stream_markdown_for_tests(&deltas, true, &cfg);
+        assert_eq!(
+            lines_to_plain_strings(&streamed),
+            vec!["def f():", "    return 'x'"]
+        );
+        let string = streamed
+            .iter()
+            .flat_map(|l| l.spans.iter())
+            .find(|s| s.content == "'x'")
+            .map(|s| s.style.fg);
+        assert_eq!(string, Some(Some(Color::Green)));
+    }
 }
@@ -1,18 +1,29 @@
 use std::io::Result;
+use std::ops::Range;
 use std::time::Duration;
 
+use crate::clipboard_copy;
+use crate::diff_render::GitDiffFile;
+use crate::diff_render::git_diff_file_header;
+use crate::diff_render::render_git_diff_file_body;
+use crate::key_hint;
+use crate::keymap::keymap;
+use crate::pager_search::PagerSearch;
 use crate::render::line_utils::push_owned_lines;
+use crate::theme::ThemeStylize;
+use crate::theme::theme;
 use crate::tui;
 use crate::tui::TuiEvent;
+use codex_ansi_escape::ansi_escape_line;
+use codex_core::config_types::DiffLayout;
+use codex_core::config_types::KeyAction;
 use crossterm::event::KeyCode;
 use crossterm::event::KeyEvent;
 use crossterm::event::KeyEventKind;
+use crossterm::event::KeyModifiers;
 use ratatui::buffer::Buffer;
 use ratatui::layout::Rect;
-use ratatui::style::Color;
-use ratatui::style::Style;
 use ratatui::style::Styled;
-use ratatui::style::Stylize;
 use ratatui::text::Line;
 use ratatui::text::Span;
 use ratatui::widgets::Paragraph;
@@ -21,21 +32,32 @@ use ratatui::widgets::WidgetRef;
 pub(crate) enum Overlay {
     Transcript(TranscriptOverlay),
     Static(StaticOverlay),
+    Diff(DiffOverlay),
 }
 
 impl Overlay {
-    pub(crate) fn new_transcript(lines: Vec<Line<'static>>) -> Self {
-        Self::Transcript(TranscriptOverlay::new(lines))
+    pub(crate) fn new_transcript(
+        lines: Vec<Line<'static>>,
+        outputs: Vec<TranscriptOutput>,
+    ) -> Self {
+        let mut overlay = TranscriptOverlay::new(lines);
+        overlay.outputs = outputs;
+        Self::Transcript(overlay)
     }
 
     pub(crate) fn new_static_with_title(lines: Vec<Line<'static>>, title: String) -> Self {
         Self::Static(StaticOverlay::with_title(lines, title))
     }
 
+    pub(crate) fn new_diff(files: Vec<GitDiffFile>, layout: DiffLayout) -> Self {
+        Self::Diff(DiffOverlay::new(files, layout))
+    }
+
     pub(crate) fn handle_event(&mut self, tui: &mut tui::Tui, event: TuiEvent) -> Result<()> {
         match self {
             Overlay::Transcript(o) => o.handle_event(tui, event),
             Overlay::Static(o) => o.handle_event(tui, event),
+            Overlay::Diff(o) => o.handle_event(tui, event),
         }
     }
 
@@ -43,32 +65,60 @@ impl Overlay {
         match self {
             Overlay::Transcript(o) => o.is_done(),
             Overlay::Static(o) => o.is_done(),
+            Overlay::Diff(o) => o.is_done(),
+        }
+    }
+
+    /// True while a search query is being typed or a command output is
+    /// expanded, when the overlay takes all keys.
+    pub(crate) fn captures_keys(&self) -> bool {
+        match self {
+            Overlay::Transcript(o) => o.view.search.is_prompting() || o.expanded.is_some(),
+            Overlay::Static(o) => o.view.search.is_prompting(),
+            Overlay::Diff(o) => o.view.search.is_prompting(),
         }
     }
 }
 
 // Common pager navigation hints rendered on the first line
-const PAGER_KEY_HINTS: &[(&str, &str)] = &[
-    ("↑/↓", "scroll"),
-    ("PgUp/PgDn", "page"),
-    ("Home/End", "jump"),
-];
-
-// Render a single line of key hints from (key, description) pairs.
-fn render_key_hints(area: Rect, buf: &mut Buffer, pairs: &[(&str, &str)]) {
-    let key_hint_style = Style::default().fg(Color::Cyan);
+fn pager_key_hints() -> Vec<(String, &'static str)> {
+    vec![
+        (
+            key_hint::actions_label(&[KeyAction::ScrollUp, KeyAction::ScrollDown]),
+            "scroll",
+        ),
+        (
+            key_hint::actions_label(&[KeyAction::PageUp, KeyAction::PageDown]),
+            "page",
+        ),
+        (
+            key_hint::actions_label(&[KeyAction::ScrollTop, KeyAction::ScrollBottom]),
+            "jump",
+        ),
+        (key_hint::actions_label(&[KeyAction::Search]), "search"),
+    ]
+}
+
+fn is_close_key(key_event: &KeyEvent) -> bool {
+    key_event.kind == KeyEventKind::Press && keymap().matches(KeyAction::PagerClose, key_event)
+}
+
+// Render a single line of key hints from (key, description) pairs, skipping
+// actions without keys.
+fn render_key_hints(area: Rect, buf: &mut Buffer, pairs: &[(String, &str)]) {
+    let key_hint_style = theme().accent;
     let mut spans: Vec<Span<'static>> = vec![" ".into()];
     let mut first = true;
-    for (key, desc) in pairs {
+    for (key, desc) in pairs.iter().filter(|(key, _)| !key.is_empty()) {
         if !first {
             spans.push("   ".into());
         }
-        spans.push(Span::from(key.to_string()).set_style(key_hint_style));
+        spans.push(Span::from(key.clone()).set_style(key_hint_style));
         spans.push(" ".into());
         spans.push(Span::from(desc.to_string()));
         first = false;
     }
-    Paragraph::new(vec![Line::from(spans).dim()]).render_ref(area, buf);
+    Paragraph::new(vec![Line::from(spans).secondary()]).render_ref(area, buf);
 }
 
 /// Generic widget for rendering a pager view.
@@ -78,6 +128,11 @@ struct PagerView {
     title: String,
     wrap_cache: Option<WrapCache>,
     last_content_height: Option<usize>,
+    search: PagerSearch,
+    /// Source line of the match last jumped to, until taken by the owner.
+    search_jump: Option<usize>,
+    /// Message shown on the separator until the next key press.
+    notice: Option<Line<'static>>,
 }
 
 impl PagerView {
@@ -88,30 +143,14 @@ impl PagerView {
             title,
             wrap_cache: None,
             last_content_height: None,
+            search: PagerSearch::default(),
+            search_jump: None,
+            notice: None,
         }
     }
 
     fn render(&mut self, area: Rect, buf: &mut Buffer) {
-        self.render_header(area, buf);
-        let content_area = self.scroll_area(area);
-        self.update_last_content_height(content_area.height);
-        self.ensure_wrapped(content_area.width);
-        // Compute page bounds without holding an immutable borrow on cache while mutating self
-        let wrapped_len = self
-            .wrap_cache
-            .as_ref()
-            .map(|c| c.wrapped.len())
-            .unwrap_or(0);
-        self.scroll_offset = self
-            .scroll_offset
-            .min(wrapped_len.saturating_sub(content_area.height as usize));
-        let start = self.scroll_offset;
-        let end = (start + content_area.height as usize).min(wrapped_len);
-
-        let (wrapped, _src_idx) = self.cached();
-        let page = &wrapped[start..end];
-        self.render_content_page_prepared(content_area, buf, page);
-        self.render_bottom_bar(area, content_area, buf, wrapped);
+        self.render_with_highlight(area, buf, None);
     }
 
     fn render_with_highlight(
@@ -144,10 +183,10 @@ impl PagerView {
 
     fn render_header(&self, area: Rect, buf: &mut Buffer) {
         Span::from("/ ".repeat(area.width as usize / 2))
-            .dim()
+            .secondary()
             .render_ref(area, buf);
         let header = format!("/ {}", self.title);
-        header.dim().render_ref(area, buf);
+        header.secondary().render_ref(area, buf);
     }
 
     // Removed unused render_content_page (replaced by render_content_page_prepared)
@@ -161,7 +200,7 @@ impl PagerView {
                 let add = ((visible + i).min(u16::MAX as usize)) as u16;
                 let y = area.y.saturating_add(add);
                 Span::from("~")
-                    .dim()
+                    .secondary()
                     .render_ref(Rect::new(area.x, y, 1, 1), buf);
             }
         }
@@ -178,7 +217,7 @@ impl PagerView {
         let sep_rect = Rect::new(full_area.x, sep_y, full_area.width, 1);
 
         Span::from("─".repeat(sep_rect.width as usize))
-            .dim()
+            .secondary()
             .render_ref(sep_rect, buf);
         let percent = if wrapped.is_empty() {
             100
@@ -195,56 +234,64 @@ impl PagerView {
         let pct_w = pct_text.chars().count() as u16;
         let pct_x = sep_rect.x + sep_rect.width - pct_w - 1;
         Span::from(pct_text)
-            .dim()
+            .secondary()
             .render_ref(Rect::new(pct_x, sep_rect.y, pct_w, 1), buf);
+        if let Some(status) = self.notice.clone().or_else(|| self.search.status_line()) {
+            let status_w = pct_x.saturating_sub(sep_rect.x + 1);
+            status.render_ref(Rect::new(sep_rect.x + 1, sep_rect.y, status_w, 1), buf);
+        }
     }
 
     fn handle_key_event(&mut self, tui: &mut tui::Tui, key_event: KeyEvent) -> Result<()> {
-        match key_event {
-            KeyEvent {
-                code: KeyCode::Up,
-                kind: KeyEventKind::Press | KeyEventKind::Repeat,
-                ..
-            } => {
+        if !matches!(key_event.kind, KeyEventKind::Press | KeyEventKind::Repeat) {
+            return Ok(());
+        }
+        if self.search.is_prompting() {
+            self.handle_search_prompt_key(key_event);
+            tui.frame_requester().schedule_frame();
+            return Ok(());
+        }
+        let action = keymap().action(
+            &[
+                KeyAction::ScrollUp,
+                KeyAction::ScrollDown,
+                KeyAction::PageUp,
+                KeyAction::PageDown,
+                KeyAction::ScrollTop,
+                KeyAction::ScrollBottom,
+                KeyAction::Search,
+                KeyAction::SearchNext,
+                KeyAction::SearchPrevious,
+            ],
+            &key_event,
+        );
+        match action {
+            Some(KeyAction::ScrollUp) => {
                 self.scroll_offset = self.scroll_offset.saturating_sub(1);
             }
-            KeyEvent {
-                code: KeyCode::Down,
-                kind: KeyEventKind::Press | KeyEventKind::Repeat,
-                ..
-            } => {
+            Some(KeyAction::ScrollDown) => {
                 self.scroll_offset = self.scroll_offset.saturating_add(1);
             }
-            KeyEvent {
-                code: KeyCode::PageUp,
-                kind: KeyEventKind::Press | KeyEventKind::Repeat,
-                ..
-            } => {
+            Some(KeyAction::PageUp) => {
                 let area = self.scroll_area(tui.terminal.viewport_area);
                 self.scroll_offset = self.scroll_offset.saturating_sub(area.height as usize);
             }
-            KeyEvent {
-                code: KeyCode::PageDown | KeyCode::Char(' '),
-                kind: KeyEventKind::Press | KeyEventKind::Repeat,
-                ..
-            } => {
+            Some(KeyAction::PageDown) => {
                 let area = self.scroll_area(tui.terminal.viewport_area);
                 self.scroll_offset = self.scroll_offset.saturating_add(area.height as usize);
             }
-            KeyEvent {
-                code: KeyCode::Home,
-                kind: KeyEventKind::Press | KeyEventKind::Repeat,
-                ..
-            } => {
+            Some(KeyAction::ScrollTop) => {
                 self.scroll_offset = 0;
             }
-            KeyEvent {
-                code: KeyCode::End,
-                kind: KeyEventKind::Press | KeyEventKind::Repeat,
-                ..
-            } => {
+            Some(KeyAction::ScrollBottom) => {
                 self.scroll_offset = usize::MAX;
             }
+            Some(KeyAction::Search) => {
+                self.search.open_prompt(self.top_row());
+            }
+            Some(action @ (KeyAction::SearchNext | KeyAction::SearchPrevious)) => {
+                self.step_search(action == KeyAction::SearchNext);
+            }
             _ => {
                 return Ok(());
             }
@@ -254,6 +301,103 @@ impl PagerView {
         Ok(())
     }
 
+    /// Keys typed into the search prompt: text extends the query and jumps
+    /// to its first match, Enter keeps the query and Esc restores the
+    /// previous one.
+    fn handle_search_prompt_key(&mut self, key_event: KeyEvent) {
+        let ctrl_or_alt = key_event
+            .modifiers
+            .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT);
+        match key_event.code {
+            KeyCode::Enter => {
+                let origin = self.search.prompt_origin().unwrap_or_default();
+                self.search.accept();
+                self.refresh_search();
+                if let Some(m) = self.search.select_from(origin) {
+                    self.scroll_to_row(m.row);
+                    self.search_jump = Some(m.line);
+                }
+            }
+            KeyCode::Esc => self.cancel_search(),
+            KeyCode::Char('c') if key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.cancel_search();
+            }
+            KeyCode::Backspace => {
+                if self.search.pop_input() {
+                    self.update_incremental_search();
+                } else {
+                    self.cancel_search();
+                }
+            }
+            KeyCode::Char(c) if !ctrl_or_alt => {
+                self.search.push_input(c);
+                self.update_incremental_search();
+            }
+            _ => {}
+        }
+    }
+
+    fn step_search(&mut self, forward: bool) {
+        if let Some(m) = self.search.step(forward, self.top_row()) {
+            self.scroll_to_row(m.row);
+            self.search_jump = Some(m.line);
+        }
+    }
+
+    fn update_incremental_search(&mut self) {
+        let origin = self.search.prompt_origin().unwrap_or_default();
+        self.refresh_search();
+        match self.search.select_from(origin) {
+            Some(m) => self.scroll_to_row(m.row),
+            None => self.scroll_offset = origin,
+        }
+    }
+
+    fn cancel_search(&mut self) {
+        if let Some(origin) = self.search.cancel() {
+            self.scroll_offset = origin;
+        }
+        self.refresh_search();
+    }
+
+    fn refresh_search(&mut self) {
+        if let Some(cache) = &self.wrap_cache {
+            self.search
+                .refresh(&self.lines, &cache.wrapped, &cache.src_idx);
+        }
+    }
+
+    /// The first visible row, with the scroll offset clamped to the content.
+    fn top_row(&self) -> usize {
+        let wrapped_len = self.wrap_cache.as_ref().map_or(0, |c| c.wrapped.len());
+        let height = self.last_content_height.unwrap_or(0);
+        self.scroll_offset.min(wrapped_len.saturating_sub(height))
+    }
+
+    /// Scrolls `row` into view, a third of the way down the page, unless it
+    /// is already visible.
+    fn scroll_to_row(&mut self, row: usize) {
+        let height = self.last_content_height.unwrap_or(1).max(1);
+        let top = self.top_row();
+        if row < top || row >= top + height {
+            self.scroll_offset = row.saturating_sub(height / 3);
+        }
+    }
+
+    /// Source lines with at least one row on the current page.
+    fn visible_lines(&self) -> Range<usize> {
+        let (_, src_idx) = self.cached();
+        let top = self.top_row();
+        let bottom = top + self.last_content_height.unwrap_or(0);
+        match (
+            src_idx.get(top),
+            src_idx.get(bottom.min(src_idx.len()).saturating_sub(1)),
+        ) {
+            (Some(&first), Some(&last)) => first..last + 1,
+            _ => 0..0,
+        }
+    }
+
     fn update_last_content_height(&mut self, height: u16) {
         self.last_content_height = Some(height as usize);
     }
@@ -291,6 +435,7 @@ impl PagerView {
             src_idx.extend(std::iter::repeat_n(i, ws.len()));
             push_owned_lines(&ws, &mut wrapped);
         }
+        self.search.refresh(&self.lines, &wrapped, &src_idx);
         self.wrap_cache = Some(WrapCache {
             width,
             wrapped,
@@ -316,10 +461,10 @@ impl PagerView {
         highlight: Option<(usize, usize)>,
     ) -> std::borrow::Cow<'a, [Line<'static>]> {
         use ratatui::style::Modifier;
-        let (hi_start, hi_end) = match highlight {
-            Some(r) => r,
-            None => return std::borrow::Cow::Borrowed(&wrapped[start..end]),
-        };
+        if highlight.is_none() && !self.search.has_matches_in(start..end) {
+            return std::borrow::Cow::Borrowed(&wrapped[start..end]);
+        }
+        let (hi_start, hi_end) = highlight.unwrap_or_default();
         let mut out: Vec<Line<'static>> = Vec::with_capacity(end - start);
         let mut bold_done = false;
         for (row, src_line) in wrapped
@@ -341,6 +486,7 @@ impl PagerView {
                     }
                 }
             }
+            self.search.highlight_row(row, &mut line);
             out.push(line);
         }
         std::borrow::Cow::Owned(out)
@@ -365,9 +511,23 @@ impl PagerView {
     }
 }
 
+/// The complete output of a command or tool call in the transcript.
+#[derive(Debug, Clone)]
+pub(crate) struct TranscriptOutput {
+    /// Transcript lines of the cell that produced the output.
+    pub(crate) lines: Range<usize>,
+    pub(crate) text: String,
+}
+
 pub(crate) struct TranscriptOverlay {
     view: PagerView,
     highlight_range: Option<(usize, usize)>,
+    outputs: Vec<TranscriptOutput>,
+    /// Index in `outputs` of the output selected with Tab.
+    focused: Option<usize>,
+    scroll_to_focused: bool,
+    /// Pager showing the focused output in full, drawn over the transcript.
+    expanded: Option<Box<PagerView>>,
     is_done: bool,
 }
 
@@ -380,6 +540,10 @@ impl TranscriptOverlay {
                 usize::MAX,
             ),
             highlight_range: None,
+            outputs: Vec::new(),
+            focused: None,
+            scroll_to_focused: false,
+            expanded: None,
             is_done: false,
         }
     }
@@ -393,59 +557,163 @@ impl TranscriptOverlay {
         }
     }
 
+    pub(crate) fn insert_output(&mut self, output: TranscriptOutput) {
+        self.outputs.push(output);
+    }
+
     pub(crate) fn set_highlight_range(&mut self, range: Option<(usize, usize)>) {
         self.highlight_range = range;
     }
 
+    /// Focuses the output after (or before) the focused one. Without a
+    /// focused output, starts from the outputs on screen.
+    fn focus_output(&mut self, forward: bool) {
+        if self.outputs.is_empty() {
+            return;
+        }
+        let last = self.outputs.len() - 1;
+        let idx = match self.focused {
+            Some(idx) if forward => (idx + 1).min(last),
+            Some(idx) => idx.saturating_sub(1),
+            None => {
+                let visible = self.view.visible_lines();
+                if forward {
+                    self.outputs
+                        .iter()
+                        .position(|o| o.lines.start >= visible.start)
+                        .unwrap_or(last)
+                } else {
+                    self.outputs
+                        .iter()
+                        .rposition(|o| o.lines.start < visible.end)
+                        .unwrap_or(0)
+                }
+            }
+        };
+        self.focused = Some(idx);
+        self.scroll_to_focused = true;
+    }
+
+    fn focused_output(&self) -> Option<&TranscriptOutput> {
+        self.focused.and_then(|idx| self.outputs.get(idx))
+    }
+
+    fn expand_focused(&mut self) {
+        if let Some(output) = self.focused_output() {
+            let lines = output.text.lines().map(ansi_escape_line).collect();
+            self.expanded = Some(Box::new(PagerView::new(
+                lines,
+                "O U T P U T".to_string(),
+                0,
+            )));
+        }
+    }
+
+    fn copy_focused(&mut self) {
+        let Some(output) = self.focused_output() else {
+            return;
+        };
+        let lines: Vec<Line<'static>> = output.text.lines().map(ansi_escape_line).collect();
+        let notice = copy_lines(&lines);
+        self.view.notice = Some(notice);
+    }
+
     fn render_hints(&self, area: Rect, buf: &mut Buffer) {
         let line1 = Rect::new(area.x, area.y, area.width, 1);
         let line2 = Rect::new(area.x, area.y.saturating_add(1), area.width, 1);
-        render_key_hints(line1, buf, PAGER_KEY_HINTS);
-        let mut pairs: Vec<(&str, &str)> = vec![("q", "quit"), ("Esc", "edit prev")];
+        render_key_hints(line1, buf, &pager_key_hints());
+        let mut pairs = vec![
+            (key_hint::actions_label(&[KeyAction::PagerClose]), "quit"),
+            (
+                key_hint::actions_label(&[KeyAction::Backtrack]),
+                "edit prev",
+            ),
+        ];
         if let Some((start, end)) = self.highlight_range
             && end > start
         {
-            pairs.push(("⏎", "edit message"));
+            pairs.push(("⏎".to_string(), "edit message"));
+        } else if self.focused.is_some() {
+            pairs.push((key_hint::actions_label(&[KeyAction::ToggleFold]), "expand"));
+            pairs.push((key_hint::actions_label(&[KeyAction::CopyOutput]), "copy"));
+        }
+        if !self.outputs.is_empty() {
+            pairs.push((
+                key_hint::actions_label(&[KeyAction::NextFile]),
+                "next output",
+            ));
         }
         render_key_hints(line2, buf, &pairs);
     }
 
+    fn render_expanded_hints(area: Rect, buf: &mut Buffer) {
+        let line1 = Rect::new(area.x, area.y, area.width, 1);
+        let line2 = Rect::new(area.x, area.y.saturating_add(1), area.width, 1);
+        render_key_hints(line1, buf, &pager_key_hints());
+        let pairs = [
+            (
+                key_hint::actions_label(&[KeyAction::PagerClose, KeyAction::Backtrack]),
+                "back",
+            ),
+            (key_hint::actions_label(&[KeyAction::CopyOutput]), "copy"),
+        ];
+        render_key_hints(line2, buf, &pairs);
+    }
+
     pub(crate) fn render(&mut self, area: Rect, buf: &mut Buffer) {
         let top_h = area.height.saturating_sub(3);
         let top = Rect::new(area.x, area.y, area.width, top_h);
         let bottom = Rect::new(area.x, area.y + top_h, area.width, 3);
+        if let Some(expanded) = &mut self.expanded {
+            expanded.render(top, buf);
+            Self::render_expanded_hints(bottom, buf);
+            return;
+        }
+        if self.scroll_to_focused {
+            self.scroll_to_focused = false;
+            self.view.ensure_wrapped(self.view.scroll_area(top).width);
+            let (_, src_idx) = self.view.cached();
+            if let Some(output) = self.focused_output()
+                && let Some(row) = src_idx.iter().position(|idx| *idx == output.lines.start)
+            {
+                self.view.scroll_to_row(row);
+            }
+        }
+        let focus_range = self
+            .focused_output()
+            .map(|output| (output.lines.start, output.lines.end));
         self.view
-            .render_with_highlight(top, buf, self.highlight_range);
+            .render_with_highlight(top, buf, self.highlight_range.or(focus_range));
         self.render_hints(bottom, buf);
     }
 }
 
 impl TranscriptOverlay {
     pub(crate) fn handle_event(&mut self, tui: &mut tui::Tui, event: TuiEvent) -> Result<()> {
+        if let TuiEvent::Key(_) = event {
+            self.view.notice = None;
+            if let Some(expanded) = &mut self.expanded {
+                expanded.notice = None;
+            }
+        }
         match event {
-            TuiEvent::Key(key_event) => match key_event {
-                KeyEvent {
-                    code: KeyCode::Char('q'),
-                    kind: KeyEventKind::Press,
-                    ..
-                }
-                | KeyEvent {
-                    code: KeyCode::Char('t'),
-                    modifiers: crossterm::event::KeyModifiers::CONTROL,
-                    kind: KeyEventKind::Press,
-                    ..
-                }
-                | KeyEvent {
-                    code: KeyCode::Char('c'),
-                    modifiers: crossterm::event::KeyModifiers::CONTROL,
-                    kind: KeyEventKind::Press,
-                    ..
-                } => {
+            TuiEvent::Key(key_event) if self.view.search.is_prompting() => {
+                self.view.handle_key_event(tui, key_event)
+            }
+            TuiEvent::Key(key_event) if self.expanded.is_some() => {
+                self.handle_expanded_key_event(tui, key_event)
+            }
+            TuiEvent::Key(key_event) => {
+                if is_close_key(&key_event)
+                    || (key_event.kind == KeyEventKind::Press
+                        && keymap().matches(KeyAction::Transcript, &key_event))
+                {
                     self.is_done = true;
                     Ok(())
+                } else {
+                    self.handle_key_event(tui, key_event)
                 }
-                other => self.view.handle_key_event(tui, other),
-            },
+            }
             TuiEvent::Draw => {
                 tui.draw(u16::MAX, |frame| {
                     self.render(frame.area(), frame.buffer);
@@ -455,12 +723,80 @@ impl TranscriptOverlay {
             _ => Ok(()),
         }
     }
+
+    fn handle_key_event(&mut self, tui: &mut tui::Tui, key_event: KeyEvent) -> Result<()> {
+        let action = keymap().action(
+            &[
+                KeyAction::NextFile,
+                KeyAction::PreviousFile,
+                KeyAction::ToggleFold,
+                KeyAction::CopyOutput,
+            ],
+            &key_event,
+        );
+        let press = key_event.kind == KeyEventKind::Press;
+        match action {
+            Some(_) if key_event.kind == KeyEventKind::Release => return Ok(()),
+            Some(KeyAction::NextFile) => self.focus_output(true),
+            Some(KeyAction::PreviousFile) => self.focus_output(false),
+            Some(KeyAction::ToggleFold) if press => self.expand_focused(),
+            Some(KeyAction::CopyOutput) if press => self.copy_focused(),
+            _ => return self.view.handle_key_event(tui, key_event),
+        }
+        tui.frame_requester().schedule_frame();
+        Ok(())
+    }
+
+    /// Keys for the expanded output: Esc or `q` return to the transcript.
+    fn handle_expanded_key_event(&mut self, tui: &mut tui::Tui, key_event: KeyEvent) -> Result<()> {
+        let Some(expanded) = &mut self.expanded else {
+            return Ok(());
+        };
+        if expanded.search.is_prompting() || key_event.kind != KeyEventKind::Press {
+            return expanded.handle_key_event(tui, key_event);
+        }
+        if is_close_key(&key_event) || keymap().matches(KeyAction::Backtrack, &key_event) {
+            self.expanded = None;
+        } else if keymap().matches(KeyAction::CopyOutput, &key_event) {
+            expanded.notice = Some(copy_lines(&expanded.lines));
+        } else {
+            return expanded.handle_key_event(tui, key_event);
+        }
+        tui.frame_requester().schedule_frame();
+        Ok(())
+    }
+
     pub(crate) fn is_done(&self) -> bool {
         self.is_done
     }
     pub(crate) fn set_scroll_offset(&mut self, offset: usize) {
         self.view.scroll_offset = offset;
     }
+
+    /// The transcript line of the search match last jumped to, if any since
+    /// the previous call.
+    pub(crate) fn take_search_jump(&mut self) -> Option<usize> {
+        self.view.search_jump.take()
+    }
+}
+
+/// Copies the text of `lines` to the clipboard and returns a notice saying
+/// whether it worked.
+fn copy_lines(lines: &[Line<'static>]) -> Line<'static> {
+    let text = lines
+        .iter()
+        .map(|line| {
+            line.spans
+                .iter()
+                .map(|span| span.content.as_ref())
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    match clipboard_copy::copy_text(&text) {
+        Ok(()) => format!(" Copied {} lines ", lines.len()).accent().into(),
+        Err(err) => format!(" Copy failed: {err} ").error().into(),
+    }
 }
 
 pub(crate) struct StaticOverlay {
@@ -479,8 +815,8 @@ impl StaticOverlay {
     fn render_hints(&self, area: Rect, buf: &mut Buffer) {
         let line1 = Rect::new(area.x, area.y, area.width, 1);
         let line2 = Rect::new(area.x, area.y.saturating_add(1), area.width, 1);
-        render_key_hints(line1, buf, PAGER_KEY_HINTS);
-        let pairs = [("q", "quit")];
+        render_key_hints(line1, buf, &pager_key_hints());
+        let pairs = [(key_hint::actions_label(&[KeyAction::PagerClose]), "quit")];
         render_key_hints(line2, buf, &pairs);
     }
 
@@ -496,23 +832,191 @@ impl StaticOverlay {
 impl StaticOverlay {
     pub(crate) fn handle_event(&mut self, tui: &mut tui::Tui, event: TuiEvent) -> Result<()> {
         match event {
-            TuiEvent::Key(key_event) => match key_event {
-                KeyEvent {
-                    code: KeyCode::Char('q'),
-                    kind: KeyEventKind::Press,
-                    ..
+            TuiEvent::Key(key_event) if self.view.search.is_prompting() => {
+                self.view.handle_key_event(tui, key_event)
+            }
+            TuiEvent::Key(key_event) => {
+                if is_close_key(&key_event) {
+                    self.is_done = true;
+                    Ok(())
+                } else {
+                    self.view.handle_key_event(tui, key_event)
                 }
-                | KeyEvent {
-                    code: KeyCode::Char('c'),
-                    modifiers: crossterm::event::KeyModifiers::CONTROL,
-                    kind: KeyEventKind::Press,
-                    ..
-                } => {
+            }
+            TuiEvent::Draw => {
+                tui.draw(u16::MAX, |frame| {
+                    self.render(frame.area(), frame.buffer);
+                })?;
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+    pub(crate) fn is_done(&self) -> bool {
+        self.is_done
+    }
+}
+
+/// Pager for the output of `/diff`, with one foldable section per file.
+pub(crate) struct DiffOverlay {
+    view: PagerView,
+    files: Vec<GitDiffFile>,
+    collapsed: Vec<bool>,
+    selected: usize,
+    layout: DiffLayout,
+    /// Rendered hunks of each file, valid for `body_width`.
+    bodies: Vec<Option<Vec<Line<'static>>>>,
+    body_width: u16,
+    /// Index in `view.lines` of each file's header line.
+    file_starts: Vec<usize>,
+    needs_rebuild: bool,
+    scroll_to_selected: bool,
+    is_done: bool,
+}
+
+impl DiffOverlay {
+    pub(crate) fn new(files: Vec<GitDiffFile>, layout: DiffLayout) -> Self {
+        let count = files.len();
+        Self {
+            view: PagerView::new(Vec::new(), "D I F F".to_string(), 0),
+            files,
+            collapsed: vec![false; count],
+            selected: 0,
+            layout,
+            bodies: vec![None; count],
+            body_width: 0,
+            file_starts: Vec::new(),
+            needs_rebuild: true,
+            scroll_to_selected: false,
+            is_done: false,
+        }
+    }
+
+    /// Rebuilds the pager lines from the file sections. Hunks are rendered
+    /// to fit `width`, so they are only rendered again when it changes.
+    fn rebuild_lines(&mut self, width: u16) {
+        if self.body_width != width {
+            self.bodies = vec![None; self.files.len()];
+            self.body_width = width;
+        }
+        let mut lines: Vec<Line<'static>> = Vec::new();
+        let mut file_starts = Vec::with_capacity(self.files.len());
+        for (idx, file) in self.files.iter().enumerate() {
+            if idx > 0 {
+                lines.push("".into());
+            }
+            file_starts.push(lines.len());
+            let collapsed = self.collapsed[idx];
+            lines.push(git_diff_file_header(file, collapsed, idx == self.selected));
+            if !collapsed {
+                let body = self.bodies[idx].get_or_insert_with(|| {
+                    render_git_diff_file_body(file, width as usize, self.layout)
+                });
+                lines.extend(body.iter().cloned());
+            }
+        }
+        self.view.lines = lines;
+        self.view.wrap_cache = None;
+        self.file_starts = file_starts;
+        self.needs_rebuild = false;
+    }
+
+    fn select(&mut self, idx: usize) {
+        self.selected = idx.min(self.files.len().saturating_sub(1));
+        self.needs_rebuild = true;
+        self.scroll_to_selected = true;
+    }
+
+    fn toggle_selected(&mut self) {
+        if let Some(collapsed) = self.collapsed.get_mut(self.selected) {
+            *collapsed = !*collapsed;
+            self.needs_rebuild = true;
+            self.scroll_to_selected = true;
+        }
+    }
+
+    /// Collapses every file, or expands them all when all are collapsed.
+    fn toggle_all(&mut self) {
+        let collapse = self.collapsed.iter().any(|collapsed| !collapsed);
+        self.collapsed.fill(collapse);
+        self.needs_rebuild = true;
+        self.scroll_to_selected = true;
+    }
+
+    fn render_hints(&self, area: Rect, buf: &mut Buffer) {
+        let line1 = Rect::new(area.x, area.y, area.width, 1);
+        let line2 = Rect::new(area.x, area.y.saturating_add(1), area.width, 1);
+        render_key_hints(line1, buf, &pager_key_hints());
+        let pairs = [
+            (key_hint::actions_label(&[KeyAction::PagerClose]), "quit"),
+            (key_hint::actions_label(&[KeyAction::NextFile]), "next file"),
+            (key_hint::actions_label(&[KeyAction::ToggleFold]), "fold"),
+            (
+                key_hint::actions_label(&[KeyAction::ToggleFoldAll]),
+                "fold all",
+            ),
+        ];
+        render_key_hints(line2, buf, &pairs);
+    }
+
+    pub(crate) fn render(&mut self, area: Rect, buf: &mut Buffer) {
+        let top_h = area.height.saturating_sub(3);
+        let top = Rect::new(area.x, area.y, area.width, top_h);
+        let bottom = Rect::new(area.x, area.y + top_h, area.width, 3);
+        let width = self.view.scroll_area(top).width.max(1);
+        if self.needs_rebuild || self.body_width != width {
+            self.rebuild_lines(width);
+        }
+        if self.scroll_to_selected {
+            self.scroll_to_selected = false;
+            self.view.ensure_wrapped(width);
+            let (_, src_idx) = self.view.cached();
+            if let Some(start) = self.file_starts.get(self.selected)
+                && let Some(row) = src_idx.iter().position(|idx| idx == start)
+            {
+                self.view.scroll_offset = row;
+            }
+        }
+        self.view.render(top, buf);
+        self.render_hints(bottom, buf);
+    }
+
+    fn handle_key_event(&mut self, tui: &mut tui::Tui, key_event: KeyEvent) -> Result<()> {
+        let action = keymap().action(
+            &[
+                KeyAction::NextFile,
+                KeyAction::PreviousFile,
+                KeyAction::ToggleFold,
+                KeyAction::ToggleFoldAll,
+            ],
+            &key_event,
+        );
+        let repeat = key_event.kind == KeyEventKind::Repeat;
+        match action {
+            Some(_) if key_event.kind == KeyEventKind::Release => return Ok(()),
+            Some(KeyAction::NextFile) => self.select(self.selected + 1),
+            Some(KeyAction::PreviousFile) => self.select(self.selected.saturating_sub(1)),
+            Some(KeyAction::ToggleFold) if !repeat => self.toggle_selected(),
+            Some(KeyAction::ToggleFoldAll) if !repeat => self.toggle_all(),
+            _ => return self.view.handle_key_event(tui, key_event),
+        }
+        tui.frame_requester().schedule_frame();
+        Ok(())
+    }
+
+    pub(crate) fn handle_event(&mut self, tui: &mut tui::Tui, event: TuiEvent) -> Result<()> {
+        match event {
+            TuiEvent::Key(key_event) if self.view.search.is_prompting() => {
+                self.view.handle_key_event(tui, key_event)
+            }
+            TuiEvent::Key(key_event) => {
+                if is_close_key(&key_event) {
                     self.is_done = true;
                     Ok(())
+                } else {
+                    self.handle_key_event(tui, key_event)
                 }
-                other => self.view.handle_key_event(tui, other),
-            },
+            }
             TuiEvent::Draw => {
                 tui.draw(u16::MAX, |frame| {
                     self.render(frame.area(), frame.buffer);
@@ -522,6 +1026,7 @@ impl StaticOverlay {
             _ => Ok(()),
         }
     }
+
     pub(crate) fn is_done(&self) -> bool {
         self.is_done
     }
@@ -604,6 +1109,93 @@ mod tests {
         assert_eq!(overlay.view.scroll_offset, 0);
     }
 
+    #[test]
+    fn transcript_search_jumps_between_matches() {
+        let mut overlay =
+            TranscriptOverlay::new((0..40).map(|i| Line::from(format!("line{i}"))).collect());
+        let mut term = Terminal::new(TestBackend::new(40, 12)).expect("term");
+        term.draw(|f| overlay.render(f.area(), f.buffer_mut()))
+            .expect("draw");
+        overlay.view.scroll_offset = 0;
+
+        overlay.view.search.open_prompt(overlay.view.top_row());
+        for c in "line3".chars() {
+            overlay
+                .view
+                .handle_search_prompt_key(KeyEvent::from(KeyCode::Char(c)));
+        }
+        overlay
+            .view
+            .handle_search_prompt_key(KeyEvent::from(KeyCode::Enter));
+        assert!(!overlay.view.search.is_prompting());
+        assert_eq!(overlay.take_search_jump(), Some(3));
+        assert_eq!(overlay.view.scroll_offset, 0);
+
+        // "line30" is below the page, so the view scrolls to it.
+        overlay.view.step_search(true);
+        assert_eq!(overlay.take_search_jump(), Some(30));
+        assert_eq!(overlay.view.scroll_offset, 28);
+        term.draw(|f| overlay.render(f.area(), f.buffer_mut()))
+            .expect("draw");
+        let status: String = (0..40)
+            .map(|x| term.backend().buffer()[(x, 8)].symbol().to_string())
+            .collect();
+        assert!(status.contains("/line3 2/11"), "{status:?}");
+
+        // Going back from the first match wraps around to the last.
+        overlay.view.step_search(false);
+        overlay.view.step_search(false);
+        assert_eq!(overlay.take_search_jump(), Some(39));
+    }
+
+    #[test]
+    fn transcript_focuses_and_expands_outputs() {
+        let mut overlay =
+            TranscriptOverlay::new((0..40).map(|i| Line::from(format!("line{i}"))).collect());
+        overlay.insert_output(TranscriptOutput {
+            lines: 5..8,
+            text: "first".to_string(),
+        });
+        overlay.insert_output(TranscriptOutput {
+            lines: 30..33,
+            text: "$ seq 3\n1\n\u{1b}[31m2\u{1b}[0m\n3".to_string(),
+        });
+        let mut term = Terminal::new(TestBackend::new(40, 12)).expect("term");
+        term.draw(|f| overlay.render(f.area(), f.buffer_mut()))
+            .expect("draw");
+
+        // Scrolled to the bottom, Shift+Tab focuses the last output on screen
+        // and Tab stops at the last output.
+        overlay.focus_output(false);
+        assert_eq!(overlay.focused, Some(1));
+        overlay.focus_output(true);
+        assert_eq!(overlay.focused, Some(1));
+
+        // From the top, Tab focuses the first output and scrolls to it.
+        overlay.focused = None;
+        overlay.view.scroll_offset = 0;
+        overlay.focus_output(true);
+        assert_eq!(overlay.focused, Some(0));
+        overlay.focus_output(true);
+        assert_eq!(overlay.focused, Some(1));
+        term.draw(|f| overlay.render(f.area(), f.buffer_mut()))
+            .expect("draw");
+        assert!(overlay.view.visible_lines().contains(&30));
+
+        overlay.expand_focused();
+        let expanded = overlay.expanded.as_ref().expect("expanded");
+        let text: Vec<String> = expanded
+            .lines
+            .iter()
+            .map(|l| l.spans.iter().map(|s| s.content.as_ref()).collect())
+            .collect();
+        assert_eq!(text, vec!["$ seq 3", "1", "2", "3"]);
+        assert!(
+            Overlay::Transcript(overlay).captures_keys(),
+            "an expanded output takes Esc and Enter"
+        );
+    }
+
     #[test]
     fn static_overlay_snapshot_basic() {
         // Prepare a static overlay with a few lines and a title
@@ -617,6 +1209,43 @@ mod tests {
         assert_snapshot!(term.backend());
     }
 
+    #[test]
+    fn diff_overlay_folds_files() {
+        let diff = "\
+diff --git a/src/a.txt b/src/a.txt
+index 1111111..2222222 100644
+--- a/src/a.txt
++++ b/src/a.txt
+@@ -1,2 +1,2 @@
+ keep
+-old
++new
+diff --git a/b.txt b/b.txt
+new file mode 100644
+index 0000000..3333333
+--- /dev/null
++++ b/b.txt
+@@ -0,0 +1 @@
++hello
+";
+        let files = crate::diff_render::parse_git_diff(diff);
+        let mut overlay = DiffOverlay::new(files, DiffLayout::Unified);
+        let mut term = Terminal::new(TestBackend::new(60, 14)).expect("term");
+        term.draw(|f| overlay.render(f.area(), f.buffer_mut()))
+            .expect("draw");
+        assert_snapshot!("diff_overlay_expanded", term.backend());
+
+        overlay.toggle_selected();
+        term.draw(|f| overlay.render(f.area(), f.buffer_mut()))
+            .expect("draw");
+        assert_snapshot!("diff_overlay_first_file_collapsed", term.backend());
+
+        overlay.toggle_all();
+        assert_eq!(overlay.collapsed, vec![true, true]);
+        overlay.toggle_all();
+        assert_eq!(overlay.collapsed, vec![false, false]);
+    }
+
     #[test]
     fn pager_wrap_cache_reuses_for_same_width_and_rebuilds_on_change() {
         let long = "This is a long line that should wrap multiple times to ensure non-empty wrapped output.";
@@ -0,0 +1,426 @@
+//! Incremental `/` search in the pager overlays.
+//!
+//! The pattern is matched against the pager's source lines, so a match may
+//! continue onto the next wrapped row; matches are then mapped back onto the
+//! wrapped rows for highlighting and scrolling.
+
+use ratatui::style::Modifier;
+use ratatui::style::Style;
+use ratatui::style::Stylize;
+use ratatui::text::Line;
+use ratatui::text::Span;
+use regex_lite::Regex;
+
+use crate::theme::ThemeStylize;
+use crate::theme::theme;
+
+/// A match of the search pattern.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct SearchMatch {
+    /// Index of the source line containing the match.
+    pub(crate) line: usize,
+    /// Byte offset of the match in the text of its source line.
+    pub(crate) start: usize,
+    /// First wrapped row showing the match.
+    pub(crate) row: usize,
+}
+
+/// The part of a match shown on one wrapped row, as a byte range of the
+/// row's text.
+#[derive(Debug, Clone, Copy)]
+struct Fragment {
+    row: usize,
+    start: usize,
+    end: usize,
+    index: usize,
+}
+
+#[derive(Debug)]
+struct Prompt {
+    input: String,
+    /// Top row when the prompt was opened; typing searches from here and
+    /// cancelling scrolls back to it.
+    origin: usize,
+    previous_query: String,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct PagerSearch {
+    prompt: Option<Prompt>,
+    query: String,
+    pattern: Option<Regex>,
+    matches: Vec<SearchMatch>,
+    /// Sorted by row.
+    fragments: Vec<Fragment>,
+    current: Option<usize>,
+}
+
+impl PagerSearch {
+    /// True while the query is being typed.
+    pub(crate) fn is_prompting(&self) -> bool {
+        self.prompt.is_some()
+    }
+
+    pub(crate) fn open_prompt(&mut self, origin: usize) {
+        let previous_query = std::mem::take(&mut self.query);
+        self.prompt = Some(Prompt {
+            input: String::new(),
+            origin,
+            previous_query,
+        });
+        self.set_query(String::new());
+    }
+
+    pub(crate) fn prompt_origin(&self) -> Option<usize> {
+        self.prompt.as_ref().map(|prompt| prompt.origin)
+    }
+
+    pub(crate) fn push_input(&mut self, c: char) {
+        if let Some(prompt) = &mut self.prompt {
+            prompt.input.push(c);
+            let query = prompt.input.clone();
+            self.set_query(query);
+        }
+    }
+
+    /// Deletes the last character of the query. Returns false if there was
+    /// nothing to delete.
+    pub(crate) fn pop_input(&mut self) -> bool {
+        let Some(prompt) = &mut self.prompt else {
+            return false;
+        };
+        if prompt.input.pop().is_none() {
+            return false;
+        }
+        let query = prompt.input.clone();
+        self.set_query(query);
+        true
+    }
+
+    /// Closes the prompt and keeps searching for the typed query, or for the
+    /// previous query when nothing was typed.
+    pub(crate) fn accept(&mut self) {
+        if let Some(prompt) = self.prompt.take()
+            && prompt.input.is_empty()
+        {
+            self.set_query(prompt.previous_query);
+        }
+    }
+
+    /// Closes the prompt and goes back to the previous query. Returns the top
+    /// row at the time the prompt was opened.
+    pub(crate) fn cancel(&mut self) -> Option<usize> {
+        let prompt = self.prompt.take()?;
+        self.set_query(prompt.previous_query);
+        Some(prompt.origin)
+    }
+
+    /// Changes the query; call [`Self::refresh`] to find its matches.
+    fn set_query(&mut self, query: String) {
+        self.pattern = compile(&query);
+        self.query = query;
+        self.matches.clear();
+        self.fragments.clear();
+        self.current = None;
+    }
+
+    /// Finds the matches of the query in `lines`, which are shown as the
+    /// `wrapped` rows; `src_idx` maps each row to its source line. The
+    /// current match is kept across calls where possible.
+    pub(crate) fn refresh(&mut self, lines: &[Line<'_>], wrapped: &[Line<'_>], src_idx: &[usize]) {
+        let previous = self.current_match().map(|m| (m.line, m.start));
+        self.matches.clear();
+        self.fragments.clear();
+        self.current = None;
+        let Some(pattern) = &self.pattern else {
+            return;
+        };
+
+        let mut row = 0;
+        while let Some(&line_idx) = src_idx.get(row) {
+            let rows_end = row
+                + src_idx[row..]
+                    .iter()
+                    .take_while(|idx| **idx == line_idx)
+                    .count();
+            let Some(line) = lines.get(line_idx) else {
+                break;
+            };
+            let text = line_text(line);
+
+            // Wrapped rows are slices of the source line with the whitespace
+            // at each break removed; find where each one starts.
+            let mut row_ranges = Vec::with_capacity(rows_end - row);
+            let mut pos = 0;
+            for wrapped_row in &wrapped[row..rows_end.min(wrapped.len())] {
+                let row_text = line_text(wrapped_row);
+                let start = text[pos..]
+                    .find(&row_text)
+                    .map_or(pos, |offset| pos + offset);
+                let end = (start + row_text.len()).min(text.len());
+                row_ranges.push((start, end));
+                pos = end;
+            }
+
+            for m in pattern.find_iter(&text).filter(|m| !m.is_empty()) {
+                let index = self.matches.len();
+                let first_row = row_ranges
+                    .iter()
+                    .rposition(|(start, _)| *start <= m.start())
+                    .unwrap_or(0);
+                self.matches.push(SearchMatch {
+                    line: line_idx,
+                    start: m.start(),
+                    row: row + first_row,
+                });
+                for (offset, (row_start, row_end)) in row_ranges.iter().enumerate() {
+                    let start = m.start().max(*row_start);
+                    let end = m.end().min(*row_end);
+                    if start < end {
+                        self.fragments.push(Fragment {
+                            row: row + offset,
+                            start: start - row_start,
+                            end: end - row_start,
+                            index,
+                        });
+                    }
+                }
+            }
+            row = rows_end;
+        }
+
+        if let Some(previous) = previous {
+            self.current = self
+                .matches
+                .iter()
+                .position(|m| (m.line, m.start) >= previous);
+        }
+    }
+
+    pub(crate) fn current_match(&self) -> Option<SearchMatch> {
+        self.current.and_then(|idx| self.matches.get(idx)).copied()
+    }
+
+    /// Selects the first match at or below `row`, wrapping around to the
+    /// first match.
+    pub(crate) fn select_from(&mut self, row: usize) -> Option<SearchMatch> {
+        if self.matches.is_empty() {
+            return None;
+        }
+        self.current = Some(self.matches.iter().position(|m| m.row >= row).unwrap_or(0));
+        self.current_match()
+    }
+
+    /// Selects the next (or previous) match, wrapping around at the ends. If
+    /// no match is selected yet, selects the first one at or below `row`.
+    pub(crate) fn step(&mut self, forward: bool, row: usize) -> Option<SearchMatch> {
+        let len = self.matches.len();
+        match self.current {
+            Some(current) if len > 0 => {
+                self.current = Some(if forward {
+                    (current + 1) % len
+                } else {
+                    (current + len - 1) % len
+                });
+                self.current_match()
+            }
+            _ => self.select_from(row),
+        }
+    }
+
+    pub(crate) fn has_matches_in(&self, rows: std::ops::Range<usize>) -> bool {
+        let first = self.fragments.partition_point(|f| f.row < rows.start);
+        self.fragments.get(first).is_some_and(|f| f.row < rows.end)
+    }
+
+    /// Highlights the matches on wrapped `row`, whose content is `line`.
+    pub(crate) fn highlight_row(&self, row: usize, line: &mut Line<'static>) {
+        let first = self.fragments.partition_point(|f| f.row < row);
+        let fragments: Vec<&Fragment> = self.fragments[first..]
+            .iter()
+            .take_while(|f| f.row == row)
+            .collect();
+        if fragments.is_empty() {
+            return;
+        }
+
+        let mut spans: Vec<Span<'static>> = Vec::with_capacity(line.spans.len() + 2);
+        let mut offset = 0;
+        for span in line.spans.drain(..) {
+            let content = span.content.as_ref();
+            let end = offset + content.len();
+            let mut pos = offset;
+            for fragment in fragments.iter().filter(|f| f.start < end && f.end > offset) {
+                let start = fragment.start.max(pos);
+                let stop = fragment.end.min(end);
+                if start > pos {
+                    spans.push(Span::styled(
+                        content[pos - offset..start - offset].to_string(),
+                        span.style,
+                    ));
+                }
+                let style = match_style(span.style, Some(fragment.index) == self.current);
+                spans.push(Span::styled(
+                    content[start - offset..stop - offset].to_string(),
+                    style,
+                ));
+                pos = stop;
+            }
+            if pos < end {
+                spans.push(Span::styled(
+                    content[pos - offset..].to_string(),
+                    span.style,
+                ));
+            }
+            offset = end;
+        }
+        line.spans = spans;
+    }
+
+    /// The prompt while typing, otherwise the query and match position.
+    pub(crate) fn status_line(&self) -> Option<Line<'static>> {
+        if let Some(prompt) = &self.prompt {
+            return Some(Line::from(vec![
+                " /".into(),
+                prompt.input.clone().into(),
+                " ".reversed(),
+                " ".into(),
+            ]));
+        }
+        if self.query.is_empty() {
+            return None;
+        }
+        if self.matches.is_empty() {
+            return Some(Line::from(format!(" Pattern not found: {} ", self.query)).error());
+        }
+        let count = match self.current {
+            Some(idx) => format!("{}/{} ", idx + 1, self.matches.len()),
+            None => format!("{} matches ", self.matches.len()),
+        };
+        Some(Line::from(vec![
+            format!(" /{} ", self.query).into(),
+            count.secondary(),
+        ]))
+    }
+}
+
+/// Compiles a query as a regex, or as literal text when it is not a valid
+/// regex. Queries without uppercase letters match case-insensitively.
+fn compile(query: &str) -> Option<Regex> {
+    if query.is_empty() {
+        return None;
+    }
+    let flags = if query.chars().any(char::is_uppercase) {
+        ""
+    } else {
+        "(?i)"
+    };
+    Regex::new(&format!("{flags}{query}"))
+        .or_else(|_| Regex::new(&format!("{flags}{}", regex_lite::escape(query))))
+        .ok()
+}
+
+fn line_text(line: &Line<'_>) -> String {
+    line.spans.iter().map(|s| s.content.as_ref()).collect()
+}
+
+/// Matches are shown in reverse video, which is toggled off inside text that
+/// is already reversed such as a selected message; the current match is also
+/// drawn in the accent color.
+fn match_style(style: Style, current: bool) -> Style {
+    let mut style = if style.add_modifier.contains(Modifier::REVERSED) {
+        style.remove_modifier(Modifier::REVERSED)
+    } else {
+        style.add_modifier(Modifier::REVERSED)
+    };
+    if current {
+        style = style.patch(theme().accent).add_modifier(Modifier::BOLD);
+    }
+    style
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn search(query: &str, lines: &[Line<'static>]) -> PagerSearch {
+        let mut search = PagerSearch::default();
+        search.set_query(query.to_string());
+        let src_idx: Vec<usize> = (0..lines.len()).collect();
+        search.refresh(lines, lines, &src_idx);
+        search
+    }
+
+    fn highlighted(search: &PagerSearch, row: usize, line: &Line<'static>) -> Vec<String> {
+        let mut line = line.clone();
+        search.highlight_row(row, &mut line);
+        line.spans
+            .iter()
+            .map(|s| {
+                if s.style.add_modifier.contains(Modifier::REVERSED) {
+                    format!("[{}]", s.content)
+                } else {
+                    s.content.to_string()
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn smart_case_and_regex_queries() {
+        let lines: Vec<Line<'static>> = vec!["Error: foo".into(), "no error here".into()];
+        assert_eq!(search("error", &lines).matches.len(), 2);
+        assert_eq!(search("Error", &lines).matches.len(), 1);
+        assert_eq!(search("^no .*here$", &lines).matches.len(), 1);
+        // Not a valid regex, so matched literally.
+        assert_eq!(search("foo(", &lines).matches.len(), 0);
+        assert_eq!(search("r: f", &lines).matches.len(), 1);
+    }
+
+    #[test]
+    fn matches_are_split_across_spans_and_wrapped_rows() {
+        let line = Line::from(vec!["$ ".into(), "cargo test".into()]);
+        let search = search("o t", std::slice::from_ref(&line));
+        assert_eq!(
+            highlighted(&search, 0, &line),
+            vec!["$ ", "carg", "[o t]", "est"]
+        );
+
+        // "cargo test" wrapped as "cargo" / "test": the match spans both rows.
+        let rows: Vec<Line<'static>> = vec!["cargo".into(), "test".into()];
+        let mut search = PagerSearch::default();
+        search.set_query("go te".to_string());
+        search.refresh(&["cargo test".into()], &rows, &[0, 0]);
+        assert_eq!(
+            search.matches,
+            vec![SearchMatch {
+                line: 0,
+                start: 3,
+                row: 0
+            }]
+        );
+        assert_eq!(highlighted(&search, 0, &rows[0]), vec!["car", "[go]"]);
+        assert_eq!(highlighted(&search, 1, &rows[1]), vec!["[te]", "st"]);
+    }
+
+    #[test]
+    fn stepping_wraps_around_and_prompt_restores_previous_query() {
+        let lines: Vec<Line<'static>> = (0..5).map(|i| Line::from(format!("x{i}"))).collect();
+        let mut search = search("x", &lines);
+        assert_eq!(search.select_from(3).map(|m| m.row), Some(3));
+        assert_eq!(search.step(true, 0).map(|m| m.row), Some(4));
+        assert_eq!(search.step(true, 0).map(|m| m.row), Some(0));
+        assert_eq!(search.step(false, 0).map(|m| m.row), Some(4));
+
+        search.open_prompt(2);
+        search.push_input('y');
+        assert!(search.is_prompting());
+        assert_eq!(search.cancel(), Some(2));
+        assert_eq!(search.query, "x");
+
+        search.open_prompt(0);
+        search.accept();
+        assert_eq!(search.query, "x");
+    }
+}
@@ -1,7 +1,19 @@
+use std::path::Path;
+use std::sync::OnceLock;
+
 use codex_core::bash::try_parse_bash;
+use ratatui::style::Style;
 use ratatui::style::Stylize;
 use ratatui::text::Line;
 use ratatui::text::Span;
+use tree_sitter::Language;
+use tree_sitter::Parser;
+use tree_sitter::Query;
+use tree_sitter::QueryCursor;
+use tree_sitter::StreamingIterator;
+
+use crate::theme::ThemeStylize;
+use crate::theme::theme;
 
 /// Convert the full bash script into per-line styled content by first
 /// computing operator-dimmed spans across the entire script, then splitting
@@ -43,7 +55,7 @@ pub(crate) fn highlight_bash_to_lines(script: &str) -> Vec<Line<'static>> {
                 if dim_start > i {
                     spans.push(script[i..dim_start].to_string().into());
                 }
-                spans.push(script[dim_start..dim_end].to_string().dim());
+                spans.push(script[dim_start..dim_end].to_string().secondary());
                 i = dim_end;
             }
         }
@@ -78,6 +90,282 @@ pub(crate) fn highlight_bash_to_lines(script: &str) -> Vec<Line<'static>> {
     lines
 }
 
+/// Languages highlighted in fenced code blocks with their tree-sitter
+/// grammar and bundled highlight query.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CodeLanguage {
+    Rust,
+    Python,
+    JavaScript,
+    TypeScript,
+    Tsx,
+    Go,
+    Json,
+    Toml,
+}
+
+const CODE_LANGUAGES: usize = 8;
+
+impl CodeLanguage {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name.to_ascii_lowercase().as_str() {
+            "rust" | "rs" => Self::Rust,
+            "python" | "py" | "python3" | "py3" | "pyi" => Self::Python,
+            "javascript" | "js" | "jsx" | "mjs" | "cjs" | "node" => Self::JavaScript,
+            "typescript" | "ts" | "mts" | "cts" => Self::TypeScript,
+            "tsx" => Self::Tsx,
+            "go" | "golang" => Self::Go,
+            "json" | "jsonc" | "json5" => Self::Json,
+            "toml" => Self::Toml,
+            _ => return None,
+        })
+    }
+
+    fn language(self) -> Language {
+        match self {
+            Self::Rust => tree_sitter_rust::LANGUAGE.into(),
+            Self::Python => tree_sitter_python::LANGUAGE.into(),
+            Self::JavaScript => tree_sitter_javascript::LANGUAGE.into(),
+            Self::TypeScript => tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
+            Self::Tsx => tree_sitter_typescript::LANGUAGE_TSX.into(),
+            Self::Go => tree_sitter_go::LANGUAGE.into(),
+            Self::Json => tree_sitter_json::LANGUAGE.into(),
+            Self::Toml => tree_sitter_toml_ng::LANGUAGE.into(),
+        }
+    }
+
+    fn highlights_query(self) -> String {
+        match self {
+            Self::Rust => tree_sitter_rust::HIGHLIGHTS_QUERY.to_string(),
+            Self::Python => tree_sitter_python::HIGHLIGHTS_QUERY.to_string(),
+            Self::JavaScript => [
+                tree_sitter_javascript::HIGHLIGHT_QUERY,
+                tree_sitter_javascript::JSX_HIGHLIGHT_QUERY,
+            ]
+            .join("\n"),
+            // The TypeScript query only covers what TypeScript adds to
+            // JavaScript; its patterns come first so that they win.
+            Self::TypeScript => [
+                tree_sitter_typescript::HIGHLIGHTS_QUERY,
+                tree_sitter_javascript::HIGHLIGHT_QUERY,
+            ]
+            .join("\n"),
+            Self::Tsx => [
+                tree_sitter_typescript::HIGHLIGHTS_QUERY,
+                tree_sitter_javascript::HIGHLIGHT_QUERY,
+                tree_sitter_javascript::JSX_HIGHLIGHT_QUERY,
+            ]
+            .join("\n"),
+            Self::Go => tree_sitter_go::HIGHLIGHTS_QUERY.to_string(),
+            Self::Json => tree_sitter_json::HIGHLIGHTS_QUERY.to_string(),
+            Self::Toml => tree_sitter_toml_ng::HIGHLIGHTS_QUERY.to_string(),
+        }
+    }
+}
+
+/// What a highlighted token is, independent of how it is drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TokenClass {
+    Keyword,
+    String,
+    Comment,
+    /// Numbers, booleans and other constants.
+    Constant,
+    /// Types, constructors and keys in configuration files.
+    Type,
+    Attribute,
+}
+
+impl TokenClass {
+    /// Maps a highlight query capture name such as `keyword` or
+    /// `string.special.key` to a class. Names left unstyled (variables,
+    /// punctuation, operators, ...) map to `None`.
+    fn from_capture_name(name: &str) -> Option<Self> {
+        if name == "string.special.key" {
+            return Some(Self::Type);
+        }
+        match name.split('.').next().unwrap_or(name) {
+            "keyword" => Some(Self::Keyword),
+            "string" | "escape" => Some(Self::String),
+            "comment" => Some(Self::Comment),
+            "number" | "boolean" | "constant" => Some(Self::Constant),
+            "type" | "constructor" => Some(Self::Type),
+            "attribute" => Some(Self::Attribute),
+            _ => None,
+        }
+    }
+
+    fn style(self) -> Style {
+        match self {
+            Self::Keyword => theme().syntax_keyword,
+            Self::String => theme().syntax_string,
+            Self::Comment => theme().syntax_comment,
+            Self::Constant => theme().syntax_constant,
+            Self::Type => theme().syntax_type,
+            Self::Attribute => theme().syntax_attribute,
+        }
+    }
+}
+
+/// A compiled highlight query and the class of each of its captures.
+struct Highlighter {
+    language: Language,
+    query: Query,
+    classes: Vec<Option<TokenClass>>,
+}
+
+impl Highlighter {
+    fn new(lang: CodeLanguage) -> Option<Self> {
+        let language = lang.language();
+        let query = Query::new(&language, &lang.highlights_query()).ok()?;
+        let classes = query
+            .capture_names()
+            .iter()
+            .map(|name| TokenClass::from_capture_name(name))
+            .collect();
+        Some(Self {
+            language,
+            query,
+            classes,
+        })
+    }
+
+    fn get(lang: CodeLanguage) -> Option<&'static Self> {
+        // Compiling a query takes a few milliseconds, so each is compiled
+        // once, on first use.
+        static HIGHLIGHTERS: [OnceLock<Option<Highlighter>>; CODE_LANGUAGES] =
+            [const { OnceLock::new() }; CODE_LANGUAGES];
+        HIGHLIGHTERS[lang as usize]
+            .get_or_init(|| Self::new(lang))
+            .as_ref()
+    }
+
+    /// The class of every byte of `code`. Nested captures override the
+    /// captures enclosing them; of several captures of the same node, the
+    /// one from the earliest pattern wins.
+    fn classify(&self, code: &str) -> Option<Vec<Option<TokenClass>>> {
+        let mut parser = Parser::new();
+        parser.set_language(&self.language).ok()?;
+        let tree = parser.parse(code, None)?;
+
+        let mut captures = Vec::new();
+        let mut cursor = QueryCursor::new();
+        let mut matches = cursor.matches(&self.query, tree.root_node(), code.as_bytes());
+        while let Some(m) = matches.next() {
+            for capture in m.captures {
+                if let Some(class) = self.classes[capture.index as usize] {
+                    captures.push((capture.node.byte_range(), m.pattern_index, class));
+                }
+            }
+        }
+        // Paint outer ranges first and, for equal ranges, later patterns
+        // first, so that what is painted last wins.
+        captures.sort_by(|(a, a_pattern, _), (b, b_pattern, _)| {
+            b.len().cmp(&a.len()).then(b_pattern.cmp(a_pattern))
+        });
+        let mut classes = vec![None; code.len()];
+        for (range, _, class) in captures {
+            if let Some(bytes) = classes.get_mut(range) {
+                bytes.fill(Some(class));
+            }
+        }
+        Some(classes)
+    }
+}
+
+/// Highlights the content of a fenced code block whose info string is
+/// `info` (e.g. `rust` or `python title="x.py"`). Returns one line per line
+/// of `code`, whose text is exactly that of the source line, or `None` when
+/// the language is not recognized.
+pub(crate) fn highlight_code_to_lines(info: &str, code: &str) -> Option<Vec<Line<'static>>> {
+    let name = info
+        .split(|c: char| c.is_whitespace() || c == ',' || c == '{')
+        .next()
+        .unwrap_or_default();
+    if code.is_empty() {
+        return None;
+    }
+    let code = code.strip_suffix('\n').unwrap_or(code);
+    match name.to_ascii_lowercase().as_str() {
+        "" => None,
+        "diff" | "patch" => Some(highlight_diff_to_lines(code)),
+        "bash" | "sh" | "shell" | "zsh" => Some(highlight_bash_to_lines(code)),
+        _ => {
+            let classes = Highlighter::get(CodeLanguage::from_name(name)?)?.classify(code)?;
+            Some(classes_to_lines(code, &classes))
+        }
+    }
+}
+
+/// Highlights the content of a source file, choosing the language from the
+/// extension of `path`. Returns one line per line of `code`, or `None` when
+/// the language is not recognized.
+pub(crate) fn highlight_file_to_lines(path: &Path, code: &str) -> Option<Vec<Line<'static>>> {
+    match path.extension()?.to_str()? {
+        // Diff coloring would read as changes to the file itself.
+        "diff" | "patch" => None,
+        ext => highlight_code_to_lines(ext, code),
+    }
+}
+
+/// Splits `code` into lines of spans, one span per run of bytes of the same
+/// class.
+fn classes_to_lines(code: &str, classes: &[Option<TokenClass>]) -> Vec<Line<'static>> {
+    let mut offset = 0;
+    code.split('\n')
+        .map(|line| {
+            let start = offset;
+            offset += line.len() + 1;
+            let mut spans: Vec<Span<'static>> = Vec::new();
+            let mut run_start = 0;
+            let mut run_class = None;
+            for (i, _) in line.char_indices() {
+                let class = classes.get(start + i).copied().flatten();
+                if i > 0 && class != run_class {
+                    spans.push(styled_span(&line[run_start..i], run_class));
+                    run_start = i;
+                }
+                run_class = class;
+            }
+            if run_start < line.len() {
+                spans.push(styled_span(&line[run_start..], run_class));
+            }
+            Line::from(spans)
+        })
+        .collect()
+}
+
+fn styled_span(text: &str, class: Option<TokenClass>) -> Span<'static> {
+    match class {
+        Some(class) => Span::styled(text.to_string(), class.style()),
+        None => Span::raw(text.to_string()),
+    }
+}
+
+/// Colors unified diff lines with the theme's diff styles, hunk headers with
+/// its accent and file headers bold.
+fn highlight_diff_to_lines(code: &str) -> Vec<Line<'static>> {
+    code.split('\n')
+        .map(|line| {
+            let span = Span::raw(line.to_string());
+            let span = if line.starts_with("+++") || line.starts_with("---") {
+                span.bold()
+            } else if line.starts_with('+') {
+                span.style(theme().diff_add)
+            } else if line.starts_with('-') {
+                span.style(theme().diff_delete)
+            } else if line.starts_with("@@") {
+                span.accent()
+            } else if line.starts_with("diff ") || line.starts_with("index ") {
+                span.bold()
+            } else {
+                span
+            };
+            Line::from(span)
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
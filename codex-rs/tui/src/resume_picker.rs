@@ -1,33 +1,60 @@
+use std::collections::BTreeSet;
 use std::path::Path;
 use std::path::PathBuf;
+use std::time::Duration;
 
 use chrono::DateTime;
+use chrono::Local;
+use chrono::NaiveDate;
+use chrono::TimeDelta;
 use chrono::Utc;
-use codex_core::ConversationItem;
-use codex_core::ConversationsPage;
-use codex_core::Cursor;
+use codex_common::fuzzy_match::fuzzy_match;
+use codex_core::ARCHIVED_SESSIONS_SUBDIR;
+use codex_core::ConversationMessage;
+use codex_core::ConversationSummary;
+use codex_core::MessageRole;
 use codex_core::RolloutRecorder;
 use color_eyre::eyre::Result;
 use crossterm::event::KeyCode;
 use crossterm::event::KeyEvent;
 use crossterm::event::KeyEventKind;
+use crossterm::event::KeyModifiers;
 use ratatui::layout::Constraint;
 use ratatui::layout::Layout;
 use ratatui::layout::Rect;
 use ratatui::style::Stylize as _;
 use ratatui::text::Line;
+use ratatui::widgets::Block;
+use ratatui::widgets::Borders;
+use ratatui::widgets::Paragraph;
+use ratatui::widgets::Wrap;
+use tokio::select;
+use tokio::sync::mpsc::UnboundedSender;
+use tokio::sync::mpsc::unbounded_channel;
+use tokio::time::Instant;
 use tokio_stream::StreamExt;
 
 use crate::text_formatting::truncate_text;
+use crate::theme::ThemeStylize as _;
+use crate::theme::theme;
 use crate::tui::FrameRequester;
 use crate::tui::Tui;
 use crate::tui::TuiEvent;
-use codex_protocol::models::ContentItem;
-use codex_protocol::models::ResponseItem;
-use codex_protocol::protocol::InputMessageKind;
-use codex_protocol::protocol::USER_MESSAGE_BEGIN;
 
-const PAGE_SIZE: usize = 25;
+/// Sessions read before the list is updated while loading.
+const LOAD_BATCH_SIZE: usize = 25;
+/// Rows moved by PageUp and PageDown.
+const PAGE_SIZE: usize = 10;
+/// Longer words, such as encoded blobs, are left out of the search index
+/// because they would fuzzy match almost any query.
+const MAX_WORD_CHARS: usize = 64;
+/// Lines of each message shown in the preview pane.
+const PREVIEW_MESSAGE_LINES: usize = 6;
+/// The preview pane is hidden on narrower terminals.
+const MIN_PREVIEW_WIDTH: u16 = 100;
+/// Typing pause after which the sessions are searched again; scoring every
+/// word of every session on each keystroke makes typing lag.
+const FILTER_DEBOUNCE: Duration = Duration::from_millis(100);
 
 #[derive(Debug, Clone)]
 pub enum ResumeSelection {
@@ -36,31 +63,53 @@ pub enum ResumeSelection {
     Exit,
 }
 
-/// Interactive session picker that lists recorded rollout files with simple
-/// search and pagination. Shows the first user input as the preview, relative
-/// time (e.g., "5 seconds ago"), and the absolute path.
+/// Interactive session picker that lists every recorded rollout file, newest
+/// first, with fuzzy search over the conversations, `key:value` filters on
+/// their metadata and a preview of the selected conversation's first and last
+/// exchanges. Sessions are read in the background, so the list fills in while
+/// the picker is already usable.
 pub async fn run_resume_picker(tui: &mut Tui, codex_home: &Path) -> Result<ResumeSelection> {
     let alt = AltScreenGuard::enter(tui);
-    let mut state = PickerState::new(codex_home.to_path_buf(), alt.tui.frame_requester());
-    state.load_page(None).await?;
+    let mut state = PickerState::new(
+        codex_home.to_path_buf(),
+        alt.tui.frame_requester(),
+        std::env::current_dir().ok(),
+    );
+    let (rows_tx, mut rows_rx) = unbounded_channel();
+    tokio::spawn(load_rows(codex_home.to_path_buf(), rows_tx));
     state.request_frame();
 
     let mut events = alt.tui.event_stream();
-    while let Some(ev) = events.next().await {
-        match ev {
-            TuiEvent::Key(key) => {
-                if matches!(key.kind, KeyEventKind::Release) {
-                    continue;
+    loop {
+        select! {
+            _ = tokio::time::sleep_until(state.filter_due.unwrap_or_else(Instant::now)),
+                if state.filter_due.is_some() =>
+            {
+                state.apply_filter();
+            }
+            rows = rows_rx.recv(), if state.loading => match rows {
+                Some(rows) => state.add_rows(rows),
+                None => {
+                    state.loading = false;
+                    state.request_frame();
                 }
-                if let Some(sel) = state.handle_key(key).await? {
-                    return Ok(sel);
+            },
+            ev = events.next() => match ev {
+                Some(TuiEvent::Key(key)) => {
+                    if matches!(key.kind, KeyEventKind::Release) {
+                        continue;
+                    }
+                    if let Some(sel) = state.handle_key(key).await {
+                        return Ok(sel);
+                    }
                 }
-            }
-            TuiEvent::Draw => {
-                draw_picker(alt.tui, &state)?;
-            }
-            // Ignore paste and attach-image in picker
-            _ => {}
+                Some(TuiEvent::Draw) => {
+                    draw_picker(alt.tui, &state)?;
+                }
+                // Ignore paste and attach-image in picker
+                Some(_) => {}
+                None => break,
+            },
         }
     }
 
@@ -86,25 +135,51 @@ impl Drop for AltScreenGuard<'_> {
     }
 }
 
+/// Read every recorded session, newest first, and send the rows in batches.
+async fn load_rows(codex_home: PathBuf, tx: UnboundedSender<Vec<Row>>) {
+    let paths = match RolloutRecorder::list_conversation_paths(&codex_home).await {
+        Ok(paths) => paths,
+        Err(err) => {
+            tracing::warn!("failed to list sessions: {err}");
+            return;
+        }
+    };
+    let mut batch = Vec::with_capacity(LOAD_BATCH_SIZE);
+    for path in paths {
+        match RolloutRecorder::read_conversation_summary(&path).await {
+            Ok(Some(summary)) => batch.push(Row::from_summary(summary)),
+            Ok(None) => {}
+            Err(err) => tracing::debug!("skipping session {}: {err}", path.display()),
+        }
+        if batch.len() == LOAD_BATCH_SIZE && tx.send(std::mem::take(&mut batch)).is_err() {
+            // The picker has closed.
+            return;
+        }
+    }
+    if !batch.is_empty() {
+        let _ = tx.send(batch);
+    }
+}
+
 struct PickerState {
     codex_home: PathBuf,
     requester: FrameRequester,
-    // pagination
-    pagination: Pagination,
+    /// The directory `cwd:.` filters to.
+    current_dir: Option<PathBuf>,
     // data
-    all_rows: Vec<Row>, // unfiltered rows for current page
-    filtered_rows: Vec<Row>,
+    rows: Vec<Row>, // every session read so far, newest first
+    loading: bool,
+    /// Indices into `rows` of the sessions matching the query, best first.
+    filtered: Vec<usize>,
     selected: usize,
     // search
     query: String,
-}
-
-#[derive(Debug, Clone)]
-struct Pagination {
-    current_anchor: Option<Cursor>,
-    backstack: Vec<Option<Cursor>>, // track previous anchors for ←/a
-    next_cursor: Option<Cursor>,
-    page_index: usize,
+    /// When to apply a query that was edited since the last search.
+    filter_due: Option<Instant>,
+    /// Session that a second Ctrl+D deletes.
+    pending_delete: Option<PathBuf>,
+    /// Shown in place of the key hints until the next key press.
+    notice: Option<String>,
 }
 
 #[derive(Clone)]
@@ -112,23 +187,166 @@ struct Row {
     path: PathBuf,
     preview: String,
     ts: Option<DateTime<Utc>>,
+    cwd: PathBuf,
+    git_repository_url: Option<String>,
+    git_branch: Option<String>,
+    /// The first user message and the replies to it.
+    first_exchange: Vec<ConversationMessage>,
+    /// The last user message and the replies to it, unless that is also the
+    /// first one.
+    last_exchange: Vec<ConversationMessage>,
+    /// Distinct lowercased words of every message, which the query searches.
+    words: Vec<String>,
+}
+
+impl Row {
+    fn from_summary(summary: ConversationSummary) -> Self {
+        let ts = DateTime::parse_from_rfc3339(&summary.timestamp)
+            .ok()
+            .map(|parsed| parsed.with_timezone(&Utc));
+        let preview = summary
+            .messages
+            .iter()
+            .find(|m| m.role == MessageRole::User)
+            .map(|m| m.text.clone())
+            .unwrap_or_else(|| String::from("(no message yet)"));
+        let words: BTreeSet<String> = summary
+            .messages
+            .iter()
+            .flat_map(|m| m.text.split_whitespace())
+            .filter(|word| word.chars().count() <= MAX_WORD_CHARS)
+            .map(str::to_lowercase)
+            .collect();
+        // Each exchange starts with a user message.
+        let mut exchanges = summary
+            .messages
+            .chunk_by(|_, next| next.role == MessageRole::Assistant);
+        let first_exchange = exchanges.next().map(<[_]>::to_vec).unwrap_or_default();
+        let last_exchange = exchanges.next_back().map(<[_]>::to_vec).unwrap_or_default();
+
+        Row {
+            path: summary.path,
+            preview,
+            ts,
+            cwd: summary.cwd,
+            git_repository_url: summary.git_repository_url,
+            git_branch: summary.git_branch,
+            first_exchange,
+            last_exchange,
+            words: words.into_iter().collect(),
+        }
+    }
+}
+
+/// The search box text: words to fuzzy match against the conversation,
+/// mixed with `cwd:`, `repo:`, `branch:`, `since:` and `until:` filters.
+#[derive(Debug, Default, PartialEq)]
+struct Query {
+    terms: Vec<String>,
+    cwd: Option<String>,
+    repo: Option<String>,
+    branch: Option<String>,
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+}
+
+impl Query {
+    /// Parse `text`, reading relative dates such as `since:7d` from `now`.
+    /// Filters with an empty or unparsable value are searched for as words.
+    fn parse(text: &str, now: DateTime<Local>) -> Self {
+        let mut query = Self::default();
+        for token in text.split_whitespace() {
+            let (key, value) = token.split_once(':').unwrap_or(("", token));
+            let value_lower = value.to_lowercase();
+            match key {
+                "cwd" if !value.is_empty() => query.cwd = Some(value.to_string()),
+                "repo" if !value.is_empty() => query.repo = Some(value_lower),
+                "branch" if !value.is_empty() => query.branch = Some(value_lower),
+                "since" | "until" => match parse_date_range(value, now) {
+                    Some((start, _)) if key == "since" => query.since = Some(start),
+                    Some((_, end)) => query.until = Some(end),
+                    None => query.terms.push(token.to_string()),
+                },
+                _ => query.terms.push(token.to_string()),
+            }
+        }
+        query
+    }
+
+    /// Whether `row` passes the filters; `current_dir` is what `cwd:.` means.
+    fn accepts(&self, row: &Row, current_dir: Option<&Path>) -> bool {
+        let contains = |haystack: Option<&str>, needle: &Option<String>| match needle {
+            Some(needle) => haystack.is_some_and(|h| h.to_lowercase().contains(needle)),
+            None => true,
+        };
+        let cwd_matches = match self.cwd.as_deref() {
+            Some(".") => current_dir == Some(row.cwd.as_path()),
+            Some(cwd) => row
+                .cwd
+                .to_string_lossy()
+                .to_lowercase()
+                .contains(&cwd.to_lowercase()),
+            None => true,
+        };
+        cwd_matches
+            && contains(row.git_repository_url.as_deref(), &self.repo)
+            && contains(row.git_branch.as_deref(), &self.branch)
+            && self
+                .since
+                .is_none_or(|since| row.ts.is_some_and(|ts| ts >= since))
+            && self
+                .until
+                .is_none_or(|until| row.ts.is_some_and(|ts| ts < until))
+    }
+
+    /// How well `row` matches the search terms, lower being better, or `None`
+    /// if some term fuzzy matches none of its words.
+    fn score(&self, row: &Row) -> Option<i64> {
+        self.terms
+            .iter()
+            .map(|term| {
+                row.words
+                    .iter()
+                    .filter_map(|word| fuzzy_match(word, term).map(|(_, score)| i64::from(score)))
+                    .min()
+            })
+            .sum()
+    }
+}
+
+/// The period a `since:`/`until:` value names, as `[start, end)`: a local
+/// `YYYY-MM-DD` day, or the instant `N` days ago for `Nd`.
+fn parse_date_range(value: &str, now: DateTime<Local>) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    if let Some(days) = value.strip_suffix('d')
+        && let Ok(days) = days.parse::<i64>()
+    {
+        let instant = (now - TimeDelta::try_days(days)?).with_timezone(&Utc);
+        return Some((instant, instant));
+    }
+    let day = NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()?;
+    let start_of = |day: NaiveDate| {
+        day.and_hms_opt(0, 0, 0)?
+            .and_local_timezone(Local)
+            .earliest()
+            .map(|start| start.with_timezone(&Utc))
+    };
+    Some((start_of(day)?, start_of(day.succ_opt()?)?))
 }
 
 impl PickerState {
-    fn new(codex_home: PathBuf, requester: FrameRequester) -> Self {
+    fn new(codex_home: PathBuf, requester: FrameRequester, current_dir: Option<PathBuf>) -> Self {
         Self {
             codex_home,
             requester,
-            pagination: Pagination {
-                current_anchor: None,
-                backstack: vec![None],
-                next_cursor: None,
-                page_index: 0,
-            },
-            all_rows: Vec::new(),
-            filtered_rows: Vec::new(),
+            current_dir,
+            rows: Vec::new(),
+            loading: true,
+            filtered: Vec::new(),
             selected: 0,
             query: String::new(),
+            filter_due: None,
+            pending_delete: None,
+            notice: None,
         }
     }
 
@@ -136,184 +354,153 @@ impl PickerState {
         self.requester.schedule_frame();
     }
 
-    async fn handle_key(&mut self, key: KeyEvent) -> Result<Option<ResumeSelection>> {
+    fn selected_row(&self) -> Option<&Row> {
+        self.filtered.get(self.selected).map(|&idx| &self.rows[idx])
+    }
+
+    async fn handle_key(&mut self, key: KeyEvent) -> Option<ResumeSelection> {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        let pending_delete = self.pending_delete.take();
+        self.notice = None;
+        let edits_query = match key.code {
+            KeyCode::Char('u') if ctrl => true,
+            KeyCode::Backspace => true,
+            KeyCode::Char(_) => !ctrl && !key.modifiers.contains(KeyModifiers::ALT),
+            _ => false,
+        };
+        if !edits_query {
+            // Act on the sessions matching what is in the search box.
+            self.flush_filter();
+        }
         match key.code {
-            KeyCode::Esc => return Ok(Some(ResumeSelection::StartFresh)),
-            KeyCode::Char('c')
-                if key
-                    .modifiers
-                    .contains(crossterm::event::KeyModifiers::CONTROL) =>
-            {
-                return Ok(Some(ResumeSelection::Exit));
-            }
+            KeyCode::Esc => return Some(ResumeSelection::StartFresh),
+            KeyCode::Char('c') if ctrl => return Some(ResumeSelection::Exit),
             KeyCode::Enter => {
-                if let Some(row) = self.filtered_rows.get(self.selected) {
-                    return Ok(Some(ResumeSelection::Resume(row.path.clone())));
+                if let Some(row) = self.selected_row() {
+                    return Some(ResumeSelection::Resume(row.path.clone()));
                 }
             }
-            KeyCode::Up => {
-                if self.selected > 0 {
-                    self.selected -= 1;
+            KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down => self.move_down(1),
+            KeyCode::PageUp => self.selected = self.selected.saturating_sub(PAGE_SIZE),
+            KeyCode::PageDown => self.move_down(PAGE_SIZE),
+            KeyCode::Char('d') if ctrl => {
+                if let Some(path) = self.selected_row().map(|row| row.path.clone()) {
+                    if pending_delete.as_ref() == Some(&path) {
+                        self.delete(path).await;
+                    } else {
+                        self.notice = Some("Press Ctrl+D again to delete this session".to_string());
+                        self.pending_delete = Some(path);
+                    }
                 }
-                self.request_frame();
             }
-            KeyCode::Down => {
-                if self.selected + 1 < self.filtered_rows.len() {
-                    self.selected += 1;
+            KeyCode::Char('a') if ctrl => {
+                if let Some(path) = self.selected_row().map(|row| row.path.clone()) {
+                    self.archive(path).await;
                 }
-                self.request_frame();
             }
-            KeyCode::Left | KeyCode::Char('a') => {
-                self.prev_page().await?;
-            }
-            KeyCode::Right | KeyCode::Char('d') => {
-                self.next_page().await?;
+            KeyCode::Char('u') if ctrl => {
+                self.query.clear();
+                self.schedule_filter();
             }
             KeyCode::Backspace => {
                 self.query.pop();
-                self.apply_filter();
+                self.schedule_filter();
             }
-            KeyCode::Char(c) => {
-                // basic text input for search
-                if !key
-                    .modifiers
-                    .contains(crossterm::event::KeyModifiers::CONTROL)
-                    && !key.modifiers.contains(crossterm::event::KeyModifiers::ALT)
-                {
-                    self.query.push(c);
-                    self.apply_filter();
-                }
+            // basic text input for search
+            KeyCode::Char(c) if edits_query => {
+                self.query.push(c);
+                self.schedule_filter();
             }
             _ => {}
         }
-        Ok(None)
+        self.request_frame();
+        None
     }
 
-    async fn prev_page(&mut self) -> Result<()> {
-        if self.pagination.page_index == 0 {
-            return Ok(());
-        }
-        // current_anchor points to the page we just loaded; backstack[page_index-1] is the anchor to reload
-        if self.pagination.page_index > 0 {
-            self.pagination.page_index -= 1;
-            let anchor = self
-                .pagination
-                .backstack
-                .get(self.pagination.page_index)
-                .cloned()
-                .flatten();
-            self.pagination.current_anchor = anchor.clone();
-            self.load_page(anchor.as_ref()).await?;
+    fn move_down(&mut self, by: usize) {
+        self.selected = (self.selected + by).min(self.filtered.len().saturating_sub(1));
+    }
+
+    async fn delete(&mut self, path: PathBuf) {
+        match tokio::fs::remove_file(&path).await {
+            Ok(()) => {
+                self.remove_row(&path);
+                self.notice = Some("Deleted the session".to_string());
+            }
+            Err(err) => self.notice = Some(format!("Failed to delete the session: {err}")),
         }
-        Ok(())
     }
 
-    async fn next_page(&mut self) -> Result<()> {
-        if let Some(next) = self.pagination.next_cursor.clone() {
-            // Record the anchor for the page we are moving to at index new_index
-            let new_index = self.pagination.page_index + 1;
-            if self.pagination.backstack.len() <= new_index {
-                self.pagination.backstack.resize(new_index + 1, None);
+    /// Move the session's rollout file into the archived sessions directory,
+    /// where it is no longer listed.
+    async fn archive(&mut self, path: PathBuf) {
+        let archive_dir = self.codex_home.join(ARCHIVED_SESSIONS_SUBDIR);
+        let result = match path.file_name() {
+            Some(file_name) => match tokio::fs::create_dir_all(&archive_dir).await {
+                Ok(()) => tokio::fs::rename(&path, archive_dir.join(file_name)).await,
+                Err(err) => Err(err),
+            },
+            None => Err(std::io::Error::other("session path has no file name")),
+        };
+        match result {
+            Ok(()) => {
+                self.remove_row(&path);
+                self.notice = Some(format!("Archived the session to {}", archive_dir.display()));
             }
-            self.pagination.backstack[new_index] = Some(next.clone());
-            self.pagination.current_anchor = Some(next.clone());
-            self.pagination.page_index = new_index;
-            let anchor = self.pagination.current_anchor.clone();
-            self.load_page(anchor.as_ref()).await?;
+            Err(err) => self.notice = Some(format!("Failed to archive the session: {err}")),
         }
-        Ok(())
     }
 
-    async fn load_page(&mut self, anchor: Option<&Cursor>) -> Result<()> {
-        let page = RolloutRecorder::list_conversations(&self.codex_home, PAGE_SIZE, anchor).await?;
-        self.pagination.next_cursor = page.next_cursor.clone();
-        self.all_rows = to_rows(page);
+    fn remove_row(&mut self, path: &Path) {
+        let selected = self.selected;
+        self.rows.retain(|row| row.path != path);
         self.apply_filter();
-        // reset selection on new page
-        self.selected = 0;
-        Ok(())
+        self.selected = selected.min(self.filtered.len().saturating_sub(1));
     }
 
-    fn apply_filter(&mut self) {
-        if self.query.is_empty() {
-            self.filtered_rows = self.all_rows.clone();
-        } else {
-            let q = self.query.to_lowercase();
-            self.filtered_rows = self
-                .all_rows
+    /// Add rows read in the background, keeping the selected session selected.
+    fn add_rows(&mut self, rows: Vec<Row>) {
+        let selected_path = self.selected_row().map(|row| row.path.clone());
+        self.rows.extend(rows);
+        self.apply_filter();
+        if let Some(path) = selected_path {
+            self.selected = self
+                .filtered
                 .iter()
-                .filter(|r| r.preview.to_lowercase().contains(&q))
-                .cloned()
-                .collect();
-        }
-        if self.selected >= self.filtered_rows.len() {
-            self.selected = self.filtered_rows.len().saturating_sub(1);
+                .position(|&idx| self.rows[idx].path == path)
+                .unwrap_or(0);
         }
-        self.request_frame();
     }
-}
 
-fn to_rows(page: ConversationsPage) -> Vec<Row> {
-    page.items.into_iter().map(|it| head_to_row(&it)).collect()
-}
-
-fn head_to_row(item: &ConversationItem) -> Row {
-    let mut ts: Option<DateTime<Utc>> = None;
-    if let Some(first) = item.head.first()
-        && let Some(t) = first.get("timestamp").and_then(|v| v.as_str())
-        && let Ok(parsed) = chrono::DateTime::parse_from_rfc3339(t)
-    {
-        ts = Some(parsed.with_timezone(&Utc));
+    /// Search again once typing pauses for [`FILTER_DEBOUNCE`].
+    fn schedule_filter(&mut self) {
+        self.filter_due = Some(Instant::now() + FILTER_DEBOUNCE);
     }
 
-    let preview = preview_from_head(&item.head)
-        .map(|s| s.trim().to_string())
-        .filter(|s| !s.is_empty())
-        .unwrap_or_else(|| String::from("(no message yet)"));
-
-    Row {
-        path: item.path.clone(),
-        preview,
-        ts,
+    fn flush_filter(&mut self) {
+        if self.filter_due.is_some() {
+            self.apply_filter();
+        }
     }
-}
 
-fn preview_from_head(head: &[serde_json::Value]) -> Option<String> {
-    head.iter()
-        .filter_map(|value| serde_json::from_value::<ResponseItem>(value.clone()).ok())
-        .find_map(|item| match item {
-            ResponseItem::Message { content, .. } => {
-                // Find the actual user message (as opposed to user instructions or ide context)
-                let preview = content
-                    .into_iter()
-                    .filter_map(|content| match content {
-                        ContentItem::InputText { text }
-                            if matches!(
-                                InputMessageKind::from(("user", text.as_str())),
-                                InputMessageKind::Plain
-                            ) =>
-                        {
-                            // Strip ide context.
-                            let text = match text.find(USER_MESSAGE_BEGIN) {
-                                Some(idx) => {
-                                    text[idx + USER_MESSAGE_BEGIN.len()..].trim().to_string()
-                                }
-                                None => text,
-                            };
-                            Some(text)
-                        }
-                        _ => None,
-                    })
-                    .collect::<String>();
-
-                if preview.is_empty() {
-                    None
-                } else {
-                    Some(preview)
-                }
-            }
-            _ => None,
-        })
+    /// Recompute the matching sessions and select the best match.
+    fn apply_filter(&mut self) {
+        self.filter_due = None;
+        let query = Query::parse(&self.query, Local::now());
+        let mut matches: Vec<(i64, usize)> = self
+            .rows
+            .iter()
+            .enumerate()
+            .filter(|(_, row)| query.accepts(row, self.current_dir.as_deref()))
+            .filter_map(|(idx, row)| query.score(row).map(|score| (score, idx)))
+            .collect();
+        // Equally good matches stay newest first.
+        matches.sort_unstable();
+        self.filtered = matches.into_iter().map(|(_, idx)| idx).collect();
+        self.selected = 0;
+        self.request_frame();
+    }
 }
 
 fn draw_picker(tui: &mut Tui, state: &PickerState) -> std::io::Result<()> {
@@ -321,7 +508,7 @@ fn draw_picker(tui: &mut Tui, state: &PickerState) -> std::io::Result<()> {
     let height = tui.terminal.size()?.height;
     tui.draw(height, |frame| {
         let area = frame.area();
-        let [header, search, list, hint] = Layout::vertical([
+        let [header, search, body, hint] = Layout::vertical([
             Constraint::Length(1),
             Constraint::Length(1),
             Constraint::Min(area.height.saturating_sub(3)),
@@ -330,44 +517,85 @@ fn draw_picker(tui: &mut Tui, state: &PickerState) -> std::io::Result<()> {
         .areas(area);
 
         // Header
+        let count = if state.loading {
+            format!("  loading… {} sessions", state.rows.len())
+        } else {
+            format!(
+                "  {} of {} sessions",
+                state.filtered.len(),
+                state.rows.len()
+            )
+        };
         frame.render_widget_ref(
-            Line::from(vec!["Resume a previous session".bold().cyan()]),
+            Line::from(vec![
+                "Resume a previous session".bold().accent(),
+                count.secondary(),
+            ]),
             header,
         );
 
         // Search line
         let q = if state.query.is_empty() {
-            "Type to search".dim().to_string()
+            "Type to search; filter with cwd: repo: branch: since: until:"
+                .secondary()
+                .to_string()
         } else {
             format!("Search: {}", state.query)
         };
         frame.render_widget_ref(Line::from(q), search);
 
-        // List
-        render_list(frame, list, state);
+        // List and preview
+        if body.width >= MIN_PREVIEW_WIDTH {
+            let [list, preview] =
+                Layout::horizontal([Constraint::Percentage(45), Constraint::Percentage(55)])
+                    .areas(body);
+            render_list(frame, list, state);
+            if let Some(row) = state.selected_row() {
+                frame.render_widget(
+                    Paragraph::new(preview_lines(row))
+                        .wrap(Wrap { trim: false })
+                        .block(
+                            Block::default()
+                                .borders(Borders::LEFT)
+                                .border_style(theme().secondary),
+                        ),
+                    preview,
+                );
+            }
+        } else {
+            render_list(frame, body, state);
+        }
 
         // Hint line
-        let hint_line: Line = vec![
-            "Enter".bold(),
-            " to resume  ".into(),
-            "Esc".bold(),
-            " to start new  ".into(),
-            "Ctrl+C".into(),
-            " to quit  ".dim(),
-            "←/a".into(),
-            " prev  ".dim(),
-            "→/d".into(),
-            " next".dim(),
-        ]
-        .into();
+        let hint_line: Line = match &state.notice {
+            Some(notice) => notice.clone().into(),
+            None => vec![
+                "Enter".bold(),
+                " to resume  ".into(),
+                "Esc".bold(),
+                " to start new  ".into(),
+                "Ctrl+C".into(),
+                " to quit  ".secondary(),
+                "Ctrl+A".into(),
+                " archive  ".secondary(),
+                "Ctrl+D".into(),
+                " delete".secondary(),
+            ]
+            .into(),
+        };
         frame.render_widget_ref(hint_line, hint);
     })
 }
 
 fn render_list(frame: &mut crate::custom_terminal::Frame, area: Rect, state: &PickerState) {
-    let rows = &state.filtered_rows;
+    let rows = &state.filtered;
     if rows.is_empty() {
-        frame.render_widget_ref(Line::from("No sessions found".italic().dim()), area);
+        let message = if state.loading {
+            "Loading sessions…"
+        } else {
+            "No sessions found"
+        };
+        frame.render_widget_ref(Line::from(message.italic().secondary()), area);
         return;
     }
 
@@ -377,14 +605,15 @@ fn render_list(frame: &mut crate::custom_terminal::Frame, area: Rect, state: &Pi
     let visible = &rows[start..rows.len().min(start + capacity)];
 
     let mut y = area.y;
-    for (idx, row) in visible.iter().enumerate() {
+    for (idx, &row_idx) in visible.iter().enumerate() {
+        let row = &state.rows[row_idx];
         let is_sel = start + idx == state.selected;
         let marker = if is_sel { "> ".bold() } else { "  ".into() };
         let ts = row
             .ts
             .map(human_time_ago)
             .unwrap_or_else(|| "".to_string())
-            .dim();
+            .secondary();
         let max_cols = area.width.saturating_sub(6) as usize;
         let preview = truncate_text(&row.preview, max_cols);
 
@@ -395,6 +624,54 @@ fn render_list(frame: &mut crate::custom_terminal::Frame, area: Rect, state: &Pi
     }
 }
 
+/// The selected session's metadata followed by its first and last exchanges.
+fn preview_lines(row: &Row) -> Vec<Line<'static>> {
+    let mut lines: Vec<Line<'static>> = Vec::new();
+    let mut field = |name: &str, value: String| {
+        lines.push(vec![format!(" {name:<11}").secondary(), value.into()].into());
+    };
+    field("Directory", row.cwd.display().to_string());
+    if let Some(url) = &row.git_repository_url {
+        field("Repository", url.clone());
+    }
+    if let Some(branch) = &row.git_branch {
+        field("Branch", branch.clone());
+    }
+    if let Some(ts) = row.ts {
+        field(
+            "Started",
+            ts.with_timezone(&Local)
+                .format("%Y-%m-%d %H:%M")
+                .to_string(),
+        );
+    }
+    push_exchange(&mut lines, "First exchange", &row.first_exchange);
+    push_exchange(&mut lines, "Last exchange", &row.last_exchange);
+    lines
+}
+
+fn push_exchange(lines: &mut Vec<Line<'static>>, title: &str, messages: &[ConversationMessage]) {
+    if messages.is_empty() {
+        return;
+    }
+    lines.push("".into());
+    lines.push(format!(" {title}").bold().into());
+    for message in messages {
+        lines.push(match message.role {
+            MessageRole::User => " user".accent().bold().into(),
+            MessageRole::Assistant => " codex".brand().bold().into(),
+        });
+        let text_lines: Vec<&str> = message.text.lines().collect();
+        for line in text_lines.iter().take(PREVIEW_MESSAGE_LINES) {
+            lines.push(format!(" {line}").into());
+        }
+        if text_lines.len() > PREVIEW_MESSAGE_LINES {
+            let hidden = text_lines.len() - PREVIEW_MESSAGE_LINES;
+            lines.push(format!(" … +{hidden} lines").secondary().into());
+        }
+    }
+}
+
 fn human_time_ago(ts: DateTime<Utc>) -> String {
     let now = Utc::now();
     let delta = now - ts;
@@ -433,65 +710,181 @@ fn human_time_ago(ts: DateTime<Utc>) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use serde_json::json;
-
-    fn head_with_ts_and_user_text(ts: &str, texts: &[&str]) -> Vec<serde_json::Value> {
-        vec![
-            json!({ "timestamp": ts }),
-            json!({
-                "type": "message",
-                "role": "user",
-                "content": texts
-                    .iter()
-                    .map(|t| json!({ "type": "input_text", "text": *t }))
-                    .collect::<Vec<_>>()
-            }),
-        ]
+    use chrono::TimeZone;
+    use pretty_assertions::assert_eq;
+
+    fn message(role: MessageRole, text: &str) -> ConversationMessage {
+        ConversationMessage {
+            role,
+            text: text.to_string(),
+        }
+    }
+
+    fn summary(timestamp: &str, messages: Vec<ConversationMessage>) -> ConversationSummary {
+        ConversationSummary {
+            path: PathBuf::from(format!("/tmp/{timestamp}.jsonl")),
+            timestamp: timestamp.to_string(),
+            cwd: PathBuf::from("/work/codex"),
+            git_repository_url: Some("https://github.com/openai/codex.git".to_string()),
+            git_branch: Some("main".to_string()),
+            messages,
+        }
     }
 
     #[test]
-    fn preview_uses_first_message_input_text() {
-        let head = vec![
-            json!({ "timestamp": "2025-01-01T00:00:00Z" }),
-            json!({
-                "type": "message",
-                "role": "user",
-                "content": [
-                    { "type": "input_text", "text": "<user_instructions>hi</user_instructions>" },
-                    { "type": "input_text", "text": "real question" },
-                    { "type": "input_image", "image_url": "ignored" }
-                ]
-            }),
-            json!({
-                "type": "message",
-                "role": "user",
-                "content": [ { "type": "input_text", "text": "later text" } ]
-            }),
-        ];
-        let preview = preview_from_head(&head);
-        assert_eq!(preview.as_deref(), Some("real question"));
+    fn row_previews_first_user_message_and_keeps_first_and_last_exchanges() {
+        let row = Row::from_summary(summary(
+            "2025-01-01T00:00:00Z",
+            vec![
+                message(MessageRole::User, "real question"),
+                message(MessageRole::Assistant, "first answer"),
+                message(MessageRole::User, "follow up"),
+                message(MessageRole::Assistant, "second answer"),
+                message(MessageRole::User, "last question"),
+                message(MessageRole::Assistant, "last answer"),
+            ],
+        ));
+
+        assert_eq!(row.preview, "real question");
+        assert_eq!(
+            row.first_exchange,
+            vec![
+                message(MessageRole::User, "real question"),
+                message(MessageRole::Assistant, "first answer"),
+            ]
+        );
+        assert_eq!(
+            row.last_exchange,
+            vec![
+                message(MessageRole::User, "last question"),
+                message(MessageRole::Assistant, "last answer"),
+            ]
+        );
+        assert!(row.words.contains(&"follow".to_string()));
     }
 
     #[test]
-    fn to_rows_preserves_backend_order() {
-        // Construct two items with different timestamps and real user text.
-        let a = ConversationItem {
-            path: PathBuf::from("/tmp/a.jsonl"),
-            head: head_with_ts_and_user_text("2025-01-01T00:00:00Z", &["A"]),
-        };
-        let b = ConversationItem {
-            path: PathBuf::from("/tmp/b.jsonl"),
-            head: head_with_ts_and_user_text("2025-01-02T00:00:00Z", &["B"]),
-        };
-        let rows = to_rows(ConversationsPage {
-            items: vec![a, b],
-            next_cursor: None,
-            num_scanned_files: 0,
-            reached_scan_cap: false,
-        });
-        assert_eq!(rows.len(), 2);
-        // Preserve the given order; backend already provides newest-first
-        assert!(rows[0].preview.contains('A'));
-        assert!(rows[1].preview.contains('B'));
+    fn single_exchange_is_not_repeated_as_the_last() {
+        let row = Row::from_summary(summary(
+            "2025-01-01T00:00:00Z",
+            vec![message(MessageRole::User, "only question")],
+        ));
+
+        assert_eq!(row.last_exchange, Vec::new());
+    }
+
+    #[test]
+    fn query_separates_filters_from_search_terms() {
+        let now = Local.with_ymd_and_hms(2025, 3, 10, 12, 0, 0).unwrap();
+
+        let query = Query::parse(
+            "flaky repo:Codex branch:main since:7d until:2025-03-09 x",
+            now,
+        );
+
+        assert_eq!(
+            query,
+            Query {
+                terms: vec!["flaky".to_string(), "x".to_string()],
+                cwd: None,
+                repo: Some("codex".to_string()),
+                branch: Some("main".to_string()),
+                since: Some((now - TimeDelta::days(7)).with_timezone(&Utc)),
+                until: Some(
+                    Local
+                        .with_ymd_and_hms(2025, 3, 10, 0, 0, 0)
+                        .unwrap()
+                        .with_timezone(&Utc)
+                ),
+            }
+        );
+        assert_eq!(
+            Query::parse("since:soon cwd:", now).terms,
+            vec!["since:soon".to_string(), "cwd:".to_string()]
+        );
+    }
+
+    #[test]
+    fn filters_check_metadata_and_date() {
+        let row = Row::from_summary(summary(
+            "2025-03-05T10:00:00Z",
+            vec![message(MessageRole::User, "hello")],
+        ));
+        let now = Local::now();
+        let accepts = |text: &str| Query::parse(text, now).accepts(&row, Some(Path::new("/work")));
+
+        assert!(accepts("repo:openai/codex branch:main cwd:work/"));
+        assert!(!accepts("branch:release"));
+        assert!(!accepts("cwd:."));
+        assert!(Query::parse("cwd:.", now).accepts(&row, Some(Path::new("/work/codex"))));
+        assert!(accepts("since:2025-03-04 until:2025-03-06"));
+        assert!(!accepts("since:2025-03-07"));
+    }
+
+    #[tokio::test]
+    async fn typing_searches_once_the_query_settles() {
+        let mut state =
+            PickerState::new(PathBuf::from("/codex"), FrameRequester::test_dummy(), None);
+        state.add_rows(
+            ["fix the parser", "write the docs"]
+                .into_iter()
+                .enumerate()
+                .map(|(i, text)| {
+                    let mut summary = summary(
+                        "2025-01-01T00:00:00Z",
+                        vec![message(MessageRole::User, text)],
+                    );
+                    summary.path = PathBuf::from(format!("/tmp/{i}.jsonl"));
+                    Row::from_summary(summary)
+                })
+                .collect(),
+        );
+        assert_eq!(state.filtered, vec![0, 1]);
+
+        for c in "docs".chars() {
+            let key = KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE);
+            assert!(state.handle_key(key).await.is_none());
+        }
+        assert_eq!(state.filtered, vec![0, 1]);
+        assert!(state.filter_due.is_some());
+
+        // Enter picks from the results for the whole query.
+        let enter = KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE);
+        assert!(matches!(
+            state.handle_key(enter).await,
+            Some(ResumeSelection::Resume(path)) if path == Path::new("/tmp/1.jsonl")
+        ));
+        assert_eq!(state.filtered, vec![1]);
+        assert_eq!(state.filter_due, None);
+    }
+
+    #[test]
+    fn fuzzy_search_covers_the_whole_conversation() {
+        let rows: Vec<Row> = [
+            vec![
+                message(MessageRole::User, "rename the config loader"),
+                message(MessageRole::Assistant, "Renamed it."),
+            ],
+            vec![
+                message(MessageRole::User, "hello"),
+                message(MessageRole::Assistant, "later we touched the configuration"),
+            ],
+            vec![message(MessageRole::User, "unrelated")],
+        ]
+        .into_iter()
+        .map(|messages| Row::from_summary(summary("2025-01-01T00:00:00Z", messages)))
+        .collect();
+        let query = Query::parse("cfg renamd", Local::now());
+
+        let scores: Vec<Option<i64>> = rows.iter().map(|row| query.score(row)).collect();
+
+        assert!(scores[0].is_some());
+        assert_eq!(scores[1], None);
+        assert_eq!(scores[2], None);
+        assert!(
+            Query::parse("configuration", Local::now())
+                .score(&rows[1])
+                .is_some()
+        );
     }
 }
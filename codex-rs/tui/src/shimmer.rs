@@ -7,6 +7,8 @@ use ratatui::style::Modifier;
 use ratatui::style::Style;
 use ratatui::text::Span;
 
+use crate::theme::theme;
+
 static PROCESS_START: OnceLock<Instant> = OnceLock::new();
 
 fn elapsed_since_start() -> Duration {
@@ -45,6 +47,12 @@ pub(crate) fn shimmer_spans(text: &str) -> Vec<Span<'static>> {
         };
         let brightness = 0.4 + 0.6 * t;
         let level = (brightness * 255.0).clamp(0.0, 255.0) as u8;
+        // Text is dark on light backgrounds, so the band darkens it instead.
+        let level = if theme().light_background {
+            255 - level
+        } else {
+            level
+        };
         let style = if has_true_color {
             // Allow custom RGB colors, as the implementation is thoughtfully
             // adjusting the level of the default foreground color.
@@ -66,7 +74,7 @@ fn color_for_level(level: u8) -> Style {
     // Tune thresholds so the edges of the shimmer band appear dim
     // in fallback mode (no true color support).
     if level < 160 {
-        Style::default().add_modifier(Modifier::DIM)
+        theme().secondary
     } else if level < 224 {
         Style::default()
     } else {
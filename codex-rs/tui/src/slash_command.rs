@@ -15,12 +15,17 @@ pub enum SlashCommand {
     Model,
     Approvals,
     New,
+    NewTab,
+    Tabs,
     Init,
     Compact,
     Diff,
     Mention,
+    Edit,
     Status,
     Mcp,
+    Rename,
+    QuitTab,
     Logout,
     Quit,
     #[cfg(debug_assertions)]
@@ -32,11 +37,16 @@ impl SlashCommand {
     pub fn description(self) -> &'static str {
         match self {
             SlashCommand::New => "start a new chat during a conversation",
+            SlashCommand::NewTab => "start a new chat in another tab",
+            SlashCommand::Tabs => "switch between the open chats",
+            SlashCommand::Rename => "name this tab: /rename <name>",
+            SlashCommand::QuitTab => "end this tab's chat and close the tab",
             SlashCommand::Init => "create an AGENTS.md file with instructions for Codex",
             SlashCommand::Compact => "summarize conversation to prevent hitting the context limit",
             SlashCommand::Quit => "exit Codex",
             SlashCommand::Diff => "show git diff (including untracked files)",
             SlashCommand::Mention => "mention a file",
+            SlashCommand::Edit => "write the message in $VISUAL or $EDITOR",
             SlashCommand::Status => "show current session configuration and token usage",
             SlashCommand::Model => "choose what model and reasoning effort to use",
             SlashCommand::Approvals => "choose what Codex can do without approval",
@@ -53,6 +63,12 @@ impl SlashCommand {
         self.into()
     }
 
+    /// Whether the text after the command is passed to it rather than
+    /// discarded.
+    pub fn takes_argument(self) -> bool {
+        matches!(self, SlashCommand::Rename)
+    }
+
     /// Whether this command can be run while a task is in progress.
     pub fn available_during_task(self) -> bool {
         match self {
@@ -63,7 +79,12 @@ impl SlashCommand {
             | SlashCommand::Approvals
             | SlashCommand::Logout => false,
             SlashCommand::Diff
+            | SlashCommand::NewTab
+            | SlashCommand::Tabs
+            | SlashCommand::Rename
+            | SlashCommand::QuitTab
             | SlashCommand::Mention
+            | SlashCommand::Edit
             | SlashCommand::Status
             | SlashCommand::Mcp
             | SlashCommand::Quit => true,
@@ -16,6 +16,7 @@ use crate::app_event::AppEvent;
 use crate::app_event_sender::AppEventSender;
 use crate::key_hint;
 use crate::shimmer::shimmer_spans;
+use crate::theme::ThemeStylize;
 use crate::tui::FrameRequester;
 
 pub(crate) struct StatusIndicatorWidget {
@@ -160,9 +161,9 @@ impl WidgetRef for StatusIndicatorWidget {
         spans.extend(shimmer_spans(&self.header));
         spans.extend(vec![
             " ".into(),
-            format!("({pretty_elapsed} • ").dim(),
-            "Esc".dim().bold(),
-            " to interrupt)".dim(),
+            format!("({pretty_elapsed} • ").secondary(),
+            "Esc".secondary().bold(),
+            " to interrupt)".secondary(),
         ]);
 
         // Build lines: status, then queued messages, then spacer.
@@ -175,15 +176,15 @@ impl WidgetRef for StatusIndicatorWidget {
             for (i, piece) in wrapped.iter().take(3).enumerate() {
                 let prefix = if i == 0 { " ↳ " } else { "   " };
                 let content = format!("{prefix}{piece}");
-                lines.push(Line::from(content.dim().italic()));
+                lines.push(Line::from(content.secondary().italic()));
             }
             if wrapped.len() > 3 {
-                lines.push(Line::from("   …".dim().italic()));
+                lines.push(Line::from("   …".secondary().italic()));
             }
         }
         if !self.queued_messages.is_empty() {
             let shortcut = key_hint::alt("↑");
-            lines.push(Line::from(vec!["   ".into(), shortcut, " edit".into()]).dim());
+            lines.push(Line::from(vec!["   ".into(), shortcut, " edit".into()]).secondary());
         }
 
         let paragraph = Paragraph::new(lines);
@@ -0,0 +1,252 @@
+//! Conversations open side by side as tabs. The conversation on screen lives
+//! in the fields of [`App`](crate::app::App); the others are parked in their
+//! [`Tab`] until switched to.
+
+use std::collections::VecDeque;
+
+use codex_core::protocol::EventMsg;
+use ratatui::style::Modifier;
+use ratatui::style::Stylize;
+use ratatui::text::Line;
+use ratatui::text::Span;
+
+use crate::backtrack_helpers;
+use crate::chatwidget::ChatWidget;
+use crate::file_search::FileSearchManager;
+use crate::history_cell::HistoryCell;
+use crate::pager_overlay::TranscriptOutput;
+use crate::text_formatting::truncate_text;
+use crate::theme::ThemeStylize;
+use crate::theme::theme;
+
+/// Identifies a tab for the life of the app.
+pub(crate) type TabId = u64;
+
+const MAX_TITLE_GRAPHEMES: usize = 24;
+
+/// Most history cells a background tab keeps for the terminal history. Older
+/// ones are only in its transcript.
+const MAX_UNSEEN_CELLS: usize = 200;
+
+/// What a background tab has been doing since it was last on screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) enum TabActivity {
+    #[default]
+    Idle,
+    Working,
+    NeedsApproval,
+    /// A task ended while the tab was in the background.
+    Finished,
+}
+
+impl TabActivity {
+    pub(crate) fn after_event(self, msg: &EventMsg) -> Self {
+        match msg {
+            EventMsg::ExecApprovalRequest(_) | EventMsg::ApplyPatchApprovalRequest(_) => {
+                Self::NeedsApproval
+            }
+            EventMsg::TaskStarted(_) => Self::Working,
+            EventMsg::TaskComplete(_) | EventMsg::TurnAborted(_) | EventMsg::Error(_) => {
+                Self::Finished
+            }
+            _ => self,
+        }
+    }
+
+    pub(crate) fn description(self) -> Option<&'static str> {
+        match self {
+            Self::Idle => None,
+            Self::Working => Some("working"),
+            Self::NeedsApproval => Some("waiting for approval"),
+            Self::Finished => Some("finished"),
+        }
+    }
+
+    fn marker(self) -> Option<Span<'static>> {
+        match self {
+            Self::Idle => None,
+            Self::Working => Some("•".accent()),
+            Self::NeedsApproval => Some("!".error().bold()),
+            Self::Finished => Some("✓".success()),
+        }
+    }
+}
+
+/// A conversation's UI state while its tab is in the background.
+pub(crate) struct TabState {
+    pub(crate) chat_widget: ChatWidget,
+    /// Runs the `@` file searches of this tab's composer.
+    pub(crate) file_search: FileSearchManager,
+    pub(crate) transcript_lines: Vec<Line<'static>>,
+    pub(crate) transcript_outputs: Vec<TranscriptOutput>,
+    /// History produced in the background, added to the terminal history
+    /// when the tab is switched to.
+    pub(crate) unseen: UnseenHistory,
+    pub(crate) has_emitted_history_lines: bool,
+}
+
+impl TabState {
+    pub(crate) fn new(chat_widget: ChatWidget, file_search: FileSearchManager) -> Self {
+        Self {
+            chat_widget,
+            file_search,
+            transcript_lines: Vec::new(),
+            transcript_outputs: Vec::new(),
+            unseen: UnseenHistory::default(),
+            has_emitted_history_lines: false,
+        }
+    }
+}
+
+/// The most recent history cells of a background tab. They are rendered only
+/// when the tab comes on screen, at the terminal width of that moment.
+#[derive(Debug, Default)]
+pub(crate) struct UnseenHistory {
+    cells: VecDeque<Box<dyn HistoryCell>>,
+    /// Cells dropped to keep `cells` bounded.
+    pub(crate) omitted: usize,
+}
+
+impl UnseenHistory {
+    pub(crate) fn push(&mut self, cell: Box<dyn HistoryCell>) {
+        if self.cells.len() == MAX_UNSEEN_CELLS {
+            self.cells.pop_front();
+            self.omitted += 1;
+        }
+        self.cells.push_back(cell);
+    }
+
+    pub(crate) fn into_cells(self) -> impl Iterator<Item = Box<dyn HistoryCell>> {
+        self.cells.into_iter()
+    }
+}
+
+pub(crate) struct Tab {
+    pub(crate) id: TabId,
+    /// Name given with `/rename`.
+    pub(crate) name: Option<String>,
+    pub(crate) activity: TabActivity,
+    /// The conversation, unless it is the one on screen.
+    pub(crate) parked: Option<TabState>,
+}
+
+impl Tab {
+    pub(crate) fn new(id: TabId, parked: Option<TabState>) -> Self {
+        Self {
+            id,
+            name: None,
+            activity: TabActivity::Idle,
+            parked,
+        }
+    }
+
+    /// The tab's name, or else the start of its first message.
+    pub(crate) fn title(&self, transcript_lines: &[Line<'_>]) -> String {
+        let title = self
+            .name
+            .clone()
+            .or_else(|| {
+                backtrack_helpers::first_user_text(transcript_lines)
+                    .and_then(|text| text.lines().next().map(str::to_string))
+            })
+            .unwrap_or_else(|| "new chat".to_string());
+        truncate_text(title.trim(), MAX_TITLE_GRAPHEMES)
+    }
+}
+
+/// One line listing the tabs by number and title, with the activity of
+/// background tabs and the active tab highlighted.
+pub(crate) fn tab_bar_line(tabs: &[(String, TabActivity)], active: usize) -> Line<'static> {
+    let mut spans: Vec<Span<'static>> = Vec::new();
+    for (idx, (title, activity)) in tabs.iter().enumerate() {
+        if idx > 0 {
+            spans.push("│".secondary());
+        }
+        let label = format!(" {} {title} ", idx + 1);
+        if idx == active {
+            spans.push(Span::styled(
+                label,
+                theme()
+                    .accent
+                    .add_modifier(Modifier::REVERSED | Modifier::BOLD),
+            ));
+        } else {
+            spans.push(label.into());
+            if let Some(marker) = activity.marker() {
+                spans.push(marker);
+                spans.push(" ".into());
+            }
+        }
+    }
+    spans.into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history_cell;
+    use codex_core::protocol::TaskCompleteEvent;
+    use codex_core::protocol::TaskStartedEvent;
+
+    fn text(line: &Line<'_>) -> String {
+        line.spans.iter().map(|s| s.content.as_ref()).collect()
+    }
+
+    #[test]
+    fn activity_follows_background_events() {
+        let started = EventMsg::TaskStarted(TaskStartedEvent {
+            model_context_window: None,
+        });
+        let complete = EventMsg::TaskComplete(TaskCompleteEvent {
+            last_agent_message: None,
+        });
+
+        let activity = TabActivity::Idle.after_event(&started);
+        assert_eq!(activity, TabActivity::Working);
+        assert_eq!(activity.after_event(&complete), TabActivity::Finished);
+    }
+
+    #[test]
+    fn title_prefers_name_then_first_message() {
+        let mut tab = Tab::new(1, None);
+        assert_eq!(tab.title(&[]), "new chat");
+
+        let transcript: Vec<Line<'static>> = vec![
+            "user".into(),
+            "fix the flaky integration tests in core".into(),
+            "".into(),
+            "user".into(),
+            "thanks".into(),
+        ];
+        assert_eq!(tab.title(&transcript), "fix the flaky integra...");
+
+        tab.name = Some("tests".to_string());
+        assert_eq!(tab.title(&transcript), "tests");
+    }
+
+    #[test]
+    fn unseen_history_keeps_the_most_recent_cells() {
+        let mut unseen = UnseenHistory::default();
+        for i in 0..MAX_UNSEEN_CELLS + 3 {
+            unseen.push(Box::new(history_cell::new_error_event(i.to_string())));
+        }
+        assert_eq!(unseen.omitted, 3);
+        let cells: Vec<_> = unseen.into_cells().collect();
+        assert_eq!(cells.len(), MAX_UNSEEN_CELLS);
+        let first: String = cells[0].display_lines(80).iter().map(text).collect();
+        assert!(first.ends_with('3'), "{first:?}");
+    }
+
+    #[test]
+    fn tab_bar_marks_background_activity() {
+        let tabs = vec![
+            ("main".to_string(), TabActivity::Idle),
+            ("refactor".to_string(), TabActivity::NeedsApproval),
+            ("docs".to_string(), TabActivity::Working),
+        ];
+
+        let line = tab_bar_line(&tabs, 0);
+
+        assert_eq!(text(&line), " 1 main │ 2 refactor ! │ 3 docs • ");
+    }
+}
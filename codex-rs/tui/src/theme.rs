@@ -0,0 +1,294 @@
+//! Styles of TUI elements, chosen by the `theme` and `colors` settings under
+//! `[tui]`.
+//!
+//! Renderers style text through [`ThemeStylize`] (`"text".accent()`,
+//! `line.secondary()`, ...) or the fields of [`theme()`] rather than with
+//! hardcoded colors, so that a single setting makes the whole UI readable on
+//! light terminals or without telling green from red.
+
+use std::sync::OnceLock;
+
+use codex_core::config_types::ColorSpec;
+use codex_core::config_types::StyleSpec;
+use codex_core::config_types::ThemeName;
+use codex_core::config_types::Tui as TuiConfig;
+use ratatui::style::Color;
+use ratatui::style::Modifier;
+use ratatui::style::Style;
+use ratatui::style::Styled;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Theme {
+    /// Key hints, selections and status indicators.
+    pub(crate) accent: Style,
+    pub(crate) success: Style,
+    pub(crate) error: Style,
+    /// Codex's own messages.
+    pub(crate) brand: Style,
+    /// Secondary text such as metadata and explanations.
+    pub(crate) secondary: Style,
+    pub(crate) diff_add: Style,
+    pub(crate) diff_delete: Style,
+    pub(crate) diff_gutter: Style,
+    pub(crate) syntax_keyword: Style,
+    pub(crate) syntax_string: Style,
+    pub(crate) syntax_comment: Style,
+    pub(crate) syntax_constant: Style,
+    pub(crate) syntax_type: Style,
+    pub(crate) syntax_attribute: Style,
+    /// Whether the palette is meant for a light background, on which the
+    /// shimmer has to darken rather than brighten text.
+    pub(crate) light_background: bool,
+}
+
+static THEME: OnceLock<Theme> = OnceLock::new();
+
+/// Selects the theme for the rest of the process. Must be called before
+/// anything is rendered; later calls have no effect.
+pub(crate) fn init(config: &TuiConfig) {
+    let _ = THEME.set(Theme::from_config(config));
+}
+
+/// The theme selected with [`init`], or the dark theme if none was.
+pub(crate) fn theme() -> &'static Theme {
+    THEME.get_or_init(|| Theme::builtin(ThemeName::Dark))
+}
+
+impl Theme {
+    pub(crate) fn builtin(name: ThemeName) -> Self {
+        let fg = |color: Color| Style::new().fg(color);
+        match name {
+            ThemeName::Dark => Self {
+                accent: fg(Color::Cyan),
+                success: fg(Color::Green),
+                error: fg(Color::Red),
+                brand: fg(Color::Magenta),
+                secondary: Style::new().add_modifier(Modifier::DIM),
+                diff_add: fg(Color::Green),
+                diff_delete: fg(Color::Red),
+                diff_gutter: Style::new().add_modifier(Modifier::DIM),
+                syntax_keyword: fg(Color::Magenta),
+                syntax_string: fg(Color::Green),
+                syntax_comment: Style::new().add_modifier(Modifier::DIM | Modifier::ITALIC),
+                syntax_constant: fg(Color::Cyan),
+                syntax_type: fg(Color::Cyan),
+                syntax_attribute: Style::new().add_modifier(Modifier::DIM),
+                light_background: false,
+            },
+            // Cyan and faint text wash out on white, so use blue and gray.
+            ThemeName::Light => Self {
+                accent: fg(Color::Blue),
+                success: fg(Color::Green),
+                error: fg(Color::Red),
+                brand: fg(Color::Magenta),
+                secondary: fg(Color::DarkGray),
+                diff_add: fg(Color::Green),
+                diff_delete: fg(Color::Red),
+                diff_gutter: fg(Color::DarkGray),
+                syntax_keyword: fg(Color::Magenta),
+                syntax_string: fg(Color::Green),
+                syntax_comment: fg(Color::DarkGray).add_modifier(Modifier::ITALIC),
+                syntax_constant: fg(Color::Blue),
+                syntax_type: fg(Color::Blue),
+                syntax_attribute: fg(Color::DarkGray),
+                light_background: true,
+            },
+            ThemeName::HighContrast => {
+                let bold = |color: Color| fg(color).add_modifier(Modifier::BOLD);
+                Self {
+                    accent: bold(Color::Cyan),
+                    success: bold(Color::Green),
+                    error: bold(Color::Red),
+                    brand: bold(Color::Magenta),
+                    secondary: Style::new(),
+                    diff_add: bold(Color::Green),
+                    diff_delete: bold(Color::Red),
+                    diff_gutter: Style::new(),
+                    syntax_keyword: bold(Color::Magenta),
+                    syntax_string: fg(Color::Green),
+                    syntax_comment: Style::new().add_modifier(Modifier::ITALIC),
+                    syntax_constant: fg(Color::Cyan),
+                    syntax_type: fg(Color::Cyan),
+                    syntax_attribute: Style::new(),
+                    light_background: false,
+                }
+            }
+            // Blue and yellow stay distinct for red-green color blindness.
+            ThemeName::ColorblindSafe => Self {
+                accent: fg(Color::Cyan),
+                success: fg(Color::Blue),
+                error: fg(Color::Yellow),
+                brand: fg(Color::Magenta),
+                secondary: Style::new().add_modifier(Modifier::DIM),
+                diff_add: fg(Color::Blue),
+                diff_delete: fg(Color::Yellow),
+                diff_gutter: Style::new().add_modifier(Modifier::DIM),
+                syntax_keyword: fg(Color::Magenta),
+                syntax_string: fg(Color::Blue),
+                syntax_comment: Style::new().add_modifier(Modifier::DIM | Modifier::ITALIC),
+                syntax_constant: fg(Color::Cyan),
+                syntax_type: fg(Color::Cyan),
+                syntax_attribute: Style::new().add_modifier(Modifier::DIM),
+                light_background: false,
+            },
+        }
+    }
+
+    pub(crate) fn from_config(config: &TuiConfig) -> Self {
+        let mut theme = Self::builtin(config.theme);
+        let colors = &config.colors;
+        for (style, spec) in [
+            (&mut theme.accent, &colors.accent),
+            (&mut theme.success, &colors.success),
+            (&mut theme.error, &colors.error),
+            (&mut theme.brand, &colors.brand),
+            (&mut theme.secondary, &colors.secondary),
+            (&mut theme.diff_add, &colors.diff_add),
+            (&mut theme.diff_delete, &colors.diff_delete),
+            (&mut theme.diff_gutter, &colors.diff_gutter),
+            (&mut theme.syntax_keyword, &colors.syntax_keyword),
+            (&mut theme.syntax_string, &colors.syntax_string),
+            (&mut theme.syntax_comment, &colors.syntax_comment),
+            (&mut theme.syntax_constant, &colors.syntax_constant),
+            (&mut theme.syntax_type, &colors.syntax_type),
+            (&mut theme.syntax_attribute, &colors.syntax_attribute),
+        ] {
+            if let Some(spec) = spec {
+                *style = style_from_spec(spec);
+            }
+        }
+        theme
+    }
+}
+
+fn style_from_spec(spec: &StyleSpec) -> Style {
+    let mut style = Style::new();
+    if let Some(fg) = spec.fg {
+        style = style.fg(color_from_spec(fg));
+    }
+    if let Some(bg) = spec.bg {
+        style = style.bg(color_from_spec(bg));
+    }
+    for (set, modifier) in [
+        (spec.bold, Modifier::BOLD),
+        (spec.dim, Modifier::DIM),
+        (spec.italic, Modifier::ITALIC),
+        (spec.underlined, Modifier::UNDERLINED),
+        (spec.reversed, Modifier::REVERSED),
+    ] {
+        if set {
+            style = style.add_modifier(modifier);
+        }
+    }
+    style
+}
+
+fn color_from_spec(color: ColorSpec) -> Color {
+    match color {
+        ColorSpec::Default => Color::Reset,
+        ColorSpec::Black => Color::Black,
+        ColorSpec::Red => Color::Red,
+        ColorSpec::Green => Color::Green,
+        ColorSpec::Yellow => Color::Yellow,
+        ColorSpec::Blue => Color::Blue,
+        ColorSpec::Magenta => Color::Magenta,
+        ColorSpec::Cyan => Color::Cyan,
+        ColorSpec::Gray => Color::Gray,
+        ColorSpec::DarkGray => Color::DarkGray,
+        ColorSpec::LightRed => Color::LightRed,
+        ColorSpec::LightGreen => Color::LightGreen,
+        ColorSpec::LightYellow => Color::LightYellow,
+        ColorSpec::LightBlue => Color::LightBlue,
+        ColorSpec::LightMagenta => Color::LightMagenta,
+        ColorSpec::LightCyan => Color::LightCyan,
+        ColorSpec::White => Color::White,
+        // Custom colors are only ever used when the user configures them.
+        #[allow(clippy::disallowed_methods)]
+        ColorSpec::Rgb(r, g, b) => Color::Rgb(r, g, b),
+    }
+}
+
+/// Theme counterparts of the color methods of [`ratatui::style::Stylize`].
+pub(crate) trait ThemeStylize<T>: Sized {
+    fn accent(self) -> T;
+    fn success(self) -> T;
+    fn error(self) -> T;
+    fn brand(self) -> T;
+    fn secondary(self) -> T;
+}
+
+impl<T, U> ThemeStylize<T> for U
+where
+    U: Styled<Item = T>,
+{
+    fn accent(self) -> T {
+        patch(self, theme().accent)
+    }
+
+    fn success(self) -> T {
+        patch(self, theme().success)
+    }
+
+    fn error(self) -> T {
+        patch(self, theme().error)
+    }
+
+    fn brand(self) -> T {
+        patch(self, theme().brand)
+    }
+
+    fn secondary(self) -> T {
+        patch(self, theme().secondary)
+    }
+}
+
+fn patch<T, U: Styled<Item = T>>(styled: U, style: Style) -> T {
+    let patched = styled.style().patch(style);
+    styled.set_style(patched)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_core::config_types::ThemeColors;
+    use pretty_assertions::assert_eq;
+    use ratatui::style::Stylize;
+    use ratatui::text::Span;
+
+    #[test]
+    fn overrides_replace_theme_styles() {
+        let config = TuiConfig {
+            theme: ThemeName::Light,
+            colors: ThemeColors {
+                diff_add: Some(StyleSpec {
+                    fg: Some(ColorSpec::Blue),
+                    bold: true,
+                    ..StyleSpec::default()
+                }),
+                ..ThemeColors::default()
+            },
+            ..TuiConfig::default()
+        };
+        let theme = Theme::from_config(&config);
+        assert_eq!(
+            theme.diff_add,
+            Style::new().fg(Color::Blue).add_modifier(Modifier::BOLD)
+        );
+        assert_eq!(
+            Theme {
+                diff_add: Theme::builtin(ThemeName::Light).diff_add,
+                ..theme
+            },
+            Theme::builtin(ThemeName::Light)
+        );
+    }
+
+    #[test]
+    fn stylize_methods_patch_existing_style() {
+        let span = Span::from("x").italic().secondary();
+        assert_eq!(
+            span.style,
+            Style::new().add_modifier(Modifier::ITALIC | Modifier::DIM)
+        );
+    }
+}
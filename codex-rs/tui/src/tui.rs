@@ -445,6 +445,28 @@ impl Tui {
         Ok(())
     }
 
+    /// Hand the terminal to a foreground program, such as an editor, while `f`
+    /// runs, then restore the TUI's terminal modes and screen. The event stream
+    /// must be dropped beforehand so that it does not read the program's input.
+    pub fn with_terminal_released<R>(&mut self, f: impl FnOnce() -> R) -> Result<R> {
+        let alt_screen_active = self.alt_screen_active.load(Ordering::Relaxed);
+        if alt_screen_active {
+            let _ = execute!(self.terminal.backend_mut(), DisableAlternateScroll);
+            let _ = execute!(self.terminal.backend_mut(), LeaveAlternateScreen);
+        }
+        restore()?;
+        let result = f();
+        set_modes()?;
+        if alt_screen_active {
+            execute!(self.terminal.backend_mut(), EnterAlternateScreen)?;
+            execute!(self.terminal.backend_mut(), EnableAlternateScroll)?;
+        }
+        // The program may have drawn over the viewport; redraw all of it.
+        self.terminal.clear()?;
+        self.frame_requester().schedule_frame();
+        Ok(result)
+    }
+
     pub fn insert_history_lines(&mut self, lines: Vec<Line<'static>>) {
         self.pending_history_lines.extend(lines);
         self.frame_requester().schedule_frame();
@@ -31,6 +31,8 @@ use crate::app_event_sender::AppEventSender;
 use crate::exec_command::strip_bash_lc_and_escape;
 use crate::history_cell;
 use crate::text_formatting::truncate_text;
+use crate::theme::ThemeStylize;
+use crate::theme::theme;
 
 /// Request coming from the agent that needs user approval.
 pub(crate) enum ApprovalRequest {
@@ -240,40 +242,40 @@ impl UserApprovalWidget {
                 match decision {
                     ReviewDecision::Approved => {
                         result_spans.extend(vec![
-                            "✔ ".fg(Color::Green),
+                            "✔ ".success(),
                             "You ".into(),
                             "approved".bold(),
                             " codex to run ".into(),
-                            snippet.clone().dim(),
+                            snippet.clone().secondary(),
                             " this time".bold(),
                         ]);
                     }
                     ReviewDecision::ApprovedForSession => {
                         result_spans.extend(vec![
-                            "✔ ".fg(Color::Green),
+                            "✔ ".success(),
                             "You ".into(),
                             "approved".bold(),
                             " codex to run ".into(),
-                            snippet.clone().dim(),
+                            snippet.clone().secondary(),
                             " every time this session".bold(),
                         ]);
                     }
                     ReviewDecision::Denied => {
                         result_spans.extend(vec![
-                            "✗ ".fg(Color::Red),
+                            "✗ ".error(),
                             "You ".into(),
                             "did not approve".bold(),
                             " codex to run ".into(),
-                            snippet.clone().dim(),
+                            snippet.clone().secondary(),
                         ]);
                     }
                     ReviewDecision::Abort => {
                         result_spans.extend(vec![
-                            "✗ ".fg(Color::Red),
+                            "✗ ".error(),
                             "You ".into(),
                             "canceled".bold(),
                             " the request to run ".into(),
-                            snippet.clone().dim(),
+                            snippet.clone().secondary(),
                         ]);
                     }
                 }
@@ -340,9 +342,9 @@ impl WidgetRef for &UserApprovalWidget {
             .enumerate()
             .map(|(idx, opt)| {
                 let style = if idx == self.selected_option {
-                    Style::new().bg(Color::Cyan).fg(Color::Black)
+                    theme().accent.add_modifier(Modifier::REVERSED)
                 } else {
-                    Style::new().add_modifier(Modifier::DIM)
+                    theme().secondary
                 };
                 opt.label.clone().alignment(Alignment::Center).style(style)
             })
@@ -374,12 +376,12 @@ impl WidgetRef for &UserApprovalWidget {
         }
 
         Line::from(self.select_options[self.selected_option].description)
-            .style(Style::new().italic().add_modifier(Modifier::DIM))
+            .style(theme().secondary.italic())
             .render(description_area.inner(Margin::new(1, 0)), buf);
 
         Block::bordered()
             .border_type(BorderType::QuadrantOutside)
-            .border_style(Style::default().fg(Color::Cyan))
+            .border_style(theme().accent)
             .borders(Borders::LEFT)
             .render_ref(
                 Rect::new(0, response_chunk.y, 1, response_chunk.height),